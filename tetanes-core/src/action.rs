@@ -12,16 +12,23 @@ use crate::{
     mapper::MapperRevision,
     video::VideoFilter,
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// A user action that maps to a possible state change on [`ControlDeck`]. Used for event
 /// handling and test abstractions.
 ///
 /// [`ControlDeck`]: crate::control_deck::ControlDeck
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Action {
     /// Reset the [`ControlDeck`](crate::control_deck::ControlDeck).
     Reset(ResetKind),
+    /// Flip the [`ControlDeck`](crate::control_deck::ControlDeck) power switch.
+    TogglePower,
+    /// Hold the [`ControlDeck`](crate::control_deck::ControlDeck) reset button, resetting when
+    /// released.
+    HoldReset,
     /// Update the [`Joypad`](crate::input::Joypad) button state.
     Joypad((Player, JoypadBtn)),
     /// Toggle the [`Zapper`](crate::input::Zapper) connected state.
@@ -32,6 +39,8 @@ pub enum Action {
     ZapperAimOffscreen,
     /// Trigger the [`Zapper`](crate::input::Zapper) trigger.
     ZapperTrigger,
+    /// Insert a coin into a Vs. System coin slot.
+    InsertCoin(u8),
     /// Set [`FourPlayer`] mode.
     FourPlayer(FourPlayer),
     /// Set the slot to use for save states.
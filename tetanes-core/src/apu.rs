@@ -4,6 +4,8 @@
 
 use crate::{
     apu::{
+        blip::BlipBuf,
+        declick::Declick,
         dmc::Dmc,
         filter::{Consume, FilterChain},
         frame_counter::{FrameCounter, FrameType},
@@ -14,16 +16,19 @@ use crate::{
     },
     common::{Clock, ClockTo, NesRegion, Regional, Reset, ResetKind, Sample},
     cpu::{Cpu, Irq},
+    logging::{trace, warn},
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tracing::{trace, warn};
 
 pub mod dmc;
 pub mod noise;
 pub mod pulse;
 pub mod triangle;
 
+pub mod blip;
+pub mod declick;
 pub mod envelope;
 pub mod filter;
 pub mod frame_counter;
@@ -37,7 +42,8 @@ pub mod timer;
 pub struct ParseChannelError;
 
 /// [`Apu`] Channel.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum Channel {
     Pulse1,
@@ -64,6 +70,66 @@ impl TryFrom<usize> for Channel {
     }
 }
 
+impl Channel {
+    /// All channels, in output order.
+    pub const ALL: [Self; Apu::MAX_CHANNEL_COUNT] = [
+        Self::Pulse1,
+        Self::Pulse2,
+        Self::Triangle,
+        Self::Noise,
+        Self::Dmc,
+        Self::Mapper,
+    ];
+
+    /// Display name of the channel.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Pulse1 => "Pulse1",
+            Self::Pulse2 => "Pulse2",
+            Self::Triangle => "Triangle",
+            Self::Noise => "Noise",
+            Self::Dmc => "DMC",
+            Self::Mapper => "Mapper",
+        }
+    }
+}
+
+/// An audio channel with a display name, e.g. for listing channels in a mixer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct AudioChannel {
+    pub channel: Channel,
+    pub name: &'static str,
+}
+
+impl From<Channel> for AudioChannel {
+    fn from(channel: Channel) -> Self {
+        Self {
+            channel,
+            name: channel.name(),
+        }
+    }
+}
+
+impl AudioChannel {
+    /// Lists the [`Apu`] channels, followed by the [`Channel::Mapper`] channel named after the
+    /// expansion audio chip, if any.
+    pub fn list(expansion_audio: Option<&'static str>) -> Vec<Self> {
+        let mut channels = Channel::ALL[..Channel::Mapper as usize]
+            .iter()
+            .map(|&channel| Self::from(channel))
+            .collect::<Vec<_>>();
+        if let Some(name) = expansion_audio {
+            channels.push(Self {
+                channel: Channel::Mapper,
+                name,
+            });
+        }
+        channels
+    }
+}
+
 /// Trait for [`Apu`] registers.
 pub trait ApuRegisters {
     fn write_ctrl(&mut self, channel: Channel, val: u8);
@@ -80,10 +146,35 @@ pub trait ApuRegisters {
     fn write_frame_counter(&mut self, val: u8);
 }
 
+/// Output of each [`Channel`] on its own, resampled and filtered like the mixed output, e.g. to
+/// record channels to separate files.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct ChannelSplit {
+    blips: [BlipBuf; Apu::MAX_CHANNEL_COUNT],
+    filter_chains: [FilterChain; Apu::MAX_CHANNEL_COUNT],
+    samples: [Vec<f32>; Apu::MAX_CHANNEL_COUNT],
+}
+
+impl ChannelSplit {
+    fn new(region: NesRegion, clock_rate: f32, sample_rate: f32) -> Self {
+        Self {
+            blips: std::array::from_fn(|_| BlipBuf::new(clock_rate, sample_rate)),
+            filter_chains: std::array::from_fn(|_| FilterChain::new(region, sample_rate)),
+            samples: Default::default(),
+        }
+    }
+
+    fn set_sample_rate(&mut self, region: NesRegion, sample_rate: f32) {
+        self.filter_chains = std::array::from_fn(|_| FilterChain::new(region, sample_rate));
+    }
+}
+
 /// NES APU (Audio Processing Unit).
 ///
 /// See: <https://wiki.nesdev.com/w/index.php/APU>
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Apu {
     pub frame_counter: FrameCounter,
@@ -98,13 +189,23 @@ pub struct Apu {
     pub noise: Noise,
     pub dmc: Dmc,
     pub filter_chain: FilterChain,
-    #[serde(skip, default = "Apu::default_channel_outputs")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub blip: BlipBuf,
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip, default = "Apu::default_channel_outputs")
+    )]
     pub channel_outputs: Vec<f32>,
-    #[serde(skip)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub audio_samples: Vec<f32>,
+    /// Output of each channel on its own, if enabled.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub channel_split: Option<Box<ChannelSplit>>,
+    /// Smooths pops from discontinuities in the mixed output.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub declick: Declick,
     pub sample_rate: f32,
     pub sample_period: f32,
-    pub sample_counter: f32,
     pub speed: f32,
     pub mapper_silenced: bool,
     pub skip_mixing: bool,
@@ -135,11 +236,13 @@ impl Apu {
             noise: Noise::new(region),
             dmc: Dmc::new(region),
             filter_chain: FilterChain::new(region, sample_rate),
+            blip: BlipBuf::new(clock_rate, sample_rate),
             channel_outputs: Self::default_channel_outputs(),
             audio_samples: Vec::with_capacity((sample_rate / 60.0) as usize),
+            channel_split: None,
+            declick: Declick::new(sample_rate),
             sample_rate,
             sample_period,
-            sample_counter: sample_period,
             speed: 1.0,
             mapper_silenced: true,
             skip_mixing: false,
@@ -156,16 +259,23 @@ impl Apu {
             [(self.master_cycle * Self::MAX_CHANNEL_COUNT) + Channel::Mapper as usize] = output;
     }
 
-    /// Filter and mix audio sample based on region sampling rate.
+    /// Mix channel outputs using the non-linear mixer lookup tables and resample them to the
+    /// output sampling rate as band-limited steps.
     pub fn process_outputs(&mut self) {
         if self.skip_mixing {
             return;
         }
 
-        for outputs in self
+        // Rates are not serialized, so ensure they're up to date, e.g. after loading a save state
+        self.blip
+            .set_rates(self.clock_rate, self.sample_rate / self.speed);
+
+        let mut channel_split = self.channel_split.as_deref_mut();
+        for (cycle, outputs) in self
             .channel_outputs
             .chunks_exact(Self::MAX_CHANNEL_COUNT)
             .take(self.master_cycle)
+            .enumerate()
         {
             let [pulse1, pulse2, triangle, noise, dmc, mapper] = outputs else {
                 warn!("invalid channel outputs");
@@ -176,21 +286,99 @@ impl Apu {
             let apu_output = PULSE_TABLE[pulse_idx] + TND_TABLE[tnd_idx];
             let mapper_output = if self.mapper_silenced { 0.0 } else { *mapper };
 
-            self.filter_chain.consume(apu_output + mapper_output);
-            self.sample_counter -= 1.0;
-            if self.sample_counter <= 1.0 {
-                self.audio_samples.push(self.filter_chain.output());
-                self.sample_counter += self.sample_period;
+            self.blip.set_amplitude(cycle, apu_output + mapper_output);
+
+            if let Some(split) = &mut channel_split {
+                // Each channel is mixed as if the others were silent
+                let outputs = [
+                    PULSE_TABLE[*pulse1 as usize],
+                    PULSE_TABLE[*pulse2 as usize],
+                    TND_TABLE[(3.0 * triangle) as usize],
+                    TND_TABLE[(2.0 * noise) as usize],
+                    TND_TABLE[*dmc as usize],
+                    mapper_output,
+                ];
+                for (blip, output) in split.blips.iter_mut().zip(outputs) {
+                    blip.set_amplitude(cycle, output);
+                }
             }
         }
+
+        self.blip.end_frame(self.master_cycle);
+        let filter_chain = &mut self.filter_chain;
+        let declick = &mut self.declick;
+        let audio_samples = &mut self.audio_samples;
+        self.blip.read_samples(|sample| {
+            filter_chain.consume(sample);
+            audio_samples.push(declick.process(filter_chain.output()));
+        });
+
+        if let Some(split) = channel_split {
+            for ((blip, filter_chain), samples) in split
+                .blips
+                .iter_mut()
+                .zip(&mut split.filter_chains)
+                .zip(&mut split.samples)
+            {
+                blip.set_rates(self.clock_rate, self.sample_rate / self.speed);
+                blip.end_frame(self.master_cycle);
+                blip.read_samples(|sample| {
+                    filter_chain.consume(sample);
+                    samples.push(filter_chain.output());
+                });
+            }
+        }
+    }
+
+    /// Enable or disable outputting samples for each channel on its own, in addition to the
+    /// mixed output.
+    pub fn set_channel_split_enabled(&mut self, enabled: bool) {
+        if enabled != self.channel_split.is_some() {
+            self.channel_split = enabled.then(|| {
+                Box::new(ChannelSplit::new(
+                    self.region,
+                    self.clock_rate,
+                    self.sample_rate / self.speed,
+                ))
+            });
+        }
+    }
+
+    /// Samples output by a channel on its own since they were last cleared. Empty unless enabled
+    /// with [`Apu::set_channel_split_enabled`].
+    #[must_use]
+    pub fn channel_samples(&self, channel: Channel) -> &[f32] {
+        self.channel_split
+            .as_ref()
+            .map_or(&[], |split| &split.samples[channel as usize])
+    }
+
+    /// Clear the samples output by each channel on its own.
+    pub fn clear_channel_samples(&mut self) {
+        if let Some(split) = &mut self.channel_split {
+            split.samples.iter_mut().for_each(Vec::clear);
+        }
+    }
+
+    /// Append samples fading the mixed output out to silence, e.g. before audio is paused. The
+    /// next samples output fade back in from silence.
+    pub fn fade_out(&mut self) {
+        if !self.skip_mixing {
+            self.audio_samples.extend(self.declick.fade_out());
+        }
     }
 
     /// Set the audio sample rate.
     #[inline]
     pub fn set_sample_rate(&mut self, sample_rate: f32) {
         self.sample_rate = sample_rate;
+        self.declick.set_sample_rate(sample_rate);
+        self.declick.smooth();
         let sample_rate = self.sample_rate / self.speed;
         self.filter_chain = FilterChain::new(self.region, sample_rate);
+        if let Some(split) = &mut self.channel_split {
+            split.set_sample_rate(self.region, sample_rate);
+        }
         let clock_rate = Cpu::region_clock_rate(self.region);
         self.sample_period = clock_rate / sample_rate;
     }
@@ -198,8 +386,12 @@ impl Apu {
     /// Set the frame speed of the APU, which affects the sampling rate.
     pub fn set_frame_speed(&mut self, speed: f32) {
         self.speed = speed;
+        self.declick.smooth();
         let sample_rate = self.sample_rate / self.speed;
         self.filter_chain = FilterChain::new(self.region, sample_rate);
+        if let Some(split) = &mut self.channel_split {
+            split.set_sample_rate(self.region, sample_rate);
+        }
         let clock_rate = Cpu::region_clock_rate(self.region);
         self.sample_period = clock_rate / sample_rate;
     }
@@ -568,8 +760,12 @@ impl Regional for Apu {
             self.clock_to(self.master_cycle);
             self.region = region;
             self.clock_rate = Cpu::region_clock_rate(region);
-            self.filter_chain = FilterChain::new(region, self.sample_rate);
-            self.sample_period = self.clock_rate / self.sample_rate;
+            let sample_rate = self.sample_rate / self.speed;
+            self.filter_chain = FilterChain::new(region, sample_rate);
+            if let Some(split) = &mut self.channel_split {
+                split.set_sample_rate(region, sample_rate);
+            }
+            self.sample_period = self.clock_rate / sample_rate;
             self.frame_counter.set_region(region);
             self.noise.set_region(region);
             self.dmc.set_region(region);
@@ -589,6 +785,7 @@ impl Reset for Apu {
         self.triangle.reset(kind);
         self.noise.reset(kind);
         self.dmc.reset(kind);
+        self.declick.smooth();
     }
 }
 
@@ -606,6 +803,7 @@ impl std::fmt::Debug for Apu {
             .field("dmc", &self.dmc)
             .field("filter_chain", &self.filter_chain)
             .field("audio_samples_len", &self.audio_samples.len())
+            .field("channel_split", &self.channel_split.is_some())
             .finish()
     }
 }
@@ -681,3 +879,42 @@ pub static TND_TABLE: [f32; 203] = [
     0.721_924_25,  0.724_020_96,  0.726_108_,    0.728_185_65,  0.730_253_8,   0.732_312_56,
     0.734_361_95,  0.736_402_1,   0.738_433_1,   0.740_454_9,   0.742_467_6,
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_split() {
+        let mut apu = Apu::default();
+        apu.set_channel_split_enabled(true);
+        apu.write_status(0x01);
+        // Pulse1 at constant volume 15
+        apu.write_ctrl(Channel::Pulse1, 0xBF);
+        apu.write_timer_lo(Channel::Pulse1, 0xFD);
+        apu.write_timer_hi(Channel::Pulse1, 0x08);
+        for _ in 0..29_781 {
+            apu.clock_lazy();
+        }
+        apu.clock_flush();
+
+        let len = apu.audio_samples.len();
+        assert!(len > 0);
+        for channel in Channel::ALL {
+            assert_eq!(apu.channel_samples(channel).len(), len, "{channel:?}");
+        }
+        assert!(apu
+            .channel_samples(Channel::Pulse1)
+            .iter()
+            .any(|sample| sample.abs() > 0.0));
+        assert!(apu
+            .channel_samples(Channel::Dmc)
+            .iter()
+            .all(|&sample| sample == 0.0));
+
+        apu.clear_channel_samples();
+        assert!(apu.channel_samples(Channel::Pulse1).is_empty());
+        apu.set_channel_split_enabled(false);
+        assert!(apu.channel_samples(Channel::Pulse1).is_empty());
+    }
+}
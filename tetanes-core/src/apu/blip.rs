@@ -0,0 +1,203 @@
+//! Band-limited step synthesis for the [`Apu`](crate::apu::Apu).
+//!
+//! Instead of filtering and resampling the mixed output every CPU cycle, only changes in
+//! amplitude are recorded. Each change is added to the output buffer as a band-limited impulse
+//! which is then integrated, producing a band-limited step at the output sample rate. Cost scales
+//! with the number of amplitude changes rather than the number of clocks.
+//!
+//! See: <http://www.slack.net/~ant/bl-synth/>
+
+use std::{f64::consts::PI, sync::OnceLock};
+
+/// Number of sub-sample phases in the step kernel.
+const PHASES: usize = 64;
+/// Number of output samples each impulse spans.
+const WIDTH: usize = 16;
+/// Kernel cutoff as a fraction of the output Nyquist frequency.
+const CUTOFF: f64 = 0.9;
+
+type Kernel = [[f32; WIDTH]; PHASES + 1];
+
+static KERNEL: OnceLock<Kernel> = OnceLock::new();
+
+/// Generate a blackman-windowed sinc impulse for each sub-sample phase, normalized so that each
+/// phase sums to `1.0`.
+fn kernel() -> &'static Kernel {
+    KERNEL.get_or_init(|| {
+        let half_width = (WIDTH / 2) as f64;
+        let mut kernel = [[0.0; WIDTH]; PHASES + 1];
+        for (phase, taps) in kernel.iter_mut().enumerate() {
+            let fraction = phase as f64 / PHASES as f64;
+            let mut sum = 0.0;
+            let mut impulse = [0.0; WIDTH];
+            for (k, tap) in impulse.iter_mut().enumerate() {
+                let x = k as f64 - fraction - (half_width - 1.0);
+                let sinc = if x == 0.0 {
+                    1.0
+                } else {
+                    (PI * CUTOFF * x).sin() / (PI * CUTOFF * x)
+                };
+                let u = x / half_width;
+                let window =
+                    0.08f64.mul_add((2.0 * PI * u).cos(), 0.5f64.mul_add((PI * u).cos(), 0.42));
+                *tap = sinc * window;
+                sum += *tap;
+            }
+            for (tap, val) in taps.iter_mut().zip(impulse) {
+                *tap = (val / sum) as f32;
+            }
+        }
+        kernel
+    })
+}
+
+/// Buffer of band-limited amplitude changes, resampled from a clock rate to an output sample
+/// rate.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct BlipBuf {
+    clock_rate: f32,
+    sample_rate: f32,
+    /// Output samples per clock.
+    factor: f64,
+    /// Output sample time of clock `0` in the current frame.
+    offset: f64,
+    amplitude: f32,
+    integrator: f32,
+    buffer: Vec<f32>,
+}
+
+impl Default for BlipBuf {
+    fn default() -> Self {
+        Self::new(1.0, 1.0)
+    }
+}
+
+impl BlipBuf {
+    /// Create a new buffer resampling from `clock_rate` to `sample_rate`.
+    pub fn new(clock_rate: f32, sample_rate: f32) -> Self {
+        Self {
+            clock_rate,
+            sample_rate,
+            factor: f64::from(sample_rate) / f64::from(clock_rate),
+            offset: 0.0,
+            amplitude: 0.0,
+            integrator: 0.0,
+            buffer: vec![0.0; WIDTH],
+        }
+    }
+
+    /// Update the input clock rate and output sample rate. Pending samples are preserved.
+    pub fn set_rates(&mut self, clock_rate: f32, sample_rate: f32) {
+        if self.clock_rate != clock_rate || self.sample_rate != sample_rate {
+            self.clock_rate = clock_rate;
+            self.sample_rate = sample_rate;
+            self.factor = f64::from(sample_rate) / f64::from(clock_rate);
+        }
+    }
+
+    /// Discard all pending samples and reset the output amplitude to `0.0`.
+    pub fn clear(&mut self) {
+        self.offset = 0.0;
+        self.amplitude = 0.0;
+        self.integrator = 0.0;
+        self.buffer.fill(0.0);
+    }
+
+    /// Set the amplitude at `clock` within the current frame, adding a band-limited step if it
+    /// changed.
+    #[inline]
+    pub fn set_amplitude(&mut self, clock: usize, amplitude: f32) {
+        let delta = amplitude - self.amplitude;
+        if delta != 0.0 {
+            self.amplitude = amplitude;
+            self.add_delta(clock, delta);
+        }
+    }
+
+    /// Add a band-limited step of `delta` at `clock` within the current frame.
+    pub fn add_delta(&mut self, clock: usize, delta: f32) {
+        let time = (clock as f64).mul_add(self.factor, self.offset);
+        let index = time as usize;
+        let phase = ((time - index as f64) * PHASES as f64).round() as usize;
+
+        let end = index + WIDTH;
+        if end > self.buffer.len() {
+            self.buffer.resize(end, 0.0);
+        }
+        for (sample, tap) in self.buffer[index..end].iter_mut().zip(&kernel()[phase]) {
+            *sample += delta * tap;
+        }
+    }
+
+    /// End the current frame after `clocks`, making all samples prior to it available to
+    /// [`BlipBuf::read_samples`]. Returns the number of samples available.
+    pub fn end_frame(&mut self, clocks: usize) -> usize {
+        self.offset = (clocks as f64).mul_add(self.factor, self.offset);
+        let end = self.offset as usize + WIDTH;
+        if end > self.buffer.len() {
+            self.buffer.resize(end, 0.0);
+        }
+        self.available()
+    }
+
+    /// Number of output samples available to read.
+    #[inline]
+    #[must_use]
+    pub const fn available(&self) -> usize {
+        self.offset as usize
+    }
+
+    /// Read all available samples into `output`, removing them from the buffer.
+    pub fn read_samples(&mut self, mut output: impl FnMut(f32)) {
+        let count = self.available();
+        for &sample in &self.buffer[..count] {
+            self.integrator += sample;
+            output(self.integrator);
+        }
+
+        let len = self.buffer.len();
+        self.buffer.copy_within(count.., 0);
+        self.buffer[len - count..].fill(0.0);
+        self.offset -= count as f64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kernel_phases_normalized() {
+        for taps in kernel() {
+            let sum = taps.iter().sum::<f32>();
+            assert!((sum - 1.0).abs() < 1e-5, "sum: {sum}");
+        }
+    }
+
+    #[test]
+    fn resamples_to_output_rate() {
+        let mut blip = BlipBuf::new(1_000_000.0, 50_000.0);
+        let mut samples = Vec::new();
+        for _ in 0..10 {
+            blip.end_frame(10_000);
+            blip.read_samples(|sample| samples.push(sample));
+        }
+        assert_eq!(samples.len(), 5_000);
+    }
+
+    #[test]
+    fn step_settles_to_amplitude() {
+        let mut blip = BlipBuf::new(1_000_000.0, 50_000.0);
+        blip.set_amplitude(5, 0.5);
+        blip.set_amplitude(7, 0.5);
+        blip.end_frame(1_000);
+
+        let mut samples = Vec::new();
+        blip.read_samples(|sample| samples.push(sample));
+        assert_eq!(samples.len(), 50);
+        assert!(samples[0].abs() < 0.01, "starts silent: {}", samples[0]);
+        let last = samples[samples.len() - 1];
+        assert!((last - 0.5).abs() < 1e-5, "settles: {last}");
+    }
+}
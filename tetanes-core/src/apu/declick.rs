@@ -0,0 +1,113 @@
+//! Pop suppression for the mixed [`Apu`](crate::apu::Apu) output.
+
+/// Smooths over jumps in the output waveform that would otherwise be heard as pops, e.g. after
+/// loading a save state, resetting or resuming from a pause.
+///
+/// A jump is removed by offsetting the output so it continues from the last sample, then ramping
+/// the offset back to zero.
+#[derive(Debug, Copy, Clone)]
+#[must_use]
+pub struct Declick {
+    /// Number of samples to ramp over.
+    ramp_len: f32,
+    /// Last sample output.
+    last: f32,
+    /// Offset added to the output, ramped towards zero.
+    offset: f32,
+    /// Amount the offset changes per sample.
+    step: f32,
+    /// Whether the next sample may be discontinuous with the last.
+    pending: bool,
+}
+
+impl Default for Declick {
+    fn default() -> Self {
+        Self::new(crate::apu::Apu::DEFAULT_SAMPLE_RATE)
+    }
+}
+
+impl Declick {
+    /// How long jumps are ramped over, short enough not to be noticeable.
+    const RAMP_SECONDS: f32 = 0.01;
+
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            ramp_len: Self::ramp_len(sample_rate),
+            last: 0.0,
+            offset: 0.0,
+            step: 0.0,
+            pending: false,
+        }
+    }
+
+    fn ramp_len(sample_rate: f32) -> f32 {
+        (sample_rate * Self::RAMP_SECONDS).max(1.0)
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.ramp_len = Self::ramp_len(sample_rate);
+    }
+
+    /// Smooth over any jump between the last sample and the next.
+    pub fn smooth(&mut self) {
+        self.pending = true;
+    }
+
+    /// Process the next sample.
+    #[must_use]
+    pub fn process(&mut self, sample: f32) -> f32 {
+        if self.pending {
+            self.pending = false;
+            self.offset += self.last - (sample + self.offset);
+            self.step = self.offset.abs() / self.ramp_len;
+        }
+        let output = sample + self.offset;
+        self.offset = if self.offset > 0.0 {
+            (self.offset - self.step).max(0.0)
+        } else {
+            (self.offset + self.step).min(0.0)
+        };
+        self.last = output;
+        output
+    }
+
+    /// Returns samples ramping from the last sample to silence, to play before the output stops,
+    /// and smooths the jump from silence when it starts again.
+    pub fn fade_out(&mut self) -> impl Iterator<Item = f32> {
+        let last = self.last;
+        let len = self.ramp_len as usize;
+        self.last = 0.0;
+        self.offset = 0.0;
+        self.pending = true;
+        (1..=len).map(move |i| last * (1.0 - i as f32 / len as f32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smooth_jump() {
+        let mut declick = Declick::new(1000.0);
+        assert_eq!(declick.process(0.5), 0.5);
+
+        declick.smooth();
+        assert_eq!(declick.process(-0.5), 0.5, "continues from last sample");
+        let ramp = (0..10).map(|_| declick.process(-0.5)).collect::<Vec<_>>();
+        assert!(ramp.windows(2).all(|w| w[1] < w[0]), "ramps down");
+        assert_eq!(declick.process(-0.5), -0.5, "ramp finished");
+    }
+
+    #[test]
+    fn fade_out() {
+        let mut declick = Declick::new(1000.0);
+        let _ = declick.process(0.8);
+
+        let fade = declick.fade_out().collect::<Vec<_>>();
+        assert_eq!(fade.len(), 10);
+        assert!(fade.windows(2).all(|w| w[1] < w[0]), "ramps down");
+        assert_eq!(fade.last().copied(), Some(0.0));
+        assert_eq!(declick.process(0.8), 0.0, "resumes from silence");
+    }
+}
@@ -6,14 +6,120 @@ use crate::{
     apu::timer::{Timer, TimerCycle},
     common::{Clock, NesRegion, Regional, Reset, ResetKind, Sample},
     cpu::{Cpu, Irq},
+    logging::trace,
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use tracing::trace;
+
+/// A DPCM sample played by the DMC, captured as its bytes were fetched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct DmcSample {
+    /// CPU address the sample starts at.
+    pub addr: u16,
+    /// Rate index written to `$4010`.
+    pub rate: u8,
+    /// Delta-encoded sample bytes, one bit per output step.
+    pub data: Vec<u8>,
+}
+
+impl DmcSample {
+    /// Output level decoding starts from, halfway through the 7-bit range.
+    pub const START_LEVEL: u8 = 64;
+
+    /// Playback rate in Hz.
+    #[must_use]
+    pub fn sample_rate(&self, region: NesRegion) -> f32 {
+        Cpu::region_clock_rate(region) / (Dmc::period(region, self.rate) + 1) as f32
+    }
+
+    /// Decode to 7-bit output levels, one per bit, the way the DMC would play it back.
+    #[must_use]
+    pub fn decode(&self) -> Vec<u8> {
+        let mut level = Self::START_LEVEL;
+        let mut levels = Vec::with_capacity(self.data.len() * 8);
+        for byte in &self.data {
+            for bit in 0..8 {
+                if byte >> bit & 0x01 == 0x01 {
+                    if level <= 125 {
+                        level += 2;
+                    }
+                } else if level >= 2 {
+                    level -= 2;
+                }
+                levels.push(level);
+            }
+        }
+        levels
+    }
+}
+
+/// A log of unique [`DmcSample`]s played. Disabled by default.
+#[derive(Default, Debug, Clone)]
+#[must_use]
+pub struct DmcSampleLog {
+    enabled: bool,
+    current: Option<DmcSample>,
+    samples: Vec<DmcSample>,
+}
+
+impl DmcSampleLog {
+    /// Whether samples are being logged.
+    #[inline]
+    #[must_use]
+    pub const fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enable or disable logging. Disabling clears any logged samples.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.current = None;
+            self.samples.clear();
+        }
+    }
+
+    /// Samples logged so far, in the order they were first played.
+    #[inline]
+    pub fn samples(&self) -> &[DmcSample] {
+        &self.samples
+    }
+
+    fn start(&mut self, addr: u16, rate: u8, length: u16) {
+        if self.enabled {
+            self.current = Some(DmcSample {
+                addr,
+                rate,
+                data: Vec::with_capacity(length.into()),
+            });
+        }
+    }
+
+    fn push(&mut self, val: u8) {
+        if let Some(sample) = &mut self.current {
+            sample.data.push(val);
+        }
+    }
+
+    fn finish(&mut self) {
+        if let Some(sample) = self.current.take() {
+            if !self.samples.contains(&sample) {
+                self.samples.push(sample);
+            }
+        }
+    }
+
+    fn cancel(&mut self) {
+        self.current = None;
+    }
+}
 
 /// APU DMC (Delta Modulation Channel) provides sample playback.
 ///
 /// See: <https://www.nesdev.org/wiki/APU_DMC>
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Dmc {
     pub region: NesRegion,
@@ -33,6 +139,8 @@ pub struct Dmc {
     pub shift: u8,
     pub silence: bool,
     pub should_clock: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub sample_log: DmcSampleLog,
 }
 
 impl Default for Dmc {
@@ -68,6 +176,11 @@ impl Dmc {
             shift: 0x00,
             silence: true,
             should_clock: false,
+            sample_log: DmcSampleLog {
+                enabled: false,
+                current: None,
+                samples: Vec::new(),
+            },
         }
     }
 
@@ -97,9 +210,23 @@ impl Dmc {
         self.addr
     }
 
+    /// Rate index last written to `$4010`.
+    fn rate(&self) -> u8 {
+        let table = match self.region {
+            NesRegion::Pal => &Self::PERIOD_TABLE_PAL,
+            _ => &Self::PERIOD_TABLE_NTSC,
+        };
+        table
+            .iter()
+            .position(|&period| period - 1 == self.timer.period)
+            .unwrap_or_default() as u8
+    }
+
     fn init_sample(&mut self) {
         self.addr = self.sample_addr;
         self.bytes_remaining = self.sample_length;
+        self.sample_log
+            .start(self.sample_addr, self.rate(), self.sample_length);
         trace!(
             "APU DMC sample started. bytes remaining: {}",
             self.bytes_remaining
@@ -117,9 +244,11 @@ impl Dmc {
                 self.addr += 1;
             }
             self.bytes_remaining -= 1;
+            self.sample_log.push(val);
             trace!("APU DMC bytes remaining: {}", self.bytes_remaining);
             if self.bytes_remaining == 0 {
                 self.should_clock = false;
+                self.sample_log.finish();
                 if self.loops {
                     self.init_sample();
                 } else if self.irq_enabled {
@@ -132,7 +261,7 @@ impl Dmc {
     const fn period(region: NesRegion, val: u8) -> usize {
         let index = (val & 0x0F) as usize;
         match region {
-            NesRegion::Auto | NesRegion::Ntsc | NesRegion::Dendy => {
+            NesRegion::Auto | NesRegion::Ntsc | NesRegion::Dendy | NesRegion::PalM => {
                 Self::PERIOD_TABLE_NTSC[index] - 1
             }
             NesRegion::Pal => Self::PERIOD_TABLE_PAL[index] - 1,
@@ -169,6 +298,7 @@ impl Dmc {
         if !enabled {
             self.bytes_remaining = 0;
             self.should_clock = false;
+            self.sample_log.cancel();
         } else if self.bytes_remaining == 0 {
             self.init_sample();
             // Delay a number of cycles based on even/odd cycle
@@ -276,10 +406,67 @@ impl Reset for Dmc {
         self.bytes_remaining = 0;
         self.sample_buffer = 0x00;
         self.buffer_empty = true;
-        self.output_level = 0x00;
+        // https://www.nesdev.org/wiki/APU_power_up_state
+        self.output_level = match kind {
+            ResetKind::Soft => self.output_level & 0x01,
+            ResetKind::Hard => 0x00,
+        };
         self.bits_remaining = 0x08;
         self.shift = 0x00;
         self.silence = true;
         self.should_clock = false;
+        self.sample_log.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_log() {
+        let mut dmc = Dmc::new(NesRegion::Ntsc);
+        dmc.write_timer(0x0F);
+        dmc.write_addr(0x01);
+        dmc.write_length(0x00);
+        dmc.set_enabled(true, 0);
+        dmc.load_buffer(0xFF);
+        assert!(dmc.sample_log.samples().is_empty(), "disabled by default");
+
+        dmc.sample_log.set_enabled(true);
+        for _ in 0..2 {
+            dmc.set_enabled(true, 0);
+            dmc.load_buffer(0xAA);
+        }
+        assert_eq!(
+            dmc.sample_log.samples(),
+            [DmcSample {
+                addr: 0xC040,
+                rate: 0x0F,
+                data: vec![0xAA],
+            }],
+            "repeated samples are only logged once"
+        );
+
+        dmc.write_length(0x01);
+        dmc.set_enabled(true, 0);
+        dmc.load_buffer(0x01);
+        dmc.set_enabled(false, 0);
+        assert_eq!(
+            dmc.sample_log.samples().len(),
+            1,
+            "interrupted samples aren't logged"
+        );
+    }
+
+    #[test]
+    fn decode_sample() {
+        let sample = DmcSample {
+            addr: 0xC000,
+            rate: 0x0F,
+            data: vec![0x0F],
+        };
+        assert_eq!(sample.decode(), [66, 68, 70, 72, 70, 68, 66, 64]);
+        assert!((sample.sample_rate(NesRegion::Ntsc) - 33_143.9).abs() < 0.1);
     }
 }
@@ -3,12 +3,14 @@
 //! See: <https://www.nesdev.org/wiki/APU_Envelope>
 
 use crate::common::{Clock, Reset, ResetKind};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// APU Envelope provides volume control for APU waveform channels.
 ///
 /// See: <https://www.nesdev.org/wiki/APU_Envelope>
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Envelope {
     pub start: bool,
@@ -2,12 +2,10 @@
 //!
 //! See <https://www.nesdev.org/wiki/APU_Mixer>
 
-use crate::{
-    common::{NesRegion, Sample},
-    cpu::Cpu,
-};
+use crate::common::{NesRegion, Sample};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::f32::consts::{PI, TAU};
+use std::f32::consts::TAU;
 
 /// A trait for audio processing that consumes samples.
 pub trait Consume {
@@ -15,7 +13,8 @@ pub trait Consume {
 }
 
 /// Represents a digital filter with certain characteristics.
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum FilterKind {
     Identity,
@@ -24,7 +23,8 @@ pub enum FilterKind {
 }
 
 /// An infinite impulse response (IIR) filter.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Iir {
     pub alpha: f32,
@@ -91,7 +91,8 @@ impl Sample for Iir {
 }
 
 /// A finite impulse response (FIR) filter.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Fir {
     pub kernel: Vec<f32>,
@@ -164,7 +165,8 @@ pub fn windowed_sinc_kernel(sample_rate: f32, cutoff: f32, window_size: usize) -
 }
 
 /// Represents a digital audio filter.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum Filter {
     Iir(Iir),
@@ -202,7 +204,8 @@ impl From<Fir> for Filter {
 }
 
 /// Represents a filter with a given sampling period.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct SampledFilter {
     pub filter: Filter,
@@ -221,7 +224,11 @@ impl SampledFilter {
 }
 
 /// Represents a chain of filters for a given [`NesRegion`].
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Filters run at the output sample rate after band-limited resampling by
+/// [`BlipBuf`](crate::apu::blip::BlipBuf).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FilterChain {
     pub region: NesRegion,
     pub dt: f32,
@@ -230,47 +237,32 @@ pub struct FilterChain {
 
 impl FilterChain {
     pub fn new(region: NesRegion, output_rate: f32) -> Self {
-        let clock_rate = Cpu::region_clock_rate(region);
-        let intermediate_sample_rate = output_rate * 2.0 + (PI / 32.0);
-        let intermediate_cutoff = output_rate * 0.4;
-
-        let mut filters = vec![
-            SampledFilter::new(Iir::identity(), 1.0),
-            SampledFilter::new(Iir::low_pass(clock_rate, intermediate_cutoff), clock_rate),
-        ];
+        let mut filters = vec![SampledFilter::new(Iir::identity(), output_rate)];
         // first-order high-pass filter at 90 Hz
         filters.push(SampledFilter::new(
-            Iir::high_pass(intermediate_sample_rate, 90.0),
-            intermediate_sample_rate,
+            Iir::high_pass(output_rate, 90.0),
+            output_rate,
         ));
         // first-order high-pass filter at 440 Hz
         filters.push(SampledFilter::new(
-            Iir::high_pass(intermediate_sample_rate, 440.0),
-            intermediate_sample_rate,
+            Iir::high_pass(output_rate, 440.0),
+            output_rate,
         ));
         // first-order low-pass filter at 14 kHz
         filters.push(SampledFilter::new(
-            Iir::low_pass(intermediate_sample_rate, 14000.0),
-            intermediate_sample_rate,
+            Iir::low_pass(output_rate, 14000.0),
+            output_rate,
         ));
         // TODO: Support famicom filter selection
         // // first-order high-pass filter at 37 Hz
         // filters.push(SampledFilter::new(
-        //     Iir::high_pass(intermediate_sample_rate, 37.0),
-        //     intermediate_sample_rate,
+        //     Iir::high_pass(output_rate, 37.0),
+        //     output_rate,
         // ));
 
-        // high-quality low-pass filter
-        let window_size = 160;
-        let intermediate_cutoff = output_rate * 0.45;
-        filters.push(SampledFilter::new(
-            Fir::low_pass(intermediate_sample_rate, intermediate_cutoff, window_size),
-            intermediate_sample_rate,
-        ));
-
         Self {
             region,
-            dt: 1.0 / clock_rate,
+            dt: 1.0 / output_rate,
             filters,
         }
     }
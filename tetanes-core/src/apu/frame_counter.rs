@@ -5,14 +5,16 @@
 use crate::{
     common::{NesRegion, Reset, ResetKind},
     cpu::{Cpu, Irq},
+    logging::trace,
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use tracing::trace;
 
 /// The APU Frame Counter generates a low-frequency clock for each APU channel.
 ///
 /// See: <https://www.nesdev.org/wiki/APU_Frame_Counter>
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FrameCounter {
     pub region: NesRegion,
     pub step_cycles: [[u16; 6]; 2],
@@ -26,7 +28,8 @@ pub struct FrameCounter {
 }
 
 /// The Frame Counter clock type.
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum FrameType {
     #[default]
     None,
@@ -75,7 +78,9 @@ impl FrameCounter {
 
     const fn step_cycles(region: NesRegion) -> [[u16; 6]; 2] {
         match region {
-            NesRegion::Auto | NesRegion::Ntsc | NesRegion::Dendy => Self::STEP_CYCLES_NTSC,
+            NesRegion::Auto | NesRegion::Ntsc | NesRegion::Dendy | NesRegion::PalM => {
+                Self::STEP_CYCLES_NTSC
+            }
             NesRegion::Pal => Self::STEP_CYCLES_PAL,
         }
     }
@@ -6,12 +6,14 @@ use crate::{
     apu::Channel,
     common::{Clock, Reset, ResetKind},
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// APU Length Counter provides duration control for APU waveform channels.
 ///
 /// See: <https://www.nesdev.org/wiki/APU_Length_Counter>
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct LengthCounter {
     pub enabled: bool,
@@ -11,10 +11,12 @@ use crate::{
     },
     common::{Clock, NesRegion, Regional, Reset, ResetKind, Sample},
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// Noise shift mode.
-#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ShiftMode {
     /// Zero (XOR bits 0 and 1)
     Zero,
@@ -25,7 +27,8 @@ pub enum ShiftMode {
 /// APU Noise Channel provides pseudo-random noise generation.
 ///
 /// See: <https://www.nesdev.org/wiki/APU_Noise>
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Noise {
     pub region: NesRegion,
@@ -80,7 +83,7 @@ impl Noise {
     const fn period(region: NesRegion, val: u8) -> usize {
         let index = (val & 0x0F) as usize;
         match region {
-            NesRegion::Auto | NesRegion::Ntsc | NesRegion::Dendy => {
+            NesRegion::Auto | NesRegion::Ntsc | NesRegion::Dendy | NesRegion::PalM => {
                 Self::PERIOD_TABLE_NTSC[index] - 1
             }
             NesRegion::Pal => Self::PERIOD_TABLE_PAL[index] - 1,
@@ -11,17 +11,20 @@ use crate::{
     },
     common::{Clock, Reset, ResetKind, Sample},
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// Pulse Channel output frequency. Supports MMC5 being able to pulse at ultrasonic frequencies.
-#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum OutputFreq {
     Default,
     Ultrasonic,
 }
 
 /// Pulse Channel selection.
-#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum PulseChannel {
     One,
     Two,
@@ -30,7 +33,8 @@ pub enum PulseChannel {
 /// APU Pulse Channel provides square wave generation.
 ///
 /// See: <https://www.nesdev.org/wiki/APU_Pulse>
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Pulse {
     pub channel: PulseChannel,
@@ -238,7 +242,8 @@ impl Reset for Pulse {
 /// APU Sweep provides frequency sweeping for the APU pulse channels.
 ///
 /// See: <https://www.nesdev.org/wiki/APU_Sweep>
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Sweep {
     pub enabled: bool,
     pub channel: PulseChannel,
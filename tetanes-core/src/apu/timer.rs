@@ -1,6 +1,7 @@
 //! Timer abstraction for the [`Apu`](crate::apu::Apu).
 
 use crate::common::{Clock, ClockTo, Reset, ResetKind};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// Trait for types that have timers.
@@ -10,7 +11,8 @@ pub trait TimerCycle {
 
 /// A timer that generates a clock signal based on a divider and a period. The timer is clocked
 /// every (period + 1) * divider cycles.
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Timer {
     pub cycle: usize,
@@ -10,12 +10,14 @@ use crate::{
     },
     common::{Clock, Reset, ResetKind, Sample},
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// APU Triangle Channel provides triangle wave generation.
 ///
 /// See: <https://www.nesdev.org/wiki/APU_Triangle>
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Triangle {
     pub timer: Timer,
@@ -136,7 +138,8 @@ impl Reset for Triangle {
 /// APU Linear Counter provides duration control for the APU triangle channel.
 ///
 /// See: <https://www.nesdev.org/wiki/APU_Triangle>
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct LinearCounter {
     pub reload: bool,
@@ -0,0 +1,303 @@
+//! Running many [`ControlDeck`]s in parallel across threads, e.g. as vectorized environments for
+//! reinforcement learning.
+
+use crate::{
+    control_deck::{Config, ControlDeck, Result},
+    cpu::{Cpu, Signals},
+    input::{JoypadBtnState, Player},
+};
+
+/// Joypad button states to apply to a [`ControlDeck`] before stepping it.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct DeckInput {
+    /// Buttons held for players one through four.
+    pub joypads: [JoypadBtnState; 4],
+}
+
+impl DeckInput {
+    /// Input with buttons held for player one only.
+    pub const fn player_one(buttons: JoypadBtnState) -> Self {
+        Self {
+            joypads: [
+                buttons,
+                JoypadBtnState::empty(),
+                JoypadBtnState::empty(),
+                JoypadBtnState::empty(),
+            ],
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PoolDeck {
+    deck: ControlDeck,
+    /// Pending interrupts and DMAs, which the [`Cpu`] stores per-thread.
+    signals: Signals,
+}
+
+impl PoolDeck {
+    /// Wrap `deck`, taking the signals it left pending on the current thread.
+    fn new(deck: ControlDeck) -> Self {
+        Self {
+            deck,
+            signals: Cpu::signals(),
+        }
+    }
+
+    /// Run `f` with this deck's signals loaded, restoring the signals of the current thread
+    /// afterwards.
+    fn with<T>(&mut self, f: impl FnOnce(&mut ControlDeck) -> T) -> T {
+        let thread_signals = Cpu::signals();
+        Cpu::set_signals(self.signals);
+        let res = f(&mut self.deck);
+        self.signals = Cpu::signals();
+        Cpu::set_signals(thread_signals);
+        res
+    }
+
+    fn step(&mut self, input: &DeckInput, frames: usize) -> Result<usize> {
+        self.with(|deck| {
+            for (player, buttons) in [Player::One, Player::Two, Player::Three, Player::Four]
+                .into_iter()
+                .zip(input.joypads)
+            {
+                deck.joypad_mut(player).buttons = buttons;
+            }
+            deck.clear_audio_samples();
+            let cycles = deck.clock_frames(frames)?;
+            // Filter the frame while still on the worker thread
+            deck.frame_buffer();
+            Ok(cycles)
+        })
+    }
+}
+
+/// A pool of [`ControlDeck`]s stepped together across threads with one input per deck.
+///
+/// Each step splits the decks evenly between up to [`DeckPool::threads`] threads, so decks never
+/// share a thread at the same time. Pending interrupts and DMAs are stored per-thread by the
+/// [`Cpu`], so the pool carries them with each deck. Use [`DeckPool::with`] or
+/// [`DeckPool::for_each`] rather than a `ControlDeck` taken out of the pool for this reason.
+///
+/// ```no_run
+/// use tetanes_core::{
+///     batch::{DeckInput, DeckPool},
+///     control_deck::Config,
+///     input::JoypadBtnState,
+/// };
+///
+/// let mut pool = DeckPool::with_config(Config::default(), 16);
+/// for res in pool.for_each(|deck| deck.load_rom_path("some_awesome_game.nes")) {
+///     res?;
+/// }
+/// let inputs = vec![DeckInput::player_one(JoypadBtnState::RIGHT); pool.len()];
+/// for res in pool.step(&inputs, 4) {
+///     res?;
+/// }
+/// for frame in pool.frame_buffers() {
+///     // Do something with each frame
+/// }
+/// # Ok::<(), tetanes_core::control_deck::Error>(())
+/// ```
+#[derive(Debug)]
+#[must_use]
+pub struct DeckPool {
+    decks: Vec<PoolDeck>,
+    threads: usize,
+}
+
+impl Default for DeckPool {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl DeckPool {
+    /// Create a pool from a list of [`ControlDeck`]s, using as many threads as there is available
+    /// parallelism.
+    pub fn new(decks: impl IntoIterator<Item = ControlDeck>) -> Self {
+        Self {
+            decks: decks.into_iter().map(PoolDeck::new).collect(),
+            threads: std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+        }
+    }
+
+    /// Create a pool of `count` [`ControlDeck`]s with a given [`Config`].
+    pub fn with_config(cfg: Config, count: usize) -> Self {
+        Self::new((0..count).map(|_| ControlDeck::with_config(cfg.clone())))
+    }
+
+    /// Number of decks in the pool.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.decks.len()
+    }
+
+    /// Whether the pool has no decks.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.decks.is_empty()
+    }
+
+    /// Maximum number of threads to step decks on.
+    #[must_use]
+    pub const fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Set the maximum number of threads to step decks on. `1` steps every deck on the calling
+    /// thread.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    /// Add a [`ControlDeck`] to the pool.
+    pub fn push(&mut self, deck: ControlDeck) {
+        self.decks.push(PoolDeck::new(deck));
+    }
+
+    /// Returns a [`ControlDeck`] in the pool, e.g. to query its state.
+    pub fn deck(&self, index: usize) -> Option<&ControlDeck> {
+        self.decks.get(index).map(|deck| &deck.deck)
+    }
+
+    /// Run `f` with mutable access to a [`ControlDeck`] in the pool, returning its result or
+    /// `None` if `index` is out of bounds.
+    pub fn with<T>(&mut self, index: usize, f: impl FnOnce(&mut ControlDeck) -> T) -> Option<T> {
+        self.decks.get_mut(index).map(|deck| deck.with(f))
+    }
+
+    /// Run `f` with mutable access to every [`ControlDeck`] in the pool on the calling thread,
+    /// e.g. to load a ROM or reset, returning the results in order.
+    pub fn for_each<T>(&mut self, mut f: impl FnMut(&mut ControlDeck) -> T) -> Vec<T> {
+        self.decks
+            .iter_mut()
+            .map(|deck| deck.with(&mut f))
+            .collect()
+    }
+
+    /// Apply one [`DeckInput`] to each deck in order and clock `frames` frames, only rendering
+    /// the last one. Returns the CPU cycles clocked by each deck, or the error it encountered.
+    ///
+    /// Audio samples are cleared at the start of each step, so [`ControlDeck::audio_samples`]
+    /// only has the audio for the last step.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of inputs doesn't match the number of decks in the pool, or if
+    /// clocking a deck panics.
+    pub fn step(&mut self, inputs: &[DeckInput], frames: usize) -> Vec<Result<usize>> {
+        assert_eq!(
+            inputs.len(),
+            self.decks.len(),
+            "expected one input per deck"
+        );
+
+        let threads = self.threads.min(self.decks.len());
+        if threads <= 1 {
+            return self
+                .decks
+                .iter_mut()
+                .zip(inputs)
+                .map(|(deck, input)| deck.step(input, frames))
+                .collect();
+        }
+
+        let chunk_size = self.decks.len().div_ceil(threads);
+        std::thread::scope(|scope| {
+            let handles = self
+                .decks
+                .chunks_mut(chunk_size)
+                .zip(inputs.chunks(chunk_size))
+                .map(|(decks, inputs)| {
+                    scope.spawn(move || {
+                        decks
+                            .iter_mut()
+                            .zip(inputs)
+                            .map(|(deck, input)| deck.step(input, frames))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|err| std::panic::resume_unwind(err))
+                })
+                .collect()
+        })
+    }
+
+    /// Returns the last rendered frame of each deck in order, as RGBA pixels with the video
+    /// filter applied.
+    pub fn frame_buffers(&mut self) -> Vec<&[u8]> {
+        self.decks
+            .iter_mut()
+            .map(|deck| deck.deck.frame_buffer())
+            .collect()
+    }
+
+    /// Take the [`ControlDeck`]s out of the pool.
+    pub fn into_decks(self) -> Vec<ControlDeck> {
+        self.decks.into_iter().map(|deck| deck.deck).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_control_deck;
+    use std::path::Path;
+
+    fn load_pool(count: usize) -> DeckPool {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_roms/cpu/nestest.nes");
+        DeckPool::new((0..count).map(|_| load_control_deck(&path)))
+    }
+
+    #[test]
+    fn control_deck_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<ControlDeck>();
+        assert_send::<DeckPool>();
+    }
+
+    #[test]
+    fn step_matches_across_threads() {
+        let mut inputs = vec![DeckInput::default(); 4];
+        inputs[1] = DeckInput::player_one(JoypadBtnState::START);
+
+        let mut threaded = load_pool(4);
+        threaded.set_threads(4);
+        let mut sequential = load_pool(4);
+        sequential.set_threads(1);
+
+        for _ in 0..10 {
+            let threaded_cycles = threaded
+                .step(&inputs, 2)
+                .into_iter()
+                .map(|res| res.expect("valid step"))
+                .collect::<Vec<_>>();
+            let sequential_cycles = sequential
+                .step(&inputs, 2)
+                .into_iter()
+                .map(|res| res.expect("valid step"))
+                .collect::<Vec<_>>();
+            assert_eq!(threaded_cycles, sequential_cycles);
+        }
+        assert_eq!(threaded.frame_buffers(), sequential.frame_buffers());
+        assert_eq!(
+            threaded.with(1, |deck| deck.joypad(Player::One).buttons),
+            Some(JoypadBtnState::START)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "expected one input per deck")]
+    fn step_input_count_mismatch() {
+        let mut pool = load_pool(2);
+        let _ = pool.step(&[DeckInput::default()], 1);
+    }
+}
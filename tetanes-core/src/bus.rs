@@ -2,20 +2,34 @@
 //!
 //! <http://wiki.nesdev.com/w/index.php/CPU_memory_map>
 
+#[cfg(any(
+    feature = "mapper-exrom",
+    feature = "mapper-namco163",
+    feature = "mapper-sunsoft-fme7",
+    feature = "mapper-vrc6",
+))]
+use crate::common::Sample;
+#[cfg(feature = "debug")]
+use crate::debug::BusActivity;
+#[cfg(feature = "serde")]
+use crate::fs;
 use crate::{
     apu::{Apu, ApuRegisters, Channel},
     cart::Cart,
-    common::{Clock, ClockTo, NesRegion, Regional, Reset, ResetKind, Sample, Sram},
+    common::{Clock, ClockTo, NesRegion, Regional, Reset, ResetKind, Sram},
     cpu::Cpu,
-    fs,
     genie::GenieCode,
-    input::{Input, InputRegisters, Player},
+    input::{FourPlayer, Input, InputRegisters, Player},
     mapper::{Mapped, MappedRead, MappedWrite, Mapper, MemMap},
     mem::{Mem, Memory, RamState},
     ppu::{Ppu, Registers},
+    vs_system::{VsPpu, VsSystem},
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, path::Path};
+use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use std::path::Path;
 
 /// NES Bus
 ///
@@ -46,9 +60,13 @@ use std::{collections::HashMap, path::Path};
 /// |- - - - - - - - -| $0100 |                 |
 /// | Zero Page       |       |                 |
 /// |-----------------| $0000 |-----------------|
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Bus {
+    #[cfg(feature = "debug")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub activity: BusActivity,
     pub apu: Apu,
     pub genie_codes: HashMap<u16, GenieCode>,
     pub input: Input,
@@ -56,7 +74,7 @@ pub struct Bus {
     pub ppu: Ppu,
     pub prg_ram_protect: bool,
     pub prg_ram: Memory,
-    #[serde(skip)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub prg_rom: Memory,
     pub ram_state: RamState,
     pub region: NesRegion,
@@ -74,6 +92,8 @@ impl Bus {
 
     pub fn new(region: NesRegion, ram_state: RamState) -> Self {
         Self {
+            #[cfg(feature = "debug")]
+            activity: BusActivity::new(),
             apu: Apu::new(region),
             genie_codes: HashMap::new(),
             input: Input::new(region),
@@ -89,6 +109,9 @@ impl Bus {
     }
 
     pub fn load_cart(&mut self, cart: Cart) {
+        self.input.vs_system = cart.is_vs_system().then(VsSystem::default);
+        self.input.cart_four_player = cart.four_player();
+        self.ppu.vs_ppu = cart.vs_ppu();
         self.prg_rom = cart.prg_rom;
         self.load_sram(cart.prg_ram);
         self.ppu.bus.load_chr_rom(cart.chr_rom);
@@ -99,6 +122,9 @@ impl Bus {
 
     pub fn unload_cart(&mut self) {
         self.ppu.load_mapper(Mapper::default());
+        self.input.vs_system = None;
+        self.input.cart_four_player = FourPlayer::Disabled;
+        self.ppu.vs_ppu = VsPpu::default();
     }
 
     #[must_use]
@@ -118,7 +144,7 @@ impl Bus {
         &self.wram
     }
 
-    /// Add a Game Genie code to override memory reads/writes.
+    /// Add a Game Genie or raw address code to override memory reads.
     ///
     /// # Errors
     ///
@@ -159,9 +185,13 @@ impl Clock for Bus {
         self.apu.clock_lazy();
         self.ppu.bus.mapper.clock();
         let output = match self.ppu.bus.mapper {
+            #[cfg(feature = "mapper-exrom")]
             Mapper::Exrom(ref exrom) => exrom.output(),
+            #[cfg(feature = "mapper-namco163")]
             Mapper::Namco163(ref namco163) => namco163.output(),
+            #[cfg(feature = "mapper-vrc6")]
             Mapper::Vrc6(ref vrc6) => vrc6.output(),
+            #[cfg(feature = "mapper-sunsoft-fme7")]
             Mapper::SunsoftFme7(ref sunsoft_fme7) => sunsoft_fme7.output(),
             _ => 0.0,
         };
@@ -180,36 +210,17 @@ impl ClockTo for Bus {
 
 impl Mem for Bus {
     fn read(&mut self, addr: u16) -> u8 {
-        let val = match addr {
-            0x0000..=0x07FF => self.wram.get(addr as usize).copied().unwrap_or(0),
-            0x4020..=0xFFFF => {
-                let val = match self.ppu.bus.mapper.map_read(addr) {
-                    MappedRead::Data(val) => val,
-                    MappedRead::PrgRam(addr) => self.prg_ram.get(addr).copied().unwrap_or(0),
-                    MappedRead::PrgRom(addr) => self.prg_rom.get(addr).copied().unwrap_or(0),
-                    _ => self.open_bus,
-                };
-                self.genie_read(addr, val)
-            }
-            0x2002 => self.ppu.read_status(),
-            0x2004 => self.ppu.read_oamdata(),
-            0x2007 => self.ppu.read_data(),
-            0x4015 => self.apu.read_status(),
-            0x4016 => self.input.read(Player::One, &self.ppu),
-            0x4017 => self.input.read(Player::Two, &self.ppu),
-            0x2000 | 0x2001 | 0x2003 | 0x2005 | 0x2006 => self.ppu.open_bus,
-            0x0800..=0x1FFF => self.read(addr & 0x07FF), // WRAM Mirrors
-            0x2008..=0x3FFF => self.read(addr & 0x2007), // Ppu Mirrors
-            _ => self.open_bus,
-        };
-        self.open_bus = val;
-        self.ppu.bus.mapper.cpu_bus_read(addr);
-        val
+        #[cfg(feature = "debug")]
+        self.activity.record_read(addr);
+        self.read_addr(addr)
     }
 
     fn peek(&self, addr: u16) -> u8 {
         match addr {
-            0x0000..=0x07FF => self.wram.get(addr as usize).copied().unwrap_or(0),
+            0x0000..=0x07FF => {
+                let val = self.wram.get(addr as usize).copied().unwrap_or(0);
+                self.genie_read(addr, val)
+            }
             0x4020..=0xFFFF => {
                 let val = match self.ppu.bus.mapper.map_peek(addr) {
                     MappedRead::Data(val) => val,
@@ -233,6 +244,47 @@ impl Mem for Bus {
     }
 
     fn write(&mut self, addr: u16, val: u8) {
+        #[cfg(feature = "debug")]
+        self.activity.record_write(addr);
+        self.write_addr(addr, val);
+    }
+}
+
+impl Bus {
+    /// Read from `addr`, resolving mirrors without counting them as separate accesses.
+    fn read_addr(&mut self, addr: u16) -> u8 {
+        let val = match addr {
+            0x0000..=0x07FF => {
+                let val = self.wram.get(addr as usize).copied().unwrap_or(0);
+                self.genie_read(addr, val)
+            }
+            0x4020..=0xFFFF => {
+                let val = match self.ppu.bus.mapper.map_read(addr) {
+                    MappedRead::Data(val) => val,
+                    MappedRead::PrgRam(addr) => self.prg_ram.get(addr).copied().unwrap_or(0),
+                    MappedRead::PrgRom(addr) => self.prg_rom.get(addr).copied().unwrap_or(0),
+                    _ => self.open_bus,
+                };
+                self.genie_read(addr, val)
+            }
+            0x2002 => self.ppu.read_status(),
+            0x2004 => self.ppu.read_oamdata(),
+            0x2007 => self.ppu.read_data(),
+            0x4015 => self.apu.read_status(),
+            0x4016 => self.input.read(Player::One, &self.ppu),
+            0x4017 => self.input.read(Player::Two, &self.ppu),
+            0x2000 | 0x2001 | 0x2003 | 0x2005 | 0x2006 => self.ppu.open_bus,
+            0x0800..=0x1FFF => self.read_addr(addr & 0x07FF), // WRAM Mirrors
+            0x2008..=0x3FFF => self.read_addr(addr & 0x2007), // Ppu Mirrors
+            _ => self.open_bus,
+        };
+        self.open_bus = val;
+        self.ppu.bus.mapper.cpu_bus_read(addr);
+        val
+    }
+
+    /// Write to `addr`, resolving mirrors without counting them as separate accesses.
+    fn write_addr(&mut self, addr: u16, val: u8) {
         match addr {
             0x0000..=0x07FF => {
                 if let Some(v) = self.wram.get_mut(addr as usize) {
@@ -280,8 +332,8 @@ impl Mem for Bus {
             0x4016 => self.input.write(val),
             0x4017 => self.apu.write_frame_counter(val),
             0x2002 => self.ppu.open_bus = val,
-            0x0800..=0x1FFF => return self.write(addr & 0x07FF, val), // WRAM Mirrors
-            0x2008..=0x3FFF => return self.write(addr & 0x2007, val), // Ppu Mirrors
+            0x0800..=0x1FFF => return self.write_addr(addr & 0x07FF, val), // WRAM Mirrors
+            0x2008..=0x3FFF => return self.write_addr(addr & 0x2007, val), // Ppu Mirrors
             _ => (),
         }
         self.open_bus = val;
@@ -311,13 +363,15 @@ impl Reset for Bus {
 }
 
 impl Sram for Bus {
+    #[cfg(feature = "serde")]
     fn save(&self, path: impl AsRef<Path>) -> fs::Result<()> {
         fs::save(path.as_ref(), self.sram())?;
         self.ppu.bus.mapper.save(path)
     }
 
+    #[cfg(feature = "serde")]
     fn load(&mut self, path: impl AsRef<Path>) -> fs::Result<()> {
-        fs::load(path.as_ref()).map(|data| self.load_sram(data))?;
+        fs::load_any_version(path.as_ref()).map(|data| self.load_sram(data))?;
         self.ppu.bus.mapper.load(path)
     }
 }
@@ -354,6 +408,27 @@ mod test {
         assert_eq!(bus.ppu.bus.mirroring(), expected_mirroring, "mirroring");
     }
 
+    #[test]
+    #[cfg(feature = "debug")]
+    fn bus_activity() {
+        let mut bus = Bus::default();
+
+        bus.read(0x0010);
+        bus.write(0x0010, 0x01);
+        assert_eq!(bus.activity.reads()[0x0010], 0, "disabled by default");
+
+        bus.activity.set_enabled(true);
+        bus.read(0x0010);
+        bus.read(0x0810);
+        bus.write(0x0010, 0x01);
+        assert_eq!(bus.activity.reads()[0x0010], 1, "zero page read");
+        assert_eq!(bus.activity.reads()[0x0810], 1, "mirror read");
+        assert_eq!(bus.activity.writes()[0x0010], 1, "zero page write");
+
+        bus.activity.set_enabled(false);
+        assert_eq!(bus.activity.reads()[0x0010], 0, "cleared when disabled");
+    }
+
     #[test]
     fn load_cart_chr_rom() {
         let mut bus = Bus::default();
@@ -439,6 +514,19 @@ mod test {
         assert_eq!(bus.read(addr), orig_value, "read orig value");
     }
 
+    #[test]
+    fn raw_codes() {
+        let mut bus = Bus::default();
+        bus.load_cart(Cart::empty());
+        bus.write(0x0075, 0x02);
+
+        bus.add_genie_code(GenieCode::from_addr(0x0075, 0x09, Some(0x02)));
+        assert_eq!(bus.read(0x0075), 0x09, "read code value");
+        assert_eq!(bus.read(0x0875), 0x09, "read mirrored code value");
+        bus.write(0x0075, 0x03);
+        assert_eq!(bus.read(0x0075), 0x03, "compare doesn't match");
+    }
+
     #[test]
     fn clock() {
         let mut bus = Bus::default();
@@ -1,17 +1,18 @@
 //! NES cartridge implementation.
 
+#[cfg(feature = "serde")]
+use crate::logging::error;
 use crate::{
     common::{NesRegion, Regional},
     fs,
-    mapper::{
-        self, m024_m026_vrc6::Revision as Vrc6Revision, m034_nina001::Nina001, Axrom, BandaiFCG,
-        Bf909x, Bnrom, Cnrom, ColorDreams, Dxrom154, Dxrom206, Dxrom76, Dxrom88, Dxrom95, Exrom,
-        Fxrom, Gxrom, JalecoSs88006, Mapper, Mmc1Revision, Namco163, Nina003006, Nrom, Pxrom,
-        SunsoftFme7, Sxrom, Txrom, Uxrom, Vrc6,
-    },
+    input::FourPlayer,
+    logging::{debug, info, warn},
+    mapper::{self, Mapper, Nrom},
     mem::{Memory, RamState},
     ppu::Mirroring,
+    vs_system::VsPpu,
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::{
     fs::File,
@@ -19,10 +20,12 @@ use std::{
     path::Path,
 };
 use thiserror::Error;
-use tracing::{debug, error, info};
 
 const PRG_ROM_BANK_SIZE: usize = 0x4000;
 const CHR_ROM_BANK_SIZE: usize = 0x2000;
+const TRAINER_SIZE: usize = 512;
+/// Offset of $7000 into PRG-RAM mapped at $6000, where the trainer is loaded.
+const TRAINER_OFFSET: usize = 0x1000;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -53,7 +56,8 @@ impl Error {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct GameInfo {
     pub crc32: u32,
@@ -76,6 +80,7 @@ pub struct Cart {
     pub(crate) prg_ram: Memory, // Program RAM
     pub(crate) ex_ram: Memory,  // Internal Extra RAM
     pub(crate) game_info: Option<GameInfo>,
+    crc32: u32,
 }
 
 impl Default for Cart {
@@ -98,6 +103,7 @@ impl Cart {
             prg_ram: Memory::new(),
             ex_ram: Memory::new(),
             game_info: None,
+            crc32: 0,
         };
         empty.mapper = Nrom::load(&mut empty).expect("valid empty mapper");
         empty
@@ -133,6 +139,25 @@ impl Cart {
         let header = NesHeader::load(&mut rom_data)?;
         debug!("{header:?}");
 
+        // Trainers are code from copier devices that precede PRG-ROM and get loaded at $7000
+        let trainer = if header.flags & 0x04 == 0x04 {
+            let mut trainer = [0x00; TRAINER_SIZE];
+            rom_data.read_exact(&mut trainer).map_err(|err| {
+                if let std::io::ErrorKind::UnexpectedEof = err.kind() {
+                    Error::InvalidHeader {
+                        byte: 6,
+                        value: header.flags,
+                        message: format!("expected {TRAINER_SIZE}-byte trainer"),
+                    }
+                } else {
+                    Error::io(err, "failed to read trainer")
+                }
+            })?;
+            Some(trainer)
+        } else {
+            None
+        };
+
         let prg_rom_len = (header.prg_rom_banks as usize) * PRG_ROM_BANK_SIZE;
         let mut prg_rom = Memory::with_size(prg_rom_len);
         rom_data.read_exact(&mut prg_rom).map_err(|err| {
@@ -178,7 +203,17 @@ impl Cart {
             }
         }
 
-        let game_info = Self::lookup_info(&prg_rom, &chr_rom);
+        // The INST-ROM and PROM data used by the PlayChoice-10 menu follow CHR-ROM and are never
+        // read, so these dumps play as the NES game they contain.
+        if header.flags & 0x30 == 0x20 {
+            info!("ignoring PlayChoice-10 INST-ROM and PROM data");
+        }
+
+        let mut crc32 = fs::compute_crc32(&prg_rom);
+        if !chr_rom.is_empty() {
+            crc32 = fs::compute_combine_crc32(crc32, &chr_rom);
+        }
+        let game_info = Self::lookup_info(crc32);
         let region = if matches!(header.variant, NesVariant::INes | NesVariant::Nes2) {
             match header.tv_mode {
                 1 => NesRegion::Pal,
@@ -207,44 +242,86 @@ impl Cart {
             prg_ram,
             ex_ram: Memory::new(),
             game_info,
+            crc32,
         };
         cart.mapper = match cart.header.mapper_num {
             0 => Nrom::load(&mut cart)?,
-            1 => Sxrom::load(&mut cart, Mmc1Revision::BC)?,
-            2 => Uxrom::load(&mut cart)?,
-            3 => Cnrom::load(&mut cart)?,
-            4 => Txrom::load(&mut cart)?,
-            5 => Exrom::load(&mut cart)?,
-            7 => Axrom::load(&mut cart)?,
-            9 => Pxrom::load(&mut cart)?,
-            10 => Fxrom::load(&mut cart)?,
-            11 => ColorDreams::load(&mut cart)?,
-            16 | 153 | 157 | 159 => BandaiFCG::load(&mut cart)?,
-            18 => JalecoSs88006::load(&mut cart)?,
-            19 | 210 => Namco163::load(&mut cart)?,
-            24 => Vrc6::load(&mut cart, Vrc6Revision::A)?,
-            26 => Vrc6::load(&mut cart, Vrc6Revision::B)?,
-            34 => {
-                // ≥ 16K implies NINA-001; ≤ 8K implies BNROM
-                if cart.has_chr_rom() && cart.chr_rom.len() >= 0x4000 {
-                    Nina001::load(&mut cart)?
-                } else {
-                    Bnrom::load(&mut cart)?
-                }
+            #[cfg(feature = "mapper-sxrom")]
+            1 => mapper::Sxrom::load(&mut cart, mapper::Mmc1Revision::BC)?,
+            #[cfg(feature = "mapper-uxrom")]
+            2 => mapper::Uxrom::load(&mut cart)?,
+            #[cfg(feature = "mapper-cnrom")]
+            3 => mapper::Cnrom::load(&mut cart)?,
+            #[cfg(feature = "mapper-txrom")]
+            4 => mapper::Txrom::load(&mut cart)?,
+            #[cfg(feature = "mapper-exrom")]
+            5 => mapper::Exrom::load(&mut cart)?,
+            #[cfg(feature = "mapper-axrom")]
+            7 => mapper::Axrom::load(&mut cart)?,
+            #[cfg(feature = "mapper-pxrom")]
+            9 => mapper::Pxrom::load(&mut cart)?,
+            #[cfg(feature = "mapper-fxrom")]
+            10 => mapper::Fxrom::load(&mut cart)?,
+            #[cfg(feature = "mapper-color-dreams")]
+            11 => mapper::ColorDreams::load(&mut cart)?,
+            #[cfg(feature = "mapper-bandai-fcg")]
+            16 | 153 | 157 | 159 => mapper::BandaiFCG::load(&mut cart)?,
+            #[cfg(feature = "mapper-jaleco-ss88006")]
+            18 => mapper::JalecoSs88006::load(&mut cart)?,
+            #[cfg(feature = "mapper-namco163")]
+            19 | 210 => mapper::Namco163::load(&mut cart)?,
+            #[cfg(feature = "mapper-vrc6")]
+            24 => mapper::Vrc6::load(&mut cart, mapper::m024_m026_vrc6::Revision::A)?,
+            #[cfg(feature = "mapper-vrc6")]
+            26 => mapper::Vrc6::load(&mut cart, mapper::m024_m026_vrc6::Revision::B)?,
+            // ≥ 16K implies NINA-001; ≤ 8K implies BNROM
+            #[cfg(feature = "mapper-nina001")]
+            34 if cart.has_chr_rom() && cart.chr_rom.len() >= 0x4000 => {
+                mapper::Nina001::load(&mut cart)?
+            }
+            #[cfg(feature = "mapper-bnrom")]
+            34 if !cart.has_chr_rom() || cart.chr_rom.len() < 0x4000 => {
+                mapper::Bnrom::load(&mut cart)?
             }
-            66 => Gxrom::load(&mut cart)?,
-            69 => SunsoftFme7::load(&mut cart)?,
-            71 => Bf909x::load(&mut cart)?,
-            76 => Dxrom76::load(&mut cart)?,
-            79 | 113 | 146 => Nina003006::load(&mut cart)?,
-            88 => Dxrom88::load(&mut cart)?,
-            95 => Dxrom95::load(&mut cart)?,
-            154 => Dxrom154::load(&mut cart)?,
-            206 => Dxrom206::load(&mut cart)?,
-            155 => Sxrom::load(&mut cart, Mmc1Revision::A)?,
+            #[cfg(feature = "mapper-rambo1")]
+            64 | 158 => mapper::Rambo1::load(&mut cart)?,
+            #[cfg(feature = "mapper-gxrom")]
+            66 => mapper::Gxrom::load(&mut cart)?,
+            #[cfg(feature = "mapper-sunsoft4")]
+            68 => mapper::Sunsoft4::load(&mut cart)?,
+            #[cfg(feature = "mapper-sunsoft-fme7")]
+            69 => mapper::SunsoftFme7::load(&mut cart)?,
+            #[cfg(feature = "mapper-bf909x")]
+            71 => mapper::Bf909x::load(&mut cart)?,
+            #[cfg(feature = "mapper-dxrom76")]
+            76 => mapper::Dxrom76::load(&mut cart)?,
+            #[cfg(feature = "mapper-nina003006")]
+            79 | 113 | 146 => mapper::Nina003006::load(&mut cart)?,
+            #[cfg(feature = "mapper-dxrom88")]
+            88 => mapper::Dxrom88::load(&mut cart)?,
+            #[cfg(feature = "mapper-dxrom95")]
+            95 => mapper::Dxrom95::load(&mut cart)?,
+            #[cfg(feature = "mapper-vs-system")]
+            99 => mapper::VsSystem::load(&mut cart)?,
+            #[cfg(feature = "mapper-dxrom154")]
+            154 => mapper::Dxrom154::load(&mut cart)?,
+            #[cfg(feature = "mapper-dxrom206")]
+            206 => mapper::Dxrom206::load(&mut cart)?,
+            #[cfg(feature = "mapper-sxrom")]
+            155 => mapper::Sxrom::load(&mut cart, mapper::Mmc1Revision::A)?,
             _ => Mapper::none(),
         };
 
+        if let Some(trainer) = trainer {
+            match cart
+                .prg_ram
+                .get_mut(TRAINER_OFFSET..TRAINER_OFFSET + TRAINER_SIZE)
+            {
+                Some(prg_ram) => prg_ram.copy_from_slice(&trainer),
+                None => warn!("ignoring trainer, no PRG-RAM at $7000"),
+            }
+        }
+
         info!("loaded ROM `{cart}`");
         debug!("{cart:?}");
         Ok(cart)
@@ -255,6 +332,13 @@ impl Cart {
         &self.name
     }
 
+    /// CRC32 of the PRG-ROM and CHR-ROM, excluding the header. Identifies the ROM regardless of
+    /// its file name or header.
+    #[must_use]
+    pub const fn crc32(&self) -> u32 {
+        self.crc32
+    }
+
     #[must_use]
     pub fn chr_rom(&self) -> &[u8] {
         &self.chr_rom
@@ -309,6 +393,42 @@ impl Cart {
         self.header.flags & 0x02 == 0x02
     }
 
+    /// Flag this cartridge as having battery-backed Save RAM, e.g. for serial EEPROM saves not
+    /// flagged in the header.
+    #[cfg(feature = "mapper-bandai-fcg")]
+    pub(crate) fn set_battery_backed(&mut self) {
+        self.header.flags |= 0x02;
+    }
+
+    /// Returns whether this cartridge is for the Vs. System arcade hardware.
+    #[must_use]
+    pub const fn is_vs_system(&self) -> bool {
+        self.header.flags & 0x30 == 0x10
+    }
+
+    /// Returns whether this cartridge is a PlayChoice-10 arcade dump, which has an INST-ROM with
+    /// the game's hint screens and PROM data after CHR-ROM.
+    #[must_use]
+    pub const fn is_playchoice10(&self) -> bool {
+        self.header.flags & 0x30 == 0x20
+    }
+
+    /// Returns whether this cartridge has a 512-byte trainer, loaded into PRG-RAM at $7000.
+    #[must_use]
+    pub const fn has_trainer(&self) -> bool {
+        self.header.flags & 0x04 == 0x04
+    }
+
+    /// Returns the PPU this Vs. System cartridge was designed for.
+    pub const fn vs_ppu(&self) -> VsPpu {
+        VsPpu::from_header(self.header.vs_data)
+    }
+
+    /// Returns the four player adapter this cartridge expects, if any.
+    pub const fn four_player(&self) -> FourPlayer {
+        FourPlayer::from_expansion_device(self.header.expansion_device)
+    }
+
     /// Returns `RamState`.
     pub const fn ram_state(&self) -> RamState {
         self.ram_state
@@ -364,6 +484,7 @@ impl Cart {
     }
 
     /// Allows mappers to add EX-RAM.
+    #[cfg(feature = "mapper-txrom")]
     pub(crate) fn add_exram(&mut self, capacity: usize) {
         self.ex_ram.resize(capacity, 0x00);
         self.ex_ram.fill_ram(self.ram_state);
@@ -383,7 +504,8 @@ impl Cart {
         }
     }
 
-    fn lookup_info(prg_rom: &[u8], chr: &[u8]) -> Option<GameInfo> {
+    #[cfg(feature = "serde")]
+    fn lookup_info(crc32: u32) -> Option<GameInfo> {
         const GAME_DB: &[u8] = include_bytes!("../game_db.dat");
 
         let Ok(games) = fs::load_bytes::<Vec<GameInfo>>(GAME_DB) else {
@@ -391,11 +513,6 @@ impl Cart {
             return None;
         };
 
-        let mut crc32 = fs::compute_crc32(prg_rom);
-        if !chr.is_empty() {
-            crc32 = fs::compute_combine_crc32(crc32, chr);
-        }
-
         match games.binary_search_by(|game| game.crc32.cmp(&crc32)) {
             Ok(index) => {
                 info!(
@@ -410,6 +527,50 @@ impl Cart {
             }
         }
     }
+
+    /// The game database requires `serde` to deserialize, so ROM headers are used as-is.
+    #[cfg(not(feature = "serde"))]
+    const fn lookup_info(_crc32: u32) -> Option<GameInfo> {
+        None
+    }
+
+    /// Builds a ROM image with a zero-filled trainer, PRG-ROM and CHR-ROM for tests.
+    ///
+    /// `flags` uses the [`NesHeader`] layout, where the lower nibble is the lower nibble of header
+    /// byte 6 and the upper nibble is the lower nibble of byte 7. `submapper_num` is only stored
+    /// when `flags` selects NES 2.0 with `0x80`.
+    #[cfg(test)]
+    pub(crate) fn test_rom(
+        mapper_num: u16,
+        submapper_num: u8,
+        prg_rom_banks: u8,
+        chr_rom_banks: u8,
+        flags: u8,
+    ) -> Vec<u8> {
+        let mut rom = b"NES\x1a".to_vec();
+        rom.extend([
+            prg_rom_banks,
+            chr_rom_banks,
+            (((mapper_num & 0x0F) as u8) << 4) | (flags & 0x0F),
+            (mapper_num & 0xF0) as u8 | (flags >> 4),
+        ]);
+        rom.resize(16, 0x00);
+        if flags & 0xC0 == 0x80 {
+            rom[8] = ((mapper_num >> 8) & 0x0F) as u8 | (submapper_num << 4);
+        }
+        let trainer_len = if flags & 0x04 == 0x04 {
+            TRAINER_SIZE
+        } else {
+            0
+        };
+        rom.resize(
+            16 + trainer_len
+                + usize::from(prg_rom_banks) * PRG_ROM_BANK_SIZE
+                + usize::from(chr_rom_banks) * CHR_ROM_BANK_SIZE,
+            0x00,
+        );
+        rom
+    }
 }
 
 impl Regional for Cart {
@@ -478,15 +639,17 @@ pub enum NesVariant {
 #[must_use]
 pub struct NesHeader {
     pub variant: NesVariant,
-    pub mapper_num: u16,    // The primary mapper number
-    pub submapper_num: u8,  // NES 2.0 https://wiki.nesdev.com/w/index.php/NES_2.0_submappers
-    pub flags: u8,          // Mirroring, Battery, Trainer, VS Unisystem, Playchoice-10, NES 2.0
-    pub prg_rom_banks: u16, // Number of 16KB PRG-ROM banks (Program ROM)
-    pub chr_rom_banks: u16, // Number of 8KB CHR-ROM banks (Character ROM)
-    pub prg_ram_shift: u8,  // NES 2.0 PRG-RAM
-    pub chr_ram_shift: u8,  // NES 2.0 CHR-RAM
-    pub tv_mode: u8,        // NES 2.0 NTSC/PAL indicator
-    pub vs_data: u8,        // NES 2.0 VS System data
+    pub mapper_num: u16,      // The primary mapper number
+    pub submapper_num: u8,    // NES 2.0 https://wiki.nesdev.com/w/index.php/NES_2.0_submappers
+    pub flags: u8,            // Mirroring, Battery, Trainer, VS Unisystem, Playchoice-10, NES 2.0
+    pub prg_rom_banks: u16,   // Number of 16KB PRG-ROM banks (Program ROM)
+    pub chr_rom_banks: u16,   // Number of 8KB CHR-ROM banks (Character ROM)
+    pub prg_ram_shift: u8,    // NES 2.0 PRG-RAM
+    pub chr_ram_shift: u8,    // NES 2.0 CHR-RAM
+    pub tv_mode: u8,          // NES 2.0 NTSC/PAL indicator
+    pub vs_data: u8,          // NES 2.0 VS System data
+    pub misc_roms: u8,        // NES 2.0 Number of miscellaneous ROMs, e.g. PlayChoice-10 INST-ROM
+    pub expansion_device: u8, // NES 2.0 Default expansion device, e.g. a four player adapter
 }
 
 impl NesHeader {
@@ -560,6 +723,8 @@ impl NesHeader {
         let mut chr_ram_shift = 0;
         let mut tv_mode = 0;
         let mut vs_data = 0;
+        let mut misc_roms = 0;
+        let mut expansion_device = 0;
         // If D2..D3 of flag 7 == 2, then NES 2.0 (supports bytes 0-15)
         let variant = if header[7] & 0x0C == 0x08 {
             // lower 4 bits of flag 8 = D8..D11 of mapper num
@@ -596,13 +761,24 @@ impl NesHeader {
                     message: "battery-backed chr-ram is currently not supported".to_string(),
                 });
             }
-            if header[14] > 0 || header[15] > 0 {
+            // Byte 14 is the number of miscellaneous ROMs after CHR-ROM and byte 15 is the default
+            // expansion device
+            if header[14] & 0xFC > 0 {
                 return Err(Error::InvalidHeader {
                     byte: 14,
                     value: header[14],
-                    message: "unrecognized data found at header offsets 14-15".to_string(),
+                    message: "invalid miscellaneous rom count in header".to_string(),
+                });
+            }
+            if header[15] & 0xC0 > 0 {
+                return Err(Error::InvalidHeader {
+                    byte: 15,
+                    value: header[15],
+                    message: "invalid default expansion device in header".to_string(),
                 });
             }
+            misc_roms = header[14];
+            expansion_device = header[15];
             NesVariant::Nes2
         } else if header[7] & 0x0C == 0x04 {
             // If D2..D3 of flag 7 == 1, then archaic iNES (supports bytes 0-7)
@@ -626,15 +802,6 @@ impl NesHeader {
             NesVariant::INes07
         };
 
-        // Trainer
-        if flags & 0x04 == 0x04 {
-            return Err(Error::InvalidHeader {
-                byte: 6,
-                value: header[6],
-                message: "trained roms are currently not supported.".to_string(),
-            });
-        }
-
         Ok(Self {
             variant,
             mapper_num,
@@ -646,6 +813,8 @@ impl NesHeader {
             chr_ram_shift,
             tv_mode,
             vs_data,
+            misc_roms,
+            expansion_device,
         })
     }
 
@@ -926,6 +1095,8 @@ impl std::fmt::Debug for NesHeader {
             .field("chr_ram_shift", &self.chr_ram_shift)
             .field("tv_mode", &self.tv_mode)
             .field("vs_data", &self.vs_data)
+            .field("misc_roms", &self.misc_roms)
+            .field("expansion_device", &self.expansion_device)
             .finish()
     }
 }
@@ -976,5 +1147,78 @@ mod tests {
                 ..NesHeader::default()
             },
         ),
+        (
+            playchoice10_nes2,
+            [0x4E, 0x45, 0x53, 0x1A,
+             0x02, 0x01, 0x01, 0x0A,
+             0x00, 0x00, 0x00, 0x00,
+             0x00, 0x00, 0x01, 0x00],
+            NesHeader {
+                variant: NesVariant::Nes2,
+                mapper_num: 0,
+                flags: 0b1010_0001,
+                prg_rom_banks: 2,
+                chr_rom_banks: 1,
+                misc_roms: 1,
+                ..NesHeader::default()
+            },
+        ),
+        (
+            four_score_nes2,
+            [0x4E, 0x45, 0x53, 0x1A,
+             0x02, 0x01, 0x01, 0x08,
+             0x00, 0x00, 0x00, 0x00,
+             0x00, 0x00, 0x00, 0x02],
+            NesHeader {
+                variant: NesVariant::Nes2,
+                mapper_num: 0,
+                flags: 0b1000_0001,
+                prg_rom_banks: 2,
+                chr_rom_banks: 1,
+                expansion_device: 0x02,
+                ..NesHeader::default()
+            },
+        ),
     );
+
+    #[test]
+    fn playchoice10_inst_rom_ignored() {
+        let mut rom = Cart::test_rom(0, 0, 2, 1, 0x21);
+        let (prg_rom, chr_rom) = rom[16..].split_at_mut(2 * PRG_ROM_BANK_SIZE);
+        prg_rom.fill(0x11);
+        chr_rom.fill(0x22);
+        // INST-ROM and PROM data
+        rom.resize(rom.len() + 0x2000 + 0x20, 0xFF);
+
+        let cart = Cart::from_rom("playchoice10", &mut rom.as_slice(), RamState::AllZeros)
+            .expect("valid cart");
+        assert!(cart.is_playchoice10());
+        assert!(!cart.is_vs_system());
+        assert_eq!(cart.prg_rom.len(), 2 * PRG_ROM_BANK_SIZE);
+        assert!(cart.chr_rom.iter().all(|&val| val == 0x22));
+    }
+
+    #[test]
+    fn trainer_loaded_at_7000() {
+        let mut rom = Cart::test_rom(0, 0, 1, 1, 0x04);
+        let (trainer, rom_data) = rom[16..].split_at_mut(TRAINER_SIZE);
+        let (prg_rom, chr_rom) = rom_data.split_at_mut(PRG_ROM_BANK_SIZE);
+        trainer.fill(0xAB);
+        prg_rom.fill(0x11);
+        chr_rom.fill(0x22);
+
+        let cart =
+            Cart::from_rom("trainer", &mut rom.as_slice(), RamState::AllZeros).expect("valid cart");
+        assert!(cart.has_trainer());
+        assert!(cart.prg_rom.iter().all(|&val| val == 0x11));
+        assert!(cart.chr_rom.iter().all(|&val| val == 0x22));
+        assert_eq!(cart.prg_ram[TRAINER_OFFSET - 1], 0x00);
+        assert!(cart.prg_ram[TRAINER_OFFSET..TRAINER_OFFSET + TRAINER_SIZE]
+            .iter()
+            .all(|&val| val == 0xAB));
+        assert_eq!(cart.prg_ram[TRAINER_OFFSET + TRAINER_SIZE], 0x00);
+
+        let truncated = &rom[..16 + 100];
+        assert!(Cart::from_rom("trainer", &mut &truncated[..], RamState::AllZeros).is_err());
+    }
 }
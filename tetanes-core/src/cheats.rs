@@ -0,0 +1,474 @@
+//! Importing and exporting cheat lists in the formats used by other emulators.
+//!
+//! Game Genie codes and raw address cheats are both mapped onto [`GenieCode`]s, which override
+//! CPU reads from the bus.
+
+use crate::genie::{self, GenieCode};
+use std::fmt::Write;
+use thiserror::Error;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Error, Debug)]
+#[must_use]
+pub enum Error {
+    #[error("unrecognized cheat file format")]
+    UnknownFormat,
+    #[error("invalid cheat on line {line}: {reason}")]
+    InvalidCheat { line: usize, reason: String },
+    #[error("invalid cheat on line {line}. {source}")]
+    InvalidCode { line: usize, source: genie::Error },
+}
+
+/// Cheat file format.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum Format {
+    /// `RetroArch` `.cht` files with numbered `cheatN_code` entries.
+    RetroArch,
+    /// Nestopia `.xml` cheat lists.
+    Nestopia,
+    /// FCEUX `.cht` files with one `S:AAAA:VV:Name` entry per line.
+    Fceux,
+}
+
+impl Format {
+    pub const fn as_slice() -> &'static [Self] {
+        &[Self::RetroArch, Self::Nestopia, Self::Fceux]
+    }
+
+    /// File extension used by this format.
+    #[must_use]
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::RetroArch | Self::Fceux => "cht",
+            Self::Nestopia => "xml",
+        }
+    }
+
+    /// Detect the format of a cheat file from its contents.
+    pub fn detect(text: &str) -> Option<Self> {
+        let text = text.trim_start_matches('\u{feff}').trim_start();
+        if text.starts_with('<') {
+            Some(Self::Nestopia)
+        } else if text.lines().any(|line| {
+            line.split_once('=')
+                .is_some_and(|(key, _)| key.trim() == "cheats")
+        }) {
+            Some(Self::RetroArch)
+        } else if text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .all(|line| line.starts_with(['*', 'S', 'C', ':']) && line.contains(':'))
+            && !text.trim().is_empty()
+        {
+            Some(Self::Fceux)
+        } else {
+            None
+        }
+    }
+}
+
+impl AsRef<str> for Format {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::RetroArch => "RetroArch",
+            Self::Nestopia => "Nestopia",
+            Self::Fceux => "FCEUX",
+        }
+    }
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_ref())
+    }
+}
+
+/// A cheat read from or written to a cheat file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Cheat {
+    pub code: GenieCode,
+    pub description: String,
+    pub enabled: bool,
+}
+
+impl Cheat {
+    pub fn new(code: GenieCode) -> Self {
+        Self {
+            description: code.code().to_string(),
+            code,
+            enabled: true,
+        }
+    }
+}
+
+/// Parse a cheat file, detecting its format.
+///
+/// # Errors
+///
+/// Errors if the format isn't recognized or any cheat is invalid.
+pub fn parse(text: &str) -> Result<(Format, Vec<Cheat>)> {
+    let format = Format::detect(text).ok_or(Error::UnknownFormat)?;
+    Ok((format, parse_format(format, text)?))
+}
+
+/// Parse a cheat file in the given format.
+///
+/// # Errors
+///
+/// Errors if any cheat is invalid.
+pub fn parse_format(format: Format, text: &str) -> Result<Vec<Cheat>> {
+    match format {
+        Format::RetroArch => parse_retroarch(text),
+        Format::Nestopia => parse_nestopia(text),
+        Format::Fceux => parse_fceux(text),
+    }
+}
+
+/// Write cheats in the given format.
+#[must_use]
+pub fn export(format: Format, cheats: &[Cheat]) -> String {
+    let mut out = String::new();
+    match format {
+        Format::RetroArch => {
+            let _ = writeln!(out, "cheats = {}", cheats.len());
+            for (index, cheat) in cheats.iter().enumerate() {
+                let _ = write!(
+                    out,
+                    "\ncheat{index}_desc = \"{}\"\ncheat{index}_code = \"{}\"\ncheat{index}_enable = {}\n",
+                    cheat.description.replace('"', "'"),
+                    cheat.code.code(),
+                    cheat.enabled,
+                );
+            }
+        }
+        Format::Nestopia => {
+            out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<cheats version=\"1.0\">\n");
+            for cheat in cheats {
+                let _ = writeln!(out, "  <cheat enabled=\"{}\">", u8::from(cheat.enabled));
+                let code = &cheat.code;
+                if code.is_raw() {
+                    let _ = writeln!(out, "    <address>0x{:04X}</address>", code.addr());
+                    let _ = writeln!(out, "    <value>0x{:02X}</value>", code.data());
+                    if let Some(compare) = code.compare() {
+                        let _ = writeln!(out, "    <compare>0x{compare:02X}</compare>");
+                    }
+                } else {
+                    let _ = writeln!(out, "    <genie>{}</genie>", code.code());
+                }
+                let _ = writeln!(
+                    out,
+                    "    <description>{}</description>",
+                    xml_escape(&cheat.description)
+                );
+                out.push_str("  </cheat>\n");
+            }
+            out.push_str("</cheats>\n");
+        }
+        Format::Fceux => {
+            // FCEUX stores Game Genie codes decoded as read substitutions
+            for cheat in cheats {
+                let code = &cheat.code;
+                let disabled = if cheat.enabled { "" } else { "*" };
+                let _ = match code.compare() {
+                    Some(compare) => writeln!(
+                        out,
+                        "{disabled}SC:{:04X}:{:02X}:{compare:02X}:{}",
+                        code.addr(),
+                        code.data(),
+                        cheat.description,
+                    ),
+                    None => writeln!(
+                        out,
+                        "{disabled}S:{:04X}:{:02X}:{}",
+                        code.addr(),
+                        code.data(),
+                        cheat.description,
+                    ),
+                };
+            }
+        }
+    }
+    out
+}
+
+/// Parses `cheatN_key = value` entries. Multiple codes for one cheat are joined with `+`.
+fn parse_retroarch(text: &str) -> Result<Vec<Cheat>> {
+    let mut entries = Vec::<(Option<(usize, String)>, String, bool)>::new();
+    for (line_num, line) in text.lines().enumerate() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let Some((index, field)) = key
+            .trim()
+            .strip_prefix("cheat")
+            .and_then(|key| key.split_once('_'))
+            .and_then(|(index, field)| Some((index.parse::<usize>().ok()?, field)))
+        else {
+            continue;
+        };
+        if entries.len() <= index {
+            entries.resize(index + 1, (None, String::new(), false));
+        }
+        let value = value.trim().trim_matches('"');
+        let entry = &mut entries[index];
+        match field {
+            "code" => entry.0 = Some((line_num + 1, value.to_string())),
+            "desc" => entry.1 = value.to_string(),
+            "enable" => entry.2 = value == "true",
+            _ => (),
+        }
+    }
+
+    let mut cheats = Vec::new();
+    for (code, description, enabled) in entries {
+        let Some((line, code)) = code else {
+            continue;
+        };
+        for code in code
+            .split('+')
+            .map(str::trim)
+            .filter(|code| !code.is_empty())
+        {
+            let code = GenieCode::new(code.to_string())
+                .map_err(|source| Error::InvalidCode { line, source })?;
+            cheats.push(Cheat {
+                code,
+                description: description.clone(),
+                enabled,
+            });
+        }
+    }
+    Ok(cheats)
+}
+
+/// Parses `<cheat>` elements containing either a `<genie>` code or an `<address>`, `<value>`
+/// and optional `<compare>`.
+fn parse_nestopia(text: &str) -> Result<Vec<Cheat>> {
+    let line_of = |offset: usize| text[..offset].lines().count().max(1);
+    let mut cheats = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest
+        .match_indices("<cheat")
+        .map(|(start, _)| start)
+        .find(|&start| rest[start + 6..].starts_with([' ', '>']))
+    {
+        let offset = text.len() - rest.len() + start;
+        let line = line_of(offset);
+        let element = &rest[start..];
+        let end = element
+            .find("</cheat>")
+            .ok_or_else(|| Error::InvalidCheat {
+                line,
+                reason: "missing </cheat>".to_string(),
+            })?;
+        let element = &element[..end];
+        rest = &rest[start + end..];
+
+        let tag = &element[..element.find('>').unwrap_or(element.len())];
+        let enabled = !tag.contains("enabled=\"0\"");
+        let description = xml_tag(element, "description")
+            .map(xml_unescape)
+            .unwrap_or_default();
+        let code = if let Some(genie) = xml_tag(element, "genie") {
+            GenieCode::new(genie.trim().to_string())
+                .map_err(|source| Error::InvalidCode { line, source })?
+        } else {
+            let invalid = |reason: &str| Error::InvalidCheat {
+                line,
+                reason: reason.to_string(),
+            };
+            let addr = xml_tag(element, "address")
+                .and_then(parse_hex)
+                .and_then(|addr| u16::try_from(addr).ok())
+                .ok_or_else(|| invalid("missing or invalid <address>"))?;
+            let data = xml_tag(element, "value")
+                .and_then(parse_hex)
+                .and_then(|value| u8::try_from(value).ok())
+                .ok_or_else(|| invalid("missing or invalid <value>"))?;
+            let compare = match xml_tag(element, "compare") {
+                Some(compare) => Some(
+                    parse_hex(compare)
+                        .and_then(|compare| u8::try_from(compare).ok())
+                        .ok_or_else(|| invalid("invalid <compare>"))?,
+                ),
+                None => None,
+            };
+            GenieCode::from_addr(addr, data, compare)
+        };
+        cheats.push(Cheat {
+            code,
+            description,
+            enabled,
+        });
+    }
+    Ok(cheats)
+}
+
+/// Parses `[*][S][C]:AAAA:VV[:CC]:Name` lines, where `*` marks a disabled cheat and `C` a
+/// compare value.
+fn parse_fceux(text: &str) -> Result<Vec<Cheat>> {
+    let mut cheats = Vec::new();
+    for (line_num, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let invalid = || Error::InvalidCheat {
+            line: line_num + 1,
+            reason: "expected `[S][C]:AAAA:VV[:CC]:Name`".to_string(),
+        };
+        let (enabled, line) = match line.strip_prefix('*') {
+            Some(line) => (false, line),
+            None => (true, line),
+        };
+        let (flags, rest) = line.split_once(':').ok_or_else(invalid)?;
+        let has_compare = flags.contains('C');
+        let mut fields = rest.splitn(if has_compare { 4 } else { 3 }, ':');
+        let mut next_hex = || fields.next().and_then(parse_hex).ok_or_else(invalid);
+        let addr = u16::try_from(next_hex()?).map_err(|_| invalid())?;
+        let data = u8::try_from(next_hex()?).map_err(|_| invalid())?;
+        let compare = if has_compare {
+            Some(u8::try_from(next_hex()?).map_err(|_| invalid())?)
+        } else {
+            None
+        };
+        let description = fields.next().unwrap_or_default().to_string();
+        cheats.push(Cheat {
+            code: GenieCode::from_addr(addr, data, compare),
+            description,
+            enabled,
+        });
+    }
+    Ok(cheats)
+}
+
+/// Parses a hex value, with or without a `0x` or `$` prefix.
+fn parse_hex(value: &str) -> Option<u32> {
+    let value = value.trim();
+    let value = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .or_else(|| value.strip_prefix('$'))
+        .unwrap_or(value);
+    u32::from_str_radix(value, 16).ok()
+}
+
+/// Returns the text inside the first `<tag>...</tag>` in `element`.
+fn xml_tag<'a>(element: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+    let start = element.find(&open)? + open.len();
+    let end = element[start..].find(&close)? + start;
+    Some(&element[start..end])
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_codes() {
+        let code = GenieCode::new("0075:09".to_string()).expect("valid raw code");
+        assert!(code.is_raw());
+        assert_eq!(
+            (code.addr(), code.data(), code.compare()),
+            (0x0075, 0x09, None)
+        );
+        let code = GenieCode::new("0075?05:09".to_string()).expect("valid raw code");
+        assert_eq!(code.compare(), Some(0x05));
+        assert!(GenieCode::new("0075:".to_string()).is_err());
+        assert!(GenieCode::new("AATOZE".to_string()).is_ok_and(|code| !code.is_raw()));
+    }
+
+    #[test]
+    fn retroarch() {
+        let text = concat!(
+            "cheats = 2\n\n",
+            "cheat0_desc = \"Start with 9 lives\"\n",
+            "cheat0_code = \"AATOZE\"\n",
+            "cheat0_enable = false\n\n",
+            "cheat1_desc = \"Infinite time\"\n",
+            "cheat1_code = \"07F8:09+07F9?00:09\"\n",
+            "cheat1_enable = true\n",
+        );
+        let (format, cheats) = parse(text).expect("valid cheats");
+        assert_eq!(format, Format::RetroArch);
+        assert_eq!(cheats.len(), 3);
+        assert_eq!(cheats[0].code.code(), "AATOZE");
+        assert_eq!(cheats[0].description, "Start with 9 lives");
+        assert!(!cheats[0].enabled);
+        assert_eq!(cheats[2].code.code(), "07F9?00:09");
+        assert!(cheats[2].enabled);
+
+        let exported = export(Format::RetroArch, &cheats);
+        assert_eq!(parse(&exported).expect("valid export").1, cheats);
+    }
+
+    #[test]
+    fn nestopia() {
+        let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+<cheats version="1.0">
+  <cheat enabled="1">
+    <genie>SXIOPO</genie>
+    <description>Infinite lives &amp; time</description>
+  </cheat>
+  <cheat enabled="0">
+    <address>0x0075</address>
+    <value>0x09</value>
+    <compare>0x05</compare>
+    <description>Lives</description>
+  </cheat>
+</cheats>
+"#;
+        let (format, cheats) = parse(text).expect("valid cheats");
+        assert_eq!(format, Format::Nestopia);
+        assert_eq!(cheats.len(), 2);
+        assert_eq!(cheats[0].description, "Infinite lives & time");
+        assert!(cheats[0].enabled);
+        assert_eq!(
+            cheats[1].code,
+            GenieCode::from_addr(0x0075, 0x09, Some(0x05))
+        );
+        assert!(!cheats[1].enabled);
+
+        let exported = export(Format::Nestopia, &cheats);
+        assert_eq!(parse(&exported).expect("valid export").1, cheats);
+    }
+
+    #[test]
+    fn fceux() {
+        let text = "S:0075:09:Lives\n*SC:07F8:09:00:Time: frozen\n";
+        let (format, cheats) = parse(text).expect("valid cheats");
+        assert_eq!(format, Format::Fceux);
+        assert_eq!(cheats.len(), 2);
+        assert_eq!(cheats[0].code, GenieCode::from_addr(0x0075, 0x09, None));
+        assert_eq!(
+            cheats[1].code,
+            GenieCode::from_addr(0x07F8, 0x09, Some(0x00))
+        );
+        assert_eq!(cheats[1].description, "Time: frozen");
+        assert!(!cheats[1].enabled);
+
+        let exported = export(Format::Fceux, &cheats);
+        assert_eq!(parse(&exported).expect("valid export").1, cheats);
+    }
+}
@@ -1,6 +1,7 @@
 //! Common traits and constants.
 
 use enum_dispatch::enum_dispatch;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::fmt::Write;
 use thiserror::Error;
@@ -13,7 +14,8 @@ pub const SRAM_DIR: &str = "sram";
 #[error("failed to parse `NesRegion`")]
 pub struct ParseNesRegionError;
 
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum NesRegion {
     Auto,
@@ -21,6 +23,8 @@ pub enum NesRegion {
     Ntsc,
     Pal,
     Dendy,
+    /// Brazilian PAL-M famiclones, which run with NTSC timing at 60Hz.
+    PalM,
 }
 
 impl NesRegion {
@@ -30,6 +34,7 @@ impl NesRegion {
             NesRegion::Ntsc,
             NesRegion::Pal,
             NesRegion::Dendy,
+            NesRegion::PalM,
         ]
     }
 
@@ -38,9 +43,10 @@ impl NesRegion {
         self == &Self::Auto
     }
 
+    /// Whether this region uses NTSC timing.
     #[must_use]
     pub const fn is_ntsc(&self) -> bool {
-        matches!(self, Self::Auto | Self::Ntsc)
+        matches!(self, Self::Auto | Self::Ntsc | Self::PalM)
     }
 
     #[must_use]
@@ -57,7 +63,7 @@ impl NesRegion {
     pub fn aspect_ratio(&self) -> f32 {
         // https://www.nesdev.org/wiki/Overscan
         match self {
-            Self::Auto | Self::Ntsc => 8.0 / 7.0,
+            Self::Auto | Self::Ntsc | Self::PalM => 8.0 / 7.0,
             Self::Pal | Self::Dendy => 18.0 / 13.0,
         }
     }
@@ -69,6 +75,7 @@ impl NesRegion {
             Self::Ntsc => "ntsc",
             Self::Pal => "pal",
             Self::Dendy => "dendy",
+            Self::PalM => "pal-m",
         }
     }
 }
@@ -80,6 +87,7 @@ impl std::fmt::Display for NesRegion {
             Self::Ntsc => "NTSC",
             Self::Pal => "PAL",
             Self::Dendy => "Dendy",
+            Self::PalM => "PAL-M",
         };
         write!(f, "{s}")
     }
@@ -100,6 +108,7 @@ impl TryFrom<&str> for NesRegion {
             "ntsc" => Ok(Self::Ntsc),
             "pal" => Ok(Self::Pal),
             "dendy" => Ok(Self::Dendy),
+            "pal-m" => Ok(Self::PalM),
             _ => Err(ParseNesRegionError),
         }
     }
@@ -114,6 +123,7 @@ impl TryFrom<usize> for NesRegion {
             1 => Ok(Self::Ntsc),
             2 => Ok(Self::Pal),
             3 => Ok(Self::Dendy),
+            4 => Ok(Self::PalM),
             _ => Err(ParseNesRegionError),
         }
     }
@@ -129,10 +139,14 @@ pub trait Regional {
 }
 
 /// Type of reset for types that have different behavior for reset vs power cycling.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum ResetKind {
+    /// Pressing the reset button. The CPU, PPU and APU are reset, but the cartridge has no reset
+    /// line, so mapper registers and RAM contents are left intact.
     Soft,
+    /// Cycling power, which resets everything to its power-on state.
     Hard,
 }
 
@@ -231,6 +245,7 @@ pub fn hexdump(data: &[u8], addr_offset: usize) -> Vec<String> {
 
 #[cfg(test)]
 pub(crate) mod tests {
+    use crate::logging::debug;
     use crate::{
         action::Action,
         common::{Regional, Reset, ResetKind},
@@ -253,7 +268,6 @@ pub(crate) mod tests {
         path::{Path, PathBuf},
         sync::OnceLock,
     };
-    use tracing::debug;
 
     pub(crate) const RESULT_DIR: &str = "test_results";
 
@@ -316,7 +330,8 @@ pub(crate) mod tests {
         Ok((file, tests))
     }
 
-    fn load_control_deck<P: AsRef<Path>>(path: P) -> ControlDeck {
+    /// Loads the ROM at `path` into a [`ControlDeck`] with zeroed RAM so tests are deterministic.
+    pub(crate) fn load_control_deck<P: AsRef<Path>>(path: P) -> ControlDeck {
         let path = path.as_ref();
         let mut rom = BufReader::new(File::open(path).expect("failed to open path"));
         let mut deck = ControlDeck::with_config(Config {
@@ -334,6 +349,7 @@ pub(crate) mod tests {
             debug!("{:?}", action);
             match action {
                 Action::Reset(kind) => deck.reset(kind),
+                Action::TogglePower => deck.set_power(!deck.is_powered()),
                 Action::MapperRevision(rev) => deck.set_mapper_revision(rev),
                 Action::SetVideoFilter(filter) => deck.set_filter(filter),
                 Action::SetNesRegion(format) => deck.set_region(format),
@@ -344,7 +360,9 @@ pub(crate) mod tests {
                 Action::ToggleZapperConnected => deck.connect_zapper(!deck.zapper_connected()),
                 Action::ZapperAim((x, y)) => deck.aim_zapper(x, y),
                 Action::ZapperTrigger => deck.trigger_zapper(),
+                Action::InsertCoin(slot) => deck.insert_coin(slot.into()),
                 Action::LoadState
+                | Action::HoldReset
                 | Action::SaveState
                 | Action::SetSaveSlot(_)
                 | Action::ToggleApuChannel(_)
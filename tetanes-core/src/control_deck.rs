@@ -1,28 +1,39 @@
 //! Control Deck implementation. The primary entry-point for emulating the NES.
 
+#[cfg(feature = "debug")]
+use crate::debug::BusActivity;
+#[cfg(feature = "serde")]
+use crate::debug::StepHistory;
 use crate::{
-    apu::{self, Apu, Channel},
+    apu::{self, dmc::DmcSample, Apu, AudioChannel, Channel},
     bus::Bus,
     cart::{self, Cart},
     common::{Clock, NesRegion, Regional, Reset, ResetKind, Sram},
-    cpu::Cpu,
-    debug::Debugger,
+    cpu::{disasm, instr::Operation, Cpu},
+    debug::{Breakpoint, CpuDebugState, Debugger, Interrupt, InterruptLog},
     fs,
     genie::{self, GenieCode},
-    input::{FourPlayer, Joypad, Player},
+    input::{FourPlayer, Joypad, MouseBtn, MousePort, Player},
+    logging::{error, info},
     mapper::{Bf909Revision, Mapper, MapperRevision, Mmc3Revision},
-    mem::RamState,
+    mem::{RamRetention, RamState},
     ppu::Ppu,
-    video::{Video, VideoFilter},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+    video::{ColorFilter, PixelFormat, Video, VideoFilter},
 };
 use bitflags::bitflags;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::{
     io::Read,
+    ops::RangeInclusive,
     path::{Path, PathBuf},
 };
 use thiserror::Error;
-use tracing::{error, info};
+
+pub mod sync;
+
+pub use sync::{SyncControlDeck, SyncControlDeckGuard};
 
 /// Result returned from [`ControlDeck`] methods.
 pub type Result<T> = std::result::Result<T, Error>;
@@ -50,6 +61,12 @@ pub enum Error {
     /// corrupt save state.
     #[error("cpu state is corrupted")]
     CpuCorrupted,
+    /// Emulation was halted mid-frame because a [`Breakpoint`] condition was met.
+    #[error("breakpoint hit: {0}")]
+    Breakpoint(Breakpoint),
+    /// When trying to step back without any earlier instructions recorded.
+    #[error("no earlier instruction to step back to")]
+    NoStepHistory,
     /// Invalid Game Genie code error.
     #[error(transparent)]
     InvalidGenieCode(#[from] genie::Error),
@@ -80,7 +97,8 @@ impl Error {
 
 bitflags! {
     /// Headless mode flags to disable audio and video processing, reducing CPU usage.
-    #[derive(Default, Debug, Copy, Clone, PartialEq, Serialize, Deserialize, )]
+    #[derive(Default, Debug, Copy, Clone, PartialEq, )]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     #[must_use]
     pub struct HeadlessMode: u8 {
         /// Disable audio mixing.
@@ -91,7 +109,8 @@ bitflags! {
 }
 
 /// Set of desired mapper revisions to use when loading a ROM matching the available mapper types.
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct MapperRevisionsConfig {
     /// MMC3 mapper revision.
@@ -110,8 +129,9 @@ impl MapperRevisionsConfig {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(default)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[must_use]
 /// Control deck configuration settings.
 pub struct Config {
@@ -120,14 +140,20 @@ pub struct Config {
     pub cycle_accurate: bool,
     /// Video filter.
     pub filter: VideoFilter,
+    /// Colorblind assistance filter applied after the video filter.
+    pub color_filter: ColorFilter,
     /// NES region.
     pub region: NesRegion,
     /// RAM initialization state.
     pub ram_state: RamState,
+    /// RAM contents kept across a reset or power cycle.
+    pub ram_retention: RamRetention,
     /// Four player adapter.
     pub four_player: FourPlayer,
     /// Enable zapper gun.
     pub zapper: bool,
+    /// Controller port a SNES mouse is plugged into.
+    pub mouse: MousePort,
     /// Game Genie codes.
     pub genie_codes: Vec<GenieCode>,
     /// Whether to support concurrent D-Pad input which wasn't possible on the original NES.
@@ -145,6 +171,9 @@ pub struct Config {
     ///
     /// See: <https://www.nesdev.org/wiki/PPU_power_up_state>
     pub emulate_ppu_warmup: bool,
+    /// DIP switches 1-8 in bits 0-7 for Vs. System games, configuring game settings like
+    /// difficulty and lives.
+    pub vs_dip_switches: u8,
 }
 
 impl Config {
@@ -175,10 +204,13 @@ impl Default for Config {
         Self {
             cycle_accurate: true,
             filter: VideoFilter::default(),
+            color_filter: ColorFilter::default(),
             region: NesRegion::Auto,
             ram_state: RamState::Random,
+            ram_retention: RamRetention::default(),
             four_player: FourPlayer::default(),
             zapper: false,
+            mouse: MousePort::default(),
             genie_codes: vec![],
             concurrent_dpad: false,
             channels_enabled: [true; Apu::MAX_CHANNEL_COUNT],
@@ -186,6 +218,7 @@ impl Default for Config {
             data_dir: Self::default_data_dir(),
             mapper_revisions: MapperRevisionsConfig::default(),
             emulate_ppu_warmup: false,
+            vs_dip_switches: 0x00,
         }
     }
 }
@@ -199,6 +232,52 @@ pub struct LoadedRom {
     pub battery_backed: bool,
     /// Auto-detected of the loaded Cart.
     pub region: NesRegion,
+    /// CRC32 of the loaded Cart's ROM data, excluding the header.
+    pub crc32: u32,
+    /// Audio channels output by the loaded Cart, including any expansion audio.
+    pub audio_channels: Vec<AudioChannel>,
+    /// Whether the loaded Cart is for the Vs. System arcade hardware.
+    pub vs_system: bool,
+    /// Whether the loaded Cart has a 512-byte trainer, loaded into PRG-RAM at $7000.
+    pub trainer: bool,
+}
+
+/// Details saved alongside the console state in a save state, e.g. to list save states without
+/// loading them.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub struct SaveStateInfo {
+    /// When the state was saved, in seconds since the Unix epoch.
+    pub saved_at: u64,
+    /// Emulated time since power on.
+    pub play_time: Duration,
+    pub frame_number: u32,
+    /// RGBA screenshot of the frame the state was saved on, downscaled to
+    /// [`SaveStateInfo::THUMBNAIL_WIDTH`] x [`SaveStateInfo::THUMBNAIL_HEIGHT`].
+    pub thumbnail: Vec<u8>,
+}
+
+impl SaveStateInfo {
+    pub const THUMBNAIL_WIDTH: u32 = Ppu::WIDTH / 2;
+    pub const THUMBNAIL_HEIGHT: u32 = Ppu::HEIGHT / 2;
+}
+
+/// Emulation counters for the last call to [`ControlDeck::clock_frame`].
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub struct FrameCounters {
+    /// Number of frames clocked, which depends on the current frame speed.
+    pub frames: usize,
+    /// Number of CPU cycles clocked, including DMA stalls.
+    pub cpu_cycles: usize,
+    /// Number of PPU dots clocked.
+    pub ppu_dots: usize,
+    /// Number of CPU cycles stalled for OAM and DMC DMA.
+    pub dma_cycles: usize,
+    /// Number of audio samples generated.
+    pub audio_samples: usize,
 }
 
 /// Represents an NES Control Deck. Encapsulates the entire emulation state.
@@ -207,10 +286,14 @@ pub struct LoadedRom {
 pub struct ControlDeck {
     /// Whether a ROM is loaded and the emulation is currently running or not.
     running: bool,
+    /// Whether the power switch is on.
+    powered: bool,
+    /// Whether the reset button is being held, keeping the console in reset.
+    reset_held: bool,
     /// Video output and filtering.
     video: Video,
     /// Last frame number rendered, allowing `frame_buffer` to be cached if called multiple times.
-    last_frame_number: u32,
+    last_frame_number: Option<u32>,
     /// The currently loaded ROM [`Cart`], if any.
     loaded_rom: Option<LoadedRom>,
     /// Directory for storing battery-backed Cart RAM if a ROM is loaded.
@@ -219,14 +302,27 @@ pub struct ControlDeck {
     mapper_revisions: MapperRevisionsConfig,
     /// Whether to auto-detect the region based on the loaded Cart.
     auto_detect_region: bool,
+    /// DIP switches for any Vs. System ROM loaded.
+    vs_dip_switches: u8,
+    /// RAM contents kept across a reset or power cycle.
+    ram_retention: RamRetention,
     /// Remaining CPU cycles to execute used to clock a given number of seconds.
     cycles_remaining: f32,
     /// Emulated frame speed ranging from 0.25 to 2.0.
     frame_speed: f32,
     /// Accumulated frame speed to account for slower 1x speeds.
     frame_accumulator: f32,
+    /// Emulation counters for the last clocked frame.
+    frame_counters: FrameCounters,
+    /// Number of frames where no controller input was read.
+    lag_frames: u32,
+    /// Whether the last clocked frame was a lag frame.
+    lag_frame: bool,
     /// NES CPU.
     cpu: Cpu,
+    /// Instruction snapshots used to step backwards, if enabled.
+    #[cfg(feature = "serde")]
+    step_history: Option<StepHistory>,
 }
 
 impl Default for ControlDeck {
@@ -236,6 +332,10 @@ impl Default for ControlDeck {
 }
 
 impl ControlDeck {
+    /// Maximum instructions run by [`ControlDeck::step_over`] and [`ControlDeck::step_out`]
+    /// before giving up, in case a subroutine never returns.
+    const MAX_STEP_INSTRS: usize = 1_000_000;
+
     /// Create a NES `ControlDeck` with the default configuration.
     pub fn new() -> Self {
         Self::with_config(Config::default())
@@ -255,28 +355,39 @@ impl ControlDeck {
         cpu.bus.input.set_concurrent_dpad(cfg.concurrent_dpad);
         cpu.bus.input.set_four_player(cfg.four_player);
         cpu.bus.input.connect_zapper(cfg.zapper);
+        cpu.bus.input.connect_mouse(cfg.mouse);
         for (i, enabled) in cfg.channels_enabled.iter().enumerate() {
             match Channel::try_from(i) {
                 Ok(channel) => cpu.bus.apu.set_channel_enabled(channel, *enabled),
-                Err(apu::ParseChannelError) => tracing::error!("invalid APU channel: {i}"),
+                Err(apu::ParseChannelError) => crate::logging::error!("invalid APU channel: {i}"),
             }
         }
         for genie_code in cfg.genie_codes.iter().cloned() {
             cpu.bus.add_genie_code(genie_code);
         }
-        let video = Video::with_filter(cfg.filter);
+        let mut video = Video::with_filter(cfg.filter);
+        video.color_filter = cfg.color_filter;
         Self {
             running: false,
+            powered: true,
+            reset_held: false,
             video,
-            last_frame_number: 0,
+            last_frame_number: None,
             loaded_rom: None,
             sram_dir: cfg.sram_dir(),
             mapper_revisions: cfg.mapper_revisions,
             auto_detect_region: cfg.region.is_auto(),
+            vs_dip_switches: cfg.vs_dip_switches,
+            ram_retention: cfg.ram_retention,
             cycles_remaining: 0.0,
             frame_speed: 1.0,
             frame_accumulator: 0.0,
+            frame_counters: FrameCounters::default(),
+            lag_frames: 0,
+            lag_frame: false,
             cpu,
+            #[cfg(feature = "serde")]
+            step_history: None,
         }
     }
 
@@ -294,7 +405,9 @@ impl ControlDeck {
     /// If there is any issue loading the ROM, then an error is returned.
     pub fn load_rom<S: ToString, F: Read>(&mut self, name: S, rom: &mut F) -> Result<LoadedRom> {
         let name = name.to_string();
-        self.unload_rom()?;
+        if let Err(err) = self.unload_rom() {
+            error!("failed to unload ROM: {err:?}");
+        }
         let cart = Cart::from_rom(&name, rom, self.cpu.bus.ram_state)?;
         if cart.mapper.is_none() {
             return Err(Error::UnimplementedMapper(cart.mapper_num()));
@@ -303,19 +416,24 @@ impl ControlDeck {
             name: name.clone(),
             battery_backed: cart.battery_backed(),
             region: cart.region(),
+            crc32: cart.crc32(),
+            audio_channels: AudioChannel::list(cart.mapper.expansion_audio()),
+            vs_system: cart.is_vs_system(),
+            trainer: cart.has_trainer(),
         };
         if self.auto_detect_region {
             self.cpu.set_region(loaded_rom.region);
         }
         self.cpu.bus.load_cart(cart);
+        self.set_vs_dip_switches(self.vs_dip_switches);
         self.update_mapper_revisions();
+        self.loaded_rom = Some(loaded_rom.clone());
         self.reset(ResetKind::Hard);
         self.running = true;
         let sram_dir = self.sram_dir(&name);
         if let Err(err) = self.load_sram(sram_dir) {
             error!("failed to load SRAM: {err:?}");
         }
-        self.loaded_rom = Some(loaded_rom.clone());
         Ok(loaded_rom)
     }
 
@@ -339,24 +457,27 @@ impl ControlDeck {
     ///
     /// # Errors
     ///
-    /// If the loaded [`Cart`] is battery-backed and saving fails, then an error is returned.
+    /// If the loaded [`Cart`] is battery-backed and saving fails, then an error is returned, though
+    /// the ROM is still unloaded.
     pub fn unload_rom(&mut self) -> Result<()> {
-        if let Some(rom) = &self.loaded_rom {
-            let sram_dir = self.sram_dir(&rom.name);
-            if let Err(err) = self.save_sram(sram_dir) {
-                error!("failed to save SRAM: {err:?}");
-            }
-        }
+        let saved = match &self.loaded_rom {
+            Some(rom) => self.save_sram(self.sram_dir(&rom.name)),
+            None => Ok(()),
+        };
         self.loaded_rom = None;
         self.cpu.bus.unload_cart();
         self.running = false;
-        Ok(())
+        saved
     }
 
     /// Load a previously saved CPU state.
     #[inline]
     pub fn load_cpu(&mut self, cpu: Cpu) {
         self.cpu.load(cpu);
+        #[cfg(feature = "serde")]
+        if let Some(step_history) = &mut self.step_history {
+            step_history.clear();
+        }
     }
 
     /// Set the [`MapperRevision`] to emulate for the any ROM loaded that uses this mapper.
@@ -378,37 +499,16 @@ impl ControlDeck {
     /// updated.
     fn update_mapper_revisions(&mut self) {
         match &mut self.cpu.bus.ppu.bus.mapper {
+            #[cfg(feature = "mapper-txrom")]
             Mapper::Txrom(mapper) => {
                 mapper.set_revision(self.mapper_revisions.mmc3);
             }
+            #[cfg(feature = "mapper-bf909x")]
             Mapper::Bf909x(mapper) => {
                 mapper.set_revision(self.mapper_revisions.bf909);
             }
             // Remaining mappers all have more concrete detection via ROM headers
-            Mapper::None(_)
-            | Mapper::Nrom(_)
-            | Mapper::Sxrom(_)
-            | Mapper::Uxrom(_)
-            | Mapper::Cnrom(_)
-            | Mapper::Exrom(_)
-            | Mapper::Axrom(_)
-            | Mapper::Pxrom(_)
-            | Mapper::Fxrom(_)
-            | Mapper::ColorDreams(_)
-            | Mapper::BandaiFCG(_)
-            | Mapper::JalecoSs88006(_)
-            | Mapper::Namco163(_)
-            | Mapper::Vrc6(_)
-            | Mapper::Bnrom(_)
-            | Mapper::Nina001(_)
-            | Mapper::Gxrom(_)
-            | Mapper::SunsoftFme7(_)
-            | Mapper::Dxrom76(_)
-            | Mapper::Nina003006(_)
-            | Mapper::Dxrom88(_)
-            | Mapper::Dxrom95(_)
-            | Mapper::Dxrom154(_)
-            | Mapper::Dxrom206(_) => (),
+            _ => (),
         }
     }
 
@@ -431,6 +531,36 @@ impl ControlDeck {
         self.cpu.bus.ram_state = ram_state;
     }
 
+    /// Returns which RAM contents are kept across a reset or power cycle.
+    #[inline]
+    pub const fn ram_retention(&self) -> RamRetention {
+        self.ram_retention
+    }
+
+    /// Set which RAM contents are kept across a reset or power cycle.
+    #[inline]
+    pub fn set_ram_retention(&mut self, ram_retention: RamRetention) {
+        self.ram_retention = ram_retention;
+    }
+
+    /// Re-initialize RAM to its power-on state, keeping battery-backed RAM or RAM holding a
+    /// trainer.
+    fn clear_ram(&mut self) {
+        let ram_state = self.cpu.bus.ram_state;
+        self.cpu.bus.wram.fill_ram(ram_state);
+        if !self
+            .loaded_rom
+            .as_ref()
+            .is_some_and(|rom| rom.battery_backed || rom.trainer)
+        {
+            self.cpu.bus.prg_ram.fill_ram(ram_state);
+        }
+        let ppu_bus = &mut self.cpu.bus.ppu.bus;
+        ppu_bus.chr_ram.fill_ram(ram_state);
+        ppu_bus.ciram.fill_ram(ram_state);
+        ppu_bus.exram.fill_ram(ram_state);
+    }
+
     /// Set the headless mode which can increase performance when the frame and audio outputs are
     /// not needed.
     #[inline]
@@ -463,6 +593,130 @@ impl ControlDeck {
         }
     }
 
+    /// Adds a [`Breakpoint`] that halts emulation with [`Error::Breakpoint`] when its condition
+    /// is met.
+    pub fn add_breakpoint(&mut self, breakpoint: impl Into<Breakpoint>) {
+        match breakpoint.into() {
+            Breakpoint::Cpu(breakpoint) => {
+                let breakpoints = &mut self.cpu.breakpoints;
+                if !breakpoints.contains(&breakpoint) {
+                    breakpoints.push(breakpoint);
+                }
+            }
+            Breakpoint::Ppu(breakpoint) => {
+                let breakpoints = &mut self.cpu.bus.ppu.breakpoints;
+                if !breakpoints.contains(&breakpoint) {
+                    breakpoints.push(breakpoint);
+                }
+            }
+        }
+    }
+
+    /// Removes a [`Breakpoint`].
+    pub fn remove_breakpoint(&mut self, breakpoint: impl Into<Breakpoint>) {
+        match breakpoint.into() {
+            Breakpoint::Cpu(breakpoint) => {
+                self.cpu.breakpoints.retain(|bp| *bp != breakpoint);
+            }
+            Breakpoint::Ppu(breakpoint) => {
+                self.cpu.bus.ppu.breakpoints.retain(|bp| *bp != breakpoint);
+            }
+        }
+    }
+
+    /// Removes all [`Breakpoint`]s.
+    pub fn clear_breakpoints(&mut self) {
+        self.cpu.breakpoints.clear();
+        self.cpu.breakpoint_hit = None;
+        self.cpu.bus.ppu.breakpoints.clear();
+        self.cpu.bus.ppu.breakpoint_hit = None;
+    }
+
+    /// Returns the list of currently set [`Breakpoint`]s.
+    #[must_use]
+    pub fn breakpoints(&self) -> Vec<Breakpoint> {
+        let cpu = self.cpu.breakpoints.iter().copied().map(Breakpoint::from);
+        let ppu = self.cpu.bus.ppu.breakpoints.iter().copied();
+        cpu.chain(ppu.map(Breakpoint::from)).collect()
+    }
+
+    /// Enable or disable logging of serviced NMI and IRQ interrupts.
+    pub fn set_interrupt_log_enabled(&mut self, enabled: bool) {
+        self.cpu.interrupt_log.set_enabled(enabled);
+    }
+
+    /// Returns the [`InterruptLog`] of recently serviced interrupts.
+    #[inline]
+    pub const fn interrupt_log(&self) -> &InterruptLog {
+        &self.cpu.interrupt_log
+    }
+
+    /// Removes and returns all [`Interrupt`]s logged since the last call.
+    pub fn take_interrupts(&mut self) -> Vec<Interrupt> {
+        self.cpu.interrupt_log.drain()
+    }
+
+    /// Enable or disable logging of the unique DPCM samples played by the APU DMC channel.
+    pub fn set_dmc_sample_log_enabled(&mut self, enabled: bool) {
+        self.cpu.bus.apu.dmc.sample_log.set_enabled(enabled);
+    }
+
+    /// Returns the [`DmcSample`]s logged since logging was enabled.
+    #[inline]
+    pub fn dmc_samples(&self) -> &[DmcSample] {
+        self.cpu.bus.apu.dmc.sample_log.samples()
+    }
+
+    /// Enable or disable outputting audio samples for each APU channel on its own, e.g. to
+    /// record channels to separate files. Unlike [`ControlDeck::audio_samples`], these are kept
+    /// until cleared with [`ControlDeck::clear_channel_samples`], other than by
+    /// [`ControlDeck::clock_frame_ahead`] which only keeps those of the run-ahead frame.
+    pub fn set_channel_split_enabled(&mut self, enabled: bool) {
+        self.cpu.bus.apu.set_channel_split_enabled(enabled);
+    }
+
+    /// Whether audio samples are output for each APU channel on its own.
+    #[inline]
+    #[must_use]
+    pub const fn channel_split_enabled(&self) -> bool {
+        self.cpu.bus.apu.channel_split.is_some()
+    }
+
+    /// Returns the audio samples output by an APU channel on its own since they were last
+    /// cleared.
+    #[inline]
+    #[must_use]
+    pub fn channel_samples(&self, channel: Channel) -> &[f32] {
+        self.cpu.bus.apu.channel_samples(channel)
+    }
+
+    /// Clear the audio samples output by each APU channel on its own.
+    #[inline]
+    pub fn clear_channel_samples(&mut self) {
+        self.cpu.bus.apu.clear_channel_samples();
+    }
+
+    /// Enable or disable counting of CPU bus reads and writes per address.
+    #[cfg(feature = "debug")]
+    pub fn set_bus_activity_enabled(&mut self, enabled: bool) {
+        self.cpu.bus.activity.set_enabled(enabled);
+    }
+
+    /// Returns the [`BusActivity`] counts accumulated since the last reset.
+    #[cfg(feature = "debug")]
+    #[inline]
+    pub const fn bus_activity(&self) -> &BusActivity {
+        &self.cpu.bus.activity
+    }
+
+    /// Returns a copy of the accumulated [`BusActivity`] and resets the counts.
+    #[cfg(feature = "debug")]
+    pub fn take_bus_activity(&mut self) -> BusActivity {
+        let activity = self.cpu.bus.activity.clone();
+        self.cpu.bus.activity.clear();
+        activity
+    }
+
     /// Returns the name of the currently loaded ROM [`Cart`]. Returns `None` if no ROM is loaded.
     #[inline]
     #[must_use]
@@ -499,6 +753,38 @@ impl ControlDeck {
         self.cpu.bus.sram()
     }
 
+    /// Returns a CRC32 hash of the CPU registers, Work RAM and Save RAM, which is cheap enough to
+    /// compute every frame and is used to detect when replays desync.
+    #[must_use]
+    pub fn state_hash(&self) -> u32 {
+        let cpu = &self.cpu;
+        let [pc_hi, pc_lo] = cpu.pc.to_be_bytes();
+        let registers = [
+            pc_hi,
+            pc_lo,
+            cpu.acc,
+            cpu.x,
+            cpu.y,
+            cpu.sp,
+            cpu.status.bits(),
+        ];
+        let crc32 = fs::compute_crc32(&registers);
+        let crc32 = fs::compute_combine_crc32(crc32, &self.frame_number().to_le_bytes());
+        let crc32 = fs::compute_combine_crc32(crc32, self.wram());
+        fs::compute_combine_crc32(crc32, self.sram())
+    }
+
+    /// Disassemble instructions starting within the given CPU address range without side-effects.
+    pub fn disassemble(&self, range: RangeInclusive<u16>) -> Vec<disasm::Instruction> {
+        disasm::disassemble_range(&self.cpu, range)
+    }
+
+    /// Returns the current CPU registers, stack and up to `before` and `after` instructions
+    /// disassembled around the program counter.
+    pub fn cpu_debug_state(&self, before: usize, after: usize) -> CpuDebugState {
+        CpuDebugState::new(&self.cpu, before, after)
+    }
+
     /// Save battery-backed Save RAM to a file (if cartridge supports it)
     ///
     /// # Errors
@@ -531,12 +817,10 @@ impl ControlDeck {
             if path.is_dir() {
                 return Err(Error::InvalidFilePath(path.to_path_buf()));
             }
-            if path.is_file() {
+            let path = path.with_extension(Config::SRAM_EXTENSION);
+            if fs::exists(&path) {
                 info!("loading SRAM...");
-                self.cpu
-                    .bus
-                    .load(path.with_extension(Config::SRAM_EXTENSION))
-                    .map_err(Error::Sram)?;
+                self.cpu.bus.load(path).map_err(Error::Sram)?;
             }
         }
         Ok(())
@@ -547,12 +831,67 @@ impl ControlDeck {
     /// # Errors
     ///
     /// If there is an issue saving the state, then an error is returned.
+    #[cfg(feature = "serde")]
     pub fn save_state(&mut self, path: impl AsRef<Path>) -> Result<()> {
         if self.loaded_rom().is_none() {
             return Err(Error::RomNotLoaded);
         };
+        self.save_cpu_state(path, &self.cpu)
+    }
+
+    /// Save the state of `cpu` into a save file, e.g. a state kept in memory. The console state
+    /// is followed by its [`SaveStateInfo`], which is ignored when loading the state.
+    ///
+    /// # Errors
+    ///
+    /// If there is an issue saving the state, then an error is returned.
+    #[cfg(feature = "serde")]
+    pub fn save_cpu_state(&self, path: impl AsRef<Path>, cpu: &Cpu) -> Result<()> {
+        let info = self.save_state_info(cpu);
+        fs::save(path, &(cpu, info)).map_err(Error::SaveState)
+    }
+
+    /// Details to save alongside the state of `cpu`, with a thumbnail rendered using the current
+    /// video filter.
+    pub fn save_state_info(&self, cpu: &Cpu) -> SaveStateInfo {
+        let ppu = &cpu.bus.ppu;
+        let mut frame = vec![0; 4 * Ppu::SIZE];
+        self.video
+            .apply_filter_into(ppu.frame_buffer(), ppu.frame_number(), &mut frame);
+        let scale = (Ppu::WIDTH / SaveStateInfo::THUMBNAIL_WIDTH) as usize;
+        let thumbnail = frame
+            .chunks_exact(4 * Ppu::WIDTH as usize)
+            .step_by(scale)
+            .flat_map(|row| row.chunks_exact(4).step_by(scale).flatten().copied())
+            .collect();
+        SaveStateInfo {
+            saved_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+            play_time: Duration::from_secs_f64(cpu.cycle as f64 / f64::from(cpu.clock_rate())),
+            frame_number: ppu.frame_number(),
+            thumbnail,
+        }
+    }
+
+    /// Load the details saved alongside a save state without loading it. Returns `None` for
+    /// save states from older versions, which didn't save them.
+    ///
+    /// # Errors
+    ///
+    /// If the save state doesn't exist or can't be read, then an error is returned.
+    #[cfg(feature = "serde")]
+    pub fn load_save_state_info(path: impl AsRef<Path>) -> Result<Option<SaveStateInfo>> {
         let path = path.as_ref();
-        fs::save(path, &self.cpu).map_err(Error::SaveState)
+        if !fs::exists(path) {
+            return Err(Error::NoSaveStateFound);
+        }
+        match fs::load::<(Cpu, SaveStateInfo)>(path) {
+            Ok((_, info)) => Ok(Some(info)),
+            // Older save states end after the console state
+            Err(fs::Error::DeserializationFailed(_)) => Ok(None),
+            Err(err) => Err(Error::SaveState(err)),
+        }
     }
 
     /// Load the console with data saved from a save state, if it exists.
@@ -560,6 +899,7 @@ impl ControlDeck {
     /// # Errors
     ///
     /// If there is an issue loading the save state, then an error is returned.
+    #[cfg(feature = "serde")]
     pub fn load_state(&mut self, path: impl AsRef<Path>) -> Result<()> {
         if self.loaded_rom().is_none() {
             return Err(Error::RomNotLoaded);
@@ -587,11 +927,11 @@ impl ControlDeck {
     pub fn frame_buffer(&mut self) -> &[u8] {
         // Avoid applying filter if the frame number hasn't changed
         let frame_number = self.cpu.bus.ppu.frame_number();
-        if self.last_frame_number == frame_number {
+        if self.last_frame_number == Some(frame_number) {
             return &self.video.frame;
         }
 
-        self.last_frame_number = frame_number;
+        self.last_frame_number = Some(frame_number);
         self.video
             .apply_filter(self.cpu.bus.ppu.frame_buffer(), frame_number)
     }
@@ -606,6 +946,22 @@ impl ControlDeck {
         );
     }
 
+    /// Load a frame worth of pixels in the given [`PixelFormat`] into the given buffer. `buffer`
+    /// should be at least [`PixelFormat::frame_size`] bytes.
+    pub fn frame_buffer_format_into(&mut self, format: PixelFormat, buffer: &mut [u8]) {
+        match format {
+            PixelFormat::Rgba8 => {
+                let frame = self.frame_buffer();
+                let len = frame.len().min(buffer.len());
+                buffer[..len].copy_from_slice(&frame[..len]);
+            }
+            PixelFormat::Rgb565 => Video::encode_rgb565(self.frame_buffer(), buffer),
+            PixelFormat::Indexed8 => {
+                Video::encode_indexed8(self.cpu.bus.ppu.frame_buffer(), buffer);
+            }
+        }
+    }
+
     /// Get the current frame number.
     #[inline]
     #[must_use]
@@ -626,6 +982,13 @@ impl ControlDeck {
         self.cpu.bus.clear_audio_samples();
     }
 
+    /// Append audio samples fading out to silence, to play before audio output is paused or
+    /// stopped so it doesn't end with a pop. Audio fades back in once the next frame is clocked.
+    #[inline]
+    pub fn fade_out_audio(&mut self) {
+        self.cpu.bus.apu.fade_out();
+    }
+
     /// CPU clock rate based on currently configured NES region.
     #[inline]
     #[must_use]
@@ -637,19 +1000,130 @@ impl ControlDeck {
     ///
     /// # Errors
     ///
-    /// If CPU encounters an invalid opcode, then an error is returned.
+    /// If CPU encounters an invalid opcode, then an error is returned. If a [`Breakpoint`] was hit
+    /// during the instruction, [`Error::Breakpoint`] is returned after the instruction completes
+    /// and emulation can be resumed by clocking again.
     pub fn clock_instr(&mut self) -> Result<usize> {
         if !self.running {
             return Err(Error::RomNotLoaded);
         }
+        #[cfg(feature = "serde")]
+        if let Some(step_history) = &mut self.step_history {
+            step_history.record(&self.cpu)?;
+        }
         let cycles = self.clock();
         if self.cpu_corrupted() {
             self.running = false;
             return Err(Error::CpuCorrupted);
         }
+        if let Some(breakpoint) = self.cpu.breakpoint_hit.take() {
+            return Err(Error::Breakpoint(breakpoint.into()));
+        }
+        if let Some(breakpoint) = self.cpu.bus.ppu.breakpoint_hit.take() {
+            return Err(Error::Breakpoint(breakpoint.into()));
+        }
         Ok(cycles)
     }
 
+    /// Enable or disable recording instruction snapshots to allow [`ControlDeck::step_back`].
+    /// Disabling discards any recorded history.
+    #[cfg(feature = "serde")]
+    pub fn set_step_history_enabled(&mut self, enabled: bool) {
+        match (enabled, &self.step_history) {
+            (true, None) => self.step_history = Some(StepHistory::default()),
+            (false, Some(_)) => self.step_history = None,
+            _ => (),
+        }
+    }
+
+    /// Returns the [`StepHistory`], if enabled.
+    #[cfg(feature = "serde")]
+    #[inline]
+    pub const fn step_history(&self) -> Option<&StepHistory> {
+        self.step_history.as_ref()
+    }
+
+    /// Steps the control deck back a single CPU instruction by restoring the nearest earlier
+    /// snapshot and replaying forward to the previous instruction. Video and audio produced by
+    /// the replay are discarded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NoStepHistory`] if step history is disabled or exhausted, or if the CPU
+    /// encounters an invalid opcode while replaying.
+    #[cfg(feature = "serde")]
+    pub fn step_back(&mut self) -> Result<()> {
+        if !self.running {
+            return Err(Error::RomNotLoaded);
+        }
+        let mut step_history = self.step_history.take().ok_or(Error::NoStepHistory)?;
+        let Some(target) = step_history.instr_count().checked_sub(1) else {
+            self.step_history = Some(step_history);
+            return Err(Error::NoStepHistory);
+        };
+        let restored = step_history.restore(target);
+        self.step_history = Some(step_history);
+        let (mut cpu, replay) = restored.ok_or(Error::NoStepHistory)??;
+
+        // Keep the current frame and avoid re-triggering breakpoints and re-logging interrupts
+        // that were already reported.
+        cpu.bus.ppu.frame.buffer = std::mem::take(&mut self.cpu.bus.ppu.frame.buffer);
+        self.cpu.load(cpu);
+        let step_history = self.step_history.take();
+        let cpu_breakpoints = std::mem::take(&mut self.cpu.breakpoints);
+        let breakpoints = std::mem::take(&mut self.cpu.bus.ppu.breakpoints);
+        let interrupt_log = std::mem::take(&mut self.cpu.interrupt_log);
+        let result = (0..replay).try_for_each(|_| self.clock_instr().map(|_| ()));
+        self.cpu.breakpoints = cpu_breakpoints;
+        self.cpu.bus.ppu.breakpoints = breakpoints;
+        self.cpu.interrupt_log = interrupt_log;
+        self.step_history = step_history;
+        self.clear_audio_samples();
+
+        result
+    }
+
+    /// Steps the control deck a single CPU instruction, running any subroutine called by `JSR`
+    /// until it returns.
+    ///
+    /// # Errors
+    ///
+    /// If CPU encounters an invalid opcode or a breakpoint is hit, then an error is returned.
+    pub fn step_over(&mut self) -> Result<usize> {
+        let instr = disasm::Instruction::peek(&self.cpu, self.cpu.pc);
+        if instr.op() != Operation::JSR {
+            return self.clock_instr();
+        }
+        let (ret, sp) = (instr.next_addr(), self.cpu.sp);
+        let mut total_cycles = 0;
+        for _ in 0..Self::MAX_STEP_INSTRS {
+            total_cycles += self.clock_instr()?;
+            if self.cpu.pc == ret && self.cpu.sp >= sp {
+                break;
+            }
+        }
+        Ok(total_cycles)
+    }
+
+    /// Steps the control deck until the current subroutine or interrupt handler returns with
+    /// `RTS` or `RTI`.
+    ///
+    /// # Errors
+    ///
+    /// If CPU encounters an invalid opcode or a breakpoint is hit, then an error is returned.
+    pub fn step_out(&mut self) -> Result<usize> {
+        let sp = self.cpu.sp;
+        let mut total_cycles = 0;
+        for _ in 0..Self::MAX_STEP_INSTRS {
+            let op = disasm::Instruction::peek(&self.cpu, self.cpu.pc).op();
+            total_cycles += self.clock_instr()?;
+            if matches!(op, Operation::RTS | Operation::RTI) && self.cpu.sp > sp {
+                break;
+            }
+        }
+        Ok(total_cycles)
+    }
+
     /// Steps the control deck the number of seconds.
     ///
     /// # Errors
@@ -686,18 +1160,100 @@ impl ControlDeck {
             frames_to_clock += 1;
         }
 
+        let ppu_dots = self.cpu.bus.ppu.cycle_count;
+        let dma_cycles = self.cpu.dma_cycles;
+        let audio_samples = self.cpu.bus.audio_samples().len();
+
         let mut total_cycles = 0;
         for _ in 0..frames_to_clock {
             let frame = self.frame_number();
+            self.cpu.bus.input.polled = false;
             while frame == self.frame_number() {
                 total_cycles += self.clock_instr()?;
             }
+            self.lag_frame = !self.cpu.bus.input.polled;
+            if self.lag_frame {
+                self.lag_frames += 1;
+            }
         }
         self.cpu.bus.apu.clock_flush();
 
+        self.frame_counters = FrameCounters {
+            frames: frames_to_clock,
+            cpu_cycles: total_cycles,
+            ppu_dots: self.cpu.bus.ppu.cycle_count.wrapping_sub(ppu_dots),
+            dma_cycles: self.cpu.dma_cycles.wrapping_sub(dma_cycles),
+            audio_samples: self
+                .cpu
+                .bus
+                .audio_samples()
+                .len()
+                .saturating_sub(audio_samples),
+        };
+
         Ok(total_cycles)
     }
 
+    /// Returns the number of lag frames, where the game did not read controller input, since the
+    /// last reset.
+    #[inline]
+    #[must_use]
+    pub const fn lag_frames(&self) -> u32 {
+        self.lag_frames
+    }
+
+    /// Whether the last clocked frame was a lag frame, where the game did not read controller
+    /// input.
+    #[inline]
+    #[must_use]
+    pub const fn is_lag_frame(&self) -> bool {
+        self.lag_frame
+    }
+
+    /// Reset the lag frame counter, e.g. when starting a replay.
+    #[inline]
+    pub fn reset_lag_frames(&mut self) {
+        self.lag_frames = 0;
+        self.lag_frame = false;
+    }
+
+    /// Returns the emulation counters for the last call to [`ControlDeck::clock_frame`]. Counters
+    /// are reset at the start of every frame.
+    #[inline]
+    pub const fn frame_counters(&self) -> FrameCounters {
+        self.frame_counters
+    }
+
+    /// Steps the control deck `frames` frames, only rendering the last one, e.g. to skip frames
+    /// when rendering every frame isn't needed. No frames are rendered if
+    /// [`HeadlessMode::NO_VIDEO`] is set. Returns the total number of CPU cycles clocked, with
+    /// [`ControlDeck::frame_counters`] covering all of the frames clocked.
+    ///
+    /// # Errors
+    ///
+    /// If CPU encounters an invalid opcode, then an error is returned.
+    pub fn clock_frames(&mut self, frames: usize) -> Result<usize> {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let skip_rendering = self.cpu.bus.ppu.skip_rendering;
+        let mut counters = FrameCounters::default();
+        let result = (0..frames).try_for_each(|frame| {
+            self.cpu.bus.ppu.skip_rendering = skip_rendering || frame + 1 < frames;
+            self.clock_frame()?;
+            let frame_counters = self.frame_counters;
+            counters.frames += frame_counters.frames;
+            counters.cpu_cycles += frame_counters.cpu_cycles;
+            counters.ppu_dots += frame_counters.ppu_dots;
+            counters.dma_cycles += frame_counters.dma_cycles;
+            counters.audio_samples += frame_counters.audio_samples;
+            Ok(())
+        });
+        self.cpu.bus.ppu.skip_rendering = skip_rendering;
+        self.frame_counters = counters;
+        result.map(|()| counters.cpu_cycles)
+    }
+
     /// Steps the control deck an entire frame, calling `handle_output` with the `cycles`, `frame_buffer` and
     /// `audio_samples` for that frame.
     ///
@@ -747,6 +1303,7 @@ impl ControlDeck {
     /// # Errors
     ///
     /// If CPU encounters an invalid opcode, then an error is returned.
+    #[cfg(feature = "serde")]
     pub fn clock_frame_ahead<T>(
         &mut self,
         run_ahead: usize,
@@ -762,26 +1319,35 @@ impl ControlDeck {
         // Clock current frame and save state so we can rewind
         self.clock_frame()?;
         let frame = std::mem::take(&mut self.cpu.bus.ppu.frame.buffer);
+        let lag = (self.lag_frames, self.lag_frame);
         // Save state so we can rewind
         let state = bincode::serialize(&self.cpu)
             .map_err(|err| fs::Error::SerializationFailed(err.to_string()))?;
 
-        // Clock additional frames and discard video/audio
+        // Clock additional frames and discard video/audio. Breakpoints are disabled as these
+        // frames are speculative and will be re-run.
+        let cpu_breakpoints = std::mem::take(&mut self.cpu.breakpoints);
+        let breakpoints = std::mem::take(&mut self.cpu.bus.ppu.breakpoints);
         self.cpu.bus.ppu.skip_rendering = true;
-        for _ in 1..run_ahead {
-            self.clock_frame()?;
-        }
-        self.cpu.bus.ppu.skip_rendering = false;
-
-        // Output the future frame video/audio
-        self.clear_audio_samples();
-        let result = self.clock_frame_output(handle_output)?;
+        let result = (1..run_ahead)
+            .try_for_each(|_| self.clock_frame().map(|_| ()))
+            .and_then(|()| {
+                self.cpu.bus.ppu.skip_rendering = false;
+                // Output the future frame video/audio
+                self.clear_audio_samples();
+                self.clear_channel_samples();
+                self.clock_frame_output(handle_output)
+            });
+        self.cpu.breakpoints = cpu_breakpoints;
+        self.cpu.bus.ppu.breakpoints = breakpoints;
+        let result = result?;
 
         // Restore back to current frame
         let mut state = bincode::deserialize::<Cpu>(&state)
             .map_err(|err| fs::Error::DeserializationFailed(err.to_string()))?;
         state.bus.ppu.frame.buffer = frame;
         self.load_cpu(state);
+        (self.lag_frames, self.lag_frame) = lag;
 
         Ok(result)
     }
@@ -791,6 +1357,7 @@ impl ControlDeck {
     /// # Errors
     ///
     /// If CPU encounters an invalid opcode, then an error is returned.
+    #[cfg(feature = "serde")]
     pub fn clock_frame_ahead_into(
         &mut self,
         run_ahead: usize,
@@ -807,24 +1374,33 @@ impl ControlDeck {
         // Clock current frame and save state so we can rewind
         self.clock_frame()?;
         let frame = std::mem::take(&mut self.cpu.bus.ppu.frame.buffer);
+        let lag = (self.lag_frames, self.lag_frame);
         // Save state so we can rewind
         let state = bincode::serialize(&self.cpu)
             .map_err(|err| fs::Error::SerializationFailed(err.to_string()))?;
 
-        // Clock additional frames and discard video/audio
-        for _ in 1..run_ahead {
-            self.clock_frame()?;
-        }
-
-        // Output the future frame/audio
-        self.clear_audio_samples();
-        let cycles = self.clock_frame_into(frame_buffer, audio_samples)?;
+        // Clock additional frames and discard video/audio. Breakpoints are disabled as these
+        // frames are speculative and will be re-run.
+        let cpu_breakpoints = std::mem::take(&mut self.cpu.breakpoints);
+        let breakpoints = std::mem::take(&mut self.cpu.bus.ppu.breakpoints);
+        let cycles = (1..run_ahead)
+            .try_for_each(|_| self.clock_frame().map(|_| ()))
+            .and_then(|()| {
+                // Output the future frame/audio
+                self.clear_audio_samples();
+                self.clear_channel_samples();
+                self.clock_frame_into(frame_buffer, audio_samples)
+            });
+        self.cpu.breakpoints = cpu_breakpoints;
+        self.cpu.bus.ppu.breakpoints = breakpoints;
+        let cycles = cycles?;
 
         // Restore back to current frame
         let mut state = bincode::deserialize::<Cpu>(&state)
             .map_err(|err| fs::Error::DeserializationFailed(err.to_string()))?;
         state.bus.ppu.frame.buffer = frame;
         self.load_cpu(state);
+        (self.lag_frames, self.lag_frame) = lag;
 
         Ok(cycles)
     }
@@ -970,12 +1546,69 @@ impl ControlDeck {
         self.cpu.bus.input.zapper.aim(x, y);
     }
 
+    /// Returns the controller port the [`Mouse`](crate::input::Mouse) is plugged into.
+    #[inline]
+    pub const fn mouse_port(&self) -> MousePort {
+        self.cpu.bus.input.mouse.port
+    }
+
+    /// Plug the [`Mouse`](crate::input::Mouse) into a controller port in place of a controller.
+    #[inline]
+    pub fn connect_mouse(&mut self, port: MousePort) {
+        self.cpu.bus.input.connect_mouse(port);
+    }
+
+    /// Move the [`Mouse`](crate::input::Mouse) by a number of counts.
+    #[inline]
+    pub fn mouse_motion(&mut self, dx: i32, dy: i32) {
+        self.cpu.bus.input.mouse.motion(dx, dy);
+    }
+
+    /// Press or release a [`Mouse`](crate::input::Mouse) button.
+    #[inline]
+    pub fn set_mouse_button(&mut self, button: MouseBtn, pressed: bool) {
+        self.cpu.bus.input.mouse.set_button(button, pressed);
+    }
+
+    /// Returns whether the loaded ROM is for the Vs. System arcade hardware.
+    #[inline]
+    #[must_use]
+    pub const fn is_vs_system(&self) -> bool {
+        self.cpu.bus.input.vs_system.is_some()
+    }
+
+    /// Insert a coin into coin slot 1 or 2 of the Vs. System. Does nothing if the loaded ROM isn't
+    /// a Vs. System game.
+    #[inline]
+    pub fn insert_coin(&mut self, slot: usize) {
+        if let Some(vs_system) = &mut self.cpu.bus.input.vs_system {
+            vs_system.insert_coin(slot);
+        }
+    }
+
+    /// Set the Vs. System DIP switches 1-8 in bits 0-7, applied to any Vs. System ROM loaded.
+    #[inline]
+    pub fn set_vs_dip_switches(&mut self, dip_switches: u8) {
+        self.vs_dip_switches = dip_switches;
+        if let Some(vs_system) = &mut self.cpu.bus.input.vs_system {
+            vs_system.dip_switches = dip_switches;
+        }
+    }
+
     /// Set the video filter for frame buffer output when calling [`ControlDeck::frame_buffer`].
     #[inline]
     pub fn set_filter(&mut self, filter: VideoFilter) {
         self.video.filter = filter;
     }
 
+    /// Set the colorblind assistance filter applied to frame buffer output.
+    #[inline]
+    pub fn set_color_filter(&mut self, color_filter: ColorFilter) {
+        self.video.color_filter = color_filter;
+        // Re-apply filters to the current frame, e.g. while paused
+        self.last_frame_number = None;
+    }
+
     /// Set the [`Apu`] sample rate.
     #[inline]
     pub fn set_sample_rate(&mut self, sample_rate: f32) {
@@ -1031,12 +1664,61 @@ impl ControlDeck {
         self.cpu.bus.apu.toggle_channel(channel);
     }
 
+    /// Returns the audio channels output by the loaded mapper, including any expansion audio.
+    pub fn audio_channels(&self) -> Vec<AudioChannel> {
+        AudioChannel::list(self.cpu.bus.ppu.bus.mapper.expansion_audio())
+    }
+
     /// Returns whether the control deck is currently running.
     #[inline]
     #[must_use]
     pub const fn is_running(&self) -> bool {
         self.running
     }
+
+    /// Flip the power switch. Turning the power off halts emulation while keeping the ROM loaded,
+    /// and turning it back on power cycles the console.
+    pub fn set_power(&mut self, on: bool) {
+        if on == self.powered {
+            return;
+        }
+        if on {
+            self.reset(ResetKind::Hard);
+        } else {
+            self.powered = false;
+            self.reset_held = false;
+            self.running = false;
+        }
+    }
+
+    /// Returns whether the power switch is on.
+    #[inline]
+    #[must_use]
+    pub const fn is_powered(&self) -> bool {
+        self.powered
+    }
+
+    /// Press and hold the reset button, halting emulation until it's released.
+    pub fn press_reset(&mut self) {
+        if self.running {
+            self.reset_held = true;
+            self.running = false;
+        }
+    }
+
+    /// Release the reset button, resetting the console if it was held.
+    pub fn release_reset(&mut self) {
+        if self.reset_held {
+            self.reset(ResetKind::Soft);
+        }
+    }
+
+    /// Returns whether the reset button is being held.
+    #[inline]
+    #[must_use]
+    pub const fn is_reset_held(&self) -> bool {
+        self.reset_held
+    }
 }
 
 impl Clock for ControlDeck {
@@ -1066,9 +1748,292 @@ impl Regional for ControlDeck {
 impl Reset for ControlDeck {
     /// Resets the console.
     fn reset(&mut self, kind: ResetKind) {
+        if self.ram_retention.clears(kind) {
+            self.clear_ram();
+        }
         self.cpu.reset(kind);
-        if self.loaded_rom.is_some() {
+        #[cfg(feature = "serde")]
+        if let Some(step_history) = &mut self.step_history {
+            step_history.clear();
+        }
+        if kind == ResetKind::Hard {
+            self.powered = true;
+            self.reset_lag_frames();
+        }
+        self.reset_held = false;
+        if self.loaded_rom.is_some() && self.powered {
             self.running = true;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::load_control_deck;
+
+    fn load_nestest() -> ControlDeck {
+        load_control_deck(Path::new(env!("CARGO_MANIFEST_DIR")).join("test_roms/cpu/nestest.nes"))
+    }
+
+    #[test]
+    fn step_back() {
+        let mut deck = load_nestest();
+
+        assert!(
+            matches!(deck.step_back(), Err(Error::NoStepHistory)),
+            "disabled by default"
+        );
+
+        deck.set_step_history_enabled(true);
+        let mut states = Vec::new();
+        for _ in 0..100 {
+            states.push((deck.cpu().pc, deck.cpu().cycle));
+            deck.clock_instr().expect("valid clock");
+        }
+        for (pc, cycle) in states.into_iter().rev() {
+            deck.step_back().expect("valid step back");
+            assert_eq!((deck.cpu().pc, deck.cpu().cycle), (pc, cycle));
+        }
+        assert!(matches!(deck.step_back(), Err(Error::NoStepHistory)));
+    }
+
+    #[test]
+    fn step_over_and_out() {
+        let mut deck = load_nestest();
+
+        let is_jsr = |deck: &ControlDeck| {
+            disasm::Instruction::peek(deck.cpu(), deck.cpu().pc).op() == Operation::JSR
+        };
+        while !is_jsr(&deck) {
+            deck.clock_instr().expect("valid clock");
+        }
+        let (ret, sp) = (deck.cpu().pc + 3, deck.cpu().sp);
+        deck.step_over().expect("valid step over");
+        assert_eq!(
+            (deck.cpu().pc, deck.cpu().sp),
+            (ret, sp),
+            "returned from subroutine"
+        );
+
+        while !is_jsr(&deck) {
+            deck.clock_instr().expect("valid clock");
+        }
+        let (ret, sp) = (deck.cpu().pc + 3, deck.cpu().sp);
+        deck.clock_instr().expect("valid clock");
+        assert_ne!(deck.cpu().pc, ret, "entered subroutine");
+        deck.step_out().expect("valid step out");
+        assert_eq!(
+            (deck.cpu().pc, deck.cpu().sp),
+            (ret, sp),
+            "stepped out of subroutine"
+        );
+
+        let state = deck.cpu_debug_state(4, 4);
+        assert_eq!(state.pc, ret);
+        assert!(state.disassembly.iter().any(|instr| instr.addr == ret));
+        assert_eq!(state.stack.len(), 0xFF - usize::from(sp));
+    }
+
+    #[test]
+    fn state_hash() {
+        let mut deck = load_nestest();
+        deck.clock_frame().expect("valid frame clock");
+
+        let hash = deck.state_hash();
+        assert_eq!(deck.state_hash(), hash, "deterministic");
+        let cpu = deck.cpu().clone();
+        deck.clock_frame().expect("valid frame clock");
+        assert_ne!(deck.state_hash(), hash, "changes each frame");
+        deck.load_cpu(cpu);
+        assert_eq!(deck.state_hash(), hash, "restored state");
+        deck.cpu_mut().bus.wram[0x10] ^= 0xFF;
+        assert_ne!(deck.state_hash(), hash, "changed memory");
+    }
+
+    #[test]
+    fn frame_counters() {
+        let mut deck = load_nestest();
+
+        assert_eq!(deck.frame_counters(), FrameCounters::default());
+        for _ in 0..3 {
+            deck.clock_frame().expect("valid frame clock");
+        }
+        let counters = deck.frame_counters();
+        assert_eq!(counters.frames, 1);
+        assert!(
+            counters.ppu_dots.abs_diff(341 * 262) < 30,
+            "ppu dots: {}",
+            counters.ppu_dots
+        );
+        assert!(counters.ppu_dots.abs_diff(counters.cpu_cycles * 3) <= 3);
+        assert!(counters.audio_samples > 0);
+    }
+
+    #[test]
+    fn clock_frames() {
+        let mut deck = load_nestest();
+        let mut skip_deck = deck.clone();
+
+        let mut expected_cycles = 0;
+        for _ in 0..4 {
+            expected_cycles += deck.clock_frame().expect("valid frame clock");
+        }
+
+        let cycles = skip_deck.clock_frames(4).expect("valid frames clock");
+        assert_eq!(cycles, expected_cycles);
+        assert_eq!(skip_deck.frame_counters().frames, 4);
+        assert_eq!(skip_deck.frame_counters().cpu_cycles, expected_cycles);
+        assert_eq!(skip_deck.state_hash(), deck.state_hash());
+        assert_eq!(
+            skip_deck.frame_buffer(),
+            deck.frame_buffer(),
+            "last frame rendered"
+        );
+        assert!(!skip_deck.cpu().bus.ppu.skip_rendering);
+    }
+
+    #[test]
+    fn pal_m_timing() {
+        let mut deck = load_nestest();
+        let mut pal_m_deck = deck.clone();
+        pal_m_deck.set_region(NesRegion::PalM);
+
+        assert_eq!(pal_m_deck.clock_rate(), deck.clock_rate());
+        for _ in 0..4 {
+            assert_eq!(
+                pal_m_deck.clock_frame().expect("valid frame clock"),
+                deck.clock_frame().expect("valid frame clock"),
+                "NTSC frame length"
+            );
+        }
+        assert_eq!(pal_m_deck.frame_buffer(), deck.frame_buffer());
+        assert_eq!(pal_m_deck.audio_samples().len(), deck.audio_samples().len());
+    }
+
+    #[test]
+    fn audio_channels() {
+        let deck = load_nestest();
+
+        let channels = deck.audio_channels();
+        assert_eq!(
+            deck.loaded_rom().map(|rom| rom.audio_channels.clone()),
+            Some(channels.clone())
+        );
+        assert_eq!(
+            channels.iter().map(|c| c.channel).collect::<Vec<_>>(),
+            Channel::ALL[..Channel::Mapper as usize],
+            "no expansion audio"
+        );
+
+        let channels = AudioChannel::list(Some("VRC6"));
+        assert_eq!(
+            channels.last(),
+            Some(&AudioChannel {
+                channel: Channel::Mapper,
+                name: "VRC6"
+            })
+        );
+    }
+
+    #[test]
+    fn front_panel() {
+        let mut deck = load_nestest();
+
+        deck.press_reset();
+        assert!(deck.is_reset_held());
+        assert!(!deck.is_running(), "halted while reset is held");
+        deck.release_reset();
+        assert!(!deck.is_reset_held());
+        assert!(deck.is_running(), "running after reset is released");
+
+        deck.set_power(false);
+        assert!(!deck.is_powered());
+        assert!(!deck.is_running(), "halted while powered off");
+        assert!(deck.loaded_rom().is_some(), "rom stays loaded");
+        deck.reset(ResetKind::Soft);
+        assert!(!deck.is_running(), "reset doesn't power on");
+        deck.press_reset();
+        assert!(
+            !deck.is_reset_held(),
+            "reset button ignored while powered off"
+        );
+
+        deck.set_power(true);
+        assert!(deck.is_powered());
+        assert!(deck.is_running(), "running after power on");
+    }
+
+    #[test]
+    fn lag_frames() {
+        let mut deck = load_nestest();
+
+        let mut lag_frames = 0;
+        for _ in 0..60 {
+            deck.clock_frame().expect("valid frame clock");
+            lag_frames += u32::from(deck.is_lag_frame());
+        }
+        assert_eq!(deck.lag_frames(), lag_frames);
+        assert!(lag_frames < 60, "nestest polls input on its menu");
+
+        deck.reset(ResetKind::Hard);
+        assert_eq!(deck.lag_frames(), 0);
+    }
+
+    #[test]
+    fn save_state_info() {
+        let mut deck = load_nestest();
+        for _ in 0..10 {
+            deck.clock_frame().expect("valid frame clock");
+        }
+
+        let dir = std::env::temp_dir().join(format!("tetanes-save-state-{}", std::process::id()));
+        let save_path = dir.join("slot-1.sav");
+        deck.save_state(&save_path).expect("save state");
+        let info = ControlDeck::load_save_state_info(&save_path)
+            .expect("load info")
+            .expect("info saved");
+        assert_eq!(info.frame_number, deck.frame_number());
+        assert!(info.play_time > Duration::ZERO);
+        assert_eq!(
+            info.thumbnail.len(),
+            4 * (SaveStateInfo::THUMBNAIL_WIDTH * SaveStateInfo::THUMBNAIL_HEIGHT) as usize
+        );
+        let cpu = fs::load::<Cpu>(&save_path).expect("state loads without info");
+        assert_eq!(cpu.cycle, deck.cpu().cycle);
+
+        fs::save(&save_path, deck.cpu()).expect("save state without info");
+        assert_eq!(
+            ControlDeck::load_save_state_info(&save_path).expect("load info"),
+            None,
+            "older save states have no info"
+        );
+        deck.load_state(&save_path).expect("load state");
+        fs::clear_dir(&dir).expect("clear dir");
+    }
+
+    #[test]
+    fn ram_retention() {
+        use crate::mem::Mem;
+
+        let mut deck = load_nestest();
+
+        let retained = |deck: &mut ControlDeck, kind| {
+            deck.bus_mut().write(0x0300, 0xAB);
+            deck.reset(kind);
+            deck.wram()[0x0300] == 0xAB
+        };
+
+        assert_eq!(deck.ram_retention(), RamRetention::Hardware);
+        assert!(retained(&mut deck, ResetKind::Soft));
+        assert!(!retained(&mut deck, ResetKind::Hard));
+
+        deck.set_ram_retention(RamRetention::KeepAll);
+        assert!(retained(&mut deck, ResetKind::Soft));
+        assert!(retained(&mut deck, ResetKind::Hard));
+
+        deck.set_ram_retention(RamRetention::ClearAll);
+        assert!(!retained(&mut deck, ResetKind::Soft));
+        assert!(!retained(&mut deck, ResetKind::Hard));
+    }
+}
@@ -0,0 +1,170 @@
+//! A [`ControlDeck`] that can be shared between threads.
+
+use crate::{
+    control_deck::{Config, ControlDeck},
+    cpu::{Cpu, Signals},
+};
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex, MutexGuard, PoisonError, TryLockError},
+};
+
+#[derive(Debug)]
+struct Shared {
+    deck: ControlDeck,
+    signals: Signals,
+}
+
+/// A [`ControlDeck`] that can be cloned and shared between threads, e.g. by an HTTP server,
+/// netplay session or scripting host.
+///
+/// All access goes through [`SyncControlDeck::lock`], so operations never run concurrently:
+/// input, configuration and state queries from other threads wait for any in-progress clock to
+/// finish and take effect from the next clock onwards. Hold the lock only as long as needed, as
+/// holding it while clocking a frame blocks all other threads for that frame.
+///
+/// Pending interrupts and DMAs are stored per-thread by the [`Cpu`], so they're carried over
+/// when the lock is taken from a different thread than the one that last clocked the deck. Use
+/// a `SyncControlDeck` rather than a `Mutex<ControlDeck>` for this reason.
+///
+/// Locking the same `SyncControlDeck` again while holding a [`SyncControlDeckGuard`] on the same
+/// thread deadlocks.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct SyncControlDeck {
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Default for SyncControlDeck {
+    fn default() -> Self {
+        Self::new(ControlDeck::default())
+    }
+}
+
+impl From<ControlDeck> for SyncControlDeck {
+    fn from(deck: ControlDeck) -> Self {
+        Self::new(deck)
+    }
+}
+
+impl SyncControlDeck {
+    /// Wrap a [`ControlDeck`] so it can be shared between threads.
+    pub fn new(deck: ControlDeck) -> Self {
+        Self {
+            shared: Arc::new(Mutex::new(Shared {
+                deck,
+                signals: Cpu::signals(),
+            })),
+        }
+    }
+
+    /// Create a shareable [`ControlDeck`] with a given [`Config`].
+    pub fn with_config(cfg: Config) -> Self {
+        Self::new(ControlDeck::with_config(cfg))
+    }
+
+    /// Lock the [`ControlDeck`] for exclusive access, blocking until it's available.
+    ///
+    /// If a thread panicked while holding the lock, the deck is still returned as it was left.
+    pub fn lock(&self) -> SyncControlDeckGuard<'_> {
+        let guard = self.shared.lock().unwrap_or_else(PoisonError::into_inner);
+        SyncControlDeckGuard::new(guard)
+    }
+
+    /// Lock the [`ControlDeck`] for exclusive access, or return `None` if it's currently locked.
+    pub fn try_lock(&self) -> Option<SyncControlDeckGuard<'_>> {
+        let guard = match self.shared.try_lock() {
+            Ok(guard) => guard,
+            Err(TryLockError::Poisoned(err)) => err.into_inner(),
+            Err(TryLockError::WouldBlock) => return None,
+        };
+        Some(SyncControlDeckGuard::new(guard))
+    }
+
+    /// Run `f` with exclusive access to the [`ControlDeck`], returning its result.
+    pub fn with<T>(&self, f: impl FnOnce(&mut ControlDeck) -> T) -> T {
+        f(&mut self.lock())
+    }
+
+    /// Unwrap the [`ControlDeck`] if this is the only remaining reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `self` if other clones of this `SyncControlDeck` still exist.
+    pub fn try_into_inner(self) -> Result<ControlDeck, Self> {
+        let shared = Arc::try_unwrap(self.shared).map_err(|shared| Self { shared })?;
+        let shared = shared.into_inner().unwrap_or_else(PoisonError::into_inner);
+        Cpu::set_signals(shared.signals);
+        Ok(shared.deck)
+    }
+}
+
+/// Exclusive access to the [`ControlDeck`] of a [`SyncControlDeck`], released when dropped.
+#[derive(Debug)]
+#[must_use]
+pub struct SyncControlDeckGuard<'a> {
+    guard: MutexGuard<'a, Shared>,
+}
+
+impl<'a> SyncControlDeckGuard<'a> {
+    fn new(guard: MutexGuard<'a, Shared>) -> Self {
+        Cpu::set_signals(guard.signals);
+        Self { guard }
+    }
+}
+
+impl Drop for SyncControlDeckGuard<'_> {
+    fn drop(&mut self) {
+        self.guard.signals = Cpu::signals();
+    }
+}
+
+impl Deref for SyncControlDeckGuard<'_> {
+    type Target = ControlDeck;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard.deck
+    }
+}
+
+impl DerefMut for SyncControlDeckGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard.deck
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{common::tests::load_control_deck, cpu::Irq};
+    use std::{path::Path, thread};
+
+    #[test]
+    fn sync_control_deck_is_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SyncControlDeck>();
+    }
+
+    #[test]
+    fn signals_follow_lock_across_threads() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_roms/cpu/nestest.nes");
+        let deck = SyncControlDeck::new(load_control_deck(path));
+
+        {
+            let _deck = deck.lock();
+            Cpu::set_irq(Irq::MAPPER);
+        }
+        let other = deck.clone();
+        let has_irq = thread::spawn(move || {
+            let _deck = other.lock();
+            Cpu::has_irq(Irq::MAPPER)
+        })
+        .join()
+        .expect("thread panicked");
+        assert!(has_irq, "pending irq carried over to other thread");
+
+        deck.with(|deck| deck.clock_frame())
+            .expect("valid frame clock");
+        assert!(deck.try_into_inner().is_ok(), "only remaining reference");
+    }
+}
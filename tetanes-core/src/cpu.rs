@@ -5,6 +5,8 @@
 use crate::{
     bus::Bus,
     common::{Clock, ClockTo, NesRegion, Regional, Reset, ResetKind},
+    debug::{CpuBreakpoint, Interrupt, InterruptKind, InterruptLog},
+    logging::trace,
     mem::Mem,
 };
 use bitflags::bitflags;
@@ -19,13 +21,14 @@ use instr::{
         TXA, TXS, TYA, XAA, XXX,
     },
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::{
     cell::Cell,
     fmt::{self, Write},
 };
-use tracing::trace;
 
+pub mod disasm;
 pub mod instr;
 
 thread_local! {
@@ -38,7 +41,8 @@ thread_local! {
 }
 
 bitflags! {
-    #[derive(Default, Serialize, Deserialize, Debug, Copy, Clone)]
+    #[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     #[must_use]
     pub struct Irq: u8 {
         const MAPPER = 1 << 1;
@@ -48,7 +52,8 @@ bitflags! {
 }
 
 bitflags! {
-    #[derive(Default, Serialize, Deserialize, Debug, Copy, Clone)]
+    #[derive(Default, Debug, Copy, Clone)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     #[must_use]
     pub struct Dma: u8 {
         const OAM = 1 << 1;
@@ -56,6 +61,22 @@ bitflags! {
     }
 }
 
+/// Pending interrupt and DMA signals, which are stored per-thread so that the APU, PPU and mappers
+/// can raise them without a reference to the [`Cpu`].
+///
+/// Use [`Cpu::signals`] and [`Cpu::set_signals`] to carry them over when clocking a [`Cpu`] from
+/// a different thread than the one that last clocked it.
+#[derive(Default, Debug, Copy, Clone)]
+#[must_use]
+pub struct Signals {
+    nmi: bool,
+    irqs: Irq,
+    dmas: Dma,
+    dma_halt: bool,
+    dma_dummy_read: bool,
+    dma_oam_addr: u16,
+}
+
 // Status Registers
 // http://wiki.nesdev.com/w/index.php/Status_flags
 // 7654 3210
@@ -71,7 +92,8 @@ bitflags! {
 // +--------- Negative
 bitflags! {
     /// CPU Status Registers.
-    #[derive(Default, Serialize, Deserialize, Debug, Copy, Clone)]
+    #[derive(Default, Debug, Copy, Clone)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     #[must_use]
     pub struct Status: u8 {
         const C = 1;      // Carry
@@ -86,14 +108,16 @@ bitflags! {
 }
 
 /// Every cycle is either a read or a write.
-#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Cycle {
     start: usize,
     end: usize,
 }
 
 /// The Central Processing Unit status and registers
-#[derive(Default, Clone, Serialize, Deserialize)]
+#[derive(Default, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Cpu {
     pub cycle: usize, // total number of cycles ran
@@ -104,6 +128,8 @@ pub struct Cpu {
     // start/end cycle counts for writes
     pub write_cycles: Cycle,
     pub master_clock: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub dma_cycles: usize, // total number of cycles stalled for DMA
     pub instr: Instr,     // The currently executing instruction
     pub fetched_data: u8, // Represents data fetched for the ALU
     pub status: Status,   // Status Registers
@@ -118,12 +144,19 @@ pub struct Cpu {
     pub nmi: bool,
     pub prev_nmi: bool,
     pub prev_nmi_pending: bool,
-    #[serde(skip)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub corrupted: bool, // Encountering an invalid opcode corrupts CPU processing
     pub region: NesRegion,
     pub cycle_accurate: bool,
-    #[serde(skip)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub disasm: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub interrupt_log: InterruptLog,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub breakpoints: Vec<CpuBreakpoint>,
+    /// The last breakpoint that was hit, if any. Cleared by the consumer.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub breakpoint_hit: Option<CpuBreakpoint>,
 }
 
 impl Cpu {
@@ -151,6 +184,7 @@ impl Cpu {
             cycle: 0,
             region: bus.region,
             master_clock: 0,
+            dma_cycles: 0,
             read_cycles: Cycle::default(),
             write_cycles: Cycle::default(),
             pc: 0x0000,
@@ -172,6 +206,9 @@ impl Cpu {
             corrupted: false,
             cycle_accurate: true,
             disasm: String::with_capacity(100),
+            interrupt_log: InterruptLog::default(),
+            breakpoints: Vec::new(),
+            breakpoint_hit: None,
         };
         cpu.set_region(cpu.region);
         cpu
@@ -189,15 +226,39 @@ impl Cpu {
         };
         // Doesn't make sense to load a debugger from a previous state
         cpu.bus.ppu.debugger = std::mem::take(&mut self.bus.ppu.debugger);
+        cpu.bus.ppu.breakpoints = std::mem::take(&mut self.bus.ppu.breakpoints);
+        cpu.breakpoints = std::mem::take(&mut self.breakpoints);
+        cpu.interrupt_log = std::mem::take(&mut self.interrupt_log);
+        cpu.bus.apu.dmc.sample_log = std::mem::take(&mut self.bus.apu.dmc.sample_log);
+        cpu.bus.apu.channel_split = self.bus.apu.channel_split.take();
+        // Continue the audio output from where it was to avoid a pop
+        cpu.bus.apu.declick = self.bus.apu.declick;
+        cpu.bus.apu.declick.smooth();
+        #[cfg(feature = "debug")]
+        {
+            cpu.bus.activity = std::mem::take(&mut self.bus.activity);
+        }
         *self = cpu;
     }
 
+    /// Records the first breakpoint matching `matches` as hit.
+    #[inline]
+    fn check_breakpoint(&mut self, matches: impl Fn(&Self, &CpuBreakpoint) -> bool) {
+        if self.breakpoint_hit.is_none() && !self.breakpoints.is_empty() {
+            self.breakpoint_hit = self
+                .breakpoints
+                .iter()
+                .copied()
+                .find(|bp| matches(self, bp));
+        }
+    }
+
     /// Returns the CPU clock rate based on [`NesRegion`].
     #[inline]
     #[must_use]
     pub const fn region_clock_rate(region: NesRegion) -> f32 {
         match region {
-            NesRegion::Auto | NesRegion::Ntsc => Self::NTSC_CPU_CLOCK_RATE,
+            NesRegion::Auto | NesRegion::Ntsc | NesRegion::PalM => Self::NTSC_CPU_CLOCK_RATE,
             NesRegion::Pal => Self::PAL_CPU_CLOCK_RATE,
             NesRegion::Dendy => Self::DENDY_CPU_CLOCK_RATE,
         }
@@ -217,6 +278,28 @@ impl Cpu {
         Cpu::INSTRUCTIONS[opcode as usize]
     }
 
+    /// Returns the pending interrupt and DMA [`Signals`] for the current thread.
+    pub fn signals() -> Signals {
+        Signals {
+            nmi: NMI.get(),
+            irqs: IRQS.get(),
+            dmas: DMAS.get(),
+            dma_halt: DMA_HALT.get(),
+            dma_dummy_read: DMA_DUMMY_READ.get(),
+            dma_oam_addr: DMA_OAM_ADDR.get(),
+        }
+    }
+
+    /// Sets the pending interrupt and DMA [`Signals`] for the current thread.
+    pub fn set_signals(signals: Signals) {
+        NMI.set(signals.nmi);
+        IRQS.set(signals.irqs);
+        DMAS.set(signals.dmas);
+        DMA_HALT.set(signals.dma_halt);
+        DMA_DUMMY_READ.set(signals.dma_dummy_read);
+        DMA_OAM_ADDR.set(signals.dma_oam_addr);
+    }
+
     #[inline]
     #[must_use]
     pub fn nmi_pending() -> bool {
@@ -319,6 +402,7 @@ impl Cpu {
     ///  6    PC     R  fetch low byte of interrupt vector
     ///  7    PC     R  fetch high byte of interrupt vector
     pub fn irq(&mut self) {
+        let pc = self.pc;
         self.read(self.pc);
         self.read(self.pc);
         self.push_u16(self.pc);
@@ -330,7 +414,7 @@ impl Cpu {
         // Set U and !B during push
         let status = ((self.status | Status::U) & !Status::B).bits();
 
-        if self.nmi {
+        let kind = if self.nmi {
             self.nmi = false;
             self.push(status);
             self.status.set(Status::I, true);
@@ -342,7 +426,9 @@ impl Cpu {
                 self.bus.ppu.scanline,
                 self.cycle
             );
+            InterruptKind::Nmi
         } else {
+            let irqs = Self::irqs();
             self.push(status);
             self.status.set(Status::I, true);
 
@@ -353,6 +439,18 @@ impl Cpu {
                 self.bus.ppu.scanline,
                 self.cycle
             );
+            InterruptKind::Irq(irqs)
+        };
+
+        if self.interrupt_log.enabled() {
+            self.interrupt_log.push(Interrupt {
+                kind,
+                frame: self.bus.ppu.frame_number(),
+                scanline: self.bus.ppu.scanline,
+                cycle: self.bus.ppu.cycle,
+                cpu_cycle: self.cycle,
+                pc,
+            });
         }
     }
 
@@ -419,6 +517,7 @@ impl Cpu {
     /// Handle a direct-memory access (DMA) request.
     fn handle_dma(&mut self, addr: u16) {
         trace!("Starting DMA - CYC:{}", self.cycle);
+        let start_cycle = self.cycle;
 
         self.start_cycle(self.read_cycles.start);
         self.bus.read(addr);
@@ -480,6 +579,10 @@ impl Cpu {
                 self.end_cycle(self.read_cycles.end);
             }
         }
+
+        self.dma_cycles = self
+            .dma_cycles
+            .wrapping_add(self.cycle.wrapping_sub(start_cycle));
     }
 
     // Status Register functions
@@ -650,7 +753,8 @@ impl Cpu {
         u16::from_le_bytes([lo, hi])
     }
 
-    /// Disassemble the instruction at the given program counter.
+    /// Disassemble the instruction at the given program counter, including current register-based
+    /// effective addresses and values. See [`disasm`] for structured disassembly.
     pub fn disassemble(&mut self, pc: &mut u16) -> &str {
         let opcode = self.peek(*pc);
         let instr = Cpu::INSTRUCTIONS[opcode as usize];
@@ -825,6 +929,16 @@ impl Clock for Cpu {
     fn clock(&mut self) -> usize {
         let start_cycle = self.cycle;
 
+        let pc = self.pc;
+        self.check_breakpoint(|cpu, bp| match *bp {
+            CpuBreakpoint::Exec(addr) => addr == pc,
+            CpuBreakpoint::Conditional {
+                pc: addr,
+                condition,
+            } => addr == pc && condition.matches(cpu),
+            _ => false,
+        });
+
         self.trace_instr();
 
         let opcode = self.read_instr(); // Cycle 1 of instruction
@@ -952,6 +1066,7 @@ impl Mem for Cpu {
         self.start_cycle(self.read_cycles.start);
         let val = self.bus.read(addr);
         self.end_cycle(self.read_cycles.end);
+        self.check_breakpoint(|_, bp| *bp == CpuBreakpoint::Read(addr));
         val
     }
 
@@ -963,6 +1078,7 @@ impl Mem for Cpu {
         self.start_cycle(self.write_cycles.start);
         self.bus.write(addr, val);
         self.end_cycle(self.write_cycles.end);
+        self.check_breakpoint(|_, bp| *bp == CpuBreakpoint::Write(addr));
     }
 }
 
@@ -973,9 +1089,9 @@ impl Regional for Cpu {
 
     fn set_region(&mut self, region: NesRegion) {
         let (start_cycles, end_cycles) = match region {
-            NesRegion::Auto | NesRegion::Ntsc => (6, 6), // NTSC_MASTER_CLOCK_DIVIDER / 2
-            NesRegion::Pal => (8, 8),                    // PAL_MASTER_CLOCK_DIVIDER / 2
-            NesRegion::Dendy => (7, 8),                  // DENDY_MASTER_CLOCK_DIVIDER / 2
+            NesRegion::Auto | NesRegion::Ntsc | NesRegion::PalM => (6, 6), // NTSC_MASTER_CLOCK_DIVIDER / 2
+            NesRegion::Pal => (8, 8),   // PAL_MASTER_CLOCK_DIVIDER / 2
+            NesRegion::Dendy => (7, 8), // DENDY_MASTER_CLOCK_DIVIDER / 2
         };
         self.region = region;
         self.read_cycles = Cycle {
@@ -1076,6 +1192,79 @@ impl fmt::Debug for Cpu {
 mod tests {
     use crate::cart::Cart;
 
+    #[test]
+    fn interrupt_log() {
+        use super::*;
+        let mut cpu = Cpu::new(Bus::default());
+        cpu.bus.load_cart(Cart::empty());
+        cpu.reset(ResetKind::Hard);
+
+        cpu.nmi = true;
+        cpu.irq();
+        assert!(cpu.interrupt_log.is_empty(), "logging disabled by default");
+
+        cpu.interrupt_log.set_enabled(true);
+        let pc = cpu.pc;
+        cpu.nmi = true;
+        cpu.irq();
+        Cpu::set_irq(Irq::DMC);
+        cpu.irq();
+        Cpu::clear_irq(Irq::DMC);
+
+        let entries = cpu.interrupt_log.drain();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].kind, InterruptKind::Nmi);
+        assert_eq!(entries[0].pc, pc);
+        assert_eq!(entries[1].kind, InterruptKind::Irq(Irq::DMC));
+        assert!(cpu.interrupt_log.is_empty());
+    }
+
+    #[test]
+    fn breakpoints() {
+        use super::*;
+        use crate::debug::{BreakComparison, BreakCondition, BreakOperand};
+
+        let mut cpu = Cpu::new(Bus::default());
+        cpu.bus.load_cart(Cart::empty());
+        cpu.reset(ResetKind::Hard);
+        // LDA #$10; STA $0200; NOP
+        for (addr, val) in [0xA9, 0x10, 0x8D, 0x00, 0x02, 0xEA].into_iter().enumerate() {
+            cpu.write(addr as u16, val);
+        }
+        let condition = BreakCondition::new(BreakOperand::A, BreakComparison::Equal, 0x10);
+        cpu.breakpoints = vec![
+            CpuBreakpoint::Conditional {
+                pc: 0x0002,
+                condition,
+            },
+            CpuBreakpoint::Write(0x0200),
+            CpuBreakpoint::Exec(0x0005),
+        ];
+
+        cpu.pc = 0x0000;
+        cpu.acc = 0x00;
+        cpu.clock();
+        assert_eq!(cpu.breakpoint_hit, None);
+        cpu.clock();
+        assert_eq!(
+            cpu.breakpoint_hit.take(),
+            Some(CpuBreakpoint::Conditional {
+                pc: 0x0002,
+                condition
+            }),
+            "condition checked before executing"
+        );
+        cpu.pc = 0x0002;
+        cpu.acc = 0x20;
+        cpu.clock();
+        assert_eq!(
+            cpu.breakpoint_hit.take(),
+            Some(CpuBreakpoint::Write(0x0200))
+        );
+        cpu.clock();
+        assert_eq!(cpu.breakpoint_hit.take(), Some(CpuBreakpoint::Exec(0x0005)));
+    }
+
     #[test]
     fn cycle_timing() {
         use super::*;
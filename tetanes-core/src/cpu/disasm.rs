@@ -0,0 +1,366 @@
+//! 6502 disassembler.
+//!
+//! Decodes instructions from either a byte slice or any [`Mem`] implementation using
+//! side-effect free [`Mem::peek`] reads, producing structured [`Instruction`]s.
+
+use crate::{
+    cpu::{
+        instr::{AddrMode, Instr, Operation},
+        Cpu,
+    },
+    mem::Mem,
+};
+use std::{fmt, ops::RangeInclusive};
+
+/// A decoded instruction operand.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum Operand {
+    /// No operand.
+    Implied,
+    /// Operates on the accumulator.
+    Accumulator,
+    /// `#$nn`
+    Immediate(u8),
+    /// `$nn`
+    ZeroPage(u8),
+    /// `$nn,X`
+    ZeroPageX(u8),
+    /// `$nn,Y`
+    ZeroPageY(u8),
+    /// `$nnnn`
+    Absolute(u16),
+    /// `$nnnn,X`
+    AbsoluteX(u16),
+    /// `$nnnn,Y`
+    AbsoluteY(u16),
+    /// `($nnnn)`
+    Indirect(u16),
+    /// `($nn,X)`
+    IndirectX(u8),
+    /// `($nn),Y`
+    IndirectY(u8),
+    /// Signed branch offset relative to the following instruction.
+    Relative(i8),
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Implied => Ok(()),
+            Self::Accumulator => write!(f, "A"),
+            Self::Immediate(val) => write!(f, "#${val:02X}"),
+            Self::ZeroPage(addr) => write!(f, "${addr:02X}"),
+            Self::ZeroPageX(addr) => write!(f, "${addr:02X},X"),
+            Self::ZeroPageY(addr) => write!(f, "${addr:02X},Y"),
+            Self::Absolute(addr) => write!(f, "${addr:04X}"),
+            Self::AbsoluteX(addr) => write!(f, "${addr:04X},X"),
+            Self::AbsoluteY(addr) => write!(f, "${addr:04X},Y"),
+            Self::Indirect(addr) => write!(f, "(${addr:04X})"),
+            Self::IndirectX(addr) => write!(f, "(${addr:02X},X)"),
+            Self::IndirectY(addr) => write!(f, "(${addr:02X}),Y"),
+            Self::Relative(offset) => write!(f, "{offset:+}"),
+        }
+    }
+}
+
+/// A single disassembled instruction.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Instruction {
+    /// Address of the opcode.
+    pub addr: u16,
+    /// Decoded instruction from [`Cpu::INSTRUCTIONS`].
+    pub instr: Instr,
+    /// Decoded operand.
+    pub operand: Operand,
+    /// Address referenced by the operand, if it can be determined without CPU register state.
+    /// Branch targets are resolved to an absolute address.
+    pub target: Option<u16>,
+    bytes: [u8; 3],
+}
+
+impl Instruction {
+    /// Decode the instruction at `addr` from `bytes`, where `bytes[0]` is the opcode. Returns
+    /// `None` if `bytes` is too short to contain the full instruction.
+    pub fn decode(addr: u16, bytes: &[u8]) -> Option<Self> {
+        let opcode = *bytes.first()?;
+        let instr = Cpu::INSTRUCTIONS[usize::from(opcode)];
+        let len = operand_len(instr.addr_mode()) + 1;
+        let bytes = match *bytes.get(..len)? {
+            [b0] => [b0, 0x00, 0x00],
+            [b0, b1] => [b0, b1, 0x00],
+            [b0, b1, b2] => [b0, b1, b2],
+            _ => unreachable!("instructions are at most 3 bytes"),
+        };
+
+        let byte = bytes[1];
+        let word = u16::from_le_bytes([bytes[1], bytes[2]]);
+        let (operand, target) = match instr.addr_mode() {
+            AddrMode::IMP => (Operand::Implied, None),
+            AddrMode::ACC => (Operand::Accumulator, None),
+            AddrMode::IMM => (Operand::Immediate(byte), None),
+            AddrMode::ZP0 => (Operand::ZeroPage(byte), Some(u16::from(byte))),
+            AddrMode::ZPX => (Operand::ZeroPageX(byte), None),
+            AddrMode::ZPY => (Operand::ZeroPageY(byte), None),
+            AddrMode::ABS => (Operand::Absolute(word), Some(word)),
+            AddrMode::ABX => (Operand::AbsoluteX(word), None),
+            AddrMode::ABY => (Operand::AbsoluteY(word), None),
+            AddrMode::IND => (Operand::Indirect(word), None),
+            AddrMode::IDX => (Operand::IndirectX(byte), None),
+            AddrMode::IDY => (Operand::IndirectY(byte), None),
+            AddrMode::REL => {
+                let offset = byte as i8;
+                let next = addr.wrapping_add(len as u16);
+                (
+                    Operand::Relative(offset),
+                    Some(next.wrapping_add_signed(offset.into())),
+                )
+            }
+        };
+
+        Some(Self {
+            addr,
+            instr,
+            operand,
+            target,
+            bytes,
+        })
+    }
+
+    /// Decode the instruction at `addr` by peeking `mem`.
+    pub fn peek(mem: &impl Mem, addr: u16) -> Self {
+        let bytes = [
+            mem.peek(addr),
+            mem.peek(addr.wrapping_add(1)),
+            mem.peek(addr.wrapping_add(2)),
+        ];
+        Self::decode(addr, &bytes).expect("3 bytes is enough for any instruction")
+    }
+
+    /// Raw instruction bytes, including the opcode.
+    #[inline]
+    #[must_use]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[..self.size()]
+    }
+
+    /// Instruction length in bytes, including the opcode.
+    #[inline]
+    #[must_use]
+    pub const fn size(&self) -> usize {
+        operand_len(self.instr.addr_mode()) + 1
+    }
+
+    /// Operation performed by the instruction.
+    #[inline]
+    pub const fn op(&self) -> Operation {
+        self.instr.op()
+    }
+
+    /// Instruction mnemonic, prefixed with `*` for unofficial opcodes.
+    #[must_use]
+    pub fn mnemonic(&self) -> String {
+        // `Instr` pads official opcodes to align with the `*` prefix
+        self.instr.to_string().trim_start().to_string()
+    }
+
+    /// Address of the next sequential instruction.
+    #[inline]
+    #[must_use]
+    pub const fn next_addr(&self) -> u16 {
+        self.addr.wrapping_add(self.size() as u16)
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "${:04X} ", self.addr)?;
+        for i in 0..3 {
+            match self.bytes().get(i) {
+                Some(byte) => write!(f, "{byte:02X} ")?,
+                None => write!(f, "   ")?,
+            }
+        }
+        write!(f, "{}", self.instr)?;
+        match (self.operand, self.target) {
+            (Operand::Implied, _) => Ok(()),
+            (Operand::Relative(_), Some(target)) => write!(f, " ${target:04X}"),
+            (operand, _) => write!(f, " {operand}"),
+        }
+    }
+}
+
+/// Number of operand bytes following the opcode for a given [`AddrMode`].
+const fn operand_len(addr_mode: AddrMode) -> usize {
+    match addr_mode {
+        AddrMode::IMP | AddrMode::ACC => 0,
+        AddrMode::IMM
+        | AddrMode::ZP0
+        | AddrMode::ZPX
+        | AddrMode::ZPY
+        | AddrMode::IDX
+        | AddrMode::IDY
+        | AddrMode::REL => 1,
+        AddrMode::ABS | AddrMode::ABX | AddrMode::ABY | AddrMode::IND => 2,
+    }
+}
+
+/// Disassemble `bytes` sequentially, treating `bytes[0]` as located at `addr`. A trailing
+/// instruction truncated by the end of `bytes` is not included.
+pub fn disassemble(bytes: &[u8], addr: u16) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut offset = 0;
+    while let Some(instruction) = bytes
+        .get(offset..)
+        .and_then(|bytes| Instruction::decode(addr.wrapping_add(offset as u16), bytes))
+    {
+        offset += instruction.size();
+        instructions.push(instruction);
+    }
+    instructions
+}
+
+/// Disassemble instructions starting within `range` by peeking `mem`. The last instruction may
+/// extend past the end of `range`.
+pub fn disassemble_range(mem: &impl Mem, range: RangeInclusive<u16>) -> Vec<Instruction> {
+    let (start, end) = range.into_inner();
+    let mut instructions = Vec::new();
+    let mut addr = start;
+    while addr <= end {
+        let instruction = Instruction::peek(mem, addr);
+        instructions.push(instruction);
+        let next = instruction.next_addr();
+        if next < addr {
+            break; // Wrapped around the address space
+        }
+        addr = next;
+    }
+    instructions
+}
+
+/// Disassemble up to `before` instructions leading up to `pc`, the instruction at `pc`, and
+/// `after` instructions following it by peeking `mem`.
+///
+/// Instructions before `pc` are found by decoding forward from earlier addresses until one
+/// lands exactly on `pc`, which may not match the true instruction boundaries, e.g. if data is
+/// interleaved with code.
+pub fn disassemble_around(
+    mem: &impl Mem,
+    pc: u16,
+    before: usize,
+    after: usize,
+) -> Vec<Instruction> {
+    let mut instructions = Vec::with_capacity(before + after + 1);
+    let max_back = (before * 3).min(usize::from(pc));
+    for back in (1..=max_back).rev() {
+        let mut addr = pc - back as u16;
+        let mut leading = Vec::new();
+        while addr < pc {
+            let instruction = Instruction::peek(mem, addr);
+            leading.push(instruction);
+            addr = instruction.next_addr();
+        }
+        if addr == pc && !leading.is_empty() {
+            let skip = leading.len().saturating_sub(before);
+            instructions.extend_from_slice(&leading[skip..]);
+            break;
+        }
+    }
+
+    let mut addr = pc;
+    for _ in 0..=after {
+        let instruction = Instruction::peek(mem, addr);
+        instructions.push(instruction);
+        addr = instruction.next_addr();
+    }
+    instructions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Ram(Vec<u8>);
+
+    impl Mem for Ram {
+        fn peek(&self, addr: u16) -> u8 {
+            self.0[usize::from(addr)]
+        }
+
+        fn write(&mut self, addr: u16, val: u8) {
+            self.0[usize::from(addr)] = val;
+        }
+    }
+
+    #[test]
+    fn decode_operands() {
+        let bytes = [
+            0xA9, 0x10, // LDA #$10
+            0x8D, 0x00, 0x20, // STA $2000
+            0xD0, 0xFB, // BNE -5
+            0x0A, // ASL A
+            0xEA, // NOP
+            0x6C, 0xFC, 0xFF, // JMP ($FFFC)
+        ];
+        let instructions = disassemble(&bytes, 0x8000);
+        assert_eq!(instructions.len(), 6);
+
+        assert_eq!(instructions[0].operand, Operand::Immediate(0x10));
+        assert_eq!(instructions[0].bytes(), &[0xA9, 0x10]);
+        assert_eq!(instructions[1].operand, Operand::Absolute(0x2000));
+        assert_eq!(instructions[1].target, Some(0x2000));
+        assert_eq!(instructions[2].addr, 0x8005);
+        assert_eq!(instructions[2].operand, Operand::Relative(-5));
+        assert_eq!(instructions[2].target, Some(0x8002));
+        assert_eq!(instructions[3].operand, Operand::Accumulator);
+        assert_eq!(instructions[4].op(), Operation::NOP);
+        assert_eq!(instructions[5].operand, Operand::Indirect(0xFFFC));
+
+        assert_eq!(instructions[0].to_string(), "$8000 A9 10     LDA #$10");
+        assert_eq!(instructions[2].to_string(), "$8005 D0 FB     BNE $8002");
+        assert_eq!(instructions[4].to_string(), "$8008 EA        NOP");
+    }
+
+    #[test]
+    fn truncated_instruction() {
+        let instructions = disassemble(&[0xEA, 0x4C, 0x00], 0x0000);
+        assert_eq!(instructions.len(), 1, "truncated JMP is skipped");
+        assert!(Instruction::decode(0x0000, &[]).is_none());
+    }
+
+    #[test]
+    fn range_via_peek() {
+        let mut mem = Ram(vec![0x00; 0x10000]);
+        mem.0[0xFFFB..].copy_from_slice(&[0xEA, 0x20, 0x00, 0x80, 0x00]);
+        mem.0[0x0000] = 0xEA;
+        let instructions = disassemble_range(&mem, 0xFFFB..=0xFFFF);
+        assert_eq!(instructions.len(), 3);
+        assert_eq!(instructions[1].mnemonic(), "JSR");
+        assert_eq!(instructions[1].target, Some(0x8000));
+        assert_eq!(instructions[2].addr, 0xFFFF);
+        assert_eq!(instructions[2].bytes(), &[0x00, 0xEA], "wraps around");
+    }
+
+    #[test]
+    fn around_pc() {
+        let mut mem = Ram(vec![0xEA; 0x10000]);
+        mem.0[0x8000..0x8007].copy_from_slice(&[
+            0xA9, 0x10, // LDA #$10
+            0x8D, 0x00, 0x20, // STA $2000
+            0xE8, // INX
+            0xEA, // NOP
+        ]);
+        let instructions = disassemble_around(&mem, 0x8005, 2, 1);
+        let addrs = instructions
+            .iter()
+            .map(|instr| instr.addr)
+            .collect::<Vec<_>>();
+        assert_eq!(addrs, [0x8000, 0x8002, 0x8005, 0x8006]);
+        assert_eq!(
+            disassemble_around(&mem, 0x0000, 2, 0).len(),
+            1,
+            "nothing before $0000"
+        );
+    }
+}
@@ -2,10 +2,11 @@
 
 use crate::{
     cpu::{Cpu, Status},
+    logging::{error, trace},
     mem::Mem,
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use tracing::{error, trace};
 
 /// List of all CPU official and unofficial operations.
 ///
@@ -15,7 +16,8 @@ use tracing::{error, trace};
 /// - <http://archive.6502.org/datasheets/rockwell_r650x_r651x.pdf>
 #[rustfmt::skip]
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum Operation {
     ADC, AND, ASL, BCC, BCS, BEQ, BIT, BMI, BNE, BPL, BRK, BVC, BVS, CLC, CLD, CLI, CLV, CMP, CPX,
@@ -27,7 +29,8 @@ pub enum Operation {
 }
 
 /// CPU Addressing mode.
-#[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[allow(clippy::upper_case_acronyms)]
 #[rustfmt::skip]
 #[must_use]
@@ -52,7 +55,8 @@ use Operation::{
 /// CPU Instruction.
 ///
 /// (opcode, Addressing Mode, Operation, cycles taken)
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Instr(u8, AddrMode, Operation, usize);
 
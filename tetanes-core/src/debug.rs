@@ -1,5 +1,13 @@
-use crate::ppu::Ppu;
-use std::sync::Arc;
+#[cfg(feature = "serde")]
+use crate::fs;
+use crate::{
+    cpu::{disasm, Cpu, Irq},
+    mem::Mem,
+    ppu::Ppu,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, sync::Arc};
 
 #[derive(Debug, Clone, PartialEq)]
 #[must_use]
@@ -35,3 +43,751 @@ impl std::fmt::Debug for PpuDebugger {
             .finish_non_exhaustive()
     }
 }
+
+/// A condition that halts emulation when met.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub enum Breakpoint {
+    Cpu(CpuBreakpoint),
+    Ppu(PpuBreakpoint),
+}
+
+impl From<CpuBreakpoint> for Breakpoint {
+    fn from(breakpoint: CpuBreakpoint) -> Self {
+        Self::Cpu(breakpoint)
+    }
+}
+
+impl From<PpuBreakpoint> for Breakpoint {
+    fn from(breakpoint: PpuBreakpoint) -> Self {
+        Self::Ppu(breakpoint)
+    }
+}
+
+impl std::fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cpu(breakpoint) => write!(f, "{breakpoint}"),
+            Self::Ppu(breakpoint) => write!(f, "{breakpoint}"),
+        }
+    }
+}
+
+/// A CPU-domain breakpoint, checked as the CPU executes instructions and accesses the bus.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub enum CpuBreakpoint {
+    /// Break when the instruction at the given address is executed.
+    Exec(u16),
+    /// Break when the CPU reads from the given address, including instruction fetches.
+    Read(u16),
+    /// Break when the CPU writes to the given address.
+    Write(u16),
+    /// Break when the instruction at the given address is executed and `condition` holds just
+    /// before it executes.
+    Conditional { pc: u16, condition: BreakCondition },
+}
+
+impl std::fmt::Display for CpuBreakpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Exec(addr) => write!(f, "Exec ${addr:04X}"),
+            Self::Read(addr) => write!(f, "Read ${addr:04X}"),
+            Self::Write(addr) => write!(f, "Write ${addr:04X}"),
+            Self::Conditional { pc, condition } => write!(f, "Exec ${pc:04X} if {condition}"),
+        }
+    }
+}
+
+/// A comparison of a CPU register or memory value against a constant, e.g. `A == $10`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub struct BreakCondition {
+    pub operand: BreakOperand,
+    pub cmp: BreakComparison,
+    pub value: u8,
+}
+
+impl BreakCondition {
+    pub const fn new(operand: BreakOperand, cmp: BreakComparison, value: u8) -> Self {
+        Self {
+            operand,
+            cmp,
+            value,
+        }
+    }
+
+    /// Whether the condition holds for the current CPU state. Memory is peeked so checking
+    /// has no side effects.
+    #[must_use]
+    pub fn matches(&self, cpu: &Cpu) -> bool {
+        let current = match self.operand {
+            BreakOperand::A => cpu.acc,
+            BreakOperand::X => cpu.x,
+            BreakOperand::Y => cpu.y,
+            BreakOperand::Sp => cpu.sp,
+            BreakOperand::Status => cpu.status.bits(),
+            BreakOperand::Mem(addr) => cpu.peek(addr),
+        };
+        self.cmp.matches(current, self.value)
+    }
+}
+
+impl std::fmt::Display for BreakCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} ${:02X}", self.operand, self.cmp, self.value)
+    }
+}
+
+/// The value a [`BreakCondition`] compares.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub enum BreakOperand {
+    A,
+    X,
+    Y,
+    Sp,
+    Status,
+    Mem(u16),
+}
+
+impl std::fmt::Display for BreakOperand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::A => write!(f, "A"),
+            Self::X => write!(f, "X"),
+            Self::Y => write!(f, "Y"),
+            Self::Sp => write!(f, "SP"),
+            Self::Status => write!(f, "P"),
+            Self::Mem(addr) => write!(f, "[${addr:04X}]"),
+        }
+    }
+}
+
+/// How a [`BreakCondition`] compares its operand against its value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub enum BreakComparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+impl BreakComparison {
+    /// Whether `lhs` compares to `rhs`.
+    #[must_use]
+    pub const fn matches(self, lhs: u8, rhs: u8) -> bool {
+        match self {
+            Self::Equal => lhs == rhs,
+            Self::NotEqual => lhs != rhs,
+            Self::Less => lhs < rhs,
+            Self::LessEqual => lhs <= rhs,
+            Self::Greater => lhs > rhs,
+            Self::GreaterEqual => lhs >= rhs,
+        }
+    }
+}
+
+impl std::fmt::Display for BreakComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+            Self::Less => "<",
+            Self::LessEqual => "<=",
+            Self::Greater => ">",
+            Self::GreaterEqual => ">=",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// A PPU-domain breakpoint, checked as the PPU is clocked.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub enum PpuBreakpoint {
+    /// Break when the CPU writes to the given VRAM address via `$2007 PPUDATA`.
+    VramWrite(u16),
+    /// Break when rendering reaches the given scanline and cycle (dot).
+    Dot { scanline: u32, cycle: u32 },
+    /// Break when the sprite 0 hit flag gets set.
+    Sprite0Hit,
+}
+
+impl std::fmt::Display for PpuBreakpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::VramWrite(addr) => write!(f, "VRAM write ${addr:04X}"),
+            Self::Dot { scanline, cycle } => write!(f, "PPU dot {scanline},{cycle}"),
+            Self::Sprite0Hit => write!(f, "Sprite 0 hit"),
+        }
+    }
+}
+
+/// The type of interrupt serviced by the CPU.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum InterruptKind {
+    /// Non-maskable interrupt, triggered by the PPU at the start of VBlank.
+    Nmi,
+    /// Maskable interrupt request with the set of IRQ sources that were asserted.
+    Irq(Irq),
+}
+
+impl std::fmt::Display for InterruptKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Nmi => write!(f, "NMI"),
+            Self::Irq(irq) => {
+                write!(f, "IRQ")?;
+                let sources = irq
+                    .iter_names()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>()
+                    .join(" | ");
+                if !sources.is_empty() {
+                    write!(f, " ({sources})")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Snapshot of CPU registers, the stack and disassembly around the program counter, used to
+/// display a debugger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct CpuDebugState {
+    pub pc: u16,
+    pub acc: u8,
+    pub x: u8,
+    pub y: u8,
+    pub sp: u8,
+    pub status: u8,
+    /// Total CPU cycles.
+    pub cycle: usize,
+    /// Stack contents from the top of the stack at `$0100 + SP + 1` up to `$01FF`.
+    pub stack: Vec<u8>,
+    /// Instructions leading up to, at and following the program counter.
+    pub disassembly: Vec<disasm::Instruction>,
+}
+
+impl CpuDebugState {
+    /// Capture the current CPU state with up to `before` instructions preceding the program
+    /// counter and `after` instructions following it.
+    pub fn new(cpu: &Cpu, before: usize, after: usize) -> Self {
+        let stack = (u16::from(cpu.sp) + 1..=0xFF)
+            .map(|offset| cpu.peek(0x0100 | offset))
+            .collect();
+        Self {
+            pc: cpu.pc,
+            acc: cpu.acc,
+            x: cpu.x,
+            y: cpu.y,
+            sp: cpu.sp,
+            status: cpu.status.bits(),
+            cycle: cpu.cycle,
+            stack,
+            disassembly: disasm::disassemble_around(cpu, cpu.pc, before, after),
+        }
+    }
+}
+
+/// An interrupt serviced by the CPU along with the timing of when it was taken.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Interrupt {
+    pub kind: InterruptKind,
+    /// PPU frame number.
+    pub frame: u32,
+    /// PPU scanline.
+    pub scanline: u32,
+    /// PPU cycle (dot).
+    pub cycle: u32,
+    /// Total CPU cycles.
+    pub cpu_cycle: usize,
+    /// Program counter at the time the interrupt was taken.
+    pub pc: u16,
+}
+
+/// A bounded log of recently serviced interrupts. Disabled by default.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct InterruptLog {
+    enabled: bool,
+    capacity: usize,
+    entries: VecDeque<Interrupt>,
+}
+
+impl Default for InterruptLog {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+impl InterruptLog {
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    /// Create a new, disabled, `InterruptLog` holding up to `capacity` entries.
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            enabled: false,
+            capacity,
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// Whether interrupts are being logged.
+    #[inline]
+    #[must_use]
+    pub const fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enable or disable logging. Disabling clears any logged entries.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.entries.clear();
+        }
+    }
+
+    /// Log an interrupt, evicting the oldest entry if at capacity.
+    pub fn push(&mut self, interrupt: Interrupt) {
+        if !self.enabled || self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(interrupt);
+    }
+
+    /// Whether any entries have been logged.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over the logged entries, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &Interrupt> {
+        self.entries.iter()
+    }
+
+    /// Remove and return all logged entries, oldest first.
+    pub fn drain(&mut self) -> Vec<Interrupt> {
+        self.entries.drain(..).collect()
+    }
+
+    /// Clear all logged entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// A bounded history of CPU snapshots taken at instruction boundaries, allowing execution to be
+/// stepped backwards by restoring the nearest earlier snapshot and replaying forward.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct StepHistory {
+    interval: u64,
+    capacity: usize,
+    instr_count: u64,
+    snapshots: VecDeque<(u64, Vec<u8>)>,
+}
+
+#[cfg(feature = "serde")]
+impl Default for StepHistory {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_INTERVAL, Self::DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl StepHistory {
+    /// Number of instructions between snapshots.
+    pub const DEFAULT_INTERVAL: u64 = 32;
+    /// Number of snapshots to keep, which with the default interval allows stepping back 4096
+    /// instructions.
+    pub const DEFAULT_CAPACITY: usize = 128;
+
+    /// Create a new `StepHistory` taking a snapshot every `interval` instructions and keeping up to
+    /// `capacity` snapshots.
+    pub fn new(interval: u64, capacity: usize) -> Self {
+        Self {
+            interval: interval.max(1),
+            capacity,
+            instr_count: 0,
+            snapshots: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Number of instructions executed since recording started.
+    #[inline]
+    #[must_use]
+    pub const fn instr_count(&self) -> u64 {
+        self.instr_count
+    }
+
+    /// Number of instructions that can currently be stepped back.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.snapshots
+            .front()
+            .map_or(0, |(index, _)| self.instr_count - index)
+    }
+
+    /// Whether there are no instructions to step back.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Record the CPU state prior to executing the next instruction.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the CPU state fails to serialize.
+    pub fn record(&mut self, cpu: &Cpu) -> fs::Result<()> {
+        let already_recorded = self
+            .snapshots
+            .back()
+            .is_some_and(|(index, _)| *index == self.instr_count);
+        if self.capacity > 0 && self.instr_count % self.interval == 0 && !already_recorded {
+            let state = bincode::serialize(cpu)
+                .map_err(|err| fs::Error::SerializationFailed(err.to_string()))?;
+            if self.snapshots.len() >= self.capacity {
+                self.snapshots.pop_front();
+            }
+            self.snapshots.push_back((self.instr_count, state));
+        }
+        self.instr_count += 1;
+        Ok(())
+    }
+
+    /// Discard snapshots taken after `target` and return the closest remaining snapshot at or
+    /// before it, along with the number of instructions to replay to reach `target`. Returns
+    /// `None` if `target` is older than the history.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the snapshot fails to deserialize.
+    pub fn restore(&mut self, target: u64) -> Option<fs::Result<(Cpu, u64)>> {
+        if self.snapshots.front()?.0 > target {
+            return None;
+        }
+        while self
+            .snapshots
+            .back()
+            .is_some_and(|(index, _)| *index > target)
+        {
+            self.snapshots.pop_back();
+        }
+        let (index, state) = self.snapshots.back()?;
+        let replay = target - index;
+        self.instr_count = target;
+        Some(
+            bincode::deserialize(state)
+                .map(|cpu| (cpu, replay))
+                .map_err(|err| fs::Error::DeserializationFailed(err.to_string())),
+        )
+    }
+
+    /// Clear all snapshots and reset the instruction count.
+    pub fn clear(&mut self) {
+        self.instr_count = 0;
+        self.snapshots.clear();
+    }
+}
+
+/// Per-address CPU bus read and write counters, used to visualize memory activity.
+///
+/// Counting only occurs while enabled and saturates rather than wrapping.
+#[cfg(feature = "debug")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct BusActivity {
+    enabled: bool,
+    reads: Vec<u32>,
+    writes: Vec<u32>,
+}
+
+#[cfg(feature = "debug")]
+impl Default for BusActivity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "debug")]
+impl BusActivity {
+    /// Number of addressable CPU bus locations.
+    pub const SIZE: usize = 0x10000;
+
+    /// Create a new, disabled, `BusActivity`.
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            reads: vec![0; Self::SIZE],
+            writes: vec![0; Self::SIZE],
+        }
+    }
+
+    /// Whether bus activity is being counted.
+    #[inline]
+    #[must_use]
+    pub const fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enable or disable counting. Disabling clears any accumulated counts.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.clear();
+        }
+    }
+
+    /// Count a read from `addr`.
+    #[inline]
+    pub fn record_read(&mut self, addr: u16) {
+        if self.enabled {
+            let count = &mut self.reads[usize::from(addr)];
+            *count = count.saturating_add(1);
+        }
+    }
+
+    /// Count a write to `addr`.
+    #[inline]
+    pub fn record_write(&mut self, addr: u16) {
+        if self.enabled {
+            let count = &mut self.writes[usize::from(addr)];
+            *count = count.saturating_add(1);
+        }
+    }
+
+    /// Read counts, indexed by address.
+    #[inline]
+    #[must_use]
+    pub fn reads(&self) -> &[u32] {
+        &self.reads
+    }
+
+    /// Write counts, indexed by address.
+    #[inline]
+    #[must_use]
+    pub fn writes(&self) -> &[u32] {
+        &self.writes
+    }
+
+    /// Reset all counts to zero.
+    pub fn clear(&mut self) {
+        self.reads.fill(0);
+        self.writes.fill(0);
+    }
+}
+
+/// A snapshot of CPU RAM, used to search for addresses that changed between two moments of
+/// gameplay.
+#[derive(Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub struct MemorySnapshot {
+    /// Internal 2K work RAM, mapped at `$0000-$07FF`.
+    pub wram: Vec<u8>,
+    /// The first bank of cartridge PRG-RAM, mapped at `$6000-$7FFF`.
+    pub prg_ram: Vec<u8>,
+}
+
+impl std::fmt::Debug for MemorySnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemorySnapshot")
+            .field("wram_len", &self.wram.len())
+            .field("prg_ram_len", &self.prg_ram.len())
+            .finish()
+    }
+}
+
+impl MemorySnapshot {
+    /// Address PRG-RAM is mapped at.
+    pub const PRG_RAM_START: u16 = 0x6000;
+    /// Size of the PRG-RAM window.
+    pub const PRG_RAM_SIZE: usize = 0x2000;
+
+    /// Take a snapshot of the RAM in `cpu`.
+    pub fn new(cpu: &Cpu) -> Self {
+        let prg_ram = &cpu.bus.prg_ram;
+        Self {
+            wram: cpu.bus.wram.to_vec(),
+            prg_ram: prg_ram[..prg_ram.len().min(Self::PRG_RAM_SIZE)].to_vec(),
+        }
+    }
+
+    /// Read the value at `addr`, if it's RAM included in the snapshot.
+    #[must_use]
+    pub fn peek(&self, addr: u16) -> Option<u8> {
+        Self::index(addr).and_then(|(prg_ram, index)| {
+            if prg_ram {
+                self.prg_ram.get(index).copied()
+            } else {
+                self.wram.get(index).copied()
+            }
+        })
+    }
+
+    /// Iterate over every address and value in the snapshot.
+    pub fn iter(&self) -> impl Iterator<Item = (u16, u8)> + '_ {
+        let wram = (0..).zip(self.wram.iter().copied());
+        let prg_ram = (Self::PRG_RAM_START..).zip(self.prg_ram.iter().copied());
+        wram.chain(prg_ram)
+    }
+
+    /// Whether the RAM location at `addr` is PRG-RAM, and its index in that RAM.
+    #[must_use]
+    pub const fn index(addr: u16) -> Option<(bool, usize)> {
+        match addr {
+            0x0000..=0x07FF => Some((false, addr as usize)),
+            0x6000..=0x7FFF => Some((true, (addr - Self::PRG_RAM_START) as usize)),
+            _ => None,
+        }
+    }
+}
+
+/// How values are compared between two [`MemorySnapshot`]s.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub enum MemoryComparison {
+    Changed,
+    Unchanged,
+    Increased,
+    Decreased,
+    /// The later value equals the given value.
+    Equals(u8),
+}
+
+impl MemoryComparison {
+    /// Whether a value changing from `before` to `after` matches this comparison.
+    #[must_use]
+    pub const fn matches(self, before: u8, after: u8) -> bool {
+        match self {
+            Self::Changed => before != after,
+            Self::Unchanged => before == after,
+            Self::Increased => after > before,
+            Self::Decreased => after < before,
+            Self::Equals(val) => after == val,
+        }
+    }
+}
+
+/// An address matching a [`MemoryComparison`] and its values in each snapshot.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct MemoryMatch {
+    pub addr: u16,
+    pub before: u8,
+    pub after: u8,
+}
+
+/// Compare two [`MemorySnapshot`]s, returning the addresses that match `comparison`.
+///
+/// If `candidates` is provided, only those addresses are compared, allowing results to be narrowed
+/// down over several searches.
+pub fn search_memory(
+    before: &MemorySnapshot,
+    after: &MemorySnapshot,
+    comparison: MemoryComparison,
+    candidates: Option<&[u16]>,
+) -> Vec<MemoryMatch> {
+    let compare = |addr: u16, before: u8| {
+        let after = after.peek(addr)?;
+        comparison.matches(before, after).then_some(MemoryMatch {
+            addr,
+            before,
+            after,
+        })
+    };
+    match candidates {
+        Some(candidates) => candidates
+            .iter()
+            .filter_map(|&addr| compare(addr, before.peek(addr)?))
+            .collect(),
+        None => before
+            .iter()
+            .filter_map(|(addr, before)| compare(addr, before))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(wram: &[(usize, u8)], prg_ram: &[(usize, u8)]) -> MemorySnapshot {
+        let mut snapshot = MemorySnapshot {
+            wram: vec![0; 0x0800],
+            prg_ram: vec![0; MemorySnapshot::PRG_RAM_SIZE],
+        };
+        for &(index, val) in wram {
+            snapshot.wram[index] = val;
+        }
+        for &(index, val) in prg_ram {
+            snapshot.prg_ram[index] = val;
+        }
+        snapshot
+    }
+
+    #[test]
+    fn search_memory_comparisons() {
+        let before = snapshot(&[(0x10, 5), (0x20, 5), (0x30, 5)], &[(0x01, 1)]);
+        let after = snapshot(&[(0x10, 6), (0x20, 4), (0x30, 5)], &[(0x01, 3)]);
+
+        let addrs = |comparison| {
+            search_memory(&before, &after, comparison, None)
+                .into_iter()
+                .map(|m| m.addr)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(addrs(MemoryComparison::Changed), [0x0010, 0x0020, 0x6001]);
+        assert_eq!(addrs(MemoryComparison::Increased), [0x0010, 0x6001]);
+        assert_eq!(addrs(MemoryComparison::Decreased), [0x0020]);
+        assert_eq!(addrs(MemoryComparison::Equals(3)), [0x6001]);
+        assert_eq!(
+            addrs(MemoryComparison::Unchanged).len(),
+            0x0800 + MemorySnapshot::PRG_RAM_SIZE - 3
+        );
+    }
+
+    #[test]
+    fn search_memory_candidates() {
+        let before = snapshot(&[(0x10, 5), (0x20, 5)], &[]);
+        let after = snapshot(&[(0x10, 6), (0x20, 6)], &[]);
+
+        let matches = search_memory(
+            &before,
+            &after,
+            MemoryComparison::Changed,
+            Some(&[0x0020, 0x0030, 0x4000]),
+        );
+        assert_eq!(
+            matches,
+            [MemoryMatch {
+                addr: 0x0020,
+                before: 5,
+                after: 6
+            }]
+        );
+    }
+}
@@ -1,19 +1,36 @@
 //! Filesystem utilities for save state and compression.
 
-use crate::sys::fs;
+use crate::{logging::warn, sys::fs};
+#[cfg(feature = "serde")]
 use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+#[cfg(feature = "serde")]
 use serde::{de::DeserializeOwned, Serialize};
+#[cfg(feature = "serde")]
+use std::io::Cursor;
 use std::{
-    io::{Cursor, Read, Write},
+    io::{Read, Write},
     path::{Path, PathBuf},
 };
 use thiserror::Error;
-use tracing::warn;
 
+#[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+mod encryption;
+
+#[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+pub use encryption::{
+    encryption, is_encrypted, lock_encryption, set_encryption_password, Encryption,
+};
+
+#[cfg(feature = "serde")]
 const SAVE_FILE_MAGIC_LEN: usize = 8;
+#[cfg(feature = "serde")]
 const SAVE_FILE_MAGIC: [u8; SAVE_FILE_MAGIC_LEN] = *b"TETANES\x1a";
 // Keep this separate from Semver because breaking API changes may not invalidate the save format.
-const SAVE_VERSION: &str = "1";
+#[cfg(feature = "serde")]
+const SAVE_VERSION: &str = "3";
+/// Oldest save version [`load_any_version`] accepts.
+#[cfg(feature = "serde")]
+const MIN_SAVE_VERSION: &str = "1";
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -34,6 +51,10 @@ pub enum Error {
     DeserializationFailed(String),
     #[error("invalid path: {0:?}")]
     InvalidPath(PathBuf),
+    #[error("saves are encrypted, enter the save password to continue")]
+    EncryptionLocked,
+    #[error("failed to decrypt data, the save password may be incorrect")]
+    DecryptionFailed,
     #[error("{context}: {source:?}")]
     Io {
         source: std::io::Error,
@@ -61,6 +82,7 @@ impl Error {
 /// # Errors
 ///
 /// If the header fails to write to disk, then an error is returned.
+#[cfg(feature = "serde")]
 pub(crate) fn write_header(f: &mut impl Write) -> std::io::Result<()> {
     f.write_all(&SAVE_FILE_MAGIC)?;
     f.write_all(SAVE_VERSION.as_bytes())
@@ -71,7 +93,15 @@ pub(crate) fn write_header(f: &mut impl Write) -> std::io::Result<()> {
 /// # Errors
 ///
 /// If the header fails to validate, then an error is returned.
+#[cfg(feature = "serde")]
 pub(crate) fn validate_header(f: &mut impl Read) -> Result<()> {
+    validate_header_since(f, SAVE_VERSION)
+}
+
+/// Verifies a `TetaNES` saved state header with a version between `min_version` and the current
+/// version.
+#[cfg(feature = "serde")]
+fn validate_header_since(f: &mut impl Read, min_version: &str) -> Result<()> {
     let mut magic = [0u8; SAVE_FILE_MAGIC_LEN];
     f.read_exact(&mut magic)
         .map_err(|s| Error::InvalidHeader(s.to_string()))?;
@@ -84,7 +114,7 @@ pub(crate) fn validate_header(f: &mut impl Read) -> Result<()> {
     let mut version = [0u8];
     f.read_exact(&mut version)
         .map_err(|s| Error::InvalidHeader(s.to_string()))?;
-    if version == SAVE_VERSION.as_bytes() {
+    if (min_version.as_bytes()..=SAVE_VERSION.as_bytes()).contains(&version.as_slice()) {
         Ok(())
     } else {
         Err(Error::InvalidHeader(format!(
@@ -93,6 +123,7 @@ pub(crate) fn validate_header(f: &mut impl Read) -> Result<()> {
     }
 }
 
+#[cfg(feature = "serde")]
 pub fn encode(mut writer: &mut impl Write, data: &[u8]) -> std::io::Result<()> {
     let mut encoder = DeflateEncoder::new(&mut writer, Compression::default());
     encoder.write_all(data)?;
@@ -100,6 +131,7 @@ pub fn encode(mut writer: &mut impl Write, data: &[u8]) -> std::io::Result<()> {
     Ok(())
 }
 
+#[cfg(feature = "serde")]
 pub fn decode(data: impl Read) -> std::io::Result<Vec<u8>> {
     let mut decoded = vec![];
     let mut decoder = DeflateDecoder::new(data);
@@ -107,10 +139,17 @@ pub fn decode(data: impl Read) -> std::io::Result<Vec<u8>> {
     Ok(decoded)
 }
 
+#[cfg(feature = "serde")]
 pub fn save<T>(path: impl AsRef<Path>, value: &T) -> Result<()>
 where
     T: ?Sized + Serialize,
 {
+    #[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+    if encryption() != Encryption::Disabled {
+        let data = encryption::encrypt(save_bytes(value)?)?;
+        return save_raw(path, &data);
+    }
+
     let data =
         bincode::serialize(value).map_err(|err| Error::SerializationFailed(err.to_string()))?;
     let mut writer = fs::writer_impl(path)?;
@@ -122,6 +161,20 @@ where
     Ok(())
 }
 
+/// Serializes a value to bytes in the same format as [`save`].
+#[cfg(feature = "serde")]
+pub fn save_bytes<T>(value: &T) -> Result<Vec<u8>>
+where
+    T: ?Sized + Serialize,
+{
+    let data =
+        bincode::serialize(value).map_err(|err| Error::SerializationFailed(err.to_string()))?;
+    let mut bytes = Vec::new();
+    write_header(&mut bytes).map_err(Error::WriteHeaderFailed)?;
+    encode(&mut bytes, &data).map_err(Error::EncodingFailed)?;
+    Ok(bytes)
+}
+
 pub fn save_raw(path: impl AsRef<Path>, value: &[u8]) -> Result<()> {
     let mut writer = fs::writer_impl(path)?;
     writer
@@ -133,16 +186,72 @@ pub fn save_raw(path: impl AsRef<Path>, value: &[u8]) -> Result<()> {
     Ok(())
 }
 
+/// Saves data by writing to a temporary file and renaming it over `path`, so a crash or power
+/// loss mid-write leaves either the previous or the new contents intact.
+pub fn save_raw_atomic(path: impl AsRef<Path>, value: &[u8]) -> Result<()> {
+    fs::write_atomic_impl(path, value)
+}
+
+/// Returns the path of the `n`th backup of `path`, e.g. `config.json.1`.
+pub fn backup_path(path: impl AsRef<Path>, n: usize) -> PathBuf {
+    let mut backup = path.as_ref().as_os_str().to_owned();
+    backup.push(format!(".{n}"));
+    PathBuf::from(backup)
+}
+
+/// Shifts existing backups of `path` up by one, keeping at most `count`, and moves `path` to
+/// the first backup slot.
+pub fn rotate_backups(path: impl AsRef<Path>, count: usize) -> Result<()> {
+    let path = path.as_ref();
+    if count == 0 || !exists(path) {
+        return Ok(());
+    }
+    for n in (1..count).rev() {
+        let backup = backup_path(path, n);
+        if exists(&backup) {
+            rename(&backup, backup_path(path, n + 1))?;
+        }
+    }
+    rename(path, backup_path(path, 1))
+}
+
+pub fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<()> {
+    fs::rename_impl(from, to)
+}
+
+#[cfg(feature = "serde")]
 pub fn load<T>(path: impl AsRef<Path>) -> Result<T>
 where
     T: DeserializeOwned,
 {
+    load_since(path, SAVE_VERSION)
+}
+
+/// Loads a value like [`load`], but also from files saved by older versions. Only for data whose
+/// serialized layout never changes, e.g. SRAM bytes, so it outlives save state format changes.
+#[cfg(feature = "serde")]
+pub fn load_any_version<T>(path: impl AsRef<Path>) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    load_since(path, MIN_SAVE_VERSION)
+}
+
+#[cfg(feature = "serde")]
+fn load_since<T>(path: impl AsRef<Path>, min_version: &str) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    #[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+    let mut reader = Cursor::new(encryption::decrypt(load_raw(path)?)?);
+    #[cfg(not(all(feature = "encryption", not(target_arch = "wasm32"))))]
     let mut reader = fs::reader_impl(path)?;
-    validate_header(&mut reader)?;
+    validate_header_since(&mut reader, min_version)?;
     let data = decode(&mut reader).map_err(Error::DecodingFailed)?;
     bincode::deserialize(&data).map_err(|err| Error::DeserializationFailed(err.to_string()))
 }
 
+#[cfg(feature = "serde")]
 pub fn load_bytes<T>(bytes: &[u8]) -> Result<T>
 where
     T: DeserializeOwned,
@@ -162,6 +271,10 @@ pub fn load_raw(path: impl AsRef<Path>) -> Result<Vec<u8>> {
     Ok(data)
 }
 
+pub fn remove(path: impl AsRef<Path>) -> Result<()> {
+    fs::remove_impl(path)
+}
+
 pub fn clear_dir(path: impl AsRef<Path>) -> Result<()> {
     fs::clear_dir_impl(path)
 }
@@ -243,6 +356,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn header_versions() {
+        let mut file = [SAVE_FILE_MAGIC.as_slice(), MIN_SAVE_VERSION.as_bytes()].concat();
+        assert!(
+            validate_header(&mut file.as_slice()).is_err(),
+            "older version invalid"
+        );
+        assert!(
+            validate_header_since(&mut file.as_slice(), MIN_SAVE_VERSION).is_ok(),
+            "older version allowed"
+        );
+        file = [SAVE_FILE_MAGIC.as_slice(), b"9"].concat();
+        assert!(
+            validate_header_since(&mut file.as_slice(), MIN_SAVE_VERSION).is_err(),
+            "newer version invalid"
+        );
+    }
+
+    #[test]
+    fn save_load_bytes() {
+        let value = (String::from("rom"), vec![1u8, 2, 3]);
+        let bytes = save_bytes(&value).expect("save bytes");
+        let loaded: (String, Vec<u8>) = load_bytes(&bytes).expect("load bytes");
+        assert_eq!(loaded, value);
+    }
+
+    #[test]
+    fn save_atomic_with_backups() {
+        let dir = std::env::temp_dir().join(format!("tetanes-fs-{}", std::process::id()));
+        let path = dir.join("config.json");
+        for data in [b"one", b"two", b"tri"] {
+            rotate_backups(&path, 2).expect("rotate backups");
+            save_raw_atomic(&path, data).expect("save atomic");
+        }
+        assert_eq!(load_raw(&path).expect("load"), b"tri");
+        assert_eq!(load_raw(backup_path(&path, 1)).expect("backup 1"), b"two");
+        assert_eq!(load_raw(backup_path(&path, 2)).expect("backup 2"), b"one");
+        assert!(!exists(&backup_path(&path, 3)), "only two backups kept");
+        clear_dir(&dir).expect("clear dir");
+    }
+
     #[test]
     fn crc32() {
         let s = "Lorem ipsum dolor sit amet, consectetur adipisicing elit";
@@ -0,0 +1,268 @@
+//! Password-based encryption of saved files at rest.
+//!
+//! Encrypted files start with their own magic string, so they're never mistaken for plain saves,
+//! and can't be loaded without the password they were saved with or by versions without
+//! encryption support.
+
+use crate::fs::{Error, Result};
+use ring::{
+    aead::{self, Aad, LessSafeKey, Nonce, UnboundKey},
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+use std::{
+    collections::HashMap,
+    num::NonZeroU32,
+    sync::{PoisonError, RwLock},
+};
+
+const MAGIC_LEN: usize = 8;
+const MAGIC: [u8; MAGIC_LEN] = *b"TETAENC\x1a";
+const VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const HEADER_LEN: usize = MAGIC_LEN + 1 + SALT_LEN + aead::NONCE_LEN;
+const PBKDF2_ITERATIONS: NonZeroU32 = match NonZeroU32::new(100_000) {
+    Some(iterations) => iterations,
+    None => unreachable!(),
+};
+
+static STATE: RwLock<State> = RwLock::new(State::Disabled);
+
+/// Whether files are encrypted when saved.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum Encryption {
+    /// Files are saved unencrypted.
+    Disabled,
+    /// Encryption is enabled, but no password has been entered this session, so saving fails
+    /// rather than writing unencrypted files.
+    Locked,
+    /// Files are encrypted with the password entered this session.
+    Unlocked,
+}
+
+enum State {
+    Disabled,
+    Locked,
+    Unlocked {
+        password: Vec<u8>,
+        /// Salt used for files saved this session.
+        salt: [u8; SALT_LEN],
+        /// Keys derived from `password` by salt, so each is only derived once.
+        keys: HashMap<[u8; SALT_LEN], [u8; 32]>,
+    },
+}
+
+impl State {
+    fn unlocked(password: &str) -> Result<Self> {
+        let mut salt = [0; SALT_LEN];
+        SystemRandom::new()
+            .fill(&mut salt)
+            .map_err(|_| Error::custom("failed to generate encryption salt"))?;
+        Ok(Self::Unlocked {
+            password: password.as_bytes().to_vec(),
+            salt,
+            keys: HashMap::new(),
+        })
+    }
+
+    fn key(&mut self, salt: [u8; SALT_LEN]) -> Option<LessSafeKey> {
+        let Self::Unlocked { password, keys, .. } = self else {
+            return None;
+        };
+        let key = keys.entry(salt).or_insert_with(|| {
+            let mut key = [0; 32];
+            pbkdf2::derive(
+                pbkdf2::PBKDF2_HMAC_SHA256,
+                PBKDF2_ITERATIONS,
+                &salt,
+                password,
+                &mut key,
+            );
+            key
+        });
+        UnboundKey::new(&aead::CHACHA20_POLY1305, key.as_slice())
+            .ok()
+            .map(LessSafeKey::new)
+    }
+
+    fn encrypt(&mut self, data: Vec<u8>) -> Result<Vec<u8>> {
+        let salt = match self {
+            Self::Disabled => return Ok(data),
+            Self::Locked => return Err(Error::EncryptionLocked),
+            Self::Unlocked { salt, .. } => *salt,
+        };
+        let key = self.key(salt).ok_or(Error::EncryptionLocked)?;
+
+        let mut nonce = [0; aead::NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| Error::custom("failed to generate encryption nonce"))?;
+
+        let mut encrypted = Vec::with_capacity(HEADER_LEN + data.len() + aead::MAX_TAG_LEN);
+        encrypted.extend_from_slice(&MAGIC);
+        encrypted.push(VERSION);
+        encrypted.extend_from_slice(&salt);
+        encrypted.extend_from_slice(&nonce);
+        let mut in_out = data;
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(&encrypted[..HEADER_LEN]),
+            &mut in_out,
+        )
+        .map_err(|_| Error::custom("failed to encrypt data"))?;
+        encrypted.extend_from_slice(&in_out);
+        Ok(encrypted)
+    }
+
+    fn decrypt(&mut self, data: Vec<u8>) -> Result<Vec<u8>> {
+        if !is_encrypted(&data) {
+            return Ok(data);
+        }
+        if data.len() < HEADER_LEN + aead::MAX_TAG_LEN {
+            return Err(Error::InvalidHeader("truncated encryption header".into()));
+        }
+        let version = data[MAGIC_LEN];
+        if version != VERSION {
+            return Err(Error::InvalidHeader(format!(
+                "invalid encryption version (expected {VERSION}, found: {version})"
+            )));
+        }
+        let (header, ciphertext) = data.split_at(HEADER_LEN);
+        let mut salt = [0; SALT_LEN];
+        salt.copy_from_slice(&header[MAGIC_LEN + 1..MAGIC_LEN + 1 + SALT_LEN]);
+        let mut nonce = [0; aead::NONCE_LEN];
+        nonce.copy_from_slice(&header[MAGIC_LEN + 1 + SALT_LEN..]);
+
+        let key = self.key(salt).ok_or(Error::EncryptionLocked)?;
+        let mut in_out = ciphertext.to_vec();
+        let len = key
+            .open_in_place(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(header),
+                &mut in_out,
+            )
+            .map_err(|_| Error::DecryptionFailed)?
+            .len();
+        in_out.truncate(len);
+        Ok(in_out)
+    }
+}
+
+/// Returns whether files are encrypted when saved.
+pub fn encryption() -> Encryption {
+    match *STATE.read().unwrap_or_else(PoisonError::into_inner) {
+        State::Disabled => Encryption::Disabled,
+        State::Locked => Encryption::Locked,
+        State::Unlocked { .. } => Encryption::Unlocked,
+    }
+}
+
+/// Require a password before saving or loading encrypted files. Any password entered previously
+/// is forgotten.
+pub fn lock_encryption() {
+    *STATE.write().unwrap_or_else(PoisonError::into_inner) = State::Locked;
+}
+
+/// Encrypt saved files with a key derived from `password` for the rest of the session, or stop
+/// encrypting if `None`.
+///
+/// # Errors
+///
+/// If no secure random salt can be generated, then an error is returned.
+pub fn set_encryption_password(password: Option<&str>) -> Result<()> {
+    let state = match password {
+        Some(password) => State::unlocked(password)?,
+        None => State::Disabled,
+    };
+    *STATE.write().unwrap_or_else(PoisonError::into_inner) = state;
+    Ok(())
+}
+
+/// Returns whether `data` was encrypted by [`encrypt`].
+#[must_use]
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(&MAGIC)
+}
+
+/// Encrypts `data` with the session password, or returns it unchanged if encryption is
+/// disabled.
+///
+/// # Errors
+///
+/// If encryption is enabled but no password has been entered, then an error is returned.
+pub fn encrypt(data: Vec<u8>) -> Result<Vec<u8>> {
+    STATE
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .encrypt(data)
+}
+
+/// Decrypts `data` saved by [`encrypt`] with the session password, or returns it unchanged if
+/// it isn't encrypted.
+///
+/// # Errors
+///
+/// If `data` is encrypted and no password has been entered, or it was saved with a different
+/// password or is corrupted, then an error is returned.
+pub fn decrypt(data: Vec<u8>) -> Result<Vec<u8>> {
+    STATE
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .decrypt(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_decrypt() {
+        let data = b"TETANES\x1a1 save data".to_vec();
+
+        let mut state = State::Disabled;
+        assert_eq!(state.encrypt(data.clone()).expect("disabled"), data);
+
+        let mut state = State::unlocked("hunter2").expect("unlocked");
+        let encrypted = state.encrypt(data.clone()).expect("encrypt");
+        assert!(is_encrypted(&encrypted));
+        assert!(
+            !encrypted.windows(9).any(|w| w == b"save data"),
+            "not plaintext"
+        );
+        assert_eq!(state.decrypt(encrypted.clone()).expect("decrypt"), data);
+        assert_eq!(
+            state.decrypt(data.clone()).expect("plain"),
+            data,
+            "plain files load"
+        );
+
+        let mut next_session = State::unlocked("hunter2").expect("unlocked");
+        assert_eq!(
+            next_session.decrypt(encrypted.clone()).expect("decrypt"),
+            data,
+            "same password in a later session"
+        );
+
+        let mut wrong = State::unlocked("hunter3").expect("unlocked");
+        assert!(matches!(
+            wrong.decrypt(encrypted.clone()),
+            Err(Error::DecryptionFailed)
+        ));
+        assert!(matches!(
+            State::Locked.decrypt(encrypted.clone()),
+            Err(Error::EncryptionLocked)
+        ));
+        assert!(matches!(
+            State::Locked.encrypt(data),
+            Err(Error::EncryptionLocked)
+        ));
+
+        let mut tampered = encrypted;
+        *tampered.last_mut().expect("tag") ^= 1;
+        assert!(matches!(
+            state.decrypt(tampered),
+            Err(Error::DecryptionFailed)
+        ));
+    }
+}
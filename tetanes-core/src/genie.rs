@@ -1,5 +1,6 @@
 //! Game Genie code parsing.
 
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, sync::OnceLock};
 use thiserror::Error;
@@ -35,10 +36,13 @@ pub enum ErrorKind {
     InvalidLength(usize),
     #[error("invalid character: `{0}`")]
     InvalidCharacter(char),
+    #[error("raw codes must be hex formatted as `AAAA:VV` or `AAAA?CC:VV`")]
+    InvalidRawCode,
 }
 
-/// Game Genie Code
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Game Genie Code, or a raw address code that overrides reads in the same way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GenieCode {
     code: String,
     addr: u16,
@@ -53,10 +57,75 @@ impl GenieCode {
     ///
     /// This function will return an error if the given code is not the correct format.
     pub fn new(code: String) -> Result<Self> {
+        if code.contains(':') {
+            return Self::parse_addr(&code);
+        }
         let hex = Self::parse(&code)?;
         Ok(Self::from_raw(code, hex))
     }
 
+    /// Creates a new `GenieCode` instance that overrides reads from `addr` with `data`, only if
+    /// the original value equals `compare`, if set. Unlike Game Genie codes, which only patch
+    /// PRG-ROM, any readable address can be overridden, such as RAM.
+    pub fn from_addr(addr: u16, data: u8, compare: Option<u8>) -> Self {
+        let code = match compare {
+            Some(compare) => format!("{addr:04X}?{compare:02X}:{data:02X}"),
+            None => format!("{addr:04X}:{data:02X}"),
+        };
+        Self {
+            code,
+            addr,
+            data,
+            compare,
+        }
+    }
+
+    /// Encodes a Game Genie code that overrides reads from PRG-ROM `addr` with `data`, only if
+    /// the original value equals `compare`, if set. Returns `None` if `addr` is outside of
+    /// PRG-ROM (`$8000-$FFFF`), which Game Genie codes can't patch. Use
+    /// [`GenieCode::from_addr`] instead for those addresses.
+    #[must_use]
+    pub fn encode(addr: u16, data: u8, compare: Option<u8>) -> Option<Self> {
+        const LETTERS: &[u8; 16] = b"APZLGITYEOXUKSVN";
+
+        if addr < 0x8000 {
+            return None;
+        }
+        let mut hex = vec![
+            ((data >> 4) & 8) | (data & 7),
+            ((addr >> 4) as u8 & 8) | ((data >> 4) & 7),
+            (addr >> 4) as u8 & 7,
+            (addr as u8 & 8) | ((addr >> 12) as u8 & 7),
+            ((addr >> 8) as u8 & 8) | (addr as u8 & 7),
+            (data & 8) | ((addr >> 8) as u8 & 7),
+        ];
+        if let Some(compare) = compare {
+            // Setting the high bit of the third letter marks an 8-letter code
+            hex[2] |= 8;
+            hex[5] = (compare & 8) | ((addr >> 8) as u8 & 7);
+            hex.push(((compare >> 4) & 8) | (compare & 7));
+            hex.push((data & 8) | ((compare >> 4) & 7));
+        }
+        let code = hex
+            .iter()
+            .map(|&h| char::from(LETTERS[usize::from(h)]))
+            .collect();
+        Some(Self::from_raw(code, hex))
+    }
+
+    /// Parses a raw address code in the `AAAA:VV` or `AAAA?CC:VV` format used by `RetroArch`.
+    fn parse_addr(code: &str) -> Result<Self> {
+        let invalid = || Error::new(code, ErrorKind::InvalidRawCode);
+        let hex_u16 = |s: &str| u16::from_str_radix(s, 16).map_err(|_| invalid());
+        let hex_u8 = |s: &str| u8::from_str_radix(s, 16).map_err(|_| invalid());
+        let (addr, data) = code.split_once(':').ok_or_else(invalid)?;
+        let (addr, compare) = match addr.split_once('?') {
+            Some((addr, compare)) => (addr, Some(hex_u8(compare)?)),
+            None => (addr, None),
+        };
+        Ok(Self::from_addr(hex_u16(addr)?, hex_u8(data)?, compare))
+    }
+
     /// Creates a new `GenieCode` instance from raw hex values. `GenieCode` may not be valid if
     /// `hex` is not the correct length. Use `GenieCode::parse` to validate the code.
     pub fn from_raw(code: String, hex: Vec<u8>) -> Self {
@@ -136,6 +205,22 @@ impl GenieCode {
         self.addr
     }
 
+    #[must_use]
+    pub const fn data(&self) -> u8 {
+        self.data
+    }
+
+    #[must_use]
+    pub const fn compare(&self) -> Option<u8> {
+        self.compare
+    }
+
+    /// Whether this is a raw address code rather than a Game Genie code.
+    #[must_use]
+    pub fn is_raw(&self) -> bool {
+        self.code.contains(':')
+    }
+
     #[must_use]
     pub const fn read(&self, val: u8) -> u8 {
         if let Some(compare) = self.compare {
@@ -155,3 +240,24 @@ impl std::fmt::Display for GenieCode {
         write!(f, "{}", &self.code)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_round_trip() {
+        for code in ["AATOZE", "SXIOPO", "YEUZUGAA", "GOISIP"] {
+            let decoded = GenieCode::new(code.to_string()).expect("valid genie code");
+            let encoded = GenieCode::encode(decoded.addr(), decoded.data(), decoded.compare())
+                .expect("valid rom address");
+            assert_eq!(encoded, decoded, "{code}");
+        }
+        // The 6-letter flag bit in the third letter is ignored when decoding
+        let decoded = GenieCode::new("GOSSIP".to_string()).expect("valid genie code");
+        let encoded =
+            GenieCode::encode(decoded.addr(), decoded.data(), None).expect("valid rom address");
+        assert_eq!(encoded.code(), "GOISIP");
+        assert!(GenieCode::encode(0x0075, 0x09, None).is_none());
+    }
+}
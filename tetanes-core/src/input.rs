@@ -1,22 +1,25 @@
-//! [`Joypad`] and [`Zapper`] implementation.
+//! [`Joypad`], [`Zapper`] and [`Mouse`] implementation.
 
 use crate::{
     common::{Clock, NesRegion, Reset, ResetKind},
     cpu::Cpu,
+    logging::trace,
     ppu::Ppu,
+    vs_system::VsSystem,
 };
 use bitflags::bitflags;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 use thiserror::Error;
-use tracing::trace;
 
 #[derive(Error, Debug)]
 #[must_use]
 #[error("failed to parse `Player`")]
 pub struct ParsePlayerError;
 
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum Player {
     #[default]
@@ -69,25 +72,49 @@ pub trait InputRegisters {
     fn write(&mut self, val: u8);
 }
 
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Four player adapter, which games detect in different ways depending on the console they were
+/// released for.
+///
+/// See: <https://www.nesdev.org/wiki/Four_player_adapters>
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum FourPlayer {
     #[default]
     Disabled,
+    /// NES Four Score or Satellite, which report controllers #3/#4 on D0 of $4016/$4017 after
+    /// controllers #1/#2, followed by a signature.
     FourScore,
-    Satellite,
+    /// Famicom expansion port adapter, which reports controllers #3/#4 on D1 of $4016/$4017
+    /// alongside controllers #1/#2.
+    #[cfg_attr(feature = "serde", serde(alias = "Satellite"))]
+    Famicom,
+    /// Use the adapter given by the default expansion device of an NES 2.0 header, if any.
+    Auto,
 }
 
 impl FourPlayer {
     pub const fn as_slice() -> &'static [Self] {
-        &[Self::Disabled, Self::FourScore, Self::Satellite]
+        &[Self::Disabled, Self::FourScore, Self::Famicom, Self::Auto]
     }
 
     pub const fn as_str(&self) -> &'static str {
         match self {
             Self::Disabled => "disabled",
             Self::FourScore => "four-score",
-            Self::Satellite => "satellite",
+            Self::Famicom => "famicom",
+            Self::Auto => "auto",
+        }
+    }
+
+    /// Returns the adapter for an NES 2.0 header default expansion device.
+    ///
+    /// See: <https://www.nesdev.org/wiki/NES_2.0#Default_Expansion_Device>
+    pub const fn from_expansion_device(device: u8) -> Self {
+        match device {
+            0x02 => Self::FourScore,
+            0x03 => Self::Famicom,
+            _ => Self::Disabled,
         }
     }
 }
@@ -103,7 +130,8 @@ impl std::fmt::Display for FourPlayer {
         let s = match self {
             Self::Disabled => "Disabled",
             Self::FourScore => "FourScore",
-            Self::Satellite => "Satellite",
+            Self::Famicom => "Famicom",
+            Self::Auto => "Auto",
         };
         write!(f, "{s}")
     }
@@ -115,22 +143,95 @@ impl FromStr for FourPlayer {
         match s {
             "disabled" => Ok(Self::Disabled),
             "four-score" => Ok(Self::FourScore),
-            "satellite" => Ok(Self::Satellite),
+            "famicom" | "satellite" => Ok(Self::Famicom),
+            "auto" => Ok(Self::Auto),
             _ => Err(
-                "invalid FourPlayer value. valid options: `disabled`, `four-score`, or `satellite`",
+                "invalid FourPlayer value. valid options: `disabled`, `four-score`, `famicom`, or `auto`",
             ),
         }
     }
 }
 
-#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+/// Controller port a [`Mouse`] is plugged into.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub enum MousePort {
+    #[default]
+    Disabled,
+    One,
+    Two,
+}
+
+impl MousePort {
+    pub const fn as_slice() -> &'static [Self] {
+        &[Self::Disabled, Self::One, Self::Two]
+    }
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Disabled => "disabled",
+            Self::One => "one",
+            Self::Two => "two",
+        }
+    }
+
+    /// Returns the player whose controller port the mouse is plugged into.
+    pub const fn player(&self) -> Option<Player> {
+        match self {
+            Self::Disabled => None,
+            Self::One => Some(Player::One),
+            Self::Two => Some(Player::Two),
+        }
+    }
+}
+
+impl AsRef<str> for MousePort {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl std::fmt::Display for MousePort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Disabled => "Disabled",
+            Self::One => "Port 1",
+            Self::Two => "Port 2",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl FromStr for MousePort {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "disabled" => Ok(Self::Disabled),
+            "one" => Ok(Self::One),
+            "two" => Ok(Self::Two),
+            _ => Err("invalid MousePort value. valid options: `disabled`, `one`, or `two`"),
+        }
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Input {
     pub joypads: [Joypad; 4],
     pub signatures: [Joypad; 2],
     pub zapper: Zapper,
+    pub mouse: Mouse,
     pub turbo_timer: u32,
     pub four_player: FourPlayer,
+    /// Four player adapter expected by the loaded cartridge, used by [`FourPlayer::Auto`].
+    pub cart_four_player: FourPlayer,
+    /// Coin slots and DIP switches when running on Vs. System hardware.
+    pub vs_system: Option<VsSystem>,
+    /// Whether a controller has been read since last cleared, used to detect lag frames.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub polled: bool,
 }
 
 impl Input {
@@ -143,8 +244,12 @@ impl Input {
                 Joypad::from_bytes(0b0000_0100),
             ],
             zapper: Zapper::new(region),
+            mouse: Mouse::default(),
             turbo_timer: 30,
             four_player: FourPlayer::default(),
+            cart_four_player: FourPlayer::Disabled,
+            vs_system: None,
+            polled: false,
         }
     }
 
@@ -170,21 +275,46 @@ impl Input {
         self.zapper.connected = connected;
     }
 
+    pub fn connect_mouse(&mut self, port: MousePort) {
+        self.mouse.port = port;
+        self.mouse.reset(ResetKind::Hard);
+    }
+
     pub fn set_four_player(&mut self, four_player: FourPlayer) {
         self.four_player = four_player;
         self.reset(ResetKind::Hard);
     }
 
+    /// Returns the four player adapter in use, resolving [`FourPlayer::Auto`].
+    pub const fn four_player_adapter(&self) -> FourPlayer {
+        match self.four_player {
+            FourPlayer::Auto => self.cart_four_player,
+            four_player => four_player,
+        }
+    }
+
     pub fn clear(&mut self) {
         for pad in &mut self.joypads {
             pad.clear();
         }
         self.zapper.clear();
+        self.mouse.clear();
+    }
+
+    /// Bits D2-D7 read alongside controller data, which are open bus on the NES and coin slots
+    /// and DIP switches on the Vs. System.
+    const fn upper_bits(&self, player: Player) -> u8 {
+        match self.vs_system {
+            Some(vs_system) => vs_system.read(0x4016 + player as u16),
+            None => 0x40,
+        }
     }
 }
 
 impl InputRegisters for Input {
     fn read(&mut self, player: Player, ppu: &Ppu) -> u8 {
+        self.polled = true;
+
         // Read $4016/$4017 D0 8x for controller #1/#2.
         // Read $4016/$4017 D0 8x for controller #3/#4.
         // Read $4016/$4017 D0 8x for signature: 0b00010000/0b00100000
@@ -194,10 +324,15 @@ impl InputRegisters for Input {
             0x00
         };
 
+        let upper_bits = self.upper_bits(player);
+        if self.mouse.port.player() == Some(player) {
+            return zapper | self.mouse.read() | upper_bits;
+        }
+
         let player = player as usize;
         assert!(player < 4);
-        let val = match self.four_player {
-            FourPlayer::Disabled => self.joypads[player].read(),
+        let val = match self.four_player_adapter() {
+            FourPlayer::Disabled | FourPlayer::Auto => self.joypads[player].read(),
             FourPlayer::FourScore => {
                 if self.joypads[player].index() < 8 {
                     self.joypads[player].read()
@@ -209,12 +344,12 @@ impl InputRegisters for Input {
                     0x01
                 }
             }
-            FourPlayer::Satellite => {
+            FourPlayer::Famicom => {
                 self.joypads[player].read() | (self.joypads[player + 2].read() << 1)
             }
         };
 
-        zapper | val | 0x40
+        zapper | val | upper_bits
     }
 
     fn peek(&self, player: Player, ppu: &Ppu) -> u8 {
@@ -227,10 +362,15 @@ impl InputRegisters for Input {
             0x00
         };
 
+        let upper_bits = self.upper_bits(player);
+        if self.mouse.port.player() == Some(player) {
+            return zapper | self.mouse.peek() | upper_bits;
+        }
+
         let player = player as usize;
         assert!(player < 4);
-        let val = match self.four_player {
-            FourPlayer::Disabled => self.joypads[player].peek(),
+        let val = match self.four_player_adapter() {
+            FourPlayer::Disabled | FourPlayer::Auto => self.joypads[player].peek(),
             FourPlayer::FourScore => {
                 if self.joypads[player].index() < 8 {
                     self.joypads[player].peek()
@@ -242,12 +382,12 @@ impl InputRegisters for Input {
                     0x01
                 }
             }
-            FourPlayer::Satellite => {
+            FourPlayer::Famicom => {
                 self.joypads[player].peek() | (self.joypads[player + 2].peek() << 1)
             }
         };
 
-        zapper | val | 0x40
+        zapper | val | upper_bits
     }
 
     fn write(&mut self, val: u8) {
@@ -257,12 +397,16 @@ impl InputRegisters for Input {
         for sig in &mut self.signatures {
             sig.write(val);
         }
+        self.mouse.write(val);
     }
 }
 
 impl Clock for Input {
     fn clock(&mut self) -> usize {
         self.zapper.clock();
+        if let Some(vs_system) = &mut self.vs_system {
+            vs_system.clock();
+        }
         if self.turbo_timer > 0 {
             self.turbo_timer -= 1;
         }
@@ -292,10 +436,15 @@ impl Reset for Input {
         self.signatures[0] = Joypad::from_bytes(0b0000_1000);
         self.signatures[1] = Joypad::from_bytes(0b0000_0100);
         self.zapper.reset(kind);
+        self.mouse.reset(kind);
+        if let Some(vs_system) = &mut self.vs_system {
+            vs_system.reset(kind);
+        }
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum JoypadBtn {
     /// Left D-Pad.
     Left,
@@ -337,7 +486,8 @@ impl AsRef<str> for JoypadBtn {
 }
 
 bitflags! {
-    #[derive(Default, Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+    #[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     #[must_use]
     pub struct JoypadBtnState: u16 {
         const A = 0x01;
@@ -370,7 +520,8 @@ impl From<JoypadBtn> for JoypadBtnState {
     }
 }
 
-#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Joypad {
     pub buttons: JoypadBtnState,
@@ -463,7 +614,8 @@ impl Reset for Joypad {
     }
 }
 
-#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Zapper {
     pub triggered: f32,
@@ -574,3 +726,206 @@ impl Reset for Zapper {
         self.triggered = 0.0;
     }
 }
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MouseBtn {
+    Left,
+    Right,
+}
+
+/// Super NES Mouse plugged into a controller port through an adapter, as supported by some
+/// homebrew.
+///
+/// Each strobe latches a 32-bit report, read out MSB first:
+///
+/// - Byte 1: `0x00`.
+/// - Byte 2: right and left buttons, sensitivity and a `0001` signature.
+/// - Byte 3: Y direction (1 = up) and magnitude of vertical motion.
+/// - Byte 4: X direction (1 = left) and magnitude of horizontal motion.
+///
+/// See: <https://www.nesdev.org/wiki/Super_NES_Mouse>
+#[derive(Default, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub struct Mouse {
+    pub port: MousePort,
+    pub left: bool,
+    pub right: bool,
+    /// Motion accumulated since the last report was latched.
+    pub dx: i32,
+    pub dy: i32,
+    /// Sensitivity from 0-2, cycled by reading while strobe is high.
+    pub sensitivity: u8,
+    pub report: u32,
+    pub index: u8,
+    pub strobe: bool,
+}
+
+impl Mouse {
+    const MAX_MOTION: i32 = 0x7F;
+
+    /// Move the mouse by a number of counts, where positive values are right and down.
+    pub fn motion(&mut self, dx: i32, dy: i32) {
+        self.dx = self.dx.saturating_add(dx);
+        self.dy = self.dy.saturating_add(dy);
+    }
+
+    pub fn set_button(&mut self, button: MouseBtn, pressed: bool) {
+        match button {
+            MouseBtn::Left => self.left = pressed,
+            MouseBtn::Right => self.right = pressed,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.left = false;
+        self.right = false;
+        self.dx = 0;
+        self.dy = 0;
+    }
+
+    /// Latch buttons and motion into a report, carrying over motion too large to report.
+    fn latch(&mut self) {
+        let dx = self.dx.clamp(-Self::MAX_MOTION, Self::MAX_MOTION);
+        let dy = self.dy.clamp(-Self::MAX_MOTION, Self::MAX_MOTION);
+        self.dx -= dx;
+        self.dy -= dy;
+
+        let axis = |delta: i32| (u32::from(delta < 0) << 7) | delta.unsigned_abs();
+        let status = (u32::from(self.right) << 7)
+            | (u32::from(self.left) << 6)
+            | (u32::from(self.sensitivity) << 4)
+            | 0x01;
+        self.report = (status << 16) | (axis(dy) << 8) | axis(dx);
+        self.index = 0;
+    }
+
+    #[must_use]
+    fn read(&mut self) -> u8 {
+        if self.strobe {
+            self.sensitivity = (self.sensitivity + 1) % 3;
+            self.report = (self.report & !0x0030_0000) | (u32::from(self.sensitivity) << 20);
+        }
+        let val = self.peek();
+        if !self.strobe && self.index < 32 {
+            self.index += 1;
+        }
+        val
+    }
+
+    #[must_use]
+    const fn peek(&self) -> u8 {
+        if self.index < 32 {
+            ((self.report >> (31 - self.index)) & 0x01) as u8
+        } else {
+            0x01
+        }
+    }
+
+    fn write(&mut self, val: u8) {
+        let prev_strobe = self.strobe;
+        self.strobe = val & 0x01 == 0x01;
+        if !prev_strobe && self.strobe {
+            self.latch();
+        }
+    }
+}
+
+impl Reset for Mouse {
+    fn reset(&mut self, _kind: ResetKind) {
+        self.clear();
+        self.sensitivity = 0;
+        self.report = 0;
+        self.index = 0;
+        self.strobe = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_bits(input: &mut Input, player: Player, count: usize) -> Vec<u8> {
+        let ppu = Ppu::default();
+        input.write(0x01);
+        input.write(0x00);
+        (0..count)
+            .map(|_| input.read(player, &ppu) & 0x03)
+            .collect()
+    }
+
+    #[test]
+    fn four_score() {
+        let mut input = Input::new(NesRegion::Ntsc);
+        input.set_four_player(FourPlayer::FourScore);
+        input.joypad_mut(Player::One).set_button(JoypadBtn::A, true);
+        input
+            .joypad_mut(Player::Three)
+            .set_button(JoypadBtn::B, true);
+
+        let bits = read_bits(&mut input, Player::One, 25);
+        assert_eq!(bits[0..8], [1, 0, 0, 0, 0, 0, 0, 0], "controller #1");
+        assert_eq!(bits[8..16], [0, 1, 0, 0, 0, 0, 0, 0], "controller #3");
+        assert_eq!(bits[16..24], [0, 0, 0, 1, 0, 0, 0, 0], "signature");
+        assert_eq!(bits[24], 1);
+
+        let bits = read_bits(&mut input, Player::Two, 24);
+        assert_eq!(bits[16..24], [0, 0, 1, 0, 0, 0, 0, 0], "signature");
+    }
+
+    #[test]
+    fn famicom() {
+        let mut input = Input::new(NesRegion::Ntsc);
+        input.set_four_player(FourPlayer::Famicom);
+        input.joypad_mut(Player::Two).set_button(JoypadBtn::A, true);
+        input
+            .joypad_mut(Player::Four)
+            .set_button(JoypadBtn::B, true);
+
+        let bits = read_bits(&mut input, Player::Two, 9);
+        assert_eq!(bits, [0b01, 0b10, 0, 0, 0, 0, 0, 0, 0b11]);
+    }
+
+    #[test]
+    fn mouse() {
+        let mut input = Input::new(NesRegion::Ntsc);
+        input.connect_mouse(MousePort::Two);
+        input.mouse.set_button(MouseBtn::Left, true);
+        input.mouse.motion(-3, 200);
+
+        let bits = read_bits(&mut input, Player::Two, 33);
+        let byte = |i: usize| {
+            bits[i * 8..i * 8 + 8]
+                .iter()
+                .fold(0, |b, bit| (b << 1) | bit)
+        };
+        assert_eq!(byte(0), 0x00);
+        assert_eq!(byte(1), 0x41, "buttons and signature");
+        assert_eq!(byte(2), 0x7F, "down, clamped");
+        assert_eq!(byte(3), 0x83, "left");
+        assert_eq!(bits[32], 1);
+
+        let bits = read_bits(&mut input, Player::Two, 24);
+        assert_eq!(bits[16..24], [0, 1, 0, 0, 1, 0, 0, 1], "remaining motion");
+        assert_eq!(read_bits(&mut input, Player::One, 1), [0], "controller #1");
+
+        input.write(0x01);
+        let _ = input.read(Player::Two, &Ppu::default());
+        assert_eq!(input.mouse.sensitivity, 1);
+    }
+
+    #[test]
+    fn four_player_auto() {
+        let mut input = Input::new(NesRegion::Ntsc);
+        input.set_four_player(FourPlayer::Auto);
+        assert_eq!(input.four_player_adapter(), FourPlayer::Disabled);
+
+        input.cart_four_player = FourPlayer::from_expansion_device(0x03);
+        assert_eq!(input.four_player_adapter(), FourPlayer::Famicom);
+        input
+            .joypad_mut(Player::Three)
+            .set_button(JoypadBtn::A, true);
+        assert_eq!(read_bits(&mut input, Player::One, 1), [0b10]);
+    }
+}
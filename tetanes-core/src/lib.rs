@@ -6,8 +6,10 @@
 
 pub mod action;
 pub mod apu;
+pub mod batch;
 pub mod bus;
 pub mod cart;
+pub mod cheats;
 pub mod debug;
 pub mod fs;
 pub mod time;
@@ -18,11 +20,13 @@ pub mod cpu;
 pub mod error;
 pub mod genie;
 pub mod input;
+mod logging;
 pub mod mapper;
 pub mod mem;
 pub mod ppu;
 pub mod sys;
 pub mod video;
+pub mod vs_system;
 
 pub mod prelude {
     //! The prelude re-exports all the common structs/enums used for basic NES emulation.
@@ -32,7 +36,7 @@ pub mod prelude {
         apu::{Apu, Channel},
         cart::Cart,
         common::{Clock, ClockTo, NesRegion, Regional, Reset, ResetKind, Sample},
-        control_deck::{Config, ControlDeck, HeadlessMode},
+        control_deck::{Config, ControlDeck, HeadlessMode, SyncControlDeck},
         cpu::Cpu,
         genie::GenieCode,
         input::{FourPlayer, Input, Player},
@@ -0,0 +1,26 @@
+//! Logging macros that forward to [`tracing`](https://docs.rs/tracing) when the `tracing` feature
+//! is enabled, and compile to nothing otherwise.
+
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::{debug, error, info, trace, warn};
+
+#[cfg(not(feature = "tracing"))]
+mod noop {
+    macro_rules! noop {
+        ($($arg:tt)*) => {
+            if false {
+                // Type-check and "use" arguments without evaluating them
+                let _ = format_args!($($arg)*);
+            }
+        };
+    }
+
+    pub(crate) use noop as debug;
+    pub(crate) use noop as error;
+    pub(crate) use noop as info;
+    pub(crate) use noop as trace;
+    pub(crate) use noop as warn;
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) use noop::{debug, error, info, trace, warn};
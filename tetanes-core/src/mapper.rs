@@ -8,59 +8,121 @@ use crate::{
     ppu::Mirroring,
 };
 use enum_dispatch::enum_dispatch;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "mapper-bandai-fcg")]
 pub use bandai_fcg::BandaiFCG; // m016, m153, m157, m159
 pub use m000_nrom::Nrom;
+#[cfg(feature = "mapper-sxrom")]
 pub use m001_sxrom::{Revision as Mmc1Revision, Sxrom};
+#[cfg(feature = "mapper-uxrom")]
 pub use m002_uxrom::Uxrom;
+#[cfg(feature = "mapper-cnrom")]
 pub use m003_cnrom::Cnrom;
-pub use m004_txrom::{Revision as Mmc3Revision, Txrom};
+#[cfg(feature = "mapper-txrom")]
+pub use m004_txrom::Txrom;
+#[cfg(feature = "mapper-exrom")]
 pub use m005_exrom::Exrom;
+#[cfg(feature = "mapper-axrom")]
 pub use m007_axrom::Axrom;
+#[cfg(feature = "mapper-pxrom")]
 pub use m009_pxrom::Pxrom;
+#[cfg(feature = "mapper-fxrom")]
 pub use m010_fxrom::Fxrom;
+#[cfg(feature = "mapper-color-dreams")]
 pub use m011_color_dreams::ColorDreams;
+#[cfg(feature = "mapper-jaleco-ss88006")]
 pub use m018_jalecoss88006::JalecoSs88006;
+#[cfg(feature = "mapper-namco163")]
 pub use m019_namco163::Namco163;
+#[cfg(feature = "mapper-vrc6")]
 pub use m024_m026_vrc6::Vrc6;
+#[cfg(feature = "mapper-bnrom")]
 pub use m034_bnrom::Bnrom;
+#[cfg(feature = "mapper-nina001")]
 pub use m034_nina001::Nina001;
+#[cfg(feature = "mapper-rambo1")]
+pub use m064_rambo1::Rambo1; // m064, m158
+#[cfg(feature = "mapper-gxrom")]
 pub use m066_gxrom::Gxrom;
+#[cfg(feature = "mapper-sunsoft4")]
+pub use m068_sunsoft4::Sunsoft4;
+#[cfg(feature = "mapper-sunsoft-fme7")]
 pub use m069_sunsoft_fme7::SunsoftFme7;
-pub use m071_bf909x::{Bf909x, Revision as Bf909Revision};
+#[cfg(feature = "mapper-bf909x")]
+pub use m071_bf909x::Bf909x;
+#[cfg(feature = "mapper-dxrom76")]
 pub use m076_dxrom::Dxrom as Dxrom76;
+#[cfg(feature = "mapper-nina003006")]
 pub use m079_nina003_006::Nina003006;
+#[cfg(feature = "mapper-dxrom88")]
 pub use m088_dxrom::Dxrom as Dxrom88;
+#[cfg(feature = "mapper-dxrom95")]
 pub use m095_dxrom::Dxrom as Dxrom95;
+#[cfg(feature = "mapper-vs-system")]
+pub use m099_vs_system::VsSystem;
+#[cfg(feature = "mapper-dxrom154")]
 pub use m154_dxrom::Dxrom as Dxrom154;
+#[cfg(feature = "mapper-dxrom206")]
 pub use m206_dxrom::Dxrom as Dxrom206;
 
+#[cfg(feature = "mapper-bandai-fcg")]
 pub mod bandai_fcg;
 pub mod m000_nrom;
+#[cfg(feature = "mapper-sxrom")]
 pub mod m001_sxrom;
+#[cfg(feature = "mapper-uxrom")]
 pub mod m002_uxrom;
+#[cfg(feature = "mapper-cnrom")]
 pub mod m003_cnrom;
+#[cfg(feature = "mapper-txrom")]
 pub mod m004_txrom;
+#[cfg(feature = "mapper-exrom")]
 pub mod m005_exrom;
+#[cfg(feature = "mapper-axrom")]
 pub mod m007_axrom;
+#[cfg(feature = "mapper-pxrom")]
 pub mod m009_pxrom;
+#[cfg(feature = "mapper-fxrom")]
 pub mod m010_fxrom;
+#[cfg(feature = "mapper-color-dreams")]
 pub mod m011_color_dreams;
+#[cfg(feature = "mapper-jaleco-ss88006")]
 pub mod m018_jalecoss88006;
+#[cfg(feature = "mapper-namco163")]
 pub mod m019_namco163;
+#[cfg(feature = "mapper-vrc6")]
 pub mod m024_m026_vrc6;
+#[cfg(feature = "mapper-bnrom")]
 pub mod m034_bnrom;
+#[cfg(feature = "mapper-nina001")]
 pub mod m034_nina001;
+#[cfg(feature = "mapper-rambo1")]
+pub mod m064_rambo1;
+#[cfg(feature = "mapper-gxrom")]
 pub mod m066_gxrom;
+#[cfg(feature = "mapper-sunsoft4")]
+pub mod m068_sunsoft4;
+#[cfg(feature = "mapper-sunsoft-fme7")]
 pub mod m069_sunsoft_fme7;
+#[cfg(feature = "mapper-bf909x")]
 pub mod m071_bf909x;
+#[cfg(feature = "mapper-dxrom76")]
 pub mod m076_dxrom;
+#[cfg(feature = "mapper-nina003006")]
 pub mod m079_nina003_006;
+#[cfg(feature = "mapper-dxrom88")]
 pub mod m088_dxrom;
+#[cfg(feature = "mapper-dxrom95")]
 pub mod m095_dxrom;
+#[cfg(feature = "mapper-vs-system")]
+pub mod m099_vs_system;
+#[cfg(feature = "mapper-dxrom154")]
 pub mod m154_dxrom;
+#[cfg(feature = "mapper-dxrom206")]
 pub mod m206_dxrom;
+#[cfg(feature = "mapper-vrc6")]
 pub mod vrc_irq;
 
 #[derive(thiserror::Error, Debug)]
@@ -70,8 +132,48 @@ pub enum Error {
     Bank(#[from] mem::Error),
 }
 
+// MMC3 Revision
+// See:<http://forums.nesdev.com/viewtopic.php?p=62546#p62546>
+//
+// Known Revisions:
+//
+// Conquest of the Crystal Palace (MMC3B S 9039 1 DB)
+// Kickle Cubicle (MMC3B S 9031 3 DA)
+// M.C. Kids (MMC3B S 9152 3 AB)
+// Mega Man 3 (MMC3B S 9046 1 DB)
+// Super Mario Bros. 3 (MMC3B S 9027 5 A)
+// Startropics (MMC6B P 03'5)
+// Batman (MMC3B 9006KP006)
+// Golgo 13: The Mafat Conspiracy (MMC3B 9016KP051)
+// Crystalis (MMC3B 9024KPO53)
+// Legacy of the Wizard (MMC3A 8940EP)
+//
+// Only major difference is the IRQ counter
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub enum Mmc3Revision {
+    /// MMC3 Revision A
+    A,
+    /// MMC3 Revisions B & C
+    #[default]
+    BC,
+    /// Acclaims MMC3 clone - clocks on falling edge
+    Acc,
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub enum Bf909Revision {
+    #[default]
+    Bf909x,
+    Bf9097,
+}
+
 /// Allow user-controlled mapper revision for mappers that are difficult to auto-detect correctly.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum MapperRevision {
     // Mmc1 and Vrc6 should be properly detected by the mapper number
@@ -99,36 +201,67 @@ impl std::fmt::Display for MapperRevision {
 }
 
 #[enum_dispatch]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[allow(clippy::large_enum_variant)]
 #[must_use]
 pub enum Mapper {
     None,
     Nrom,
+    #[cfg(feature = "mapper-sxrom")]
     Sxrom,
+    #[cfg(feature = "mapper-uxrom")]
     Uxrom,
+    #[cfg(feature = "mapper-cnrom")]
     Cnrom,
+    #[cfg(feature = "mapper-txrom")]
     Txrom,
+    #[cfg(feature = "mapper-exrom")]
     Exrom,
+    #[cfg(feature = "mapper-axrom")]
     Axrom,
+    #[cfg(feature = "mapper-pxrom")]
     Pxrom,
+    #[cfg(feature = "mapper-fxrom")]
     Fxrom,
+    #[cfg(feature = "mapper-color-dreams")]
     ColorDreams,
+    #[cfg(feature = "mapper-bandai-fcg")]
     BandaiFCG,
+    #[cfg(feature = "mapper-jaleco-ss88006")]
     JalecoSs88006,
+    #[cfg(feature = "mapper-namco163")]
     Namco163,
+    #[cfg(feature = "mapper-vrc6")]
     Vrc6,
+    #[cfg(feature = "mapper-bnrom")]
     Bnrom,
+    #[cfg(feature = "mapper-nina001")]
     Nina001,
+    #[cfg(feature = "mapper-gxrom")]
     Gxrom,
+    #[cfg(feature = "mapper-sunsoft-fme7")]
     SunsoftFme7,
+    #[cfg(feature = "mapper-bf909x")]
     Bf909x,
+    #[cfg(feature = "mapper-dxrom76")]
     Dxrom76,
+    #[cfg(feature = "mapper-nina003006")]
     Nina003006,
+    #[cfg(feature = "mapper-dxrom88")]
     Dxrom88,
+    #[cfg(feature = "mapper-dxrom95")]
     Dxrom95,
+    #[cfg(feature = "mapper-dxrom154")]
     Dxrom154,
+    #[cfg(feature = "mapper-dxrom206")]
     Dxrom206,
+    #[cfg(feature = "mapper-rambo1")]
+    Rambo1,
+    #[cfg(feature = "mapper-sunsoft4")]
+    Sunsoft4,
+    #[cfg(feature = "mapper-vs-system")]
+    VsSystem,
 }
 
 impl Mapper {
@@ -139,6 +272,24 @@ impl Mapper {
     pub const fn is_none(&self) -> bool {
         matches!(self, Self::None(_))
     }
+
+    /// Name of the expansion audio chip output on [`Channel::Mapper`], if any.
+    ///
+    /// [`Channel::Mapper`]: crate::apu::Channel::Mapper
+    #[must_use]
+    pub const fn expansion_audio(&self) -> Option<&'static str> {
+        match self {
+            #[cfg(feature = "mapper-exrom")]
+            Self::Exrom(_) => Some("MMC5"),
+            #[cfg(feature = "mapper-namco163")]
+            Self::Namco163(_) => Some("Namco 163"),
+            #[cfg(feature = "mapper-vrc6")]
+            Self::Vrc6(_) => Some("VRC6"),
+            #[cfg(feature = "mapper-sunsoft-fme7")]
+            Self::SunsoftFme7(_) => Some("Sunsoft 5B"),
+            _ => Option::None,
+        }
+    }
 }
 
 impl Default for Mapper {
@@ -148,7 +299,8 @@ impl Default for Mapper {
 }
 
 /// Type of read operation for an address for a given Mapper.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum MappedRead {
     /// Defer to default data bus behavior for this read. Primarily used to read from
@@ -170,7 +322,8 @@ pub enum MappedRead {
 }
 
 /// Type of write operation for an address for a given Mapper.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum MappedWrite {
     /// Do nothing with this write.
@@ -217,7 +370,8 @@ pub trait Mapped {
     fn cpu_bus_write(&mut self, _addr: u16, _val: u8) {}
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct None;
 
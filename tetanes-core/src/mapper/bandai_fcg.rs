@@ -10,10 +10,12 @@ use crate::{
     mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap, Mirroring},
     mem::{Banks, Memory},
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::{cmp::Ordering, path::Path};
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Regs {
     pub prg_page: u8,
@@ -25,7 +27,8 @@ pub struct Regs {
     pub irq_reload: u16,
 }
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum MemoryOp {
     None,
@@ -35,7 +38,8 @@ pub enum MemoryOp {
     ReadWrite,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct BandaiFCG {
     pub regs: Regs,
@@ -56,6 +60,17 @@ impl BandaiFCG {
     const PRG_WINDOW: usize = 16 * 1024;
     const CHR_ROM_WINDOW: usize = 1024;
     const CHR_RAM_SIZE: usize = 8 * 1024;
+    /// PRG-ROM and CHR-ROM CRC32s of iNES 1.0 mapper 016 games with a 256-byte serial EEPROM
+    /// (24C02), which the header can't specify.
+    const X24C02_CRC32S: [u32; 7] = [
+        0x136C_A449, // Dragon Ball Z Gaiden - Saiya Jin Zetsumetsu Keikaku
+        0x9924_0573, // Dragon Ball Z II - Gekishin Freeza!!
+        0xA262_A81F, // Rokudenashi Blues
+        0xA954_1452, // Dragon Ball Z II - Gekishin Freeza!! (Rev 1)
+        0xB049_A8C4, // SD Gundam Gaiden - Knight Gundam Monogatari 2 - Hikari no Knight
+        0xC284_0372, // SD Gundam Gaiden - Knight Gundam Monogatari 3 - Densetsu no Kishi Dan
+        0xDC52_BF0C, // Dragon Ball Z III - Ressen Jinzou Ningen
+    ];
 
     pub fn load(cart: &mut Cart) -> Result<Mapper, mapper::Error> {
         let (chr_len, chr_window) = if cart.has_chr_rom() {
@@ -88,9 +103,14 @@ impl BandaiFCG {
             // CHR-ROM
 
             // Add a 256 byte serial EEPROM (24C02)
-            if matches!(bandai_fcg.submapper_num, 0 | 5) && cart.prg_ram().len() == 256 {
-                // Connect a 256-byte EEPROM for iNES roms, and when submapper 5 + 256 bytes of
-                // save ram in header
+            let has_eeprom = if cart.is_nes2() {
+                // Submapper 5 + 256 bytes of save ram in header
+                bandai_fcg.submapper_num == 5 && cart.prg_ram().len() == 256
+            } else {
+                // iNES roms can't specify the EEPROM, so rely on the battery flag or known games
+                cart.battery_backed() || Self::X24C02_CRC32S.contains(&cart.crc32())
+            };
+            if has_eeprom {
                 bandai_fcg.standard_eeprom = Some(Eeprom::new(EepromModel::X24C02));
             }
         } else if bandai_fcg.mapper_num == 157 {
@@ -115,6 +135,10 @@ impl BandaiFCG {
             // LZ93D50 with 128 byte serial EEPROM (24C01)
             bandai_fcg.standard_eeprom = Some(Eeprom::new(EepromModel::X24C01));
         }
+        // EEPROM saves aren't always flagged as battery-backed in the header
+        if bandai_fcg.standard_eeprom.is_some() || bandai_fcg.extra_eeprom.is_some() {
+            cart.set_battery_backed();
+        }
 
         if bandai_fcg.mapper_num == 16 {
             if matches!(bandai_fcg.submapper_num, 0 | 4) {
@@ -379,7 +403,8 @@ impl Sram for BandaiFCG {
 impl Regional for BandaiFCG {}
 impl Reset for BandaiFCG {}
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct BarcodeReader {
     data: Vec<u8>,
@@ -565,14 +590,16 @@ impl Clock for BarcodeReader {
     }
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum EepromModel {
     X24C01,
     X24C02,
 }
 
-#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum EepromMode {
     #[default]
@@ -585,7 +612,8 @@ pub enum EepromMode {
     ChipAddr,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Eeprom {
     model: EepromModel,
@@ -874,13 +902,64 @@ impl Eeprom {
 }
 
 impl Sram for Eeprom {
+    #[cfg(feature = "serde")]
     fn save(&self, path: impl AsRef<Path>) -> fs::Result<()> {
         let extension = self.sram_extension();
         fs::save(path.as_ref().with_extension(extension), &self.rom_data)
     }
 
+    #[cfg(feature = "serde")]
     fn load(&mut self, path: impl AsRef<Path>) -> fs::Result<()> {
-        let extension = self.sram_extension();
-        fs::load(path.as_ref().with_extension(extension)).map(|data| self.rom_data = data)
+        let path = path.as_ref().with_extension(self.sram_extension());
+        // Save RAM may have been saved before the EEPROM was written
+        if !fs::exists(&path) {
+            return Ok(());
+        }
+        fs::load_any_version(path).map(|data| self.rom_data = data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::RamState;
+
+    fn load(flags: u8) -> (BandaiFCG, bool) {
+        let rom = Cart::test_rom(16, 0, 1, 1, flags);
+        let cart = Cart::from_rom("bandai_fcg", &mut rom.as_slice(), RamState::AllZeros)
+            .expect("valid cart");
+        let battery_backed = cart.battery_backed();
+        let Mapper::BandaiFCG(mapper) = cart.mapper else {
+            panic!("expected Bandai FCG mapper");
+        };
+        (mapper, battery_backed)
+    }
+
+    #[test]
+    fn ines_eeprom_detection() {
+        // iNES mapper 016 without the battery flag set or a known CRC, e.g. FCG-1/2 boards
+        let (mapper, battery_backed) = load(0x00);
+        assert!(mapper.standard_eeprom.is_none(), "no EEPROM by default");
+        assert!(!battery_backed);
+
+        let (mapper, battery_backed) = load(0x02);
+        assert!(mapper.standard_eeprom.is_some(), "battery flag adds 24C02");
+        assert!(battery_backed);
+    }
+
+    #[test]
+    fn eeprom_saves() {
+        let (mut mapper, _) = load(0x02);
+        let eeprom = mapper.standard_eeprom.as_mut().expect("24C02 EEPROM");
+        eeprom.rom_data[0x10] = 0x42;
+
+        let dir = std::env::temp_dir().join(format!("tetanes-eeprom-{}", std::process::id()));
+        let path = dir.join("bandai_fcg.sram");
+        let mut eeprom = Eeprom::new(EepromModel::X24C02);
+        eeprom.load(&path).expect("missing EEPROM save is skipped");
+        mapper.save(&path).expect("save EEPROM");
+        eeprom.load(&path).expect("load EEPROM");
+        assert_eq!(eeprom.rom_data[0x10], 0x42);
+        fs::clear_dir(&dir).expect("clear dir");
     }
 }
@@ -8,9 +8,11 @@ use crate::{
     mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
     ppu::Mirroring,
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Nrom {
     pub mirroring: Mirroring,
@@ -10,9 +10,11 @@ use crate::{
     mem::Banks,
     ppu::Mirroring,
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum Revision {
     /// MMC1 Revision A
@@ -22,7 +24,8 @@ pub enum Revision {
     BC,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Regs {
     write_just_occurred: u8,
@@ -33,7 +36,8 @@ pub struct Regs {
     prg: u8,            // $E000-$FFFF
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Sxrom {
     pub regs: Regs,
@@ -277,12 +281,12 @@ impl MemMap for Sxrom {
 
 impl Reset for Sxrom {
     fn reset(&mut self, kind: ResetKind) {
-        self.regs.shift_register = Self::DEFAULT_SHIFT_REGISTER;
-        self.regs.control = Self::DEFAULT_PRG_MODE;
-        self.regs.prg = Self::PRG_RAM_DISABLED;
-        self.update_banks(0x0000);
         if kind == ResetKind::Hard {
+            self.regs.shift_register = Self::DEFAULT_SHIFT_REGISTER;
+            self.regs.control = Self::DEFAULT_PRG_MODE;
+            self.regs.prg = Self::PRG_RAM_DISABLED;
             self.regs.write_just_occurred = 0;
+            self.update_banks(0x0000);
         }
     }
 }
@@ -9,9 +9,11 @@ use crate::{
     mem::Banks,
     ppu::Mirroring,
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Uxrom {
     pub mirroring: Mirroring,
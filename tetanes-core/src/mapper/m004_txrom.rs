@@ -11,38 +11,13 @@ use crate::{
     mem::Banks,
     ppu::Mirroring,
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-// MMC3 Revision
-// See:<http://forums.nesdev.com/viewtopic.php?p=62546#p62546>
-//
-// Known Revisions:
-//
-// Conquest of the Crystal Palace (MMC3B S 9039 1 DB)
-// Kickle Cubicle (MMC3B S 9031 3 DA)
-// M.C. Kids (MMC3B S 9152 3 AB)
-// Mega Man 3 (MMC3B S 9046 1 DB)
-// Super Mario Bros. 3 (MMC3B S 9027 5 A)
-// Startropics (MMC6B P 03'5)
-// Batman (MMC3B 9006KP006)
-// Golgo 13: The Mafat Conspiracy (MMC3B 9016KP051)
-// Crystalis (MMC3B 9024KPO53)
-// Legacy of the Wizard (MMC3A 8940EP)
-//
-// Only major difference is the IRQ counter
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[must_use]
-pub enum Revision {
-    /// MMC3 Revision A
-    A,
-    /// MMC3 Revisions B & C
-    #[default]
-    BC,
-    /// Acclaims MMC3 clone - clocks on falling edge
-    Acc,
-}
+pub use crate::mapper::Mmc3Revision as Revision;
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Regs {
     pub bank_select: u8,
@@ -54,7 +29,8 @@ pub struct Regs {
     pub last_clock: u16,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Txrom {
     pub regs: Regs,
@@ -318,9 +294,11 @@ impl MemMap for Txrom {
 }
 
 impl Reset for Txrom {
-    fn reset(&mut self, _kind: ResetKind) {
-        self.regs = Regs::default();
-        self.update_banks();
+    fn reset(&mut self, kind: ResetKind) {
+        if kind == ResetKind::Hard {
+            self.regs = Regs::default();
+            self.update_banks();
+        }
     }
 }
 
@@ -12,15 +12,17 @@ use crate::{
     cart::Cart,
     common::{Clock, NesRegion, Regional, Reset, ResetKind, Sample, Sram},
     cpu::{Cpu, Irq},
+    logging::warn,
     mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
     mem::Banks,
     ppu::{bus::PpuAddr, Mirroring, Ppu},
 };
 use bitflags::bitflags;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use tracing::warn;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum PrgMode {
     Bank32k,
@@ -29,7 +31,8 @@ pub enum PrgMode {
     Bank8k,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum ChrMode {
     Bank8k,
@@ -38,7 +41,8 @@ pub enum ChrMode {
     Bank1k,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum ChrBank {
     Spr,
@@ -46,7 +50,8 @@ pub enum ChrBank {
 }
 
 bitflags! {
-    #[derive(Default, Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+    #[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     #[must_use]
     pub struct ExRamRW: u8 {
         const W = 0x01;
@@ -55,7 +60,8 @@ bitflags! {
     }
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct ExRamMode {
     bits: u8,
@@ -94,7 +100,8 @@ impl ExRamMode {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum Nametable {
     ScreenA,
@@ -103,7 +110,8 @@ pub enum Nametable {
     Fill,
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct NametableMapping {
     pub mode: u8,
@@ -142,7 +150,8 @@ impl NametableMapping {
     }
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Fill {
     pub tile: u8,    // $5106
@@ -164,14 +173,16 @@ impl Fill {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum Side {
     Left,
     Right,
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct VSplit {
     pub mode: u8,      // $5200 [ES.T TTTT]
@@ -203,7 +214,8 @@ impl VSplit {
     }
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Regs {
     pub prg_mode: PrgMode,                   // $5100
@@ -251,7 +263,8 @@ impl Regs {
     }
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct IrqState {
     pub in_frame: bool,
@@ -260,7 +273,8 @@ pub struct IrqState {
     pub pending: bool,
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct PpuStatus {
     pub fetch_count: u32,
@@ -271,7 +285,8 @@ pub struct PpuStatus {
     pub scanline: u16,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Exrom {
     pub regs: Regs,
@@ -999,9 +1014,11 @@ impl MemMap for Exrom {
 }
 
 impl Reset for Exrom {
-    fn reset(&mut self, _kind: ResetKind) {
-        self.regs.prg_mode = PrgMode::Bank8k;
-        self.regs.chr_mode = ChrMode::Bank1k;
+    fn reset(&mut self, kind: ResetKind) {
+        if kind == ResetKind::Hard {
+            self.regs.prg_mode = PrgMode::Bank8k;
+            self.regs.chr_mode = ChrMode::Bank1k;
+        }
     }
 }
 
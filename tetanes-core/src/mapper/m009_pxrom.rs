@@ -8,9 +8,11 @@ use crate::{
     mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap, Mirroring},
     mem::Banks,
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Pxrom {
     pub mirroring: Mirroring,
@@ -127,10 +129,12 @@ impl MemMap for Pxrom {
 }
 
 impl Reset for Pxrom {
-    fn reset(&mut self, _kind: ResetKind) {
-        self.latch = [0x00; 2];
-        self.latch_banks = [0x00; 4];
-        self.update_banks();
+    fn reset(&mut self, kind: ResetKind) {
+        if kind == ResetKind::Hard {
+            self.latch = [0x00; 2];
+            self.latch_banks = [0x00; 4];
+            self.update_banks();
+        }
     }
 }
 
@@ -8,9 +8,11 @@ use crate::{
     mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap, Mirroring},
     mem::Banks,
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct ColorDreams {
     pub mirroring: Mirroring,
@@ -10,6 +10,7 @@ use crate::{
     mem::{BankAccess, Banks},
     ppu::Mirroring,
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug)]
@@ -39,7 +40,8 @@ impl From<u16> for PageBits {
     }
 }
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Regs {
     pub irq_enabled: bool,
@@ -47,7 +49,8 @@ pub struct Regs {
     pub irq_counter_size: u8,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct JalecoSs88006 {
     pub regs: Regs,
@@ -202,8 +205,8 @@ impl MemMap for JalecoSs88006 {
 
 impl Reset for JalecoSs88006 {
     fn reset(&mut self, kind: ResetKind) {
-        self.regs = Regs::default();
         if kind == ResetKind::Hard {
+            self.regs = Regs::default();
             self.prg_rom_banks.set(3, self.prg_rom_banks.last());
         }
     }
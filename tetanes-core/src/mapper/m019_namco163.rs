@@ -2,18 +2,21 @@
 //!
 //! <https://www.nesdev.org/wiki/INES_Mapper_019>
 
+#[cfg(feature = "serde")]
+use crate::fs;
 use crate::{
     cart::Cart,
     common::{Clock, Regional, Reset, ResetKind, Sample, Sram},
     cpu::{Cpu, Irq},
-    fs,
     mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
     mem::{BankAccess, Banks, Memory},
     ppu::Mirroring,
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum Board {
     #[default]
@@ -23,7 +26,8 @@ pub enum Board {
     Namco340,
 }
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Regs {
     irq_counter: u16,
@@ -32,7 +36,8 @@ pub struct Regs {
     prg_ram_protect: u8,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Namco163 {
     pub regs: Regs,
@@ -137,7 +142,7 @@ impl Namco163 {
             && (!self.prg_ram_written_to || self.board != Board::Namco340)
             && self.board != board
         {
-            tracing::debug!("auto detecting board: {board:?}");
+            crate::logging::debug!("auto detecting board: {board:?}");
             self.board = board;
         }
     }
@@ -322,15 +327,15 @@ impl Reset for Namco163 {
     fn reset(&mut self, kind: ResetKind) {
         if kind == ResetKind::Hard {
             self.regs = Regs::default();
+            for bank in 8..12 {
+                self.nt_bank_enable[bank] = true;
+                self.chr_banks.set(bank, ((bank - 8) * 0x0400) & 0x03FF);
+            }
+            self.prg_ram_written_to = false;
+            self.prg_rom_banks.set(3, self.prg_rom_banks.last());
+            self.update_prg_ram_access();
+            self.audio = Audio::new();
         }
-        for bank in 8..12 {
-            self.nt_bank_enable[bank] = true;
-            self.chr_banks.set(bank, ((bank - 8) * 0x0400) & 0x03FF);
-        }
-        self.prg_ram_written_to = false;
-        self.prg_rom_banks.set(3, self.prg_rom_banks.last());
-        self.update_prg_ram_access();
-        self.audio = Audio::new();
     }
 }
 
@@ -356,12 +361,15 @@ impl Clock for Namco163 {
 impl Regional for Namco163 {}
 
 impl Sram for Namco163 {
+    #[cfg(feature = "serde")]
     fn save(&self, path: impl AsRef<std::path::Path>) -> fs::Result<()> {
         fs::save(path.as_ref().with_extension("ciram"), &self.audio.ram)
     }
 
+    #[cfg(feature = "serde")]
     fn load(&mut self, path: impl AsRef<std::path::Path>) -> fs::Result<()> {
-        fs::load(path.as_ref().with_extension("ciram")).map(|data| self.audio.ram = data)
+        fs::load_any_version(path.as_ref().with_extension("ciram"))
+            .map(|data| self.audio.ram = data)
     }
 }
 
@@ -371,7 +379,8 @@ impl Sample for Namco163 {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Audio {
     ram: Memory,
@@ -10,9 +10,11 @@ use crate::{
     mem::Banks,
     ppu::Mirroring,
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum Revision {
     /// VRC6a
@@ -22,7 +24,8 @@ pub enum Revision {
     B,
 }
 
-#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Regs {
     pub banking_mode: u8,
@@ -30,7 +33,8 @@ pub struct Regs {
     pub chr: [usize; 8],
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Vrc6 {
     pub regs: Regs,
@@ -325,8 +329,10 @@ impl MemMap for Vrc6 {
 
 impl Reset for Vrc6 {
     fn reset(&mut self, kind: ResetKind) {
-        self.irq.reset(kind);
-        self.audio.reset(kind);
+        if kind == ResetKind::Hard {
+            self.irq.reset(kind);
+            self.audio.reset(kind);
+        }
     }
 }
 
@@ -348,7 +354,8 @@ impl Sample for Vrc6 {
     }
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Audio {
     pub pulse1: Pulse,
@@ -424,7 +431,8 @@ impl Reset for Audio {
     }
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Pulse {
     enabled: bool,
@@ -503,7 +511,8 @@ impl Clock for Pulse {
     }
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Saw {
     enabled: bool,
@@ -0,0 +1,435 @@
+//! `Tengen RAMBO-1` (Mapper 064) and `Tengen 800037` (Mapper 158)
+//!
+//! <https://www.nesdev.org/wiki/RAMBO-1>
+//! <https://www.nesdev.org/wiki/INES_Mapper_158>
+
+use crate::{
+    cart::Cart,
+    common::{Clock, Regional, Reset, ResetKind, Sram},
+    cpu::{Cpu, Irq},
+    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mem::Banks,
+    ppu::Mirroring,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub struct Regs {
+    pub bank_select: u8,
+    pub bank_values: [u8; 16],
+    pub irq_latch: u8,
+    pub irq_counter: u8,
+    pub irq_enabled: bool,
+    pub irq_reload: bool,
+    /// Clock the IRQ counter every 4 CPU cycles instead of on PPU A12 rises.
+    pub irq_cycle_mode: bool,
+    pub irq_prescaler: u8,
+    /// CPU cycles left until a pending IRQ is asserted.
+    pub irq_delay: u8,
+    pub last_clock: u16,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub struct Rambo1 {
+    pub regs: Regs,
+    pub mirroring: Mirroring,
+    pub mapper_num: u16,
+    /// CIRAM page for each nametable, selected by CHR bank bit 7 on mapper 158.
+    pub nametables: [u8; 4],
+    pub chr_banks: Banks,
+    pub prg_rom_banks: Banks,
+}
+
+impl Rambo1 {
+    const PRG_WINDOW: usize = 8 * 1024;
+    const CHR_WINDOW: usize = 1024;
+    const CHR_RAM_SIZE: usize = 8 * 1024;
+
+    const CHR_INVERSION_MASK: u8 = 0x80; // Bit 7 of bank select
+    const PRG_MODE_MASK: u8 = 0x40; // Bit 6 of bank select
+    const CHR_1K_MASK: u8 = 0x20; // Bit 5 of bank select
+
+    /// CPU cycles between the IRQ counter reaching zero and the IRQ being asserted, which Klax
+    /// and Skull & Crossbones rely on to avoid flickering split screens.
+    const A12_IRQ_DELAY: u8 = 2;
+    const CPU_IRQ_DELAY: u8 = 1;
+
+    pub fn load(cart: &mut Cart) -> Result<Mapper, mapper::Error> {
+        let chr_len = if cart.has_chr_rom() {
+            cart.chr_rom.len()
+        } else {
+            if cart.chr_ram.is_empty() {
+                cart.add_chr_ram(Self::CHR_RAM_SIZE);
+            }
+            cart.chr_ram.len()
+        };
+        let mut rambo1 = Self {
+            regs: Regs::default(),
+            mirroring: cart.mirroring(),
+            mapper_num: cart.mapper_num(),
+            nametables: [0, 0, 1, 1],
+            chr_banks: Banks::new(0x0000, 0x1FFF, chr_len, Self::CHR_WINDOW)?,
+            prg_rom_banks: Banks::new(0x8000, 0xFFFF, cart.prg_rom.len(), Self::PRG_WINDOW)?,
+        };
+        rambo1.update_banks();
+        Ok(rambo1.into())
+    }
+
+    pub fn update_prg_banks(&mut self) {
+        let prg = self.regs.bank_values;
+        let (r6, r7, rf) = (prg[6].into(), prg[7].into(), prg[15].into());
+        if self.regs.bank_select & Self::PRG_MODE_MASK == Self::PRG_MODE_MASK {
+            self.prg_rom_banks.set(0, rf);
+            self.prg_rom_banks.set(1, r6);
+            self.prg_rom_banks.set(2, r7);
+        } else {
+            self.prg_rom_banks.set(0, r6);
+            self.prg_rom_banks.set(1, r7);
+            self.prg_rom_banks.set(2, rf);
+        }
+        self.prg_rom_banks.set(3, self.prg_rom_banks.last());
+    }
+
+    pub fn update_chr_banks(&mut self) {
+        let chr = self.regs.bank_values;
+        // 1: four 1K banks from R0, R8, R1 and R9 instead of two 2K banks from R0 and R1
+        let left = if self.regs.bank_select & Self::CHR_1K_MASK == Self::CHR_1K_MASK {
+            [chr[0], chr[8], chr[1], chr[9]]
+        } else {
+            [chr[0] & 0xFE, chr[0] | 0x01, chr[1] & 0xFE, chr[1] | 0x01]
+        };
+        let right = [chr[2], chr[3], chr[4], chr[5]];
+        // 1: R2-R5 at $0000-$0FFF, R0/R1 (or R0/R8/R1/R9) at $1000-$1FFF
+        let banks = if self.regs.bank_select & Self::CHR_INVERSION_MASK == Self::CHR_INVERSION_MASK
+        {
+            [right, left]
+        } else {
+            [left, right]
+        };
+        for (slot, bank) in banks.into_iter().flatten().enumerate() {
+            self.chr_banks.set(slot, bank.into());
+        }
+        if self.mapper_num == 158 {
+            // Nametables follow bit 7 of the 1K CHR banks at $0000-$0FFF
+            for (nametable, bank) in self.nametables.iter_mut().zip(banks[0]) {
+                *nametable = bank >> 7;
+            }
+        }
+    }
+
+    pub fn update_banks(&mut self) {
+        self.update_prg_banks();
+        self.update_chr_banks();
+    }
+
+    fn clock_irq_counter(&mut self, delay: u8) {
+        if self.regs.irq_reload {
+            // Reloading after a write to $C001 counts one more clock than MMC3 for latch values
+            // above 1, needed by Hard Drivin'
+            self.regs.irq_counter = if self.regs.irq_latch <= 1 {
+                self.regs.irq_latch + 1
+            } else {
+                self.regs.irq_latch.wrapping_add(2)
+            };
+            self.regs.irq_reload = false;
+        } else if self.regs.irq_counter == 0 {
+            self.regs.irq_counter = self.regs.irq_latch.wrapping_add(1);
+        }
+        self.regs.irq_counter = self.regs.irq_counter.wrapping_sub(1);
+        if self.regs.irq_counter == 0 && self.regs.irq_enabled {
+            self.regs.irq_delay = delay;
+        }
+    }
+
+    pub fn clock_irq(&mut self, addr: u16) {
+        if addr < 0x2000 {
+            let next_clock = (addr >> 12) & 1;
+            if !self.regs.irq_cycle_mode && self.regs.last_clock == 0 && next_clock == 1 {
+                self.clock_irq_counter(Self::A12_IRQ_DELAY);
+            }
+            self.regs.last_clock = next_clock;
+        }
+    }
+}
+
+impl Mapped for Rambo1 {
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn set_mirroring(&mut self, mirroring: Mirroring) {
+        self.mirroring = mirroring;
+    }
+
+    fn ppu_bus_read(&mut self, addr: u16) {
+        self.clock_irq(addr);
+    }
+
+    fn ppu_bus_write(&mut self, addr: u16, _val: u8) {
+        self.clock_irq(addr);
+    }
+}
+
+impl MemMap for Rambo1 {
+    // PPU $0000..=$07FF (or $1000..=$17FF) 2K CHR-ROM/RAM Bank 1 Switchable (or two 1K banks)
+    // PPU $0800..=$0FFF (or $1800..=$1FFF) 2K CHR-ROM/RAM Bank 2 Switchable (or two 1K banks)
+    // PPU $1000..=$13FF (or $0000..=$03FF) 1K CHR-ROM/RAM Bank 3 Switchable
+    // PPU $1400..=$17FF (or $0400..=$07FF) 1K CHR-ROM/RAM Bank 4 Switchable
+    // PPU $1800..=$1BFF (or $0800..=$0BFF) 1K CHR-ROM/RAM Bank 5 Switchable
+    // PPU $1C00..=$1FFF (or $0C00..=$0FFF) 1K CHR-ROM/RAM Bank 6 Switchable
+    // PPU $2000..=$3EFF Nametables selected by CHR banks (Mapper 158)
+
+    // CPU $8000..=$9FFF (or $A000..=$BFFF) 8K PRG-ROM Bank 1 Switchable
+    // CPU $A000..=$BFFF (or $C000..=$DFFF) 8K PRG-ROM Bank 2 Switchable
+    // CPU $C000..=$DFFF (or $8000..=$9FFF) 8K PRG-ROM Bank 3 Switchable
+    // CPU $E000..=$FFFF 8K PRG-ROM Bank 4 Fixed to Last
+
+    fn map_read(&mut self, addr: u16) -> MappedRead {
+        self.clock_irq(addr);
+        self.map_peek(addr)
+    }
+
+    fn map_peek(&self, addr: u16) -> MappedRead {
+        match addr {
+            0x0000..=0x1FFF => MappedRead::Chr(self.chr_banks.translate(addr)),
+            0x2000..=0x3EFF if self.mapper_num == 158 => {
+                let nametable = self.nametables[usize::from((addr >> 10) & 0x03)];
+                MappedRead::CIRam((usize::from(nametable) << 10) | usize::from(addr & 0x03FF))
+            }
+            0x8000..=0xFFFF => MappedRead::PrgRom(self.prg_rom_banks.translate(addr)),
+            _ => MappedRead::Bus,
+        }
+    }
+
+    fn map_write(&mut self, addr: u16, val: u8) -> MappedWrite {
+        match addr {
+            0x0000..=0x1FFF => MappedWrite::ChrRam(self.chr_banks.translate(addr), val),
+            0x2000..=0x3EFF if self.mapper_num == 158 => {
+                let nametable = self.nametables[usize::from((addr >> 10) & 0x03)];
+                MappedWrite::CIRam(
+                    (usize::from(nametable) << 10) | usize::from(addr & 0x03FF),
+                    val,
+                )
+            }
+            0x8000..=0xFFFF => {
+                //  7654 3210
+                // `CPKx RRRR`
+                //  |||  ++++- Specify which bank register to update on next write to Bank Data register
+                //  |||        0: Select 2K (or 1K) CHR bank at PPU $0000 (or $1000);
+                //  |||        1: Select 2K (or 1K) CHR bank at PPU $0800 (or $1800);
+                //  |||        2-5: Select 1K CHR bank at PPU $1000-$1FFF (or $0000-$0FFF);
+                //  |||        6: Select 8K PRG-ROM bank at $8000-$9FFF (or $A000-$BFFF);
+                //  |||        7: Select 8K PRG-ROM bank at $A000-$BFFF (or $C000-$DFFF);
+                //  |||        8: Select 1K CHR bank at PPU $0400 (or $1400) if K is set;
+                //  |||        9: Select 1K CHR bank at PPU $0C00 (or $1C00) if K is set;
+                //  |||        F: Select 8K PRG-ROM bank at $C000-$DFFF (or $8000-$9FFF)
+                //  ||+------- Full 1K CHR bank mode
+                //  |+-------- PRG-ROM bank mode
+                //  +--------- CHR A12 inversion
+
+                // Match only $8000/1, $A000/1, $C000/1, and $E000/1
+                match addr & 0xE001 {
+                    0x8000 => {
+                        self.regs.bank_select = val;
+                        self.update_banks();
+                    }
+                    0x8001 => {
+                        let bank = self.regs.bank_select & 0x0F;
+                        self.regs.bank_values[usize::from(bank)] = val;
+                        self.update_banks();
+                    }
+                    0xA000 => {
+                        // Mapper 158 ignores mirroring in favor of CHR bank nametable selection
+                        if self.mapper_num != 158 {
+                            self.set_mirroring(match val & 0x01 {
+                                0 => Mirroring::Vertical,
+                                1 => Mirroring::Horizontal,
+                                _ => unreachable!("impossible mirroring"),
+                            });
+                        }
+                    }
+                    0xA001 => (),
+                    // IRQ
+                    0xC000 => self.regs.irq_latch = val,
+                    0xC001 => {
+                        self.regs.irq_cycle_mode = val & 0x01 == 0x01;
+                        if self.regs.irq_cycle_mode {
+                            self.regs.irq_prescaler = 0;
+                        }
+                        self.regs.irq_reload = true;
+                    }
+                    0xE000 => {
+                        Cpu::clear_irq(Irq::MAPPER);
+                        self.regs.irq_enabled = false;
+                        self.regs.irq_delay = 0;
+                    }
+                    0xE001 => self.regs.irq_enabled = true,
+                    _ => unreachable!("impossible address"),
+                }
+                MappedWrite::Bus
+            }
+            _ => MappedWrite::Bus,
+        }
+    }
+}
+
+impl Reset for Rambo1 {
+    fn reset(&mut self, kind: ResetKind) {
+        if kind == ResetKind::Hard {
+            self.regs = Regs::default();
+            self.update_banks();
+        }
+    }
+}
+
+impl Clock for Rambo1 {
+    fn clock(&mut self) -> usize {
+        if self.regs.irq_delay > 0 {
+            self.regs.irq_delay -= 1;
+            if self.regs.irq_delay == 0 {
+                Cpu::set_irq(Irq::MAPPER);
+            }
+        }
+        if self.regs.irq_cycle_mode {
+            self.regs.irq_prescaler = (self.regs.irq_prescaler + 1) & 0x03;
+            if self.regs.irq_prescaler == 0 {
+                self.clock_irq_counter(Self::CPU_IRQ_DELAY);
+            }
+            1
+        } else {
+            0
+        }
+    }
+}
+
+impl Regional for Rambo1 {}
+impl Sram for Rambo1 {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::RamState;
+
+    fn load(mapper_num: u16) -> Rambo1 {
+        let rom = Cart::test_rom(mapper_num, 0, 2, 1, 0x00);
+        let cart =
+            Cart::from_rom("rambo1", &mut rom.as_slice(), RamState::AllZeros).expect("valid cart");
+        let Mapper::Rambo1(mapper) = cart.mapper else {
+            panic!("expected RAMBO-1 mapper");
+        };
+        Cpu::clear_irq(Irq::MAPPER);
+        mapper
+    }
+
+    fn a12_rise(mapper: &mut Rambo1) {
+        mapper.ppu_bus_read(0x0000);
+        mapper.ppu_bus_read(0x1000);
+    }
+
+    fn clock(mapper: &mut Rambo1, cycles: usize) {
+        for _ in 0..cycles {
+            mapper.clock();
+        }
+    }
+
+    #[test]
+    fn scanline_irq() {
+        let mut mapper = load(64);
+        let _ = mapper.map_write(0xC000, 2);
+        let _ = mapper.map_write(0xC001, 0x00);
+        let _ = mapper.map_write(0xE001, 0x00);
+
+        // Reloading after $C001 takes one more scanline than MMC3
+        for _ in 0..3 {
+            a12_rise(&mut mapper);
+            clock(&mut mapper, 113);
+            assert!(!Cpu::has_irq(Irq::MAPPER));
+        }
+        a12_rise(&mut mapper);
+        clock(&mut mapper, 1);
+        assert!(!Cpu::has_irq(Irq::MAPPER), "IRQ is delayed");
+        clock(&mut mapper, 1);
+        assert!(Cpu::has_irq(Irq::MAPPER));
+
+        let _ = mapper.map_write(0xE000, 0x00);
+        assert!(!Cpu::has_irq(Irq::MAPPER), "IRQ acknowledged");
+        let _ = mapper.map_write(0xE001, 0x00);
+
+        // Reloading after reaching zero fires every latch + 1 scanlines
+        for _ in 0..2 {
+            a12_rise(&mut mapper);
+            clock(&mut mapper, 113);
+            assert!(!Cpu::has_irq(Irq::MAPPER));
+        }
+        a12_rise(&mut mapper);
+        clock(&mut mapper, 2);
+        assert!(Cpu::has_irq(Irq::MAPPER));
+    }
+
+    #[test]
+    fn scanline_irq_ignores_cpu_cycles() {
+        let mut mapper = load(64);
+        let _ = mapper.map_write(0xC000, 0);
+        let _ = mapper.map_write(0xC001, 0x00);
+        let _ = mapper.map_write(0xE001, 0x00);
+        clock(&mut mapper, 1000);
+        assert!(!Cpu::has_irq(Irq::MAPPER));
+    }
+
+    #[test]
+    fn cycle_irq() {
+        let mut mapper = load(64);
+        let _ = mapper.map_write(0xC000, 3);
+        let _ = mapper.map_write(0xC001, 0x01);
+        let _ = mapper.map_write(0xE001, 0x00);
+
+        // Counter is clocked every 4 CPU cycles, reloading to latch + 2 and asserting the IRQ a
+        // cycle after reaching zero
+        clock(&mut mapper, 5 * 4);
+        assert!(!Cpu::has_irq(Irq::MAPPER));
+        clock(&mut mapper, 1);
+        assert!(Cpu::has_irq(Irq::MAPPER));
+        let _ = mapper.map_write(0xE000, 0x00);
+        let _ = mapper.map_write(0xE001, 0x00);
+
+        // A12 rises don't clock the counter in cycle mode
+        for _ in 0..8 {
+            a12_rise(&mut mapper);
+        }
+        clock(&mut mapper, 4 * 4 - 1);
+        assert!(!Cpu::has_irq(Irq::MAPPER));
+        clock(&mut mapper, 1);
+        assert!(Cpu::has_irq(Irq::MAPPER));
+    }
+
+    #[test]
+    fn cycle_irq_disabled() {
+        let mut mapper = load(64);
+        let _ = mapper.map_write(0xC000, 3);
+        let _ = mapper.map_write(0xC001, 0x01);
+        clock(&mut mapper, 100);
+        assert!(!Cpu::has_irq(Irq::MAPPER));
+    }
+
+    #[test]
+    fn mapper_158_nametables() {
+        let mut mapper = load(158);
+        // R0 = $80, R1 = $00 with 2K CHR banks
+        let _ = mapper.map_write(0x8000, 0x00);
+        let _ = mapper.map_write(0x8001, 0x80);
+        let _ = mapper.map_write(0x8000, 0x01);
+        let _ = mapper.map_write(0x8001, 0x00);
+        assert_eq!(mapper.nametables, [1, 1, 0, 0]);
+        assert!(matches!(mapper.map_peek(0x2000), MappedRead::CIRam(0x0400)));
+        assert!(matches!(mapper.map_peek(0x2C10), MappedRead::CIRam(0x0010)));
+
+        // Mirroring register is ignored
+        let _ = mapper.map_write(0xA000, 0x01);
+        assert!(matches!(mapper.map_peek(0x2400), MappedRead::CIRam(0x0400)));
+    }
+}
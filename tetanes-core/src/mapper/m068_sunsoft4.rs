@@ -0,0 +1,254 @@
+//! `Sunsoft-4` (Mapper 068)
+//!
+//! <https://www.nesdev.org/wiki/INES_Mapper_068>
+
+use crate::{
+    cart::Cart,
+    common::{Clock, Regional, Reset, ResetKind, Sram},
+    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mem::Banks,
+    ppu::{bus::Bus as PpuBus, Mirroring},
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub struct Regs {
+    pub prg_ram_enabled: bool,
+    /// Use CHR-ROM pages for nametables instead of CIRAM.
+    pub chr_nametables: bool,
+    /// Whether the PRG-ROM bank is from the external option ROM.
+    pub external_rom: bool,
+    /// CPU cycles left before the licensing IC disables the external option ROM.
+    pub licensing_timer: u32,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub struct Sunsoft4 {
+    pub regs: Regs,
+    pub mirroring: Mirroring,
+    pub chr_banks: Banks,
+    /// 1K CHR-ROM pages used for nametables A and B.
+    pub nametable_banks: Banks,
+    pub prg_ram_banks: Banks,
+    pub prg_rom_banks: Banks,
+}
+
+impl Sunsoft4 {
+    const PRG_WINDOW: usize = 16 * 1024;
+    const CHR_WINDOW: usize = 2 * 1024;
+    const NAMETABLE_WINDOW: usize = 1024;
+    const PRG_RAM_SIZE: usize = 8 * 1024;
+
+    /// Number of internal 16K PRG-ROM banks, with any banks after them being from an external
+    /// option ROM, e.g. Nantettatte!! Baseball.
+    const INTERNAL_PRG_BANKS: usize = 8;
+    /// CPU cycles the external option ROM stays enabled after a write to $6000-$7FFF.
+    const LICENSING_CYCLES: u32 = 1024 * 105;
+
+    pub fn load(cart: &mut Cart) -> Result<Mapper, mapper::Error> {
+        cart.add_prg_ram(Self::PRG_RAM_SIZE);
+        let mut sunsoft4 = Self {
+            regs: Regs::default(),
+            mirroring: cart.mirroring(),
+            chr_banks: Banks::new(0x0000, 0x1FFF, cart.chr_rom.len(), Self::CHR_WINDOW)?,
+            nametable_banks: Banks::new(
+                0x0000,
+                0x07FF,
+                cart.chr_rom.len(),
+                Self::NAMETABLE_WINDOW,
+            )?,
+            prg_ram_banks: Banks::new(0x6000, 0x7FFF, cart.prg_ram.len(), Self::PRG_RAM_SIZE)?,
+            prg_rom_banks: Banks::new(0x8000, 0xFFFF, cart.prg_rom.len(), Self::PRG_WINDOW)?,
+        };
+        let last_bank = sunsoft4
+            .prg_rom_banks
+            .last()
+            .min(Self::INTERNAL_PRG_BANKS - 1);
+        sunsoft4.prg_rom_banks.set(1, last_bank);
+        Ok(sunsoft4.into())
+    }
+
+    const fn has_external_rom(&self) -> bool {
+        self.prg_rom_banks.page_count() > Self::INTERNAL_PRG_BANKS
+    }
+
+    /// CHR-ROM address for a nametable address, using the page for the nametable selected by
+    /// mirroring.
+    fn nametable_addr(&self, addr: u16) -> usize {
+        let nametable = PpuBus::ciram_mirror(addr, self.mirroring) >> 10;
+        self.nametable_banks.page_offset(nametable) | usize::from(addr & 0x03FF)
+    }
+}
+
+impl Mapped for Sunsoft4 {
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn set_mirroring(&mut self, mirroring: Mirroring) {
+        self.mirroring = mirroring;
+    }
+}
+
+impl MemMap for Sunsoft4 {
+    // PPU $0000..=$07FF 2K CHR-ROM Bank 1 Switchable
+    // PPU $0800..=$0FFF 2K CHR-ROM Bank 2 Switchable
+    // PPU $1000..=$17FF 2K CHR-ROM Bank 3 Switchable
+    // PPU $1800..=$1FFF 2K CHR-ROM Bank 4 Switchable
+    // PPU $2000..=$3EFF CIRAM or 1K CHR-ROM Nametables A/B Switchable
+
+    // CPU $6000..=$7FFF 8K PRG-RAM Bank (optional)
+    // CPU $8000..=$BFFF 16K PRG-ROM Bank Switchable (or external option ROM)
+    // CPU $C000..=$FFFF 16K PRG-ROM Bank Fixed to Last
+
+    fn map_peek(&self, addr: u16) -> MappedRead {
+        match addr {
+            0x0000..=0x1FFF => MappedRead::Chr(self.chr_banks.translate(addr)),
+            0x2000..=0x3EFF if self.regs.chr_nametables => {
+                MappedRead::Chr(self.nametable_addr(addr))
+            }
+            0x6000..=0x7FFF if self.regs.prg_ram_enabled => {
+                MappedRead::PrgRam(self.prg_ram_banks.translate(addr))
+            }
+            // The external option ROM is only readable while the licensing IC is enabled
+            0x8000..=0xBFFF if self.regs.external_rom && self.regs.licensing_timer == 0 => {
+                MappedRead::Bus
+            }
+            0x8000..=0xFFFF => MappedRead::PrgRom(self.prg_rom_banks.translate(addr)),
+            _ => MappedRead::Bus,
+        }
+    }
+
+    fn map_write(&mut self, addr: u16, val: u8) -> MappedWrite {
+        match addr {
+            0x2000..=0x3EFF if self.regs.chr_nametables => return MappedWrite::None,
+            0x6000..=0x7FFF => {
+                self.regs.licensing_timer = Self::LICENSING_CYCLES;
+                if self.regs.prg_ram_enabled {
+                    return MappedWrite::PrgRam(self.prg_ram_banks.translate(addr), val);
+                }
+                return MappedWrite::None;
+            }
+            0x8000..=0xBFFF => {
+                let bank = usize::from((addr >> 12) & 0x03);
+                self.chr_banks.set(bank, val.into());
+            }
+            // Nametable pages are always from the upper 128K of CHR-ROM
+            0xC000..=0xCFFF => self.nametable_banks.set(0, (val | 0x80).into()),
+            0xD000..=0xDFFF => self.nametable_banks.set(1, (val | 0x80).into()),
+            0xE000..=0xEFFF => {
+                //  7654 3210
+                // `xxxN xxMM`
+                //     |   ++- Mirroring (0: Vertical, 1: Horizontal, 2: Single Screen A,
+                //     |                  3: Single Screen B)
+                //     +------ Nametables (0: CIRAM, 1: CHR-ROM)
+                self.set_mirroring(match val & 0x03 {
+                    0 => Mirroring::Vertical,
+                    1 => Mirroring::Horizontal,
+                    2 => Mirroring::SingleScreenA,
+                    _ => Mirroring::SingleScreenB,
+                });
+                self.regs.chr_nametables = val & 0x10 == 0x10;
+            }
+            0xF000..=0xFFFF => {
+                //  7654 3210
+                // `xxxR IPPP`
+                //     | |+++- Select 16K PRG-ROM bank at $8000-$BFFF
+                //     | +---- PRG-ROM (0: External option ROM, 1: Internal) if present
+                //     +------ PRG-RAM enable
+                self.regs.prg_ram_enabled = val & 0x10 == 0x10;
+                self.regs.external_rom = self.has_external_rom() && val & 0x08 == 0x00;
+                let bank = if self.regs.external_rom {
+                    let external_banks = self.prg_rom_banks.page_count() - Self::INTERNAL_PRG_BANKS;
+                    Self::INTERNAL_PRG_BANKS + usize::from(val & 0x07) % external_banks
+                } else if self.has_external_rom() {
+                    usize::from(val & 0x07)
+                } else {
+                    usize::from(val & 0x0F)
+                };
+                self.prg_rom_banks.set(0, bank);
+            }
+            _ => return MappedWrite::Bus,
+        }
+        MappedWrite::None
+    }
+}
+
+impl Reset for Sunsoft4 {
+    fn reset(&mut self, kind: ResetKind) {
+        if kind == ResetKind::Hard {
+            self.regs = Regs::default();
+        }
+    }
+}
+
+impl Clock for Sunsoft4 {
+    fn clock(&mut self) -> usize {
+        if self.regs.licensing_timer > 0 {
+            self.regs.licensing_timer -= 1;
+            1
+        } else {
+            0
+        }
+    }
+}
+
+impl Regional for Sunsoft4 {}
+impl Sram for Sunsoft4 {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::RamState;
+
+    fn load(prg_banks: u8) -> Sunsoft4 {
+        let rom = Cart::test_rom(68, 0, prg_banks, 0x20, 0x00);
+        let cart = Cart::from_rom("sunsoft4", &mut rom.as_slice(), RamState::AllZeros)
+            .expect("valid cart");
+        let Mapper::Sunsoft4(mapper) = cart.mapper else {
+            panic!("expected Sunsoft-4 mapper");
+        };
+        mapper
+    }
+
+    #[test]
+    fn chr_nametables() {
+        let mut mapper = load(8);
+        assert_eq!(mapper.map_peek(0x2000), MappedRead::Bus);
+
+        let _ = mapper.map_write(0xC000, 0x00);
+        let _ = mapper.map_write(0xD000, 0x01);
+        // Vertical mirroring with CHR-ROM nametables
+        let _ = mapper.map_write(0xE000, 0x10);
+        assert_eq!(mapper.map_peek(0x2005), MappedRead::Chr(0x20005));
+        assert_eq!(mapper.map_peek(0x2405), MappedRead::Chr(0x20405));
+        assert_eq!(mapper.map_peek(0x2805), MappedRead::Chr(0x20005));
+        assert_eq!(mapper.map_write(0x2005, 0xFF), MappedWrite::None);
+
+        // Single screen B
+        let _ = mapper.map_write(0xE000, 0x13);
+        assert_eq!(mapper.map_peek(0x2005), MappedRead::Chr(0x20405));
+    }
+
+    #[test]
+    fn external_rom_licensing() {
+        let mut mapper = load(10);
+        let _ = mapper.map_write(0xF000, 0x01);
+        assert_eq!(mapper.map_peek(0x8000), MappedRead::Bus);
+
+        let _ = mapper.map_write(0x6000, 0x00);
+        assert_eq!(mapper.map_peek(0x8000), MappedRead::PrgRom(0x24000));
+        for _ in 0..Sunsoft4::LICENSING_CYCLES {
+            mapper.clock();
+        }
+        assert_eq!(mapper.map_peek(0x8000), MappedRead::Bus);
+
+        let _ = mapper.map_write(0xF000, 0x09);
+        assert_eq!(mapper.map_peek(0x8000), MappedRead::PrgRom(0x04000));
+    }
+}
@@ -11,27 +11,34 @@ use crate::{
     mem::Banks,
     ppu::Mirroring,
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Regs {
     command: u8,
     parameter: u8,
+    /// Map PRG-RAM instead of PRG-ROM at $6000-$7FFF.
+    prg_ram_selected: bool,
     prg_ram_enabled: bool,
     irq_enabled: bool,
     irq_counter_enabled: bool,
     irq_counter: u16,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct SunsoftFme7 {
     pub regs: Regs,
     pub mirroring: Mirroring,
     pub audio: Audio,
     pub chr_banks: Banks,
-    pub prg_banks: Banks,
+    pub prg_ram_banks: Banks,
+    /// PRG-ROM bank mapped at $6000-$7FFF when PRG-RAM isn't selected.
+    pub prg_rom_6000_bank: Banks,
     pub prg_rom_banks: Banks,
 }
 
@@ -41,13 +48,17 @@ impl SunsoftFme7 {
     const CHR_WINDOW: usize = 1024;
 
     pub fn load(cart: &mut Cart) -> Result<Mapper, mapper::Error> {
-        cart.add_prg_ram(Self::PRG_RAM_SIZE);
+        // Keep the PRG-RAM size from NES 2.0 headers so battery saves match other emulators
+        if !cart.has_prg_ram() {
+            cart.add_prg_ram(Self::PRG_RAM_SIZE);
+        }
         let mut sunsoft_fme7 = Self {
             regs: Regs::default(),
             mirroring: cart.mirroring(),
             audio: Audio::new(),
             chr_banks: Banks::new(0x0000, 0x1FFF, cart.chr_rom.len(), Self::CHR_WINDOW)?,
-            prg_banks: Banks::new(0x6000, 0x7FFF, cart.prg_ram.len(), Self::PRG_WINDOW)?,
+            prg_ram_banks: Banks::new(0x6000, 0x7FFF, cart.prg_ram.len(), Self::PRG_WINDOW)?,
+            prg_rom_6000_bank: Banks::new(0x6000, 0x7FFF, cart.prg_rom.len(), Self::PRG_WINDOW)?,
             prg_rom_banks: Banks::new(0x8000, 0xFFFF, cart.prg_rom.len(), Self::PRG_WINDOW)?,
         };
         sunsoft_fme7
@@ -77,7 +88,7 @@ impl MemMap for SunsoftFme7 {
     // PPU $1800..=$1BFF 1K CHR-ROM Bank 7 Switchable
     // PPU $1C00..=$1FFF 1K CHR-ROM Bank 8 Switchable
 
-    // CPU $6000..=$7FFF 8K PRG-ROM or PRG-RAM Bank 1 Switchable
+    // CPU $6000..=$7FFF 8K PRG-ROM or PRG-RAM Bank 1 Switchable (or open bus if disabled)
     // CPU $8000..=$9FFF 8K PRG-ROM Bank 1 Switchable
     // CPU $A000..=$BFFF 8K PRG-ROM Bank 2 Switchable
     // CPU $C000..=$DFFF 8K PRG-ROM Bank 3 Switchable
@@ -86,13 +97,11 @@ impl MemMap for SunsoftFme7 {
     fn map_peek(&self, addr: u16) -> MappedRead {
         match addr {
             0x0000..=0x1FFF => MappedRead::Chr(self.chr_banks.translate(addr)),
-            0x6000..=0x7FFF => {
-                if self.regs.prg_ram_enabled {
-                    MappedRead::PrgRam(self.prg_banks.translate(addr))
-                } else {
-                    MappedRead::PrgRom(self.prg_banks.translate(addr))
-                }
-            }
+            0x6000..=0x7FFF => match (self.regs.prg_ram_selected, self.regs.prg_ram_enabled) {
+                (false, _) => MappedRead::PrgRom(self.prg_rom_6000_bank.translate(addr)),
+                (true, true) => MappedRead::PrgRam(self.prg_ram_banks.translate(addr)),
+                (true, false) => MappedRead::Bus,
+            },
             0x8000..=0xFFFF => MappedRead::PrgRom(self.prg_rom_banks.translate(addr)),
             _ => MappedRead::Bus,
         }
@@ -101,17 +110,25 @@ impl MemMap for SunsoftFme7 {
     fn map_write(&mut self, addr: u16, val: u8) -> MappedWrite {
         match addr {
             0x6000..=0x7FFF => {
-                if self.regs.prg_ram_enabled {
-                    return MappedWrite::PrgRam(self.prg_banks.translate(addr), val);
+                if self.regs.prg_ram_selected && self.regs.prg_ram_enabled {
+                    return MappedWrite::PrgRam(self.prg_ram_banks.translate(addr), val);
                 }
             }
             0x8000..=0x9FFF => self.regs.command = val & 0x0F,
             0xA000..=0xBFFF => match self.regs.command {
                 0..=7 => self.chr_banks.set(self.regs.command.into(), val.into()),
                 8 => {
+                    //  7654 3210
+                    // `ERBB BBBB`
+                    //  ||++-++++- Select 8K PRG-ROM or PRG-RAM bank at $6000-$7FFF
+                    //  |+-------- 0: PRG-ROM, 1: PRG-RAM
+                    //  +--------- PRG-RAM enable
                     self.regs.parameter = val;
+                    self.regs.prg_ram_selected = val & 0x40 == 0x40;
                     self.regs.prg_ram_enabled = val & 0x80 == 0x80;
-                    self.prg_banks.set(0, (val & 0x3F).into());
+                    let bank = (val & 0x3F).into();
+                    self.prg_ram_banks.set(0, bank);
+                    self.prg_rom_6000_bank.set(0, bank);
                 }
                 9..=0xB => {
                     let bank = self.regs.command - 9;
@@ -124,6 +141,7 @@ impl MemMap for SunsoftFme7 {
                     _ => Mirroring::SingleScreenB,
                 }),
                 0xD => {
+                    // Any write acknowledges a pending IRQ, even if counting continues
                     self.regs.irq_enabled = (val & 0x01) == 0x01;
                     self.regs.irq_counter_enabled = (val & 0x80) == 0x80;
                     Cpu::clear_irq(Irq::MAPPER);
@@ -145,6 +163,8 @@ impl Reset for SunsoftFme7 {}
 
 impl Clock for SunsoftFme7 {
     fn clock(&mut self) -> usize {
+        // The counter decrements every CPU cycle while enabled, even with IRQs disabled, and
+        // fires when wrapping from $0000 to $FFFF
         let cycles = if self.regs.irq_counter_enabled {
             self.regs.irq_counter = self.regs.irq_counter.wrapping_sub(1);
             if self.regs.irq_counter == 0xFFFF && self.regs.irq_enabled {
@@ -169,7 +189,8 @@ impl Sample for SunsoftFme7 {
     }
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Audio {
     clock_timer: u8,
@@ -293,3 +314,92 @@ impl Clock for Audio {
         0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::RamState;
+
+    fn load(flags: u8) -> SunsoftFme7 {
+        let rom = Cart::test_rom(69, 0, 8, 1, flags);
+        let cart = Cart::from_rom("sunsoft_fme7", &mut rom.as_slice(), RamState::AllZeros)
+            .expect("valid cart");
+        let Mapper::SunsoftFme7(mapper) = cart.mapper else {
+            panic!("expected Sunsoft FME-7 mapper");
+        };
+        Cpu::clear_irq(Irq::MAPPER);
+        mapper
+    }
+
+    fn write_register(mapper: &mut SunsoftFme7, command: u8, val: u8) {
+        let _ = mapper.map_write(0x8000, command);
+        let _ = mapper.map_write(0xA000, val);
+    }
+
+    fn clock(mapper: &mut SunsoftFme7, cycles: usize) {
+        for _ in 0..cycles {
+            mapper.clock();
+        }
+    }
+
+    #[test]
+    fn prg_ram_banking() {
+        let mut mapper = load(0x02);
+
+        write_register(&mut mapper, 8, 0x01);
+        assert_eq!(mapper.map_peek(0x6000), MappedRead::PrgRom(0x2000));
+        assert_eq!(mapper.map_write(0x6000, 0xFF), MappedWrite::None);
+
+        // PRG-RAM selected but disabled is open bus
+        write_register(&mut mapper, 8, 0x41);
+        assert_eq!(mapper.map_peek(0x6000), MappedRead::Bus);
+        assert_eq!(mapper.map_write(0x6000, 0xFF), MappedWrite::None);
+
+        write_register(&mut mapper, 8, 0xC1);
+        assert_eq!(mapper.map_peek(0x6000), MappedRead::PrgRam(0x2000));
+        assert_eq!(
+            mapper.map_write(0x6010, 0xFF),
+            MappedWrite::PrgRam(0x2010, 0xFF)
+        );
+        // RAM banks wrap around the 32K of PRG-RAM
+        write_register(&mut mapper, 8, 0xC5);
+        assert_eq!(mapper.map_peek(0x6000), MappedRead::PrgRam(0x2000));
+    }
+
+    #[test]
+    fn irq_counter() {
+        let mut mapper = load(0x00);
+        write_register(&mut mapper, 0xE, 0x02);
+        write_register(&mut mapper, 0xF, 0x00);
+        write_register(&mut mapper, 0xD, 0x81);
+
+        clock(&mut mapper, 2);
+        assert!(!Cpu::has_irq(Irq::MAPPER), "reaching $0000 doesn't fire");
+        clock(&mut mapper, 1);
+        assert!(Cpu::has_irq(Irq::MAPPER), "wrapping to $FFFF fires");
+
+        // Acknowledged with IRQs disabled, but the counter keeps running
+        write_register(&mut mapper, 0xD, 0x80);
+        assert!(!Cpu::has_irq(Irq::MAPPER));
+        clock(&mut mapper, 0x10000);
+        assert!(!Cpu::has_irq(Irq::MAPPER));
+        assert_eq!(mapper.regs.irq_counter, 0xFFFF);
+
+        write_register(&mut mapper, 0xD, 0x81);
+        clock(&mut mapper, 0xFFFF);
+        assert!(!Cpu::has_irq(Irq::MAPPER));
+        clock(&mut mapper, 1);
+        assert!(Cpu::has_irq(Irq::MAPPER), "fires every 65536 cycles");
+    }
+
+    #[test]
+    fn irq_counter_disabled() {
+        let mut mapper = load(0x00);
+        write_register(&mut mapper, 0xE, 0x00);
+        write_register(&mut mapper, 0xF, 0x00);
+        write_register(&mut mapper, 0xD, 0x01);
+        clock(&mut mapper, 10);
+        assert!(!Cpu::has_irq(Irq::MAPPER));
+        assert_eq!(mapper.regs.irq_counter, 0x0000);
+    }
+}
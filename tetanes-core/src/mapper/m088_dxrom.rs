@@ -10,10 +10,12 @@ use crate::{
     mapper::{self, Dxrom206, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
     ppu::Mirroring,
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Dxrom {
     pub inner: Dxrom206,
@@ -0,0 +1,141 @@
+//! `Vs. System` (Mapper 099)
+//!
+//! <https://www.nesdev.org/wiki/INES_Mapper_099>
+
+use crate::{
+    cart::Cart,
+    common::{Clock, Regional, Reset, ResetKind, Sram},
+    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mem::Banks,
+    ppu::Mirroring,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub struct VsSystem {
+    pub mirroring: Mirroring,
+    pub chr_banks: Banks,
+    pub prg_ram_banks: Banks,
+    pub prg_rom_banks: Banks,
+}
+
+impl VsSystem {
+    const PRG_ROM_WINDOW: usize = 8 * 1024;
+    const PRG_RAM_SIZE: usize = 2 * 1024;
+    const CHR_WINDOW: usize = 8 * 1024;
+
+    pub fn load(cart: &mut Cart) -> Result<Mapper, mapper::Error> {
+        if !cart.has_prg_ram() {
+            cart.add_prg_ram(Self::PRG_RAM_SIZE);
+        }
+        if !cart.has_chr_rom() && cart.chr_ram.is_empty() {
+            cart.add_chr_ram(Self::CHR_WINDOW);
+        }
+        let chr_len = if cart.has_chr_rom() {
+            cart.chr_rom.len()
+        } else {
+            cart.chr_ram.len()
+        };
+        let vs_system = Self {
+            mirroring: cart.mirroring(),
+            chr_banks: Banks::new(0x0000, 0x1FFF, chr_len, Self::CHR_WINDOW)?,
+            prg_ram_banks: Banks::new(0x6000, 0x7FFF, cart.prg_ram.len(), Self::PRG_RAM_SIZE)?,
+            prg_rom_banks: Banks::new(0x8000, 0xFFFF, cart.prg_rom.len(), Self::PRG_ROM_WINDOW)?,
+        };
+        Ok(vs_system.into())
+    }
+}
+
+impl Mapped for VsSystem {
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn set_mirroring(&mut self, mirroring: Mirroring) {
+        self.mirroring = mirroring;
+    }
+
+    fn cpu_bus_write(&mut self, addr: u16, val: u8) {
+        //  7654 3210
+        // `xxxx xCxx`
+        //        +--- Select 8K CHR bank and 8K PRG-ROM bank 0 or 4 at $8000-$9FFF
+        if addr == 0x4016 {
+            let bank = usize::from((val >> 2) & 0x01);
+            self.chr_banks.set(0, bank);
+            if self.prg_rom_banks.page_count() > 4 {
+                self.prg_rom_banks.set(0, bank * 4);
+            }
+        }
+    }
+}
+
+impl MemMap for VsSystem {
+    // PPU $0000..=$1FFF 8K CHR Bank Switchable
+
+    // CPU $6000..=$7FFF 2K PRG-RAM Bank, mirrored
+    // CPU $8000..=$9FFF 8K PRG-ROM Bank Switchable for 40K PRG-ROM
+    // CPU $A000..=$FFFF 24K PRG-ROM Bank Fixed
+
+    fn map_peek(&self, addr: u16) -> MappedRead {
+        match addr {
+            0x0000..=0x1FFF => MappedRead::Chr(self.chr_banks.translate(addr)),
+            0x6000..=0x7FFF => MappedRead::PrgRam(self.prg_ram_banks.translate(addr)),
+            0x8000..=0xFFFF => MappedRead::PrgRom(self.prg_rom_banks.translate(addr)),
+            _ => MappedRead::Bus,
+        }
+    }
+
+    fn map_write(&mut self, addr: u16, val: u8) -> MappedWrite {
+        match addr {
+            0x0000..=0x1FFF => MappedWrite::ChrRam(self.chr_banks.translate(addr), val),
+            0x6000..=0x7FFF => MappedWrite::PrgRam(self.prg_ram_banks.translate(addr), val),
+            _ => MappedWrite::Bus,
+        }
+    }
+}
+
+impl Reset for VsSystem {
+    fn reset(&mut self, kind: ResetKind) {
+        if kind == ResetKind::Hard {
+            self.chr_banks.set(0, 0);
+            self.prg_rom_banks.set(0, 0);
+        }
+    }
+}
+
+impl Clock for VsSystem {}
+impl Regional for VsSystem {}
+impl Sram for VsSystem {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::RamState;
+
+    #[test]
+    fn chr_and_prg_banking() {
+        // Vs. System, 48K PRG-ROM, 16K CHR-ROM
+        let rom = Cart::test_rom(99, 0, 3, 2, 0x10);
+        let cart = Cart::from_rom("vs_system", &mut rom.as_slice(), RamState::AllZeros)
+            .expect("valid cart");
+        assert!(cart.is_vs_system());
+        let Mapper::VsSystem(mut mapper) = cart.mapper else {
+            panic!("expected Vs. System mapper");
+        };
+
+        assert_eq!(mapper.map_peek(0x0000), MappedRead::Chr(0x0000));
+        assert_eq!(mapper.map_peek(0x8000), MappedRead::PrgRom(0x0000));
+        assert_eq!(mapper.map_peek(0x6800), MappedRead::PrgRam(0x0000));
+
+        mapper.cpu_bus_write(0x4016, 0x04);
+        assert_eq!(mapper.map_peek(0x0000), MappedRead::Chr(0x2000));
+        assert_eq!(mapper.map_peek(0x8000), MappedRead::PrgRom(0x8000));
+        assert_eq!(mapper.map_peek(0xA000), MappedRead::PrgRom(0x2000));
+
+        mapper.cpu_bus_write(0x4016, 0x00);
+        assert_eq!(mapper.map_peek(0x0000), MappedRead::Chr(0x0000));
+    }
+}
@@ -6,9 +6,11 @@ use crate::{
     common::{Clock, Reset, ResetKind},
     cpu::{Cpu, Irq},
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct VrcIrq {
     pub reload: u8,
@@ -2,6 +2,7 @@
 
 use crate::common::{Reset, ResetKind};
 use rand::Rng;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::{
     fmt,
@@ -12,7 +13,8 @@ use std::{
 
 /// Represents ROM or RAM memory in bytes, with a custom Debug implementation that avoids printing
 /// the entire contents..
-#[derive(Default, Clone, Serialize, Deserialize)]
+#[derive(Default, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Memory(Vec<u8>);
 
@@ -146,7 +148,8 @@ pub trait Mem {
 }
 
 /// RAM [`Memory`] in a given state on startup.
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum RamState {
     #[default]
@@ -211,8 +214,82 @@ impl FromStr for RamState {
     }
 }
 
+/// RAM contents kept across a reset or power cycle. Battery-backed RAM is always kept.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub enum RamRetention {
+    /// Keep RAM on reset and re-initialize it on power cycle, as on real hardware. Some games
+    /// check for values left in RAM to detect a reset.
+    #[default]
+    Hardware,
+    /// Keep RAM on both reset and power cycle.
+    KeepAll,
+    /// Re-initialize RAM on both reset and power cycle.
+    ClearAll,
+}
+
+impl RamRetention {
+    /// Return `RamRetention` options as a slice.
+    pub const fn as_slice() -> &'static [Self] {
+        &[Self::Hardware, Self::KeepAll, Self::ClearAll]
+    }
+
+    /// Return `RamRetention` as a `str`.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Hardware => "hardware",
+            Self::KeepAll => "keep-all",
+            Self::ClearAll => "clear-all",
+        }
+    }
+
+    /// Whether RAM is re-initialized by a reset of `kind`.
+    #[must_use]
+    pub const fn clears(&self, kind: ResetKind) -> bool {
+        match self {
+            Self::Hardware => matches!(kind, ResetKind::Hard),
+            Self::KeepAll => false,
+            Self::ClearAll => true,
+        }
+    }
+}
+
+impl AsRef<str> for RamRetention {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl std::fmt::Display for RamRetention {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Hardware => "Hardware",
+            Self::KeepAll => "Keep All",
+            Self::ClearAll => "Clear All",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl FromStr for RamRetention {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hardware" => Ok(Self::Hardware),
+            "keep-all" => Ok(Self::KeepAll),
+            "clear-all" => Ok(Self::ClearAll),
+            _ => Err(
+                "invalid RamRetention value. valid options: `hardware`, `keep-all`, or `clear-all`",
+            ),
+        }
+    }
+}
+
 /// Represents allowed [`Memory`] bank access.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum BankAccess {
     None,
@@ -221,7 +298,8 @@ pub enum BankAccess {
 }
 
 /// Represents a set of [`Memory`] banks.
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Banks {
     start: usize,
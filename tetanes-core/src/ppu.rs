@@ -3,19 +3,21 @@
 use crate::{
     common::{Clock, ClockTo, NesRegion, Regional, Reset, ResetKind},
     cpu::Cpu,
-    debug::PpuDebugger,
+    debug::{PpuBreakpoint, PpuDebugger},
+    logging::trace,
     mapper::{Mapped, Mapper},
     mem::Mem,
     ppu::{bus::Bus, frame::Frame},
+    vs_system::VsPpu,
 };
 use ctrl::Ctrl;
 use mask::Mask;
 use scroll::Scroll;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use sprite::Sprite;
 use status::Status;
 use std::cmp::Ordering;
-use tracing::trace;
 
 pub mod bus;
 pub mod ctrl;
@@ -28,7 +30,8 @@ pub mod status;
 /// Nametable Mirroring Mode
 ///
 /// <http://wiki.nesdev.com/w/index.php/Mirroring#Nametable_Mirroring>
-#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum Mirroring {
     Vertical = 0,
@@ -72,7 +75,8 @@ pub trait Registers {
 /// NES PPU.
 ///
 /// See: <https://wiki.nesdev.com/w/index.php/PPU>
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Ppu {
     /// Master clock.
@@ -148,11 +152,18 @@ pub struct Ppu {
     /// See: <https://www.nesdev.org/wiki/PPU_power_up_state>
     pub reset_signal: bool,
     pub emulate_warmup: bool,
+    /// The Vs. System PPU variant, which may have a different palette.
+    pub vs_ppu: VsPpu,
 
     pub open_bus: u8,
 
-    #[serde(skip)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub debugger: Option<PpuDebugger>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub breakpoints: Vec<PpuBreakpoint>,
+    /// The last breakpoint that was hit, if any. Cleared by the consumer.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub breakpoint_hit: Option<PpuBreakpoint>,
 }
 
 impl Default for Ppu {
@@ -291,9 +302,12 @@ impl Ppu {
             reset_signal: false,
             emulate_warmup: false,
 
+            vs_ppu: VsPpu::default(),
             open_bus: 0x00,
 
             debugger: None,
+            breakpoints: Vec::new(),
+            breakpoint_hit: None,
         };
 
         ppu.set_region(ppu.region);
@@ -360,6 +374,14 @@ impl Ppu {
         }
     }
 
+    /// Records the first breakpoint matching `matches` as hit.
+    #[inline]
+    fn check_breakpoint(&mut self, matches: impl Fn(&PpuBreakpoint) -> bool) {
+        if self.breakpoint_hit.is_none() && !self.breakpoints.is_empty() {
+            self.breakpoint_hit = self.breakpoints.iter().copied().find(|bp| matches(bp));
+        }
+    }
+
     /// Load the passed given buffer with RGBA pixels from the current nametables.
     pub fn load_nametables(&self, nametables: &mut [u8]) {
         for i in 0..4 {
@@ -415,6 +437,13 @@ impl Ppu {
 
     /// Load the given buffer with RGBA pixels from the current pattern tables.
     pub fn load_pattern_tables(&self, pattern_tables: &mut [u8]) {
+        self.load_pattern_tables_with_palette(pattern_tables, 0);
+    }
+
+    /// Load the given buffer with RGBA pixels from the current pattern tables, colored using one
+    /// of the 8 palettes, where `0..=3` are background palettes and `4..=7` are sprite palettes.
+    pub fn load_pattern_tables_with_palette(&self, pattern_tables: &mut [u8], palette: u8) {
+        let palette_addr = u16::from(palette & 0x07) << 2;
         for i in 0..2 {
             let start = (i as u16) * 0x1000;
             let end = start + 0x1000;
@@ -426,7 +455,9 @@ impl Ppu {
                     let tile_lo = u16::from(self.bus.peek_chr(tile_addr + y));
                     let tile_hi = u16::from(self.bus.peek_chr(tile_addr + y + 8));
                     for x in 0..8 {
-                        let palette = (((tile_hi >> x) & 0x01) << 1) | ((tile_lo >> x) & 0x01);
+                        let tile_palette = (((tile_hi >> x) & 0x01) << 1) | ((tile_lo >> x) & 0x01);
+                        // Transparent pixels use the backdrop color
+                        let palette = (tile_palette > 0) as u16 * (palette_addr | tile_palette);
                         let color = u16::from(self.bus.peek_palette(Ppu::PALETTE_START | palette));
                         let x = u32::from(tile_x + (7 - x));
                         let y = u32::from(tile_y + y);
@@ -890,6 +921,11 @@ impl Ppu {
                             && x != 255
                         {
                             self.status.set_spr_zero_hit(true);
+                            if self.breakpoint_hit.is_none()
+                                && self.breakpoints.contains(&PpuBreakpoint::Sprite0Hit)
+                            {
+                                self.breakpoint_hit = Some(PpuBreakpoint::Sprite0Hit);
+                            }
                         }
 
                         if bg_color == 0 || !sprite.bg_priority {
@@ -921,12 +957,13 @@ impl Ppu {
             } else {
                 self.bus.read_palette(addr)
             };
+        let color = match self.vs_ppu.palette_lut() {
+            Some(lut) => lut[usize::from(color & self.mask.grayscale & 0x3F)],
+            None => color & self.mask.grayscale,
+        };
 
-        self.frame.set_pixel(
-            x,
-            y,
-            u16::from(color & self.mask.grayscale) | self.mask.emphasis,
-        );
+        self.frame
+            .set_pixel(x, y, u16::from(color) | self.mask.emphasis);
     }
 
     fn tick(&mut self) {
@@ -1332,6 +1369,9 @@ impl Registers for Ppu {
         );
         self.increment_vram_addr();
         self.bus.write(addr, val);
+        self.check_breakpoint(
+            |bp| matches!(bp, PpuBreakpoint::VramWrite(bp_addr) if *bp_addr & 0x3FFF == addr & 0x3FFF),
+        );
 
         // MMC3 clocks using A12
         let addr = self.scroll.addr();
@@ -1373,6 +1413,12 @@ impl Clock for Ppu {
             }
         }
 
+        let (cycle, scanline) = (self.cycle, self.scanline);
+        self.check_breakpoint(|bp| {
+            matches!(bp, PpuBreakpoint::Dot { scanline: bp_scanline, cycle: bp_cycle }
+                if *bp_scanline == scanline && *bp_cycle == cycle)
+        });
+
         1
     }
 }
@@ -1396,7 +1442,7 @@ impl Regional for Ppu {
     fn set_region(&mut self, region: NesRegion) {
         // https://www.nesdev.org/wiki/Cycle_reference_chart
         let (clock_divider, vblank_scanline, prerender_scanline) = match region {
-            NesRegion::Auto | NesRegion::Ntsc => (
+            NesRegion::Auto | NesRegion::Ntsc | NesRegion::PalM => (
                 Self::CLOCK_DIVIDER_NTSC,
                 Self::VBLANK_SCANLINE_NTSC,
                 Self::PRERENDER_SCANLINE_NTSC,
@@ -1524,6 +1570,36 @@ mod tests {
         assert_eq!(ppu.bus.read_ciram(0x2305), 0x66);
     }
 
+    #[test]
+    fn vram_write_breakpoint() {
+        let mut ppu = Ppu::default();
+        ppu.breakpoints.push(PpuBreakpoint::VramWrite(0x2306));
+        ppu.write_addr(0x23);
+        ppu.write_addr(0x05);
+        ppu.clock();
+        ppu.clock();
+        ppu.write_data(0x66); // write to $2305
+        assert_eq!(ppu.breakpoint_hit, None);
+        ppu.write_data(0x77); // write to $2306
+        assert_eq!(ppu.breakpoint_hit, Some(PpuBreakpoint::VramWrite(0x2306)));
+    }
+
+    #[test]
+    fn dot_breakpoint() {
+        let mut ppu = Ppu::default();
+        let breakpoint = PpuBreakpoint::Dot {
+            scanline: 1,
+            cycle: 10,
+        };
+        ppu.breakpoints.push(breakpoint);
+        while ppu.breakpoint_hit.is_none() {
+            assert!(ppu.scanline <= 1, "breakpoint not hit");
+            ppu.clock();
+        }
+        assert_eq!((ppu.scanline, ppu.cycle), (1, 10));
+        assert_eq!(ppu.breakpoint_hit, Some(breakpoint));
+    }
+
     #[test]
     fn vram_reads() {
         let mut ppu = Ppu::default();
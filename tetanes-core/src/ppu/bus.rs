@@ -2,12 +2,13 @@
 
 use crate::{
     common::{NesRegion, Regional, Reset, ResetKind},
+    logging::error,
     mapper::{Mapped, MappedRead, MappedWrite, Mapper, MemMap},
     mem::{Mem, Memory},
     ppu::{Mirroring, Ppu},
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use tracing::error;
 
 pub trait PpuAddr {
     /// Returns whether this value can be used to fetch a nametable attribute byte.
@@ -25,12 +26,13 @@ impl PpuAddr for u16 {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Bus {
     pub mapper: Mapper,
     pub chr_ram: Memory,
-    #[serde(skip)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub chr_rom: Memory,
     pub ciram: Memory, // $2007 PPUDATA
     pub palette: [u8; Self::PALETTE_SIZE],
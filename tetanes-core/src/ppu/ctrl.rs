@@ -4,6 +4,7 @@
 
 use crate::common::{Reset, ResetKind};
 use bitflags::bitflags;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 const NAMETABLE1: u16 = 0x2000;
@@ -14,7 +15,8 @@ const NAMETABLE4: u16 = 0x2C00;
 /// PPUCTRL register.
 ///
 /// See: <https://wiki.nesdev.com/w/index.php/PPU_registers#PPUCTRL>
-#[derive(Default, Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Default, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Ctrl {
     pub spr_select: u16,
@@ -42,7 +44,8 @@ bitflags! {
     // ||+------- Sprite Height: 0 = 8x8, 1 = 8x16
     // |+-------- PPU Master/Slave: 0 = read from EXT, 1 = write to EXT
     // +--------- NMI Enable: NMI at next vblank: 0 = off, 1: on
-    #[derive(Default, Serialize, Deserialize, Debug, Copy, Clone)]
+    #[derive(Default, Debug, Copy, Clone)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     #[must_use]
     pub struct Bits: u8 {
         const NAMETABLE1 = 0x01;
@@ -4,12 +4,14 @@ use crate::{
     common::{Reset, ResetKind},
     ppu::Ppu,
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::ops::{Deref, DerefMut};
 
 /// PPU frame.
-#[derive(Clone, Serialize, Deserialize)]
-#[serde(transparent)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 #[must_use]
 pub struct Buffer(Vec<u16>);
 
@@ -39,11 +41,12 @@ impl DerefMut for Buffer {
 }
 
 /// PPU frame.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Frame {
     pub count: u32,
-    #[serde(skip)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub buffer: Buffer,
 }
 
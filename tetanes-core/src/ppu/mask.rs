@@ -4,12 +4,14 @@
 
 use crate::common::{NesRegion, Reset, ResetKind};
 use bitflags::bitflags;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// PPUMASK register.
 ///
 /// See: <https://wiki.nesdev.com/w/index.php/PPU_registers#PPUMASK>
-#[derive(Default, Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Default, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Mask {
     pub rendering_enabled: bool,
@@ -36,7 +38,8 @@ bitflags! {
     // ||+------- Emphasize red
     // |+-------- Emphasize green
     // +--------- Emphasize blue
-    #[derive(Default, Serialize, Deserialize, Debug, Copy, Clone)]
+    #[derive(Default, Debug, Copy, Clone)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     #[must_use]
     pub struct Bits: u8 {
         const GRAYSCALE = 0x01;
@@ -74,7 +77,7 @@ impl Mask {
         self.rendering_enabled = self.show_bg || self.show_spr;
         self.emphasis = u16::from(
             match self.region {
-                NesRegion::Auto | NesRegion::Ntsc => self.bits.intersection(
+                NesRegion::Auto | NesRegion::Ntsc | NesRegion::PalM => self.bits.intersection(
                     Bits::EMPHASIZE_RED | Bits::EMPHASIZE_GREEN | Bits::EMPHASIZE_BLUE,
                 ),
                 NesRegion::Pal | NesRegion::Dendy => {
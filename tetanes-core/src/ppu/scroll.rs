@@ -3,12 +3,14 @@
 //! See: <https://wiki.nesdev.com/w/index.php/PPU_registers#PPUSCROLL>
 
 use crate::common::{Reset, ResetKind};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// PPUSCROLL register.
 ///
 /// See: <https://wiki.nesdev.com/w/index.php/PPU_registers#PPUSCROLL>
-#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Scroll {
     pub fine_x: u16,
@@ -245,6 +247,7 @@ impl Reset for Scroll {
             // v is not cleared on a a soft reset
             self.v = 0x0000;
         }
+        self.t = 0x0000;
         self.fine_x = 0x00;
         self.write_latch = false;
         self.delay_v_cycles = 0;
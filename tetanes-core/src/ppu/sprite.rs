@@ -2,13 +2,15 @@
 //!
 //! See: <https://www.nesdev.org/wiki/PPU_OAM>
 
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// PPU OAM Sprite entry.
 ///
 /// See: <https://www.nesdev.org/wiki/PPU_OAM>
-#[derive(Copy, Clone, Serialize, Deserialize)]
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Sprite {
     pub x: u32,
@@ -4,12 +4,14 @@
 
 use crate::common::{Reset, ResetKind};
 use bitflags::bitflags;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// PPUSTATUS register.
 ///
 /// See: <https://wiki.nesdev.com/w/index.php/PPU_registers#PPUSTATUS>
-#[derive(Default, Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Default, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub struct Status {
     pub spr_overflow: bool,
@@ -38,7 +40,8 @@ bitflags! {
     //            Set at dot 1 of line 241 (the line *after* the post-render
     //            line); cleared after reading $2002 and at dot 1 of the
     //            pre-render line.
-    #[derive(Default, Serialize, Deserialize, Debug, Copy, Clone)]
+    #[derive(Default, Debug, Copy, Clone)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     #[must_use]
     pub struct Bits: u8 {
         const UNUSED1 = 0x01;
@@ -2,13 +2,12 @@
 
 use crate::fs::{Error, Result};
 use std::{
-    fs::{create_dir_all, remove_dir_all, File},
+    fs::{create_dir_all, remove_dir_all, remove_file, rename, File},
     io::{Read, Write},
     path::Path,
 };
 
-pub fn writer_impl(path: impl AsRef<Path>) -> Result<impl Write> {
-    let path = path.as_ref();
+fn create_parent_dir(path: &Path) -> Result<()> {
     let Some(directory) = path.parent() else {
         return Err(Error::InvalidPath(path.to_path_buf()));
     };
@@ -16,15 +15,50 @@ pub fn writer_impl(path: impl AsRef<Path>) -> Result<impl Write> {
         create_dir_all(directory)
             .map_err(|err| Error::io(err, format!("failed to create directory {directory:?}")))?;
     }
+    Ok(())
+}
+
+pub fn writer_impl(path: impl AsRef<Path>) -> Result<impl Write> {
+    let path = path.as_ref();
+    create_parent_dir(path)?;
     File::create(path)
         .map_err(|source| Error::io(source, format!("failed to create file {path:?}")))
 }
 
+pub fn write_atomic_impl(path: impl AsRef<Path>, data: &[u8]) -> Result<()> {
+    let path = path.as_ref();
+    create_parent_dir(path)?;
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = Path::new(&tmp_path);
+
+    let mut file = File::create(tmp_path)
+        .map_err(|source| Error::io(source, format!("failed to create file {tmp_path:?}")))?;
+    file.write_all(data)
+        .and_then(|_| file.sync_all())
+        .map_err(|source| Error::io(source, format!("failed to write file {tmp_path:?}")))?;
+    drop(file);
+
+    rename_impl(tmp_path, path)
+}
+
+pub fn rename_impl(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<()> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+    rename(from, to)
+        .map_err(|source| Error::io(source, format!("failed to rename {from:?} to {to:?}")))
+}
+
 pub fn reader_impl(path: impl AsRef<Path>) -> Result<impl Read> {
     let path = path.as_ref();
     File::open(path).map_err(|source| Error::io(source, format!("failed to open file {path:?}")))
 }
 
+pub fn remove_impl(path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    remove_file(path).map_err(|source| Error::io(source, format!("failed to remove file {path:?}")))
+}
+
 pub fn clear_dir_impl(path: impl AsRef<Path>) -> Result<()> {
     let path = path.as_ref();
     remove_dir_all(path)
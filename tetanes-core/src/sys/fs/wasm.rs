@@ -24,7 +24,7 @@ pub fn local_storage() -> Result<web_sys::Storage> {
     window
         .local_storage()
         .map_err(|err| {
-            tracing::error!("failed to get local storage: {err:?}");
+            crate::logging::error!("failed to get local storage: {err:?}");
             Error::custom("failed to get storage")
         })?
         .ok_or_else(|| Error::custom("no storage available"))
@@ -45,14 +45,14 @@ impl Write for StoreWriter {
             Ok(value) => value,
             Err(err) => {
                 self.data = data;
-                tracing::error!("failed to serialize data: {err:?}");
+                crate::logging::error!("failed to serialize data: {err:?}");
                 return Err(io::Error::other("failed to serialize data"));
             }
         };
 
         if let Err(err) = local_storage.set_item(&key, &value) {
             self.data = data;
-            tracing::error!("failed to store data in local storage: {err:?}");
+            crate::logging::error!("failed to store data in local storage: {err:?}");
             return Err(io::Error::other("failed to write data"));
         }
 
@@ -74,6 +74,33 @@ pub fn writer_impl(path: impl AsRef<Path>) -> Result<impl Write> {
     })
 }
 
+pub fn write_atomic_impl(path: impl AsRef<Path>, data: &[u8]) -> Result<()> {
+    // Local storage writes are already atomic per key.
+    let mut writer = writer_impl(path)?;
+    writer
+        .write_all(data)
+        .and_then(|_| writer.flush())
+        .map_err(|err| Error::io(err, "failed to save data"))
+}
+
+pub fn rename_impl(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<()> {
+    let local_storage = local_storage()?;
+
+    let from = from.as_ref().to_string_lossy();
+    let to = to.as_ref().to_string_lossy();
+    let value = local_storage
+        .get_item(&from)
+        .ok()
+        .flatten()
+        .ok_or_else(|| Error::custom(format!("failed to find data for {from}")))?;
+    local_storage
+        .set_item(&to, &value)
+        .map_err(|_| Error::custom(format!("failed to write data for {to}")))?;
+    let _ = local_storage.remove_item(&from);
+
+    Ok(())
+}
+
 pub fn reader_impl(path: impl AsRef<Path>) -> Result<impl Read> {
     let path = path.as_ref();
     let local_storage = local_storage()?;
@@ -84,7 +111,7 @@ pub fn reader_impl(path: impl AsRef<Path>) -> Result<impl Read> {
         .map_err(|_| Error::custom("failed to find data for {key}"))?
         .map(|value| {
             serde_json::from_str(&value).map_err(|err| {
-                tracing::error!("failed to deserialize data: {err:?}");
+                crate::logging::error!("failed to deserialize data: {err:?}");
                 Error::custom("failed to deserialize data")
             })
         })
@@ -95,6 +122,13 @@ pub fn reader_impl(path: impl AsRef<Path>) -> Result<impl Read> {
     })
 }
 
+pub fn remove_impl(path: impl AsRef<Path>) -> Result<()> {
+    let key = path.as_ref().to_string_lossy();
+    local_storage()?
+        .remove_item(&key)
+        .map_err(|_| Error::custom(format!("failed to remove data for {key}")))
+}
+
 pub fn clear_dir_impl(path: impl AsRef<Path>) -> Result<()> {
     let path = path.as_ref().to_string_lossy();
     let local_storage = local_storage()?;
@@ -1,6 +1,7 @@
 //! Video output and filtering.
 
 use crate::ppu::Ppu;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::{
     f64::consts::PI,
@@ -14,7 +15,8 @@ use thiserror::Error;
 #[error("failed to parse `VideoFilter`")]
 pub struct ParseVideoFilterError;
 
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[must_use]
 pub enum VideoFilter {
     Pixellate,
@@ -49,6 +51,239 @@ impl TryFrom<usize> for VideoFilter {
     }
 }
 
+/// A color vision deficiency to simulate or correct for with a [`ColorFilter`].
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub enum ColorBlindness {
+    #[default]
+    None,
+    /// Missing red cones.
+    Protanopia,
+    /// Missing green cones.
+    Deuteranopia,
+    /// Missing blue cones.
+    Tritanopia,
+}
+
+impl ColorBlindness {
+    pub const fn as_slice() -> &'static [Self] {
+        &[
+            Self::None,
+            Self::Protanopia,
+            Self::Deuteranopia,
+            Self::Tritanopia,
+        ]
+    }
+
+    /// Simulation matrix at full severity.
+    ///
+    /// See: <https://www.inf.ufrgs.br/~oliveira/pubs_files/CVD_Simulation/CVD_Simulation.html>
+    const fn simulation(&self) -> [[f32; 3]; 3] {
+        match self {
+            Self::None => IDENTITY,
+            Self::Protanopia => [
+                [0.152_286, 1.052_583, -0.204_868],
+                [0.114_503, 0.786_281, 0.099_216],
+                [-0.003_882, -0.048_116, 1.051_998],
+            ],
+            Self::Deuteranopia => [
+                [0.367_322, 0.860_646, -0.227_968],
+                [0.280_085, 0.672_501, 0.047_413],
+                [-0.011_820, 0.042_940, 0.968_881],
+            ],
+            Self::Tritanopia => [
+                [1.255_528, -0.076_749, -0.178_779],
+                [-0.078_411, 0.930_809, 0.147_602],
+                [0.004_733, 0.691_367, 0.303_900],
+            ],
+        }
+    }
+
+    /// Daltonization matrix, shifting the color information lost to the deficiency into channels
+    /// that can still be distinguished.
+    const fn correction(&self) -> [[f32; 3]; 3] {
+        match self {
+            Self::None => [[0.0; 3]; 3],
+            Self::Protanopia | Self::Deuteranopia => {
+                [[0.0, 0.0, 0.0], [0.7, 1.0, 0.0], [0.7, 0.0, 1.0]]
+            }
+            Self::Tritanopia => [[1.0, 0.0, 0.7], [0.0, 1.0, 0.7], [0.0, 0.0, 0.0]],
+        }
+    }
+}
+
+impl AsRef<str> for ColorBlindness {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::None => "None",
+            Self::Protanopia => "Protanopia",
+            Self::Deuteranopia => "Deuteranopia",
+            Self::Tritanopia => "Tritanopia",
+        }
+    }
+}
+
+/// Whether a [`ColorFilter`] simulates or corrects for a [`ColorBlindness`].
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub enum ColorBlindMode {
+    /// Show colors as they appear with the deficiency, e.g. to check palette choices.
+    Simulate,
+    /// Adjust colors so they're easier to distinguish with the deficiency.
+    #[default]
+    Correct,
+}
+
+impl AsRef<str> for ColorBlindMode {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Simulate => "Simulate",
+            Self::Correct => "Correct",
+        }
+    }
+}
+
+/// Color transform applied to frames after palette lookup to assist colorblind players.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[must_use]
+pub struct ColorFilter {
+    pub deficiency: ColorBlindness,
+    pub mode: ColorBlindMode,
+    /// How strongly to apply the transform, from `0.0` to `1.0`.
+    pub strength: f32,
+}
+
+impl Default for ColorFilter {
+    fn default() -> Self {
+        Self {
+            deficiency: ColorBlindness::None,
+            mode: ColorBlindMode::default(),
+            strength: 1.0,
+        }
+    }
+}
+
+const IDENTITY: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+impl ColorFilter {
+    /// Returns the RGB transform matrix, or `None` if colors are left unchanged.
+    #[must_use]
+    pub fn matrix(&self) -> Option<[[f32; 3]; 3]> {
+        let strength = self.strength.clamp(0.0, 1.0);
+        if self.deficiency == ColorBlindness::None || strength == 0.0 {
+            return None;
+        }
+        let simulation = self.deficiency.simulation();
+        let mut matrix = IDENTITY;
+        match self.mode {
+            // I + s * (S - I)
+            ColorBlindMode::Simulate => {
+                for (row, sim_row) in matrix.iter_mut().zip(simulation) {
+                    for (val, sim) in row.iter_mut().zip(sim_row) {
+                        *val += strength * (sim - *val);
+                    }
+                }
+            }
+            // I + s * C * (I - S)
+            ColorBlindMode::Correct => {
+                let correction = self.deficiency.correction();
+                for (i, row) in matrix.iter_mut().enumerate() {
+                    for (j, val) in row.iter_mut().enumerate() {
+                        let error = (0..3)
+                            .map(|k| correction[i][k] * (IDENTITY[k][j] - simulation[k][j]))
+                            .sum::<f32>();
+                        *val += strength * error;
+                    }
+                }
+            }
+        }
+        Some(matrix)
+    }
+
+    /// Transforms the colors of an RGBA8 frame in place.
+    pub fn apply(&self, frame: &mut [u8]) {
+        let Some(matrix) = self.matrix() else {
+            return;
+        };
+        for pixel in frame.chunks_exact_mut(4) {
+            let rgb = [pixel[0], pixel[1], pixel[2]].map(f32::from);
+            for (out, row) in pixel.iter_mut().zip(matrix) {
+                let val = row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2];
+                *out = val.round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+#[must_use]
+#[error("failed to parse `PixelFormat`")]
+pub struct ParsePixelFormatError;
+
+/// Pixel format for frame buffer output.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub enum PixelFormat {
+    /// 4 bytes per pixel, in `R`, `G`, `B`, `A` order.
+    #[default]
+    Rgba8,
+    /// 2 bytes per pixel, packed as little-endian `RRRRRGGGGGGBBBBB`.
+    Rgb565,
+    /// 1 byte per pixel, containing the `0x00..=0x3F` system palette index. Color emphasis bits
+    /// and the selected [`VideoFilter`] are not applied.
+    Indexed8,
+}
+
+impl PixelFormat {
+    pub const fn as_slice() -> &'static [Self] {
+        &[Self::Rgba8, Self::Rgb565, Self::Indexed8]
+    }
+
+    /// Number of bytes used per pixel.
+    #[must_use]
+    pub const fn bytes_per_pixel(&self) -> usize {
+        match self {
+            Self::Rgba8 => 4,
+            Self::Rgb565 => 2,
+            Self::Indexed8 => 1,
+        }
+    }
+
+    /// Number of bytes required for an entire frame.
+    #[must_use]
+    pub const fn frame_size(&self) -> usize {
+        Ppu::SIZE * self.bytes_per_pixel()
+    }
+}
+
+impl AsRef<str> for PixelFormat {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Rgba8 => "RGBA8",
+            Self::Rgb565 => "RGB565",
+            Self::Indexed8 => "Indexed8",
+        }
+    }
+}
+
+impl TryFrom<usize> for PixelFormat {
+    type Error = ParsePixelFormatError;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::Rgba8,
+            1 => Self::Rgb565,
+            2 => Self::Indexed8,
+            _ => return Err(ParsePixelFormatError),
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 #[must_use]
 pub struct Frame(Vec<u8>);
@@ -90,6 +325,7 @@ impl DerefMut for Frame {
 #[must_use]
 pub struct Video {
     pub filter: VideoFilter,
+    pub color_filter: ColorFilter,
     pub frame: Frame,
 }
 
@@ -109,6 +345,7 @@ impl Video {
     pub fn with_filter(filter: VideoFilter) -> Self {
         Self {
             filter,
+            color_filter: ColorFilter::default(),
             frame: Frame::new(),
         }
     }
@@ -122,6 +359,7 @@ impl Video {
             VideoFilter::Pixellate => Self::decode_buffer(buffer, &mut self.frame),
             VideoFilter::Ntsc => Self::apply_ntsc_filter(buffer, frame_number, &mut self.frame),
         }
+        self.color_filter.apply(&mut self.frame);
 
         &self.frame
     }
@@ -135,6 +373,7 @@ impl Video {
             VideoFilter::Pixellate => Self::decode_buffer(buffer, output),
             VideoFilter::Ntsc => Self::apply_ntsc_filter(buffer, frame_number, output),
         }
+        self.color_filter.apply(output);
     }
 
     /// Fills a fully rendered frame with RGB colors.
@@ -149,6 +388,22 @@ impl Video {
         }
     }
 
+    /// Converts a filtered RGBA8 frame into RGB565.
+    pub fn encode_rgb565(rgba: &[u8], output: &mut [u8]) {
+        for (pixel, out) in rgba.chunks_exact(4).zip(output.chunks_exact_mut(2)) {
+            let [red, green, blue] = [pixel[0], pixel[1], pixel[2]].map(u16::from);
+            let rgb565 = ((red >> 3) << 11) | ((green >> 2) << 5) | (blue >> 3);
+            out.copy_from_slice(&rgb565.to_le_bytes());
+        }
+    }
+
+    /// Converts a raw PPU video buffer into system palette indexes.
+    pub fn encode_indexed8(buffer: &[u16], output: &mut [u8]) {
+        for (color, out) in buffer.iter().zip(output.iter_mut()) {
+            *out = (color & 0x3F) as u8;
+        }
+    }
+
     /// Applies the NTSC filter to the given video buffer.
     ///
     /// Amazing implementation Bisqwit! Much faster than my original, but boy what a pain
@@ -183,6 +438,7 @@ impl std::fmt::Debug for Video {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Video")
             .field("filter", &self.filter)
+            .field("color_filter", &self.color_filter)
             .finish()
     }
 }
@@ -284,3 +540,91 @@ fn generate_ntsc_palette() -> Vec<u32> {
 
     ntsc_palette
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_rgb565() {
+        let rgba = [
+            0xFF, 0x00, 0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFF, 0x08, 0x04, 0xFF, 0xFF,
+        ];
+        let mut output = [0; 6];
+        Video::encode_rgb565(&rgba, &mut output);
+        assert_eq!(output, [0x00, 0xF8, 0xE0, 0x07, 0x3F, 0x08]);
+    }
+
+    #[test]
+    fn encode_indexed8() {
+        let buffer = [0x000F, 0x0030, 0x01C5];
+        let mut output = [0; 3];
+        Video::encode_indexed8(&buffer, &mut output);
+        assert_eq!(output, [0x0F, 0x30, 0x05]);
+    }
+
+    #[test]
+    fn color_filter_disabled() {
+        let mut filter = ColorFilter::default();
+        assert_eq!(filter.matrix(), None);
+        filter.deficiency = ColorBlindness::Deuteranopia;
+        filter.strength = 0.0;
+        assert_eq!(filter.matrix(), None);
+
+        let mut frame = [0x12, 0x34, 0x56, 0xFF];
+        filter.apply(&mut frame);
+        assert_eq!(frame, [0x12, 0x34, 0x56, 0xFF]);
+    }
+
+    #[test]
+    fn color_filter_preserves_grays() {
+        for deficiency in &ColorBlindness::as_slice()[1..] {
+            for mode in [ColorBlindMode::Simulate, ColorBlindMode::Correct] {
+                let filter = ColorFilter {
+                    deficiency: *deficiency,
+                    mode,
+                    strength: 1.0,
+                };
+                let mut frame = [0x80, 0x80, 0x80, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+                filter.apply(&mut frame);
+                for (pixel, expected) in frame.chunks_exact(4).zip([0x80, 0xFF]) {
+                    for val in &pixel[..3] {
+                        assert!(
+                            val.abs_diff(expected) <= 2,
+                            "{deficiency:?} {mode:?}: {pixel:?}"
+                        );
+                    }
+                    assert_eq!(pixel[3], 0xFF, "alpha unchanged");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn color_filter_simulate_strength() {
+        let full = ColorFilter {
+            deficiency: ColorBlindness::Protanopia,
+            mode: ColorBlindMode::Simulate,
+            strength: 1.0,
+        };
+        let half = ColorFilter {
+            strength: 0.5,
+            ..full
+        };
+        let mut red = [0xFF, 0x00, 0x00, 0xFF];
+        full.apply(&mut red);
+        // Protanopes see pure red as a dark yellow-brown
+        assert!(red[0] < 0x40 && red[1] < 0x40, "{red:?}");
+
+        let mut half_red = [0xFF, 0x00, 0x00, 0xFF];
+        half.apply(&mut half_red);
+        assert!(half_red[0] > red[0] && half_red[0] < 0xFF, "{half_red:?}");
+    }
+
+    #[test]
+    fn pixel_format_frame_size() {
+        assert_eq!(PixelFormat::Rgba8.frame_size(), Frame::SIZE);
+        assert_eq!(PixelFormat::Rgb565.frame_size(), Frame::SIZE / 2);
+        assert_eq!(PixelFormat::Indexed8.frame_size(), Frame::SIZE / 4);
+    }
+}
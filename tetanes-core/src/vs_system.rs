@@ -0,0 +1,196 @@
+//! Nintendo Vs. System arcade hardware: PPU palette variants, DIP switches and coin slots.
+//!
+//! <https://www.nesdev.org/wiki/Vs._System>
+
+use crate::common::{Clock, Reset, ResetKind};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The PPU a Vs. System game was designed for, from the NES 2.0 header. Games expect the palette
+/// of their PPU and display garbled colors otherwise.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub enum VsPpu {
+    /// RP2C03B, RP2C03G, RC2C03B and RC2C03C use the standard NES palette.
+    #[default]
+    Rp2c03,
+    Rp2c04_0001,
+    Rp2c04_0002,
+    Rp2c04_0003,
+    Rp2c04_0004,
+    /// RC2C05-01..05 use the standard NES palette with swapped $2000/$2001 registers and a PPU
+    /// identifier in $2002.
+    Rc2c05,
+}
+
+impl VsPpu {
+    /// Color lookup tables for the RP2C04 PPUs, mapping each color to the standard NES palette
+    /// color with the same appearance.
+    ///
+    /// See: <https://www.nesdev.org/wiki/PPU_palettes#2C04>
+    #[rustfmt::skip]
+    const RP2C04_0001: [u8; 64] = [
+        0x35, 0x23, 0x16, 0x22, 0x1C, 0x09, 0x1D, 0x15, 0x20, 0x00, 0x27, 0x05, 0x04, 0x28, 0x08, 0x20,
+        0x21, 0x3E, 0x1F, 0x29, 0x3C, 0x32, 0x36, 0x12, 0x3F, 0x2B, 0x2E, 0x1E, 0x3D, 0x2D, 0x24, 0x01,
+        0x0E, 0x31, 0x33, 0x2A, 0x2C, 0x0C, 0x1B, 0x14, 0x2E, 0x07, 0x34, 0x06, 0x13, 0x02, 0x26, 0x2E,
+        0x2E, 0x19, 0x10, 0x0A, 0x39, 0x03, 0x37, 0x17, 0x0F, 0x11, 0x0B, 0x0D, 0x38, 0x25, 0x18, 0x3A,
+    ];
+    #[rustfmt::skip]
+    const RP2C04_0002: [u8; 64] = [
+        0x2E, 0x27, 0x18, 0x39, 0x3A, 0x25, 0x1C, 0x31, 0x16, 0x13, 0x38, 0x34, 0x20, 0x23, 0x3C, 0x0B,
+        0x0F, 0x21, 0x06, 0x3D, 0x1B, 0x29, 0x1E, 0x22, 0x1D, 0x24, 0x0E, 0x2B, 0x32, 0x08, 0x2E, 0x03,
+        0x04, 0x36, 0x26, 0x33, 0x11, 0x1F, 0x10, 0x02, 0x14, 0x3F, 0x00, 0x09, 0x12, 0x2E, 0x28, 0x20,
+        0x3E, 0x0D, 0x2A, 0x17, 0x0C, 0x01, 0x15, 0x19, 0x2E, 0x2C, 0x07, 0x37, 0x35, 0x05, 0x0A, 0x2D,
+    ];
+    #[rustfmt::skip]
+    const RP2C04_0003: [u8; 64] = [
+        0x14, 0x25, 0x3A, 0x10, 0x0B, 0x20, 0x31, 0x09, 0x01, 0x2E, 0x36, 0x08, 0x15, 0x3D, 0x3E, 0x3C,
+        0x22, 0x1C, 0x05, 0x12, 0x19, 0x18, 0x17, 0x1B, 0x00, 0x03, 0x2E, 0x02, 0x16, 0x06, 0x34, 0x35,
+        0x23, 0x0F, 0x0E, 0x37, 0x0D, 0x27, 0x26, 0x20, 0x29, 0x04, 0x21, 0x24, 0x11, 0x2D, 0x2E, 0x1F,
+        0x2C, 0x1E, 0x39, 0x33, 0x07, 0x2A, 0x28, 0x1D, 0x0A, 0x2E, 0x32, 0x38, 0x13, 0x2B, 0x3F, 0x0C,
+    ];
+    #[rustfmt::skip]
+    const RP2C04_0004: [u8; 64] = [
+        0x18, 0x03, 0x1C, 0x28, 0x2E, 0x35, 0x01, 0x17, 0x10, 0x1F, 0x2A, 0x0E, 0x36, 0x37, 0x0B, 0x39,
+        0x25, 0x1E, 0x12, 0x34, 0x2E, 0x1D, 0x06, 0x26, 0x3E, 0x1B, 0x22, 0x19, 0x04, 0x2E, 0x3A, 0x21,
+        0x05, 0x0A, 0x07, 0x02, 0x13, 0x14, 0x00, 0x15, 0x0C, 0x3D, 0x11, 0x0F, 0x0D, 0x38, 0x2D, 0x24,
+        0x33, 0x20, 0x08, 0x16, 0x3F, 0x2B, 0x20, 0x3C, 0x2E, 0x27, 0x23, 0x31, 0x29, 0x32, 0x2C, 0x09,
+    ];
+
+    /// The PPU from the NES 2.0 header Vs. System type byte.
+    pub const fn from_header(vs_data: u8) -> Self {
+        match vs_data & 0x0F {
+            0x02 => Self::Rp2c04_0001,
+            0x03 => Self::Rp2c04_0002,
+            0x04 => Self::Rp2c04_0003,
+            0x05 => Self::Rp2c04_0004,
+            0x08..=0x0C => Self::Rc2c05,
+            _ => Self::Rp2c03,
+        }
+    }
+
+    /// Color lookup table to the standard NES palette, if this PPU has a different palette.
+    #[must_use]
+    pub const fn palette_lut(&self) -> Option<&'static [u8; 64]> {
+        match self {
+            Self::Rp2c04_0001 => Some(&Self::RP2C04_0001),
+            Self::Rp2c04_0002 => Some(&Self::RP2C04_0002),
+            Self::Rp2c04_0003 => Some(&Self::RP2C04_0003),
+            Self::Rp2c04_0004 => Some(&Self::RP2C04_0004),
+            Self::Rp2c03 | Self::Rc2c05 => None,
+        }
+    }
+}
+
+/// Coin slots, service button and DIP switches read through $4016/$4017 alongside the
+/// controllers.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[must_use]
+pub struct VsSystem {
+    /// DIP switches 1-8 in bits 0-7, configuring game settings like difficulty and lives.
+    pub dip_switches: u8,
+    /// CPU cycles left that each coin slot is held for after inserting a coin.
+    pub coins: [u32; 2],
+    pub service: bool,
+}
+
+impl VsSystem {
+    /// CPU cycles a coin is held in a coin slot for, long enough to be seen by games polling once
+    /// a frame.
+    const COIN_CYCLES: u32 = 4 * 29_781;
+
+    pub const fn new(dip_switches: u8) -> Self {
+        Self {
+            dip_switches,
+            coins: [0; 2],
+            service: false,
+        }
+    }
+
+    /// Insert a coin into coin slot 1 or 2.
+    pub fn insert_coin(&mut self, slot: usize) {
+        if let Some(coin) = self.coins.get_mut(slot) {
+            *coin = Self::COIN_CYCLES;
+        }
+    }
+
+    /// Bits D2-D7 read from $4016 or $4017, with controller data in D0.
+    ///
+    /// ```text
+    /// $4016: xCCD DSxx
+    ///         ||| ||
+    ///         ||| |+---- Service button
+    ///         ||+-+----- DIP switches 1-2
+    ///         ++-------- Coin slots 1-2
+    /// $4017: DDDD DDxx
+    ///        ++++-++---- DIP switches 3-8
+    /// ```
+    #[must_use]
+    pub const fn read(&self, addr: u16) -> u8 {
+        if addr == 0x4016 {
+            let mut val = ((self.service as u8) << 2) | ((self.dip_switches & 0x03) << 3);
+            if self.coins[0] > 0 {
+                val |= 0x20;
+            }
+            if self.coins[1] > 0 {
+                val |= 0x40;
+            }
+            val
+        } else {
+            self.dip_switches & 0xFC
+        }
+    }
+}
+
+impl Clock for VsSystem {
+    fn clock(&mut self) -> usize {
+        for coin in &mut self.coins {
+            *coin = coin.saturating_sub(1);
+        }
+        1
+    }
+}
+
+impl Reset for VsSystem {
+    fn reset(&mut self, _kind: ResetKind) {
+        self.coins = [0; 2];
+        self.service = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palette_luts() {
+        for ppu in [
+            VsPpu::Rp2c04_0001,
+            VsPpu::Rp2c04_0002,
+            VsPpu::Rp2c04_0003,
+            VsPpu::Rp2c04_0004,
+        ] {
+            let lut = ppu.palette_lut().expect("palette lut");
+            assert!(lut.iter().all(|&color| color < 0x40), "{ppu:?}");
+        }
+        assert_eq!(VsPpu::from_header(0x05), VsPpu::Rp2c04_0004);
+        assert!(VsPpu::from_header(0x00).palette_lut().is_none());
+    }
+
+    #[test]
+    fn inputs() {
+        let mut vs = VsSystem::new(0b1010_0110);
+        assert_eq!(vs.read(0x4016), 0x10);
+        assert_eq!(vs.read(0x4017), 0xA4);
+
+        vs.insert_coin(1);
+        vs.service = true;
+        assert_eq!(vs.read(0x4016), 0x54);
+        for _ in 0..VsSystem::COIN_CYCLES {
+            vs.clock();
+        }
+        assert_eq!(vs.read(0x4016), 0x14);
+    }
+}
@@ -0,0 +1,233 @@
+//! Frame-accurate video comparison for tracking down accuracy regressions.
+//!
+//! Record golden frames for a ROM with one build or configuration, then compare another build or
+//! configuration against them to find the first frame where video output differs.
+
+use anyhow::{bail, Context};
+use clap::{Parser, Subcommand, ValueEnum};
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tetanes_core::{
+    common::NesRegion,
+    control_deck::{Config, ControlDeck, HeadlessMode},
+    fs,
+    mem::RamState,
+    ppu::Ppu,
+    video::{Frame, Video},
+};
+
+fn main() -> anyhow::Result<()> {
+    let opt = Opt::parse();
+    match opt.command {
+        Command::Golden {
+            rom,
+            frames,
+            output,
+            deck,
+        } => {
+            let golden = Golden {
+                rom: fs::filename(&rom).to_string(),
+                frames: run(&rom, frames, &deck)?,
+            };
+            fs::save(&output, &golden)
+                .with_context(|| format!("failed to save golden frames to {output:?}"))?;
+            println!(
+                "Saved {} golden frames for {:?} to {output:?}",
+                golden.frames.len(),
+                golden.rom
+            );
+        }
+        Command::Compare {
+            rom,
+            golden,
+            frames,
+            output,
+            deck,
+        } => {
+            let golden: Golden = fs::load(&golden)
+                .with_context(|| format!("failed to load golden frames from {golden:?}"))?;
+            let frames = frames.unwrap_or(golden.frames.len() as u32);
+            if frames as usize > golden.frames.len() {
+                bail!(
+                    "only {} golden frames available, but {frames} requested",
+                    golden.frames.len()
+                );
+            }
+            let actual = run(&rom, frames, &deck)?;
+            let mismatch = golden
+                .frames
+                .iter()
+                .zip(&actual)
+                .position(|(expected, actual)| expected != actual);
+            match mismatch {
+                Some(frame) => {
+                    let path = output.join(format!("{}_frame_{frame}_diff.png", golden.rom));
+                    save_diff(&path, &golden.frames[frame], &actual[frame])?;
+                    bail!("frame {frame} differs from golden. Saved diff to {path:?}");
+                }
+                None => println!("All {frames} frames match golden"),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Golden frames recorded for a ROM.
+#[derive(Debug, Serialize, Deserialize)]
+#[must_use]
+struct Golden {
+    rom: String,
+    /// Raw PPU frame buffers for each frame.
+    frames: Vec<Vec<u16>>,
+}
+
+/// Run a ROM for a number of frames, returning the raw PPU frame buffer for each frame.
+fn run(rom: &Path, frames: u32, opts: &DeckOpts) -> anyhow::Result<Vec<Vec<u16>>> {
+    let mut deck = ControlDeck::with_config(Config {
+        cycle_accurate: !opts.no_cycle_accurate,
+        region: opts.region.into(),
+        ram_state: opts.ram_state.into(),
+        headless_mode: HeadlessMode::NO_AUDIO,
+        ..Default::default()
+    });
+    deck.load_rom_path(rom)
+        .with_context(|| format!("failed to load {rom:?}"))?;
+    (0..frames)
+        .map(|frame| {
+            deck.clock_frame()
+                .with_context(|| format!("failed to clock frame {frame}"))?;
+            Ok(deck.frame_buffer_raw().to_vec())
+        })
+        .collect()
+}
+
+/// Save a side-by-side image of the expected and actual frames, followed by a mask highlighting
+/// differing pixels in red.
+fn save_diff(path: &Path, expected: &[u16], actual: &[u16]) -> anyhow::Result<()> {
+    const WIDTH: usize = Ppu::WIDTH as usize;
+    const HEIGHT: usize = Ppu::HEIGHT as usize;
+
+    let mut expected_rgba = Frame::new();
+    Video::decode_buffer(expected, &mut expected_rgba);
+    let mut actual_rgba = Frame::new();
+    Video::decode_buffer(actual, &mut actual_rgba);
+    let diff_rgba = expected
+        .iter()
+        .zip(actual)
+        .zip(expected_rgba.chunks_exact(4))
+        .flat_map(|((expected, actual), pixel)| {
+            if expected == actual {
+                // Dim matching pixels so differences stand out
+                [pixel[0] / 4, pixel[1] / 4, pixel[2] / 4, 255]
+            } else {
+                [255, 0, 0, 255]
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let row_len = WIDTH * 4;
+    let mut image = Vec::with_capacity(Frame::SIZE * 3);
+    for y in 0..HEIGHT {
+        let row = y * row_len..(y + 1) * row_len;
+        image.extend_from_slice(&expected_rgba[row.clone()]);
+        image.extend_from_slice(&actual_rgba[row.clone()]);
+        image.extend_from_slice(&diff_rgba[row]);
+    }
+    let Some(image) = RgbaImage::from_raw(3 * WIDTH as u32, HEIGHT as u32, image) else {
+        bail!("invalid diff image dimensions");
+    };
+    image
+        .save(path)
+        .with_context(|| format!("failed to save diff image {path:?}"))
+}
+
+#[derive(Parser, Debug)]
+#[must_use]
+struct Opt {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Record golden frames for a ROM.
+    Golden {
+        /// The NES ROM to run.
+        rom: PathBuf,
+        /// Number of frames to record.
+        #[arg(short, long, default_value_t = 600)]
+        frames: u32,
+        /// File to save golden frames to.
+        #[arg(short, long)]
+        output: PathBuf,
+        #[command(flatten)]
+        deck: DeckOpts,
+    },
+    /// Compare a ROM against recorded golden frames, saving a side-by-side diff image of the first
+    /// frame that differs.
+    Compare {
+        /// The NES ROM to run.
+        rom: PathBuf,
+        /// Golden frames file to compare against.
+        #[arg(short, long)]
+        golden: PathBuf,
+        /// Number of frames to compare. [default: all golden frames]
+        #[arg(short, long)]
+        frames: Option<u32>,
+        /// Directory to save the diff image to.
+        #[arg(short, long, default_value = ".")]
+        output: PathBuf,
+        #[command(flatten)]
+        deck: DeckOpts,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct DeckOpts {
+    /// Disable cycle-accurate emulation.
+    #[arg(long)]
+    no_cycle_accurate: bool,
+    /// NES region to emulate.
+    #[arg(long, value_enum, default_value_t = Region::Auto)]
+    region: Region,
+    /// Startup RAM state. Defaults to zeros so results are deterministic.
+    #[arg(long, value_enum, default_value_t = Ram::AllZeros)]
+    ram_state: Ram,
+}
+
+#[derive(ValueEnum, Debug, Copy, Clone)]
+enum Region {
+    Auto,
+    Ntsc,
+    Pal,
+    Dendy,
+}
+
+impl From<Region> for NesRegion {
+    fn from(region: Region) -> Self {
+        match region {
+            Region::Auto => Self::Auto,
+            Region::Ntsc => Self::Ntsc,
+            Region::Pal => Self::Pal,
+            Region::Dendy => Self::Dendy,
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Copy, Clone)]
+enum Ram {
+    AllZeros,
+    AllOnes,
+    Random,
+}
+
+impl From<Ram> for RamState {
+    fn from(ram: Ram) -> Self {
+        match ram {
+            Ram::AllZeros => Self::AllZeros,
+            Ram::AllOnes => Self::AllOnes,
+            Ram::Random => Self::Random,
+        }
+    }
+}
@@ -70,25 +70,29 @@ struct Output {
 @group(1) @binding(0) var tex: texture_2d<f32>;
 @group(1) @binding(1) var tex_sampler: sampler;
 
+struct Params {
+    sharpness_h: f32,
+    sharpness_v: f32,
+    mask_strength: f32,
+    scanline_strength: f32,
+    gamma_input: f32,
+    gamma_output: f32,
+    bright_boost: f32,
+    dilation: f32,
+}
+@group(1) @binding(2) var<uniform> params: Params;
+
 const PI = 3.141592653589;
 
-const SHARPNESS_H = 0.5;
-const SHARPNESS_V = 1.0;
-const MASK_STRENGTH = 0.3;
 const MASK_DOT_WIDTH = 1.0;
 const MASK_DOT_HEIGHT = 1.0;
 const MASK_STAGGER = 0.0;
 const MASK_SIZE = 1.0;
-const SCANLINE_STRENGTH = 1.0;
 const SCANLINE_BEAM_WIDTH_MIN = 1.5;
 const SCANLINE_BEAM_WIDTH_MAX = 1.5;
 const SCANLINE_BRIGHT_MIN = 0.35;
 const SCANLINE_BRIGHT_MAX = 0.65;
 const SCANLINE_CUTOFF = 400.0;
-const GAMMA_INPUT = 2.0;
-const GAMMA_OUTPUT = 1.8;
-const BRIGHT_BOOST = 1.2;
-const DILATION = 1.0;
 
 // apply half-circle s-curve to distance for sharper (more pixelated) interpolation
 fn curve_distance(x: f32, sharp: f32) -> f32 {
@@ -109,7 +113,7 @@ fn filter_lanczos(coeffs: vec4<f32>, color_matrix: mat4x4<f32>) -> vec3<f32> {
 }
 
 fn dilate(col: vec4<f32>) -> vec4<f32> {
-    let x = mix(vec4<f32>(1.0), col, DILATION);
+    let x = mix(vec4<f32>(1.0), col, params.dilation);
 
     return col * x;
 }
@@ -132,8 +136,10 @@ fn fs_main(
     let pix_co = v_uv * tex_dims - vec2<f32>(0.5, 0.5);
     let tex_co = (floor(pix_co) + vec2<f32>(0.5, 0.5)) * inv_tex_dims;
     let dist = fract(pix_co);
+    // Keep the source alpha so translucent windows stay translucent
+    let alpha = textureSample(tex, tex_sampler, v_uv).a;
 
-    var curve_x = curve_distance(dist.x, SHARPNESS_H * SHARPNESS_H);
+    var curve_x = curve_distance(dist.x, params.sharpness_h * params.sharpness_h);
     var coeffs = PI * vec4<f32>(1.0 + curve_x, curve_x, 1.0 - curve_x, 2.0 - curve_x);
 
     coeffs = max(abs(coeffs), vec4(1e-5));
@@ -145,17 +151,17 @@ fn fs_main(
     var col = filter_lanczos(coeffs, get_color_matrix(tex_co, dx));
     var col2 = filter_lanczos(coeffs, get_color_matrix(tex_co + dy, dx));
 
-    col = mix(col, col2, curve_distance(dist.y, SHARPNESS_V));
-    col = pow(col, vec3<f32>(GAMMA_INPUT / (DILATION + 1.0)));
+    col = mix(col, col2, curve_distance(dist.y, params.sharpness_v));
+    col = pow(col, vec3<f32>(params.gamma_input / (params.dilation + 1.0)));
 
     let luma = dot(vec3<f32>(0.2126, 0.7152, 0.0722), col);
     let bright = (max(col.r, max(col.g, col.b)) + luma) * 0.5;
     let scan_bright = clamp(bright, SCANLINE_BRIGHT_MIN, SCANLINE_BRIGHT_MAX);
     let scan_beam = clamp(bright * SCANLINE_BEAM_WIDTH_MAX, SCANLINE_BEAM_WIDTH_MIN, SCANLINE_BEAM_WIDTH_MAX);
-    var scan_weight = 1.0 - pow(cos(v_uv.y * 2.0 * PI * tex_dims.y) * 0.5 + 0.5, scan_beam) * SCANLINE_STRENGTH;
+    var scan_weight = 1.0 - pow(cos(v_uv.y * 2.0 * PI * tex_dims.y) * 0.5 + 0.5, scan_beam) * params.scanline_strength;
 
     let insize = tex_dims;
-    let mask = 1.0 - MASK_STRENGTH;
+    let mask = 1.0 - params.mask_strength;
     let mod_fac = floor(v_uv * out.screen_size * tex_dims / (insize * vec2<f32>(MASK_SIZE, MASK_DOT_HEIGHT * MASK_SIZE)));
     let dot_no = i32(((mod_fac.x + (mod_fac.y % 2.0) * MASK_STAGGER) / MASK_DOT_WIDTH % 3.0));
 
@@ -176,7 +182,7 @@ fn fs_main(
     col *= vec3<f32>(scan_weight);
     col = mix(col, col2, scan_bright);
     col *= mask_weight;
-    col = pow(col, vec3<f32>(1.0 / GAMMA_OUTPUT));
+    col = pow(col, vec3<f32>(1.0 / params.gamma_output));
 
-    return vec4<f32>(col * BRIGHT_BOOST, 1.0);
+    return vec4<f32>(col * params.bright_boost, alpha);
 }
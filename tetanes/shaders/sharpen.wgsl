@@ -0,0 +1,64 @@
+//  Sharpen Shader
+//
+//  A simple unsharp mask that boosts the difference between each pixel and its neighbors, useful
+//  after blurrier passes like CRT or NTSC filtering.
+
+var<private> vertices: array<vec2<f32>, 3> = array<vec2<f32>, 3>(
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(3.0, -1.0),
+    vec2<f32>(-1.0, 3.0),
+);
+
+// Vertex shader
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) inv_tex_dims: vec2<f32>,
+    @location(1) v_uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) v_idx: u32
+) -> VertexOutput {
+    var out: VertexOutput;
+    let vert = vertices[v_idx];
+
+    // Convert x from -1.0..1.0 to 0.0..1.0 and y from -1.0..1.0 to 1.0..0.0
+    out.position = vec4(vert, 0.0, 1.0);
+    out.inv_tex_dims = 1.0 / vec2<f32>(textureDimensions(tex));
+    out.v_uv = fma(vert, vec2(0.5, -0.5), vec2(0.5, 0.5));
+    return out;
+}
+
+// Fragment shader
+
+@group(1) @binding(0) var tex: texture_2d<f32>;
+@group(1) @binding(1) var tex_sampler: sampler;
+
+struct Params {
+    strength: f32,
+    // Uniform buffers need to be at least 16 bytes in WebGL.
+    // See https://github.com/gfx-rs/wgpu/issues/2072
+    _padding: vec3<f32>,
+}
+@group(1) @binding(2) var<uniform> params: Params;
+
+@fragment
+fn fs_main(
+    @location(0) inv_tex_dims: vec2<f32>,
+    @location(1) v_uv: vec2<f32>
+) -> @location(0) vec4<f32> {
+    let dx = vec2<f32>(inv_tex_dims.x, 0.0);
+    let dy = vec2<f32>(0.0, inv_tex_dims.y);
+
+    let center_rgba = textureSample(tex, tex_sampler, v_uv);
+    let center = center_rgba.rgb;
+    let neighbors = textureSample(tex, tex_sampler, v_uv - dx).rgb
+        + textureSample(tex, tex_sampler, v_uv + dx).rgb
+        + textureSample(tex, tex_sampler, v_uv - dy).rgb
+        + textureSample(tex, tex_sampler, v_uv + dy).rgb;
+    let col = center + (4.0 * center - neighbors) * params.strength;
+
+    return vec4<f32>(clamp(col, vec3<f32>(0.0), vec3<f32>(1.0)), center_rgba.a);
+}
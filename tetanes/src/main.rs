@@ -2,6 +2,7 @@
 //!
 //! USAGE:
 //!     tetanes [FLAGS] [OPTIONS] [path]
+//!     tetanes test <spec>
 //!
 //! FLAGS:
 //!     -f, --fullscreen    Start fullscreen.
@@ -42,6 +43,24 @@ fn main() -> anyhow::Result<()> {
                 let opts = opts::Opts::parse();
                 tracing::debug!("CLI Options: {opts:?}");
 
+                if let Some(opts::Command::Test { spec }) = &opts.command {
+                    return tetanes::nes::test_runner::run(spec);
+                }
+
+                if opts.install_associations || opts.uninstall_associations {
+                    use tetanes::platform::associations;
+
+                    let extensions = associations::extensions().collect::<Vec<_>>().join(", .");
+                    if opts.install_associations {
+                        associations::install()?;
+                        println!("Registered .{extensions} file associations");
+                    } else {
+                        associations::uninstall()?;
+                        println!("Removed .{extensions} file associations");
+                    }
+                    return Ok(());
+                }
+
                 opts.load()?
             }
         }
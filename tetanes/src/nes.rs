@@ -4,17 +4,19 @@ use crate::{
     nes::{
         event::NesEventProxy,
         input::{Gamepads, InputBindings},
-        renderer::{painter::Painter, FrameRecycle, Resources},
+        renderer::{gui::MessageType, painter::Painter, FrameRecycle, Resources},
     },
     platform::Initialize,
 };
 use anyhow::Context;
+use attract::Attract;
 use cfg_if::cfg_if;
 use config::Config;
 use crossbeam::channel::Receiver;
 use egui::ahash::HashMap;
 use emulation::Emulation;
 use event::NesEvent;
+use hooks::{HookEvent, Hooks};
 use renderer::Renderer;
 use std::sync::Arc;
 use tetanes_core::{time::Instant, video::Frame};
@@ -26,14 +28,26 @@ use winit::{
 };
 
 pub mod action;
+pub mod annotation;
+pub mod api;
+pub mod attract;
 pub mod audio;
+pub mod audit;
 pub mod config;
 pub mod emulation;
 pub mod event;
+pub mod hooks;
+pub mod import;
 pub mod input;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod instance;
+pub mod netplay;
 pub mod renderer;
 pub mod rom;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod test_runner;
 pub mod version;
+pub mod widget;
 
 /// Represents all the NES Emulation state.
 #[derive(Debug)]
@@ -46,6 +60,10 @@ pub struct Nes {
     /// Initially `Suspended`. `Pending` after `Resume` event received and spanwed. `Running` after
     /// resources future completes.
     pub(crate) state: State,
+    /// Events received before running, handled in order once resources are ready.
+    pub(crate) pending_events: Vec<NesEvent>,
+    /// Hooks added before running, moved to `Running` once resources are ready.
+    pub(crate) hooks: Hooks,
 }
 
 #[derive(Debug, Default)]
@@ -110,7 +128,11 @@ pub(crate) struct Running {
     pub(crate) replay_recording: bool,
     pub(crate) audio_recording: bool,
     pub(crate) rewinding: bool,
+    /// Whether emulation was paused because the Player One gamepad disconnected.
+    pub(crate) gamepad_disconnect_paused: bool,
     pub(crate) repaint_times: HashMap<WindowId, Instant>,
+    pub(crate) attract: Attract,
+    pub(crate) hooks: Hooks,
 }
 
 impl Nes {
@@ -120,6 +142,26 @@ impl Nes {
     ///
     /// If event loop fails to build or run, then an error is returned.
     pub fn run(cfg: Config) -> anyhow::Result<()> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let instance = if cfg.emulation.allow_multiple_instances {
+            None
+        } else {
+            let rom = cfg
+                .renderer
+                .roms_path
+                .as_deref()
+                .filter(|path| path.is_file());
+            match instance::Instance::acquire(rom) {
+                Ok(Some(instance)) => Some(instance),
+                // Handed off to the running instance
+                Ok(None) => return Ok(()),
+                Err(err) => {
+                    tracing::error!("failed to acquire single instance lock: {err:?}");
+                    None
+                }
+            }
+        };
+
         // Set up window, events and NES state
         let event_loop = EventLoop::<NesEvent>::with_user_event().build()?;
         let nes = Nes::new(cfg, &event_loop);
@@ -128,6 +170,9 @@ impl Nes {
                 use winit::platform::web::EventLoopExtWebSys;
                 event_loop.spawn_app(nes);
             } else {
+                if let Some(instance) = instance {
+                    instance.listen(NesEventProxy::new(&event_loop))?;
+                }
                 let mut nes = nes;
                 event_loop.run_app(&mut nes)?;
             }
@@ -140,6 +185,16 @@ impl Nes {
         Self {
             init_state: Some((cfg, NesEventProxy::new(event_loop))),
             state: State::Suspended,
+            pending_events: Vec::new(),
+            hooks: Hooks::default(),
+        }
+    }
+
+    /// Add a hook called with high-level events, e.g. when a ROM is loaded. See [`hooks`].
+    pub fn add_hook(&mut self, hook: impl FnMut(&HookEvent) + 'static) {
+        match &mut self.state {
+            State::Running(running) => running.hooks.add(hook),
+            _ => self.hooks.add(hook),
         }
     }
 
@@ -199,7 +254,10 @@ impl Nes {
                 cfg.input.update_gamepad_assignments(&gamepads);
 
                 let emulation = Emulation::new(tx.clone(), frame_tx.clone(), &cfg)?;
-                let renderer = Renderer::new(tx.clone(), resources, frame_rx, &cfg)?;
+                let mut renderer = Renderer::new(tx.clone(), resources, frame_rx, &cfg)?;
+                if let Some(warning) = Config::take_load_warning() {
+                    renderer.add_message(MessageType::Warn, warning);
+                }
 
                 let mut running = Running {
                     cfg,
@@ -213,7 +271,10 @@ impl Nes {
                     replay_recording: false,
                     audio_recording: false,
                     rewinding: false,
+                    gamepad_disconnect_paused: false,
                     repaint_times: HashMap::default(),
+                    attract: Attract::new(),
+                    hooks: std::mem::take(&mut self.hooks),
                 };
                 running.initialize()?;
                 self.state = State::Running(running);
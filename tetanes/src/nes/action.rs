@@ -37,7 +37,7 @@ impl Ord for Action {
 }
 
 impl Action {
-    pub const BINDABLE: [Self; 111] = [
+    pub const BINDABLE: [Self; 139] = [
         Self::Ui(Ui::Quit),
         Self::Ui(Ui::TogglePause),
         Self::Ui(Ui::LoadRom),
@@ -47,28 +47,45 @@ impl Action {
         Self::Menu(Menu::Keybinds),
         Self::Menu(Menu::PerfStats),
         Self::Menu(Menu::Preferences),
+        Self::Menu(Menu::QuickSettings),
+        Self::Menu(Menu::Bookmarks),
         Self::Feature(Feature::ToggleReplayRecording),
+        Self::Feature(Feature::SaveBackgroundReplay),
         Self::Feature(Feature::ToggleAudioRecording),
         Self::Feature(Feature::VisualRewind),
         Self::Feature(Feature::InstantRewind),
         Self::Feature(Feature::TakeScreenshot),
+        Self::Feature(Feature::SaveClip),
+        Self::Feature(Feature::CopyFrame),
+        Self::Feature(Feature::CopyDisplayedFrame),
+        Self::Feature(Feature::CopyFrameInfo),
         Self::Setting(Setting::ToggleFullscreen),
         Self::Setting(Setting::ToggleEmbedViewports),
         Self::Setting(Setting::ToggleAlwaysOnTop),
+        Self::Setting(Setting::ToggleTranslucent),
+        Self::Setting(Setting::ToggleClickThrough),
         Self::Setting(Setting::ToggleAudio),
         Self::Setting(Setting::ToggleCycleAccurate),
         Self::Setting(Setting::ToggleRewinding),
         Self::Setting(Setting::ToggleOverscan),
+        Self::Setting(Setting::ToggleRomOverscan),
+        Self::Setting(Setting::ToggleOverscanOutline),
         Self::Setting(Setting::ToggleMenubar),
         Self::Setting(Setting::ToggleMessages),
+        Self::Setting(Setting::ToggleAudioScope),
         Self::Setting(Setting::ToggleFps),
         Self::Setting(Setting::FastForward),
         Self::Setting(Setting::IncrementScale),
         Self::Setting(Setting::DecrementScale),
+        Self::Setting(Setting::ResizeToScale(2)),
+        Self::Setting(Setting::ResizeToScale(3)),
+        Self::Setting(Setting::ResizeToScale(4)),
         Self::Setting(Setting::IncrementSpeed),
         Self::Setting(Setting::DecrementSpeed),
         Self::Deck(DeckAction::Reset(ResetKind::Soft)),
         Self::Deck(DeckAction::Reset(ResetKind::Hard)),
+        Self::Deck(DeckAction::TogglePower),
+        Self::Deck(DeckAction::HoldReset),
         Self::Deck(DeckAction::Joypad((Player::One, JoypadBtn::Left))),
         Self::Deck(DeckAction::Joypad((Player::One, JoypadBtn::Right))),
         Self::Deck(DeckAction::Joypad((Player::One, JoypadBtn::Up))),
@@ -112,9 +129,12 @@ impl Action {
         Self::Deck(DeckAction::ToggleZapperConnected),
         // Self::Deck(DeckAction::ZapperAim), // Binding doesn't make sense
         Self::Deck(DeckAction::ZapperTrigger),
+        Self::Deck(DeckAction::InsertCoin(0)),
+        Self::Deck(DeckAction::InsertCoin(1)),
         Self::Deck(DeckAction::FourPlayer(FourPlayer::Disabled)),
         Self::Deck(DeckAction::FourPlayer(FourPlayer::FourScore)),
-        Self::Deck(DeckAction::FourPlayer(FourPlayer::Satellite)),
+        Self::Deck(DeckAction::FourPlayer(FourPlayer::Famicom)),
+        Self::Deck(DeckAction::FourPlayer(FourPlayer::Auto)),
         // Only allow bindings up to 8 slots
         Self::Deck(DeckAction::SetSaveSlot(1)),
         Self::Deck(DeckAction::SetSaveSlot(2)),
@@ -126,6 +146,12 @@ impl Action {
         Self::Deck(DeckAction::SetSaveSlot(8)),
         Self::Deck(DeckAction::SaveState),
         Self::Deck(DeckAction::LoadState),
+        Self::Feature(Feature::QuickSaveState),
+        Self::Feature(Feature::QuickLoadState),
+        Self::Feature(Feature::PersistQuickState),
+        Self::Feature(Feature::SaveCheckpoint),
+        Self::Feature(Feature::LoadPreviousCheckpoint),
+        Self::Feature(Feature::AddBookmark),
         Self::Deck(DeckAction::ToggleApuChannel(Channel::Pulse1)),
         Self::Deck(DeckAction::ToggleApuChannel(Channel::Pulse2)),
         Self::Deck(DeckAction::ToggleApuChannel(Channel::Triangle)),
@@ -151,6 +177,7 @@ impl Action {
         Self::Deck(DeckAction::SetNesRegion(NesRegion::Ntsc)),
         Self::Deck(DeckAction::SetNesRegion(NesRegion::Pal)),
         Self::Deck(DeckAction::SetNesRegion(NesRegion::Dendy)),
+        Self::Deck(DeckAction::SetNesRegion(NesRegion::PalM)),
         Self::Deck(DeckAction::SetVideoFilter(VideoFilter::Pixellate)),
         Self::Deck(DeckAction::SetVideoFilter(VideoFilter::Ntsc)),
         Self::Debug(Debug::Toggle(DebugKind::Cpu)),
@@ -161,6 +188,7 @@ impl Action {
         Self::Debug(Debug::Step(DebugStep::Over)),
         Self::Debug(Debug::Step(DebugStep::Scanline)),
         Self::Debug(Debug::Step(DebugStep::Frame)),
+        Self::Debug(Debug::Step(DebugStep::Back)),
     ];
 
     pub const fn is_joypad(&self) -> bool {
@@ -192,31 +220,56 @@ impl AsRef<str> for Action {
                 Menu::About => "Toggle About",
                 Menu::Keybinds => "Toggle Keybinds",
                 Menu::PerfStats => "Toggle Performance Stats",
+                Menu::CpuDebugger => "Toggle CPU Debugger",
                 Menu::PpuViewer => "Toggle PPU Viewer",
+                Menu::InterruptLog => "Toggle Interrupt Log",
+                Menu::MemorySearch => "Toggle Memory Search",
                 Menu::Preferences => "Toggle Preferences",
+                Menu::QuickSettings => "Toggle Quick Settings",
+                Menu::Bookmarks => "Toggle Bookmarks",
             },
             Action::Feature(feature) => match feature {
                 Feature::ToggleReplayRecording => "Toggle Replay Recording",
+                Feature::SaveBackgroundReplay => "Save Background Replay",
+                Feature::QuickSaveState => "Quick Save State",
+                Feature::QuickLoadState => "Quick Load State",
+                Feature::PersistQuickState => "Persist Quick State to Disk",
+                Feature::SaveCheckpoint => "Save Checkpoint",
+                Feature::LoadPreviousCheckpoint => "Load Previous Checkpoint",
+                Feature::AddBookmark => "Add Bookmark",
                 Feature::ToggleAudioRecording => "Toggle Audio Recording",
                 Feature::VisualRewind => "Visual Rewind",
                 Feature::InstantRewind => "Instant Rewind",
                 Feature::TakeScreenshot => "Take Screenshot",
+                Feature::SaveClip => "Save Clip",
+                Feature::CopyFrame => "Copy Frame",
+                Feature::CopyDisplayedFrame => "Copy Displayed Frame",
+                Feature::CopyFrameInfo => "Copy ROM Name and Frame",
             },
             Action::Setting(setting) => match setting {
                 Setting::ToggleFullscreen => "Toggle Fullscreen",
                 Setting::ToggleEmbedViewports => "Toggle Embed Viewports",
                 Setting::ToggleAlwaysOnTop => "Toggle Always On Top",
+                Setting::ToggleTranslucent => "Toggle Translucent Window",
+                Setting::ToggleClickThrough => "Toggle Click-Through",
                 Setting::ToggleAudio => "Toggle Audio",
                 Setting::ToggleCycleAccurate => "Toggle Cycle Accurate",
                 Setting::ToggleRewinding => "Toggle Rewinding",
                 Setting::ToggleOverscan => "Toggle Overscan",
+                Setting::ToggleRomOverscan => "Toggle Overscan for ROM",
+                Setting::ToggleOverscanOutline => "Toggle Overscan Outline",
                 Setting::ToggleMenubar => "Toggle Menubar",
                 Setting::ToggleMessages => "Toggle Messages",
+                Setting::ToggleAudioScope => "Toggle Audio Scope",
                 Setting::ToggleScreenReader => "Toggle Screen Reader",
                 Setting::ToggleFps => "Toggle FPS",
                 Setting::FastForward => "Fast Forward",
                 Setting::IncrementScale => "Scale Increment",
                 Setting::DecrementScale => "Scale Decrement",
+                Setting::ResizeToScale(2) => "Resize Window to 2x",
+                Setting::ResizeToScale(3) => "Resize Window to 3x",
+                Setting::ResizeToScale(4) => "Resize Window to 4x",
+                Setting::ResizeToScale(_) => "Resize Window to Nx",
                 Setting::IncrementSpeed => "Speed Increment",
                 Setting::DecrementSpeed => "Speed Increment",
             },
@@ -225,6 +278,8 @@ impl AsRef<str> for Action {
                     ResetKind::Soft => "Reset",
                     ResetKind::Hard => "Power Cycle",
                 },
+                DeckAction::TogglePower => "Power Switch",
+                DeckAction::HoldReset => "Reset Button",
                 DeckAction::Joypad((_, joypad)) => match joypad {
                     JoypadBtn::Left => "Joypad Left",
                     JoypadBtn::Right => "Joypad Right",
@@ -241,9 +296,12 @@ impl AsRef<str> for Action {
                 DeckAction::ZapperAim(_) => "Zapper Aim",
                 DeckAction::ZapperAimOffscreen => "Zapper Aim Offscreen (Hold)",
                 DeckAction::ZapperTrigger => "Zapper Trigger",
+                DeckAction::InsertCoin(0) => "Insert Coin 1",
+                DeckAction::InsertCoin(_) => "Insert Coin 2",
                 DeckAction::FourPlayer(FourPlayer::Disabled) => "4-Player Disable",
                 DeckAction::FourPlayer(FourPlayer::FourScore) => "4-Player Enable (FourScore)",
-                DeckAction::FourPlayer(FourPlayer::Satellite) => "4-Player Enable (Satellite)",
+                DeckAction::FourPlayer(FourPlayer::Famicom) => "4-Player Enable (Famicom)",
+                DeckAction::FourPlayer(FourPlayer::Auto) => "4-Player Auto",
                 DeckAction::SetSaveSlot(1) => "Set Save Slot 1",
                 DeckAction::SetSaveSlot(2) => "Set Save Slot 2",
                 DeckAction::SetSaveSlot(3) => "Set Save Slot 3",
@@ -279,6 +337,7 @@ impl AsRef<str> for Action {
                     NesRegion::Ntsc => "Set Region to NTSC",
                     NesRegion::Pal => "Set Region to PAL",
                     NesRegion::Dendy => "Set Region to Dendy",
+                    NesRegion::PalM => "Set Region to PAL-M",
                 },
                 DeckAction::SetVideoFilter(filter) => match filter {
                     VideoFilter::Pixellate => "Set Filter to Pixellate",
@@ -287,7 +346,7 @@ impl AsRef<str> for Action {
             },
             Action::Debug(debug) => match debug {
                 Debug::Toggle(debugger) => match debugger {
-                    DebugKind::Cpu => "Toggle Debugger",
+                    DebugKind::Cpu => "Toggle CPU Debugger",
                     DebugKind::Ppu => "Toggle PPU Viewer",
                     DebugKind::Apu => "Toggle APU Mixer",
                 },
@@ -297,6 +356,7 @@ impl AsRef<str> for Action {
                     DebugStep::Over => "Debug Step Over",
                     DebugStep::Scanline => "Debug Step Scanline",
                     DebugStep::Frame => "Debug Step Frame",
+                    DebugStep::Back => "Debug Step Back",
                 },
             },
         }
@@ -317,29 +377,54 @@ impl TryFrom<&str> for Action {
             "Toggle Keybinds Menu" => Self::Menu(Menu::Keybinds),
             "Toggle Performance Stats Window" => Self::Menu(Menu::PerfStats),
             "Toggle PPU Viewer" => Self::Menu(Menu::PpuViewer),
+            "Toggle Interrupt Log" => Self::Menu(Menu::InterruptLog),
+            "Toggle Memory Search" => Self::Menu(Menu::MemorySearch),
             "Toggle Preferences Menu" => Self::Menu(Menu::Preferences),
+            "Toggle Quick Settings" => Self::Menu(Menu::QuickSettings),
+            "Toggle Bookmarks" => Self::Menu(Menu::Bookmarks),
             "Toggle Replay Recording" => Self::Feature(Feature::ToggleReplayRecording),
+            "Save Background Replay" => Self::Feature(Feature::SaveBackgroundReplay),
+            "Quick Save State" => Self::Feature(Feature::QuickSaveState),
+            "Quick Load State" => Self::Feature(Feature::QuickLoadState),
+            "Persist Quick State to Disk" => Self::Feature(Feature::PersistQuickState),
+            "Save Checkpoint" => Self::Feature(Feature::SaveCheckpoint),
+            "Load Previous Checkpoint" => Self::Feature(Feature::LoadPreviousCheckpoint),
+            "Add Bookmark" => Self::Feature(Feature::AddBookmark),
             "Toggle Audio Recording" => Self::Feature(Feature::ToggleAudioRecording),
             "Visual Rewind" => Self::Feature(Feature::VisualRewind),
             "Instant Rewind" => Self::Feature(Feature::InstantRewind),
             "Take Screenshot" => Self::Feature(Feature::TakeScreenshot),
+            "Save Clip" => Self::Feature(Feature::SaveClip),
+            "Copy Frame" => Self::Feature(Feature::CopyFrame),
+            "Copy Displayed Frame" => Self::Feature(Feature::CopyDisplayedFrame),
+            "Copy ROM Name and Frame" => Self::Feature(Feature::CopyFrameInfo),
             "Toggle Fullscreen" => Self::Setting(Setting::ToggleFullscreen),
             "Toggle Embed Viewports" => Self::Setting(Setting::ToggleEmbedViewports),
             "Toggle Always On Top" => Self::Setting(Setting::ToggleAlwaysOnTop),
+            "Toggle Translucent Window" => Self::Setting(Setting::ToggleTranslucent),
+            "Toggle Click-Through" => Self::Setting(Setting::ToggleClickThrough),
             "Toggle Audio" => Self::Setting(Setting::ToggleAudio),
             "Toggle Cycle Accurate" => Self::Setting(Setting::ToggleCycleAccurate),
             "Toggle Rewinding" => Self::Setting(Setting::ToggleRewinding),
             "Toggle Overscan" => Self::Setting(Setting::ToggleOverscan),
+            "Toggle Overscan for ROM" => Self::Setting(Setting::ToggleRomOverscan),
+            "Toggle Overscan Outline" => Self::Setting(Setting::ToggleOverscanOutline),
             "Toggle Menubar" => Self::Setting(Setting::ToggleMenubar),
             "Toggle Messages" => Self::Setting(Setting::ToggleMessages),
+            "Toggle Audio Scope" => Self::Setting(Setting::ToggleAudioScope),
             "Toggle FPS" => Self::Setting(Setting::ToggleFps),
             "Fast Forward" => Self::Setting(Setting::FastForward),
             "Increment Scale" => Self::Setting(Setting::IncrementScale),
             "Decrement Scale" => Self::Setting(Setting::DecrementScale),
+            "Resize Window to 2x" => Self::Setting(Setting::ResizeToScale(2)),
+            "Resize Window to 3x" => Self::Setting(Setting::ResizeToScale(3)),
+            "Resize Window to 4x" => Self::Setting(Setting::ResizeToScale(4)),
             "Increment Speed" => Self::Setting(Setting::IncrementSpeed),
             "Decrement Speed" => Self::Setting(Setting::DecrementSpeed),
             "Reset" => Self::Deck(DeckAction::Reset(ResetKind::Soft)),
             "Power Cycle" => Self::Deck(DeckAction::Reset(ResetKind::Hard)),
+            "Power Switch" => Self::Deck(DeckAction::TogglePower),
+            "Reset Button" => Self::Deck(DeckAction::HoldReset),
             "Joypad Left (P1)" => Self::Deck(DeckAction::Joypad((Player::One, JoypadBtn::Left))),
             "Joypad Right (P1)" => Self::Deck(DeckAction::Joypad((Player::One, JoypadBtn::Right))),
             "Joypad Up (P1)" => Self::Deck(DeckAction::Joypad((Player::One, JoypadBtn::Up))),
@@ -360,13 +445,16 @@ impl TryFrom<&str> for Action {
             "Zapper Aim" => Self::Deck(DeckAction::ZapperAim((0, 0))),
             "Zapper Aim Offscreen (Hold)" => Self::Deck(DeckAction::ZapperAimOffscreen),
             "Zapper Trigger" => Self::Deck(DeckAction::ZapperTrigger),
+            "Insert Coin 1" => Self::Deck(DeckAction::InsertCoin(0)),
+            "Insert Coin 2" => Self::Deck(DeckAction::InsertCoin(1)),
             "Disable Four Player Mode" => Self::Deck(DeckAction::FourPlayer(FourPlayer::Disabled)),
             "Enable Four Player (FourScore)" => {
                 Self::Deck(DeckAction::FourPlayer(FourPlayer::FourScore))
             }
-            "Enable Four Player (Satellite)" => {
-                Self::Deck(DeckAction::FourPlayer(FourPlayer::Satellite))
+            "Enable Four Player (Famicom)" | "Enable Four Player (Satellite)" => {
+                Self::Deck(DeckAction::FourPlayer(FourPlayer::Famicom))
             }
+            "Auto Four Player" => Self::Deck(DeckAction::FourPlayer(FourPlayer::Auto)),
             "Set Save Slot 1" => Self::Deck(DeckAction::SetSaveSlot(1)),
             "Set Save Slot 2" => Self::Deck(DeckAction::SetSaveSlot(2)),
             "Set Save Slot 3" => Self::Deck(DeckAction::SetSaveSlot(3)),
@@ -404,6 +492,7 @@ impl TryFrom<&str> for Action {
             "Set Region to NTSC" => Self::Deck(DeckAction::SetNesRegion(NesRegion::Ntsc)),
             "Set Region to PAL" => Self::Deck(DeckAction::SetNesRegion(NesRegion::Pal)),
             "Set Region to Dendy" => Self::Deck(DeckAction::SetNesRegion(NesRegion::Dendy)),
+            "Set Region to PAL-M" => Self::Deck(DeckAction::SetNesRegion(NesRegion::PalM)),
             "Set Filter to Pixellate" => {
                 Self::Deck(DeckAction::SetVideoFilter(VideoFilter::Pixellate))
             }
@@ -416,6 +505,7 @@ impl TryFrom<&str> for Action {
             "Step Over (CPU Debugger)" => Self::Debug(Debug::Step(DebugStep::Over)),
             "Step Scanline (CPU Debugger)" => Self::Debug(Debug::Step(DebugStep::Scanline)),
             "Step Frame (CPU Debugger)" => Self::Debug(Debug::Step(DebugStep::Frame)),
+            "Step Back (CPU Debugger)" => Self::Debug(Debug::Step(DebugStep::Back)),
             _ => return Err(anyhow::anyhow!("Invalid action string")),
         })
     }
@@ -475,10 +565,25 @@ pub enum Ui {
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Feature {
     ToggleReplayRecording,
+    SaveBackgroundReplay,
+    QuickSaveState,
+    QuickLoadState,
+    PersistQuickState,
+    /// Capture a state into a ring of checkpoints, independent of save slots.
+    SaveCheckpoint,
+    /// Cycle backward through checkpoints, starting from the most recent.
+    LoadPreviousCheckpoint,
+    /// Bookmark the current frame with a save state to jump back to later.
+    AddBookmark,
     ToggleAudioRecording,
     VisualRewind,
     InstantRewind,
     TakeScreenshot,
+    /// Save the last few seconds of gameplay as an animated image.
+    SaveClip,
+    CopyFrame,
+    CopyDisplayedFrame,
+    CopyFrameInfo,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -486,17 +591,29 @@ pub enum Setting {
     ToggleFullscreen,
     ToggleEmbedViewports,
     ToggleAlwaysOnTop,
+    /// Show windows underneath through the game window.
+    ToggleTranslucent,
+    /// Pass mouse input through the game window while translucent.
+    ToggleClickThrough,
     ToggleAudio,
     ToggleCycleAccurate,
     ToggleRewinding,
     ToggleOverscan,
+    /// Toggle Hide Overscan for the loaded ROM only.
+    ToggleRomOverscan,
+    /// Outline the overscan area over the game while it's shown.
+    ToggleOverscanOutline,
     ToggleMenubar,
     ToggleMessages,
+    /// Draw an oscilloscope of the audio output over the game.
+    ToggleAudioScope,
     ToggleScreenReader,
     ToggleFps,
     FastForward,
     IncrementScale,
     DecrementScale,
+    /// Resize the window to a whole scale, accounting for the menubar.
+    ResizeToScale(u8),
     IncrementSpeed,
     DecrementSpeed,
 }
@@ -517,6 +634,7 @@ pub enum DebugStep {
     Over,
     Scanline,
     Frame,
+    Back,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -0,0 +1,175 @@
+//! User annotations of memory addresses, such as naming the RAM byte holding the player's lives.
+//!
+//! Annotations are saved per ROM, keyed by the CRC32 of its ROM data so they follow the game
+//! across renames, and are shared by the memory watch and interrupt log windows.
+
+use crate::nes::config::Config;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
+use tetanes_core::fs;
+use tracing::{info, warn};
+
+/// How the value at an annotated address is displayed.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub enum DataType {
+    /// Hexadecimal, followed by the unsigned value.
+    #[default]
+    Hex,
+    Unsigned,
+    Signed,
+    /// Binary-coded decimal, e.g. `$42` is `42`. Common for scores and timers.
+    Bcd,
+    /// Individual bits, e.g. a set of status flags.
+    Bits,
+}
+
+impl DataType {
+    pub const ALL: [Self; 5] = [
+        Self::Hex,
+        Self::Unsigned,
+        Self::Signed,
+        Self::Bcd,
+        Self::Bits,
+    ];
+
+    /// Format a byte value as this type.
+    #[must_use]
+    pub fn format(self, val: u8) -> String {
+        match self {
+            Self::Hex => format!("${val:02X} ({val})"),
+            Self::Unsigned => val.to_string(),
+            Self::Signed => (val as i8).to_string(),
+            Self::Bcd if val >> 4 < 10 && val & 0x0F < 10 => format!("{val:02X}"),
+            Self::Bcd => format!("${val:02X} (invalid BCD)"),
+            Self::Bits => format!("%{val:08b}"),
+        }
+    }
+}
+
+impl AsRef<str> for DataType {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Hex => "Hex",
+            Self::Unsigned => "Unsigned",
+            Self::Signed => "Signed",
+            Self::Bcd => "BCD",
+            Self::Bits => "Bits",
+        }
+    }
+}
+
+/// A user note about a memory address.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+#[serde(default)]
+pub struct Annotation {
+    pub name: String,
+    pub comment: String,
+    pub data_type: DataType,
+}
+
+impl Annotation {
+    /// Whether there's nothing worth saving.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.name.is_empty() && self.comment.is_empty() && self.data_type == DataType::default()
+    }
+}
+
+/// Annotations for the loaded ROM.
+#[derive(Default, Debug, Clone)]
+#[must_use]
+pub struct Annotations {
+    /// CRC32 of the ROM these belong to, or `None` if no ROM is loaded.
+    crc32: Option<u32>,
+    entries: BTreeMap<u16, Annotation>,
+}
+
+impl Annotations {
+    pub const DIR: &'static str = "annotations";
+    pub const EXTENSION: &'static str = "json";
+
+    #[must_use]
+    pub fn path(crc32: u32) -> PathBuf {
+        Config::default_data_dir()
+            .join(Self::DIR)
+            .join(format!("{crc32:08X}"))
+            .with_extension(Self::EXTENSION)
+    }
+
+    /// Load the annotations saved for a ROM, if any.
+    pub fn load(crc32: u32) -> Self {
+        let path = Self::path(crc32);
+        let entries = if fs::exists(&path) {
+            fs::load_raw(&path)
+                .context("failed to read annotations")
+                .and_then(|data| {
+                    serde_json::from_slice(&data).context("failed to deserialize annotations")
+                })
+                .unwrap_or_else(|err| {
+                    warn!("failed to load {path:?}: {err:?}");
+                    BTreeMap::new()
+                })
+        } else {
+            BTreeMap::new()
+        };
+        if !entries.is_empty() {
+            info!("loaded {} annotations from {path:?}", entries.len());
+        }
+        Self {
+            crc32: Some(crc32),
+            entries,
+        }
+    }
+
+    /// Save annotations for the loaded ROM.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(crc32) = self.crc32 else {
+            return Ok(());
+        };
+        let data =
+            serde_json::to_vec_pretty(&self.entries).context("failed to serialize annotations")?;
+        fs::save_raw_atomic(Self::path(crc32), &data).context("failed to save annotations")
+    }
+
+    #[must_use]
+    pub fn get(&self, addr: u16) -> Option<&Annotation> {
+        self.entries.get(&addr)
+    }
+
+    /// The annotated name for an address, if it has one.
+    #[must_use]
+    pub fn name(&self, addr: u16) -> Option<&str> {
+        self.get(addr)
+            .map(|annotation| annotation.name.as_str())
+            .filter(|name| !name.is_empty())
+    }
+
+    /// Format a byte value using the address's annotated data type.
+    #[must_use]
+    pub fn format(&self, addr: u16, val: u8) -> String {
+        self.get(addr)
+            .map_or_else(DataType::default, |annotation| annotation.data_type)
+            .format(val)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u16, &Annotation)> {
+        self.entries
+            .iter()
+            .map(|(&addr, annotation)| (addr, annotation))
+    }
+
+    /// Set the annotation for an address, removing it if empty, and save.
+    pub fn set(&mut self, addr: u16, annotation: Annotation) {
+        if annotation.is_empty() {
+            self.entries.remove(&addr);
+        } else {
+            self.entries.insert(addr, annotation);
+        }
+        if let Err(err) = self.save() {
+            warn!("{err:?}");
+        }
+    }
+}
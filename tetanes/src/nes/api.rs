@@ -0,0 +1,213 @@
+//! A typed API for embedding the [`Nes`] runtime in another application, e.g. a Tauri or bevy
+//! shell, and driving it programmatically.
+//!
+//! Internally, the event loop, emulation thread and renderer communicate with [`NesEvent`]s sent
+//! through a [`NesEventProxy`]. Those events change freely between releases as features are added.
+//! [`Command`] is the stable subset meant for downstream crates: it's `#[non_exhaustive]` so new
+//! commands can be added without breaking changes, and existing ones only change with a major
+//! version.
+//!
+//! ```no_run
+//! use tetanes::nes::{
+//!     api::{Command, NesBuilder},
+//!     event::NesEvent,
+//! };
+//! use tetanes_core::input::{JoypadBtn, Player};
+//! use winit::event_loop::EventLoop;
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let event_loop = EventLoop::<NesEvent>::with_user_event().build()?;
+//! let (mut nes, controller) = NesBuilder::new().rom("game.nes").build(&event_loop);
+//!
+//! std::thread::spawn(move || {
+//!     std::thread::sleep(std::time::Duration::from_secs(5));
+//!     controller.send(Command::Joypad {
+//!         player: Player::One,
+//!         button: JoypadBtn::Start,
+//!         pressed: true,
+//!     })
+//! });
+//!
+//! event_loop.run_app(&mut nes)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::nes::{
+    config::Config,
+    event::{ConfigEvent, EmulationEvent, NesEvent, NesEventProxy, UiEvent},
+    hooks::{HookEvent, Hooks},
+    renderer::gui::MessageType,
+    rom::RomData,
+    Nes, RunState,
+};
+use std::path::PathBuf;
+use tetanes_core::{
+    common::{NesRegion, ResetKind},
+    input::{JoypadBtn, Player},
+};
+use thiserror::Error;
+use winit::{event::ElementState, event_loop::EventLoop};
+
+/// A command to control a running [`Nes`].
+///
+/// Commands sent before the window and emulation have finished starting up are queued and handled
+/// in order once they're ready.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Command {
+    /// Load a ROM from disk.
+    LoadRomPath(PathBuf),
+    /// Load a ROM from memory, e.g. one bundled with the application.
+    LoadRom {
+        name: String,
+        data: Vec<u8>,
+    },
+    UnloadRom,
+    /// Play back a replay recording from disk.
+    LoadReplayPath(PathBuf),
+    /// Pause or resume emulation.
+    Pause(bool),
+    Reset(ResetKind),
+    /// Press or release a controller button.
+    Joypad {
+        player: Player,
+        button: JoypadBtn,
+        pressed: bool,
+    },
+    /// Save the current state to a save slot.
+    SaveState(u8),
+    /// Load the state from a save slot.
+    LoadState(u8),
+    /// Set the emulation speed, e.g. `2.0` for double speed.
+    Speed(f32),
+    Region(NesRegion),
+    Fullscreen(bool),
+    /// Save a screenshot to the pictures directory.
+    Screenshot,
+    /// Show a message in the UI.
+    Message(String),
+    Quit,
+}
+
+impl From<Command> for NesEvent {
+    fn from(command: Command) -> Self {
+        match command {
+            Command::LoadRomPath(path) => EmulationEvent::LoadRomPath(path).into(),
+            Command::LoadRom { name, data } => {
+                EmulationEvent::LoadRom((name, RomData(data))).into()
+            }
+            Command::UnloadRom => EmulationEvent::UnloadRom.into(),
+            Command::LoadReplayPath(path) => EmulationEvent::LoadReplayPath(path).into(),
+            Command::Pause(paused) => EmulationEvent::RunState(if paused {
+                RunState::ManuallyPaused
+            } else {
+                RunState::Running
+            })
+            .into(),
+            Command::Reset(kind) => EmulationEvent::Reset(kind).into(),
+            Command::Joypad {
+                player,
+                button,
+                pressed,
+            } => {
+                let state = if pressed {
+                    ElementState::Pressed
+                } else {
+                    ElementState::Released
+                };
+                EmulationEvent::Joypad((player, button, state)).into()
+            }
+            Command::SaveState(slot) => EmulationEvent::SaveState(slot).into(),
+            Command::LoadState(slot) => EmulationEvent::LoadState(slot).into(),
+            Command::Speed(speed) => ConfigEvent::Speed(speed).into(),
+            Command::Region(region) => ConfigEvent::Region(region).into(),
+            Command::Fullscreen(fullscreen) => ConfigEvent::Fullscreen(fullscreen).into(),
+            Command::Screenshot => EmulationEvent::Screenshot.into(),
+            Command::Message(text) => UiEvent::Message((MessageType::Info, text)).into(),
+            Command::Quit => UiEvent::Terminate.into(),
+        }
+    }
+}
+
+/// Returned when sending a [`Command`] after the event loop has exited.
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+#[error("the event loop has exited")]
+pub struct EventLoopClosed;
+
+/// Sends [`Command`]s to a [`Nes`] from any thread.
+///
+/// Unlike [`NesEventProxy`], sending never exits the process if the event loop has closed.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct Controller {
+    proxy: NesEventProxy,
+}
+
+impl Controller {
+    /// Create a controller for the [`Nes`] running on an event loop.
+    pub fn new(event_loop: &EventLoop<NesEvent>) -> Self {
+        Self {
+            proxy: NesEventProxy::new(event_loop),
+        }
+    }
+
+    /// Send a command to the [`Nes`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event loop has exited.
+    pub fn send(&self, command: Command) -> Result<(), EventLoopClosed> {
+        self.proxy
+            .inner()
+            .send_event(command.into())
+            .map_err(|_| EventLoopClosed)
+    }
+}
+
+/// Builds a [`Nes`] along with a [`Controller`] to drive it.
+#[derive(Default, Debug)]
+#[must_use]
+pub struct NesBuilder {
+    cfg: Option<Config>,
+    rom: Option<PathBuf>,
+    hooks: Hooks,
+}
+
+impl NesBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use a configuration instead of the one saved by the user, if any.
+    pub fn config(mut self, cfg: Config) -> Self {
+        self.cfg = Some(cfg);
+        self
+    }
+
+    /// Load a ROM, or show ROMs in a directory, on start.
+    pub fn rom(mut self, path: impl Into<PathBuf>) -> Self {
+        self.rom = Some(path.into());
+        self
+    }
+
+    /// Add a hook called with high-level events, e.g. to update rich presence when a ROM is
+    /// loaded.
+    pub fn hook(mut self, hook: impl FnMut(&HookEvent) + 'static) -> Self {
+        self.hooks.add(hook);
+        self
+    }
+
+    /// Create the [`Nes`] to run on an event loop, e.g. with
+    /// [`EventLoop::run_app`](winit::event_loop::EventLoop::run_app).
+    pub fn build(self, event_loop: &EventLoop<NesEvent>) -> (Nes, Controller) {
+        let mut cfg = self.cfg.unwrap_or_else(|| Config::load(None));
+        if let Some(rom) = self.rom {
+            cfg.renderer.roms_path = Some(rom);
+        }
+        let mut nes = Nes::new(cfg, event_loop);
+        nes.hooks = self.hooks;
+        (nes, Controller::new(event_loop))
+    }
+}
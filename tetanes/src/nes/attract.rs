@@ -0,0 +1,73 @@
+//! Attract mode, playing a demo as a screensaver after being idle without a ROM loaded.
+//!
+//! When started, the configured ROM is loaded along with an optional replay to drive it, otherwise
+//! the next bundled homebrew ROM is played. Any input stops the demo and unloads the ROM.
+
+use crate::nes::{
+    config::AttractModeConfig,
+    event::{EmulationEvent, NesEventProxy},
+    rom::HOMEBREW_ROMS,
+};
+use tetanes_core::time::{Duration, Instant};
+use tracing::debug;
+
+#[derive(Debug)]
+#[must_use]
+pub struct Attract {
+    last_input: Instant,
+    active: bool,
+    /// Index of the next bundled homebrew ROM to play.
+    next_rom: usize,
+}
+
+impl Default for Attract {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Attract {
+    pub fn new() -> Self {
+        Self {
+            last_input: Instant::now(),
+            active: false,
+            next_rom: 0,
+        }
+    }
+
+    /// Record user input, stopping the demo if one is playing. Returns `true` if a demo was
+    /// stopped, in which case the input should be ignored.
+    pub fn on_input(&mut self, tx: &NesEventProxy) -> bool {
+        self.last_input = Instant::now();
+        if self.active {
+            debug!("stopping attract mode");
+            self.active = false;
+            tx.event(EmulationEvent::UnloadRom);
+            return true;
+        }
+        false
+    }
+
+    /// Start the demo if idle long enough without a ROM loaded.
+    pub fn update(&mut self, tx: &NesEventProxy, cfg: &AttractModeConfig, rom_loaded: bool) {
+        let idle = Duration::from_secs(u64::from(cfg.idle_minutes) * 60);
+        if !cfg.enabled || self.active || rom_loaded || self.last_input.elapsed() < idle {
+            return;
+        }
+
+        debug!("starting attract mode");
+        self.active = true;
+        match &cfg.rom {
+            Some(rom) => {
+                tx.event(EmulationEvent::LoadRomPath(rom.clone()));
+                if let Some(replay) = &cfg.replay {
+                    tx.event(EmulationEvent::LoadReplayPath(replay.clone()));
+                }
+            }
+            None => {
+                HOMEBREW_ROMS[self.next_rom].load(tx);
+                self.next_rom = (self.next_rom + 1) % HOMEBREW_ROMS.len();
+            }
+        }
+    }
+}
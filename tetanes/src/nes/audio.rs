@@ -1,4 +1,4 @@
-use crate::nes::config::Config;
+use crate::nes::audio::recording::{Recording, RecordingFormat};
 use anyhow::{anyhow, Context};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use ringbuf::{
@@ -6,14 +6,23 @@ use ringbuf::{
     traits::{Consumer, Observer, Split},
     CachingCons, CachingProd, HeapRb,
 };
-use std::{fs::File, io::BufWriter, iter, path::PathBuf, sync::Arc};
-use tetanes_core::time::Duration;
+use std::{iter, path::PathBuf, sync::Arc};
+use tetanes_core::{
+    apu::{AudioChannel, Channel},
+    time::Duration,
+};
 use tracing::{debug, error, info, trace, warn};
 
+pub mod flac;
+pub mod recording;
+
 type SampleRb = Arc<HeapRb<f32>>;
 type SampleProducer = CachingProd<SampleRb>;
 type SampleConsumer = CachingCons<SampleRb>;
 
+/// Duration of the fade in and out applied to scrubbed audio to suppress clicks.
+const SCRUB_FADE: Duration = Duration::from_millis(2);
+
 /// Represents the state of the audio stream.
 #[derive(Debug)]
 #[must_use]
@@ -43,6 +52,11 @@ pub struct Audio {
     pub sample_rate: f32,
     pub latency: Duration,
     pub buffer_size: usize,
+    /// Whether to play audio through the speakers while recording it to a file.
+    pub monitor_recording: bool,
+    pub recording_format: RecordingFormat,
+    /// Whether to record each APU channel to its own file.
+    pub split_channels: bool,
     pub host: cpal::Host,
     output: Option<Output>,
 }
@@ -54,6 +68,9 @@ impl std::fmt::Debug for Audio {
             .field("sample_rate", &self.sample_rate)
             .field("latency", &self.latency)
             .field("buffer_size", &self.buffer_size)
+            .field("monitor_recording", &self.monitor_recording)
+            .field("recording_format", &self.recording_format)
+            .field("split_channels", &self.split_channels)
             .field("output", &self.output)
             .finish_non_exhaustive()
     }
@@ -83,6 +100,9 @@ impl Audio {
             sample_rate,
             latency,
             buffer_size,
+            monitor_recording: true,
+            recording_format: RecordingFormat::default(),
+            split_channels: false,
             host,
             output,
         }
@@ -116,7 +136,19 @@ impl Audio {
             .as_mut()
             .and_then(|output| output.mixer.as_mut())
         {
-            mixer.process(samples);
+            mixer.process(samples, self.monitor_recording);
+        }
+    }
+
+    /// Plays a short slice of audio while paused, such as the samples for a single stepped frame.
+    /// Does nothing while playing, as samples are queued by [`Audio::process`] instead.
+    pub fn scrub(&mut self, samples: &[f32]) {
+        if let Some(mixer) = &mut self
+            .output
+            .as_mut()
+            .and_then(|output| output.mixer.as_mut())
+        {
+            mixer.scrub(samples);
         }
     }
 
@@ -153,6 +185,18 @@ impl Audio {
         }
     }
 
+    /// Resume the audio output stream if it was suspended by the platform, e.g. after being
+    /// backgrounded or because browsers block audio until a user gesture.
+    pub fn resume(&mut self) {
+        if let Some(mixer) = &mut self
+            .output
+            .as_mut()
+            .and_then(|output| output.mixer.as_mut())
+        {
+            mixer.resume();
+        }
+    }
+
     /// Recreate audio output device.
     fn recreate_output(&mut self) -> anyhow::Result<State> {
         let _ = self.stop();
@@ -189,19 +233,42 @@ impl Audio {
             .is_some_and(|mixer| mixer.recording.is_some())
     }
 
-    /// Start recording audio to a file.
-    pub fn start_recording(&mut self) -> anyhow::Result<()> {
+    /// Whether the mixer is currently recording each channel to its own file, expecting samples
+    /// from [`Audio::record_channel`].
+    pub fn is_recording_channels(&self) -> bool {
+        self.output
+            .as_ref()
+            .and_then(|output| output.mixer.as_ref())
+            .and_then(|mixer| mixer.recording.as_ref())
+            .is_some_and(Recording::is_split)
+    }
+
+    /// Start recording audio to a file, or to one file per channel in `audio_channels` if
+    /// `split_channels` is set.
+    pub fn start_recording(&mut self, audio_channels: &[AudioChannel]) -> anyhow::Result<()> {
+        let split_channels = self.split_channels.then_some(audio_channels);
         if let Some(mixer) = &mut self
             .output
             .as_mut()
             .and_then(|output| output.mixer.as_mut())
         {
-            mixer.start_recording()
+            mixer.start_recording(self.recording_format, split_channels)
         } else {
             Ok(())
         }
     }
 
+    /// Writes samples output by a single channel to its recording file.
+    pub fn record_channel(&mut self, channel: Channel, samples: &[f32]) {
+        if let Some(mixer) = &mut self
+            .output
+            .as_mut()
+            .and_then(|output| output.mixer.as_mut())
+        {
+            mixer.record_channel(channel, samples);
+        }
+    }
+
     /// Stop recording audio to a file.
     pub fn stop_recording(&mut self) -> anyhow::Result<Option<PathBuf>> {
         self.output
@@ -413,7 +480,7 @@ pub(crate) struct Mixer {
     sample_latency: usize,
     producer: SampleProducer,
     processed_samples: Vec<f32>,
-    recording: Option<(PathBuf, hound::WavWriter<BufWriter<File>>)>,
+    recording: Option<Recording>,
 }
 
 impl std::fmt::Debug for Mixer {
@@ -494,45 +561,35 @@ impl Mixer {
         self.paused = paused;
     }
 
-    fn start_recording(&mut self) -> anyhow::Result<()> {
-        let _ = self.stop_recording();
-        let path = Config::default_audio_dir()
-            .join(
-                chrono::Local::now()
-                    .format("recording_%Y-%m-%d_at_%H_%M_%S")
-                    .to_string(),
-            )
-            .with_extension("wav");
-        if let Some(parent) = path.parent() {
-            if !parent.exists() {
-                std::fs::create_dir_all(parent).with_context(|| {
-                    format!(
-                        "failed to create audio recording directory: {}",
-                        parent.display()
-                    )
-                })?;
+    /// Resume a stream suspended by the platform. Playing an already playing stream is a no-op.
+    fn resume(&mut self) {
+        if !self.paused {
+            if let Err(err) = self.stream.play() {
+                error!("failed to resume audio stream: {err:?}");
             }
         }
-        let spec = hound::WavSpec {
-            channels: self.channels,
-            sample_rate: self.sample_rate,
-            bits_per_sample: 32,
-            sample_format: hound::SampleFormat::Float,
-        };
-        let writer =
-            hound::WavWriter::create(&path, spec).context("failed to create audio recording")?;
-        self.recording = Some((path, writer));
+    }
+
+    fn start_recording(
+        &mut self,
+        format: RecordingFormat,
+        split_channels: Option<&[AudioChannel]>,
+    ) -> anyhow::Result<()> {
+        let _ = self.stop_recording();
+        self.recording = Some(Recording::create(format, self.sample_rate, split_channels)?);
         Ok(())
     }
 
     fn stop_recording(&mut self) -> anyhow::Result<Option<PathBuf>> {
-        if let Some((path, mut recording)) = self.recording.take() {
-            match recording.flush() {
-                Ok(_) => Ok(Some(path)),
-                Err(err) => Err(anyhow!("failed to flush audio recording: {err:?}")),
+        self.recording.take().map(Recording::finalize).transpose()
+    }
+
+    fn record_channel(&mut self, channel: Channel, samples: &[f32]) {
+        if let Some(recording) = &mut self.recording {
+            if let Err(err) = recording.write_channel(channel, samples) {
+                error!("failed to write audio samples: {err:?}");
+                let _ = self.stop_recording();
             }
-        } else {
-            Ok(None)
         }
     }
 
@@ -570,23 +627,28 @@ impl Mixer {
         )?)
     }
 
-    fn process(&mut self, samples: &[f32]) {
+    /// Queues samples for output, writing them to the recording if there is one. Unless
+    /// `monitor_recording` is set, silence is queued in their place while recording so emulation
+    /// stays paced by the audio output.
+    fn process(&mut self, samples: &[f32], monitor_recording: bool) {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
 
         if self.paused {
             return;
         }
+        let muted = self.recording.is_some() && !monitor_recording;
         for sample in samples {
+            let output = if muted { 0.0 } else { *sample };
             for _ in 0..self.channels {
-                self.processed_samples.push(*sample);
+                self.processed_samples.push(output);
             }
-            if let Some((_, recording)) = &mut self.recording {
-                // TODO: push slice to recording thread
-                if let Err(err) = recording.write_sample(*sample) {
-                    error!("failed to write audio sample: {err:?}");
-                    let _ = self.stop_recording();
-                }
+        }
+        if let Some(recording) = &mut self.recording {
+            // TODO: push slice to recording thread
+            if let Err(err) = recording.write_mixed(samples) {
+                error!("failed to write audio samples: {err:?}");
+                let _ = self.stop_recording();
             }
         }
         let processed_len = self.processed_samples.len();
@@ -599,4 +661,25 @@ impl Mixer {
             self.producer.occupied_len()
         );
     }
+
+    fn scrub(&mut self, samples: &[f32]) {
+        if !self.paused || samples.is_empty() {
+            return;
+        }
+        // Fade the edges so the jump from and back to silence doesn't click
+        let len = samples.len();
+        let fade_len = ((SCRUB_FADE.as_secs_f32() * self.sample_rate as f32) as usize)
+            .clamp(1, len.div_ceil(2));
+        let channels = self.channels as usize;
+        let faded = samples.iter().enumerate().flat_map(|(i, sample)| {
+            let edge = i.min(len - 1 - i);
+            let gain = ((edge + 1) as f32 / fade_len as f32).min(1.0);
+            iter::repeat(sample * gain).take(channels)
+        });
+        let queued_len = self.producer.push_iter(faded);
+        trace!(
+            "scrubbed: {len}, queued: {queued_len}, buffer len: {}",
+            self.producer.occupied_len()
+        );
+    }
 }
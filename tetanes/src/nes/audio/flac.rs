@@ -0,0 +1,321 @@
+//! A minimal FLAC encoder for audio recordings, using fixed linear predictors and Rice-coded
+//! residuals.
+//!
+//! See: <https://xiph.org/flac/format.html>
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+/// Samples per channel in each frame.
+const BLOCK_SIZE: usize = 4096;
+const BITS_PER_SAMPLE: u32 = 16;
+const MAX_FIXED_ORDER: usize = 4;
+const MAX_RICE_PARAM: u32 = 14;
+/// Byte offset of the STREAMINFO block data, after the `fLaC` marker and block header.
+const STREAMINFO_OFFSET: u64 = 8;
+const STREAMINFO_LEN: u32 = 34;
+
+/// Writes big-endian bit fields to a byte buffer.
+#[derive(Default, Debug)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u64,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, val: u64, bits: u32) {
+        debug_assert!(bits <= 32);
+        if bits == 0 {
+            return;
+        }
+        self.acc = (self.acc << bits) | (val & ((1 << bits) - 1));
+        self.bits += bits;
+        while self.bits >= 8 {
+            self.bits -= 8;
+            self.bytes.push((self.acc >> self.bits) as u8);
+        }
+    }
+
+    fn write_unary(&mut self, zeros: u64) {
+        let mut zeros = zeros;
+        while zeros >= 32 {
+            self.write(0, 32);
+            zeros -= 32;
+        }
+        self.write(1, zeros as u32 + 1);
+    }
+
+    fn write_rice(&mut self, residual: i64, param: u32) {
+        let val = zigzag(residual);
+        self.write_unary(val >> param);
+        self.write(val, param);
+    }
+
+    /// Pad with zero bits to the next byte boundary.
+    fn align(&mut self) {
+        if self.bits > 0 {
+            self.write(0, 8 - self.bits);
+        }
+    }
+}
+
+const fn zigzag(val: i64) -> u64 {
+    ((val << 1) ^ (val >> 63)) as u64
+}
+
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |crc, &byte| {
+        (0..8).fold(crc ^ byte, |crc, _| {
+            if crc & 0x80 == 0x80 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0, |crc, &byte| {
+        (0..8).fold(crc ^ (u16::from(byte) << 8), |crc, _| {
+            if crc & 0x8000 == 0x8000 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+/// Residuals of a fixed linear predictor of `order`, skipping the warm-up samples.
+fn fixed_residuals(samples: &[i64], order: usize) -> impl Iterator<Item = i64> + '_ {
+    samples.windows(order + 1).map(move |window| {
+        let s = |i: usize| window[order - i];
+        match order {
+            0 => s(0),
+            1 => s(0) - s(1),
+            2 => s(0) - 2 * s(1) + s(2),
+            3 => s(0) - 3 * s(1) + 3 * s(2) - s(3),
+            _ => s(0) - 4 * s(1) + 6 * s(2) - 4 * s(3) + s(4),
+        }
+    })
+}
+
+/// Returns the Rice parameter that encodes `residuals` in the fewest bits, and that bit count.
+fn best_rice_param(residuals: &[i64]) -> (u32, u64) {
+    (0..=MAX_RICE_PARAM)
+        .map(|param| {
+            let bits = residuals
+                .iter()
+                .map(|&residual| (zigzag(residual) >> param) + 1 + u64::from(param))
+                .sum();
+            (param, bits)
+        })
+        .min_by_key(|&(_, bits)| bits)
+        .unwrap_or((0, 0))
+}
+
+/// Writes a FLAC stream of 16-bit samples, updating the stream info with the total number of
+/// samples when finalized.
+#[derive(Debug)]
+#[must_use]
+pub struct FlacWriter<W: Write + Seek> {
+    writer: W,
+    sample_rate: u32,
+    channels: u16,
+    /// Samples buffered for the next frame, per channel.
+    block: Vec<Vec<i64>>,
+    /// Channel of the next interleaved sample.
+    channel: usize,
+    frame_number: u64,
+    total_samples: u64,
+    min_frame_size: u32,
+    max_frame_size: u32,
+}
+
+impl FlacWriter<BufWriter<File>> {
+    /// Create a FLAC file at `path`.
+    pub fn create(path: impl AsRef<Path>, sample_rate: u32, channels: u16) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), sample_rate, channels)
+    }
+}
+
+impl<W: Write + Seek> FlacWriter<W> {
+    pub fn new(mut writer: W, sample_rate: u32, channels: u16) -> io::Result<Self> {
+        if !(1..=8).contains(&channels) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unsupported channel count: {channels}"),
+            ));
+        }
+        writer.write_all(b"fLaC")?;
+        let mut flac = Self {
+            writer,
+            sample_rate,
+            channels,
+            block: vec![Vec::with_capacity(BLOCK_SIZE); channels.into()],
+            channel: 0,
+            frame_number: 0,
+            total_samples: 0,
+            min_frame_size: 0,
+            max_frame_size: 0,
+        };
+        // Last metadata block, STREAMINFO
+        flac.writer.write_all(&[0x80])?;
+        flac.writer.write_all(&STREAMINFO_LEN.to_be_bytes()[1..])?;
+        flac.write_stream_info()?;
+        Ok(flac)
+    }
+
+    fn write_stream_info(&mut self) -> io::Result<()> {
+        let mut bits = BitWriter::default();
+        bits.write(BLOCK_SIZE as u64, 16);
+        bits.write(BLOCK_SIZE as u64, 16);
+        bits.write(self.min_frame_size.into(), 24);
+        bits.write(self.max_frame_size.into(), 24);
+        bits.write(self.sample_rate.into(), 20);
+        bits.write(u64::from(self.channels) - 1, 3);
+        bits.write(u64::from(BITS_PER_SAMPLE) - 1, 5);
+        bits.write(self.total_samples >> 32, 4);
+        bits.write(self.total_samples, 32);
+        // MD5 signature of the unencoded audio, left unset
+        bits.write(0, 32);
+        bits.write(0, 32);
+        bits.write(0, 32);
+        bits.write(0, 32);
+        self.writer.write_all(&bits.bytes)
+    }
+
+    /// Write the next sample, interleaved by channel, clamping it to `-1.0..=1.0`.
+    pub fn write_sample(&mut self, sample: f32) -> io::Result<()> {
+        let sample = (sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i64;
+        self.block[self.channel].push(sample);
+        self.channel += 1;
+        if self.channel == self.block.len() {
+            self.channel = 0;
+            if self.block[0].len() == BLOCK_SIZE {
+                self.write_frame()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_frame(&mut self) -> io::Result<()> {
+        let block_size = self.block[0].len();
+        if block_size == 0 {
+            return Ok(());
+        }
+
+        let mut bits = BitWriter::default();
+        // Sync code, fixed block size
+        bits.write(0xFFF8, 16);
+        // Block size stored as 16 bits at the end of the header, sample rate from STREAMINFO
+        bits.write(0b0111, 4);
+        bits.write(0b0000, 4);
+        // Independent channels, 16 bits per sample
+        bits.write(u64::from(self.channels) - 1, 4);
+        bits.write(0b100, 3);
+        bits.write(0, 1);
+        self.write_frame_number(&mut bits);
+        bits.write(block_size as u64 - 1, 16);
+        let crc = crc8(&bits.bytes);
+        bits.write(crc.into(), 8);
+
+        for samples in &self.block {
+            Self::write_subframe(&mut bits, samples);
+        }
+        bits.align();
+        let crc = crc16(&bits.bytes);
+        bits.write(crc.into(), 16);
+
+        self.writer.write_all(&bits.bytes)?;
+        let frame_size = bits.bytes.len() as u32;
+        self.min_frame_size = if self.min_frame_size == 0 {
+            frame_size
+        } else {
+            self.min_frame_size.min(frame_size)
+        };
+        self.max_frame_size = self.max_frame_size.max(frame_size);
+        self.frame_number += 1;
+        self.total_samples += block_size as u64;
+        self.block.iter_mut().for_each(Vec::clear);
+        Ok(())
+    }
+
+    /// Write the frame number as UTF-8 style coded bytes.
+    fn write_frame_number(&self, bits: &mut BitWriter) {
+        let number = self.frame_number;
+        if number < 0x80 {
+            bits.write(number, 8);
+            return;
+        }
+        let mut continuation = 1;
+        while number >> (6 * continuation) >= 1 << (6 - continuation) {
+            continuation += 1;
+        }
+        let lead = (0xFF00_u64 >> (continuation + 1)) & 0xFF;
+        bits.write(lead | (number >> (6 * continuation)), 8);
+        for i in (0..continuation).rev() {
+            bits.write(0x80 | ((number >> (6 * i)) & 0x3F), 8);
+        }
+    }
+
+    fn write_subframe(bits: &mut BitWriter, samples: &[i64]) {
+        if samples.iter().all(|&sample| sample == samples[0]) {
+            // Constant
+            bits.write(0b0000_0000, 8);
+            bits.write(samples[0] as u64, BITS_PER_SAMPLE);
+            return;
+        }
+
+        let verbatim_bits = samples.len() as u64 * u64::from(BITS_PER_SAMPLE);
+        let best = (0..=MAX_FIXED_ORDER.min(samples.len() - 1))
+            .map(|order| {
+                let residuals = fixed_residuals(samples, order).collect::<Vec<_>>();
+                let (param, residual_bits) = best_rice_param(&residuals);
+                let bits = order as u64 * u64::from(BITS_PER_SAMPLE) + 10 + residual_bits;
+                (order, param, residuals, bits)
+            })
+            .min_by_key(|(.., bits)| *bits);
+
+        match best {
+            Some((order, param, residuals, bits_len)) if bits_len < verbatim_bits => {
+                // Fixed predictor
+                bits.write(0b0001_0000 | ((order as u64) << 1), 8);
+                for &sample in &samples[..order] {
+                    bits.write(sample as u64, BITS_PER_SAMPLE);
+                }
+                // Rice coding with 4-bit parameters, partition order 0
+                bits.write(0b00, 2);
+                bits.write(0, 4);
+                bits.write(param.into(), 4);
+                for residual in residuals {
+                    bits.write_rice(residual, param);
+                }
+            }
+            _ => {
+                // Verbatim
+                bits.write(0b0000_0010, 8);
+                for &sample in samples {
+                    bits.write(sample as u64, BITS_PER_SAMPLE);
+                }
+            }
+        }
+    }
+
+    /// Write any buffered samples and update the stream info, dropping a partially written set
+    /// of interleaved samples.
+    pub fn finalize(mut self) -> io::Result<()> {
+        for samples in &mut self.block[..self.channel] {
+            samples.pop();
+        }
+        self.write_frame()?;
+        self.writer.seek(SeekFrom::Start(STREAMINFO_OFFSET))?;
+        self.write_stream_info()?;
+        self.writer.flush()
+    }
+}
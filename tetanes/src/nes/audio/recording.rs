@@ -0,0 +1,212 @@
+use crate::nes::{audio::flac::FlacWriter, config::Config};
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+use tetanes_core::apu::{AudioChannel, Channel};
+
+/// Container format audio recordings are written in.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub enum RecordingFormat {
+    /// Uncompressed 32-bit float WAV.
+    #[default]
+    Wav,
+    /// Lossless compressed 16-bit FLAC.
+    Flac,
+}
+
+impl RecordingFormat {
+    pub const fn as_slice() -> &'static [Self] {
+        &[Self::Wav, Self::Flac]
+    }
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Wav => "WAV",
+            Self::Flac => "FLAC",
+        }
+    }
+
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Wav => "wav",
+            Self::Flac => "flac",
+        }
+    }
+}
+
+impl AsRef<str> for RecordingFormat {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+enum Writer {
+    Wav(hound::WavWriter<BufWriter<File>>),
+    Flac(FlacWriter<BufWriter<File>>),
+}
+
+impl Writer {
+    fn create(path: &Path, format: RecordingFormat, sample_rate: u32) -> anyhow::Result<Self> {
+        let writer = match format {
+            RecordingFormat::Wav => {
+                let spec = hound::WavSpec {
+                    channels: 1,
+                    sample_rate,
+                    bits_per_sample: 32,
+                    sample_format: hound::SampleFormat::Float,
+                };
+                hound::WavWriter::create(path, spec).map(Self::Wav)?
+            }
+            RecordingFormat::Flac => FlacWriter::create(path, sample_rate, 1).map(Self::Flac)?,
+        };
+        Ok(writer)
+    }
+
+    fn write_samples(&mut self, samples: &[f32]) -> anyhow::Result<()> {
+        match self {
+            Self::Wav(writer) => {
+                for sample in samples {
+                    writer.write_sample(*sample)?;
+                }
+            }
+            Self::Flac(writer) => {
+                for sample in samples {
+                    writer.write_sample(*sample)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finalize(self) -> anyhow::Result<()> {
+        match self {
+            Self::Wav(writer) => writer.finalize()?,
+            Self::Flac(writer) => writer.finalize()?,
+        }
+        Ok(())
+    }
+}
+
+/// An audio recording in progress of either the mixed output to a single file, or of each APU
+/// channel to its own file in a directory.
+#[must_use]
+pub(crate) struct Recording {
+    path: PathBuf,
+    mixed: Option<Writer>,
+    channels: Vec<(Channel, Writer)>,
+}
+
+impl std::fmt::Debug for Recording {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Recording")
+            .field("path", &self.path)
+            .field(
+                "channels",
+                &self
+                    .channels
+                    .iter()
+                    .map(|(channel, _)| channel)
+                    .collect::<Vec<_>>(),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl Recording {
+    /// Create a recording in the default audio directory. With `split_channels`, each channel is
+    /// recorded to a file named after it, such as `pulse1.flac`.
+    pub(crate) fn create(
+        format: RecordingFormat,
+        sample_rate: u32,
+        split_channels: Option<&[AudioChannel]>,
+    ) -> anyhow::Result<Self> {
+        let path = Config::default_audio_dir().join(
+            chrono::Local::now()
+                .format("recording_%Y-%m-%d_at_%H_%M_%S")
+                .to_string(),
+        );
+        let dir = match split_channels {
+            Some(_) => path.as_path(),
+            None => path.parent().unwrap_or(&path),
+        };
+        if !dir.exists() {
+            std::fs::create_dir_all(dir).with_context(|| {
+                format!(
+                    "failed to create audio recording directory: {}",
+                    dir.display()
+                )
+            })?;
+        }
+
+        match split_channels {
+            Some(audio_channels) => {
+                let channels = audio_channels
+                    .iter()
+                    .map(|audio_channel| {
+                        let name = audio_channel.name.to_lowercase().replace(' ', "_");
+                        let path = path.join(name).with_extension(format.extension());
+                        Writer::create(&path, format, sample_rate)
+                            .map(|writer| (audio_channel.channel, writer))
+                    })
+                    .collect::<anyhow::Result<_>>()
+                    .context("failed to create audio recording")?;
+                Ok(Self {
+                    path,
+                    mixed: None,
+                    channels,
+                })
+            }
+            None => {
+                let path = path.with_extension(format.extension());
+                let mixed = Writer::create(&path, format, sample_rate)
+                    .context("failed to create audio recording")?;
+                Ok(Self {
+                    path,
+                    mixed: Some(mixed),
+                    channels: Vec::new(),
+                })
+            }
+        }
+    }
+
+    /// Whether each channel is recorded to its own file.
+    pub(crate) fn is_split(&self) -> bool {
+        !self.channels.is_empty()
+    }
+
+    /// Write samples of the mixed output, if recorded.
+    pub(crate) fn write_mixed(&mut self, samples: &[f32]) -> anyhow::Result<()> {
+        match &mut self.mixed {
+            Some(writer) => writer.write_samples(samples),
+            None => Ok(()),
+        }
+    }
+
+    /// Write samples output by a single channel, if recorded.
+    pub(crate) fn write_channel(
+        &mut self,
+        channel: Channel,
+        samples: &[f32],
+    ) -> anyhow::Result<()> {
+        match self.channels.iter_mut().find(|(c, _)| *c == channel) {
+            Some((_, writer)) => writer.write_samples(samples),
+            None => Ok(()),
+        }
+    }
+
+    /// Finish writing the recording, returning the path of the file, or the directory of files
+    /// if channels were split.
+    pub(crate) fn finalize(self) -> anyhow::Result<PathBuf> {
+        self.mixed
+            .into_iter()
+            .chain(self.channels.into_iter().map(|(_, writer)| writer))
+            .try_for_each(Writer::finalize)
+            .map_err(|err| anyhow!("failed to finalize audio recording: {err:?}"))?;
+        Ok(self.path)
+    }
+}
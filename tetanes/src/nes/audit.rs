@@ -0,0 +1,296 @@
+//! Auditing a ROM folder against a No-Intro DAT file.
+//!
+//! DAT files list the known good dump of each game with its canonical file name, size and CRC32.
+//! ROMs are matched by CRC32 with or without their iNES header, since No-Intro publishes both
+//! headered and headerless DATs.
+
+use anyhow::Context;
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+};
+use tetanes_core::fs;
+use tracing::{info, warn};
+
+/// Size of the iNES header No-Intro excludes from headerless DATs.
+const INES_HEADER_SIZE: usize = 16;
+
+/// A known good dump listed in a DAT file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct DatRom {
+    /// Name of the game the ROM belongs to.
+    pub game: String,
+    /// Canonical file name.
+    pub name: String,
+    pub size: usize,
+    pub crc32: u32,
+}
+
+/// A parsed No-Intro DAT file.
+#[derive(Default, Debug, Clone)]
+#[must_use]
+pub struct Dat {
+    pub name: String,
+    pub roms: Vec<DatRom>,
+}
+
+impl Dat {
+    /// Load a Logiqx XML DAT file, as published by No-Intro.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let xml =
+            std::fs::read_to_string(path).with_context(|| format!("failed to read {path:?}"))?;
+        let dat = Self::parse(&xml);
+        if dat.roms.is_empty() {
+            anyhow::bail!("no ROMs found in {path:?}, is it a No-Intro DAT file?");
+        }
+        info!("loaded {} ROMs from {:?}", dat.roms.len(), dat.name);
+        Ok(dat)
+    }
+
+    /// Parse the games and ROMs from a Logiqx XML DAT file. Entries missing a name, size or
+    /// CRC32 are skipped.
+    pub fn parse(xml: &str) -> Self {
+        let name = xml
+            .split_once("<name>")
+            .and_then(|(_, rest)| rest.split_once("</name>"))
+            .map(|(name, _)| unescape(name.trim()))
+            .unwrap_or_default();
+
+        let mut roms = Vec::new();
+        let mut game = String::new();
+        for tag in xml
+            .split('<')
+            .filter_map(|tag| tag.split_once('>'))
+            .map(|(tag, _)| tag)
+        {
+            if tag.starts_with("game ") || tag.starts_with("machine ") {
+                game = attr(tag, "name").unwrap_or_default();
+            } else if tag.starts_with("rom ") {
+                let rom = attr(tag, "name").and_then(|name| {
+                    Some(DatRom {
+                        game: game.clone(),
+                        name,
+                        size: attr(tag, "size")?.parse().ok()?,
+                        crc32: u32::from_str_radix(&attr(tag, "crc")?, 16).ok()?,
+                    })
+                });
+                match rom {
+                    Some(rom) => roms.push(rom),
+                    None => warn!("skipping invalid DAT rom entry: <{tag}>"),
+                }
+            }
+        }
+
+        Self { name, roms }
+    }
+}
+
+/// Audit result for a single ROM file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum Status {
+    /// Matches a known good dump and is named correctly.
+    Verified,
+    /// Matches a known good dump under a different file name.
+    Rename(String),
+    /// Contains a known good dump followed by extra data.
+    Overdump(String),
+    /// Named after a known game, but doesn't match its good dump.
+    BadDump(String),
+    /// Not listed in the DAT file.
+    Unknown,
+}
+
+impl AsRef<str> for Status {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Verified => "Verified",
+            Self::Rename(_) => "Rename",
+            Self::Overdump(_) => "Overdump",
+            Self::BadDump(_) => "Bad Dump",
+            Self::Unknown => "Unknown",
+        }
+    }
+}
+
+/// Audit result for a ROM file.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct Entry {
+    pub path: PathBuf,
+    pub status: Status,
+}
+
+/// Number of ROM files with each [`Status`].
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Summary {
+    pub verified: usize,
+    pub renames: usize,
+    pub overdumps: usize,
+    pub bad_dumps: usize,
+    pub unknown: usize,
+}
+
+/// Results of a ROM folder audit.
+#[derive(Default, Debug, Clone)]
+#[must_use]
+pub struct Report {
+    pub entries: Vec<Entry>,
+    /// Number of ROMs in the DAT file not found in the folder.
+    pub missing: usize,
+}
+
+impl Report {
+    /// Count the entries with each status.
+    pub fn summary(&self) -> Summary {
+        let mut summary = Summary::default();
+        for entry in &self.entries {
+            match entry.status {
+                Status::Verified => summary.verified += 1,
+                Status::Rename(_) => summary.renames += 1,
+                Status::Overdump(_) => summary.overdumps += 1,
+                Status::BadDump(_) => summary.bad_dumps += 1,
+                Status::Unknown => summary.unknown += 1,
+            }
+        }
+        summary
+    }
+
+    /// Rename every ROM that matches a good dump to its canonical name, skipping any where a file
+    /// with that name already exists. Returns the number of ROMs renamed.
+    pub fn rename_all(&mut self) -> usize {
+        let mut renamed = 0;
+        for entry in &mut self.entries {
+            let Status::Rename(name) = &entry.status else {
+                continue;
+            };
+            let dest = entry.path.with_file_name(name);
+            if fs::exists(&dest) {
+                warn!("skipped renaming {:?}, {dest:?} already exists", entry.path);
+                continue;
+            }
+            match std::fs::rename(&entry.path, &dest) {
+                Ok(()) => {
+                    info!("renamed {:?} to {dest:?}", entry.path);
+                    entry.path = dest;
+                    entry.status = Status::Verified;
+                    renamed += 1;
+                }
+                Err(err) => warn!("failed to rename {:?}: {err:?}", entry.path),
+            }
+        }
+        renamed
+    }
+}
+
+/// Check the `.nes` files in a folder against a DAT file.
+pub fn audit(dat: &Dat, dir: &Path) -> anyhow::Result<Report> {
+    info!("auditing {dir:?} against {:?}", dat.name);
+
+    let mut paths = std::fs::read_dir(dir)
+        .with_context(|| format!("failed to read {dir:?}"))?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("nes"))
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    let by_crc32 = dat
+        .roms
+        .iter()
+        .map(|rom| (rom.crc32, rom))
+        .collect::<HashMap<_, _>>();
+    let sizes = dat.roms.iter().map(|rom| rom.size).collect::<BTreeSet<_>>();
+
+    let mut entries = Vec::with_capacity(paths.len());
+    for path in paths {
+        let data = match fs::load_raw(&path) {
+            Ok(data) => data,
+            Err(err) => {
+                warn!("failed to read {path:?}: {err:?}");
+                continue;
+            }
+        };
+        let headerless = data
+            .strip_prefix(b"NES\x1a")
+            .and_then(|_| data.get(INES_HEADER_SIZE..))
+            .unwrap_or(&data);
+
+        let status = status(dat, &by_crc32, &sizes, &path, &data, headerless);
+        entries.push(Entry { path, status });
+    }
+
+    let found = entries
+        .iter()
+        .filter_map(|entry| match &entry.status {
+            Status::Verified => Some(fs::filename(&entry.path)),
+            Status::Rename(name) => Some(name.as_str()),
+            _ => None,
+        })
+        .collect::<BTreeSet<_>>();
+    let missing = dat
+        .roms
+        .iter()
+        .filter(|rom| !found.contains(rom.name.as_str()))
+        .count();
+
+    Ok(Report { entries, missing })
+}
+
+fn status(
+    dat: &Dat,
+    by_crc32: &HashMap<u32, &DatRom>,
+    sizes: &BTreeSet<usize>,
+    path: &Path,
+    data: &[u8],
+    headerless: &[u8],
+) -> Status {
+    if let Some(rom) = [data, headerless]
+        .into_iter()
+        .find_map(|data| by_crc32.get(&fs::compute_crc32(data)))
+    {
+        return if fs::filename(path) == rom.name {
+            Status::Verified
+        } else {
+            Status::Rename(rom.name.clone())
+        };
+    }
+
+    // Check whether the start of the ROM matches a smaller good dump
+    if let Some(rom) = sizes.range(..headerless.len()).find_map(|&size| {
+        by_crc32
+            .get(&fs::compute_crc32(&headerless[..size]))
+            .filter(|rom| rom.size == size)
+    }) {
+        return Status::Overdump(rom.name.clone());
+    }
+
+    let stem = path.file_stem();
+    dat.roms
+        .iter()
+        .find(|rom| stem.is_some() && Path::new(&rom.name).file_stem() == stem)
+        .map_or(Status::Unknown, |rom| Status::BadDump(rom.name.clone()))
+}
+
+/// Returns the unescaped value of an XML attribute in a tag.
+fn attr(tag: &str, name: &str) -> Option<String> {
+    let start = tag.find(&format!(" {name}=\""))? + name.len() + 3;
+    let len = tag[start..].find('"')?;
+    Some(unescape(&tag[start..start + len]))
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
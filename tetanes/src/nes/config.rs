@@ -1,17 +1,23 @@
 use crate::nes::{
     action::Action,
+    audio::recording::RecordingFormat,
+    emulation::{replay::ReplayFormat, video_recording::VideoFormat},
     input::{ActionBindings, Gamepads, Input},
-    renderer::shader::Shader,
+    renderer::{clip::ClipFormat, shader::ShaderPipeline},
 };
 use anyhow::Context;
 use egui::ahash::HashSet;
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
 use tetanes_core::{
     action::Action as DeckAction, common::NesRegion, control_deck::Config as DeckConfig, fs,
     input::Player, ppu::Ppu, time::Duration,
 };
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -21,6 +27,13 @@ pub struct AudioConfig {
     pub enabled: bool,
     pub buffer_size: usize,
     pub latency: Duration,
+    /// Play the audio for each frame stepped while paused.
+    pub frame_step_audio: bool,
+    /// Keep playing audio through the speakers while recording it to a file.
+    pub monitor_recording: bool,
+    pub recording_format: RecordingFormat,
+    /// Record each APU channel to its own file.
+    pub split_channels: bool,
 }
 
 impl Default for AudioConfig {
@@ -38,11 +51,15 @@ impl Default for AudioConfig {
             } else {
                 Duration::from_millis(50)
             },
+            frame_step_audio: false,
+            monitor_recording: true,
+            recording_format: RecordingFormat::default(),
+            split_channels: false,
         }
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[must_use]
 #[serde(default)] // Ensures new fields don't break existing configurations
 pub struct EmulationConfig {
@@ -52,10 +69,37 @@ pub struct EmulationConfig {
     pub rewind: bool,
     pub rewind_seconds: u32,
     pub rewind_interval: u32,
+    /// Maximum megabytes of rewind history to keep, limiting `rewind_seconds` if needed.
+    pub rewind_memory_budget: u32,
     pub run_ahead: usize,
     pub save_slot: u8,
     pub speed: f32,
     pub threaded: bool,
+    /// Raise the priority of the emulation thread to reduce stutter from background processes.
+    pub high_priority: bool,
+    /// Pin the emulation thread to a CPU core index, e.g. a performance core on hybrid CPUs.
+    pub cpu_affinity: Option<usize>,
+    /// Allow running multiple instances instead of handing off ROMs to the running instance.
+    pub allow_multiple_instances: bool,
+    pub replay_author: String,
+    pub replay_description: String,
+    /// Also export replay recordings as TAS movies for other emulators.
+    pub replay_format: ReplayFormat,
+    /// Format video recordings are encoded in.
+    pub video_format: VideoFormat,
+    /// Always record inputs into a rolling buffer so recent play can be saved as a replay.
+    pub background_replay: bool,
+    /// Minutes of recent play kept by `background_replay`.
+    pub background_replay_minutes: u32,
+    /// Region to emulate for specific ROMs by name, overriding the NES region.
+    pub rom_regions: BTreeMap<String, NesRegion>,
+    pub attract_mode: AttractModeConfig,
+    /// Load the newest ROM written to this folder automatically, e.g. by a homebrew toolchain.
+    pub watch_folder: Option<PathBuf>,
+    /// Encrypt save states and SRAM with a password entered each session.
+    pub encrypt_saves: bool,
+    /// Frames local input is delayed by during netplay to hide network latency.
+    pub netplay_input_delay: u32,
 }
 
 impl Default for EmulationConfig {
@@ -65,8 +109,14 @@ impl Default for EmulationConfig {
             auto_save: true,
             auto_save_interval: Duration::from_secs(5),
             rewind: true,
-            rewind_seconds: 30,
+            // Browsers limit WASM memory, so keep a shorter history.
+            rewind_seconds: if cfg!(target_arch = "wasm32") { 10 } else { 30 },
             rewind_interval: 2,
+            rewind_memory_budget: if cfg!(target_arch = "wasm32") {
+                64
+            } else {
+                256
+            },
             // WASM struggles to run fast enough with run-ahead and low latency is not needed in
             // debug builds.
             run_ahead: if cfg!(any(debug_assertions, target_arch = "wasm32")) {
@@ -77,6 +127,45 @@ impl Default for EmulationConfig {
             save_slot: 1,
             speed: 1.0,
             threaded: true,
+            high_priority: false,
+            cpu_affinity: None,
+            allow_multiple_instances: false,
+            replay_author: String::new(),
+            replay_description: String::new(),
+            replay_format: ReplayFormat::default(),
+            video_format: VideoFormat::default(),
+            background_replay: false,
+            background_replay_minutes: 5,
+            rom_regions: BTreeMap::new(),
+            attract_mode: AttractModeConfig::default(),
+            watch_folder: None,
+            encrypt_saves: false,
+            netplay_input_delay: 2,
+        }
+    }
+}
+
+/// Settings for playing a demo as a screensaver when idle, e.g. for kiosks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+#[serde(default)] // Ensures new fields don't break existing configurations
+pub struct AttractModeConfig {
+    pub enabled: bool,
+    /// Minutes without input and no ROM loaded before starting.
+    pub idle_minutes: u32,
+    /// ROM to play instead of the bundled homebrew ROMs.
+    pub rom: Option<PathBuf>,
+    /// Replay to play back on `rom`.
+    pub replay: Option<PathBuf>,
+}
+
+impl Default for AttractModeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_minutes: 5,
+            rom: None,
+            replay: None,
         }
     }
 }
@@ -88,16 +177,39 @@ pub struct RendererConfig {
     pub fullscreen: bool,
     pub always_on_top: bool,
     pub hide_overscan: bool,
+    /// Hide Overscan for specific ROMs by name, overriding `hide_overscan`.
+    pub rom_overscan: BTreeMap<String, bool>,
+    /// Outline the overscan area over the game while it's shown.
+    pub show_overscan_outline: bool,
+    /// Scale the game by whole physical pixels, ignoring fractional display scaling.
+    pub pixel_perfect: bool,
+    /// Present games at their native frame rate, e.g. 50Hz for PAL, by preferring a matching
+    /// display mode in fullscreen and evenly pacing frames otherwise.
+    pub match_frame_rate: bool,
     pub scale: f32,
+    /// Snap the window size to the nearest whole scale when resized.
+    pub snap_window_size: bool,
     pub zoom: f32,
     pub recent_roms: HashSet<PathBuf>,
     pub roms_path: Option<PathBuf>,
     pub show_perf_stats: bool,
     pub show_messages: bool,
     pub show_menubar: bool,
+    /// Draw an oscilloscope of the audio output over the game.
+    pub show_audio_scope: bool,
+    /// Show CRT static while no ROM is loaded and briefly when one powers on.
+    pub tv_static: bool,
     pub embed_viewports: bool,
     pub dark_theme: bool,
-    pub shader: Shader,
+    /// Shader passes applied in order to the rendered window.
+    pub shader: ShaderPipeline,
+    /// Show windows underneath the game window through it, where supported by the platform.
+    pub translucent: bool,
+    /// Opacity of the game while translucent.
+    pub window_opacity: f32,
+    /// Seconds of recent gameplay kept to save as a clip.
+    pub clip_seconds: u32,
+    pub clip_format: ClipFormat,
 }
 
 impl Default for RendererConfig {
@@ -106,16 +218,27 @@ impl Default for RendererConfig {
             fullscreen: false,
             always_on_top: false,
             hide_overscan: true,
+            rom_overscan: BTreeMap::new(),
+            show_overscan_outline: false,
+            pixel_perfect: false,
+            match_frame_rate: true,
             scale: 3.0,
+            snap_window_size: false,
             zoom: 1.0,
             recent_roms: HashSet::default(),
             roms_path: std::env::current_dir().ok(),
             show_perf_stats: false,
             show_messages: true,
             show_menubar: true,
+            show_audio_scope: false,
+            tv_static: false,
             embed_viewports: false,
             dark_theme: true,
-            shader: Shader::default(),
+            shader: ShaderPipeline::default(),
+            translucent: false,
+            window_opacity: 0.6,
+            clip_seconds: 5,
+            clip_format: ClipFormat::default(),
         }
     }
 }
@@ -126,6 +249,8 @@ impl Default for RendererConfig {
 pub struct InputConfig {
     pub action_bindings: Vec<ActionBindings>,
     pub gamepad_assignments: [(Player, Option<Uuid>); 4],
+    /// Navigate the GUI with a gamepad while paused or no ROM is loaded.
+    pub gamepad_navigation: bool,
     #[serde(skip)]
     pub shortcuts: BTreeMap<Action, ActionBindings>,
     #[serde(skip)]
@@ -150,6 +275,7 @@ impl Default for InputConfig {
             gamepad_assignments: std::array::from_fn(|i| {
                 (Player::try_from(i).expect("valid player assignment"), None)
             }),
+            gamepad_navigation: true,
         }
     }
 }
@@ -275,6 +401,9 @@ impl InputConfig {
     }
 }
 
+/// Warning from the last [`Config::load`] to show once the UI is running.
+static LOAD_WARNING: Mutex<Option<String>> = Mutex::new(None);
+
 /// NES emulation configuration settings.
 ///
 /// # Config JSON
@@ -297,6 +426,8 @@ impl Config {
     pub const SAVE_EXTENSION: &'static str = "sav";
     pub const WINDOW_TITLE: &'static str = "TetaNES";
     pub const FILENAME: &'static str = "config.json";
+    /// Number of previous configurations kept as `config.json.1`, `config.json.2`, etc.
+    pub const BACKUP_COUNT: usize = 3;
 
     #[must_use]
     pub fn default_config_dir() -> PathBuf {
@@ -330,6 +461,14 @@ impl Config {
         )
     }
 
+    #[must_use]
+    pub fn default_video_dir() -> PathBuf {
+        dirs::video_dir().map_or_else(
+            || PathBuf::from("videos"),
+            |dir| dir.join(DeckConfig::BASE_DIR),
+        )
+    }
+
     #[must_use]
     pub fn config_path() -> PathBuf {
         Self::default_config_dir().join(Self::FILENAME)
@@ -344,6 +483,17 @@ impl Config {
             .with_extension(Self::SAVE_EXTENSION)
     }
 
+    /// Path of the state saved when emulation is interrupted, e.g. by Player One's gamepad
+    /// disconnecting, kept apart from the save slots so it never overwrites them.
+    #[must_use]
+    pub fn safety_save_path(name: &str) -> PathBuf {
+        Self::default_data_dir()
+            .join(Self::SAVE_DIR)
+            .join(name)
+            .join("safety")
+            .with_extension(Self::SAVE_EXTENSION)
+    }
+
     pub fn reset(&mut self) {
         *self = Self::default();
     }
@@ -352,7 +502,14 @@ impl Config {
         let path = Config::config_path();
         let data = serde_json::to_vec_pretty(&self).context("failed to serialize config")?;
 
-        fs::save_raw(path, &data).context("failed to save config")?;
+        // Avoid rotating out older backups when nothing changed, e.g. on auto-save
+        if fs::exists(&path) && fs::load_raw(&path).is_ok_and(|existing| existing == data) {
+            return Ok(());
+        }
+        if let Err(err) = fs::rotate_backups(&path, Self::BACKUP_COUNT) {
+            warn!("failed to rotate config backups: {err:?}");
+        }
+        fs::save_raw_atomic(path, &data).context("failed to save config")?;
 
         Ok(())
     }
@@ -360,22 +517,40 @@ impl Config {
     pub fn load(path: Option<PathBuf>) -> Self {
         let path = path.unwrap_or_else(Config::config_path);
 
-        let mut config = fs::exists(&path)
-            .then(|| {
-                info!("Loading saved configuration");
-                fs::load_raw(&path)
-                    .context("failed to load config")
-                    .and_then(|data| Ok(serde_json::from_slice::<Self>(&data)?))
-                    .with_context(|| format!("failed to parse {path:?}"))
-                    .unwrap_or_else(|err| {
-                        error!("Invalid config: {path:?}, reverting to defaults. Error: {err:?}",);
-                        Self::default()
+        let mut config = if fs::exists(&path) {
+            info!("Loading saved configuration");
+            Self::load_path(&path).unwrap_or_else(|err| {
+                error!("Invalid config: {path:?}. Error: {err:?}");
+                let (warning, config) = (1..=Self::BACKUP_COUNT)
+                    .map(|n| fs::backup_path(&path, n))
+                    .filter(|backup| fs::exists(backup))
+                    .find_map(|backup| match Self::load_path(&backup) {
+                        Ok(config) => Some((
+                            format!(
+                                "Configuration was corrupt, restored from backup: {}",
+                                fs::filename(&backup)
+                            ),
+                            config,
+                        )),
+                        Err(err) => {
+                            error!("Invalid config backup: {backup:?}. Error: {err:?}");
+                            None
+                        }
                     })
+                    .unwrap_or_else(|| {
+                        (
+                            "Configuration was corrupt, reverted to defaults".to_string(),
+                            Self::default(),
+                        )
+                    });
+                warn!("{warning}");
+                *LOAD_WARNING.lock().unwrap_or_else(|err| err.into_inner()) = Some(warning);
+                config
             })
-            .unwrap_or_else(|| {
-                info!("Loading default configuration");
-                Self::default()
-            });
+        } else {
+            info!("Loading default configuration");
+            Self::default()
+        };
 
         for binding in &config.input.action_bindings {
             if let Action::Deck(DeckAction::Joypad((player, _))) = binding.action {
@@ -388,6 +563,20 @@ impl Config {
         config
     }
 
+    fn load_path(path: &Path) -> anyhow::Result<Self> {
+        let data = fs::load_raw(path).context("failed to load config")?;
+        serde_json::from_slice::<Self>(&data).with_context(|| format!("failed to parse {path:?}"))
+    }
+
+    /// Takes the warning from the last [`Config::load`], if it had to recover from a corrupt
+    /// configuration.
+    pub fn take_load_warning() -> Option<String> {
+        LOAD_WARNING
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take()
+    }
+
     pub fn increment_speed(&mut self) -> f32 {
         self.emulation.speed = self.next_increment_speed();
         self.emulation.speed
@@ -440,14 +629,28 @@ impl Config {
         }
     }
 
+    /// Whether overscan is hidden, accounting for any override for a ROM.
+    #[must_use]
+    pub fn hide_overscan(&self, rom_name: Option<&str>) -> bool {
+        rom_name
+            .and_then(|name| self.renderer.rom_overscan.get(name))
+            .copied()
+            .unwrap_or(self.renderer.hide_overscan)
+    }
+
     #[must_use]
-    pub fn window_size(&self, aspect_ratio: f32) -> egui::Vec2 {
-        self.window_size_for_scale(aspect_ratio, self.renderer.scale)
+    pub fn window_size(&self, aspect_ratio: f32, hide_overscan: bool) -> egui::Vec2 {
+        self.window_size_for_scale(aspect_ratio, hide_overscan, self.renderer.scale)
     }
 
     #[must_use]
-    pub fn window_size_for_scale(&self, aspect_ratio: f32, scale: f32) -> egui::Vec2 {
-        let texture_size = self.texture_size();
+    pub fn window_size_for_scale(
+        &self,
+        aspect_ratio: f32,
+        hide_overscan: bool,
+        scale: f32,
+    ) -> egui::Vec2 {
+        let texture_size = Self::texture_size(hide_overscan);
         egui::Vec2::new(
             (scale * aspect_ratio * texture_size.x).ceil(),
             (scale * texture_size.y).ceil(),
@@ -455,9 +658,9 @@ impl Config {
     }
 
     #[must_use]
-    pub const fn texture_size(&self) -> egui::Vec2 {
+    pub const fn texture_size(hide_overscan: bool) -> egui::Vec2 {
         let width = Ppu::WIDTH;
-        let height = if self.renderer.hide_overscan {
+        let height = if hide_overscan {
             Ppu::HEIGHT - 16
         } else {
             Ppu::HEIGHT
@@ -541,7 +744,7 @@ impl From<&FrameRate> for f32 {
 impl From<NesRegion> for FrameRate {
     fn from(region: NesRegion) -> Self {
         match region {
-            NesRegion::Auto | NesRegion::Ntsc => Self::X60,
+            NesRegion::Auto | NesRegion::Ntsc | NesRegion::PalM => Self::X60,
             NesRegion::Pal => Self::X50,
             NesRegion::Dendy => Self::X59,
         }
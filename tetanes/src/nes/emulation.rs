@@ -3,8 +3,22 @@ use crate::{
         action::DebugStep,
         audio::{Audio, State as AudioState},
         config::{Config, FrameRate},
-        emulation::{replay::Record, rewind::Rewind},
-        event::{ConfigEvent, EmulationEvent, NesEvent, NesEventProxy, RendererEvent, UiEvent},
+        emulation::{
+            bookmarks::Bookmarks,
+            checkpoints::Checkpoints,
+            failed_saves::{PendingSave, SaveKind},
+            quick_slots::QuickSlots,
+            replay::{BackgroundRecord, Record},
+            rewind::Rewind,
+            transfer::Transfer,
+            video_recording::{VideoFormat, VideoRecording},
+            watch_folder::WatchFolder,
+        },
+        event::{
+            ConfigEvent, DebugEvent, EmulationEvent, NesEvent, NesEventProxy, RendererEvent,
+            UiEvent,
+        },
+        netplay::Netplay,
         renderer::{gui::MessageType, FrameRecycle},
         RunState,
     },
@@ -12,30 +26,54 @@ use crate::{
 };
 use anyhow::{anyhow, Context};
 use chrono::Local;
+use compare::Compare;
 use crossbeam::channel;
 use egui::ViewportId;
-use replay::Replay;
+use memory::MemoryWatch;
+use movie::Movie;
+use replay::{Replay, ReplayFormat};
+use seek::Seek;
 use std::{
-    collections::VecDeque,
+    collections::{BTreeMap, VecDeque},
     io::{self, Read},
     path::{Path, PathBuf},
     thread::JoinHandle,
 };
 use tetanes_core::{
-    apu::Apu,
+    apu::{Apu, Channel},
     common::{NesRegion, Regional, Reset, ResetKind},
-    control_deck::{self, ControlDeck, LoadedRom},
+    control_deck::{self, Config as DeckConfig, ControlDeck, FrameCounters, LoadedRom},
     cpu::Cpu,
+    input::Player,
     ppu::Ppu,
     time::{Duration, Instant},
     video::Frame,
 };
 use thingbuf::mpsc::{blocking::Sender as BufSender, errors::TrySendError};
-use tracing::{debug, error, trace};
+use tracing::{debug, error, trace, warn};
 use winit::event::ElementState;
 
+pub mod bookmarks;
+pub mod checkpoints;
+pub mod clipboard;
+pub mod compare;
+pub mod dmc_samples;
+pub mod failed_saves;
+pub mod front_panel;
+pub mod gallery;
+pub mod memory;
+pub mod movie;
+pub mod netplay;
+pub mod quick_slots;
 pub mod replay;
 pub mod rewind;
+pub mod save_states;
+pub mod script;
+pub mod seek;
+pub mod subtitles;
+pub mod transfer;
+pub mod video_recording;
+pub mod watch_folder;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 #[must_use]
@@ -46,6 +84,8 @@ pub struct FrameStats {
     pub frame_time: f32,
     pub frame_time_max: f32,
     pub frame_count: usize,
+    pub counters: FrameCounters,
+    pub lag_frames: u32,
 }
 
 impl Default for FrameStats {
@@ -57,6 +97,8 @@ impl Default for FrameStats {
             frame_time: 0.0,
             frame_time_max: 0.0,
             frame_count: 0,
+            counters: FrameCounters::default(),
+            lag_frames: 0,
         }
     }
 }
@@ -181,6 +223,17 @@ impl Multi {
         cfg: &Config,
     ) {
         debug!("emulation thread started");
+        // Failing to tune the thread is not fatal, emulation just runs with OS defaults
+        if cfg.emulation.high_priority {
+            if let Err(err) = thread::set_high_priority() {
+                warn!("{err:?}");
+            }
+        }
+        if let Some(core) = cfg.emulation.cpu_affinity {
+            if let Err(err) = thread::set_affinity(core) {
+                warn!("{err:?}");
+            }
+        }
         let mut state = State::new(tx, frame_tx, cfg); // Has to be created on the thread, since
         loop {
             #[cfg(feature = "profiling")]
@@ -263,6 +316,8 @@ pub struct State {
     tx: NesEventProxy,
     control_deck: ControlDeck,
     audio: Audio,
+    video_format: VideoFormat,
+    video_recording: Option<VideoRecording>,
     frame_tx: BufSender<Frame, FrameRecycle>,
     frame_latency: usize,
     target_frame_duration: Duration,
@@ -275,7 +330,24 @@ pub struct State {
     rewinding: bool,
     rewind: Rewind,
     record: Record,
+    background_record: BackgroundRecord,
     replay: Replay,
+    seek: Option<Seek>,
+    compare: Option<Compare>,
+    /// When the front panel reset button was pressed, if it's being held.
+    reset_pressed: Option<Instant>,
+    /// Configured NES region, used for ROMs without a region in `rom_regions`.
+    region: NesRegion,
+    rom_regions: BTreeMap<String, NesRegion>,
+    memory_watch: MemoryWatch,
+    quick_slots: QuickSlots,
+    checkpoints: Checkpoints,
+    bookmarks: Bookmarks,
+    watch_folder: WatchFolder,
+    /// Saves that failed to be written, kept until they're retried or discarded.
+    failed_saves: Vec<PendingSave>,
+    netplay: Option<Netplay>,
+    netplay_input_delay: u32,
     save_slot: u8,
     auto_save: bool,
     auto_save_interval: Duration,
@@ -284,6 +356,16 @@ pub struct State {
     speed: f32,
     run_ahead: usize,
     show_frame_stats: bool,
+    /// Whether the save state manager is shown, which needs save states sent as they change.
+    show_save_states: bool,
+    frame_step_audio: bool,
+    /// Send audio samples for the audio scope overlay each frame.
+    audio_scope: bool,
+    cpu_debugger: bool,
+    #[cfg(feature = "debug")]
+    bus_activity_window: u32,
+    #[cfg(feature = "debug")]
+    bus_activity_start_frame: u32,
 }
 
 impl Drop for State {
@@ -293,14 +375,21 @@ impl Drop for State {
 }
 
 impl State {
+    /// Instructions disassembled before and after the program counter for the CPU debugger.
+    const DEBUGGER_INSTRS_BEFORE: usize = 16;
+    const DEBUGGER_INSTRS_AFTER: usize = 32;
+
     fn new(tx: NesEventProxy, frame_tx: BufSender<Frame, FrameRecycle>, cfg: &Config) -> Self {
         let mut control_deck = ControlDeck::with_config(cfg.deck.clone());
-        let audio = Audio::new(
+        let mut audio = Audio::new(
             cfg.audio.enabled,
             Apu::DEFAULT_SAMPLE_RATE,
             cfg.audio.latency,
             cfg.audio.buffer_size,
         );
+        audio.monitor_recording = cfg.audio.monitor_recording;
+        audio.recording_format = cfg.audio.recording_format;
+        audio.split_channels = cfg.audio.split_channels;
         if Apu::DEFAULT_SAMPLE_RATE != audio.sample_rate {
             control_deck.set_sample_rate(audio.sample_rate);
         }
@@ -308,12 +397,16 @@ impl State {
             cfg.emulation.rewind,
             cfg.emulation.rewind_seconds,
             cfg.emulation.rewind_interval,
+            cfg.emulation.rewind_memory_budget,
         );
         let target_frame_duration = FrameRate::from(cfg.deck.region).duration();
+        Self::set_save_encryption(cfg.emulation.encrypt_saves);
         let mut state = Self {
             tx,
             control_deck,
             audio,
+            video_format: cfg.emulation.video_format,
+            video_recording: None,
             frame_tx,
             frame_latency: 1,
             target_frame_duration,
@@ -326,8 +419,29 @@ impl State {
                 && std::thread::available_parallelism().is_ok_and(|count| count.get() > 1),
             rewinding: false,
             rewind,
-            record: Record::new(),
+            record: Record::with_info(
+                cfg.emulation.replay_author.clone(),
+                cfg.emulation.replay_description.clone(),
+                cfg.emulation.replay_format,
+            ),
+            background_record: BackgroundRecord::new(
+                cfg.emulation.background_replay,
+                cfg.emulation.background_replay_minutes,
+            ),
             replay: Replay::new(),
+            seek: None,
+            compare: None,
+            reset_pressed: None,
+            region: cfg.deck.region,
+            rom_regions: cfg.emulation.rom_regions.clone(),
+            memory_watch: MemoryWatch::new(),
+            quick_slots: QuickSlots::default(),
+            checkpoints: Checkpoints::default(),
+            bookmarks: Bookmarks::default(),
+            watch_folder: WatchFolder::new(cfg.emulation.watch_folder.clone()),
+            failed_saves: Vec::new(),
+            netplay: None,
+            netplay_input_delay: cfg.emulation.netplay_input_delay,
             save_slot: cfg.emulation.save_slot,
             auto_save: cfg.emulation.auto_save,
             auto_save_interval: cfg.emulation.auto_save_interval,
@@ -336,6 +450,14 @@ impl State {
             speed: cfg.emulation.speed,
             run_ahead: cfg.emulation.run_ahead,
             show_frame_stats: false,
+            show_save_states: false,
+            frame_step_audio: cfg.audio.frame_step_audio,
+            audio_scope: cfg.renderer.show_audio_scope,
+            cpu_debugger: false,
+            #[cfg(feature = "debug")]
+            bus_activity_window: 0,
+            #[cfg(feature = "debug")]
+            bus_activity_start_frame: 0,
         };
         state.update_region(cfg.deck.region);
         state
@@ -350,13 +472,21 @@ impl State {
         writer: impl FnOnce(&mut ControlDeck) -> control_deck::Result<T>,
     ) -> Option<T> {
         writer(&mut self.control_deck)
-            .map_err(|err| {
-                self.set_run_state(RunState::Paused);
-                self.on_error(err);
-            })
+            .map_err(|err| self.on_clock_error(err))
             .ok()
     }
 
+    fn on_clock_error(&mut self, err: control_deck::Error) {
+        self.set_run_state(RunState::Paused);
+        if let control_deck::Error::Breakpoint(breakpoint) = err {
+            // Show the partially rendered frame up to the breakpoint
+            self.send_frame();
+            self.add_message(MessageType::Info, format!("Breakpoint hit: {breakpoint}"));
+        } else {
+            self.on_error(err);
+        }
+    }
+
     fn on_error(&mut self, err: impl Into<anyhow::Error>) {
         let err = err.into();
         error!("Emulation error: {err:?}");
@@ -371,6 +501,7 @@ impl State {
         match event {
             NesEvent::Ui(UiEvent::Terminate) => {
                 self.unload_rom();
+                self.flush_failed_saves();
                 debug!("emulation stopped");
             }
             NesEvent::Emulation(event) => self.on_emulation_event(event),
@@ -391,26 +522,40 @@ impl State {
             EmulationEvent::RemoveDebugger(debugger) => {
                 self.control_deck.remove_debugger(debugger.clone());
             }
+            EmulationEvent::AddBreakpoint(breakpoint) => {
+                self.control_deck.add_breakpoint(*breakpoint);
+            }
+            EmulationEvent::RemoveBreakpoint(breakpoint) => {
+                self.control_deck.remove_breakpoint(*breakpoint);
+            }
+            EmulationEvent::ClearBreakpoints => self.control_deck.clear_breakpoints(),
             EmulationEvent::AudioRecord(recording) => {
                 if self.control_deck.is_running() {
                     self.audio_record(*recording);
                 }
             }
+            EmulationEvent::VideoRecord(recording) => {
+                if self.control_deck.is_running() {
+                    self.video_record(*recording);
+                }
+            }
             EmulationEvent::DebugStep(step) => {
                 if self.control_deck.is_running() {
+                    // Record history while paused and stepping so that steps can be undone
+                    if self.run_state.paused() {
+                        self.control_deck.set_step_history_enabled(true);
+                    }
                     match step {
                         DebugStep::Into => {
                             self.write_deck(|deck| deck.clock_instr());
                             self.send_frame();
                         }
                         DebugStep::Out => {
-                            // TODO: track stack frames list on jsr, irq, brk
-                            // while stack frame == previous stack frame, clock_instr, send_frame
+                            self.write_deck(|deck| deck.step_out());
                             self.send_frame();
                         }
                         DebugStep::Over => {
-                            // TODO: track stack frames list on jsr, irq, brk
-                            // while stack frame != previous stack frame, clock_instr, send_frame
+                            self.write_deck(|deck| deck.step_over());
                             self.send_frame();
                         }
                         DebugStep::Scanline => {
@@ -419,37 +564,77 @@ impl State {
                             }
                         }
                         DebugStep::Frame => {
+                            let scrub = self.frame_step_audio && self.run_state.paused();
+                            if scrub {
+                                self.control_deck.clear_audio_samples();
+                            }
                             if self.write_deck(|deck| deck.clock_frame()).is_some() {
+                                if scrub {
+                                    self.audio.scrub(self.control_deck.audio_samples());
+                                    self.control_deck.clear_audio_samples();
+                                }
+                                self.send_frame();
+                            }
+                        }
+                        DebugStep::Back => {
+                            if self.write_deck(|deck| deck.step_back()).is_some() {
                                 self.send_frame();
                             }
                         }
                     }
                 }
             }
+            EmulationEvent::CpuDebugger(enabled) => {
+                self.cpu_debugger = *enabled;
+                self.send_cpu_debug_state();
+            }
+            EmulationEvent::DmcSampleLog(enabled) => {
+                self.control_deck.set_dmc_sample_log_enabled(*enabled);
+            }
             EmulationEvent::EmulatePpuWarmup(enabled) => {
                 self.control_deck.set_emulate_ppu_warmup(*enabled);
             }
+            EmulationEvent::ExportDmcSamples => self.export_dmc_samples(),
             EmulationEvent::InstantRewind => {
-                if self.control_deck.is_running() {
+                if self.control_deck.is_running() && !self.netplay_blocked() {
                     self.instant_rewind();
                 }
             }
+            EmulationEvent::InterruptLog(enabled) => {
+                self.control_deck.set_interrupt_log_enabled(*enabled);
+            }
+            #[cfg(feature = "debug")]
+            EmulationEvent::BusActivity(window) => {
+                self.control_deck.set_bus_activity_enabled(false);
+                if let Some(window) = window {
+                    self.bus_activity_window = *window;
+                    self.bus_activity_start_frame = self.control_deck.frame_number();
+                    self.control_deck.set_bus_activity_enabled(true);
+                }
+            }
             EmulationEvent::Joypad((player, button, state)) => {
-                if self.control_deck.is_running() {
+                if self.netplay_connected() {
+                    // Local input is always sent as the netplay player
+                    if *player == Player::One {
+                        self.netplay_button(*button, *state == ElementState::Pressed);
+                    }
+                } else if self.control_deck.is_running() {
                     let pressed = *state == ElementState::Pressed;
                     let joypad = self.control_deck.joypad_mut(*player);
                     joypad.set_button(*button, pressed);
                     self.record
                         .push(self.control_deck.frame_number(), event.clone());
+                    self.background_record
+                        .push(self.control_deck.frame_number(), event.clone());
                 }
             }
             EmulationEvent::LoadReplay((name, replay)) => {
-                if self.control_deck.is_running() {
+                if self.control_deck.is_running() && !self.netplay_blocked() {
                     self.load_replay(name, &mut io::Cursor::new(replay));
                 }
             }
             EmulationEvent::LoadReplayPath(path) => {
-                if self.control_deck.is_running() {
+                if self.control_deck.is_running() && !self.netplay_blocked() {
                     self.load_replay_path(path);
                 }
             }
@@ -457,7 +642,26 @@ impl State {
                 self.load_rom(name, &mut io::Cursor::new(rom));
             }
             EmulationEvent::LoadRomPath(path) => self.load_rom_path(path),
-            EmulationEvent::LoadState(slot) => self.load_state(*slot),
+            EmulationEvent::LoadState(slot) => {
+                if !self.netplay_blocked() {
+                    self.load_state(*slot);
+                }
+            }
+            EmulationEvent::LoadStateTransfer(data) => {
+                if !self.netplay_blocked() {
+                    self.load_state_transfer(data.as_ref());
+                }
+            }
+            EmulationEvent::MemoryFreeze((addr, val)) => self.freeze_memory(*addr, *val),
+            EmulationEvent::MemorySnapshots((before, after)) => {
+                if self.control_deck.is_running() {
+                    self.send_memory_snapshots(*before, *after);
+                }
+            }
+            EmulationEvent::MemoryWatch(addrs) => self.memory_watch.addrs.clone_from(addrs),
+            EmulationEvent::NetplayHost(port) => self.netplay_host(*port),
+            EmulationEvent::NetplayJoin(addr) => self.netplay_join(addr),
+            EmulationEvent::NetplayDisconnect => self.netplay_disconnect(),
             EmulationEvent::RunState(mode) => self.set_run_state(*mode),
             EmulationEvent::ReplayRecord(recording) => {
                 if self.control_deck.is_running() {
@@ -466,7 +670,7 @@ impl State {
             }
             EmulationEvent::Reset(kind) => {
                 self.frame_time_diag.reset();
-                if self.control_deck.is_running() {
+                if self.control_deck.is_running() && !self.netplay_blocked() {
                     self.control_deck.reset(*kind);
                     self.set_run_state(RunState::Running);
                     match kind {
@@ -475,9 +679,19 @@ impl State {
                     }
                 }
             }
+            EmulationEvent::ResetButton(pressed) => {
+                if !(*pressed && self.netplay_blocked()) {
+                    self.reset_button(*pressed);
+                }
+            }
+            EmulationEvent::TogglePower => {
+                if !self.netplay_blocked() {
+                    self.toggle_power();
+                }
+            }
             EmulationEvent::RequestFrame => self.send_frame(),
             EmulationEvent::Rewinding(rewind) => {
-                if self.control_deck.is_running() {
+                if self.control_deck.is_running() && !(*rewind && self.netplay_blocked()) {
                     if self.rewind.enabled {
                         self.rewinding = *rewind;
                         if self.rewinding {
@@ -488,7 +702,64 @@ impl State {
                     }
                 }
             }
+            EmulationEvent::ShowRewindTimeline(show) => {
+                if !(*show && self.netplay_blocked()) {
+                    self.show_rewind_timeline(*show);
+                }
+            }
+            EmulationEvent::PreviewRewind(offset) => self.preview_rewind(*offset),
+            EmulationEvent::CommitRewind(offset) => self.commit_rewind(*offset),
             EmulationEvent::SaveState(slot) => self.save_state(*slot, false),
+            EmulationEvent::SafetySaveState => self.safety_save_state(),
+            EmulationEvent::QuickSaveState(slot) => self.quick_save_state(*slot),
+            EmulationEvent::QuickLoadState(slot) => {
+                if !self.netplay_blocked() {
+                    self.quick_load_state(*slot);
+                }
+            }
+            EmulationEvent::SaveCheckpoint => self.save_checkpoint(),
+            EmulationEvent::LoadPreviousCheckpoint => {
+                if !self.netplay_blocked() {
+                    self.load_previous_checkpoint();
+                }
+            }
+            EmulationEvent::AddBookmark => self.add_bookmark(),
+            EmulationEvent::LoadBookmark(id) => {
+                if !self.netplay_blocked() {
+                    self.load_bookmark(*id);
+                }
+            }
+            EmulationEvent::RemoveBookmark(id) => self.remove_bookmark(*id),
+            EmulationEvent::BookmarkNote((id, note)) => self.set_bookmark_note(*id, note.clone()),
+            EmulationEvent::PersistQuickState(slot) => self.persist_quick_state(*slot),
+            EmulationEvent::SeekToFrame { frame, video } => {
+                if self.control_deck.is_running() && !self.netplay_blocked() {
+                    self.seek_to_frame(*frame, *video);
+                }
+            }
+            EmulationEvent::CancelSeek => self.cancel_seek(),
+            EmulationEvent::CompareReplays((a, b)) => self.compare_replays(a, b),
+            EmulationEvent::CancelCompare => self.cancel_compare(),
+            EmulationEvent::SendState => self.send_state(),
+            EmulationEvent::ResumeAudio => {
+                if !self.run_state.paused() {
+                    self.audio.resume();
+                }
+            }
+            EmulationEvent::StopReplay => {
+                self.replay.stop();
+                self.update_subtitle();
+                self.send_hash_status(
+                    self.control_deck.frame_number(),
+                    self.control_deck.state_hash(),
+                );
+                self.set_run_state(RunState::Running);
+            }
+            EmulationEvent::AddSubtitle { text, seconds } => {
+                let frames = (seconds / self.target_frame_duration.as_secs_f32()).round() as u32;
+                self.record
+                    .add_subtitle(self.control_deck.frame_number(), frames, text.clone());
+            }
             EmulationEvent::ShowFrameStats(show) => {
                 self.frame_time_diag.reset();
                 self.show_frame_stats = *show;
@@ -506,16 +777,54 @@ impl State {
                     }
                 }
             }
+            EmulationEvent::ExportSaveStateGallery => self.export_save_state_gallery(),
+            EmulationEvent::ShowSaveStates(show) => self.show_save_states(*show),
+            EmulationEvent::DeleteSaveState(slot) => self.delete_save_state(*slot),
+            EmulationEvent::ExportSaveState((slot, dir)) => self.export_save_state(*slot, dir),
+            EmulationEvent::SaveBackgroundReplay => self.save_background_replay(),
+            EmulationEvent::CopyFrame(copy) => {
+                if self.control_deck.is_running() {
+                    self.copy_frame(*copy);
+                }
+            }
+            EmulationEvent::CopyFrameInfo => self.copy_frame_info(),
             EmulationEvent::UnloadRom => self.unload_rom(),
+            EmulationEvent::RetryFailedSaves(dir) => self.retry_failed_saves(dir.as_deref()),
+            EmulationEvent::DiscardFailedSaves => self.discard_failed_saves(),
             EmulationEvent::ZapperAim((x, y)) => {
                 self.control_deck.aim_zapper(*x, *y);
                 self.record
                     .push(self.control_deck.frame_number(), event.clone());
+                self.background_record
+                    .push(self.control_deck.frame_number(), event.clone());
             }
             EmulationEvent::ZapperTrigger => {
                 self.control_deck.trigger_zapper();
                 self.record
                     .push(self.control_deck.frame_number(), event.clone());
+                self.background_record
+                    .push(self.control_deck.frame_number(), event.clone());
+            }
+            EmulationEvent::MouseMotion((dx, dy)) => {
+                self.control_deck.mouse_motion(*dx, *dy);
+                self.record
+                    .push(self.control_deck.frame_number(), event.clone());
+                self.background_record
+                    .push(self.control_deck.frame_number(), event.clone());
+            }
+            EmulationEvent::MouseButton((button, pressed)) => {
+                self.control_deck.set_mouse_button(*button, *pressed);
+                self.record
+                    .push(self.control_deck.frame_number(), event.clone());
+                self.background_record
+                    .push(self.control_deck.frame_number(), event.clone());
+            }
+            EmulationEvent::InsertCoin(slot) => {
+                self.control_deck.insert_coin((*slot).into());
+                self.record
+                    .push(self.control_deck.frame_number(), event.clone());
+                self.background_record
+                    .push(self.control_deck.frame_number(), event.clone());
             }
         }
     }
@@ -553,6 +862,10 @@ impl State {
                 },
                 Err(err) => self.on_error(err),
             },
+            ConfigEvent::AudioFrameStep(enabled) => self.frame_step_audio = *enabled,
+            ConfigEvent::AudioMonitorRecording(enabled) => self.audio.monitor_recording = *enabled,
+            ConfigEvent::AudioRecordingFormat(format) => self.audio.recording_format = *format,
+            ConfigEvent::AudioSplitChannels(enabled) => self.audio.split_channels = *enabled,
             ConfigEvent::AudioLatency(latency) => {
                 if let Err(err) = self.audio.set_latency(*latency) {
                     self.on_error(err);
@@ -561,12 +874,23 @@ impl State {
             ConfigEvent::AutoLoad(enabled) => self.auto_load = *enabled,
             ConfigEvent::AutoSave(enabled) => self.auto_save = *enabled,
             ConfigEvent::AutoSaveInterval(interval) => self.auto_save_interval = *interval,
+            ConfigEvent::BackgroundReplay(enabled) => self.background_record.set_enabled(*enabled),
+            ConfigEvent::BackgroundReplayMinutes(minutes) => {
+                self.background_record.set_minutes(*minutes);
+            }
+            ConfigEvent::ColorFilter(color_filter) => {
+                self.control_deck.set_color_filter(*color_filter);
+                if self.run_state.paused() && self.control_deck.is_running() {
+                    self.send_frame();
+                }
+            }
             ConfigEvent::ConcurrentDpad(enabled) => {
                 self.control_deck.set_concurrent_dpad(*enabled);
             }
             ConfigEvent::CycleAccurate(enabled) => {
                 self.control_deck.set_cycle_accurate(*enabled);
             }
+            ConfigEvent::EncryptSaves(enabled) => Self::set_save_encryption(*enabled),
             ConfigEvent::FourPlayer(four_player) => {
                 self.control_deck.set_four_player(*four_player);
             }
@@ -579,26 +903,63 @@ impl State {
             ConfigEvent::GenieCodeRemoved(code) => {
                 self.control_deck.remove_genie_code(code);
             }
+            ConfigEvent::RamRetention(ram_retention) => {
+                self.control_deck.set_ram_retention(*ram_retention);
+            }
             ConfigEvent::RamState(ram_state) => {
                 self.control_deck.set_ram_state(*ram_state);
             }
+            ConfigEvent::ReplayAuthor(author) => self.record.info.author.clone_from(author),
+            ConfigEvent::ReplayDescription(description) => {
+                self.record.info.description.clone_from(description);
+            }
             ConfigEvent::Region(region) => {
+                self.region = *region;
                 self.control_deck.set_region(*region);
-                self.update_region(*region);
+                self.update_region(self.control_deck.region());
             }
             ConfigEvent::RewindEnabled(enabled) => self.rewind.set_enabled(*enabled),
             ConfigEvent::RewindInterval(interval) => self.rewind.set_interval(*interval),
+            ConfigEvent::RewindMemoryBudget(mb) => self.rewind.set_memory_budget(*mb),
             ConfigEvent::RewindSeconds(seconds) => self.rewind.set_seconds(*seconds),
+            ConfigEvent::RomRegion((name, region)) => {
+                match region {
+                    Some(region) => self.rom_regions.insert(name.clone(), *region),
+                    None => self.rom_regions.remove(name),
+                };
+                if self
+                    .control_deck
+                    .loaded_rom()
+                    .is_some_and(|rom| &rom.name == name)
+                {
+                    self.apply_rom_region();
+                }
+            }
             ConfigEvent::RunAhead(run_ahead) => self.run_ahead = *run_ahead,
+            ConfigEvent::NetplayInputDelay(delay) => self.set_netplay_input_delay(*delay),
             ConfigEvent::MapperRevisions(revs) => {
                 self.control_deck.set_mapper_revisions(*revs);
             }
+            ConfigEvent::MousePort(port) => self.control_deck.connect_mouse(*port),
             ConfigEvent::SaveSlot(slot) => self.save_slot = *slot,
+            ConfigEvent::ShowAudioScope(show) => self.audio_scope = *show,
             ConfigEvent::Speed(speed) => {
                 self.speed = *speed;
                 self.control_deck.set_frame_speed(*speed);
             }
             ConfigEvent::VideoFilter(filter) => self.control_deck.set_filter(*filter),
+            ConfigEvent::VsDipSwitches(dip_switches) => {
+                self.control_deck.set_vs_dip_switches(*dip_switches);
+            }
+            ConfigEvent::WatchFolder(dir) => {
+                self.watch_folder.set_dir(dir.clone());
+                if let Some(dir) = dir {
+                    self.add_message(
+                        MessageType::Info,
+                        format!("Watching {} for New ROMs", dir.display()),
+                    );
+                }
+            }
             ConfigEvent::ZapperConnected(connected) => {
                 self.control_deck.connect_zapper(*connected);
             }
@@ -637,45 +998,136 @@ impl State {
             frame_time: frame_time * 1000.0,
             frame_time_max: frame_time_max * 1000.0,
             frame_count: self.frame_time_diag.frame_count,
+            counters: self.control_deck.frame_counters(),
+            lag_frames: self.control_deck.lag_frames(),
         }));
     }
 
+    fn send_interrupts(&mut self) {
+        if self.control_deck.interrupt_log().enabled()
+            && !self.control_deck.interrupt_log().is_empty()
+        {
+            self.tx
+                .event(DebugEvent::Interrupts(self.control_deck.take_interrupts()));
+        }
+    }
+
+    /// Require the save password to be entered before saving or loading, or save unencrypted.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn set_save_encryption(enabled: bool) {
+        use tetanes_core::fs::{self, Encryption};
+
+        if !enabled {
+            if let Err(err) = fs::set_encryption_password(None) {
+                error!("failed to disable save encryption: {err:?}");
+            }
+        } else if fs::encryption() == Encryption::Disabled {
+            fs::lock_encryption();
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn set_save_encryption(_enabled: bool) {}
+
+    fn send_cpu_debug_state(&mut self) {
+        if self.cpu_debugger && self.control_deck.is_running() {
+            let state = self
+                .control_deck
+                .cpu_debug_state(Self::DEBUGGER_INSTRS_BEFORE, Self::DEBUGGER_INSTRS_AFTER);
+            self.tx.event(DebugEvent::Cpu(Box::new(state)));
+        }
+    }
+
+    #[cfg(feature = "debug")]
+    fn send_bus_activity(&mut self) {
+        if !self.control_deck.bus_activity().enabled() {
+            return;
+        }
+        let frame_number = self.control_deck.frame_number();
+        let elapsed = frame_number.wrapping_sub(self.bus_activity_start_frame);
+        if elapsed >= self.bus_activity_window {
+            self.bus_activity_start_frame = frame_number;
+            let activity = self.control_deck.take_bus_activity();
+            self.tx.event(DebugEvent::BusActivity((activity, elapsed)));
+        }
+    }
+
     fn send_frame(&mut self) {
         match self.frame_tx.try_send_ref() {
             Ok(mut frame) => self.control_deck.frame_buffer_into(&mut frame),
             Err(TrySendError::Full(_)) => trace!("dropped frame"),
             Err(_) => shutdown(&self.tx, "failed to get frame"),
         }
+        self.send_interrupts();
+        self.send_cpu_debug_state();
     }
 
     fn set_run_state(&mut self, mode: RunState) {
         if !self.control_deck.cpu_corrupted() {
+            let was_paused = self.run_state.paused();
             self.run_state = mode;
             if self.run_state.paused() {
+                if !was_paused {
+                    // Fade out what's left playing instead of cutting it off with a pop
+                    self.control_deck.fade_out_audio();
+                    self.audio.process(self.control_deck.audio_samples());
+                    self.control_deck.clear_audio_samples();
+                }
                 if let Some(rom) = self.control_deck.loaded_rom() {
                     if let Err(err) = self.record.stop(&rom.name) {
                         self.on_error(err);
                     }
                 }
             } else {
+                // Step history is only recorded while debug stepping
+                self.control_deck.set_step_history_enabled(false);
                 self.last_auto_save = Instant::now();
                 // To avoid having a large dip in frame stats when unpausing
                 self.last_frame_time = Instant::now();
             }
             self.audio.pause(self.run_state.paused());
+            if !self.run_state.paused() {
+                self.audio.resume();
+            }
         }
     }
 
     fn save_state(&mut self, slot: u8, auto: bool) {
         if let Some(rom) = self.control_deck.loaded_rom() {
-            let data_dir = Config::save_path(&rom.name, slot);
-            match self.control_deck.save_state(data_dir) {
+            let name = rom.name.clone();
+            let save_path = Config::save_path(&name, slot);
+            match self.control_deck.save_state(&save_path) {
                 Ok(_) => {
                     if !auto {
                         self.add_message(MessageType::Info, format!("State {slot} Saved"));
+                        self.tx.event(RendererEvent::StateSaved(slot));
                     }
+                    self.update_save_states();
+                }
+                Err(err) => {
+                    let cpu = self.control_deck.cpu().clone();
+                    self.on_save_failed(SaveKind::State(slot), &name, save_path, cpu, err);
+                }
+            }
+        }
+    }
+
+    fn safety_save_state(&mut self) {
+        if let Some(rom) = self.control_deck.loaded_rom() {
+            let name = rom.name.clone();
+            let save_path = Config::safety_save_path(&name);
+            match self.control_deck.save_state(&save_path) {
+                Ok(_) => self.add_message(
+                    MessageType::Info,
+                    format!(
+                        "Safety State Saved to {}, Save Slots Unchanged",
+                        save_path.display()
+                    ),
+                ),
+                Err(err) => {
+                    let cpu = self.control_deck.cpu().clone();
+                    self.on_save_failed(SaveKind::Safety, &name, save_path, cpu, err);
                 }
-                Err(err) => self.on_error(err),
             }
         }
     }
@@ -684,7 +1136,12 @@ impl State {
         if let Some(rom) = self.control_deck.loaded_rom() {
             let save_path = Config::save_path(&rom.name, slot);
             match self.control_deck.load_state(save_path) {
-                Ok(_) => self.add_message(MessageType::Info, format!("State {slot} Loaded")),
+                Ok(_) => {
+                    self.record.rerecord(self.control_deck.frame_number());
+                    self.background_record.clear();
+                    self.add_message(MessageType::Info, format!("State {slot} Loaded"));
+                    self.tx.event(RendererEvent::StateLoaded(slot));
+                }
                 Err(control_deck::Error::NoSaveStateFound) => {
                     self.add_message(MessageType::Warn, format!("State {slot} Not Found"));
                 }
@@ -695,20 +1152,79 @@ impl State {
         }
     }
 
-    fn unload_rom(&mut self) {
+    /// Serve the current state for another device to download.
+    fn send_state(&mut self) {
         if let Some(rom) = self.control_deck.loaded_rom() {
+            let transfer = Transfer {
+                rom: rom.name.clone(),
+                cpu: self.control_deck.cpu().clone(),
+            };
+            match transfer.encode().and_then(transfer::serve) {
+                Ok(url) => self.tx.event(RendererEvent::StateTransferLink(url)),
+                Err(err) => self.on_error(err),
+            }
+        }
+    }
+
+    /// Load a state received from another device.
+    fn load_state_transfer(&mut self, data: &[u8]) {
+        let Some(loaded_rom) = self.control_deck.loaded_rom().map(|rom| rom.name.clone()) else {
+            self.add_message(MessageType::Warn, "Load a ROM before receiving a state");
+            return;
+        };
+        match Transfer::decode(data) {
+            Ok(Transfer { rom, .. }) if rom != loaded_rom => {
+                self.add_message(
+                    MessageType::Warn,
+                    format!("Received state is for {rom:?}. Load it before receiving."),
+                );
+            }
+            Ok(Transfer { mut cpu, .. }) => {
+                cpu.bus.input.clear();
+                self.control_deck.load_cpu(cpu);
+                self.record.rerecord(self.control_deck.frame_number());
+                self.background_record.clear();
+                self.add_message(MessageType::Info, "State Received");
+            }
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    fn unload_rom(&mut self) {
+        self.netplay_disconnect();
+        self.cancel_seek();
+        self.cancel_compare();
+        if let Some(rom) = self.control_deck.loaded_rom().cloned() {
             if self.auto_save {
-                let save_path = Config::save_path(&rom.name, self.save_slot);
-                if let Err(err) = self.control_deck.save_state(save_path) {
-                    self.on_error(err);
-                }
+                self.save_state(self.save_slot, true);
             }
             self.replay_record(false);
+            self.video_record(false);
+            self.reset_pressed = None;
             self.rewind.clear();
+            self.background_record.clear();
+            self.quick_slots.clear();
+            self.checkpoints.clear();
+            self.bookmarks.clear();
+            self.send_bookmarks();
+            self.memory_watch.clear();
+            self.control_deck.set_dmc_sample_log_enabled(false);
             let _ = self.audio.stop();
+            // Unloading the ROM also unloads the cart, so keep a copy in case saving SRAM fails
+            let cpu = rom.battery_backed.then(|| self.control_deck.cpu().clone());
             if let Err(err) = self.control_deck.unload_rom() {
-                self.on_error(err);
+                match cpu {
+                    Some(cpu) => {
+                        let path = self
+                            .control_deck
+                            .sram_dir(&rom.name)
+                            .with_extension(DeckConfig::SRAM_EXTENSION);
+                        self.on_save_failed(SaveKind::Sram, &rom.name, path, cpu, err);
+                    }
+                    None => self.on_error(err),
+                }
             }
+            self.update_save_states();
             self.tx.event(RendererEvent::RomUnloaded);
             self.tx.event(RendererEvent::RequestRedraw {
                 viewport_id: ViewportId::ROOT,
@@ -727,6 +1243,15 @@ impl State {
                 }
             }
         }
+        if rom.trainer {
+            self.add_message(
+                MessageType::Info,
+                "ROM includes a 512-byte trainer, loaded at $7000",
+            );
+        }
+        self.apply_rom_region();
+        self.load_bookmarks();
+        self.update_save_states();
         if let Err(err) = self.audio.start() {
             self.on_error(err);
         }
@@ -765,16 +1290,69 @@ impl State {
             format!("Loaded Replay Recording {:?}", name.as_ref()),
         );
         self.control_deck.load_cpu(start);
-        self.set_run_state(RunState::Running);
-        self.tx.event(RendererEvent::ReplayLoaded);
+        self.control_deck.reset_lag_frames();
+        self.background_record.clear();
+        // Wait to start playback until the replay info has been confirmed
+        self.set_run_state(RunState::ManuallyPaused);
+        self.tx
+            .event(RendererEvent::ReplayLoaded(self.replay.info.clone()));
         self.tx.event(RendererEvent::RequestRedraw {
             viewport_id: ViewportId::ROOT,
             when: Instant::now(),
         });
     }
 
+    /// Power cycles and starts playback of an input script.
+    fn load_script(&mut self, name: impl AsRef<str>, script: &[u8]) {
+        let script = match std::str::from_utf8(script) {
+            Ok(script) => script,
+            Err(err) => return self.on_error(err),
+        };
+        self.control_deck.reset(ResetKind::Hard);
+        match self
+            .replay
+            .load_script(script, self.control_deck.frame_number())
+        {
+            Ok(()) => self.on_load_replay(self.control_deck.cpu().clone(), name),
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    /// Power cycles and starts playback of a TAS movie.
+    fn load_movie(&mut self, name: impl AsRef<str>, format: ReplayFormat, movie: &[u8]) {
+        let movie = match Movie::parse(format, movie) {
+            Ok(movie) => movie,
+            Err(err) => return self.on_error(err),
+        };
+        if movie.pal != (self.control_deck.region() == NesRegion::Pal) {
+            warn!("movie region differs from the emulated region and may desync");
+        }
+        self.control_deck.reset(ResetKind::Hard);
+        self.replay
+            .load_movie(&movie, self.control_deck.frame_number());
+        self.on_load_replay(self.control_deck.cpu().clone(), name);
+    }
+
     fn load_replay_path(&mut self, path: impl AsRef<Path>) {
         let path = path.as_ref();
+        if self.control_deck.loaded_rom().is_none() {
+            self.add_message(MessageType::Warn, "Load a ROM before loading a replay");
+            return;
+        }
+        if path.extension().is_some_and(|ext| ext == script::EXTENSION) {
+            match tetanes_core::fs::load_raw(path) {
+                Ok(script) => self.load_script(path.to_string_lossy(), &script),
+                Err(err) => self.on_error(err),
+            }
+            return;
+        }
+        if let Some(format) = ReplayFormat::from_path(path) {
+            match tetanes_core::fs::load_raw(path) {
+                Ok(movie) => self.load_movie(path.to_string_lossy(), format, &movie),
+                Err(err) => self.on_error(err),
+            }
+            return;
+        }
         match self.replay.load_path(path) {
             Ok(start) => self.on_load_replay(start, path.to_string_lossy()),
             Err(err) => self.on_error(err),
@@ -782,12 +1360,76 @@ impl State {
     }
 
     fn load_replay(&mut self, name: &str, replay: &mut impl Read) {
+        if Path::new(name)
+            .extension()
+            .is_some_and(|ext| ext == script::EXTENSION)
+        {
+            let mut script = Vec::new();
+            match replay.read_to_end(&mut script) {
+                Ok(_) => self.load_script(name, &script),
+                Err(err) => self.on_error(err),
+            }
+            return;
+        }
+        if let Some(format) = ReplayFormat::from_path(name) {
+            let mut movie = Vec::new();
+            match replay.read_to_end(&mut movie) {
+                Ok(_) => self.load_movie(name, format, &movie),
+                Err(err) => self.on_error(err),
+            }
+            return;
+        }
         match self.replay.load(replay) {
             Ok(start) => self.on_load_replay(start, name),
             Err(err) => self.on_error(err),
         }
     }
 
+    /// Sends the replay subtitle to show for the current frame, if it changed.
+    fn update_subtitle(&mut self) {
+        if let Some(subtitle) = self.replay.next_subtitle(self.control_deck.frame_number()) {
+            self.tx.event(RendererEvent::ReplaySubtitle(subtitle));
+        }
+    }
+
+    /// Records the state hash of the last clocked frame while recording and compares it against
+    /// the replay during playback, warning about the first desync.
+    fn check_replay_hash(&mut self) {
+        let frame = self.control_deck.frame_number();
+        let hash = self.control_deck.state_hash();
+        self.record.push_hash(frame, hash);
+        if let Some(expected) = self.replay.check_hash(frame, hash) {
+            warn!(
+                "replay desynced at frame {frame}: expected state hash {:08X}, found {hash:08X}",
+                expected.hash
+            );
+            self.add_message(
+                MessageType::Warn,
+                format!("Replay desynced at frame {frame}"),
+            );
+        }
+        self.send_hash_status(frame, hash);
+    }
+
+    /// Sends the replay state hash to show for the current frame, if any.
+    fn send_hash_status(&mut self, frame: u32, hash: u32) {
+        if let Some(status) = self.replay.next_hash_status(frame, hash) {
+            self.tx.event(RendererEvent::ReplayHash(status));
+        }
+    }
+
+    /// Emulates the region chosen for the loaded ROM, or the configured region otherwise.
+    fn apply_rom_region(&mut self) {
+        let region = self
+            .control_deck
+            .loaded_rom()
+            .and_then(|rom| self.rom_regions.get(&rom.name))
+            .copied()
+            .unwrap_or(self.region);
+        self.control_deck.set_region(region);
+        self.update_region(self.control_deck.region());
+    }
+
     fn update_region(&mut self, region: NesRegion) {
         self.target_frame_duration = FrameRate::from(region).duration();
         self.frame_latency = (self.audio.latency.as_secs_f32()
@@ -809,10 +1451,55 @@ impl State {
                     _ => (),
                 }
             } else if recording {
-                if let Err(err) = self.audio.start_recording() {
+                let audio_channels = self.control_deck.audio_channels();
+                if let Err(err) = self.audio.start_recording(&audio_channels) {
                     self.on_error(err);
                 }
             }
+            self.control_deck
+                .set_channel_split_enabled(self.audio.is_recording_channels());
+        }
+    }
+
+    fn video_record(&mut self, recording: bool) {
+        if recording {
+            if self.video_recording.is_none() {
+                match VideoRecording::create(
+                    self.video_format,
+                    self.control_deck.region(),
+                    self.audio.sample_rate as u32,
+                ) {
+                    Ok(video_recording) => self.video_recording = Some(video_recording),
+                    Err(err) => self.on_error(err),
+                }
+            }
+        } else if let Some(video_recording) = self.video_recording.take() {
+            match video_recording.finalize() {
+                Ok(path) => {
+                    self.add_message(MessageType::Info, format!("Saved Video Recording {path:?}"));
+                }
+                Err(err) => self.on_error(err),
+            }
+        }
+        self.tx.event(RendererEvent::VideoRecording(
+            self.video_recording.is_some(),
+        ));
+    }
+
+    /// Write the samples output by each APU channel to their recordings when recording channels
+    /// separately, disabling the channel split once the recording stops.
+    fn record_channel_samples(&mut self) {
+        if !self.control_deck.channel_split_enabled() {
+            return;
+        }
+        if self.audio.is_recording_channels() {
+            for channel in Channel::ALL {
+                self.audio
+                    .record_channel(channel, self.control_deck.channel_samples(channel));
+            }
+            self.control_deck.clear_channel_samples();
+        } else {
+            self.control_deck.set_channel_split_enabled(false);
         }
     }
 
@@ -820,6 +1507,7 @@ impl State {
         if self.control_deck.is_running() {
             if recording {
                 self.record.start(self.control_deck.cpu().clone());
+                self.control_deck.reset_lag_frames();
             } else if let Some(rom) = self.control_deck.loaded_rom() {
                 match self.record.stop(&rom.name) {
                     Ok(Some(filename)) => {
@@ -835,6 +1523,32 @@ impl State {
         }
     }
 
+    fn save_background_replay(&mut self) {
+        let Some(rom) = self.control_deck.loaded_rom() else {
+            return;
+        };
+        if !self.background_record.enabled {
+            self.add_message(
+                MessageType::Warn,
+                "Background replay recording is disabled. You can enable it in the Preferences menu.",
+            );
+            return;
+        }
+        match self
+            .background_record
+            .save(&rom.name, &self.record.info, self.record.format)
+        {
+            Ok(Some(filename)) => {
+                self.add_message(
+                    MessageType::Info,
+                    format!("Saved Background Replay {filename:?}"),
+                );
+            }
+            Ok(None) => self.add_message(MessageType::Warn, "No Inputs Recorded"),
+            Err(err) => self.on_error(err),
+        }
+    }
+
     fn save_screenshot(&mut self) -> anyhow::Result<PathBuf> {
         let picture_dir = Config::default_picture_dir();
         let filename = picture_dir
@@ -913,30 +1627,59 @@ impl State {
             });
         }
 
+        self.poll_watch_folder();
+        self.poll_netplay();
+
+        if self.clock_seek() {
+            return;
+        }
+        if self.clock_compare() {
+            return;
+        }
+
         if let Some(park_timeout) = self.park_duration() {
             thread::park_timeout(park_timeout);
             return;
         }
 
+        if !self.netplay_ready() {
+            return;
+        }
+
         if self.rewinding {
             match self.rewind.pop() {
                 Some(cpu) => {
                     self.control_deck.load_cpu(cpu);
+                    self.background_record
+                        .truncate(self.control_deck.frame_number());
                     self.send_frame();
                     self.update_frame_stats();
                 }
                 None => self.rewinding = false,
             }
         } else {
-            if let Some(event) = self.replay.next(self.control_deck.frame_number()) {
+            while let Some(event) = self.replay.next(self.control_deck.frame_number()) {
                 self.on_emulation_event(&event);
             }
+            self.update_subtitle();
 
+            self.apply_frozen_memory();
+            let clocked_frame = self.control_deck.frame_number();
             let run_ahead = if self.speed > 1.0 { 0 } else { self.run_ahead };
+            let mut video_recording_err = None;
             let res = self.control_deck.clock_frame_ahead(
                 run_ahead,
                 |_cycles, frame_buffer, audio_samples| {
                     self.audio.process(audio_samples);
+                    if let Some(video_recording) = &mut self.video_recording {
+                        video_recording_err = video_recording
+                            .write_frame(frame_buffer, audio_samples)
+                            .err();
+                    }
+                    if self.audio_scope {
+                        self.tx
+                            .event(RendererEvent::AudioScope(audio_samples.to_vec()));
+                    }
                     match self.frame_tx.try_send_ref() {
                         Ok(mut frame) => {
                             frame.clear();
@@ -947,22 +1690,34 @@ impl State {
                     }
                 },
             );
+            if let Some(err) = video_recording_err {
+                self.video_record(false);
+                self.on_error(err);
+            }
             match res {
                 Ok(()) => {
+                    self.record_channel_samples();
                     self.update_frame_stats();
+                    self.send_interrupts();
+                    self.send_memory_watch();
+                    #[cfg(feature = "debug")]
+                    self.send_bus_activity();
                     if let Err(err) = self.rewind.push(self.control_deck.cpu()) {
                         self.rewind.set_enabled(false);
                         self.on_error(err);
                     }
+                    if let Err(err) = self.background_record.update(self.control_deck.cpu()) {
+                        self.background_record.set_enabled(false);
+                        self.on_error(err);
+                    }
+                    self.check_replay_hash();
+                    self.advance_netplay(clocked_frame);
                     if self.auto_save && self.last_auto_save.elapsed() > self.auto_save_interval {
                         self.last_auto_save = Instant::now();
                         self.save_state(self.save_slot, true);
                     }
                 }
-                Err(err) => {
-                    self.set_run_state(RunState::Paused);
-                    self.on_error(err);
-                }
+                Err(err) => self.on_clock_error(err),
             }
         }
 
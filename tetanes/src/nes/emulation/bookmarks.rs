@@ -0,0 +1,188 @@
+//! Bookmarks: save states for frames marked during play, each with a note and a thumbnail. Unlike
+//! checkpoints they're kept per ROM on disk, so practice routes can jump straight back to them
+//! between sessions.
+
+use crate::nes::{
+    config::Config, emulation::State, event::RendererEvent, renderer::gui::MessageType,
+};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tetanes_core::{cpu::Cpu, fs, ppu::Ppu};
+
+/// A bookmarked frame.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+struct Bookmark {
+    id: u64,
+    frame: u32,
+    note: String,
+    /// Downscaled RGBA frame, [`BookmarkInfo::THUMBNAIL_WIDTH`] pixels wide.
+    thumbnail: Vec<u8>,
+    cpu: Box<Cpu>,
+}
+
+/// A bookmark as shown in the UI, without its save state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct BookmarkInfo {
+    pub id: u64,
+    pub frame: u32,
+    pub note: String,
+    pub thumbnail: Vec<u8>,
+}
+
+impl BookmarkInfo {
+    /// How many times smaller thumbnails are than the frame.
+    const THUMBNAIL_SCALE: u32 = 4;
+    pub const THUMBNAIL_WIDTH: u32 = Ppu::WIDTH / Self::THUMBNAIL_SCALE;
+    pub const THUMBNAIL_HEIGHT: u32 = Ppu::HEIGHT / Self::THUMBNAIL_SCALE;
+}
+
+/// Bookmarks for the loaded ROM, oldest first.
+#[derive(Default, Debug)]
+#[must_use]
+pub struct Bookmarks {
+    path: Option<PathBuf>,
+    bookmarks: Vec<Bookmark>,
+    next_id: u64,
+}
+
+impl Bookmarks {
+    const DIR: &'static str = "bookmarks";
+    const EXTENSION: &'static str = "bookmarks";
+
+    fn path(name: &str) -> PathBuf {
+        Config::default_data_dir()
+            .join(Self::DIR)
+            .join(name)
+            .with_extension(Self::EXTENSION)
+    }
+
+    fn get(&self, id: u64) -> Option<&Bookmark> {
+        self.bookmarks.iter().find(|bookmark| bookmark.id == id)
+    }
+
+    pub fn clear(&mut self) {
+        self.path = None;
+        self.bookmarks.clear();
+        self.next_id = 0;
+    }
+}
+
+/// Downscale an RGBA frame by sampling every [`BookmarkInfo::THUMBNAIL_SCALE`]th pixel.
+fn thumbnail(frame: &[u8]) -> Vec<u8> {
+    let scale = BookmarkInfo::THUMBNAIL_SCALE as usize;
+    let width = Ppu::WIDTH as usize;
+    frame
+        .chunks_exact(4 * width)
+        .step_by(scale)
+        .flat_map(|row| row.chunks_exact(4).step_by(scale).flatten().copied())
+        .collect()
+}
+
+impl State {
+    /// Load bookmarks saved for the loaded ROM.
+    pub fn load_bookmarks(&mut self) {
+        self.bookmarks.clear();
+        let Some(rom) = self.control_deck.loaded_rom() else {
+            return;
+        };
+        let path = Bookmarks::path(&rom.name);
+        if fs::exists(&path) {
+            match fs::load::<Vec<Bookmark>>(&path) {
+                Ok(bookmarks) => {
+                    self.bookmarks.next_id = bookmarks
+                        .iter()
+                        .map(|bookmark| bookmark.id + 1)
+                        .max()
+                        .unwrap_or(0);
+                    self.bookmarks.bookmarks = bookmarks;
+                }
+                Err(err) => self.on_error(err),
+            }
+        }
+        self.bookmarks.path = Some(path);
+        self.send_bookmarks();
+    }
+
+    /// Bookmark the current frame with a save state and thumbnail.
+    pub fn add_bookmark(&mut self) {
+        if self.control_deck.loaded_rom().is_none() {
+            return;
+        }
+        let frame = self.control_deck.frame_number();
+        let id = self.bookmarks.next_id;
+        self.bookmarks.next_id += 1;
+        self.bookmarks.bookmarks.push(Bookmark {
+            id,
+            frame,
+            note: String::new(),
+            thumbnail: thumbnail(self.control_deck.frame_buffer()),
+            cpu: Box::new(self.control_deck.cpu().clone()),
+        });
+        self.save_bookmarks();
+        self.add_message(MessageType::Info, format!("Frame {frame} Bookmarked"));
+    }
+
+    pub fn load_bookmark(&mut self, id: u64) {
+        let Some(bookmark) = self.bookmarks.get(id) else {
+            return;
+        };
+        let frame = bookmark.frame;
+        let mut cpu = Cpu::clone(&bookmark.cpu);
+        cpu.bus.input.clear();
+        self.control_deck.load_cpu(cpu);
+        self.record.rerecord(self.control_deck.frame_number());
+        self.background_record.clear();
+        self.add_message(
+            MessageType::Info,
+            format!("Bookmark at Frame {frame} Loaded"),
+        );
+    }
+
+    pub fn remove_bookmark(&mut self, id: u64) {
+        self.bookmarks
+            .bookmarks
+            .retain(|bookmark| bookmark.id != id);
+        self.save_bookmarks();
+    }
+
+    pub fn set_bookmark_note(&mut self, id: u64, note: String) {
+        if let Some(bookmark) = self
+            .bookmarks
+            .bookmarks
+            .iter_mut()
+            .find(|bookmark| bookmark.id == id)
+        {
+            bookmark.note = note;
+            self.save_bookmarks();
+        }
+    }
+
+    fn save_bookmarks(&mut self) {
+        let res = self
+            .bookmarks
+            .path
+            .as_ref()
+            .map(|path| fs::save(path, &self.bookmarks.bookmarks));
+        if let Some(Err(err)) = res {
+            self.on_error(err);
+        }
+        self.send_bookmarks();
+    }
+
+    pub fn send_bookmarks(&mut self) {
+        let bookmarks = self
+            .bookmarks
+            .bookmarks
+            .iter()
+            .map(|bookmark| BookmarkInfo {
+                id: bookmark.id,
+                frame: bookmark.frame,
+                note: bookmark.note.clone(),
+                thumbnail: bookmark.thumbnail.clone(),
+            })
+            .collect();
+        self.tx.event(RendererEvent::Bookmarks(bookmarks));
+    }
+}
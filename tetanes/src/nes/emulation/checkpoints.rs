@@ -0,0 +1,81 @@
+//! Checkpoints: a ring of memory-only save states captured on demand, independent of save slots.
+//! Unlike rewind, states are only captured when asked, making them a lighter-weight way to
+//! practice a section repeatedly.
+
+use crate::nes::{emulation::State, renderer::gui::MessageType};
+use std::collections::VecDeque;
+use tetanes_core::cpu::Cpu;
+
+/// Most recent checkpoints for the loaded ROM, oldest first.
+#[derive(Default, Debug)]
+#[must_use]
+pub struct Checkpoints {
+    states: VecDeque<Box<Cpu>>,
+    /// How many checkpoints back from the most recent was last loaded.
+    cursor: Option<usize>,
+}
+
+impl Checkpoints {
+    pub const CAPACITY: usize = 8;
+
+    fn push(&mut self, cpu: Cpu) {
+        if self.states.len() == Self::CAPACITY {
+            self.states.pop_front();
+        }
+        self.states.push_back(Box::new(cpu));
+        self.cursor = None;
+    }
+
+    /// Returns the checkpoint before the last one loaded, starting from the most recent and
+    /// wrapping back around to it after the oldest, along with its number counting from oldest.
+    fn previous(&mut self) -> Option<(usize, &Cpu)> {
+        let len = self.states.len();
+        if len == 0 {
+            return None;
+        }
+        let back = self.cursor.map_or(0, |back| (back + 1) % len);
+        self.cursor = Some(back);
+        let index = len - 1 - back;
+        Some((index + 1, &self.states[index]))
+    }
+
+    pub fn clear(&mut self) {
+        self.states.clear();
+        self.cursor = None;
+    }
+}
+
+impl State {
+    /// Capture the current state as the most recent checkpoint, dropping the oldest if full.
+    pub fn save_checkpoint(&mut self) {
+        if self.control_deck.loaded_rom().is_none() {
+            return;
+        }
+        self.checkpoints.push(self.control_deck.cpu().clone());
+        let count = self.checkpoints.states.len();
+        self.add_message(
+            MessageType::Info,
+            format!("Checkpoint {count}/{} Saved", Checkpoints::CAPACITY),
+        );
+    }
+
+    /// Load the checkpoint before the last one loaded, cycling backward through the ring.
+    pub fn load_previous_checkpoint(&mut self) {
+        if self.control_deck.loaded_rom().is_none() {
+            return;
+        }
+        let Some((number, cpu)) = self.checkpoints.previous() else {
+            return self.add_message(MessageType::Warn, "No Checkpoints Saved");
+        };
+        let mut cpu = Cpu::clone(cpu);
+        cpu.bus.input.clear();
+        self.control_deck.load_cpu(cpu);
+        self.record.rerecord(self.control_deck.frame_number());
+        self.background_record.clear();
+        let count = self.checkpoints.states.len();
+        self.add_message(
+            MessageType::Info,
+            format!("Checkpoint {number}/{count} Loaded"),
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! Copying the current frame to the system clipboard.
+
+use crate::{
+    nes::{emulation::State, event::RendererEvent, renderer::gui::MessageType},
+    platform,
+};
+use anyhow::anyhow;
+use image::{
+    imageops::{self, FilterType},
+    RgbaImage,
+};
+use tetanes_core::{common::Regional, ppu::Ppu};
+
+/// Rows cropped from the top and bottom of the frame when hiding overscan.
+const OVERSCAN_ROWS: u32 = 8;
+
+/// Which version of the current frame to copy.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[must_use]
+pub enum FrameCopy {
+    /// The frame as output by the PPU.
+    Native,
+    /// The frame as shown in the window, with overscan cropped and the aspect ratio and scale
+    /// applied.
+    Displayed { hide_overscan: bool, scale: f32 },
+}
+
+impl State {
+    /// Copy the current frame to the clipboard as an image.
+    pub fn copy_frame(&mut self, copy: FrameCopy) {
+        let res = self.frame_image(copy).and_then(|image| {
+            platform::copy_image(
+                image.width() as usize,
+                image.height() as usize,
+                image.as_raw(),
+            )
+        });
+        match res {
+            Ok(()) => self.add_message(MessageType::Info, "Frame copied to clipboard"),
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    /// Copy the loaded ROM name and current frame number to the clipboard as text.
+    pub fn copy_frame_info(&mut self) {
+        let Some(rom) = self.control_deck.loaded_rom() else {
+            return;
+        };
+        let text = format!("{} - frame {}", rom.name, self.control_deck.frame_number());
+        self.tx.event(RendererEvent::CopyText(text.clone()));
+        self.add_message(MessageType::Info, format!("Copied: {text}"));
+    }
+
+    fn frame_image(&mut self, copy: FrameCopy) -> anyhow::Result<RgbaImage> {
+        let image = RgbaImage::from_raw(
+            Ppu::WIDTH,
+            Ppu::HEIGHT,
+            self.control_deck.frame_buffer().to_vec(),
+        )
+        .ok_or_else(|| anyhow!("failed to create image buffer"))?;
+        let FrameCopy::Displayed {
+            hide_overscan,
+            scale,
+        } = copy
+        else {
+            return Ok(image);
+        };
+
+        let region = self.control_deck.region();
+        let trim = if hide_overscan && region.is_ntsc() {
+            OVERSCAN_ROWS
+        } else {
+            0
+        };
+        let height = Ppu::HEIGHT - 2 * trim;
+        let image = imageops::crop_imm(&image, 0, trim, Ppu::WIDTH, height).to_image();
+        Ok(imageops::resize(
+            &image,
+            (Ppu::WIDTH as f32 * region.aspect_ratio() * scale).round() as u32,
+            (height as f32 * scale).round() as u32,
+            FilterType::Nearest,
+        ))
+    }
+}
@@ -0,0 +1,245 @@
+//! A/B comparison of two replays for finding where recordings desync.
+//!
+//! Each replay is played back on its own copy of the control deck with the loaded ROM, in lockstep
+//! relative to the frame each replay starts on, until their PPU output differs or both run out of
+//! inputs. The first frame where their inputs differ is reported along the way.
+
+use crate::nes::{
+    emulation::{replay::Replay, State},
+    event::{EmulationEvent, RendererEvent},
+    renderer::gui::MessageType,
+};
+use egui::ViewportId;
+use std::path::Path;
+use tetanes_core::{
+    control_deck::ControlDeck,
+    cpu::{Cpu, Signals},
+    time::{Duration, Instant},
+};
+use winit::event::ElementState;
+
+/// How long to clock frames before yielding to handle events and report progress.
+const BATCH_DURATION: Duration = Duration::from_millis(16);
+/// Frames to keep comparing after the last input, to catch output changes caused by it.
+const TRAILING_FRAMES: u32 = 120;
+
+/// One of the replays being compared.
+#[derive(Debug)]
+struct Side {
+    deck: ControlDeck,
+    replay: Replay,
+    start_frame: u32,
+    /// Pending interrupts and DMAs, kept separate from the main deck since they're per-thread.
+    signals: Signals,
+}
+
+impl Side {
+    fn new(deck: &ControlDeck, path: &Path) -> anyhow::Result<Self> {
+        let mut replay = Replay::new();
+        let start = replay.load_path(path)?;
+        let mut deck = deck.clone();
+        deck.load_cpu(start);
+        deck.clear_audio_samples();
+        Ok(Self {
+            start_frame: deck.frame_number(),
+            deck,
+            replay,
+            signals: Signals::default(),
+        })
+    }
+
+    /// Last frame with input, relative to the start of the replay.
+    fn last_input_frame(&self) -> u32 {
+        // Events are stored in reverse
+        self.replay
+            .events
+            .first()
+            .map_or(0, |event| event.frame.saturating_sub(self.start_frame))
+    }
+
+    /// Apply any inputs for the current frame and clock it, returning the inputs applied.
+    fn clock_frame(&mut self) -> anyhow::Result<Vec<EmulationEvent>> {
+        let mut inputs = Vec::new();
+        while let Some(event) = self.replay.next(self.deck.frame_number()) {
+            match &event {
+                EmulationEvent::Joypad((player, button, state)) => {
+                    let pressed = *state == ElementState::Pressed;
+                    self.deck.joypad_mut(*player).set_button(*button, pressed);
+                }
+                EmulationEvent::ZapperAim((x, y)) => self.deck.aim_zapper(*x, *y),
+                EmulationEvent::ZapperTrigger => self.deck.trigger_zapper(),
+                EmulationEvent::InsertCoin(slot) => self.deck.insert_coin((*slot).into()),
+                EmulationEvent::MouseMotion((dx, dy)) => self.deck.mouse_motion(*dx, *dy),
+                EmulationEvent::MouseButton((button, pressed)) => {
+                    self.deck.set_mouse_button(*button, *pressed);
+                }
+                _ => (),
+            }
+            inputs.push(event);
+        }
+
+        Cpu::set_signals(self.signals);
+        let res = self.deck.clock_frame();
+        self.signals = Cpu::signals();
+        self.deck.clear_audio_samples();
+        res?;
+
+        Ok(inputs)
+    }
+}
+
+/// An in-progress comparison of two replays.
+#[derive(Debug)]
+#[must_use]
+pub struct Compare {
+    sides: Box<[Side; 2]>,
+    /// Current frame, relative to the start of each replay.
+    frame: u32,
+    last_frame: u32,
+    input_divergence: Option<u32>,
+}
+
+/// Outcome of comparing two replays.
+#[derive(Clone, PartialEq, Eq)]
+#[must_use]
+pub struct CompareResult {
+    /// Number of frames compared, relative to the start of each replay.
+    pub frames: u32,
+    /// First frame where the replays' inputs differ.
+    pub input_divergence: Option<u32>,
+    /// First frame where the PPU output differs, where the comparison stopped.
+    pub output_divergence: Option<u32>,
+    /// The last frame compared from each replay, as RGBA pixels.
+    pub screens: [Vec<u8>; 2],
+}
+
+impl std::fmt::Debug for CompareResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompareResult")
+            .field("frames", &self.frames)
+            .field("input_divergence", &self.input_divergence)
+            .field("output_divergence", &self.output_divergence)
+            .finish_non_exhaustive()
+    }
+}
+
+impl State {
+    /// Start comparing two replays of the loaded ROM.
+    pub fn compare_replays(&mut self, a: &Path, b: &Path) {
+        if self.control_deck.loaded_rom().is_none() {
+            return self.add_message(MessageType::Warn, "Load a ROM before comparing replays");
+        }
+        let sides = Side::new(&self.control_deck, a)
+            .and_then(|a| Ok([a, Side::new(&self.control_deck, b)?]));
+        match sides {
+            Ok(sides) => {
+                let last_frame = sides[0]
+                    .last_input_frame()
+                    .max(sides[1].last_input_frame())
+                    .saturating_add(TRAILING_FRAMES);
+                self.compare = Some(Compare {
+                    sides: Box::new(sides),
+                    frame: 0,
+                    last_frame,
+                    input_divergence: None,
+                });
+                self.add_message(MessageType::Info, "Comparing replays...");
+            }
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    /// Cancel an in-progress comparison, reporting the results so far.
+    pub fn cancel_compare(&mut self) {
+        if let Some(compare) = self.compare.take() {
+            self.finish_compare(compare, None);
+        }
+    }
+
+    /// Clock both replays for up to [`BATCH_DURATION`], returning whether a comparison is in
+    /// progress.
+    pub fn clock_compare(&mut self) -> bool {
+        let Some(compare) = &mut self.compare else {
+            return false;
+        };
+
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let signals = Cpu::signals();
+        let start = Instant::now();
+        let mut result = None;
+        while start.elapsed() < BATCH_DURATION {
+            let [a, b] = &mut *compare.sides;
+            let inputs = a
+                .clock_frame()
+                .and_then(|a_inputs| Ok((a_inputs, b.clock_frame()?)));
+            match inputs {
+                Ok((a_inputs, b_inputs)) => {
+                    if a_inputs != b_inputs && compare.input_divergence.is_none() {
+                        compare.input_divergence = Some(compare.frame);
+                    }
+                }
+                Err(err) => {
+                    result = Some(Err(err));
+                    break;
+                }
+            }
+            if a.deck.frame_buffer_raw() != b.deck.frame_buffer_raw() {
+                result = Some(Ok(Some(compare.frame)));
+                break;
+            }
+            compare.frame += 1;
+            if compare.frame > compare.last_frame {
+                result = Some(Ok(None));
+                break;
+            }
+        }
+        Cpu::set_signals(signals);
+
+        let progress = RendererEvent::CompareProgress {
+            frame: compare.frame,
+            total: compare.last_frame,
+        };
+        match result {
+            Some(result) => {
+                if let Some(compare) = self.compare.take() {
+                    match result {
+                        Ok(output_divergence) => self.finish_compare(compare, output_divergence),
+                        Err(err) => {
+                            self.finish_compare(compare, None);
+                            self.on_error(err);
+                        }
+                    }
+                }
+            }
+            None => self.tx.event(progress),
+        }
+        self.tx.event(RendererEvent::RequestRedraw {
+            viewport_id: ViewportId::ROOT,
+            when: Instant::now(),
+        });
+
+        true
+    }
+
+    fn finish_compare(&mut self, mut compare: Compare, output_divergence: Option<u32>) {
+        let message = match (compare.input_divergence, output_divergence) {
+            (_, Some(frame)) => format!("Replays diverged at frame {frame}"),
+            (Some(frame), None) => format!("Replay inputs differ from frame {frame}"),
+            (None, None) => format!("Replays match for {} frames", compare.frame),
+        };
+        self.add_message(MessageType::Info, message);
+
+        let [a, b] = &mut *compare.sides;
+        self.tx.event(RendererEvent::CompareFinished(CompareResult {
+            frames: compare.frame,
+            input_divergence: compare.input_divergence,
+            output_divergence,
+            screens: [
+                a.deck.frame_buffer().to_vec(),
+                b.deck.frame_buffer().to_vec(),
+            ],
+        }));
+    }
+}
@@ -0,0 +1,79 @@
+//! Exporting DPCM samples logged as the DMC fetched them, e.g. to reuse or analyze the drum kits
+//! and voice clips a game plays.
+
+use crate::nes::{config::Config, emulation::State, renderer::gui::MessageType};
+use anyhow::Context;
+use chrono::Local;
+use std::{fmt::Write, path::PathBuf};
+use tetanes_core::{apu::dmc::DmcSample, common::Regional};
+
+impl State {
+    /// Write every logged DMC sample to a WAV in a new folder in the audio directory, along with a
+    /// `samples.txt` listing their addresses and rates.
+    pub fn export_dmc_samples(&mut self) {
+        match self.save_dmc_samples() {
+            Ok(Some((dir, count))) => self.add_message(
+                MessageType::Info,
+                format!("Exported {count} DMC Sample(s) to {}", dir.display()),
+            ),
+            Ok(None) => self.add_message(MessageType::Warn, "No DMC Samples Logged"),
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    fn save_dmc_samples(&self) -> anyhow::Result<Option<(PathBuf, usize)>> {
+        let Some(rom) = self.control_deck.loaded_rom() else {
+            return Ok(None);
+        };
+        let samples = self.control_deck.dmc_samples();
+        if samples.is_empty() {
+            return Ok(None);
+        }
+
+        let dir = Config::default_audio_dir().join(format!(
+            "{}_dmc_samples_{}",
+            rom.name,
+            Local::now().format("%Y-%m-%d_at_%H_%M_%S")
+        ));
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create dmc samples dir: {dir:?}"))?;
+
+        let region = self.control_deck.region();
+        let mut listing = String::from("file\taddress\tlength\trate\thz\n");
+        for (index, sample) in samples.iter().enumerate() {
+            let filename = format!("{index:02}_${:04X}_rate{:X}.wav", sample.addr, sample.rate);
+            let sample_rate = sample.sample_rate(region);
+            save_wav(&dir.join(&filename), sample, sample_rate.round() as u32)
+                .with_context(|| format!("failed to save dmc sample: {filename:?}"))?;
+            let _ = writeln!(
+                listing,
+                "{filename}\t${:04X}\t{}\t${:X}\t{sample_rate:.1}",
+                sample.addr,
+                sample.data.len(),
+                sample.rate,
+            );
+        }
+        let listing_path = dir.join("samples.txt");
+        std::fs::write(&listing_path, listing)
+            .with_context(|| format!("failed to save dmc sample listing: {listing_path:?}"))?;
+
+        Ok(Some((dir, samples.len())))
+    }
+}
+
+/// Write a sample as a 16-bit mono WAV at its playback rate, centered on the level decoding
+/// starts from.
+fn save_wav(path: &std::path::Path, sample: &DmcSample, sample_rate: u32) -> anyhow::Result<()> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec)?;
+    for level in sample.decode() {
+        writer.write_sample((i16::from(level) - i16::from(DmcSample::START_LEVEL)) * 512)?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
@@ -0,0 +1,160 @@
+//! Saves that failed to be written, e.g. because the disk is full or the save directory isn't
+//! writable, kept in memory until they're written or explicitly discarded so progress is never
+//! silently lost.
+
+use crate::nes::{emulation::State, event::RendererEvent, renderer::gui::MessageType};
+use std::path::{Path, PathBuf};
+use tetanes_core::{common::Sram, control_deck::ControlDeck, cpu::Cpu};
+use tracing::error;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum SaveKind {
+    /// Battery-backed save RAM.
+    Sram,
+    State(u8),
+    /// State saved apart from the save slots when emulation is interrupted.
+    Safety,
+}
+
+impl std::fmt::Display for SaveKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sram => write!(f, "Save RAM"),
+            Self::State(slot) => write!(f, "Save State {slot}"),
+            Self::Safety => write!(f, "Safety Save State"),
+        }
+    }
+}
+
+/// A save that failed to be written, shown so it can be retried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct FailedSave {
+    pub kind: SaveKind,
+    pub rom: String,
+    pub path: PathBuf,
+    pub error: String,
+}
+
+/// A failed save along with the state it was saving.
+#[derive(Debug)]
+#[must_use]
+pub struct PendingSave {
+    save: FailedSave,
+    cpu: Box<Cpu>,
+}
+
+impl PendingSave {
+    fn write(&self, control_deck: &ControlDeck, path: &Path) -> anyhow::Result<()> {
+        match self.save.kind {
+            SaveKind::Sram => self.cpu.bus.save(path)?,
+            SaveKind::State(_) | SaveKind::Safety => {
+                control_deck.save_cpu_state(path, &self.cpu)?
+            }
+        }
+        Ok(())
+    }
+
+    /// The path to write to, keeping the file name if written to another directory.
+    fn path(&self, dir: Option<&Path>) -> PathBuf {
+        match (dir, self.save.path.file_name()) {
+            (Some(dir), Some(file_name)) => dir.join(file_name),
+            _ => self.save.path.clone(),
+        }
+    }
+}
+
+impl State {
+    /// Keep a save that failed to be written in memory and ask what to do with it.
+    pub fn on_save_failed(
+        &mut self,
+        kind: SaveKind,
+        rom: &str,
+        path: PathBuf,
+        cpu: Cpu,
+        err: impl Into<anyhow::Error>,
+    ) {
+        let err = err.into();
+        error!("failed to save {kind} to {path:?}: {err:?}");
+        // A newer save to the same path supersedes the older one
+        self.failed_saves
+            .retain(|pending| pending.save.path != path);
+        self.failed_saves.push(PendingSave {
+            save: FailedSave {
+                kind,
+                rom: rom.to_string(),
+                path,
+                error: err.to_string(),
+            },
+            cpu: Box::new(cpu),
+        });
+        self.send_failed_saves();
+    }
+
+    /// Try writing failed saves again, either to their original path or to another directory.
+    pub fn retry_failed_saves(&mut self, dir: Option<&Path>) {
+        for mut pending in std::mem::take(&mut self.failed_saves) {
+            let path = pending.path(dir);
+            match pending.write(&self.control_deck, &path) {
+                Ok(()) => self.add_message(
+                    MessageType::Info,
+                    format!("{} Saved to {}", pending.save.kind, path.display()),
+                ),
+                Err(err) => {
+                    pending.save.error = err.to_string();
+                    self.failed_saves.push(pending);
+                }
+            }
+        }
+        self.send_failed_saves();
+    }
+
+    pub fn discard_failed_saves(&mut self) {
+        if self.failed_saves.is_empty() {
+            return;
+        }
+        for pending in self.failed_saves.drain(..) {
+            error!(
+                "discarded {} for {:?} that failed to save",
+                pending.save.kind, pending.save.rom
+            );
+        }
+        self.add_message(MessageType::Warn, "Discarded Unsaved Progress");
+        self.send_failed_saves();
+    }
+
+    /// Last resort when exiting with saves that still couldn't be written, trying the temporary
+    /// directory instead.
+    pub fn flush_failed_saves(&mut self) {
+        for pending in self.failed_saves.drain(..) {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let path = pending.path(Some(&std::env::temp_dir()));
+                match pending.write(&self.control_deck, &path) {
+                    Ok(()) => {
+                        error!(
+                            "saved {} for {:?} to {path:?} instead",
+                            pending.save.kind, pending.save.rom
+                        );
+                        continue;
+                    }
+                    Err(err) => error!("failed to save {} to {path:?}: {err:?}", pending.save.kind),
+                }
+            }
+            error!(
+                "lost {} for {:?} that failed to save",
+                pending.save.kind, pending.save.rom
+            );
+        }
+    }
+
+    fn send_failed_saves(&mut self) {
+        let saves = self
+            .failed_saves
+            .iter()
+            .map(|pending| pending.save.clone())
+            .collect();
+        self.tx.event(RendererEvent::FailedSaves(saves));
+    }
+}
@@ -0,0 +1,74 @@
+//! The console front panel: a power switch that can leave the console off with the ROM loaded, and
+//! a reset button that holds the console in reset until released.
+
+use crate::nes::{emulation::State, event::RendererEvent, renderer::gui::MessageType, RunState};
+use tetanes_core::time::Instant;
+
+/// Front panel state, shown in the UI.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct FrontPanel {
+    pub powered: bool,
+    pub reset_held: bool,
+}
+
+impl Default for FrontPanel {
+    fn default() -> Self {
+        Self {
+            powered: true,
+            reset_held: false,
+        }
+    }
+}
+
+impl State {
+    /// Flip the power switch. Turning the power back on power cycles the console.
+    pub fn toggle_power(&mut self) {
+        if self.control_deck.loaded_rom().is_none() {
+            return;
+        }
+        self.reset_pressed = None;
+        let powered = !self.control_deck.is_powered();
+        self.control_deck.set_power(powered);
+        if powered {
+            self.frame_time_diag.reset();
+            self.set_run_state(RunState::Running);
+            self.add_message(MessageType::Info, "Power On");
+        } else {
+            self.add_message(MessageType::Info, "Power Off");
+        }
+        self.send_front_panel();
+    }
+
+    /// Press or release the reset button. The console is held in reset while pressed and resets
+    /// once released.
+    pub fn reset_button(&mut self, pressed: bool) {
+        if pressed {
+            if self.reset_pressed.is_some() || !self.control_deck.is_running() {
+                return;
+            }
+            self.control_deck.press_reset();
+            self.reset_pressed = Some(Instant::now());
+            self.add_message(MessageType::Info, "Holding Reset...");
+        } else {
+            let Some(pressed) = self.reset_pressed.take() else {
+                return;
+            };
+            self.control_deck.release_reset();
+            self.frame_time_diag.reset();
+            self.set_run_state(RunState::Running);
+            self.add_message(
+                MessageType::Info,
+                format!("Reset (held {:.1}s)", pressed.elapsed().as_secs_f32()),
+            );
+        }
+        self.send_front_panel();
+    }
+
+    fn send_front_panel(&mut self) {
+        self.tx.event(RendererEvent::FrontPanel(FrontPanel {
+            powered: self.control_deck.is_powered(),
+            reset_held: self.control_deck.is_reset_held(),
+        }));
+    }
+}
@@ -0,0 +1,137 @@
+//! Exporting a gallery of save state screenshots for the loaded ROM.
+//!
+//! Save states don't include the frame buffer, so each one is loaded and run for a single frame to
+//! render it, then the emulation state from before the export is restored.
+
+use crate::nes::{
+    config::Config,
+    emulation::{save_states::SAVE_SLOTS, State},
+    renderer::gui::MessageType,
+};
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Local};
+use std::{fmt::Write, path::PathBuf};
+use tetanes_core::{cpu::Cpu, fs, ppu::Ppu};
+
+/// A save state rendered to a PNG.
+#[derive(Debug, Clone)]
+#[must_use]
+struct Thumbnail {
+    slot: u8,
+    saved_at: Option<DateTime<Local>>,
+    filename: String,
+}
+
+impl State {
+    /// Render every save state for the loaded ROM to labeled PNGs in a new folder in the
+    /// pictures directory, along with an `index.html` showing them in slot order.
+    pub fn export_save_state_gallery(&mut self) {
+        match self.save_state_gallery() {
+            Ok(Some((dir, count))) => self.add_message(
+                MessageType::Info,
+                format!("Exported {count} Save State(s) to {}", dir.display()),
+            ),
+            Ok(None) => self.add_message(MessageType::Warn, "No Save States Found"),
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    fn save_state_gallery(&mut self) -> anyhow::Result<Option<(PathBuf, usize)>> {
+        let Some(rom) = self.control_deck.loaded_rom() else {
+            return Ok(None);
+        };
+        let name = rom.name.clone();
+        let saves = (1..=SAVE_SLOTS)
+            .map(|slot| (slot, Config::save_path(&name, slot)))
+            .filter(|(_, path)| fs::exists(path))
+            .collect::<Vec<_>>();
+        if saves.is_empty() {
+            return Ok(None);
+        }
+
+        let dir = Config::default_picture_dir().join(format!(
+            "{name}_save_states_{}",
+            Local::now().format("%Y-%m-%d_at_%H_%M_%S")
+        ));
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create gallery dir: {dir:?}"))?;
+
+        let current = self.control_deck.cpu().clone();
+        let mut thumbnails = Vec::with_capacity(saves.len());
+        let res = saves.into_iter().try_for_each(|(slot, path)| {
+            let saved_at = std::fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .map(DateTime::<Local>::from);
+            let mut cpu = fs::load::<Cpu>(&path)
+                .with_context(|| format!("failed to load save state: {path:?}"))?;
+            cpu.bus.input.clear();
+            self.control_deck.load_cpu(cpu);
+            // Run a single frame regardless of emulation speed to render the state
+            let frame = self.control_deck.frame_number();
+            while self.control_deck.frame_number() == frame {
+                self.control_deck.clock_instr()?;
+            }
+            self.control_deck.clear_audio_samples();
+
+            let mut filename = format!("slot-{slot}");
+            if let Some(saved_at) = saved_at {
+                let _ = write!(filename, "_{}", saved_at.format("%Y-%m-%d_at_%H_%M_%S"));
+            }
+            filename.push_str(".png");
+            // The cached frame buffer is skipped as states may share a frame number
+            let mut frame = vec![0; 4 * Ppu::SIZE];
+            self.control_deck.frame_buffer_into(&mut frame);
+            image::RgbaImage::from_raw(Ppu::WIDTH, Ppu::HEIGHT, frame)
+                .ok_or_else(|| anyhow!("failed to create image buffer"))?
+                .save(dir.join(&filename))
+                .with_context(|| format!("failed to save thumbnail: {filename:?}"))?;
+
+            thumbnails.push(Thumbnail {
+                slot,
+                saved_at,
+                filename,
+            });
+            anyhow::Ok(())
+        });
+        self.control_deck.load_cpu(current);
+        res?;
+
+        let index = dir.join("index.html");
+        std::fs::write(&index, gallery_html(&name, &thumbnails))
+            .with_context(|| format!("failed to save gallery: {index:?}"))?;
+
+        Ok(Some((dir, thumbnails.len())))
+    }
+}
+
+fn gallery_html(name: &str, thumbnails: &[Thumbnail]) -> String {
+    let name = escape_html(name);
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{name} Save States</title>\n\
+        <style>body {{ font-family: sans-serif; }} figure {{ display: inline-block; }} \
+        img {{ width: 512px; image-rendering: pixelated; }}</style>\n</head>\n<body>\n\
+        <h1>{name}</h1>\n"
+    );
+    for thumbnail in thumbnails {
+        let mut caption = format!("Slot {}", thumbnail.slot);
+        if let Some(saved_at) = thumbnail.saved_at {
+            let _ = write!(caption, " &mdash; {}", saved_at.format("%Y-%m-%d %H:%M:%S"));
+        }
+        let _ = writeln!(
+            html,
+            "<figure><img src=\"{}\" alt=\"Slot {}\"><figcaption>{caption}</figcaption></figure>",
+            escape_html(&thumbnail.filename),
+            thumbnail.slot,
+        );
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,121 @@
+//! RAM snapshots for memory search, and watched or frozen RAM addresses.
+
+use crate::nes::{config::Config, emulation::State, event::DebugEvent, renderer::gui::MessageType};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tetanes_core::{cpu::Cpu, debug::MemorySnapshot, fs};
+
+/// Where to take a [`MemorySnapshot`] from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub enum MemorySource {
+    Current,
+    SaveSlot(u8),
+}
+
+impl std::fmt::Display for MemorySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Current => write!(f, "Current RAM"),
+            Self::SaveSlot(slot) => write!(f, "Save Slot {slot}"),
+        }
+    }
+}
+
+/// RAM addresses being watched or frozen to a value.
+#[derive(Default, Debug)]
+#[must_use]
+pub struct MemoryWatch {
+    pub addrs: Vec<u16>,
+    pub frozen: BTreeMap<u16, u8>,
+}
+
+impl MemoryWatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.addrs.clear();
+        self.frozen.clear();
+    }
+}
+
+impl State {
+    fn memory_snapshot(&mut self, source: MemorySource) -> Option<MemorySnapshot> {
+        let name = self.control_deck.loaded_rom()?.name.clone();
+        match source {
+            MemorySource::Current => Some(MemorySnapshot::new(self.control_deck.cpu())),
+            MemorySource::SaveSlot(slot) => {
+                let save_path = Config::save_path(&name, slot);
+                if !fs::exists(&save_path) {
+                    self.add_message(MessageType::Warn, format!("State {slot} Not Found"));
+                    return None;
+                }
+                match fs::load::<Cpu>(&save_path) {
+                    Ok(cpu) => Some(MemorySnapshot::new(&cpu)),
+                    Err(err) => {
+                        self.on_error(err);
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    /// Send snapshots of RAM from `before` and `after` to be compared.
+    pub fn send_memory_snapshots(&mut self, before: MemorySource, after: MemorySource) {
+        let Some(before) = self.memory_snapshot(before) else {
+            return;
+        };
+        let Some(after) = self.memory_snapshot(after) else {
+            return;
+        };
+        self.tx
+            .event(DebugEvent::MemorySnapshots(Box::new((before, after))));
+    }
+
+    /// Freeze the RAM at `addr` to a value, or unfreeze it if `None`.
+    pub fn freeze_memory(&mut self, addr: u16, val: Option<u8>) {
+        match val {
+            Some(val) => self.memory_watch.frozen.insert(addr, val),
+            None => self.memory_watch.frozen.remove(&addr),
+        };
+    }
+
+    /// Write frozen values back to RAM, so the game always reads them.
+    pub fn apply_frozen_memory(&mut self) {
+        let bus = &mut self.control_deck.cpu_mut().bus;
+        for (&addr, &val) in &self.memory_watch.frozen {
+            let ram = match MemorySnapshot::index(addr) {
+                Some((true, index)) => bus.prg_ram.get_mut(index),
+                Some((false, index)) => bus.wram.get_mut(index),
+                None => None,
+            };
+            if let Some(ram) = ram {
+                *ram = val;
+            }
+        }
+    }
+
+    /// Send the current values of watched addresses.
+    pub fn send_memory_watch(&mut self) {
+        if self.memory_watch.addrs.is_empty() {
+            return;
+        }
+        let bus = &self.control_deck.cpu().bus;
+        let values = self
+            .memory_watch
+            .addrs
+            .iter()
+            .filter_map(|&addr| {
+                let val = match MemorySnapshot::index(addr)? {
+                    (true, index) => bus.prg_ram.get(index),
+                    (false, index) => bus.wram.get(index),
+                };
+                val.map(|&val| (addr, val))
+            })
+            .collect();
+        self.tx.event(DebugEvent::MemoryWatch(values));
+    }
+}
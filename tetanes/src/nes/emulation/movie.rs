@@ -0,0 +1,429 @@
+//! Import and export of TAS movie input logs, so replays can be exchanged with other emulators.
+//!
+//! Supports FCEUX `.fm2` text movies and BizHawk `.bk2` archives. Both log the buttons held by
+//! each controller every frame, starting from power-on, so only recordings started from
+//! power-on will play back in sync in other emulators.
+//!
+//! ```text
+//! |0|R.....BA|........||
+//! ```
+
+use crate::nes::emulation::replay::{ReplayEvent, ReplayFormat, ReplayFrame, ReplayInfo};
+use anyhow::{anyhow, bail, Context};
+use std::{
+    fmt::Write as _,
+    io::{Cursor, Read, Write},
+};
+use tetanes_core::input::{JoypadBtn, Player};
+use tracing::warn;
+use winit::event::ElementState;
+use zip::{read::ZipArchive, write::SimpleFileOptions, ZipWriter};
+
+/// Buttons in controller shift register order, indexed by the bits of a [`Movie`] frame.
+const BUTTONS: [JoypadBtn; 8] = [
+    JoypadBtn::A,
+    JoypadBtn::B,
+    JoypadBtn::Select,
+    JoypadBtn::Start,
+    JoypadBtn::Up,
+    JoypadBtn::Down,
+    JoypadBtn::Left,
+    JoypadBtn::Right,
+];
+
+/// FCEUX button mnemonics, in log order.
+const FM2_BUTTONS: [(char, JoypadBtn); 8] = [
+    ('R', JoypadBtn::Right),
+    ('L', JoypadBtn::Left),
+    ('D', JoypadBtn::Down),
+    ('U', JoypadBtn::Up),
+    ('T', JoypadBtn::Start),
+    ('S', JoypadBtn::Select),
+    ('B', JoypadBtn::B),
+    ('A', JoypadBtn::A),
+];
+
+/// BizHawk button mnemonics and log key names, in log order.
+const BK2_BUTTONS: [(char, &str, JoypadBtn); 8] = [
+    ('U', "Up", JoypadBtn::Up),
+    ('D', "Down", JoypadBtn::Down),
+    ('L', "Left", JoypadBtn::Left),
+    ('R', "Right", JoypadBtn::Right),
+    ('S', "Start", JoypadBtn::Start),
+    ('s', "Select", JoypadBtn::Select),
+    ('B', "B", JoypadBtn::B),
+    ('A', "A", JoypadBtn::A),
+];
+
+const BK2_HEADER: &str = "Header.txt";
+const BK2_INPUT_LOG: &str = "Input Log.txt";
+
+/// A TAS movie input log.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Movie {
+    pub author: String,
+    pub rerecords: u32,
+    pub pal: bool,
+    pub rom_name: String,
+    /// Buttons held by each player every frame, as bits indexed by [`BUTTONS`].
+    pub frames: Vec<[u8; 4]>,
+}
+
+impl Movie {
+    /// Parses a movie file in the given `format`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the movie is invalid or uses unsupported controllers, e.g. a zapper.
+    pub fn parse(format: ReplayFormat, bytes: &[u8]) -> anyhow::Result<Self> {
+        match format {
+            ReplayFormat::Fm2 => Self::parse_fm2(&String::from_utf8_lossy(bytes)),
+            ReplayFormat::Bk2 => Self::parse_bk2(bytes),
+            ReplayFormat::Tetanes => bail!("not a movie format: {}", format.as_str()),
+        }
+    }
+
+    /// Encodes the movie as a file in the given `format`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the movie can't be written in `format`.
+    pub fn encode(&self, format: ReplayFormat) -> anyhow::Result<Vec<u8>> {
+        match format {
+            ReplayFormat::Fm2 => Ok(self.to_fm2().into_bytes()),
+            ReplayFormat::Bk2 => self.to_bk2(),
+            ReplayFormat::Tetanes => bail!("not a movie format: {}", format.as_str()),
+        }
+    }
+
+    /// Converts recorded replay events starting at `start_frame` into a movie. Turbo buttons are
+    /// logged as held and events other than joypad buttons, e.g. zapper input, are skipped.
+    pub fn from_replay_frames<'a>(
+        frames: impl IntoIterator<Item = &'a ReplayFrame>,
+        start_frame: u32,
+        info: &ReplayInfo,
+    ) -> Self {
+        let mut held = [0; 4];
+        let mut movie_frames = Vec::new();
+        for ReplayFrame { frame, event } in frames {
+            let ReplayEvent::Joypad((player, button, state)) = event else {
+                continue;
+            };
+            let frame = frame.saturating_sub(start_frame) as usize;
+            if frame > movie_frames.len() {
+                movie_frames.resize(frame, held);
+            }
+            let held = &mut held[*player as usize];
+            match state {
+                ElementState::Pressed => *held |= button_bit(*button),
+                ElementState::Released => *held &= !button_bit(*button),
+            }
+        }
+        movie_frames.push(held);
+
+        Self {
+            author: info.author.clone(),
+            rerecords: info.rerecords,
+            frames: movie_frames,
+            ..Default::default()
+        }
+    }
+
+    /// Compiles the movie into replay events starting at `start_frame`.
+    pub fn to_replay_frames(&self, start_frame: u32) -> Vec<ReplayFrame> {
+        let mut events = Vec::new();
+        let mut held = [0; 4];
+        // Release anything still held after the last frame
+        let frames = self.frames.iter().chain(std::iter::once(&[0; 4]));
+        for (frame, buttons) in (start_frame..).zip(frames) {
+            for (index, (held, &buttons)) in held.iter_mut().zip(buttons).enumerate() {
+                let changed = *held ^ buttons;
+                for (bit, button) in BUTTONS.iter().enumerate() {
+                    if changed & (1 << bit) == 0 {
+                        continue;
+                    }
+                    let state = if buttons & (1 << bit) == 0 {
+                        ElementState::Released
+                    } else {
+                        ElementState::Pressed
+                    };
+                    let player = Player::try_from(index).unwrap_or_default();
+                    events.push(ReplayFrame {
+                        frame,
+                        event: ReplayEvent::Joypad((player, *button, state)),
+                    });
+                }
+                *held = buttons;
+            }
+        }
+        events
+    }
+
+    /// Whether players three or four press any buttons, requiring a Four Score.
+    fn four_score(&self) -> bool {
+        self.frames
+            .iter()
+            .any(|players| players[2] != 0 || players[3] != 0)
+    }
+
+    fn parse_fm2(text: &str) -> anyhow::Result<Self> {
+        let mut movie = Self::default();
+        let mut four_score = false;
+        let mut ignored_commands = false;
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim_end();
+            if let Some(input) = line.strip_prefix('|') {
+                let mut fields = input.split('|');
+                let commands = fields.next().unwrap_or_default();
+                ignored_commands |= commands.trim().parse::<u8>().is_ok_and(|c| c != 0);
+                let players = if four_score { 4 } else { 2 };
+                let mut buttons = [0; 4];
+                for (held, field) in buttons.iter_mut().zip(fields).take(players) {
+                    *held = parse_fm2_buttons(field)
+                        .with_context(|| format!("invalid fm2 input line {}", number + 1))?;
+                }
+                movie.frames.push(buttons);
+                continue;
+            }
+
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let value = value.trim();
+            match key {
+                "binary" if value != "0" => bail!("binary fm2 movies are not supported"),
+                "fourscore" => four_score = value == "1",
+                "port0" | "port1" if !four_score && value == "2" => {
+                    bail!("fm2 zapper input is not supported")
+                }
+                "palFlag" => movie.pal = value == "1",
+                "romFilename" => movie.rom_name = value.to_string(),
+                "rerecordCount" => movie.rerecords = value.parse().unwrap_or_default(),
+                "comment" => {
+                    if let Some(author) = value.strip_prefix("author ") {
+                        movie.author = author.trim().to_string();
+                    }
+                }
+                _ => (),
+            }
+        }
+        if ignored_commands {
+            warn!("ignoring fm2 reset and power commands");
+        }
+        Ok(movie)
+    }
+
+    fn to_fm2(&self) -> String {
+        let four_score = self.four_score();
+        let mut fm2 = String::new();
+        let _ = writeln!(fm2, "version 3");
+        let _ = writeln!(fm2, "emuVersion 22020");
+        let _ = writeln!(fm2, "rerecordCount {}", self.rerecords);
+        let _ = writeln!(fm2, "palFlag {}", u8::from(self.pal));
+        let _ = writeln!(fm2, "romFilename {}", self.rom_name);
+        let _ = writeln!(
+            fm2,
+            "guid {}",
+            uuid::Uuid::new_v4().to_string().to_uppercase()
+        );
+        let _ = writeln!(fm2, "fourscore {}", u8::from(four_score));
+        let _ = writeln!(fm2, "microphone 0");
+        let _ = writeln!(fm2, "port0 {}", u8::from(!four_score));
+        let _ = writeln!(fm2, "port1 {}", u8::from(!four_score));
+        let _ = writeln!(fm2, "port2 0");
+        let _ = writeln!(fm2, "FDS 0");
+        let _ = writeln!(fm2, "NewPPU 0");
+        if !self.author.is_empty() {
+            let _ = writeln!(fm2, "comment author {}", self.author);
+        }
+        for buttons in &self.frames {
+            fm2.push_str("|0|");
+            let players = if four_score { 4 } else { 2 };
+            for &held in &buttons[..players] {
+                for (c, button) in FM2_BUTTONS {
+                    fm2.push(if held & button_bit(button) == 0 {
+                        '.'
+                    } else {
+                        c
+                    });
+                }
+                fm2.push('|');
+            }
+            if !four_score {
+                fm2.push('|');
+            }
+            fm2.push('\n');
+        }
+        fm2
+    }
+
+    fn parse_bk2(bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut archive = ZipArchive::new(Cursor::new(bytes)).context("invalid bk2 archive")?;
+        let mut read_file = |name: &str| -> anyhow::Result<String> {
+            let mut text = String::new();
+            archive
+                .by_name(name)
+                .with_context(|| format!("missing bk2 {name:?}"))?
+                .read_to_string(&mut text)?;
+            Ok(text)
+        };
+
+        let mut movie = Self::default();
+        let header = read_file(BK2_HEADER).unwrap_or_default();
+        for line in header.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let value = value.trim();
+            match key {
+                "Platform" if value != "NES" => bail!("unsupported bk2 platform: {value:?}"),
+                "Author" => movie.author = value.to_string(),
+                "GameName" => movie.rom_name = value.to_string(),
+                "rerecordCount" => movie.rerecords = value.parse().unwrap_or_default(),
+                "PAL" => movie.pal = value.eq_ignore_ascii_case("true"),
+                _ => (),
+            }
+        }
+
+        let input_log = read_file(BK2_INPUT_LOG)?;
+        let mut keys = Vec::new();
+        let mut ignored_commands = false;
+        for (number, line) in input_log.lines().enumerate() {
+            let line = line.trim_end();
+            if let Some(log_key) = line.strip_prefix("LogKey:") {
+                keys = log_key
+                    .split('|')
+                    .map(|key| key.trim_start_matches('#'))
+                    .filter(|key| !key.is_empty())
+                    .map(parse_bk2_key)
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                continue;
+            }
+            if !line.starts_with('|') {
+                continue;
+            }
+            let mut buttons = [0; 4];
+            let inputs = line.chars().filter(|&c| c != '|').collect::<Vec<_>>();
+            if inputs.len() != keys.len() {
+                bail!(
+                    "unsupported bk2 input on line {}: expected {} buttons, found {}",
+                    number + 1,
+                    keys.len(),
+                    inputs.len()
+                );
+            }
+            for (key, input) in keys.iter().zip(inputs) {
+                let pressed = input != '.' && input != ' ';
+                match key {
+                    Some((player, button)) if pressed => {
+                        buttons[*player as usize] |= button_bit(*button);
+                    }
+                    Some(_) => (),
+                    None => ignored_commands |= pressed,
+                }
+            }
+            movie.frames.push(buttons);
+        }
+        if keys.is_empty() {
+            bail!("missing bk2 input log key");
+        }
+        if ignored_commands {
+            warn!("ignoring bk2 reset and power commands");
+        }
+        Ok(movie)
+    }
+
+    fn to_bk2(&self) -> anyhow::Result<Vec<u8>> {
+        let players = if self.four_score() { 4 } else { 2 };
+
+        let mut header = String::new();
+        let _ = writeln!(header, "MovieVersion BizHawk v2.0.0");
+        let _ = writeln!(header, "Author {}", self.author);
+        let _ = writeln!(header, "emuVersion TetaNES {}", env!("CARGO_PKG_VERSION"));
+        let _ = writeln!(header, "Platform NES");
+        let _ = writeln!(header, "GameName {}", self.rom_name);
+        let _ = writeln!(header, "Core NesHawk");
+        let _ = writeln!(header, "rerecordCount {}", self.rerecords);
+        if self.pal {
+            let _ = writeln!(header, "PAL True");
+        }
+
+        let mut input_log = String::from("[Input]\nLogKey:#Reset|Power|");
+        for player in 1..=players {
+            input_log.push('#');
+            for (_, name, _) in BK2_BUTTONS {
+                let _ = write!(input_log, "P{player} {name}|");
+            }
+        }
+        input_log.push('\n');
+        for buttons in &self.frames {
+            input_log.push_str("|..|");
+            for &held in &buttons[..players] {
+                for (c, _, button) in BK2_BUTTONS {
+                    input_log.push(if held & button_bit(button) == 0 {
+                        '.'
+                    } else {
+                        c
+                    });
+                }
+                input_log.push('|');
+            }
+            input_log.push('\n');
+        }
+        input_log.push_str("[/Input]\n");
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, text) in [(BK2_HEADER, header), (BK2_INPUT_LOG, input_log)] {
+            zip.start_file(name, SimpleFileOptions::default())?;
+            zip.write_all(text.as_bytes())?;
+        }
+        Ok(zip.finish()?.into_inner())
+    }
+}
+
+/// Movie frame bit for `button`, logging turbo buttons as held.
+const fn button_bit(button: JoypadBtn) -> u8 {
+    let index = match button {
+        JoypadBtn::A | JoypadBtn::TurboA => 0,
+        JoypadBtn::B | JoypadBtn::TurboB => 1,
+        JoypadBtn::Select => 2,
+        JoypadBtn::Start => 3,
+        JoypadBtn::Up => 4,
+        JoypadBtn::Down => 5,
+        JoypadBtn::Left => 6,
+        JoypadBtn::Right => 7,
+    };
+    1 << index
+}
+
+/// Parses an FCEUX controller field, where each mnemonic is shown when pressed and `.` otherwise.
+fn parse_fm2_buttons(field: &str) -> anyhow::Result<u8> {
+    if field.is_empty() {
+        return Ok(0);
+    }
+    if field.chars().count() != FM2_BUTTONS.len() {
+        bail!("unsupported controller input: {field:?}");
+    }
+    Ok(field
+        .chars()
+        .zip(FM2_BUTTONS)
+        .filter(|(input, _)| *input != '.' && *input != ' ')
+        .fold(0, |held, (_, (_, button))| held | button_bit(button)))
+}
+
+/// Parses a BizHawk log key, e.g. `P1 Up`, returning `None` for console commands like `Reset`.
+fn parse_bk2_key(key: &str) -> anyhow::Result<Option<(Player, JoypadBtn)>> {
+    if matches!(key, "Reset" | "Power") {
+        return Ok(None);
+    }
+    let unsupported = || anyhow!("unsupported bk2 input: {key:?}");
+    let (player, name) = key.split_once(' ').ok_or_else(unsupported)?;
+    let player = player
+        .strip_prefix('P')
+        .and_then(|number| number.parse::<usize>().ok())
+        .and_then(|number| number.checked_sub(1))
+        .and_then(|index| Player::try_from(index).ok())
+        .ok_or_else(unsupported)?;
+    let (_, _, button) = BK2_BUTTONS
+        .into_iter()
+        .find(|(_, button, _)| *button == name)
+        .ok_or_else(unsupported)?;
+    Ok(Some((player, button)))
+}
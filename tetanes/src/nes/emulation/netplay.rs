@@ -0,0 +1,214 @@
+use crate::{
+    nes::{
+        emulation::{transfer::Transfer, State},
+        event::RendererEvent,
+        netplay::{Netplay, NetplayEvent},
+        renderer::gui::MessageType,
+    },
+    thread,
+};
+use anyhow::anyhow;
+use tetanes_core::{
+    input::{JoypadBtn, JoypadBtnState, Player},
+    time::Duration,
+};
+
+impl State {
+    /// Whether a netplay session is connected, which limits what can change the emulated state.
+    pub fn netplay_connected(&self) -> bool {
+        self.netplay
+            .as_ref()
+            .is_some_and(|netplay| !netplay.is_listening())
+    }
+
+    /// Warns and returns `true` if an action that changes the emulated state isn't available
+    /// because a netplay session is connected.
+    pub fn netplay_blocked(&mut self) -> bool {
+        let blocked = self.netplay_connected();
+        if blocked {
+            self.add_message(MessageType::Warn, "Not available during netplay");
+        }
+        blocked
+    }
+
+    pub fn netplay_host(&mut self, port: u16) {
+        self.netplay_disconnect();
+        match Netplay::host(port, self.netplay_input_delay) {
+            Ok(netplay) => {
+                self.add_message(MessageType::Info, format!("Hosting Netplay on Port {port}"));
+                self.netplay = Some(netplay);
+            }
+            Err(err) => self.on_error(err),
+        }
+        self.send_netplay_status();
+    }
+
+    pub fn netplay_join(&mut self, addr: &str) {
+        self.netplay_disconnect();
+        let Some(crc32) = self.control_deck.loaded_rom().map(|rom| rom.crc32) else {
+            self.add_message(MessageType::Warn, "Load a ROM before joining netplay");
+            return;
+        };
+        match Netplay::join(addr, crc32, self.netplay_input_delay) {
+            Ok(netplay) => self.netplay = Some(netplay),
+            Err(err) => self.on_error(err),
+        }
+        self.send_netplay_status();
+    }
+
+    pub fn netplay_disconnect(&mut self) {
+        if let Some(mut netplay) = self.netplay.take() {
+            netplay.disconnect();
+            self.add_message(MessageType::Info, "Netplay Disconnected");
+            self.send_netplay_status();
+        }
+    }
+
+    pub fn set_netplay_input_delay(&mut self, input_delay: u32) {
+        self.netplay_input_delay = input_delay;
+        if let Some(netplay) = &mut self.netplay {
+            netplay.set_input_delay(input_delay);
+        }
+    }
+
+    /// Update the local controller sent to the netplay peer.
+    pub fn netplay_button(&mut self, button: JoypadBtn, pressed: bool) {
+        if let Some(netplay) = &mut self.netplay {
+            netplay.set_button(button, pressed);
+        }
+    }
+
+    /// Exchange packets with the netplay peer and handle any resulting events.
+    pub fn poll_netplay(&mut self) {
+        let Some(netplay) = &mut self.netplay else {
+            return;
+        };
+        let prev_status = netplay.status();
+        for event in netplay.poll() {
+            self.on_netplay_event(event);
+        }
+        if self
+            .netplay
+            .as_ref()
+            .is_some_and(|netplay| netplay.status() != prev_status)
+        {
+            self.send_netplay_status();
+        }
+    }
+
+    fn on_netplay_event(&mut self, event: NetplayEvent) {
+        match event {
+            NetplayEvent::Join { crc32 } => {
+                let Some(rom) = self.control_deck.loaded_rom() else {
+                    return;
+                };
+                let state = if rom.crc32 == crc32 {
+                    Transfer {
+                        rom: rom.name.clone(),
+                        cpu: self.control_deck.cpu().clone(),
+                    }
+                    .encode()
+                } else {
+                    Err(anyhow!("the peer loaded a different ROM"))
+                };
+                if let Some(netplay) = &mut self.netplay {
+                    match state {
+                        Ok(state) => netplay.accept(&state),
+                        Err(err) => netplay.reject(err.to_string()),
+                    }
+                }
+            }
+            NetplayEvent::State(state) => match Transfer::decode(&state) {
+                Ok(Transfer { cpu, .. }) => {
+                    self.control_deck.load_cpu(cpu);
+                    self.start_netplay();
+                }
+                Err(err) => {
+                    self.on_error(err);
+                    self.netplay_disconnect();
+                }
+            },
+            NetplayEvent::Connected => self.start_netplay(),
+            NetplayEvent::Desync { frame } => {
+                self.add_message(
+                    MessageType::Warn,
+                    format!("Netplay desynced at frame {frame}"),
+                );
+            }
+            NetplayEvent::Disconnected(reason) => {
+                self.netplay = None;
+                self.add_message(MessageType::Warn, format!("Netplay Disconnected: {reason}"));
+                self.send_netplay_status();
+            }
+        }
+    }
+
+    /// Start lockstep once both peers have loaded the same state.
+    fn start_netplay(&mut self) {
+        let Some(netplay) = &mut self.netplay else {
+            return;
+        };
+        let player = netplay.local_player();
+        let mut local = *self.control_deck.joypad(player);
+        if player != Player::One {
+            local.clear();
+        }
+        netplay.start(self.control_deck.frame_number(), local);
+        self.rewinding = false;
+        self.rewind.clear();
+        self.record.rerecord(self.control_deck.frame_number());
+        self.background_record.clear();
+        self.add_message(
+            MessageType::Info,
+            format!("Netplay Connected as Player {player}"),
+        );
+    }
+
+    /// Sets both controllers from netplay input for the next frame, returning `false` if still
+    /// waiting on the peer.
+    pub fn netplay_ready(&mut self) -> bool {
+        let Some(netplay) = &mut self.netplay else {
+            return true;
+        };
+        if netplay.is_listening() {
+            return true;
+        }
+        match netplay.frame_inputs(self.control_deck.frame_number()) {
+            Some(inputs) => {
+                for (player, buttons) in inputs {
+                    self.control_deck.joypad_mut(player).buttons =
+                        JoypadBtnState::from_bits_truncate(buttons);
+                }
+                true
+            }
+            None => {
+                if self.threaded {
+                    thread::park_timeout(Duration::from_millis(1));
+                }
+                false
+            }
+        }
+    }
+
+    /// Notify the netplay peer that `frame` was clocked.
+    pub fn advance_netplay(&mut self, frame: u32) {
+        let Some(netplay) = &mut self.netplay else {
+            return;
+        };
+        if netplay.is_listening() {
+            return;
+        }
+        if let Some(event) = netplay.advance(frame, self.control_deck.state_hash()) {
+            self.on_netplay_event(event);
+        }
+    }
+
+    fn send_netplay_status(&mut self) {
+        let status = self
+            .netplay
+            .as_ref()
+            .map(Netplay::status)
+            .unwrap_or_default();
+        self.tx.event(RendererEvent::NetplayStatus(status));
+    }
+}
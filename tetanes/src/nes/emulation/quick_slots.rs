@@ -0,0 +1,112 @@
+//! Quick slots: save states kept only in memory for rapid practice loops. They never touch disk
+//! unless explicitly persisted to the save slot with the same number.
+
+use crate::nes::{
+    config::Config, emulation::State, event::RendererEvent, renderer::gui::MessageType,
+};
+use tetanes_core::cpu::Cpu;
+
+/// State of a quick slot, shown in the UI.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum QuickSlot {
+    #[default]
+    Empty,
+    /// Holds a state that hasn't been persisted to disk.
+    Unsaved,
+    /// Holds a state that has been persisted to its save slot.
+    Persisted,
+}
+
+/// Memory-only save states for the loaded ROM, one per save slot.
+#[derive(Default, Debug)]
+#[must_use]
+pub struct QuickSlots {
+    slots: [Option<(Box<Cpu>, bool)>; QuickSlots::COUNT],
+}
+
+impl QuickSlots {
+    pub const COUNT: usize = 8;
+
+    fn index(slot: u8) -> Option<usize> {
+        (1..=Self::COUNT)
+            .contains(&usize::from(slot))
+            .then(|| usize::from(slot) - 1)
+    }
+
+    pub fn status(&self) -> [QuickSlot; Self::COUNT] {
+        self.slots.each_ref().map(|slot| match slot {
+            None => QuickSlot::Empty,
+            Some((_, false)) => QuickSlot::Unsaved,
+            Some((_, true)) => QuickSlot::Persisted,
+        })
+    }
+
+    pub fn clear(&mut self) {
+        self.slots = Default::default();
+    }
+}
+
+impl State {
+    /// Save the current state to a quick slot in memory.
+    pub fn quick_save_state(&mut self, slot: u8) {
+        if self.control_deck.loaded_rom().is_none() {
+            return;
+        }
+        let Some(index) = QuickSlots::index(slot) else {
+            return;
+        };
+        self.quick_slots.slots[index] = Some((Box::new(self.control_deck.cpu().clone()), false));
+        self.add_message(MessageType::Info, format!("Quick State {slot} Saved"));
+        self.send_quick_slots();
+    }
+
+    /// Load the state held in a quick slot.
+    pub fn quick_load_state(&mut self, slot: u8) {
+        if self.control_deck.loaded_rom().is_none() {
+            return;
+        }
+        match QuickSlots::index(slot).and_then(|index| self.quick_slots.slots[index].as_ref()) {
+            Some((cpu, _)) => {
+                let mut cpu = Cpu::clone(cpu);
+                cpu.bus.input.clear();
+                self.control_deck.load_cpu(cpu);
+                self.record.rerecord(self.control_deck.frame_number());
+                self.background_record.clear();
+                self.add_message(MessageType::Info, format!("Quick State {slot} Loaded"));
+            }
+            None => self.add_message(MessageType::Warn, format!("Quick State {slot} Not Found")),
+        }
+    }
+
+    /// Write the state held in a quick slot to the save slot on disk with the same number.
+    pub fn persist_quick_state(&mut self, slot: u8) {
+        let Some(rom) = self.control_deck.loaded_rom() else {
+            return;
+        };
+        let save_path = Config::save_path(&rom.name, slot);
+        let Some(index) = QuickSlots::index(slot) else {
+            return;
+        };
+        let Some((cpu, persisted)) = &mut self.quick_slots.slots[index] else {
+            return self.add_message(MessageType::Warn, format!("Quick State {slot} Not Found"));
+        };
+        match self.control_deck.save_cpu_state(save_path, cpu.as_ref()) {
+            Ok(()) => {
+                *persisted = true;
+                self.add_message(
+                    MessageType::Info,
+                    format!("Quick State {slot} Saved to State {slot}"),
+                );
+                self.send_quick_slots();
+                self.update_save_states();
+            }
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    pub fn send_quick_slots(&mut self) {
+        self.tx
+            .event(RendererEvent::QuickSlots(self.quick_slots.status()));
+    }
+}
@@ -1,27 +1,202 @@
-use crate::nes::{config::Config, event::EmulationEvent};
+use crate::nes::{
+    config::{Config, FrameRate},
+    emulation::{
+        movie::Movie,
+        script::{self, Script},
+        subtitles::{self, Subtitles},
+    },
+    event::EmulationEvent,
+};
 use chrono::Local;
 use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
+    collections::VecDeque,
     io::Read,
     path::{Path, PathBuf},
 };
 use tetanes_core::{
+    common::NesRegion,
     cpu::Cpu,
     fs,
-    input::{JoypadBtn, Player},
+    input::{JoypadBtn, MouseBtn, Player},
 };
 use tracing::warn;
 use winit::event::ElementState;
 
+/// Marks replay files that include a [`ReplayInfo`] header.
+const INFO_MAGIC: [u8; 8] = *b"TNREPLAY";
+/// Marks replay files that include a [`ReplayInfo`] header and a [`Subtitles`] track.
+const SUBTITLES_MAGIC: [u8; 8] = *b"TNREPSUB";
+/// Marks replay files that include a [`ReplayInfo`] header, a [`Subtitles`] track and
+/// [`FrameHash`]es.
+const HASHES_MAGIC: [u8; 8] = *b"TNREPHSH";
+/// Number of frames between recorded state hashes.
+const HASH_INTERVAL: u32 = 30;
+
+/// Format replay recordings are exported in, in addition to the `.replay` file.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub enum ReplayFormat {
+    /// Only `TetaNES` `.replay` files.
+    #[default]
+    Tetanes,
+    /// FCEUX `.fm2` movies.
+    Fm2,
+    /// BizHawk `.bk2` movies.
+    Bk2,
+}
+
+impl ReplayFormat {
+    pub const fn as_slice() -> &'static [Self] {
+        &[Self::Tetanes, Self::Fm2, Self::Bk2]
+    }
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Tetanes => "tetanes",
+            Self::Fm2 => "fm2",
+            Self::Bk2 => "bk2",
+        }
+    }
+
+    /// Returns the TAS movie format of `path` based on its extension, if any.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let ext = path.as_ref().extension()?;
+        [Self::Fm2, Self::Bk2]
+            .into_iter()
+            .find(|format| ext.eq_ignore_ascii_case(format.as_str()))
+    }
+}
+
+impl AsRef<str> for ReplayFormat {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct State(([u8; 8], ReplayInfo, Cpu, Vec<ReplayFrame>));
+
+/// Replay file format with a subtitle track, only used when there are subtitles so replays
+/// without them can still be read by older versions.
 #[derive(Debug, Serialize, Deserialize)]
-pub struct State((Cpu, Vec<ReplayFrame>));
+pub struct SubtitledState(([u8; 8], ReplayInfo, Cpu, Vec<ReplayFrame>, Subtitles));
 
+/// Replay file format with state hashes to detect playback desyncs.
 #[derive(Debug, Serialize, Deserialize)]
+pub struct HashedState(
+    (
+        [u8; 8],
+        ReplayInfo,
+        Cpu,
+        Vec<ReplayFrame>,
+        Subtitles,
+        Vec<FrameHash>,
+    ),
+);
+
+/// Replay file format prior to [`ReplayInfo`] being added.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LegacyState((Cpu, Vec<ReplayFrame>));
+
+/// Metadata describing a replay recording.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub struct ReplayInfo {
+    pub author: String,
+    pub description: String,
+    /// Version of `TetaNES` the replay was recorded with.
+    pub version: String,
+    /// Number of times recording resumed from a loaded save state.
+    pub rerecords: u32,
+    /// Number of recorded events.
+    pub events: usize,
+}
+
+/// State hash of a recorded frame, compared during playback to detect desyncs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub struct FrameHash {
+    pub frame: u32,
+    pub hash: u32,
+}
+
+/// State hash shown while a replay plays back.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct HashStatus {
+    pub frame: u32,
+    pub hash: u32,
+    /// First frame found to have desynced from the recording.
+    pub desync: Option<u32>,
+}
+
+/// A replay file decoded from any supported format.
+struct Decoded {
+    info: ReplayInfo,
+    cpu: Cpu,
+    events: Vec<ReplayFrame>,
+    subtitles: Subtitles,
+    hashes: Vec<FrameHash>,
+}
+
+impl ReplayInfo {
+    /// Decodes a replay file, falling back to the legacy format without a [`ReplayInfo`] header.
+    fn decode(bytes: &[u8]) -> anyhow::Result<Decoded> {
+        if let Ok(HashedState((magic, info, cpu, events, subtitles, hashes))) =
+            fs::load_bytes(bytes)
+        {
+            if magic == HASHES_MAGIC {
+                return Ok(Decoded {
+                    info,
+                    cpu,
+                    events,
+                    subtitles,
+                    hashes,
+                });
+            }
+        }
+        if let Ok(SubtitledState((magic, info, cpu, events, subtitles))) = fs::load_bytes(bytes) {
+            if magic == SUBTITLES_MAGIC {
+                return Ok(Decoded {
+                    info,
+                    cpu,
+                    events,
+                    subtitles,
+                    hashes: Vec::new(),
+                });
+            }
+        }
+        let (info, cpu, events) = match fs::load_bytes::<State>(bytes) {
+            Ok(State((magic, info, cpu, events))) if magic == INFO_MAGIC => (info, cpu, events),
+            _ => {
+                let LegacyState((cpu, events)) = fs::load_bytes(bytes)?;
+                let info = Self {
+                    events: events.len(),
+                    ..Default::default()
+                };
+                (info, cpu, events)
+            }
+        };
+        Ok(Decoded {
+            info,
+            cpu,
+            events,
+            subtitles: Subtitles::default(),
+            hashes: Vec::new(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ReplayEvent {
     Joypad((Player, JoypadBtn, ElementState)),
     ZapperAim((u32, u32)),
     ZapperTrigger,
+    InsertCoin(u8),
+    MouseMotion((i32, i32)),
+    MouseButton((MouseBtn, bool)),
 }
 
 impl From<ReplayEvent> for EmulationEvent {
@@ -30,6 +205,9 @@ impl From<ReplayEvent> for EmulationEvent {
             ReplayEvent::Joypad(state) => Self::Joypad(state),
             ReplayEvent::ZapperAim(pos) => Self::ZapperAim(pos),
             ReplayEvent::ZapperTrigger => Self::ZapperTrigger,
+            ReplayEvent::InsertCoin(slot) => Self::InsertCoin(slot),
+            ReplayEvent::MouseMotion(motion) => Self::MouseMotion(motion),
+            ReplayEvent::MouseButton(button) => Self::MouseButton(button),
         }
     }
 }
@@ -42,12 +220,15 @@ impl TryFrom<EmulationEvent> for ReplayEvent {
             EmulationEvent::Joypad(state) => Self::Joypad(state),
             EmulationEvent::ZapperAim(pos) => Self::ZapperAim(pos),
             EmulationEvent::ZapperTrigger => Self::ZapperTrigger,
+            EmulationEvent::InsertCoin(slot) => Self::InsertCoin(slot),
+            EmulationEvent::MouseMotion(motion) => Self::MouseMotion(motion),
+            EmulationEvent::MouseButton(button) => Self::MouseButton(button),
             _ => return Err(anyhow::anyhow!("invalid replay event: {event:?}")),
         })
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[must_use]
 pub struct ReplayFrame {
     pub frame: u32,
@@ -59,6 +240,11 @@ pub struct ReplayFrame {
 pub struct Record {
     pub start: Option<Cpu>,
     pub events: Vec<ReplayFrame>,
+    pub info: ReplayInfo,
+    pub subtitles: Subtitles,
+    pub hashes: Vec<FrameHash>,
+    /// Additional format to export recordings in.
+    pub format: ReplayFormat,
 }
 
 impl Record {
@@ -66,9 +252,37 @@ impl Record {
         Self::default()
     }
 
+    /// Create a new recorder which tags recordings with an `author` and `description`, also
+    /// exporting them in `format`.
+    pub fn with_info(author: String, description: String, format: ReplayFormat) -> Self {
+        Self {
+            info: ReplayInfo {
+                author,
+                description,
+                ..Default::default()
+            },
+            format,
+            ..Default::default()
+        }
+    }
+
     pub fn start(&mut self, cpu: Cpu) {
         self.start = Some(cpu);
         self.events.clear();
+        self.subtitles.clear();
+        self.hashes.clear();
+        self.info.version = env!("CARGO_PKG_VERSION").to_string();
+        self.info.rerecords = 0;
+    }
+
+    /// Resume recording from a loaded save state at `frame`, discarding any events after it.
+    pub fn rerecord(&mut self, frame: u32) {
+        if self.start.is_some() {
+            self.events.retain(|event| event.frame < frame);
+            self.subtitles.truncate(frame);
+            self.hashes.retain(|hash| hash.frame < frame);
+            self.info.rerecords += 1;
+        }
     }
 
     pub fn stop(&mut self, name: &str) -> anyhow::Result<Option<PathBuf>> {
@@ -83,6 +297,20 @@ impl Record {
         }
     }
 
+    /// Record the state `hash` after `frame` was clocked, if due.
+    pub fn push_hash(&mut self, frame: u32, hash: u32) {
+        if self.start.is_some() && frame % HASH_INTERVAL == 0 {
+            self.hashes.push(FrameHash { frame, hash });
+        }
+    }
+
+    /// Show `text` for `frames` frames starting at `frame` when the recording is played back.
+    pub fn add_subtitle(&mut self, frame: u32, frames: u32, text: String) {
+        if self.start.is_some() {
+            self.subtitles.push(frame, frames, text);
+        }
+    }
+
     /// Saves the replay recording out to a file.
     pub fn save(&mut self, name: &str) -> anyhow::Result<Option<PathBuf>> {
         let Some(start) = self.start.take() else {
@@ -102,17 +330,195 @@ impl Record {
             )
             .with_extension("replay");
         let events = std::mem::take(&mut self.events);
+        let subtitles = std::mem::take(&mut self.subtitles);
+        let hashes = std::mem::take(&mut self.hashes);
+        let info = ReplayInfo {
+            events: events.len(),
+            ..self.info.clone()
+        };
 
-        fs::save(&replay_path, &State((start, events)))?;
+        // Export a hand-editable input script alongside the replay
+        let start_frame = start.bus.ppu.frame_number();
+        let input_script = Script::from_replay_frames(&events, start_frame);
+        fs::save_raw(
+            replay_path.with_extension(script::EXTENSION),
+            input_script.to_string().as_bytes(),
+        )?;
+        if self.format != ReplayFormat::Tetanes {
+            // Export a TAS movie for use with other emulators
+            let movie = Movie {
+                pal: start.region == NesRegion::Pal,
+                rom_name: name.to_string(),
+                ..Movie::from_replay_frames(&events, start_frame, &info)
+            };
+            fs::save_raw(
+                replay_path.with_extension(self.format.as_str()),
+                &movie.encode(self.format)?,
+            )?;
+        }
+        if !subtitles.is_empty() {
+            // Export subtitles for use with video editors and players
+            let frame_rate = f32::from(FrameRate::from(start.region));
+            fs::save_raw(
+                replay_path.with_extension(subtitles::EXTENSION),
+                subtitles.to_srt(start_frame, frame_rate).as_bytes(),
+            )?;
+        }
+        if !hashes.is_empty() {
+            fs::save(
+                &replay_path,
+                &HashedState((HASHES_MAGIC, info, start, events, subtitles, hashes)),
+            )?;
+        } else if !subtitles.is_empty() {
+            fs::save(
+                &replay_path,
+                &SubtitledState((SUBTITLES_MAGIC, info, start, events, subtitles)),
+            )?;
+        } else {
+            fs::save(&replay_path, &State((INFO_MAGIC, info, start, events)))?;
+        }
 
         Ok(Some(replay_path))
     }
 }
 
+/// Always-on input recording into a rolling buffer, so the last few minutes of play can be saved
+/// as a replay after something interesting happens.
+///
+/// A compressed save state is kept every [`BackgroundRecord::CHECKPOINT_FRAMES`] frames to start
+/// saved replays from. Checkpoints and events older than the configured duration are dropped to
+/// keep memory use bounded.
+#[derive(Default, Debug)]
+#[must_use]
+pub struct BackgroundRecord {
+    pub enabled: bool,
+    /// Number of frames of history to keep.
+    frames: u32,
+    checkpoints: VecDeque<(u32, Vec<u8>)>,
+    events: VecDeque<ReplayFrame>,
+}
+
+impl BackgroundRecord {
+    const TARGET_FPS: u32 = 60;
+    /// ~30 seconds @ 60 FPS
+    pub const CHECKPOINT_FRAMES: u32 = 30 * Self::TARGET_FPS;
+
+    pub fn new(enabled: bool, minutes: u32) -> Self {
+        Self {
+            enabled,
+            frames: Self::frame_size(minutes),
+            ..Default::default()
+        }
+    }
+
+    const fn frame_size(minutes: u32) -> u32 {
+        minutes * 60 * Self::TARGET_FPS
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.clear();
+        }
+    }
+
+    pub fn set_minutes(&mut self, minutes: u32) {
+        self.frames = Self::frame_size(minutes);
+    }
+
+    /// Checkpoint the current state if due and drop anything older than the configured duration.
+    /// Called once per clocked frame.
+    pub fn update(&mut self, cpu: &Cpu) -> fs::Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let frame = cpu.bus.ppu.frame_number();
+        if self
+            .checkpoints
+            .back()
+            .map_or(true, |&(last, _)| frame >= last + Self::CHECKPOINT_FRAMES)
+        {
+            self.checkpoints.push_back((frame, fs::save_bytes(cpu)?));
+        }
+
+        // Keep the newest checkpoint old enough to cover the full duration
+        let oldest = frame.saturating_sub(self.frames);
+        while self
+            .checkpoints
+            .get(1)
+            .is_some_and(|&(frame, _)| frame <= oldest)
+        {
+            self.checkpoints.pop_front();
+        }
+        if let Some(&(start, _)) = self.checkpoints.front() {
+            while self.events.front().is_some_and(|event| event.frame < start) {
+                self.events.pop_front();
+            }
+        }
+        Ok(())
+    }
+
+    pub fn push(&mut self, frame: u32, event: EmulationEvent) {
+        if self.enabled {
+            if let Ok(event) = ReplayEvent::try_from(event) {
+                self.events.push_back(ReplayFrame { frame, event });
+            }
+        }
+    }
+
+    /// Discard history after `frame`, e.g. after rewinding.
+    pub fn truncate(&mut self, frame: u32) {
+        self.checkpoints
+            .retain(|&(checkpoint, _)| checkpoint <= frame);
+        self.events.retain(|event| event.frame < frame);
+    }
+
+    pub fn clear(&mut self) {
+        self.checkpoints.clear();
+        self.events.clear();
+    }
+
+    /// Saves the buffered history out to a replay file, tagged with `info` and also exported in
+    /// `format`. Recording continues afterwards.
+    pub fn save(
+        &self,
+        name: &str,
+        info: &ReplayInfo,
+        format: ReplayFormat,
+    ) -> anyhow::Result<Option<PathBuf>> {
+        let Some((_, start)) = self.checkpoints.front() else {
+            return Ok(None);
+        };
+        let mut record = Record {
+            start: Some(fs::load_bytes(start)?),
+            events: self.events.iter().cloned().collect(),
+            info: ReplayInfo {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                rerecords: 0,
+                ..info.clone()
+            },
+            subtitles: Subtitles::default(),
+            hashes: Vec::new(),
+            format,
+        };
+        record.save(name)
+    }
+}
+
 #[derive(Default, Debug)]
 #[must_use]
 pub struct Replay {
     pub events: Vec<ReplayFrame>,
+    pub info: ReplayInfo,
+    pub subtitles: Subtitles,
+    /// Recorded state hashes not yet compared, in reverse order.
+    hashes: Vec<FrameHash>,
+    /// First frame found to have desynced from the recording.
+    desync: Option<u32>,
+    /// Whether a [`HashStatus`] is currently shown.
+    hash_shown: bool,
+    /// Subtitle text currently shown.
+    subtitle: Option<String>,
 }
 
 impl Replay {
@@ -120,25 +526,141 @@ impl Replay {
         Self::default()
     }
 
-    /// Loads a replay recording file.
+    /// Loads a replay recording file, replacing its subtitles with an `.srt` file of the same
+    /// name if one exists so exported subtitles can be edited.
     pub fn load_path(&mut self, path: impl AsRef<Path>) -> anyhow::Result<Cpu> {
         let path = path.as_ref();
-        let State((cpu, mut events)) = fs::load(path)?;
-        events.reverse(); // So we can pop off the end
-        self.events = events;
+        let replay = fs::load_raw(path)?;
+        let cpu = self.load_bytes(&replay)?;
+
+        let srt_path = path.with_extension(subtitles::EXTENSION);
+        if fs::exists(&srt_path) {
+            let srt = fs::load_raw(&srt_path)?;
+            self.subtitles = Subtitles::parse_srt(
+                &String::from_utf8_lossy(&srt),
+                cpu.bus.ppu.frame_number(),
+                f32::from(FrameRate::from(cpu.region)),
+            )?;
+        }
+
         Ok(cpu)
     }
 
     /// Loads a replay from a reader.
     pub fn load(&mut self, mut replay: impl Read) -> anyhow::Result<Cpu> {
-        let mut events = Vec::new();
-        replay.read_to_end(&mut events)?;
-        let State((cpu, mut events)) = fs::load_bytes(&events)?;
-        events.reverse(); // So we can pop off the end
+        let mut bytes = Vec::new();
+        replay.read_to_end(&mut bytes)?;
+        self.load_bytes(&bytes)
+    }
+
+    fn load_bytes(&mut self, bytes: &[u8]) -> anyhow::Result<Cpu> {
+        let Decoded {
+            info,
+            cpu,
+            mut events,
+            subtitles,
+            mut hashes,
+        } = ReplayInfo::decode(bytes)?;
+        // So we can pop off the end
+        events.reverse();
+        hashes.reverse();
         self.events = events;
+        self.info = info;
+        self.subtitles = subtitles;
+        self.hashes = hashes;
+        self.desync = None;
         Ok(cpu)
     }
 
+    /// Loads an input script, starting playback at `start_frame`.
+    pub fn load_script(&mut self, script: &str, start_frame: u32) -> anyhow::Result<()> {
+        let script = Script::parse(script)?;
+        let mut events = script.to_replay_frames(start_frame)?;
+        events.reverse(); // So we can pop off the end
+        self.info = ReplayInfo {
+            events: events.len(),
+            ..Default::default()
+        };
+        self.events = events;
+        self.subtitles.clear();
+        self.hashes.clear();
+        self.desync = None;
+        Ok(())
+    }
+
+    /// Loads a TAS movie, starting playback at `start_frame`.
+    pub fn load_movie(&mut self, movie: &Movie, start_frame: u32) {
+        let mut events = movie.to_replay_frames(start_frame);
+        events.reverse(); // So we can pop off the end
+        self.info = ReplayInfo {
+            author: movie.author.clone(),
+            rerecords: movie.rerecords,
+            events: events.len(),
+            ..Default::default()
+        };
+        self.events = events;
+        self.subtitles.clear();
+        self.hashes.clear();
+        self.desync = None;
+    }
+
+    /// Stops playback, discarding any remaining events.
+    pub fn stop(&mut self) {
+        self.events.clear();
+        self.subtitles.clear();
+        self.hashes.clear();
+        self.desync = None;
+    }
+
+    /// Whether playback is in progress.
+    pub fn is_playing(&self) -> bool {
+        !self.events.is_empty() || !self.hashes.is_empty()
+    }
+
+    /// Compares the state `hash` after `frame` was clocked against the recording, returning the
+    /// expected hash the first time they differ.
+    pub fn check_hash(&mut self, frame: u32, hash: u32) -> Option<FrameHash> {
+        while self
+            .hashes
+            .last()
+            .is_some_and(|expected| expected.frame < frame)
+        {
+            self.hashes.pop();
+        }
+        if self.hashes.last()?.frame != frame {
+            return None;
+        }
+        let expected = self.hashes.pop()?;
+        (expected.hash != hash && self.desync.is_none()).then(|| {
+            self.desync = Some(frame);
+            expected
+        })
+    }
+
+    /// Returns the state hash to show after `frame` was clocked, or `Some(None)` once playback
+    /// has stopped and it should be hidden.
+    pub fn next_hash_status(&mut self, frame: u32, hash: u32) -> Option<Option<HashStatus>> {
+        if self.is_playing() || self.desync.is_some() {
+            self.hash_shown = true;
+            Some(Some(HashStatus {
+                frame,
+                hash,
+                desync: self.desync,
+            }))
+        } else {
+            std::mem::take(&mut self.hash_shown).then_some(None)
+        }
+    }
+
+    /// Returns the subtitle text to show at `frame` if it changed since the last call.
+    pub fn next_subtitle(&mut self, frame: u32) -> Option<Option<String>> {
+        let subtitle = self.subtitles.text_at(frame);
+        (subtitle != self.subtitle).then(|| {
+            self.subtitle.clone_from(&subtitle);
+            subtitle
+        })
+    }
+
     pub fn next(&mut self, frame: u32) -> Option<EmulationEvent> {
         if let Some(event) = self.events.last() {
             match event.frame.cmp(&frame) {
@@ -1,16 +1,63 @@
-use crate::nes::{emulation::State, renderer::gui::MessageType};
+use crate::nes::{
+    emulation::State,
+    event::{EmulationEvent, RendererEvent},
+    renderer::gui::MessageType,
+    RunState,
+};
 use tetanes_core::{
     cpu::Cpu,
     fs::{Error, Result},
-    ppu::frame::Buffer,
+    ppu::{frame::Buffer, Ppu},
+    video::Video,
 };
-use tracing::error;
+use tracing::{debug, error};
 
 #[derive(Default, Debug, Clone)]
 #[must_use]
 pub struct Frame {
     pub buffer: Buffer,
     pub state: Vec<u8>,
+    pub number: u32,
+    /// Downscaled RGBA frame for keyframes shown in the rewind timeline.
+    pub thumbnail: Option<Vec<u8>>,
+}
+
+/// A rewind keyframe as shown in the rewind timeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct RewindKeyframe {
+    /// Number of rewind states back from the latest, starting at 1.
+    pub offset: u32,
+    pub frame: u32,
+    /// Downscaled RGBA frame, [`RewindKeyframe::THUMBNAIL_WIDTH`] pixels wide.
+    pub thumbnail: Vec<u8>,
+}
+
+impl RewindKeyframe {
+    /// How many times smaller thumbnails are than the frame.
+    const THUMBNAIL_SCALE: u32 = 8;
+    pub const THUMBNAIL_WIDTH: u32 = Ppu::WIDTH / Self::THUMBNAIL_SCALE;
+    pub const THUMBNAIL_HEIGHT: u32 = Ppu::HEIGHT / Self::THUMBNAIL_SCALE;
+}
+
+/// The rewind history shown in the rewind timeline, oldest keyframe first.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct RewindTimeline {
+    /// Number of rewind states that can be previewed.
+    pub len: u32,
+    /// Frames between rewind states.
+    pub interval: u32,
+    pub keyframes: Vec<RewindKeyframe>,
+}
+
+/// State kept while previewing rewind states, to return to if the preview is cancelled.
+#[derive(Debug)]
+#[must_use]
+pub struct Scrub {
+    pub run_state: RunState,
+    /// The CPU state before the first preview, if any have been loaded.
+    pub live: Option<Box<Cpu>>,
 }
 
 #[derive(Default, Debug)]
@@ -22,13 +69,24 @@ pub struct Rewind {
     pub count: usize,
     pub interval: usize,
     pub seconds: usize,
+    /// Maximum bytes of rewind frames to keep, limiting `seconds` if needed.
+    pub memory_budget: usize,
+    /// Size of the last rewind frame, used to estimate how many fit in `memory_budget`.
+    pub frame_bytes: Option<usize>,
     pub frames: Vec<Option<Frame>>,
+    /// Rewind states pushed since the last keyframe thumbnail.
+    pub keyframe_counter: usize,
+    /// Set while the rewind timeline is shown.
+    pub scrub: Option<Scrub>,
 }
 
 impl Rewind {
     const TARGET_FPS: usize = 60;
+    const MB: usize = 1024 * 1024;
+    /// Frames between keyframe thumbnails.
+    const KEYFRAME_FRAMES: usize = 30;
 
-    pub fn new(enabled: bool, seconds: u32, interval: u32) -> Self {
+    pub fn new(enabled: bool, seconds: u32, interval: u32, memory_budget_mb: u32) -> Self {
         let interval = interval as usize;
         let seconds = seconds as usize;
         Self {
@@ -38,7 +96,11 @@ impl Rewind {
             count: 0,
             interval,
             seconds,
+            memory_budget: memory_budget_mb as usize * Self::MB,
+            frame_bytes: None,
             frames: vec![None; Self::frame_size(seconds, interval)],
+            keyframe_counter: 0,
+            scrub: None,
         }
     }
 
@@ -46,6 +108,26 @@ impl Rewind {
         Self::TARGET_FPS * seconds / interval
     }
 
+    /// Resize the frame buffer to fit `seconds` of history within the memory budget, keeping at
+    /// least one frame.
+    fn resize(&mut self) {
+        let mut len = Self::frame_size(self.seconds, self.interval);
+        if let Some(frame_bytes) = self.frame_bytes.filter(|&bytes| bytes > 0) {
+            len = len.min(self.memory_budget / frame_bytes);
+        }
+        let len = len.max(1);
+        if len != self.frames.len() {
+            if len < self.frames.len() {
+                debug!("limiting rewind to {len} frames to fit memory budget");
+            }
+            self.frames.resize(len, None);
+            if self.index >= len {
+                self.index = 0;
+            }
+            self.count = self.count.min(len);
+        }
+    }
+
     pub fn set_enabled(&mut self, enabled: bool) {
         self.enabled = enabled;
         if !enabled {
@@ -55,14 +137,17 @@ impl Rewind {
 
     pub fn set_seconds(&mut self, seconds: u32) {
         self.seconds = seconds as usize;
-        self.frames
-            .resize(Self::frame_size(self.seconds, self.interval), None);
+        self.resize();
     }
 
     pub fn set_interval(&mut self, interval: u32) {
         self.interval = interval as usize;
-        self.frames
-            .resize(Self::frame_size(self.seconds, self.interval), None);
+        self.resize();
+    }
+
+    pub fn set_memory_budget(&mut self, memory_budget_mb: u32) {
+        self.memory_budget = memory_budget_mb as usize * Self::MB;
+        self.resize();
     }
 
     pub fn push(&mut self, cpu: &Cpu) -> Result<()> {
@@ -75,12 +160,24 @@ impl Rewind {
 
             let state = bincode::serialize(&cpu)
                 .map_err(|err| Error::SerializationFailed(err.to_string()))?;
+            let buffer = cpu.bus.ppu.frame.buffer.clone();
+            let frame_bytes = state.len() + std::mem::size_of_val(&buffer[..]);
+            if self.frame_bytes.replace(frame_bytes).is_none() {
+                self.resize();
+            }
+            let thumbnail = (self.keyframe_counter == 0).then(|| thumbnail(&buffer));
+            self.keyframe_counter += 1;
+            if self.keyframe_counter >= (Self::KEYFRAME_FRAMES / self.interval).max(1) {
+                self.keyframe_counter = 0;
+            }
             self.frames[self.index] = Some(Frame {
-                buffer: cpu.bus.ppu.frame.buffer.clone(),
+                buffer,
                 state,
+                number: cpu.bus.ppu.frame_number(),
+                thumbnail,
             });
 
-            self.count += 1;
+            self.count = (self.count + 1).min(self.frames.len());
             self.index += 1;
             if self.index >= self.frames.len() {
                 self.index = 0;
@@ -95,33 +192,96 @@ impl Rewind {
         }
         if self.count > 0 {
             self.count -= 1;
-            self.index -= 1;
-            if self.index == 0 {
-                self.index = self.frames.len() - 1;
-            }
+            self.index = self.index.checked_sub(1).unwrap_or(self.frames.len() - 1);
 
             let frame = self.frames[self.index].take()?;
-            bincode::deserialize::<Cpu>(&frame.state)
-                .map(|mut cpu| {
-                    cpu.bus.input.clear();
-                    cpu.bus.ppu.frame.buffer = frame.buffer;
-                    cpu
-                })
-                .map_err(|err| error!("Failed to deserialize CPU state: {err:?}"))
-                .ok()
+            Self::deserialize(&frame)
         } else {
             None
         }
     }
 
+    /// Pop `offset` rewind states, returning the oldest one popped.
+    pub fn pop_to(&mut self, offset: usize) -> Option<Cpu> {
+        if !self.enabled || offset == 0 || offset > self.count {
+            return None;
+        }
+        for _ in 1..offset {
+            self.count -= 1;
+            self.index = self.index.checked_sub(1).unwrap_or(self.frames.len() - 1);
+            self.frames[self.index] = None;
+        }
+        self.pop()
+    }
+
+    /// Returns the rewind state `offset` states back from the latest without removing it.
+    pub fn peek(&self, offset: usize) -> Option<Cpu> {
+        if !self.enabled || offset == 0 || offset > self.count {
+            return None;
+        }
+        let len = self.frames.len();
+        let index = (self.index + len - offset) % len;
+        self.frames[index].as_ref().and_then(Self::deserialize)
+    }
+
+    fn deserialize(frame: &Frame) -> Option<Cpu> {
+        bincode::deserialize::<Cpu>(&frame.state)
+            .map(|mut cpu| {
+                cpu.bus.input.clear();
+                cpu.bus.ppu.frame.buffer = frame.buffer.clone();
+                cpu
+            })
+            .map_err(|err| error!("Failed to deserialize CPU state: {err:?}"))
+            .ok()
+    }
+
+    /// The keyframes that can be rewound to, oldest first.
+    pub fn timeline(&self) -> RewindTimeline {
+        let len = self.frames.len();
+        let keyframes = (1..=self.count)
+            .rev()
+            .filter_map(|offset| {
+                let frame = self.frames[(self.index + len - offset) % len].as_ref()?;
+                let thumbnail = frame.thumbnail.clone()?;
+                Some(RewindKeyframe {
+                    offset: offset as u32,
+                    frame: frame.number,
+                    thumbnail,
+                })
+            })
+            .collect();
+        RewindTimeline {
+            len: self.count as u32,
+            interval: self.interval as u32,
+            keyframes,
+        }
+    }
+
     pub fn clear(&mut self) {
         self.interval_counter = 0;
         self.index = 0;
         self.count = 0;
+        self.frame_bytes = None;
         self.frames.fill(None);
+        self.keyframe_counter = 0;
+        self.scrub = None;
     }
 }
 
+/// Downscale a frame buffer to RGBA by sampling every [`RewindKeyframe::THUMBNAIL_SCALE`]th
+/// pixel.
+fn thumbnail(buffer: &[u16]) -> Vec<u8> {
+    let scale = RewindKeyframe::THUMBNAIL_SCALE as usize;
+    let pixels = buffer
+        .chunks_exact(Ppu::WIDTH as usize)
+        .step_by(scale)
+        .flat_map(|row| row.iter().step_by(scale).copied())
+        .collect::<Vec<_>>();
+    let mut thumbnail = vec![0xFF; 4 * pixels.len()];
+    Video::decode_buffer(&pixels, &mut thumbnail);
+    thumbnail
+}
+
 impl State {
     pub fn rewind_disabled(&mut self) {
         self.add_message(
@@ -143,5 +303,85 @@ impl State {
                 break;
             }
         }
+        self.background_record
+            .truncate(self.control_deck.frame_number());
+    }
+
+    /// Show or hide the rewind timeline, pausing while it's shown and discarding any previewed
+    /// state when it's hidden.
+    pub fn show_rewind_timeline(&mut self, show: bool) {
+        if show {
+            if !self.rewind.enabled {
+                return self.rewind_disabled();
+            }
+            if self.rewind.scrub.is_none() {
+                self.rewind.scrub = Some(Scrub {
+                    run_state: self.run_state,
+                    live: None,
+                });
+                self.tx
+                    .event(EmulationEvent::RunState(RunState::ManuallyPaused));
+            }
+            self.tx
+                .event(RendererEvent::RewindTimeline(self.rewind.timeline()));
+        } else if let Some(scrub) = self.rewind.scrub.take() {
+            if let Some(cpu) = scrub.live {
+                self.control_deck.load_cpu(*cpu);
+                self.send_frame();
+            }
+            self.tx.event(EmulationEvent::RunState(scrub.run_state));
+        }
+    }
+
+    /// Show the rewind state `offset` states back from the latest without discarding the
+    /// states after it. An `offset` of `0` shows the state from before previewing.
+    pub fn preview_rewind(&mut self, offset: u32) {
+        if self.rewind.scrub.is_none() {
+            return;
+        }
+        let cpu = if offset == 0 {
+            self.rewind
+                .scrub
+                .as_mut()
+                .and_then(|scrub| scrub.live.take())
+                .map(|cpu| *cpu)
+        } else {
+            let cpu = self.rewind.peek(offset as usize);
+            if let Some(scrub) = self.rewind.scrub.as_mut().filter(|_| cpu.is_some()) {
+                scrub
+                    .live
+                    .get_or_insert_with(|| Box::new(self.control_deck.cpu().clone()));
+            }
+            cpu
+        };
+        if let Some(cpu) = cpu {
+            self.control_deck.load_cpu(cpu);
+            self.send_frame();
+        }
+    }
+
+    /// Rewind to the state `offset` states back from the latest, discarding the states after it.
+    pub fn commit_rewind(&mut self, offset: u32) {
+        let Some(scrub) = self.rewind.scrub.take() else {
+            return;
+        };
+        match self.rewind.pop_to(offset as usize) {
+            Some(cpu) => {
+                self.control_deck.load_cpu(cpu);
+                self.background_record
+                    .truncate(self.control_deck.frame_number());
+                self.add_message(
+                    MessageType::Info,
+                    format!("Rewound to Frame {}", self.control_deck.frame_number()),
+                );
+            }
+            None => {
+                if let Some(cpu) = scrub.live {
+                    self.control_deck.load_cpu(*cpu);
+                }
+            }
+        }
+        self.send_frame();
+        self.tx.event(EmulationEvent::RunState(scrub.run_state));
     }
 }
@@ -0,0 +1,110 @@
+//! Listing the save states for the loaded ROM along with the details saved alongside them, so
+//! save slots can be managed without loading each one.
+
+use crate::nes::{
+    config::Config, emulation::State, event::RendererEvent, renderer::gui::MessageType,
+};
+use chrono::{DateTime, Local};
+use std::path::Path;
+use tetanes_core::{
+    control_deck::{ControlDeck, SaveStateInfo},
+    fs,
+};
+use tracing::warn;
+
+/// Number of save slots to look for save states in.
+pub const SAVE_SLOTS: u8 = 8;
+
+/// A save state in a save slot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct SaveStateSlot {
+    pub slot: u8,
+    /// When the state was saved, if known.
+    pub saved_at: Option<DateTime<Local>>,
+    /// Details saved alongside the state, which older save states don't have.
+    pub info: Option<SaveStateInfo>,
+}
+
+impl SaveStateSlot {
+    fn load(slot: u8, path: &Path) -> Self {
+        let info = ControlDeck::load_save_state_info(path)
+            .inspect_err(|err| warn!("failed to load save state info: {path:?}: {err:?}"))
+            .ok()
+            .flatten();
+        let saved_at = match &info {
+            Some(info) => DateTime::from_timestamp(info.saved_at as i64, 0)
+                .map(|saved_at| saved_at.with_timezone(&Local)),
+            None => std::fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .map(DateTime::<Local>::from),
+        };
+        Self {
+            slot,
+            saved_at,
+            info,
+        }
+    }
+}
+
+impl State {
+    /// Show or hide the save state manager, sending the save states for the loaded ROM while
+    /// it's shown.
+    pub fn show_save_states(&mut self, show: bool) {
+        self.show_save_states = show;
+        if show {
+            self.send_save_states();
+        }
+    }
+
+    /// Send the save states for the loaded ROM if the save state manager is shown.
+    pub fn update_save_states(&mut self) {
+        if self.show_save_states {
+            self.send_save_states();
+        }
+    }
+
+    pub fn delete_save_state(&mut self, slot: u8) {
+        let Some(rom) = self.control_deck.loaded_rom() else {
+            return;
+        };
+        let save_path = Config::save_path(&rom.name, slot);
+        match fs::remove(save_path) {
+            Ok(()) => self.add_message(MessageType::Info, format!("State {slot} Deleted")),
+            Err(err) => self.on_error(err),
+        }
+        self.update_save_states();
+    }
+
+    /// Copy the save state in a slot to a directory, named after the ROM and slot.
+    pub fn export_save_state(&mut self, slot: u8, dir: &Path) {
+        let Some(rom) = self.control_deck.loaded_rom() else {
+            return;
+        };
+        let save_path = Config::save_path(&rom.name, slot);
+        let export_path = dir
+            .join(format!("{}-slot-{slot}", rom.name))
+            .with_extension(Config::SAVE_EXTENSION);
+        match fs::load_raw(save_path).and_then(|data| fs::save_raw(&export_path, &data)) {
+            Ok(()) => self.add_message(
+                MessageType::Info,
+                format!("State {slot} Exported to {}", export_path.display()),
+            ),
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    fn send_save_states(&mut self) {
+        let slots = match self.control_deck.loaded_rom() {
+            Some(rom) => (1..=SAVE_SLOTS)
+                .filter_map(|slot| {
+                    let path = Config::save_path(&rom.name, slot);
+                    fs::exists(&path).then(|| SaveStateSlot::load(slot, &path))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        self.tx.event(RendererEvent::SaveStates(slots));
+    }
+}
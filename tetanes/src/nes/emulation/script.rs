@@ -0,0 +1,255 @@
+//! Plain-text input scripts which compile to replay events.
+//!
+//! Each line presses buttons for an inclusive range of frames, relative to when the script
+//! starts, with an optional player prefix. Lines starting with `#` are comments.
+//!
+//! ```text
+//! # Press start to skip the title screen
+//! 120: START
+//! 200-260: A+RIGHT
+//! P2 300-310: B
+//! ```
+
+use crate::nes::emulation::replay::{ReplayEvent, ReplayFrame};
+use anyhow::{anyhow, bail, Context};
+use std::{collections::HashMap, fmt::Write};
+use tetanes_core::input::{JoypadBtn, Player};
+use winit::event::ElementState;
+
+/// File extension for input scripts.
+pub const EXTENSION: &str = "txt";
+
+/// A range of frames to hold a set of buttons.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct ScriptInput {
+    pub player: Player,
+    pub start: u32,
+    /// Last frame the buttons are held, inclusive.
+    pub end: u32,
+    pub buttons: Vec<JoypadBtn>,
+}
+
+/// A parsed input script.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Script {
+    pub inputs: Vec<ScriptInput>,
+}
+
+impl Script {
+    /// Parses an input script.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with the offending line number if any line is invalid.
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let inputs = text
+            .lines()
+            .enumerate()
+            .filter_map(|(number, line)| {
+                let line = line.split_once('#').map_or(line, |(line, _)| line).trim();
+                (!line.is_empty()).then_some((number, line))
+            })
+            .map(|(number, line)| {
+                Self::parse_line(line)
+                    .with_context(|| format!("invalid input script line {}: {line:?}", number + 1))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { inputs })
+    }
+
+    fn parse_line(line: &str) -> anyhow::Result<ScriptInput> {
+        let (player, line) = match line.split_once(char::is_whitespace) {
+            Some((prefix, rest)) if prefix.starts_with(['P', 'p']) => {
+                let number = prefix[1..]
+                    .parse::<usize>()
+                    .map_err(|_| anyhow!("invalid player: {prefix:?}"))?;
+                let player = number
+                    .checked_sub(1)
+                    .and_then(|index| Player::try_from(index).ok())
+                    .ok_or_else(|| anyhow!("invalid player: {prefix:?}"))?;
+                (player, rest.trim())
+            }
+            _ => (Player::One, line),
+        };
+
+        let (range, buttons) = line
+            .split_once(':')
+            .ok_or_else(|| anyhow!("expected `<frames>: <buttons>`"))?;
+        let parse_frame = |frame: &str| {
+            frame
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| anyhow!("invalid frame: {frame:?}"))
+        };
+        let (start, end) = match range.split_once('-') {
+            Some((start, end)) => (parse_frame(start)?, parse_frame(end)?),
+            None => {
+                let frame = parse_frame(range)?;
+                (frame, frame)
+            }
+        };
+        if end < start {
+            bail!("frame range ends before it starts: {start}-{end}");
+        }
+        if end.checked_add(1).is_none() {
+            bail!("frame range ends too late to release buttons: {end}");
+        }
+
+        let buttons = buttons
+            .split('+')
+            .map(|button| parse_button(button.trim()))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(ScriptInput {
+            player,
+            start,
+            end,
+            buttons,
+        })
+    }
+
+    /// Compiles the script into replay events starting at `start_frame`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any input would end past the last representable frame.
+    pub fn to_replay_frames(&self, start_frame: u32) -> anyhow::Result<Vec<ReplayFrame>> {
+        let mut events = self
+            .inputs
+            .iter()
+            .flat_map(|input| {
+                input.buttons.iter().flat_map(move |button| {
+                    [
+                        (Some(input.start), ElementState::Pressed),
+                        (input.end.checked_add(1), ElementState::Released),
+                    ]
+                    .map(|(frame, state)| {
+                        let frame = frame
+                            .and_then(|frame| start_frame.checked_add(frame))
+                            .ok_or_else(|| {
+                                let (start, end) = (input.start, input.end);
+                                anyhow!("frames {start}-{end} overflow from frame {start_frame}")
+                            })?;
+                        Ok((frame, input.player, *button, state))
+                    })
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        // Release before pressing so back-to-back ranges for the same button are held
+        events.sort_by_key(|(frame, _, _, state)| (*frame, *state == ElementState::Pressed));
+        Ok(events
+            .into_iter()
+            .map(|(frame, player, button, state)| ReplayFrame {
+                frame,
+                event: ReplayEvent::Joypad((player, button, state)),
+            })
+            .collect())
+    }
+
+    /// Converts recorded replay events starting at `start_frame` back into a script. Events other
+    /// than joypad buttons, e.g. zapper input, are skipped.
+    pub fn from_replay_frames<'a>(
+        frames: impl IntoIterator<Item = &'a ReplayFrame>,
+        start_frame: u32,
+    ) -> Self {
+        let mut held = HashMap::new();
+        let mut ranges = Vec::new();
+        let mut last_frame = 0;
+        for ReplayFrame { frame, event } in frames {
+            let ReplayEvent::Joypad((player, button, state)) = event else {
+                continue;
+            };
+            let frame = frame.saturating_sub(start_frame);
+            last_frame = last_frame.max(frame);
+            match state {
+                ElementState::Pressed => {
+                    held.entry((*player, *button)).or_insert(frame);
+                }
+                ElementState::Released => {
+                    if let Some(start) = held.remove(&(*player, *button)) {
+                        ranges.push((*player, *button, start, frame.saturating_sub(1).max(start)));
+                    }
+                }
+            }
+        }
+        ranges.extend(
+            held.into_iter()
+                .map(|((player, button), start)| (player, button, start, last_frame)),
+        );
+
+        let mut inputs = Vec::<ScriptInput>::new();
+        for (player, button, start, end) in ranges {
+            match inputs
+                .iter_mut()
+                .find(|input| (input.player, input.start, input.end) == (player, start, end))
+            {
+                Some(input) => input.buttons.push(button),
+                None => inputs.push(ScriptInput {
+                    player,
+                    start,
+                    end,
+                    buttons: vec![button],
+                }),
+            }
+        }
+        inputs.sort_by_key(|input| (input.start, input.player as usize, input.end));
+
+        Self { inputs }
+    }
+}
+
+impl std::fmt::Display for Script {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for input in &self.inputs {
+            if input.player != Player::One {
+                write!(f, "P{} ", input.player as usize + 1)?;
+            }
+            write!(f, "{}", input.start)?;
+            if input.end != input.start {
+                write!(f, "-{}", input.end)?;
+            }
+            f.write_str(": ")?;
+            for (i, button) in input.buttons.iter().enumerate() {
+                if i > 0 {
+                    f.write_char('+')?;
+                }
+                f.write_str(button_name(*button))?;
+            }
+            f.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+const fn button_name(button: JoypadBtn) -> &'static str {
+    match button {
+        JoypadBtn::Left => "LEFT",
+        JoypadBtn::Right => "RIGHT",
+        JoypadBtn::Up => "UP",
+        JoypadBtn::Down => "DOWN",
+        JoypadBtn::A => "A",
+        JoypadBtn::B => "B",
+        JoypadBtn::TurboA => "TURBOA",
+        JoypadBtn::TurboB => "TURBOB",
+        JoypadBtn::Select => "SELECT",
+        JoypadBtn::Start => "START",
+    }
+}
+
+fn parse_button(button: &str) -> anyhow::Result<JoypadBtn> {
+    Ok(match button.to_ascii_uppercase().as_str() {
+        "LEFT" => JoypadBtn::Left,
+        "RIGHT" => JoypadBtn::Right,
+        "UP" => JoypadBtn::Up,
+        "DOWN" => JoypadBtn::Down,
+        "A" => JoypadBtn::A,
+        "B" => JoypadBtn::B,
+        "TURBOA" => JoypadBtn::TurboA,
+        "TURBOB" => JoypadBtn::TurboB,
+        "SELECT" => JoypadBtn::Select,
+        "START" => JoypadBtn::Start,
+        _ => bail!("invalid button: {button:?}"),
+    })
+}
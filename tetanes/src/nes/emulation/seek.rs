@@ -0,0 +1,146 @@
+//! Deterministic fast-forward to a target frame.
+//!
+//! Seeking clocks frames back-to-back without audio, applying any loaded replay events exactly as
+//! normal playback would, until the target frame is reached. Seeking backwards first restores the
+//! closest earlier rewind state.
+
+use crate::nes::{
+    emulation::State,
+    event::{EmulationEvent, RendererEvent},
+    renderer::gui::MessageType,
+    RunState,
+};
+use egui::ViewportId;
+use tetanes_core::time::{Duration, Instant};
+
+/// How long to clock frames before yielding to handle events and report progress.
+const BATCH_DURATION: Duration = Duration::from_millis(16);
+
+/// An in-progress seek to a target frame.
+#[derive(Debug, Copy, Clone)]
+#[must_use]
+pub struct Seek {
+    pub start: u32,
+    pub target: u32,
+    /// Whether to send frames to be rendered while seeking.
+    pub video: bool,
+}
+
+impl State {
+    /// Start seeking to the `target` frame number.
+    pub fn seek_to_frame(&mut self, target: u32, video: bool) {
+        let frame = self.control_deck.frame_number();
+        if target < frame {
+            if !self.replay.events.is_empty() {
+                return self.add_message(
+                    MessageType::Warn,
+                    "Can't seek backwards during replay playback",
+                );
+            }
+            if !self.rewind.enabled {
+                return self.rewind_disabled();
+            }
+            while self.control_deck.frame_number() > target {
+                let Some(cpu) = self.rewind.pop() else {
+                    break;
+                };
+                self.control_deck.load_cpu(cpu);
+            }
+            if self.control_deck.frame_number() > target {
+                self.send_frame();
+                return self.add_message(
+                    MessageType::Warn,
+                    format!("Frame {target} is earlier than the oldest rewind state"),
+                );
+            }
+        }
+
+        self.seek = Some(Seek {
+            start: self.control_deck.frame_number(),
+            target,
+            video,
+        });
+        // Clock exactly one frame at a time, regardless of emulation speed
+        self.control_deck.set_frame_speed(1.0);
+        self.audio.pause(true);
+        self.add_message(MessageType::Info, format!("Seeking to frame {target}..."));
+    }
+
+    /// Cancel an in-progress seek, leaving emulation at the current frame.
+    pub fn cancel_seek(&mut self) {
+        if self.seek.is_some() {
+            self.finish_seek();
+            self.add_message(
+                MessageType::Info,
+                format!(
+                    "Seek cancelled at frame {}",
+                    self.control_deck.frame_number()
+                ),
+            );
+        }
+    }
+
+    /// Clock frames towards the seek target for up to [`BATCH_DURATION`], returning whether a
+    /// seek is in progress.
+    pub fn clock_seek(&mut self) -> bool {
+        let Some(seek) = self.seek else {
+            return false;
+        };
+
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let start = Instant::now();
+        while self.control_deck.frame_number() < seek.target && start.elapsed() < BATCH_DURATION {
+            while let Some(event) = self.replay.next(self.control_deck.frame_number()) {
+                self.on_emulation_event(&event);
+            }
+            if let Err(err) = self.control_deck.clock_frame() {
+                self.finish_seek();
+                self.on_clock_error(err);
+                return true;
+            }
+            self.control_deck.clear_audio_samples();
+            self.control_deck.clear_channel_samples();
+            if let Err(err) = self.rewind.push(self.control_deck.cpu()) {
+                self.rewind.set_enabled(false);
+                self.on_error(err);
+            }
+        }
+
+        let frame = self.control_deck.frame_number();
+        if seek.video {
+            self.send_frame();
+        }
+        self.tx.event(RendererEvent::SeekProgress {
+            start: seek.start,
+            frame,
+            target: seek.target,
+        });
+        if frame >= seek.target {
+            self.finish_seek();
+            self.tx
+                .event(EmulationEvent::RunState(RunState::ManuallyPaused));
+            self.add_message(MessageType::Info, format!("Seeked to frame {frame}"));
+        }
+        self.tx.event(RendererEvent::RequestRedraw {
+            viewport_id: ViewportId::ROOT,
+            when: Instant::now(),
+        });
+
+        true
+    }
+
+    fn finish_seek(&mut self) {
+        self.seek = None;
+        self.control_deck.set_frame_speed(self.speed);
+        self.send_frame();
+        self.audio.pause(self.run_state.paused());
+        if !self.run_state.paused() {
+            self.audio.resume();
+        }
+        // To avoid having a large dip in frame stats after seeking
+        self.last_frame_time = Instant::now();
+        self.tx.event(RendererEvent::SeekFinished);
+    }
+}
@@ -0,0 +1,148 @@
+//! Timed text subtitles attached to replays, e.g. to annotate a TAS run.
+//!
+//! Subtitles are stored by frame number and converted to and from [SubRip] (`.srt`) timestamps
+//! using the replay's frame rate, relative to the frame the replay starts on.
+//!
+//! [SubRip]: https://en.wikipedia.org/wiki/SubRip
+
+use anyhow::{anyhow, bail, Context};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// File extension for exported subtitles.
+pub const EXTENSION: &str = "srt";
+
+/// Text shown for an inclusive range of frames.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub struct Subtitle {
+    pub start: u32,
+    /// Last frame the text is shown, inclusive.
+    pub end: u32,
+    pub text: String,
+}
+
+/// A subtitle track.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub struct Subtitles {
+    pub entries: Vec<Subtitle>,
+}
+
+impl Subtitles {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Show `text` for `frames` frames starting at `start`.
+    pub fn push(&mut self, start: u32, frames: u32, text: impl Into<String>) {
+        let text = text.into();
+        if text.trim().is_empty() || frames == 0 {
+            return;
+        }
+        self.entries.push(Subtitle {
+            start,
+            end: start.saturating_add(frames - 1),
+            text,
+        });
+        self.entries.sort_by_key(|subtitle| subtitle.start);
+    }
+
+    /// Removes subtitles starting at or after `frame`, e.g. when re-recording from a save state.
+    pub fn truncate(&mut self, frame: u32) {
+        self.entries.retain(|subtitle| subtitle.start < frame);
+    }
+
+    /// Returns the text to show at `frame`, with overlapping subtitles on separate lines.
+    #[must_use]
+    pub fn text_at(&self, frame: u32) -> Option<String> {
+        let mut lines = self
+            .entries
+            .iter()
+            .filter(|subtitle| (subtitle.start..=subtitle.end).contains(&frame))
+            .map(|subtitle| subtitle.text.as_str());
+        let first = lines.next()?;
+        Some(lines.fold(first.to_string(), |text, line| text + "\n" + line))
+    }
+
+    /// Parses a SubRip subtitle file, with timestamps relative to `start_frame`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a cue has an invalid timestamp line.
+    pub fn parse_srt(srt: &str, start_frame: u32, frame_rate: f32) -> anyhow::Result<Self> {
+        let mut subtitles = Self::default();
+        let srt = srt.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+        for cue in srt
+            .split("\n\n")
+            .map(str::trim)
+            .filter(|cue| !cue.is_empty())
+        {
+            let mut lines = cue.lines();
+            let mut timing = lines.next().unwrap_or_default();
+            // The cue number is optional in practice
+            if !timing.contains("-->") {
+                timing = lines.next().unwrap_or_default();
+            }
+            let (start, end) = timing
+                .split_once("-->")
+                .ok_or_else(|| anyhow!("expected `<start> --> <end>`: {timing:?}"))?;
+            let to_frame = |timestamp: &str| {
+                parse_timestamp(timestamp.trim())
+                    .map(|secs| start_frame.saturating_add((secs * frame_rate).round() as u32))
+                    .with_context(|| format!("invalid subtitle timing: {timing:?}"))
+            };
+            let (start, end) = (to_frame(start)?, to_frame(end)?);
+            let text = lines.collect::<Vec<_>>().join("\n");
+            subtitles.push(start, end.saturating_sub(start).max(1), text);
+        }
+        Ok(subtitles)
+    }
+
+    /// Formats the subtitles as a SubRip file, with timestamps relative to `start_frame`.
+    pub fn to_srt(&self, start_frame: u32, frame_rate: f32) -> String {
+        let timestamp =
+            |frame: u32| format_timestamp(frame.saturating_sub(start_frame), frame_rate);
+        let mut srt = String::new();
+        for (number, subtitle) in self.entries.iter().enumerate() {
+            let _ = write!(
+                srt,
+                "{}\n{} --> {}\n{}\n\n",
+                number + 1,
+                timestamp(subtitle.start),
+                timestamp(subtitle.end + 1),
+                subtitle.text
+            );
+        }
+        srt
+    }
+}
+
+/// Parses an `HH:MM:SS,mmm` timestamp into seconds.
+fn parse_timestamp(timestamp: &str) -> anyhow::Result<f32> {
+    let (time, millis) = timestamp.split_once([',', '.']).unwrap_or((timestamp, "0"));
+    let mut parts = time.split(':').map(str::parse::<u32>);
+    let (Some(Ok(hours)), Some(Ok(minutes)), Some(Ok(seconds)), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        bail!("expected `HH:MM:SS,mmm`: {timestamp:?}");
+    };
+    let millis = millis.parse::<u32>()?;
+    Ok((hours * 3600 + minutes * 60 + seconds) as f32 + millis as f32 / 1000.0)
+}
+
+/// Formats a frame count as an `HH:MM:SS,mmm` timestamp.
+fn format_timestamp(frames: u32, frame_rate: f32) -> String {
+    let millis = (frames as f64 * 1000.0 / f64::from(frame_rate)).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
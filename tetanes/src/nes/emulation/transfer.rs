@@ -0,0 +1,207 @@
+//! Save state transfer to another device over a one-time local HTTP link.
+//!
+//! The sending instance serves the current state, including battery-backed Save RAM, at a
+//! random URL until it's downloaded once or the link expires. The URL includes a checksum the
+//! receiving instance verifies before loading the state.
+
+use crate::nes::event::{EmulationEvent, NesEventProxy, StateData, UiEvent};
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+use tetanes_core::{cpu::Cpu, fs};
+
+/// A save state transferred between devices.
+#[derive(Serialize, Deserialize)]
+#[must_use]
+pub struct Transfer {
+    /// Name of the ROM the state belongs to.
+    pub rom: String,
+    pub cpu: Cpu,
+}
+
+impl std::fmt::Debug for Transfer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Transfer").field("rom", &self.rom).finish()
+    }
+}
+
+impl Transfer {
+    pub fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(fs::save_bytes(self)?)
+    }
+
+    pub fn decode(bytes: &[u8]) -> anyhow::Result<Self> {
+        Ok(fs::load_bytes(bytes)?)
+    }
+}
+
+/// Parses the checksum from a state transfer link.
+fn link_crc32(url: &str) -> Option<u32> {
+    let (_, query) = url.split_once('?')?;
+    query
+        .split('&')
+        .find_map(|param| param.strip_prefix("crc32="))
+        .and_then(|crc32| u32::from_str_radix(crc32, 16).ok())
+}
+
+/// Verifies received state data against the checksum from its link.
+fn verify(data: Vec<u8>, expected_crc32: u32) -> anyhow::Result<Vec<u8>> {
+    let crc32 = fs::compute_crc32(&data);
+    if crc32 != expected_crc32 {
+        bail!("checksum mismatch (expected {expected_crc32:08x}, found {crc32:08x})");
+    }
+    Ok(data)
+}
+
+/// Downloads a save state from a link created by [`serve`] in the background, sending
+/// [`EmulationEvent::LoadStateTransfer`] once verified.
+pub fn receive(tx: NesEventProxy, url: String) {
+    let url = url.trim().to_string();
+    let Some(crc32) = link_crc32(&url) else {
+        tx.event(UiEvent::Error(format!(
+            "invalid state transfer link: {url}"
+        )));
+        return;
+    };
+    let on_received =
+        move |data: anyhow::Result<Vec<u8>>| match data.and_then(|data| verify(data, crc32)) {
+            Ok(data) => tx.event(EmulationEvent::LoadStateTransfer(StateData(data))),
+            Err(err) => tx.event(UiEvent::Error(format!("failed to receive state: {err:?}"))),
+        };
+
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            crate::thread::spawn(async move {
+                on_received(crate::platform::fetch_asset(&url).await);
+            });
+        } else {
+            let spawned = std::thread::Builder::new()
+                .name("state_transfer".into())
+                .spawn(move || {
+                    let data = reqwest::blocking::get(&url)
+                        .and_then(|res| res.error_for_status())
+                        .and_then(|res| res.bytes())
+                        .map(|bytes| bytes.to_vec())
+                        .map_err(|err| anyhow::anyhow!("failed to fetch {url}: {err}"));
+                    on_received(data);
+                });
+            if let Err(err) = spawned {
+                tracing::error!("failed to spawn state transfer thread: {err:?}");
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn serve(_data: Vec<u8>) -> anyhow::Result<String> {
+    bail!("sending states is not supported in the browser")
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use server::serve;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod server {
+    use std::{
+        io::{self, BufRead, BufReader, Write},
+        net::{IpAddr, Ipv4Addr, TcpListener, TcpStream, UdpSocket},
+        time::{Duration, Instant},
+    };
+    use tetanes_core::fs;
+    use tracing::{debug, error, warn};
+    use uuid::Uuid;
+
+    /// How long a link remains valid if it's never downloaded.
+    const LINK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Serves state data in the background until it's downloaded once or the link expires,
+    /// returning the link to download it from.
+    pub fn serve(data: Vec<u8>) -> anyhow::Result<String> {
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+        listener.set_nonblocking(true)?;
+        let port = listener.local_addr()?.port();
+        let ip = local_ip().unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        let token = Uuid::new_v4().simple().to_string();
+        let crc32 = fs::compute_crc32(&data);
+        let url = format!("http://{ip}:{port}/{token}?crc32={crc32:08x}");
+
+        std::thread::Builder::new()
+            .name("state_transfer".into())
+            .spawn(move || {
+                let expires = Instant::now() + LINK_TIMEOUT;
+                while Instant::now() < expires {
+                    match listener.accept() {
+                        Ok((stream, addr)) => match respond(stream, &token, &data) {
+                            Ok(true) => {
+                                debug!("sent state to {addr}");
+                                return;
+                            }
+                            Ok(false) => debug!("rejected state request from {addr}"),
+                            Err(err) => warn!("failed to respond to {addr}: {err:?}"),
+                        },
+                        Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                            std::thread::sleep(Duration::from_millis(100));
+                        }
+                        Err(err) => {
+                            error!("state transfer server failed: {err:?}");
+                            return;
+                        }
+                    }
+                }
+                debug!("state transfer link expired");
+            })?;
+
+        Ok(url)
+    }
+
+    /// Responds to a request, returning whether the state was sent.
+    fn respond(mut stream: TcpStream, token: &str, data: &[u8]) -> io::Result<bool> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+
+        // Read the full request header so closing the connection doesn't reset it
+        let mut reader = BufReader::new(&stream);
+        let mut request = String::new();
+        reader.read_line(&mut request)?;
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+            header.clear();
+        }
+
+        // e.g. `GET /<token>?crc32=<crc32> HTTP/1.1`
+        let mut parts = request.split_whitespace();
+        let method = parts.next();
+        let path = parts.next().unwrap_or_default();
+        let path = path.split_once('?').map_or(path, |(path, _)| path);
+        let valid = method == Some("GET") && path.strip_prefix('/') == Some(token);
+
+        if valid {
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\n\
+                Content-Type: application/octet-stream\r\n\
+                Content-Length: {}\r\n\
+                Content-Disposition: attachment; filename=\"tetanes.state\"\r\n\
+                Access-Control-Allow-Origin: *\r\n\
+                Connection: close\r\n\r\n",
+                data.len()
+            )?;
+            stream.write_all(data)?;
+        } else {
+            write!(
+                stream,
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            )?;
+        }
+        stream.flush()?;
+        Ok(valid)
+    }
+
+    /// Finds the local network address other devices can reach this one on.
+    fn local_ip() -> Option<IpAddr> {
+        // Connecting a UDP socket doesn't send anything, but selects the outbound interface
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+        socket.connect((Ipv4Addr::new(8, 8, 8, 8), 80)).ok()?;
+        socket.local_addr().ok().map(|addr| addr.ip())
+    }
+}
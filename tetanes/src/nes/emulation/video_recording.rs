@@ -0,0 +1,284 @@
+//! Video recordings of the game and its audio, encoded by an `ffmpeg` process.
+//!
+//! Frames are piped to `ffmpeg` as they're emulated while audio is written to a temporary WAV
+//! file, which is then muxed into the final video when the recording stops.
+
+use crate::nes::config::Config;
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    process::{Child, ChildStdin, Command, Stdio},
+};
+use tetanes_core::{common::NesRegion, cpu::Cpu, ppu::Ppu};
+use tracing::warn;
+
+const FFMPEG: &str = "ffmpeg";
+
+/// Returns the exact frame rate of `region`, e.g. ~60.0988Hz for NTSC.
+pub fn frame_rate(region: NesRegion) -> f64 {
+    let cycles_per_frame = match region {
+        // One dot is skipped every other frame
+        NesRegion::Auto | NesRegion::Ntsc | NesRegion::PalM => 29_780.5,
+        NesRegion::Pal => 33_247.5,
+        NesRegion::Dendy => 35_464.0,
+    };
+    f64::from(Cpu::region_clock_rate(region)) / cycles_per_frame
+}
+
+/// Container format video recordings are written in.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub enum VideoFormat {
+    /// H.264 video and AAC audio.
+    #[default]
+    Mp4,
+    /// VP9 video and Opus audio.
+    WebM,
+}
+
+impl VideoFormat {
+    pub const fn as_slice() -> &'static [Self] {
+        &[Self::Mp4, Self::WebM]
+    }
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mp4 => "MP4",
+            Self::WebM => "WebM",
+        }
+    }
+
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Mp4 => "mp4",
+            Self::WebM => "webm",
+        }
+    }
+
+    /// `ffmpeg` arguments to encode the video stream, fast enough to keep up with emulation.
+    const fn video_args(&self) -> &'static [&'static str] {
+        match self {
+            Self::Mp4 => &[
+                "-c:v", "libx264", "-preset", "veryfast", "-crf", "18", "-pix_fmt", "yuv420p",
+            ],
+            Self::WebM => &[
+                "-c:v",
+                "libvpx-vp9",
+                "-deadline",
+                "realtime",
+                "-cpu-used",
+                "8",
+                "-crf",
+                "30",
+                "-b:v",
+                "0",
+                "-pix_fmt",
+                "yuv420p",
+            ],
+        }
+    }
+
+    /// `ffmpeg` arguments to encode the audio stream.
+    const fn audio_args(&self) -> &'static [&'static str] {
+        match self {
+            Self::Mp4 => &["-c:a", "aac", "-b:a", "192k"],
+            Self::WebM => &["-c:a", "libopus", "-b:a", "128k"],
+        }
+    }
+}
+
+impl AsRef<str> for VideoFormat {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// A video recording in progress.
+#[must_use]
+pub struct VideoRecording {
+    format: VideoFormat,
+    path: PathBuf,
+    video_path: PathBuf,
+    audio_path: PathBuf,
+    encoder: Child,
+    frames: BufWriter<ChildStdin>,
+    audio: hound::WavWriter<BufWriter<File>>,
+}
+
+impl std::fmt::Debug for VideoRecording {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VideoRecording")
+            .field("format", &self.format)
+            .field("path", &self.path)
+            .field("encoder", &self.encoder)
+            .finish_non_exhaustive()
+    }
+}
+
+impl VideoRecording {
+    /// Start a recording in the default video directory of frames emulated for `region` and
+    /// audio output at `sample_rate`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the recording files can't be created or `ffmpeg` fails to start.
+    pub fn create(
+        format: VideoFormat,
+        region: NesRegion,
+        sample_rate: u32,
+    ) -> anyhow::Result<Self> {
+        let dir = Config::default_video_dir();
+        if !dir.exists() {
+            std::fs::create_dir_all(&dir).with_context(|| {
+                format!(
+                    "failed to create video recording directory: {}",
+                    dir.display()
+                )
+            })?;
+        }
+        let name = chrono::Local::now()
+            .format("recording_%Y-%m-%d_at_%H_%M_%S")
+            .to_string();
+        let path = dir.join(&name).with_extension(format.extension());
+        let video_path = dir
+            .join(format!("{name}.video"))
+            .with_extension(format.extension());
+        let audio_path = dir.join(format!("{name}.audio")).with_extension("wav");
+
+        let mut encoder = Command::new(FFMPEG)
+            .args([
+                "-y",
+                "-loglevel",
+                "error",
+                "-f",
+                "rawvideo",
+                "-pix_fmt",
+                "rgba",
+            ])
+            .args(["-s", &format!("{}x{}", Ppu::WIDTH, Ppu::HEIGHT)])
+            .args([
+                "-framerate",
+                &frame_rate(region).to_string(),
+                "-i",
+                "pipe:0",
+            ])
+            .args(format.video_args())
+            .arg(&video_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("failed to start ffmpeg, is it installed?")?;
+        let frames = encoder
+            .stdin
+            .take()
+            .map(BufWriter::new)
+            .ok_or_else(|| anyhow!("failed to open ffmpeg input"))?;
+
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let audio = match hound::WavWriter::create(&audio_path, spec) {
+            Ok(audio) => audio,
+            Err(err) => {
+                let _ = encoder.kill();
+                return Err(err).context("failed to create video recording audio");
+            }
+        };
+
+        Ok(Self {
+            format,
+            path,
+            video_path,
+            audio_path,
+            encoder,
+            frames,
+            audio,
+        })
+    }
+
+    /// Write an RGBA frame and the audio samples output during it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `ffmpeg` stopped or the audio failed to write.
+    pub fn write_frame(&mut self, frame_buffer: &[u8], samples: &[f32]) -> anyhow::Result<()> {
+        self.frames
+            .write_all(frame_buffer)
+            .context("failed to write video recording frame")?;
+        for sample in samples {
+            self.audio.write_sample(*sample)?;
+        }
+        Ok(())
+    }
+
+    /// Finish encoding the video and mux in the audio, returning the path of the video.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either `ffmpeg` process fails.
+    pub fn finalize(self) -> anyhow::Result<PathBuf> {
+        let Self {
+            format,
+            path,
+            video_path,
+            audio_path,
+            mut encoder,
+            frames,
+            audio,
+        } = self;
+
+        let res = Self::finish_encoding(frames, &mut encoder, audio).and_then(|()| {
+            Self::run_ffmpeg(
+                Command::new(FFMPEG)
+                    .args(["-y", "-loglevel", "error", "-i"])
+                    .arg(&video_path)
+                    .arg("-i")
+                    .arg(&audio_path)
+                    .args(["-c:v", "copy"])
+                    .args(format.audio_args())
+                    .arg(&path),
+            )
+        });
+        for path in [&video_path, &audio_path] {
+            if let Err(err) = std::fs::remove_file(path) {
+                warn!("failed to remove {}: {err:?}", path.display());
+            }
+        }
+        res.context("failed to finalize video recording")?;
+        Ok(path)
+    }
+
+    fn finish_encoding(
+        frames: BufWriter<ChildStdin>,
+        encoder: &mut Child,
+        audio: hound::WavWriter<BufWriter<File>>,
+    ) -> anyhow::Result<()> {
+        // Closing the input lets ffmpeg finish encoding
+        drop(frames.into_inner().map_err(|err| err.into_error())?);
+        let status = encoder.wait()?;
+        if !status.success() {
+            return Err(anyhow!("ffmpeg failed to encode video: {status}"));
+        }
+        audio.finalize()?;
+        Ok(())
+    }
+
+    fn run_ffmpeg(command: &mut Command) -> anyhow::Result<()> {
+        let status = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+        if !status.success() {
+            return Err(anyhow!("ffmpeg failed to mux audio: {status}"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+//! Watching a folder for new ROM builds, e.g. from a homebrew toolchain that emits timestamped
+//! builds, and loading the newest one automatically.
+
+use crate::nes::{emulation::State, event::EmulationEvent};
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tetanes_core::time::{Duration, Instant};
+use tracing::debug;
+
+/// A ROM file as of when it was last checked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+struct RomFile {
+    path: PathBuf,
+    modified: SystemTime,
+    len: u64,
+}
+
+/// Watches a folder for the newest `.nes` file.
+#[derive(Debug)]
+#[must_use]
+pub struct WatchFolder {
+    dir: Option<PathBuf>,
+    last_poll: Instant,
+    /// Newest ROM already seen, which isn't loaded again unless it changes.
+    seen: Option<RomFile>,
+    /// Newest ROM and when it was first seen, loaded once it stops changing.
+    pending: Option<(RomFile, Instant)>,
+}
+
+impl WatchFolder {
+    const POLL_INTERVAL: Duration = Duration::from_millis(500);
+    /// How long the newest ROM has to stay unchanged before loading it, so it isn't loaded while
+    /// still being written.
+    const DEBOUNCE: Duration = Duration::from_secs(1);
+
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        let mut watch_folder = Self {
+            dir: None,
+            last_poll: Instant::now(),
+            seen: None,
+            pending: None,
+        };
+        watch_folder.set_dir(dir);
+        watch_folder
+    }
+
+    /// Start watching a folder, or stop if `None`. ROMs already in the folder aren't loaded.
+    pub fn set_dir(&mut self, dir: Option<PathBuf>) {
+        self.seen = dir.as_deref().and_then(newest_rom);
+        self.pending = None;
+        self.dir = dir;
+    }
+
+    /// Returns the newest ROM once it's appeared or changed and stopped changing.
+    fn poll(&mut self) -> Option<PathBuf> {
+        let dir = self.dir.as_deref()?;
+        if self.last_poll.elapsed() < Self::POLL_INTERVAL {
+            return None;
+        }
+        self.last_poll = Instant::now();
+
+        let newest = newest_rom(dir)?;
+        if self.seen.as_ref() == Some(&newest) {
+            self.pending = None;
+            return None;
+        }
+        match &self.pending {
+            Some((pending, since)) if *pending == newest => {
+                if since.elapsed() >= Self::DEBOUNCE {
+                    self.pending = None;
+                    self.seen = Some(newest.clone());
+                    return Some(newest.path);
+                }
+            }
+            _ => self.pending = Some((newest, Instant::now())),
+        }
+        None
+    }
+}
+
+/// Returns the most recently modified `.nes` file in a folder.
+fn newest_rom(dir: &Path) -> Option<RomFile> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            debug!("failed to read watch folder {dir:?}: {err:?}");
+            return None;
+        }
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("nes"))
+        })
+        .filter_map(|entry| {
+            let metadata = entry
+                .metadata()
+                .ok()
+                .filter(|metadata| metadata.is_file())?;
+            Some(RomFile {
+                path: entry.path(),
+                modified: metadata.modified().ok()?,
+                len: metadata.len(),
+            })
+        })
+        .max_by_key(|rom| rom.modified)
+}
+
+impl State {
+    /// Load the newest ROM in the watch folder, if a new one has finished being written.
+    pub fn poll_watch_folder(&mut self) {
+        if let Some(path) = self.watch_folder.poll() {
+            debug!("loading newest ROM from watch folder: {path:?}");
+            // Sent through the event loop so it's added to recently played ROMs
+            self.tx.event(EmulationEvent::LoadRomPath(path));
+        }
+    }
+}
@@ -2,12 +2,27 @@ use crate::{
     feature,
     nes::{
         action::{Action, Debug, DebugKind, DebugStep, Feature, Setting, Ui},
-        config::Config,
-        emulation::FrameStats,
+        audio::recording::RecordingFormat,
+        config::{AttractModeConfig, Config},
+        emulation::{
+            bookmarks::BookmarkInfo,
+            clipboard::FrameCopy,
+            compare::CompareResult,
+            failed_saves::FailedSave,
+            front_panel::FrontPanel,
+            memory::MemorySource,
+            quick_slots::{QuickSlot, QuickSlots},
+            replay::{HashStatus, ReplayFormat, ReplayInfo},
+            rewind::RewindTimeline,
+            save_states::SaveStateSlot,
+            FrameStats,
+        },
+        hooks::HookEvent,
         input::{ActionBindings, AxisDirection, Gamepads, Input, InputBindings},
+        netplay::NetplayStatus,
         renderer::{
             gui::{Menu, MessageType},
-            shader::Shader,
+            shader::ShaderPipeline,
         },
         rom::RomData,
         Nes, RunState, Running, State,
@@ -17,18 +32,20 @@ use crate::{
 use anyhow::anyhow;
 use egui::ViewportId;
 use std::path::PathBuf;
+#[cfg(feature = "debug")]
+use tetanes_core::debug::BusActivity;
 use tetanes_core::{
     action::Action as DeckAction,
     apu::{Apu, Channel},
     common::{NesRegion, ResetKind},
     control_deck::{LoadedRom, MapperRevisionsConfig},
-    debug::Debugger,
+    debug::{Breakpoint, CpuDebugState, Debugger, Interrupt, MemorySnapshot},
     genie::GenieCode,
-    input::{FourPlayer, JoypadBtn, Player},
-    mem::RamState,
+    input::{FourPlayer, JoypadBtn, MouseBtn, MousePort, Player},
+    mem::{RamRetention, RamState},
     ppu::Ppu,
     time::{Duration, Instant},
-    video::VideoFilter,
+    video::{ColorFilter, VideoFilter},
 };
 use tracing::{debug, error, trace};
 use uuid::Uuid;
@@ -118,43 +135,81 @@ pub enum ConfigEvent {
     ActionBindings(Vec<ActionBindings>),
     ActionBindingSet((Action, Input, usize)),
     ActionBindingClear(Input),
+    AllowMultipleInstances(bool),
     AlwaysOnTop(bool),
     ApuChannelEnabled((Channel, bool)),
     ApuChannelsEnabled([bool; Apu::MAX_CHANNEL_COUNT]),
+    AttractMode(AttractModeConfig),
     AudioBuffer(usize),
     AudioEnabled(bool),
+    AudioFrameStep(bool),
     AudioLatency(Duration),
+    /// Keep playing audio through the speakers while recording it to a file.
+    AudioMonitorRecording(bool),
+    AudioRecordingFormat(RecordingFormat),
+    /// Record each APU channel to its own file.
+    AudioSplitChannels(bool),
     AutoLoad(bool),
     AutoSave(bool),
     AutoSaveInterval(Duration),
+    BackgroundReplay(bool),
+    BackgroundReplayMinutes(u32),
+    ColorFilter(ColorFilter),
     ConcurrentDpad(bool),
     CycleAccurate(bool),
     DarkTheme(bool),
     EmbedViewports(bool),
+    /// Encrypt save states and SRAM with a password entered each session.
+    EncryptSaves(bool),
     FourPlayer(FourPlayer),
     Fullscreen(bool),
     GamepadAssign((Player, Uuid)),
     GamepadAssignments([(Player, Option<Uuid>); 4]),
+    GamepadNavigation(bool),
     GamepadUnassign(Player),
     GenieCodeAdded(GenieCode),
     GenieCodeClear,
     GenieCodeRemoved(String),
     HideOverscan(bool),
     MapperRevisions(MapperRevisionsConfig),
+    MatchFrameRate(bool),
+    MousePort(MousePort),
+    /// Frames local input is delayed by during netplay.
+    NetplayInputDelay(u32),
+    PixelPerfect(bool),
+    RamRetention(RamRetention),
     RamState(RamState),
     RecentRomsClear,
+    ReplayAuthor(String),
+    ReplayDescription(String),
     Region(NesRegion),
     RewindEnabled(bool),
     RewindInterval(u32),
+    /// Maximum megabytes of rewind history to keep.
+    RewindMemoryBudget(u32),
     RewindSeconds(u32),
+    /// Set or clear the region to emulate for a ROM by name.
+    RomRegion((String, Option<NesRegion>)),
+    /// Set or clear whether to hide overscan for a ROM by name.
+    RomOverscan((String, Option<bool>)),
     RunAhead(usize),
     SaveSlot(u8),
     Scale(f32),
-    Shader(Shader),
+    Shader(ShaderPipeline),
+    ShowAudioScope(bool),
     ShowMenubar(bool),
     ShowMessages(bool),
+    ShowOverscanOutline(bool),
+    SnapWindowSize(bool),
     Speed(f32),
+    Translucent(bool),
+    TvStatic(bool),
     VideoFilter(VideoFilter),
+    /// Vs. System DIP switches 1-8 in bits 0-7.
+    VsDipSwitches(u8),
+    /// Watch a folder for the newest ROM to load, or stop watching if `None`.
+    WatchFolder(Option<PathBuf>),
+    WindowOpacity(f32),
     ZapperConnected(bool),
 }
 
@@ -167,7 +222,14 @@ impl From<ConfigEvent> for NesEvent {
 #[derive(Debug, Clone)]
 #[must_use]
 pub enum DebugEvent {
-    Ppu(Ppu),
+    Cpu(Box<CpuDebugState>),
+    Ppu(Box<Ppu>),
+    Interrupts(Vec<Interrupt>),
+    MemorySnapshots(Box<(MemorySnapshot, MemorySnapshot)>),
+    MemoryWatch(Vec<(u16, u8)>),
+    /// Accumulated bus activity and the number of frames it was accumulated over.
+    #[cfg(feature = "debug")]
+    BusActivity((BusActivity, u32)),
 }
 
 impl From<DebugEvent> for NesEvent {
@@ -181,27 +243,141 @@ impl From<DebugEvent> for NesEvent {
 pub enum EmulationEvent {
     AddDebugger(Debugger),
     RemoveDebugger(Debugger),
+    AddBreakpoint(Breakpoint),
+    RemoveBreakpoint(Breakpoint),
+    ClearBreakpoints,
     AudioRecord(bool),
+    /// Start or stop recording a video of the game and its audio.
+    VideoRecord(bool),
+    /// Count bus activity, sending it every given number of frames, or stop counting if `None`.
+    #[cfg(feature = "debug")]
+    BusActivity(Option<u32>),
+    /// Send CPU registers and disassembly for the debugger each frame and after each step.
+    CpuDebugger(bool),
     DebugStep(DebugStep),
+    /// Log DPCM samples as the DMC fetches them, or stop and clear the log.
+    DmcSampleLog(bool),
     EmulatePpuWarmup(bool),
+    /// Export logged DPCM samples as WAV files.
+    ExportDmcSamples,
     InstantRewind,
+    InterruptLog(bool),
     Joypad((Player, JoypadBtn, ElementState)),
     LoadReplay((String, ReplayData)),
     LoadReplayPath(PathBuf),
     LoadRom((String, RomData)),
     LoadRomPath(PathBuf),
     LoadState(u8),
+    /// Load a verified save state received from another device.
+    LoadStateTransfer(StateData),
+    /// Freeze a RAM address to a value, or unfreeze it if `None`.
+    MemoryFreeze((u16, Option<u8>)),
+    /// Request snapshots of RAM to compare for memory search.
+    MemorySnapshots((MemorySource, MemorySource)),
+    /// Send the values of RAM addresses every frame.
+    MemoryWatch(Vec<u16>),
+    /// Host a netplay session on a UDP port.
+    NetplayHost(u16),
+    /// Join a netplay session at an address, e.g. `192.168.1.2:7845`.
+    NetplayJoin(String),
+    NetplayDisconnect,
     RunState(RunState),
     ReplayRecord(bool),
     Reset(ResetKind),
+    /// Press or release the front panel reset button.
+    ResetButton(bool),
+    /// Flip the front panel power switch.
+    TogglePower,
+    /// Resume audio output suspended by the platform, e.g. browsers block audio until a user
+    /// gesture.
+    ResumeAudio,
+    StopReplay,
     RequestFrame,
     Rewinding(bool),
+    /// Whether the rewind timeline is shown, pausing emulation to preview rewind states.
+    ShowRewindTimeline(bool),
+    /// Preview the rewind state a number of states back from the latest.
+    PreviewRewind(u32),
+    /// Rewind to the state a number of states back from the latest.
+    CommitRewind(u32),
     SaveState(u8),
+    /// Save the current state to the safety save, separate from the save slots.
+    SafetySaveState,
+    /// Save the current state to a memory-only quick slot.
+    QuickSaveState(u8),
+    QuickLoadState(u8),
+    /// Write a quick slot to the save slot on disk with the same number.
+    PersistQuickState(u8),
+    /// Capture the current state into the ring of checkpoints.
+    SaveCheckpoint,
+    /// Load the checkpoint before the last one loaded.
+    LoadPreviousCheckpoint,
+    /// Bookmark the current frame with a save state.
+    AddBookmark,
+    LoadBookmark(u64),
+    RemoveBookmark(u64),
+    /// Set the note for a bookmark.
+    BookmarkNote((u64, String)),
+    /// Fast-forward to a frame number as fast as possible without audio, optionally rendering
+    /// video along the way.
+    SeekToFrame {
+        frame: u32,
+        video: bool,
+    },
+    CancelSeek,
+    /// Play back two replays side by side to find the first frame where they diverge.
+    CompareReplays((PathBuf, PathBuf)),
+    CancelCompare,
+    /// Serve the current save state for another device to download.
+    SendState,
     ShowFrameStats(bool),
     Screenshot,
+    /// Render every save state for the loaded ROM to PNGs in a new folder.
+    ExportSaveStateGallery,
+    /// Whether the save state manager is shown, which needs save states sent as they change.
+    ShowSaveStates(bool),
+    DeleteSaveState(u8),
+    /// Copy the save state in a slot to a directory.
+    ExportSaveState((u8, PathBuf)),
+    /// Save the inputs buffered by background recording as a replay.
+    SaveBackgroundReplay,
+    /// Copy the current frame to the clipboard as an image.
+    CopyFrame(FrameCopy),
+    /// Copy the loaded ROM name and current frame number to the clipboard.
+    CopyFrameInfo,
     UnloadRom,
+    /// Write saves that failed to be written again, either to the same path or to another
+    /// directory.
+    RetryFailedSaves(Option<PathBuf>),
+    /// Give up on saves that failed to be written.
+    DiscardFailedSaves,
     ZapperAim((u32, u32)),
     ZapperTrigger,
+    /// Move the mouse by a number of counts.
+    MouseMotion((i32, i32)),
+    MouseButton((MouseBtn, bool)),
+    /// Insert a coin into a Vs. System coin slot.
+    InsertCoin(u8),
+    /// Add a subtitle to the replay being recorded, shown from the current frame for a number of
+    /// seconds.
+    AddSubtitle {
+        text: String,
+        seconds: f32,
+    },
+}
+
+impl EmulationEvent {
+    /// Load a ROM or replay file opened from the command line or file manager, based on its
+    /// extension.
+    pub fn load_path(path: PathBuf) -> Self {
+        if path.extension().is_some_and(|ext| ext == "replay")
+            || ReplayFormat::from_path(&path).is_some()
+        {
+            Self::LoadReplayPath(path)
+        } else {
+            Self::LoadRomPath(path)
+        }
+    }
 }
 
 impl From<EmulationEvent> for NesEvent {
@@ -217,7 +393,11 @@ pub enum RendererEvent {
     FrameStats(FrameStats),
     ShowMenubar(bool),
     ToggleFullscreen,
-    ReplayLoaded,
+    ReplayLoaded(ReplayInfo),
+    /// Subtitle text to show during replay playback, or `None` to hide it.
+    ReplaySubtitle(Option<String>),
+    /// State hash to show during replay playback, or `None` to hide it.
+    ReplayHash(Option<HashStatus>),
     ResizeTexture,
     ResizeWindow,
     ResourcesReady,
@@ -228,6 +408,39 @@ pub enum RendererEvent {
     RomLoaded(LoadedRom),
     RomUnloaded,
     Menu(Menu),
+    /// A link another device can download the current save state from.
+    StateTransferLink(String),
+    StateSaved(u8),
+    StateLoaded(u8),
+    NetplayStatus(NetplayStatus),
+    SeekProgress {
+        start: u32,
+        frame: u32,
+        target: u32,
+    },
+    SeekFinished,
+    CompareProgress {
+        frame: u32,
+        total: u32,
+    },
+    CompareFinished(CompareResult),
+    CopyText(String),
+    FrontPanel(FrontPanel),
+    QuickSlots([QuickSlot; QuickSlots::COUNT]),
+    /// Audio samples output for the latest frame, sent while the audio scope is shown.
+    AudioScope(Vec<f32>),
+    /// Saves that failed to be written and are being kept in memory.
+    FailedSaves(Vec<FailedSave>),
+    /// Bookmarks for the loaded ROM.
+    Bookmarks(Vec<BookmarkInfo>),
+    /// Rewind keyframes, sent when the rewind timeline is shown.
+    RewindTimeline(RewindTimeline),
+    /// Save states for the loaded ROM, sent while the save state manager is shown.
+    SaveStates(Vec<SaveStateSlot>),
+    /// Whether a video is being recorded, sent when a recording starts or stops.
+    VideoRecording(bool),
+    /// Save the last few seconds of gameplay as an animated image.
+    SaveClip,
 }
 
 impl From<RendererEvent> for NesEvent {
@@ -245,6 +458,10 @@ pub enum UiEvent {
     LoadRomDialog,
     LoadReplayDialog,
     FileDialogCancelled,
+    /// Bring the main window to the front, e.g. when another instance hands off a ROM.
+    FocusWindow,
+    /// The application became visible or hidden, e.g. switching browser tabs.
+    VisibilityChanged(bool),
     Terminate,
 }
 
@@ -269,6 +486,21 @@ impl AsRef<[u8]> for ReplayData {
     }
 }
 
+#[derive(Clone, PartialEq)]
+pub struct StateData(pub Vec<u8>);
+
+impl std::fmt::Debug for StateData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "StateData({} bytes)", self.0.len())
+    }
+}
+
+impl AsRef<[u8]> for StateData {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
 impl ApplicationHandler<NesEvent> for Nes {
     fn user_event(&mut self, event_loop: &ActiveEventLoop, event: NesEvent) {
         if self.state.is_exiting() {
@@ -317,8 +549,23 @@ impl ApplicationHandler<NesEvent> for Nes {
             _ => (),
         }
 
-        if let State::Running(state) = &mut self.state {
-            state.user_event(event_loop, event);
+        match &mut self.state {
+            State::Running(state) => {
+                for event in self.pending_events.drain(..) {
+                    state.user_event(event_loop, event);
+                }
+                state.user_event(event_loop, event);
+            }
+            // Queue events sent before startup completes, e.g. by an embedding application or
+            // another instance handing off a ROM
+            _ => {
+                if matches!(
+                    event,
+                    NesEvent::Config(_) | NesEvent::Emulation(_) | NesEvent::Ui(_)
+                ) {
+                    self.pending_events.push(event);
+                }
+            }
         }
     }
 
@@ -349,6 +596,7 @@ impl ApplicationHandler<NesEvent> for Nes {
         if let Some(window_id) = state.renderer.root_window_id() {
             state.repaint_times.insert(window_id, Instant::now());
         }
+        state.auto_pause(false);
     }
 
     fn window_event(
@@ -440,6 +688,7 @@ impl ApplicationHandler<NesEvent> for Nes {
 
 impl ApplicationHandler<NesEvent> for Running {
     fn user_event(&mut self, _event_loop: &ActiveEventLoop, mut event: NesEvent) {
+        self.hooks.on_event(&event);
         match event {
             NesEvent::Config(ref event) => {
                 let Config {
@@ -462,6 +711,9 @@ impl ApplicationHandler<NesEvent> for Running {
                         input.clear_binding(*clear_input);
                         self.input_bindings.remove(clear_input);
                     }
+                    ConfigEvent::AllowMultipleInstances(allow) => {
+                        emulation.allow_multiple_instances = *allow;
+                    }
                     ConfigEvent::AlwaysOnTop(always_on_top) => {
                         renderer.always_on_top = *always_on_top;
                         self.renderer
@@ -473,20 +725,37 @@ impl ApplicationHandler<NesEvent> for Running {
                     ConfigEvent::ApuChannelsEnabled(enabled) => {
                         deck.channels_enabled = *enabled;
                     }
+                    ConfigEvent::AttractMode(attract_mode) => {
+                        emulation.attract_mode.clone_from(attract_mode);
+                    }
                     ConfigEvent::AudioBuffer(buffer_size) => {
                         audio.buffer_size = *buffer_size;
                     }
                     ConfigEvent::AudioEnabled(enabled) => audio.enabled = *enabled,
+                    ConfigEvent::AudioFrameStep(enabled) => audio.frame_step_audio = *enabled,
                     ConfigEvent::AudioLatency(latency) => audio.latency = *latency,
+                    ConfigEvent::AudioMonitorRecording(enabled) => {
+                        audio.monitor_recording = *enabled;
+                    }
+                    ConfigEvent::AudioRecordingFormat(format) => audio.recording_format = *format,
+                    ConfigEvent::AudioSplitChannels(enabled) => audio.split_channels = *enabled,
                     ConfigEvent::AutoLoad(enabled) => emulation.auto_load = *enabled,
                     ConfigEvent::AutoSave(enabled) => emulation.auto_save = *enabled,
                     ConfigEvent::AutoSaveInterval(interval) => {
                         emulation.auto_save_interval = *interval;
                     }
+                    ConfigEvent::BackgroundReplay(enabled) => {
+                        emulation.background_replay = *enabled;
+                    }
+                    ConfigEvent::BackgroundReplayMinutes(minutes) => {
+                        emulation.background_replay_minutes = *minutes;
+                    }
+                    ConfigEvent::ColorFilter(color_filter) => deck.color_filter = *color_filter,
                     ConfigEvent::ConcurrentDpad(enabled) => deck.concurrent_dpad = *enabled,
                     ConfigEvent::CycleAccurate(enabled) => deck.cycle_accurate = *enabled,
                     ConfigEvent::DarkTheme(enabled) => renderer.dark_theme = *enabled,
                     ConfigEvent::EmbedViewports(embed) => renderer.embed_viewports = *embed,
+                    ConfigEvent::EncryptSaves(enabled) => emulation.encrypt_saves = *enabled,
                     ConfigEvent::FourPlayer(four_player) => deck.four_player = *four_player,
                     ConfigEvent::Fullscreen(fullscreen) => renderer.fullscreen = *fullscreen,
                     ConfigEvent::GamepadAssign((player, uuid)) => {
@@ -511,8 +780,13 @@ impl ApplicationHandler<NesEvent> for Running {
                     ConfigEvent::GamepadAssignments(assignments) => {
                         input.gamepad_assignments = *assignments;
                     }
+                    ConfigEvent::GamepadNavigation(enabled) => {
+                        input.gamepad_navigation = *enabled;
+                    }
                     ConfigEvent::GenieCodeAdded(genie_code) => {
-                        deck.genie_codes.push(genie_code.clone());
+                        if !deck.genie_codes.contains(genie_code) {
+                            deck.genie_codes.push(genie_code.clone());
+                        }
                     }
                     ConfigEvent::GenieCodeClear => deck.genie_codes.clear(),
                     ConfigEvent::GenieCodeRemoved(code) => {
@@ -520,24 +794,70 @@ impl ApplicationHandler<NesEvent> for Running {
                     }
                     ConfigEvent::HideOverscan(hide) => renderer.hide_overscan = *hide,
                     ConfigEvent::MapperRevisions(revs) => deck.mapper_revisions = *revs,
+                    ConfigEvent::MatchFrameRate(enabled) => {
+                        renderer.match_frame_rate = *enabled;
+                    }
+                    ConfigEvent::MousePort(port) => deck.mouse = *port,
+                    ConfigEvent::NetplayInputDelay(delay) => {
+                        emulation.netplay_input_delay = *delay;
+                    }
+                    ConfigEvent::PixelPerfect(enabled) => renderer.pixel_perfect = *enabled,
+                    ConfigEvent::RamRetention(ram_retention) => {
+                        deck.ram_retention = *ram_retention;
+                    }
                     ConfigEvent::RamState(ram_state) => deck.ram_state = *ram_state,
                     ConfigEvent::RecentRomsClear => renderer.recent_roms.clear(),
+                    ConfigEvent::ReplayAuthor(author) => {
+                        emulation.replay_author.clone_from(author);
+                    }
+                    ConfigEvent::ReplayDescription(description) => {
+                        emulation.replay_description.clone_from(description);
+                    }
                     ConfigEvent::Region(region) => deck.region = *region,
                     ConfigEvent::RewindEnabled(enabled) => emulation.rewind = *enabled,
                     ConfigEvent::RewindInterval(interval) => {
                         emulation.rewind_interval = *interval;
                     }
+                    ConfigEvent::RewindMemoryBudget(mb) => emulation.rewind_memory_budget = *mb,
                     ConfigEvent::RewindSeconds(seconds) => {
                         emulation.rewind_seconds = *seconds;
                     }
+                    ConfigEvent::RomRegion((name, region)) => match region {
+                        Some(region) => {
+                            emulation.rom_regions.insert(name.clone(), *region);
+                        }
+                        None => {
+                            emulation.rom_regions.remove(name);
+                        }
+                    },
+                    ConfigEvent::RomOverscan((name, hide)) => match hide {
+                        Some(hide) => {
+                            renderer.rom_overscan.insert(name.clone(), *hide);
+                        }
+                        None => {
+                            renderer.rom_overscan.remove(name);
+                        }
+                    },
                     ConfigEvent::RunAhead(run_ahead) => emulation.run_ahead = *run_ahead,
                     ConfigEvent::SaveSlot(slot) => emulation.save_slot = *slot,
                     ConfigEvent::Scale(scale) => renderer.scale = *scale,
-                    ConfigEvent::Shader(shader) => renderer.shader = *shader,
+                    ConfigEvent::Shader(shaders) => renderer.shader.clone_from(shaders),
+                    ConfigEvent::ShowAudioScope(show) => renderer.show_audio_scope = *show,
                     ConfigEvent::ShowMenubar(show) => renderer.show_menubar = *show,
                     ConfigEvent::ShowMessages(show) => renderer.show_messages = *show,
+                    ConfigEvent::ShowOverscanOutline(show) => {
+                        renderer.show_overscan_outline = *show;
+                    }
+                    ConfigEvent::SnapWindowSize(snap) => renderer.snap_window_size = *snap,
                     ConfigEvent::Speed(speed) => emulation.speed = *speed,
+                    ConfigEvent::Translucent(translucent) => renderer.translucent = *translucent,
+                    ConfigEvent::TvStatic(enabled) => renderer.tv_static = *enabled,
                     ConfigEvent::VideoFilter(filter) => deck.filter = *filter,
+                    ConfigEvent::VsDipSwitches(dip_switches) => {
+                        deck.vs_dip_switches = *dip_switches;
+                    }
+                    ConfigEvent::WatchFolder(dir) => emulation.watch_folder.clone_from(dir),
+                    ConfigEvent::WindowOpacity(opacity) => renderer.window_opacity = *opacity,
                     ConfigEvent::ZapperConnected(connected) => deck.zapper = *connected,
                 }
 
@@ -553,6 +873,8 @@ impl ApplicationHandler<NesEvent> for Running {
                     );
                 }
             }
+            // Emulation can change the run state itself, e.g. pausing after seeking
+            NesEvent::Emulation(EmulationEvent::RunState(mode)) => self.run_state = mode,
             NesEvent::Ui(ref event) => self.on_ui_event(event),
             _ => (),
         }
@@ -572,6 +894,18 @@ impl ApplicationHandler<NesEvent> for Running {
         window_id: WindowId,
         event: WindowEvent,
     ) {
+        let is_input = matches!(
+            event,
+            WindowEvent::KeyboardInput { .. }
+                | WindowEvent::MouseInput { .. }
+                | WindowEvent::MouseWheel { .. }
+                | WindowEvent::CursorMoved { .. }
+                | WindowEvent::Touch(_)
+        );
+        if is_input && self.attract.on_input(&self.tx) {
+            return;
+        }
+
         let res = self.renderer.on_window_event(window_id, &event);
         if res.repaint && event != WindowEvent::RedrawRequested {
             self.repaint_times.insert(window_id, Instant::now());
@@ -590,27 +924,29 @@ impl ApplicationHandler<NesEvent> for Running {
                     ) {
                         self.renderer.on_error(err);
                     }
+                    if Some(window_id) == self.renderer.root_window_id() {
+                        self.hooks.call(&HookEvent::FramePresented);
+                    }
                     self.repaint_times.remove(&window_id);
                 }
-                WindowEvent::Resized(_) => {
+                WindowEvent::Resized(size) => {
                     if Some(window_id) == self.renderer.root_window_id() {
                         self.cfg.renderer.fullscreen = self.renderer.fullscreen();
+                        if self.cfg.renderer.snap_window_size {
+                            if let Some(scale) = self.renderer.snap_window_size(&size, &self.cfg) {
+                                if scale != self.cfg.renderer.scale {
+                                    self.event(ConfigEvent::Scale(scale));
+                                }
+                            }
+                        }
                     }
                 }
                 WindowEvent::Focused(focused) => {
                     if focused {
                         self.repaint_times.insert(window_id, Instant::now());
-                        if self.renderer.rom_loaded() && self.run_state.auto_paused() {
-                            self.run_state = RunState::Running;
-                            self.event(EmulationEvent::RunState(self.run_state));
-                        }
+                        self.auto_pause(false);
                     } else {
-                        let time_since_last_save = Instant::now() - self.renderer.last_save_time;
-                        if time_since_last_save > Duration::from_secs(30) {
-                            if let Err(err) = self.renderer.save(&self.cfg) {
-                                error!("failed to save rendererer state: {err:?}");
-                            }
-                        }
+                        self.save_if_stale();
                         if self
                             .renderer
                             .window(window_id)
@@ -618,10 +954,7 @@ impl ApplicationHandler<NesEvent> for Running {
                             .unwrap_or(false)
                         {
                             self.repaint_times.remove(&window_id);
-                            if self.renderer.rom_loaded() && !self.run_state.paused() {
-                                self.run_state = RunState::Paused;
-                                self.event(EmulationEvent::RunState(self.run_state));
-                            }
+                            self.auto_pause(true);
                         }
                     }
                 }
@@ -629,16 +962,10 @@ impl ApplicationHandler<NesEvent> for Running {
                     // Note: Does not trigger on all platforms (e.g. linux)
                     if occluded {
                         self.repaint_times.remove(&window_id);
-                        if self.renderer.rom_loaded() && !self.run_state.paused() {
-                            self.run_state = RunState::Paused;
-                            self.event(EmulationEvent::RunState(self.run_state));
-                        }
+                        self.auto_pause(true);
                     } else {
                         self.repaint_times.insert(window_id, Instant::now());
-                        if self.renderer.rom_loaded() && self.run_state.auto_paused() {
-                            self.run_state = RunState::Running;
-                            self.event(EmulationEvent::RunState(self.run_state));
-                        }
+                        self.auto_pause(false);
                     }
                 }
                 WindowEvent::KeyboardInput {
@@ -713,10 +1040,19 @@ impl ApplicationHandler<NesEvent> for Running {
             }
         }
 
+        self.attract.update(
+            &self.tx,
+            &self.cfg.emulation.attract_mode,
+            self.renderer.rom_loaded(),
+        );
         self.update_repaint_times(event_loop);
     }
 
     fn suspended(&mut self, event_loop: &ActiveEventLoop) {
+        self.save_if_stale();
+        self.auto_pause(true);
+        // Windows are invalid until resumed, so don't attempt to redraw them
+        self.repaint_times.clear();
         if feature!(Suspend) {
             if let Err(err) = self.renderer.drop_window() {
                 error!("failed to suspend window: {err:?}");
@@ -802,7 +1138,7 @@ impl Running {
                 match open_file_dialog(
                     "Load Replay",
                     "Replay Recording",
-                    &["replay"],
+                    &["replay", "txt", "fm2", "bk2"],
                     Some(Config::default_data_dir()),
                 ) {
                     Ok(maybe_path) => {
@@ -822,10 +1158,85 @@ impl Running {
                     self.event(EmulationEvent::RunState(self.run_state));
                 }
             }
+            UiEvent::FocusWindow => {
+                if let Some(window) = self.renderer.root_window() {
+                    window.set_minimized(false);
+                    window.focus_window();
+                }
+            }
+            UiEvent::VisibilityChanged(visible) => {
+                if *visible {
+                    if let Some(window_id) = self.renderer.root_window_id() {
+                        self.repaint_times.insert(window_id, Instant::now());
+                    }
+                } else {
+                    self.save_if_stale();
+                    self.repaint_times.clear();
+                }
+                self.auto_pause(!visible);
+            }
             UiEvent::UpdateAvailable(_) | UiEvent::Terminate => (),
         }
     }
 
+    /// Pause emulation when the application is hidden or suspended, resuming it once restored
+    /// unless the user paused manually.
+    fn auto_pause(&mut self, paused: bool) {
+        if !self.renderer.rom_loaded() {
+            return;
+        }
+        if paused && !self.run_state.paused() {
+            self.run_state = RunState::Paused;
+        } else if !paused && self.run_state.auto_paused() && !self.renderer.failed_saves_pending() {
+            self.run_state = RunState::Running;
+        } else {
+            return;
+        }
+        self.event(EmulationEvent::RunState(self.run_state));
+    }
+
+    /// Pause and save a safety save state when the Player One gamepad disconnects mid-game so
+    /// progress isn't lost.
+    fn on_player_one_gamepad_disconnected(&mut self) {
+        if !self.renderer.rom_loaded() || self.run_state.paused() {
+            return;
+        }
+        self.gamepad_disconnect_paused = true;
+        self.run_state = RunState::ManuallyPaused;
+        self.event(EmulationEvent::RunState(self.run_state));
+        self.event(EmulationEvent::SafetySaveState);
+        self.renderer.add_message(
+            MessageType::Warn,
+            "Player One gamepad disconnected. Reconnect it to resume.",
+        );
+    }
+
+    /// Resume emulation paused by [`Self::on_player_one_gamepad_disconnected`], unless the user
+    /// has since changed the run state.
+    fn on_player_one_gamepad_reconnected(&mut self) {
+        if !std::mem::take(&mut self.gamepad_disconnect_paused) {
+            return;
+        }
+        if self.renderer.rom_loaded() && self.run_state.manually_paused() {
+            self.run_state = RunState::Running;
+            self.event(EmulationEvent::RunState(self.run_state));
+            self.renderer.add_message(
+                MessageType::Info,
+                "Player One gamepad reconnected. Resuming.",
+            );
+        }
+    }
+
+    /// Save renderer state if it hasn't been saved recently.
+    fn save_if_stale(&mut self) {
+        let time_since_last_save = Instant::now() - self.renderer.last_save_time;
+        if time_since_last_save > Duration::from_secs(30) {
+            if let Err(err) = self.renderer.save(&self.cfg) {
+                error!("failed to save rendererer state: {err:?}");
+            }
+        }
+    }
+
     /// Trigger a custom event.
     pub fn event(&mut self, event: impl Into<NesEvent>) {
         #[cfg(feature = "profiling")]
@@ -834,6 +1245,7 @@ impl Running {
         let mut event = event.into();
         trace!("Nes event: {event:?}");
 
+        self.hooks.on_event(&event);
         self.emulation.on_event(&event);
         self.renderer.on_event(&mut event, &self.cfg);
         match event {
@@ -859,6 +1271,33 @@ impl Running {
             self.gamepads.connect(event.id);
         }
 
+        if matches!(event.event, EventType::ButtonPressed(..)) && self.attract.on_input(&self.tx) {
+            return;
+        }
+
+        if self.renderer.input_test_open() {
+            self.log_raw_gamepad_event(&event);
+        }
+
+        if self.cfg.input.gamepad_navigation {
+            match event.event {
+                EventType::ButtonPressed(gilrs::Button::Mode, _) => {
+                    self.event(RendererEvent::Menu(Menu::QuickSettings));
+                    return;
+                }
+                EventType::ButtonPressed(button, _) | EventType::ButtonRepeated(button, _) => {
+                    let res = self.renderer.on_gamepad_navigation(button);
+                    if res.repaint {
+                        self.repaint_times.insert(window_id, Instant::now());
+                    }
+                    if res.consumed {
+                        return;
+                    }
+                }
+                _ => (),
+            }
+        }
+
         if let Some(uuid) = self.gamepads.gamepad_uuid(event.id) {
             match event.event {
                 EventType::ButtonPressed(button, _) => {
@@ -923,18 +1362,25 @@ impl Running {
                                 format!("Assigned gamepad `{name}` to player {player:?}."),
                             );
                             self.cfg.input.assign_gamepad(player, uuid);
+                            if player == Player::One {
+                                self.on_player_one_gamepad_reconnected();
+                            }
                         }
                     }
                 }
                 EventType::Disconnected => {
+                    let name = self.gamepads.gamepad_name_by_uuid(&uuid);
                     self.gamepads.disconnect(event.id);
                     if let Some(player) = self.cfg.input.unassign_gamepad_name(&uuid) {
-                        if let Some(name) = self.gamepads.gamepad_name_by_uuid(&uuid) {
+                        if let Some(name) = name {
                             self.renderer.add_message(
                                 MessageType::Info,
                                 format!("Unassigned gamepad `{name}` from player {player:?}."),
                             );
                         }
+                        if player == Player::One {
+                            self.on_player_one_gamepad_disconnected();
+                        }
                     }
                 }
                 _ => (),
@@ -944,6 +1390,33 @@ impl Running {
         self.renderer.prepare(&self.gamepads, &self.cfg);
     }
 
+    /// Log gamepad events that aren't passed on as inputs for the input test window, e.g. from
+    /// unassigned gamepads or axis values below the pressed threshold.
+    fn log_raw_gamepad_event(&mut self, event: &gilrs::Event) {
+        use gilrs::EventType;
+
+        let uuid = self.gamepads.gamepad_uuid(event.id);
+        let name = uuid
+            .and_then(|uuid| self.gamepads.gamepad_name_by_uuid(&uuid))
+            .unwrap_or_else(|| format!("{:?}", event.id));
+        let assigned = uuid.is_some_and(|uuid| self.cfg.input.gamepad_assignment(&uuid).is_some());
+        let (source, pressed) = match event.event {
+            EventType::ButtonPressed(button, _) if !assigned => {
+                (format!("{name}: {button:?} (Unassigned)"), Some(true))
+            }
+            EventType::ButtonReleased(button, _) if !assigned => {
+                (format!("{name}: {button:?} (Unassigned)"), Some(false))
+            }
+            EventType::AxisChanged(axis, value, _) => {
+                (format!("{name}: {axis:?} {value:.2}"), None)
+            }
+            EventType::Connected => (format!("{name}: Connected"), None),
+            EventType::Disconnected => (format!("{name}: Disconnected"), None),
+            _ => return,
+        };
+        self.renderer.log_test_input(source, pressed, None);
+    }
+
     /// Handle user input mapped to key bindings.
     pub fn on_input(
         &mut self,
@@ -955,6 +1428,14 @@ impl Running {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
 
+        if !repeat && self.renderer.input_test_open() {
+            self.renderer.log_test_input(
+                Input::fmt(input),
+                Some(state == ElementState::Pressed),
+                self.input_bindings.get(&input).copied(),
+            );
+        }
+
         if let Some(action) = self.input_bindings.get(&input).copied() {
             trace!("action: {action:?}, state: {state:?}, repeat: {repeat:?}");
             let released = state == ElementState::Released;
@@ -968,6 +1449,7 @@ impl Running {
                                 RunState::Running => RunState::ManuallyPaused,
                                 RunState::ManuallyPaused | RunState::Paused => RunState::Running,
                             };
+                            self.gamepad_disconnect_paused = false;
                             self.event(EmulationEvent::RunState(self.run_state));
                         }
                     }
@@ -1025,6 +1507,61 @@ impl Running {
                             );
                         }
                     }
+                    Feature::QuickSaveState if released => {
+                        if self.renderer.rom_loaded() {
+                            self.event(EmulationEvent::QuickSaveState(
+                                self.cfg.emulation.save_slot,
+                            ));
+                        }
+                    }
+                    Feature::QuickLoadState if released => {
+                        if self.renderer.rom_loaded() {
+                            self.event(EmulationEvent::QuickLoadState(
+                                self.cfg.emulation.save_slot,
+                            ));
+                        }
+                    }
+                    Feature::SaveCheckpoint if released => {
+                        if self.renderer.rom_loaded() {
+                            self.event(EmulationEvent::SaveCheckpoint);
+                        }
+                    }
+                    Feature::LoadPreviousCheckpoint if released => {
+                        if self.renderer.rom_loaded() {
+                            self.event(EmulationEvent::LoadPreviousCheckpoint);
+                        }
+                    }
+                    Feature::AddBookmark if released => {
+                        if self.renderer.rom_loaded() {
+                            self.event(EmulationEvent::AddBookmark);
+                        }
+                    }
+                    Feature::PersistQuickState if released => {
+                        if feature!(Storage) {
+                            if self.renderer.rom_loaded() {
+                                self.event(EmulationEvent::PersistQuickState(
+                                    self.cfg.emulation.save_slot,
+                                ));
+                            }
+                        } else {
+                            self.renderer.add_message(
+                                MessageType::Warn,
+                                "Save states are not supported yet on this platform.",
+                            );
+                        }
+                    }
+                    Feature::SaveBackgroundReplay if released => {
+                        if feature!(Filesystem) {
+                            if self.renderer.rom_loaded() {
+                                self.event(EmulationEvent::SaveBackgroundReplay);
+                            }
+                        } else {
+                            self.renderer.add_message(
+                                MessageType::Warn,
+                                "Replay recordings are not supported yet on this platform.",
+                            );
+                        }
+                    }
                     Feature::TakeScreenshot if released => {
                         if feature!(Filesystem) {
                             if self.renderer.rom_loaded() {
@@ -1037,6 +1574,43 @@ impl Running {
                             );
                         }
                     }
+                    Feature::SaveClip if released => {
+                        if feature!(Filesystem) {
+                            if self.renderer.rom_loaded() {
+                                self.event(RendererEvent::SaveClip);
+                            }
+                        } else {
+                            self.renderer.add_message(
+                                MessageType::Warn,
+                                "Clips are not supported yet on this platform.",
+                            );
+                        }
+                    }
+                    Feature::CopyFrame | Feature::CopyDisplayedFrame if released => {
+                        if feature!(CopyImage) {
+                            if self.renderer.rom_loaded() {
+                                let copy = if feature == Feature::CopyFrame {
+                                    FrameCopy::Native
+                                } else {
+                                    FrameCopy::Displayed {
+                                        hide_overscan: self.renderer.hide_overscan(),
+                                        scale: self.cfg.renderer.scale,
+                                    }
+                                };
+                                self.event(EmulationEvent::CopyFrame(copy));
+                            }
+                        } else {
+                            self.renderer.add_message(
+                                MessageType::Warn,
+                                "Copying frames is not supported yet on this platform.",
+                            );
+                        }
+                    }
+                    Feature::CopyFrameInfo if released => {
+                        if self.renderer.rom_loaded() {
+                            self.event(EmulationEvent::CopyFrameInfo);
+                        }
+                    }
                     Feature::VisualRewind => {
                         if !self.rewinding {
                             if repeat {
@@ -1070,10 +1644,68 @@ impl Running {
                         self.renderer
                             .set_always_on_top(self.cfg.renderer.always_on_top);
                     }
+                    Setting::ToggleTranslucent if released => {
+                        self.cfg.renderer.translucent = !self.cfg.renderer.translucent;
+                        self.event(ConfigEvent::Translucent(self.cfg.renderer.translucent));
+                    }
+                    Setting::ToggleClickThrough if released => {
+                        let click_through = !self.renderer.click_through();
+                        if click_through && !self.cfg.renderer.translucent {
+                            self.renderer.add_message(
+                                MessageType::Warn,
+                                "Click-Through requires a Translucent Window",
+                            );
+                        } else {
+                            self.renderer.set_click_through(click_through);
+                            let message = if click_through {
+                                format!(
+                                    "Click-Through Enabled. Press {} while the window is focused \
+                                    to disable it",
+                                    self.cfg.shortcut(Setting::ToggleClickThrough)
+                                )
+                            } else {
+                                "Click-Through Disabled".to_string()
+                            };
+                            self.renderer.add_message(MessageType::Info, message);
+                        }
+                    }
                     Setting::ToggleAudio if released => {
                         self.cfg.audio.enabled = !self.cfg.audio.enabled;
                         self.event(ConfigEvent::AudioEnabled(self.cfg.audio.enabled));
                     }
+                    Setting::ToggleOverscan if released => {
+                        self.cfg.renderer.hide_overscan = !self.cfg.renderer.hide_overscan;
+                        self.event(ConfigEvent::HideOverscan(self.cfg.renderer.hide_overscan));
+                    }
+                    Setting::ToggleRomOverscan if released => {
+                        if let Some(name) = self.renderer.loaded_rom_name() {
+                            let hide = !self.cfg.hide_overscan(Some(&name));
+                            // Clear the override when it matches the global setting
+                            let hide = (hide != self.cfg.renderer.hide_overscan).then_some(hide);
+                            match hide {
+                                Some(hide) => {
+                                    self.cfg.renderer.rom_overscan.insert(name.clone(), hide);
+                                }
+                                None => {
+                                    self.cfg.renderer.rom_overscan.remove(&name);
+                                }
+                            }
+                            self.event(ConfigEvent::RomOverscan((name, hide)));
+                        }
+                    }
+                    Setting::ToggleOverscanOutline if released => {
+                        self.cfg.renderer.show_overscan_outline =
+                            !self.cfg.renderer.show_overscan_outline;
+                        self.event(ConfigEvent::ShowOverscanOutline(
+                            self.cfg.renderer.show_overscan_outline,
+                        ));
+                    }
+                    Setting::ToggleAudioScope if released => {
+                        self.cfg.renderer.show_audio_scope = !self.cfg.renderer.show_audio_scope;
+                        self.event(ConfigEvent::ShowAudioScope(
+                            self.cfg.renderer.show_audio_scope,
+                        ));
+                    }
                     Setting::ToggleMenubar if released => {
                         self.cfg.renderer.show_menubar = !self.cfg.renderer.show_menubar;
                         self.event(RendererEvent::ShowMenubar(self.cfg.renderer.show_menubar));
@@ -1092,6 +1724,10 @@ impl Running {
                             self.event(ConfigEvent::Scale(new_scale));
                         }
                     }
+                    Setting::ResizeToScale(scale) if released => {
+                        // Sent even if the scale is unchanged to undo manual window resizing
+                        self.event(ConfigEvent::Scale(f32::from(scale)));
+                    }
                     Setting::IncrementSpeed if released => {
                         let speed = self.cfg.emulation.speed;
                         let new_speed = self.cfg.increment_speed();
@@ -1136,6 +1772,12 @@ impl Running {
                         self.run_state = RunState::Running;
                         self.event(EmulationEvent::RunState(self.run_state));
                     }
+                    DeckAction::TogglePower if released => {
+                        self.event(EmulationEvent::TogglePower);
+                    }
+                    DeckAction::HoldReset if !repeat => {
+                        self.event(EmulationEvent::ResetButton(!released));
+                    }
                     DeckAction::Joypad((player, button)) if !repeat && is_root_window => {
                         self.event(EmulationEvent::Joypad((player, button, state)));
                     }
@@ -1143,6 +1785,9 @@ impl Running {
                     DeckAction::ZapperAim(_)
                     | DeckAction::ZapperAimOffscreen
                     | DeckAction::ZapperTrigger => (),
+                    DeckAction::InsertCoin(slot) if !repeat && !released && is_root_window => {
+                        self.event(EmulationEvent::InsertCoin(slot));
+                    }
                     DeckAction::SetSaveSlot(slot) if released => {
                         if feature!(Storage) {
                             if self.cfg.emulation.save_slot != slot {
@@ -1215,16 +1860,14 @@ impl Running {
                     _ => (),
                 },
                 Action::Debug(action) => match action {
-                    Debug::Toggle(kind) if released => {
-                        if matches!(kind, DebugKind::Ppu) {
-                            self.event(RendererEvent::Menu(Menu::PpuViewer));
-                        } else {
-                            self.renderer.add_message(
-                                MessageType::Warn,
-                                format!("{kind:?} is not implemented yet"),
-                            );
-                        }
-                    }
+                    Debug::Toggle(kind) if released => match kind {
+                        DebugKind::Cpu => self.event(RendererEvent::Menu(Menu::CpuDebugger)),
+                        DebugKind::Ppu => self.event(RendererEvent::Menu(Menu::PpuViewer)),
+                        DebugKind::Apu => self.renderer.add_message(
+                            MessageType::Warn,
+                            format!("{kind:?} is not implemented yet"),
+                        ),
+                    },
                     Debug::Step(step) if (released | repeat) && is_root_window => {
                         self.event(EmulationEvent::DebugStep(step));
                     }
@@ -0,0 +1,87 @@
+//! Observer callbacks for high-level events, so optional integrations like rich presence,
+//! achievements or stream overlays can be kept in their own modules.
+//!
+//! Hooks are registered with [`Nes::add_hook`](crate::nes::Nes::add_hook) or
+//! [`NesBuilder::hook`](crate::nes::api::NesBuilder::hook) and called on the event loop thread,
+//! so they should return quickly and hand off any slow work to another thread.
+
+use crate::nes::{
+    event::{NesEvent, RendererEvent, UiEvent},
+    renderer::gui::MessageType,
+};
+use tetanes_core::control_deck::LoadedRom;
+
+/// A high-level event passed to registered hooks.
+///
+/// Like [`Command`](crate::nes::api::Command), this is `#[non_exhaustive]` so new events can be
+/// added without breaking changes.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum HookEvent {
+    /// The main window presented a frame.
+    FramePresented,
+    /// The current state was saved to a save slot.
+    StateSaved(u8),
+    /// The state from a save slot was loaded.
+    StateLoaded(u8),
+    RomLoaded(LoadedRom),
+    RomUnloaded,
+    /// A message was shown in the UI.
+    Message(MessageType, String),
+}
+
+impl HookEvent {
+    /// Returns the hook event for a [`NesEvent`], if any.
+    fn from_event(event: &NesEvent) -> Option<Self> {
+        Some(match event {
+            NesEvent::Renderer(RendererEvent::StateSaved(slot)) => Self::StateSaved(*slot),
+            NesEvent::Renderer(RendererEvent::StateLoaded(slot)) => Self::StateLoaded(*slot),
+            NesEvent::Renderer(RendererEvent::RomLoaded(rom)) => Self::RomLoaded(rom.clone()),
+            NesEvent::Renderer(RendererEvent::RomUnloaded) => Self::RomUnloaded,
+            NesEvent::Ui(UiEvent::Message((ty, msg))) => Self::Message(*ty, msg.clone()),
+            NesEvent::Ui(UiEvent::Error(err)) => Self::Message(MessageType::Error, err.clone()),
+            _ => return None,
+        })
+    }
+}
+
+/// A callback for [`HookEvent`]s.
+pub type Hook = Box<dyn FnMut(&HookEvent)>;
+
+/// Registered hooks, called in the order they were added.
+#[derive(Default)]
+#[must_use]
+pub struct Hooks {
+    hooks: Vec<Hook>,
+}
+
+impl std::fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Hooks")
+            .field("len", &self.hooks.len())
+            .finish()
+    }
+}
+
+impl Hooks {
+    pub fn add(&mut self, hook: impl FnMut(&HookEvent) + 'static) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    /// Call each hook with `event`.
+    pub fn call(&mut self, event: &HookEvent) {
+        for hook in &mut self.hooks {
+            hook(event);
+        }
+    }
+
+    /// Call each hook with the hook event for a [`NesEvent`], if any.
+    pub fn on_event(&mut self, event: &NesEvent) {
+        if self.hooks.is_empty() {
+            return;
+        }
+        if let Some(event) = HookEvent::from_event(event) {
+            self.call(&event);
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Importing saves and settings from other emulators.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+use tetanes_core::{control_deck::Config as DeckConfig, fs};
+use tracing::{info, warn};
+
+/// Maximum directory depth searched below the selected emulator directory.
+const MAX_DEPTH: usize = 2;
+
+/// Emulator to import from.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum Source {
+    #[default]
+    Fceux,
+    Nestopia,
+    Mesen,
+}
+
+impl Source {
+    pub const fn as_slice() -> &'static [Self] {
+        &[Self::Fceux, Self::Nestopia, Self::Mesen]
+    }
+
+    /// Describes where this emulator stores its files, relative to its base directory.
+    pub const fn layout(&self) -> &'static str {
+        match self {
+            Self::Fceux => "sav/, fcs/, cheats/, palettes/ and fceux.cfg",
+            Self::Nestopia => "save/, state/, cheats/ and nestopia.conf or nestopia.xml",
+            Self::Mesen => "Saves/, SaveStates/ and settings.xml or settings.json",
+        }
+    }
+
+    /// Classify a file found in this emulator's directory, or `None` if it's not relevant.
+    fn classify(&self, path: &Path) -> Option<Kind> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        let name = fs::filename(path).to_ascii_lowercase();
+        let in_cheats_dir = path
+            .parent()
+            .is_some_and(|parent| fs::filename(parent).eq_ignore_ascii_case("cheats"));
+        match (self, ext.as_str()) {
+            (_, "sav") => Some(Kind::Sram),
+            (_, "pal") => Some(Kind::Palette),
+            (Self::Fceux, "fcs") => Some(Kind::State),
+            (Self::Fceux, ext)
+                if ext.len() == 3 && ext.starts_with("fc") && ext.ends_with(char::is_numeric) =>
+            {
+                Some(Kind::State)
+            }
+            (Self::Fceux, "cht") => Some(Kind::Cheats),
+            (Self::Fceux, "cfg") if name == "fceux.cfg" => Some(Kind::Settings),
+            (Self::Nestopia, "nst") => Some(Kind::State),
+            (Self::Nestopia, "xml") if in_cheats_dir => Some(Kind::Cheats),
+            (Self::Nestopia, "conf" | "xml") if name.starts_with("nestopia") => {
+                Some(Kind::Settings)
+            }
+            (Self::Mesen, "mst" | "mss") => Some(Kind::State),
+            (Self::Mesen, "xml" | "json") if name.starts_with("settings") => Some(Kind::Settings),
+            _ => None,
+        }
+    }
+}
+
+impl AsRef<str> for Source {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Fceux => "FCEUX",
+            Self::Nestopia => "Nestopia",
+            Self::Mesen => "Mesen",
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_ref())
+    }
+}
+
+/// Kind of file found while importing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum Kind {
+    Sram,
+    State,
+    Cheats,
+    Palette,
+    Settings,
+}
+
+impl AsRef<str> for Kind {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Sram => "Battery Save",
+            Self::State => "Save State",
+            Self::Cheats => "Cheat List",
+            Self::Palette => "Palette",
+            Self::Settings => "Settings",
+        }
+    }
+}
+
+/// Outcome of importing a single file.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct Entry {
+    pub path: PathBuf,
+    pub kind: Kind,
+    /// `Ok` with the imported destination, or `Err` with why it couldn't be imported.
+    pub result: Result<PathBuf, String>,
+}
+
+/// Summary of an import, listing what was and wasn't imported.
+#[derive(Default, Debug, Clone)]
+#[must_use]
+pub struct Report {
+    pub entries: Vec<Entry>,
+}
+
+impl Report {
+    pub fn imported(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter().filter(|entry| entry.result.is_ok())
+    }
+
+    pub fn skipped(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter().filter(|entry| entry.result.is_err())
+    }
+}
+
+/// Import files from another emulator's directory into the `TetaNES` data directories.
+///
+/// Battery-backed saves are converted, while files `TetaNES` has no equivalent for are reported
+/// as skipped. Existing `TetaNES` saves are never overwritten.
+pub fn import(source: Source, dir: &Path, cfg: &DeckConfig) -> Report {
+    info!("importing {source} files from {dir:?}");
+
+    let mut paths = Vec::new();
+    collect_files(dir, 0, &mut paths);
+    paths.sort();
+
+    let sram_dir = cfg.sram_dir();
+    let entries = paths
+        .into_iter()
+        .filter_map(|path| {
+            let kind = source.classify(&path)?;
+            let result = match kind {
+                Kind::Sram => import_sram(&path, &sram_dir),
+                Kind::State => Err(format!("{source} save states use an incompatible format")),
+                Kind::Cheats => {
+                    Err("open cheat lists from Game Genie Codes > Cheat Files".to_string())
+                }
+                Kind::Palette => Err("custom palettes aren't supported".to_string()),
+                Kind::Settings => Err(format!("{source} settings have no TetaNES equivalent")),
+            };
+            if let Err(err) = &result {
+                warn!("skipped importing {path:?}: {err}");
+            }
+            Some(Entry { path, kind, result })
+        })
+        .collect();
+
+    Report { entries }
+}
+
+fn collect_files(dir: &Path, depth: usize, paths: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.filter_map(Result::ok).map(|entry| entry.path()) {
+        if path.is_dir() {
+            if depth < MAX_DEPTH {
+                collect_files(&path, depth + 1, paths);
+            }
+        } else {
+            paths.push(path);
+        }
+    }
+}
+
+/// Battery-backed saves from other emulators are raw PRG-RAM dumps named after the ROM, which
+/// matches the `TetaNES` SRAM contents once wrapped in its save format.
+fn import_sram(path: &Path, sram_dir: &Path) -> Result<PathBuf, String> {
+    // Mirrors `ControlDeck::sram_dir` so the ROM name, not the `.sav` extension, is kept.
+    let dest = sram_dir
+        .join(fs::filename(path))
+        .with_extension(DeckConfig::SRAM_EXTENSION);
+    if fs::exists(&dest) {
+        return Err("a TetaNES save already exists for this game".to_string());
+    }
+    let data = fs::load_raw(path).map_err(|err| format!("failed to read: {err}"))?;
+    if data.is_empty() {
+        return Err("save is empty".to_string());
+    }
+    fs::save(&dest, &data).map_err(|err| format!("failed to save: {err}"))?;
+    info!("imported {path:?} to {dest:?}");
+    Ok(dest)
+}
@@ -305,6 +305,7 @@ impl ActionBindings {
             .collect::<BTreeMap<_, _>>();
 
         bindings.extend(shortcut_map!(
+            { Debug::Step(DebugStep::Back) => :SHIFT, KeyC },
             { Debug::Step(DebugStep::Frame) => :SHIFT, KeyF },
             { Debug::Step(DebugStep::Into) => KeyC },
             { Debug::Step(DebugStep::Out) => :SHIFT, KeyO },
@@ -313,6 +314,8 @@ impl ActionBindings {
             { Debug::Toggle(DebugKind::Apu) => :SHIFT, KeyA },
             { Debug::Toggle(DebugKind::Cpu) => :SHIFT, KeyD },
             { Debug::Toggle(DebugKind::Ppu) => :SHIFT, KeyP },
+            { DeckAction::InsertCoin(0) => Digit5 },
+            { DeckAction::InsertCoin(1) => Digit6 },
             { DeckAction::LoadState => :CONTROL, KeyL },
             { DeckAction::Reset(ResetKind::Hard) => :CONTROL, KeyH },
             { DeckAction::Reset(ResetKind::Soft) => :CONTROL, KeyR },
@@ -333,22 +336,35 @@ impl ActionBindings {
             { DeckAction::ToggleApuChannel(Channel::Pulse2) => :SHIFT, Digit2 },
             { DeckAction::ToggleApuChannel(Channel::Triangle) => :SHIFT, Digit3 },
             { Feature::InstantRewind => KeyR },
+            { Feature::LoadPreviousCheckpoint => F7 },
+            { Feature::PersistQuickState => :SHIFT, F5 },
+            { Feature::QuickLoadState => F8 },
+            { Feature::QuickSaveState => F5 },
+            { Feature::SaveCheckpoint => F6 },
             { Feature::TakeScreenshot => F10 },
+            { Feature::SaveClip => F11 },
+            { Feature::CopyFrame => :SHIFT, F10 },
+            { Feature::CopyDisplayedFrame => :CONTROL, F10 },
+            { Feature::AddBookmark => F9 },
             { Feature::ToggleAudioRecording => :SHIFT, KeyR },
             { Feature::ToggleReplayRecording => :SHIFT, KeyV },
             { Feature::VisualRewind => KeyR },
             { Menu::About => F1 },
+            { Menu::Bookmarks => :SHIFT, F9 },
             { Menu::Keybinds => :CONTROL, KeyK; F3 },
             { Menu::Preferences => :CONTROL, KeyP; F2 },
             { Menu::PerfStats => :CONTROL, KeyF },
+            { Menu::QuickSettings => F4 },
             { Setting::DecrementScale => :SHIFT, Minus },
             { Setting::DecrementSpeed => Minus },
             { Setting::FastForward => Space },
             { Setting::IncrementScale => :SHIFT, Equal },
             { Setting::IncrementSpeed => Equal },
             { Setting::ToggleAudio => :CONTROL, KeyM },
+            { Setting::ToggleClickThrough => :SHIFT, KeyT },
             { Setting::ToggleFullscreen => :CONTROL, Enter },
             { Setting::ToggleMenubar => :CONTROL, KeyE },
+            { Setting::ToggleTranslucent => :CONTROL, KeyT },
             { Ui::LoadRom => :CONTROL, KeyO; F3 },
             { Ui::Quit => :CONTROL, KeyQ },
             { Ui::TogglePause => Escape },
@@ -574,7 +590,8 @@ impl Gamepads {
     }
 
     pub fn gamepad_uuid(&self, id: gilrs::GamepadId) -> Option<Uuid> {
-        self.connected_gamepad(id).map(|g| Self::create_uuid(&g))
+        // Gamepads are no longer connected by the time a disconnect event is received
+        self.connected.get(&id).copied()
     }
 
     pub fn is_connected(&self, uuid: &Uuid) -> bool {
@@ -0,0 +1,134 @@
+//! Single-instance detection, handing off ROMs opened while `TetaNES` is already running.
+//!
+//! The running instance listens on a local TCP port recorded in a lock file. Subsequent instances
+//! connect to it, forward the ROM path they were opened with and exit.
+
+use crate::nes::{
+    config::Config,
+    event::{EmulationEvent, NesEventProxy, UiEvent},
+};
+use anyhow::{bail, Context};
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tracing::{debug, error};
+
+/// Lock file recording the port the running instance listens on.
+const LOCK_FILENAME: &str = "instance.lock";
+/// Identifies handoff connections, since a stale port may have been reused by another process.
+const HANDOFF_MAGIC: &str = "TETANES_HANDOFF";
+const HANDOFF_ACK: &str = "OK";
+const HANDOFF_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// The single running instance.
+#[derive(Debug)]
+#[must_use]
+pub struct Instance {
+    listener: TcpListener,
+}
+
+impl Instance {
+    /// Acquire the single-instance lock. If another instance is already running, `rom` is
+    /// forwarded to it and `None` is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lock file can't be written or the listener can't be bound.
+    pub fn acquire(rom: Option<&Path>) -> anyhow::Result<Option<Self>> {
+        let lock_path = Self::lock_path();
+        if let Some(port) = fs::read_to_string(&lock_path)
+            .ok()
+            .and_then(|port| port.trim().parse::<u16>().ok())
+        {
+            match Self::handoff(port, rom) {
+                Ok(()) => {
+                    debug!("handed off to running instance on port {port}");
+                    return Ok(None);
+                }
+                Err(err) => debug!("stale instance lock: {err:?}"),
+            }
+        }
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .context("failed to bind instance listener")?;
+        let port = listener.local_addr()?.port();
+        if let Some(dir) = lock_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&lock_path, port.to_string())
+            .with_context(|| format!("failed to write instance lock: {lock_path:?}"))?;
+        debug!("acquired instance lock on port {port}");
+
+        Ok(Some(Self { listener }))
+    }
+
+    /// Listen for ROMs forwarded from other instances in a background thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listener thread fails to spawn.
+    pub fn listen(self, tx: NesEventProxy) -> anyhow::Result<()> {
+        std::thread::Builder::new()
+            .name("instance".into())
+            .spawn(move || {
+                for stream in self.listener.incoming() {
+                    match stream.map_err(anyhow::Error::from).and_then(Self::receive) {
+                        Ok(rom) => {
+                            tx.event(UiEvent::FocusWindow);
+                            if let Some(rom) = rom {
+                                tx.event(EmulationEvent::load_path(rom));
+                            }
+                        }
+                        Err(err) => error!("failed to receive instance handoff: {err:?}"),
+                    }
+                }
+            })?;
+        Ok(())
+    }
+
+    fn lock_path() -> PathBuf {
+        Config::default_data_dir().join(LOCK_FILENAME)
+    }
+
+    /// Forward `rom` to the instance listening on `port`, waiting for it to acknowledge.
+    fn handoff(port: u16, rom: Option<&Path>) -> anyhow::Result<()> {
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+        let mut stream = TcpStream::connect_timeout(&addr, HANDOFF_TIMEOUT)?;
+        stream.set_read_timeout(Some(HANDOFF_TIMEOUT))?;
+
+        let rom = rom
+            .and_then(|rom| rom.canonicalize().ok())
+            .map(|rom| rom.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        writeln!(stream, "{HANDOFF_MAGIC}\n{rom}")?;
+
+        let mut ack = String::new();
+        BufReader::new(stream).read_line(&mut ack)?;
+        if ack.trim() != HANDOFF_ACK {
+            bail!("unexpected handoff response: {ack:?}");
+        }
+        Ok(())
+    }
+
+    /// Receive a forwarded ROM path, if any, from another instance.
+    fn receive(stream: TcpStream) -> anyhow::Result<Option<PathBuf>> {
+        stream.set_read_timeout(Some(HANDOFF_TIMEOUT))?;
+        let mut reader = BufReader::new(&stream);
+
+        let mut magic = String::new();
+        reader.read_line(&mut magic)?;
+        if magic.trim() != HANDOFF_MAGIC {
+            bail!("invalid handoff: {magic:?}");
+        }
+        let mut rom = String::new();
+        reader.read_line(&mut rom)?;
+        writeln!(&stream, "{HANDOFF_ACK}")?;
+
+        let rom = rom.trim_end_matches(['\r', '\n']);
+        Ok((!rom.is_empty()).then(|| PathBuf::from(rom)))
+    }
+}
@@ -0,0 +1,597 @@
+//! Peer-to-peer netplay using delay-based lockstep over UDP.
+//!
+//! The host plays normally until a peer joins, then sends its current state, including
+//! battery-backed Save RAM, to the joining peer. From then on both peers clock the same frames,
+//! each only advancing once the other's input for the frame has arrived. Local input is scheduled
+//! a configurable number of frames ahead to hide network latency. The host is player one and the
+//! joining peer is player two.
+//!
+//! Inputs are resent until acknowledged, so lost packets only cause a brief stall. State hashes
+//! are exchanged periodically to detect desyncs.
+
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    io,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+};
+use tetanes_core::{
+    input::{Joypad, JoypadBtn, Player},
+    time::{Duration, Instant},
+};
+use tracing::{debug, warn};
+
+/// Default UDP port to host on.
+pub const DEFAULT_PORT: u16 = 7845;
+/// Bumped whenever [`Message`] changes so mismatched versions are rejected.
+const PROTOCOL_VERSION: u32 = 1;
+/// Size of the state chunks sent to a joining peer, small enough to avoid IP fragmentation on
+/// most networks.
+const CHUNK_SIZE: usize = 1024;
+/// Largest state accepted from a host, so a bogus chunk count can't exhaust memory.
+const MAX_STATE_SIZE: usize = 16 * 1024 * 1024;
+/// Maximum state chunks requested at a time.
+const MAX_CHUNK_REQUESTS: usize = 16;
+const MAX_PACKET_SIZE: usize = 4 * 1024;
+/// Maximum inputs sent per packet, limiting how far behind a peer can fall before stalling.
+const MAX_INPUTS: usize = 64;
+/// Minimum time between sending packets, to avoid flooding the peer while waiting on input.
+const SEND_INTERVAL: Duration = Duration::from_millis(4);
+/// Time between resending unanswered connection packets.
+const RESEND_INTERVAL: Duration = Duration::from_millis(250);
+/// Time between requesting missing state chunks.
+const REQUEST_INTERVAL: Duration = Duration::from_millis(50);
+/// Time without hearing from the peer before disconnecting.
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// Number of frames between exchanged state hashes.
+const HASH_INTERVAL: u32 = 60;
+
+#[derive(Debug, Serialize, Deserialize)]
+enum Message {
+    /// Sent by a joining peer with the checksum of its loaded ROM.
+    Hello {
+        version: u32,
+        crc32: u32,
+    },
+    Reject(String),
+    /// Sent by the host with the number of state chunks to request.
+    Welcome {
+        chunks: u32,
+    },
+    RequestChunks(Vec<u32>),
+    Chunk {
+        index: u32,
+        data: Vec<u8>,
+    },
+    /// Local inputs starting at frame `start`, along with the next frame of input needed from the
+    /// peer and the latest state hash.
+    Inputs {
+        ack: u32,
+        start: u32,
+        buttons: Vec<u16>,
+        hash: Option<(u32, u32)>,
+    },
+    Disconnect,
+}
+
+/// Connection status shown in the netplay window.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum NetplayStatus {
+    #[default]
+    Disconnected,
+    Hosting {
+        port: u16,
+    },
+    Joining {
+        addr: String,
+    },
+    /// Transferring the host state to the joining peer.
+    Syncing {
+        received: u32,
+        total: u32,
+    },
+    Connected {
+        peer: String,
+        player: Player,
+    },
+}
+
+/// Events returned by [`Netplay::poll`] for emulation to handle.
+#[derive(Debug)]
+#[must_use]
+pub enum NetplayEvent {
+    /// A peer with a ROM matching `crc32` wants to join. The host should reply with
+    /// [`Netplay::accept`] or [`Netplay::reject`].
+    Join {
+        crc32: u32,
+    },
+    /// The host state was received and should be loaded, followed by [`Netplay::start`].
+    State(Vec<u8>),
+    Connected,
+    Desync {
+        frame: u32,
+    },
+    Disconnected(String),
+}
+
+#[derive(Debug)]
+enum Phase {
+    Listening,
+    Joining {
+        crc32: u32,
+    },
+    SendingState {
+        chunks: Vec<Vec<u8>>,
+    },
+    ReceivingState {
+        chunks: Vec<Option<Vec<u8>>>,
+    },
+    /// Waiting for emulation to load the received state or start once the peer has.
+    Loading,
+    Running,
+}
+
+/// A netplay session with a single peer.
+#[derive(Debug)]
+#[must_use]
+pub struct Netplay {
+    socket: UdpSocket,
+    host: bool,
+    peer: Option<SocketAddr>,
+    phase: Phase,
+    input_delay: u32,
+    local: Joypad,
+    local_inputs: BTreeMap<u32, u16>,
+    remote_inputs: BTreeMap<u32, u16>,
+    /// Next frame to clock.
+    frame: u32,
+    /// Next frame local input hasn't been scheduled for.
+    next_local_frame: u32,
+    /// Next frame the peer needs local input for.
+    peer_ack: u32,
+    /// Next frame remote input is needed for.
+    remote_next: u32,
+    local_hash: Option<(u32, u32)>,
+    remote_hash: Option<(u32, u32)>,
+    desynced: bool,
+    last_received: Instant,
+    last_sent: Instant,
+}
+
+impl Netplay {
+    /// Host a session on `port`, waiting for a peer to join.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the port can't be bound.
+    pub fn host(port: u16, input_delay: u32) -> anyhow::Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", port))
+            .with_context(|| format!("failed to host netplay on port {port}"))?;
+        Self::new(socket, true, None, Phase::Listening, input_delay)
+    }
+
+    /// Join a session hosted at `addr`, e.g. `192.168.1.2:7845`, with the checksum of the loaded
+    /// ROM. The port defaults to [`DEFAULT_PORT`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `addr` can't be resolved or a socket can't be bound.
+    pub fn join(addr: &str, crc32: u32, input_delay: u32) -> anyhow::Result<Self> {
+        let addr = addr.trim();
+        let peer = addr
+            .to_socket_addrs()
+            .or_else(|_| (addr, DEFAULT_PORT).to_socket_addrs())
+            .ok()
+            .and_then(|mut addrs| addrs.next())
+            .ok_or_else(|| anyhow!("invalid netplay address: {addr:?}"))?;
+        let socket = UdpSocket::bind(("0.0.0.0", 0)).context("failed to bind netplay socket")?;
+        let mut netplay = Self::new(
+            socket,
+            false,
+            Some(peer),
+            Phase::Joining { crc32 },
+            input_delay,
+        )?;
+        netplay.send(&Message::Hello {
+            version: PROTOCOL_VERSION,
+            crc32,
+        });
+        Ok(netplay)
+    }
+
+    fn new(
+        socket: UdpSocket,
+        host: bool,
+        peer: Option<SocketAddr>,
+        phase: Phase,
+        input_delay: u32,
+    ) -> anyhow::Result<Self> {
+        socket
+            .set_nonblocking(true)
+            .context("failed to configure netplay socket")?;
+        Ok(Self {
+            socket,
+            host,
+            peer,
+            phase,
+            input_delay,
+            local: Joypad::new(),
+            local_inputs: BTreeMap::new(),
+            remote_inputs: BTreeMap::new(),
+            frame: 0,
+            next_local_frame: 0,
+            peer_ack: 0,
+            remote_next: 0,
+            local_hash: None,
+            remote_hash: None,
+            desynced: false,
+            last_received: Instant::now(),
+            last_sent: Instant::now(),
+        })
+    }
+
+    pub fn status(&self) -> NetplayStatus {
+        let peer = || self.peer.map(|peer| peer.to_string()).unwrap_or_default();
+        match &self.phase {
+            Phase::Listening => NetplayStatus::Hosting {
+                port: self
+                    .socket
+                    .local_addr()
+                    .map_or(DEFAULT_PORT, |addr| addr.port()),
+            },
+            Phase::Joining { .. } => NetplayStatus::Joining { addr: peer() },
+            Phase::SendingState { chunks } => NetplayStatus::Syncing {
+                received: 0,
+                total: chunks.len() as u32,
+            },
+            Phase::ReceivingState { chunks } => NetplayStatus::Syncing {
+                received: chunks.iter().filter(|chunk| chunk.is_some()).count() as u32,
+                total: chunks.len() as u32,
+            },
+            Phase::Loading => NetplayStatus::Syncing {
+                received: 1,
+                total: 1,
+            },
+            Phase::Running => NetplayStatus::Connected {
+                peer: peer(),
+                player: self.local_player(),
+            },
+        }
+    }
+
+    /// Whether a host is still waiting for a peer to join, and can play normally meanwhile.
+    pub const fn is_listening(&self) -> bool {
+        matches!(self.phase, Phase::Listening)
+    }
+
+    pub const fn local_player(&self) -> Player {
+        if self.host {
+            Player::One
+        } else {
+            Player::Two
+        }
+    }
+
+    pub const fn remote_player(&self) -> Player {
+        if self.host {
+            Player::Two
+        } else {
+            Player::One
+        }
+    }
+
+    /// Set the number of frames local input is delayed by. Higher values hide more latency.
+    pub fn set_input_delay(&mut self, input_delay: u32) {
+        self.input_delay = input_delay;
+    }
+
+    /// Update the local controller.
+    pub fn set_button(&mut self, button: JoypadBtn, pressed: bool) {
+        self.local.set_button(button, pressed);
+    }
+
+    /// Accept a joining peer, sending it the serialized host `state`.
+    pub fn accept(&mut self, state: &[u8]) {
+        let chunks = state
+            .chunks(CHUNK_SIZE)
+            .map(<[u8]>::to_vec)
+            .collect::<Vec<_>>();
+        self.send(&Message::Welcome {
+            chunks: chunks.len() as u32,
+        });
+        self.phase = Phase::SendingState { chunks };
+    }
+
+    /// Reject a joining peer with a `reason` and keep waiting for another.
+    pub fn reject(&mut self, reason: impl Into<String>) {
+        self.send(&Message::Reject(reason.into()));
+        self.peer = None;
+        self.phase = Phase::Listening;
+    }
+
+    /// Start lockstep from `frame` once both peers have the same state, with `local` as the
+    /// current local controller.
+    pub fn start(&mut self, frame: u32, local: Joypad) {
+        self.phase = Phase::Running;
+        self.local = local;
+        self.local_inputs.clear();
+        self.remote_inputs.clear();
+        self.frame = frame;
+        self.next_local_frame = frame;
+        self.peer_ack = frame;
+        self.remote_next = frame;
+        self.local_hash = None;
+        self.remote_hash = None;
+        self.desynced = false;
+        self.last_received = Instant::now();
+    }
+
+    /// Returns the buttons held by each player for `frame`, or `None` if still waiting on the
+    /// peer.
+    pub fn frame_inputs(&mut self, frame: u32) -> Option<[(Player, u16); 2]> {
+        if !matches!(self.phase, Phase::Running) {
+            return None;
+        }
+        let scheduled = frame + self.input_delay;
+        while self.next_local_frame <= scheduled {
+            self.local_inputs
+                .insert(self.next_local_frame, self.local.buttons.bits());
+            self.next_local_frame += 1;
+        }
+        let local = *self.local_inputs.get(&frame)?;
+        let remote = *self.remote_inputs.get(&frame)?;
+        Some([(self.local_player(), local), (self.remote_player(), remote)])
+    }
+
+    /// Record that `frame` was clocked, resulting in the state `hash`.
+    pub fn advance(&mut self, frame: u32, hash: u32) -> Option<NetplayEvent> {
+        self.remote_inputs.retain(|&input, _| input > frame);
+        let next = frame + 1;
+        self.frame = next;
+        self.discard_acked_inputs();
+        if next % HASH_INTERVAL == 0 {
+            self.local_hash = Some((next, hash));
+        }
+        self.check_desync()
+    }
+
+    /// Send and receive pending packets, returning any events to handle.
+    pub fn poll(&mut self) -> Vec<NetplayEvent> {
+        let mut events = Vec::new();
+        let mut buf = [0; MAX_PACKET_SIZE];
+        loop {
+            match self.socket.recv_from(&mut buf) {
+                Ok((len, addr)) => match bincode::deserialize::<Message>(&buf[..len]) {
+                    Ok(message) => self.on_message(addr, message, &mut events),
+                    Err(err) => debug!("invalid netplay packet from {addr}: {err:?}"),
+                },
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                // Windows reports ICMP port unreachable from an earlier send as a receive error
+                Err(err) if err.kind() == io::ErrorKind::ConnectionReset => continue,
+                Err(err) => {
+                    events.push(NetplayEvent::Disconnected(err.to_string()));
+                    return events;
+                }
+            }
+        }
+
+        if !self.is_listening() && self.last_received.elapsed() > TIMEOUT {
+            events.push(NetplayEvent::Disconnected("connection timed out".into()));
+            return events;
+        }
+        self.send_pending();
+        events
+    }
+
+    /// Notify the peer the session ended.
+    pub fn disconnect(&mut self) {
+        self.send(&Message::Disconnect);
+    }
+
+    fn on_message(&mut self, addr: SocketAddr, message: Message, events: &mut Vec<NetplayEvent>) {
+        if let Message::Hello { version, crc32 } = message {
+            if !self.host {
+                return;
+            }
+            if self.peer.is_some_and(|peer| peer != addr) {
+                let reject = Message::Reject("a game is already in progress".into());
+                self.send_to(addr, &reject);
+                return;
+            }
+            if version != PROTOCOL_VERSION {
+                self.send_to(
+                    addr,
+                    &Message::Reject("incompatible TetaNES version".into()),
+                );
+                return;
+            }
+            self.last_received = Instant::now();
+            match &self.phase {
+                Phase::Listening => {
+                    self.peer = Some(addr);
+                    events.push(NetplayEvent::Join { crc32 });
+                }
+                // The Welcome was lost
+                Phase::SendingState { chunks } => {
+                    let chunks = chunks.len() as u32;
+                    self.send(&Message::Welcome { chunks });
+                }
+                _ => (),
+            }
+            return;
+        }
+        if self.peer != Some(addr) {
+            return;
+        }
+        self.last_received = Instant::now();
+
+        match message {
+            Message::Hello { .. } => (),
+            Message::Reject(reason) => events.push(NetplayEvent::Disconnected(reason)),
+            Message::Welcome { chunks } => {
+                if matches!(self.phase, Phase::Joining { .. }) {
+                    if chunks as usize > MAX_STATE_SIZE / CHUNK_SIZE {
+                        self.disconnect();
+                        return events.push(NetplayEvent::Disconnected(format!(
+                            "host state of {chunks} chunks exceeds the maximum size"
+                        )));
+                    }
+                    self.phase = Phase::ReceivingState {
+                        chunks: vec![None; chunks as usize],
+                    };
+                    self.request_chunks();
+                }
+            }
+            Message::RequestChunks(indexes) => {
+                if let Phase::SendingState { chunks } = &self.phase {
+                    let responses = indexes
+                        .into_iter()
+                        .filter_map(|index| {
+                            let data = chunks.get(index as usize)?.clone();
+                            Some(Message::Chunk { index, data })
+                        })
+                        .collect::<Vec<_>>();
+                    for response in &responses {
+                        self.send(response);
+                    }
+                }
+            }
+            Message::Chunk { index, data } => {
+                if let Phase::ReceivingState { chunks } = &mut self.phase {
+                    if let Some(chunk) = chunks.get_mut(index as usize) {
+                        *chunk = Some(data);
+                    }
+                    if chunks.iter().all(Option::is_some) {
+                        let state = chunks.drain(..).flatten().flatten().collect();
+                        self.phase = Phase::Loading;
+                        events.push(NetplayEvent::State(state));
+                    }
+                }
+            }
+            Message::Inputs {
+                ack,
+                start,
+                buttons,
+                hash,
+            } => {
+                // The first inputs from a joining peer mean it loaded the state
+                if matches!(self.phase, Phase::SendingState { .. }) {
+                    self.phase = Phase::Loading;
+                    return events.push(NetplayEvent::Connected);
+                }
+                if !matches!(self.phase, Phase::Running) {
+                    return;
+                }
+                self.peer_ack = self.peer_ack.max(ack);
+                self.discard_acked_inputs();
+                for (frame, buttons) in (start..).zip(buttons) {
+                    if frame >= self.remote_next {
+                        self.remote_inputs.insert(frame, buttons);
+                    }
+                }
+                while self.remote_inputs.contains_key(&self.remote_next) {
+                    self.remote_next += 1;
+                }
+                if hash.is_some() {
+                    self.remote_hash = hash;
+                }
+                if let Some(event) = self.check_desync() {
+                    events.push(event);
+                }
+            }
+            Message::Disconnect => {
+                events.push(NetplayEvent::Disconnected("peer disconnected".into()));
+            }
+        }
+    }
+
+    /// Discard local inputs both peers are done with. The peer may be ahead of the local frame.
+    fn discard_acked_inputs(&mut self) {
+        let oldest = self.peer_ack.min(self.frame);
+        self.local_inputs.retain(|&frame, _| frame >= oldest);
+    }
+
+    fn check_desync(&mut self) -> Option<NetplayEvent> {
+        let (Some((local_frame, local)), Some((remote_frame, remote))) =
+            (self.local_hash, self.remote_hash)
+        else {
+            return None;
+        };
+        if local_frame != remote_frame || self.desynced {
+            return None;
+        }
+        (local != remote).then(|| {
+            self.desynced = true;
+            warn!("netplay desynced at frame {local_frame}: {local:08X} != {remote:08X}");
+            NetplayEvent::Desync { frame: local_frame }
+        })
+    }
+
+    fn send_pending(&mut self) {
+        match &self.phase {
+            Phase::Joining { crc32 } => {
+                if self.last_sent.elapsed() > RESEND_INTERVAL {
+                    let crc32 = *crc32;
+                    self.send(&Message::Hello {
+                        version: PROTOCOL_VERSION,
+                        crc32,
+                    });
+                }
+            }
+            Phase::ReceivingState { .. } => {
+                if self.last_sent.elapsed() > REQUEST_INTERVAL {
+                    self.request_chunks();
+                }
+            }
+            Phase::Running => {
+                if self.last_sent.elapsed() > SEND_INTERVAL {
+                    let buttons = self
+                        .local_inputs
+                        .range(self.peer_ack..)
+                        .map(|(_, &buttons)| buttons)
+                        .take(MAX_INPUTS)
+                        .collect();
+                    self.send(&Message::Inputs {
+                        ack: self.remote_next,
+                        start: self.peer_ack,
+                        buttons,
+                        hash: self.local_hash,
+                    });
+                }
+            }
+            Phase::Listening | Phase::SendingState { .. } | Phase::Loading => (),
+        }
+    }
+
+    fn request_chunks(&mut self) {
+        if let Phase::ReceivingState { chunks } = &self.phase {
+            let missing = (0..)
+                .zip(chunks)
+                .filter(|(_, chunk)| chunk.is_none())
+                .map(|(index, _)| index)
+                .take(MAX_CHUNK_REQUESTS)
+                .collect();
+            self.send(&Message::RequestChunks(missing));
+        }
+    }
+
+    fn send(&mut self, message: &Message) {
+        if let Some(peer) = self.peer {
+            self.send_to(peer, message);
+            self.last_sent = Instant::now();
+        }
+    }
+
+    fn send_to(&self, addr: SocketAddr, message: &Message) {
+        match bincode::serialize(message) {
+            Ok(packet) => {
+                if let Err(err) = self.socket.send_to(&packet, addr) {
+                    debug!("failed to send netplay packet to {addr}: {err:?}");
+                }
+            }
+            Err(err) => warn!("failed to serialize netplay packet: {err:?}"),
+        }
+    }
+}
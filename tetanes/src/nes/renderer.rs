@@ -1,12 +1,15 @@
 use crate::{
     feature,
     nes::{
-        config::Config,
+        action::Action,
+        config::{Config, FrameRate},
         event::{EmulationEvent, NesEvent, NesEventProxy, RendererEvent, UiEvent},
         input::Gamepads,
         renderer::{
+            clip::ClipBuffer,
             clipboard::Clipboard,
             event::translate_cursor,
+            frame_pacing::FramePacer,
             gui::{Gui, MessageType},
             painter::Painter,
         },
@@ -38,11 +41,13 @@ use tracing::{debug, error, info, trace};
 use winit::{
     dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
     event_loop::ActiveEventLoop,
-    window::{CursorGrabMode, Theme, Window, WindowButtons, WindowId},
+    window::{CursorGrabMode, Fullscreen, Theme, Window, WindowButtons, WindowId},
 };
 
+pub mod clip;
 pub mod clipboard;
 pub mod event;
+pub mod frame_pacing;
 pub mod gui;
 pub mod painter;
 pub mod shader;
@@ -132,6 +137,11 @@ pub struct Renderer {
     pub(crate) last_save_time: Instant,
     zoom_changed: bool,
     resize_texture: bool,
+    frame_pacer: FramePacer,
+    /// Whether mouse input passes through the root window to the windows underneath it.
+    click_through: bool,
+    /// Recent frames to save as a clip.
+    clip_buffer: ClipBuffer,
 }
 
 impl std::fmt::Debug for Renderer {
@@ -148,6 +158,9 @@ impl std::fmt::Debug for Renderer {
             .field("last_save_time", &self.last_save_time)
             .field("zoom_changed", &self.zoom_changed)
             .field("resize_texture", &self.resize_texture)
+            .field("frame_pacer", &self.frame_pacer)
+            .field("click_through", &self.click_through)
+            .field("clip_buffer", &self.clip_buffer)
             .finish_non_exhaustive()
     }
 }
@@ -221,7 +234,10 @@ impl Renderer {
         Viewport::update_info(&mut viewport.info, &ctx, &window);
         viewports.insert(viewport.ids.this, viewport);
 
-        painter.set_shader(cfg.renderer.shader);
+        painter.set_shaders(&cfg.renderer.shader);
+        if !painter.set_translucent(cfg.renderer.translucent) {
+            tracing::warn!("translucent windows are not supported on this platform");
+        }
         let render_state = painter.render_state_mut();
         let Some(render_state) = render_state else {
             anyhow::bail!("painter state is not initialized yet");
@@ -249,6 +265,10 @@ impl Renderer {
             start_time: Instant::now(),
         };
 
+        let mut frame_pacer = FramePacer::new();
+        frame_pacer.set_enabled(cfg.renderer.match_frame_rate);
+        frame_pacer.set_refresh_rate(&window);
+
         Ok(Self {
             state: Rc::new(RefCell::new(state)),
             painter: Rc::new(RefCell::new(painter)),
@@ -263,6 +283,9 @@ impl Renderer {
             last_save_time: Instant::now(),
             zoom_changed: false,
             resize_texture: false,
+            frame_pacer,
+            click_through: false,
+            clip_buffer: ClipBuffer::new(cfg.renderer.clip_seconds),
         })
     }
 
@@ -327,7 +350,7 @@ impl Renderer {
     pub fn window_size_for_scale(&self, cfg: &Config, scale: f32) -> Vec2 {
         let gui = self.gui.borrow();
         let aspect_ratio = gui.aspect_ratio();
-        let mut window_size = cfg.window_size_for_scale(aspect_ratio, scale);
+        let mut window_size = cfg.window_size_for_scale(aspect_ratio, gui.hide_overscan(), scale);
         window_size.y += gui.menu_height;
         window_size
     }
@@ -363,10 +386,45 @@ impl Renderer {
         }
         self.ctx
             .send_viewport_cmd_to(ViewportId::ROOT, ViewportCommand::Focus);
+        self.set_window_fullscreen(fullscreen);
+    }
+
+    /// Set the root window fullscreen, preferring a display mode matching the emulated frame rate
+    /// if enabled.
+    pub fn set_window_fullscreen(&mut self, fullscreen: bool) {
+        if fullscreen && feature!(ExclusiveFullscreen) {
+            if let Some(window) = self.root_window() {
+                if let Some(mode) = self.frame_pacer.video_mode(&window) {
+                    info!(
+                        "switching to {}x{} @ {}Hz",
+                        mode.size().width,
+                        mode.size().height,
+                        mode.refresh_rate_millihertz() as f32 / 1000.0
+                    );
+                    window.set_fullscreen(Some(Fullscreen::Exclusive(mode)));
+                    return;
+                }
+            }
+        }
         self.ctx
             .send_viewport_cmd_to(ViewportId::ROOT, ViewportCommand::Fullscreen(fullscreen));
     }
 
+    pub fn set_match_frame_rate(&mut self, enabled: bool) {
+        self.frame_pacer.set_enabled(enabled);
+        if self.fullscreen() {
+            self.set_window_fullscreen(true);
+        }
+    }
+
+    /// Update the emulated frame rate to pace, switching display modes if fullscreen.
+    fn update_frame_rate(&mut self) {
+        let frame_rate = f32::from(FrameRate::from(self.gui.borrow().region()));
+        if self.frame_pacer.set_frame_rate(frame_rate) && self.fullscreen() {
+            self.set_window_fullscreen(true);
+        }
+    }
+
     pub fn set_embed_viewports(&mut self, embed: bool) {
         self.ctx.set_embed_viewports(embed);
     }
@@ -385,6 +443,32 @@ impl Renderer {
         }
     }
 
+    pub fn set_translucent(&mut self, translucent: bool) {
+        if !self.painter.borrow_mut().set_translucent(translucent) {
+            self.add_message(
+                MessageType::Warn,
+                "Translucent windows are not supported on this platform",
+            );
+        }
+        if !translucent && self.click_through {
+            self.set_click_through(false);
+        }
+    }
+
+    pub const fn click_through(&self) -> bool {
+        self.click_through
+    }
+
+    /// Let mouse input pass through the root window to the windows underneath it. Keyboard input
+    /// still goes to the window while it's focused.
+    pub fn set_click_through(&mut self, click_through: bool) {
+        self.click_through = click_through;
+        self.ctx.send_viewport_cmd_to(
+            ViewportId::ROOT,
+            ViewportCommand::MousePassthrough(click_through),
+        );
+    }
+
     fn initialize_all_windows(&mut self, event_loop: &ActiveEventLoop) {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
@@ -413,6 +497,45 @@ impl Renderer {
         self.gui.borrow().loaded_rom.is_some()
     }
 
+    pub fn loaded_rom_name(&self) -> Option<String> {
+        self.gui
+            .borrow()
+            .loaded_rom
+            .as_ref()
+            .map(|rom| rom.name.clone())
+    }
+
+    /// Whether overscan is hidden, accounting for any override for the loaded ROM.
+    pub fn hide_overscan(&self) -> bool {
+        self.gui.borrow().hide_overscan()
+    }
+
+    /// Whether inputs should be logged for the input test window.
+    /// Whether saves that failed to be written are waiting to be retried or discarded.
+    pub fn failed_saves_pending(&self) -> bool {
+        !self.gui.borrow().failed_saves.is_empty()
+    }
+
+    pub fn input_test_open(&self) -> bool {
+        self.gui.borrow().input_test.open()
+    }
+
+    /// Log an input for the input test window.
+    pub fn log_test_input(
+        &mut self,
+        source: String,
+        pressed: Option<bool>,
+        action: Option<Action>,
+    ) {
+        self.gui
+            .borrow_mut()
+            .input_test
+            .push(source, pressed, action);
+        if let Some(window) = self.root_window() {
+            window.request_redraw();
+        }
+    }
+
     pub fn add_message<S>(&mut self, ty: MessageType, text: S)
     where
         S: Into<String>,
@@ -420,6 +543,21 @@ impl Renderer {
         self.gui.borrow_mut().add_message(ty, text);
     }
 
+    /// Save the last few seconds of frames as an animated image.
+    pub fn save_clip(&mut self, cfg: &Config) {
+        if self.clip_buffer.is_empty() {
+            self.add_message(MessageType::Warn, "No frames to save as a clip yet.");
+            return;
+        }
+        let region = self.gui.borrow().region();
+        match self.clip_buffer.save(cfg.renderer.clip_format, region) {
+            Ok(path) => {
+                self.add_message(MessageType::Info, format!("Clip Saved: {}", path.display()));
+            }
+            Err(err) => self.on_error(err),
+        }
+    }
+
     pub fn on_error(&mut self, err: anyhow::Error) {
         error!("error: {err:?}");
         self.tx.event(EmulationEvent::RunState(RunState::Paused));
@@ -476,7 +614,8 @@ impl Renderer {
     ) -> anyhow::Result<(egui::Context, Arc<Window>, Receiver<Painter>)> {
         let ctx = egui::Context::default();
 
-        let window_size = cfg.window_size(cfg.deck.region.aspect_ratio());
+        let window_size =
+            cfg.window_size(cfg.deck.region.aspect_ratio(), cfg.renderer.hide_overscan);
         let mut builder = egui::ViewportBuilder::default()
             .with_title(Config::WINDOW_TITLE)
             .with_visible(false) // hide until first frame is rendered. required by AccessKit
@@ -484,7 +623,9 @@ impl Renderer {
             .with_active(true)
             .with_resizable(true)
             .with_inner_size(window_size)
-            .with_min_inner_size(Vec2::new(Ppu::WIDTH as f32, Ppu::HEIGHT as f32));
+            .with_min_inner_size(Vec2::new(Ppu::WIDTH as f32, Ppu::HEIGHT as f32))
+            // Allows toggling translucency without recreating the window
+            .with_transparent(true);
         if cfg.renderer.always_on_top {
             builder = builder.with_always_on_top();
         }
@@ -549,6 +690,7 @@ impl Renderer {
             icon,
             active,
             visible,
+            transparent,
             window_level,
             ..
         } = builder;
@@ -558,6 +700,7 @@ impl Renderer {
             .with_title(title.clone())
             .with_resizable(resizable.unwrap_or(true))
             .with_visible(visible.unwrap_or(true))
+            .with_transparent(transparent.unwrap_or(false))
             .with_maximized(maximized.unwrap_or(false))
             .with_window_level(match window_level.unwrap_or_default() {
                 WindowLevel::AlwaysOnBottom => winit::window::WindowLevel::AlwaysOnBottom,
@@ -1053,6 +1196,7 @@ impl Renderer {
         puffin::GlobalProfiler::lock().new_frame();
 
         self.gui.borrow_mut().prepare(gamepads, cfg);
+        self.update_frame_rate();
 
         self.handle_resize(viewport_id, cfg);
 
@@ -1103,7 +1247,21 @@ impl Renderer {
         // resize tied to a configuration change.
         if viewport_id == ViewportId::ROOT {
             if let Some(render_state) = &self.painter.borrow().render_state() {
-                let mut frame_buffer = self.frame_rx.try_recv_ref();
+                let mut frame_buffer = match self.frame_pacer.next_frames() {
+                    // Repeat the current frame for this refresh
+                    Some(0) => Err(TryRecvError::Empty),
+                    Some(frames) => {
+                        let mut frame_buffer = self.frame_rx.try_recv_ref();
+                        for _ in 1..frames {
+                            if let Ok(next_frame) = self.frame_rx.try_recv_ref() {
+                                trace!("dropping frame");
+                                frame_buffer = Ok(next_frame);
+                            }
+                        }
+                        frame_buffer
+                    }
+                    None => self.frame_rx.try_recv_ref(),
+                };
                 while self.frame_rx.remaining() < 2 {
                     trace!("skipping frame");
                     frame_buffer = self.frame_rx.try_recv_ref();
@@ -1112,14 +1270,16 @@ impl Renderer {
                     Ok(frame_buffer) => {
                         let gui = self.gui.borrow_mut();
                         let is_ntsc = gui.loaded_region().unwrap_or(cfg.deck.region).is_ntsc();
-                        gui.nes_texture.update(
-                            &render_state.queue,
-                            if cfg.renderer.hide_overscan && is_ntsc {
-                                &frame_buffer[OVERSCAN_TRIM..frame_buffer.len() - OVERSCAN_TRIM]
-                            } else {
-                                &frame_buffer
-                            },
-                        );
+                        let hide_overscan = gui.hide_overscan();
+                        let frame_buffer = if hide_overscan && is_ntsc {
+                            &frame_buffer[OVERSCAN_TRIM..frame_buffer.len() - OVERSCAN_TRIM]
+                        } else {
+                            &frame_buffer
+                        };
+                        gui.nes_texture.update(&render_state.queue, frame_buffer);
+                        if gui.loaded_rom.is_some() {
+                            self.clip_buffer.push(frame_buffer);
+                        }
                         // self.nametables_texture.update_partial(
                         //     &render_state.queue,
                         //     &frame_buffer,
@@ -1216,6 +1376,14 @@ impl Renderer {
                 .retain_surfaces(&active_viewports_ids);
         }
 
+        // Redraw every display refresh so paced frames are presented on schedule
+        if viewport_id == ViewportId::ROOT && self.frame_pacer.is_pacing() {
+            let gui = self.gui.borrow();
+            if gui.loaded_rom.is_some() && !gui.run_state.paused() {
+                self.ctx.request_repaint_of(ViewportId::ROOT);
+            }
+        }
+
         if let Err(err) = self.auto_save(cfg) {
             error!("failed to auto save UI state: {err:?}");
         }
@@ -1223,6 +1391,14 @@ impl Renderer {
         Ok(())
     }
 
+    /// Resize the texture if a ROM was loaded or unloaded with a different Hide Overscan setting.
+    pub fn check_overscan_resize(&mut self) {
+        let gui = self.gui.borrow();
+        if gui.nes_texture.size != Config::texture_size(gui.hide_overscan()) {
+            self.resize_texture = true;
+        }
+    }
+
     fn handle_resize(&mut self, viewport_id: ViewportId, cfg: &Config) {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
@@ -1234,8 +1410,8 @@ impl Renderer {
             self.resize_window(cfg);
 
             if let Some(render_state) = self.painter.borrow_mut().render_state_mut() {
-                let texture_size = cfg.texture_size();
                 let mut gui = self.gui.borrow_mut();
+                let texture_size = Config::texture_size(gui.hide_overscan());
                 let aspect_ratio = gui.aspect_ratio();
                 gui.nes_texture
                     .resize(render_state, texture_size, aspect_ratio);
@@ -1244,6 +1420,32 @@ impl Renderer {
         }
     }
 
+    /// Snap the root window to the nearest whole scale after being resized, returning the scale.
+    /// Returns `None` if fullscreen or maximized.
+    pub fn snap_window_size(&self, size: &PhysicalSize<u32>, cfg: &Config) -> Option<f32> {
+        const MAX_SCALE: f32 = 5.0;
+
+        let window = self.root_window()?;
+        if self.fullscreen() || window.is_maximized() {
+            return None;
+        }
+
+        let size = size.to_logical::<f32>(window.scale_factor());
+        let menu_height = self.gui.borrow().menu_height;
+        let unit = self.window_size_for_scale(cfg, 1.0);
+        let scale_x = size.width / unit.x;
+        let scale_y = (size.height - menu_height) / (unit.y - menu_height);
+        let scale = ((scale_x + scale_y) / 2.0).round().clamp(1.0, MAX_SCALE);
+
+        let snapped_size = self.window_size_for_scale(cfg, scale);
+        if (snapped_size.x - size.width).abs() > 1.0 || (snapped_size.y - size.height).abs() > 1.0 {
+            tracing::debug!("snapping window size to {scale}x: {snapped_size:?}");
+            let _ = window.request_inner_size(LogicalSize::new(snapped_size.x, snapped_size.y));
+        }
+
+        Some(scale)
+    }
+
     fn resize_window(&self, cfg: &Config) {
         if !self.fullscreen() {
             let desired_window_size = self.window_size(cfg);
@@ -0,0 +1,477 @@
+//! Short clips of recent gameplay, kept in a rolling buffer of frames and exported as an
+//! animated GIF or APNG.
+
+use crate::nes::{
+    config::Config,
+    emulation::video_recording::frame_rate,
+    renderer::clip::gif::{GifFrame, GifWriter},
+};
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::Entry, HashMap, VecDeque},
+    fs::File,
+    io::BufWriter,
+    path::PathBuf,
+};
+use tetanes_core::{common::NesRegion, ppu::Ppu};
+
+pub mod gif;
+
+/// Only every other frame is kept, which is plenty for sharing and halves the memory used.
+const FRAME_INTERVAL: u32 = 2;
+/// Colors available to frames, leaving the last palette index for transparency.
+const MAX_COLORS: usize = 255;
+
+/// Image format clips are saved in.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub enum ClipFormat {
+    #[default]
+    Gif,
+    Apng,
+}
+
+impl ClipFormat {
+    pub const fn as_slice() -> &'static [Self] {
+        &[Self::Gif, Self::Apng]
+    }
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gif => "GIF",
+            Self::Apng => "APNG",
+        }
+    }
+
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Gif => "gif",
+            Self::Apng => "png",
+        }
+    }
+}
+
+impl AsRef<str> for ClipFormat {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// A buffered frame, stored as palette indices when it has few enough colors, which is nearly
+/// always the case without filters.
+#[derive(Debug)]
+enum ClipFrame {
+    Indexed {
+        palette: Vec<[u8; 3]>,
+        pixels: Vec<u8>,
+    },
+    Rgb(Vec<[u8; 3]>),
+}
+
+impl ClipFrame {
+    fn new(frame_buffer: &[u8]) -> Self {
+        let mut palette = Vec::new();
+        let mut lookup = HashMap::new();
+        let mut pixels = Vec::with_capacity(frame_buffer.len() / 4);
+        for pixel in frame_buffer.chunks_exact(4) {
+            let color = [pixel[0], pixel[1], pixel[2]];
+            let index = match lookup.entry(color) {
+                Entry::Occupied(entry) => *entry.get(),
+                Entry::Vacant(entry) => {
+                    if palette.len() == 256 {
+                        return Self::Rgb(
+                            frame_buffer
+                                .chunks_exact(4)
+                                .map(|pixel| [pixel[0], pixel[1], pixel[2]])
+                                .collect(),
+                        );
+                    }
+                    palette.push(color);
+                    *entry.insert((palette.len() - 1) as u8)
+                }
+            };
+            pixels.push(index);
+        }
+        Self::Indexed { palette, pixels }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::Indexed { pixels, .. } => pixels.len(),
+            Self::Rgb(pixels) => pixels.len(),
+        }
+    }
+
+    fn count_colors(&self, counts: &mut HashMap<[u8; 3], u32>) {
+        match self {
+            Self::Indexed { palette, pixels } => {
+                let mut index_counts = [0; 256];
+                for &index in pixels {
+                    index_counts[usize::from(index)] += 1;
+                }
+                for (color, count) in palette.iter().zip(index_counts) {
+                    *counts.entry(*color).or_default() += count;
+                }
+            }
+            Self::Rgb(pixels) => {
+                for color in pixels {
+                    *counts.entry(*color).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    /// Map each pixel to its closest color in `palette`.
+    fn quantize(&self, palette: &mut Palette) -> Vec<u8> {
+        match self {
+            Self::Indexed {
+                palette: colors,
+                pixels,
+            } => {
+                let indices = colors
+                    .iter()
+                    .map(|color| palette.index(*color))
+                    .collect::<Vec<_>>();
+                pixels
+                    .iter()
+                    .map(|&index| indices[usize::from(index)])
+                    .collect()
+            }
+            Self::Rgb(pixels) => pixels.iter().map(|color| palette.index(*color)).collect(),
+        }
+    }
+}
+
+/// Colors shared by every frame of a clip.
+#[derive(Debug)]
+struct Palette {
+    colors: Vec<[u8; 3]>,
+    /// Low bits ignored when looking up colors that aren't exactly in the palette.
+    precision_mask: u8,
+    cache: HashMap<[u8; 3], u8>,
+}
+
+impl Palette {
+    /// Choose up to `MAX_COLORS` colors to represent `frames`, using the exact colors if there
+    /// are few enough, otherwise by median cut.
+    fn new<'a>(frames: impl Iterator<Item = &'a ClipFrame>) -> Self {
+        let mut counts = HashMap::new();
+        for frame in frames {
+            frame.count_colors(&mut counts);
+        }
+        let (colors, precision_mask) = if counts.len() <= MAX_COLORS {
+            (counts.into_keys().collect(), 0xFF)
+        } else {
+            // Filtered frames can have many thousands of colors, so nearby colors share a
+            // lookup to keep searching the palette fast
+            (Self::median_cut(counts.into_iter().collect()), 0xFC)
+        };
+        Self {
+            colors,
+            precision_mask,
+            cache: HashMap::new(),
+        }
+    }
+
+    fn median_cut(colors: Vec<([u8; 3], u32)>) -> Vec<[u8; 3]> {
+        let widest_channel = |colors: &[([u8; 3], u32)]| {
+            (0..3)
+                .map(|channel| {
+                    let (min, max) = colors.iter().fold((u8::MAX, 0), |(min, max), (color, _)| {
+                        (min.min(color[channel]), max.max(color[channel]))
+                    });
+                    (channel, max - min)
+                })
+                .max_by_key(|(_, range)| *range)
+                .unwrap_or_default()
+        };
+
+        let mut boxes = vec![(widest_channel(&colors), colors)];
+        while boxes.len() < MAX_COLORS {
+            let Some((i, &((channel, _), _))) = boxes
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, colors))| colors.len() > 1)
+                .max_by_key(|(_, ((_, range), _))| *range)
+            else {
+                break;
+            };
+
+            // Split at the median pixel so busier colors get finer detail
+            let (_, mut colors) = boxes.swap_remove(i);
+            colors.sort_unstable_by_key(|(color, _)| color[channel]);
+            let total = colors.iter().map(|(_, n)| u64::from(*n)).sum::<u64>();
+            let mut seen = 0;
+            let median = colors
+                .iter()
+                .position(|(_, n)| {
+                    seen += u64::from(*n);
+                    2 * seen >= total
+                })
+                .unwrap_or_default();
+            let upper = colors.split_off((median + 1).clamp(1, colors.len() - 1));
+            boxes.push((widest_channel(&colors), colors));
+            boxes.push((widest_channel(&upper), upper));
+        }
+
+        boxes
+            .iter()
+            .map(|(_, colors)| {
+                let total = colors
+                    .iter()
+                    .map(|(_, n)| u64::from(*n))
+                    .sum::<u64>()
+                    .max(1);
+                let mut sum = [0u64; 3];
+                for (color, n) in colors {
+                    for (sum, channel) in sum.iter_mut().zip(color) {
+                        *sum += u64::from(*channel) * u64::from(*n);
+                    }
+                }
+                sum.map(|sum| ((sum + total / 2) / total) as u8)
+            })
+            .collect()
+    }
+
+    /// The index of the closest color to `color`.
+    fn index(&mut self, color: [u8; 3]) -> u8 {
+        let colors = &self.colors;
+        let key = color.map(|channel| channel & self.precision_mask);
+        *self.cache.entry(key).or_insert_with(|| {
+            let distance = |other: &[u8; 3]| {
+                color
+                    .iter()
+                    .zip(other)
+                    .map(|(a, b)| (i32::from(*a) - i32::from(*b)).pow(2))
+                    .sum::<i32>()
+            };
+            colors
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, other)| distance(other))
+                .map_or(0, |(index, _)| index as u8)
+        })
+    }
+
+    /// Palette index used for pixels unchanged from the previous frame.
+    fn transparent(&self) -> u8 {
+        self.colors.len() as u8
+    }
+}
+
+/// The area of a clip frame that changed from the previous frame.
+#[derive(Debug)]
+struct ClipImage {
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+    indices: Vec<u8>,
+    /// Delay after this frame in hundredths of a second.
+    delay: u16,
+}
+
+/// A rolling buffer of recent frames.
+#[derive(Debug)]
+#[must_use]
+pub struct ClipBuffer {
+    seconds: u32,
+    frames: VecDeque<ClipFrame>,
+    frame_count: u32,
+}
+
+impl ClipBuffer {
+    pub const fn new(seconds: u32) -> Self {
+        Self {
+            seconds,
+            frames: VecDeque::new(),
+            frame_count: 0,
+        }
+    }
+
+    /// The most frames kept, at the fastest frame rate of any region.
+    const fn capacity(&self) -> usize {
+        (self.seconds * 60 / FRAME_INTERVAL) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.frame_count = 0;
+    }
+
+    /// Add an RGBA frame as displayed, discarding the oldest frame if the buffer is full.
+    pub fn push(&mut self, frame_buffer: &[u8]) {
+        self.frame_count = self.frame_count.wrapping_add(1);
+        if self.seconds == 0 || self.frame_count % FRAME_INTERVAL != 0 {
+            return;
+        }
+        // Frames change size when overscan is toggled
+        if self
+            .frames
+            .back()
+            .is_some_and(|frame| 4 * frame.len() != frame_buffer.len())
+        {
+            self.frames.clear();
+        }
+        while self.frames.len() >= self.capacity() {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(ClipFrame::new(frame_buffer));
+    }
+
+    /// Save the buffered frames emulated for `region` in the default picture directory,
+    /// returning the path of the clip.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are no frames or the clip fails to save.
+    pub fn save(&self, format: ClipFormat, region: NesRegion) -> anyhow::Result<PathBuf> {
+        if self.frames.is_empty() {
+            return Err(anyhow!("no frames to save"));
+        }
+
+        let fps = frame_rate(region) / f64::from(FRAME_INTERVAL);
+        let count = ((f64::from(self.seconds) * fps).ceil() as usize).min(self.frames.len());
+        let frames = self.frames.range(self.frames.len() - count..);
+        let src_width = Ppu::WIDTH as usize;
+        let height = self.frames[self.frames.len() - count].len() / src_width;
+        // Stretch to the region's pixel aspect ratio as seen on a TV
+        let width = (src_width as f32 * region.aspect_ratio()).round() as usize;
+
+        let mut palette = Palette::new(frames.clone());
+        let transparent = palette.transparent();
+        let mut images = Vec::<ClipImage>::with_capacity(count);
+        let mut prev = Vec::new();
+        for (i, frame) in frames.enumerate() {
+            let pixels = frame.quantize(&mut palette);
+            let indices = (0..width * height)
+                .map(|i| pixels[(i / width) * src_width + (i % width) * src_width / width])
+                .collect::<Vec<_>>();
+            // Delays are rounded from exact timestamps so errors don't accumulate
+            let timestamp = |i: usize| (i as f64 * 100.0 / fps).round() as u32;
+            let delay = (timestamp(i + 1) - timestamp(i)) as u16;
+
+            let mut changed: Option<(usize, usize, usize, usize)> = None;
+            for (i, index) in indices.iter().enumerate() {
+                if prev.get(i) != Some(index) {
+                    let (x, y) = (i % width, i / width);
+                    changed = Some(changed.map_or((x, y, x, y), |(left, top, right, bottom)| {
+                        (left.min(x), top.min(y), right.max(x), bottom.max(y))
+                    }));
+                }
+            }
+            match (changed, images.last_mut()) {
+                (None, Some(image)) => image.delay = image.delay.saturating_add(delay),
+                (Some((left, top, right, bottom)), _) => {
+                    let mut image = ClipImage {
+                        left,
+                        top,
+                        width: right - left + 1,
+                        height: bottom - top + 1,
+                        indices: Vec::new(),
+                        delay,
+                    };
+                    for y in top..=bottom {
+                        image.indices.extend((left..=right).map(|x| {
+                            let i = y * width + x;
+                            if prev.get(i) == Some(&indices[i]) {
+                                transparent
+                            } else {
+                                indices[i]
+                            }
+                        }));
+                    }
+                    images.push(image);
+                }
+                (None, None) => unreachable!("first frame always changes"),
+            }
+            prev = indices;
+        }
+
+        let dir = Config::default_picture_dir();
+        if !dir.exists() {
+            std::fs::create_dir_all(&dir)
+                .with_context(|| format!("failed to create clip directory: {}", dir.display()))?;
+        }
+        let path = dir
+            .join(
+                chrono::Local::now()
+                    .format("clip_%Y-%m-%d_at_%H_%M_%S")
+                    .to_string(),
+            )
+            .with_extension(format.extension());
+        let file = BufWriter::new(
+            File::create(&path)
+                .with_context(|| format!("failed to create clip: {}", path.display()))?,
+        );
+        match format {
+            ClipFormat::Gif => Self::write_gif(file, width, height, &palette, &images),
+            ClipFormat::Apng => Self::write_apng(file, width, height, &palette, &images),
+        }
+        .with_context(|| format!("failed to save clip: {}", path.display()))?;
+
+        Ok(path)
+    }
+
+    fn write_gif(
+        file: BufWriter<File>,
+        width: usize,
+        height: usize,
+        palette: &Palette,
+        images: &[ClipImage],
+    ) -> anyhow::Result<()> {
+        let mut colors = palette.colors.clone();
+        colors.push([0x00; 3]);
+        let mut writer = GifWriter::new(file, width as u16, height as u16, &colors)?;
+        for image in images {
+            writer.write_frame(&GifFrame {
+                left: image.left as u16,
+                top: image.top as u16,
+                width: image.width as u16,
+                height: image.height as u16,
+                indices: &image.indices,
+                delay: image.delay,
+                transparent: Some(palette.transparent()),
+            })?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+
+    fn write_apng(
+        file: BufWriter<File>,
+        width: usize,
+        height: usize,
+        palette: &Palette,
+        images: &[ClipImage],
+    ) -> anyhow::Result<()> {
+        let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut colors = palette.colors.concat();
+        colors.extend([0x00; 3]);
+        encoder.set_palette(colors);
+        let mut alpha = vec![0xFF; palette.colors.len()];
+        alpha.push(0x00);
+        encoder.set_trns(alpha);
+        encoder.set_animated(images.len() as u32, 0)?;
+
+        let mut writer = encoder.write_header()?;
+        for image in images {
+            writer.reset_frame_position()?;
+            writer.set_frame_dimension(image.width as u32, image.height as u32)?;
+            writer.set_frame_position(image.left as u32, image.top as u32)?;
+            writer.set_frame_delay(image.delay, 100)?;
+            // Draw over the previous frame, leaving unchanged pixels visible
+            writer.set_blend_op(png::BlendOp::Over)?;
+            writer.write_image_data(&image.indices)?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+}
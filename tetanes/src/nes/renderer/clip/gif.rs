@@ -0,0 +1,164 @@
+//! A minimal animated GIF encoder for indexed frames.
+//!
+//! See: <https://www.w3.org/Graphics/GIF/spec-gif89a.txt>
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+const MAX_CODE_SIZE: u8 = 12;
+const MAX_CODES: u16 = 1 << MAX_CODE_SIZE;
+
+/// Writes LSB-first variable width codes, split into data sub-blocks.
+#[derive(Default, Debug)]
+struct CodeWriter {
+    bytes: Vec<u8>,
+    acc: u32,
+    bits: u8,
+}
+
+impl CodeWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.acc |= u32::from(code) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push(self.acc as u8);
+            self.acc >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.acc as u8);
+        }
+        self.bytes
+    }
+}
+
+/// LZW compress `indices` into the sub-blocks of a GIF image.
+fn lzw_encode(indices: &[u8], min_code_size: u8, out: &mut Vec<u8>) {
+    let clear = 1 << min_code_size;
+    let end = clear + 1;
+    let mut codes = CodeWriter::default();
+    let mut table = HashMap::<(u16, u8), u16>::new();
+    let mut size = min_code_size + 1;
+    let mut next = end + 1;
+
+    codes.write(clear, size);
+    if let Some((&first, rest)) = indices.split_first() {
+        let mut prefix = u16::from(first);
+        for &index in rest {
+            if let Some(&code) = table.get(&(prefix, index)) {
+                prefix = code;
+                continue;
+            }
+            codes.write(prefix, size);
+            if next == 1 << size && size < MAX_CODE_SIZE {
+                size += 1;
+            }
+            if next < MAX_CODES {
+                table.insert((prefix, index), next);
+                next += 1;
+            } else {
+                codes.write(clear, size);
+                table.clear();
+                size = min_code_size + 1;
+                next = end + 1;
+            }
+            prefix = u16::from(index);
+        }
+        codes.write(prefix, size);
+        if next == 1 << size && size < MAX_CODE_SIZE {
+            size += 1;
+        }
+    }
+    codes.write(end, size);
+
+    out.push(min_code_size);
+    for block in codes.finish().chunks(255) {
+        out.push(block.len() as u8);
+        out.extend_from_slice(block);
+    }
+    out.push(0x00);
+}
+
+/// A frame of palette indices positioned within the logical screen.
+#[derive(Debug)]
+pub struct GifFrame<'a> {
+    pub left: u16,
+    pub top: u16,
+    pub width: u16,
+    pub height: u16,
+    pub indices: &'a [u8],
+    /// Delay after this frame in hundredths of a second.
+    pub delay: u16,
+    /// Palette index treated as transparent, leaving the previous frame visible.
+    pub transparent: Option<u8>,
+}
+
+/// Writes a looping GIF with a global palette of up to 256 colors.
+#[derive(Debug)]
+#[must_use]
+pub struct GifWriter<W: Write> {
+    writer: W,
+    min_code_size: u8,
+}
+
+impl<W: Write> GifWriter<W> {
+    pub fn new(mut writer: W, width: u16, height: u16, palette: &[[u8; 3]]) -> io::Result<Self> {
+        if palette.is_empty() || palette.len() > 256 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unsupported palette size: {}", palette.len()),
+            ));
+        }
+        // Color tables are sized in powers of two, with at least 2 colors
+        let table_bits = (palette.len().max(2).next_power_of_two().trailing_zeros()) as u8;
+
+        writer.write_all(b"GIF89a")?;
+        writer.write_all(&width.to_le_bytes())?;
+        writer.write_all(&height.to_le_bytes())?;
+        // Global color table, 8 bits per primary color
+        writer.write_all(&[0xF0 | (table_bits - 1), 0x00, 0x00])?;
+        for i in 0..1 << table_bits {
+            writer.write_all(palette.get(i).unwrap_or(&[0x00; 3]))?;
+        }
+        // Loop forever
+        writer.write_all(&[0x21, 0xFF, 0x0B])?;
+        writer.write_all(b"NETSCAPE2.0")?;
+        writer.write_all(&[0x03, 0x01, 0x00, 0x00, 0x00])?;
+
+        Ok(Self {
+            writer,
+            min_code_size: table_bits.max(2),
+        })
+    }
+
+    pub fn write_frame(&mut self, frame: &GifFrame<'_>) -> io::Result<()> {
+        // Graphic control extension, leaving the frame in place to draw the next over it
+        let transparent_flag = u8::from(frame.transparent.is_some());
+        self.writer.write_all(&[0x21, 0xF9, 0x04])?;
+        self.writer.write_all(&[(1 << 2) | transparent_flag])?;
+        self.writer.write_all(&frame.delay.to_le_bytes())?;
+        self.writer
+            .write_all(&[frame.transparent.unwrap_or(0x00), 0x00])?;
+
+        // Image descriptor, using the global color table
+        self.writer.write_all(&[0x2C])?;
+        for val in [frame.left, frame.top, frame.width, frame.height] {
+            self.writer.write_all(&val.to_le_bytes())?;
+        }
+        self.writer.write_all(&[0x00])?;
+
+        let mut data = Vec::with_capacity(frame.indices.len() / 2);
+        lzw_encode(frame.indices, self.min_code_size, &mut data);
+        self.writer.write_all(&data)
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.writer.write_all(&[0x3B])?;
+        self.writer.flush()
+    }
+}
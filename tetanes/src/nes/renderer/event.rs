@@ -39,12 +39,19 @@ impl Renderer {
                 RendererEvent::ViewportResized(_) => self.resize_window(cfg),
                 RendererEvent::ResizeTexture => self.resize_texture = true,
                 RendererEvent::RomLoaded(_) => {
+                    self.check_overscan_resize();
+                    self.clip_buffer.clear();
                     let state = self.state.borrow();
                     if state.focused != Some(ViewportId::ROOT) {
                         self.ctx
                             .send_viewport_cmd_to(ViewportId::ROOT, ViewportCommand::Focus);
                     }
                 }
+                RendererEvent::RomUnloaded => {
+                    self.check_overscan_resize();
+                    self.clip_buffer.clear();
+                }
+                RendererEvent::SaveClip => self.save_clip(cfg),
                 _ => (),
             },
             NesEvent::Config(event) => match event {
@@ -68,18 +75,20 @@ impl Renderer {
                     if self.fullscreen() != *fullscreen {
                         self.ctx
                             .send_viewport_cmd_to(ViewportId::ROOT, ViewportCommand::Focus);
-                        self.ctx.send_viewport_cmd_to(
-                            ViewportId::ROOT,
-                            ViewportCommand::Fullscreen(*fullscreen),
-                        );
+                        self.set_window_fullscreen(*fullscreen);
                     }
                 }
-                ConfigEvent::Region(_) | ConfigEvent::HideOverscan(_) | ConfigEvent::Scale(_) => {
+                ConfigEvent::MatchFrameRate(enabled) => self.set_match_frame_rate(*enabled),
+                ConfigEvent::Region(_)
+                | ConfigEvent::HideOverscan(_)
+                | ConfigEvent::RomOverscan(_)
+                | ConfigEvent::Scale(_) => {
                     self.resize_texture = true;
                 }
-                ConfigEvent::Shader(shader) => {
-                    self.painter.borrow_mut().set_shader(*shader);
+                ConfigEvent::Shader(shaders) => {
+                    self.painter.borrow_mut().set_shaders(shaders);
                 }
+                ConfigEvent::Translucent(translucent) => self.set_translucent(*translucent),
                 _ => (),
             },
             // TODO: Update accesskit when egui supports an updated version
@@ -194,10 +203,21 @@ impl Renderer {
                     }
                 }
             }
+            // The window may have moved to another monitor or changed display modes
+            WindowEvent::Moved(_) if viewport_id == ViewportId::ROOT => {
+                if let Some(window) = &viewport.window {
+                    self.frame_pacer.set_refresh_rate(window);
+                }
+            }
             WindowEvent::Resized(size) => {
                 self.painter
                     .borrow_mut()
                     .on_window_resized(viewport_id, size.width, size.height);
+                if viewport_id == ViewportId::ROOT {
+                    if let Some(window) = &viewport.window {
+                        self.frame_pacer.set_refresh_rate(window);
+                    }
+                }
             }
             WindowEvent::ThemeChanged(theme) => {
                 self.ctx
@@ -604,6 +624,33 @@ impl Renderer {
             Response::default()
         }
     }
+
+    /// Handle gamepad button presses for navigating the GUI.
+    pub fn on_gamepad_navigation(&mut self, button: gilrs::Button) -> Response {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let mut gui = self.gui.borrow_mut();
+        if !gui.gamepad_navigation() {
+            return Response::default();
+        }
+
+        let State {
+            viewports, focused, ..
+        } = &mut *self.state.borrow_mut();
+        let viewport_id = focused.unwrap_or(ViewportId::ROOT);
+        let Some(viewport) = viewports.get_mut(&viewport_id) else {
+            return Response::default();
+        };
+        let events = gui.gamepad_nav.on_button(&self.ctx, button);
+        viewport.raw_input.events.extend(events);
+        self.ctx.request_repaint_of(viewport_id);
+
+        Response {
+            repaint: true,
+            consumed: true,
+        }
+    }
 }
 
 impl TryFrom<(egui::Key, egui::Modifiers)> for Input {
@@ -0,0 +1,109 @@
+//! Presenting emulated frames at a different rate than the display refreshes, e.g. 50Hz PAL games
+//! on a 60Hz display.
+//!
+//! Left alone, which refresh a frame lands on depends on emulation timing jitter, causing uneven
+//! judder. Instead, each refresh is scheduled to either repeat the current frame or advance one or
+//! more frames, spreading repeats and drops evenly over time.
+
+use winit::{monitor::VideoModeHandle, window::Window};
+
+/// Schedules which emulated frame to present on each display refresh.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[must_use]
+pub struct FramePacer {
+    enabled: bool,
+    /// Emulated frames per second.
+    frame_rate: f32,
+    /// Display refreshes per second, if known.
+    refresh_rate: Option<f32>,
+    /// Fraction of an emulated frame elapsed since the last frame presented.
+    accumulator: f32,
+}
+
+impl Default for FramePacer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FramePacer {
+    /// Rates within this many Hz of each other are considered matched, e.g. 60Hz NTSC on a
+    /// 59.94Hz display.
+    const TOLERANCE: f32 = 0.5;
+
+    pub const fn new() -> Self {
+        Self {
+            enabled: true,
+            frame_rate: 60.0,
+            refresh_rate: None,
+            accumulator: 0.0,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Set the emulated frame rate, returning whether it changed.
+    pub fn set_frame_rate(&mut self, frame_rate: f32) -> bool {
+        let changed = self.frame_rate != frame_rate;
+        self.frame_rate = frame_rate;
+        changed
+    }
+
+    /// Set the display refresh rate from the monitor the window is on.
+    pub fn set_refresh_rate(&mut self, window: &Window) {
+        self.refresh_rate = window
+            .current_monitor()
+            .and_then(|monitor| monitor.refresh_rate_millihertz())
+            .map(|millihertz| millihertz as f32 / 1000.0);
+    }
+
+    /// Whether frames are being paced because the emulated frame rate differs from the display
+    /// refresh rate.
+    pub fn is_pacing(&self) -> bool {
+        self.enabled
+            && self
+                .refresh_rate
+                .is_some_and(|refresh_rate| !Self::matches(self.frame_rate, refresh_rate))
+    }
+
+    /// Advance one display refresh, returning how many emulated frames to advance: `0` to repeat
+    /// the current frame, `1` for the next frame, or more to drop frames. Returns `None` if not
+    /// pacing, in which case the latest frame should be presented.
+    pub fn next_frames(&mut self) -> Option<usize> {
+        let refresh_rate = self.refresh_rate.filter(|_| self.is_pacing())?;
+        self.accumulator += self.frame_rate / refresh_rate;
+        let frames = self.accumulator.floor();
+        self.accumulator -= frames;
+        Some(frames as usize)
+    }
+
+    /// Finds a fullscreen video mode for the window's monitor at its current resolution with a
+    /// refresh rate matching the emulated frame rate, if the current refresh rate doesn't already.
+    pub fn video_mode(&self, window: &Window) -> Option<VideoModeHandle> {
+        if !self.enabled {
+            return None;
+        }
+        let monitor = window.current_monitor()?;
+        let refresh_rate = monitor.refresh_rate_millihertz()? as f32 / 1000.0;
+        if Self::matches(self.frame_rate, refresh_rate) {
+            return None;
+        }
+        let size = monitor.size();
+        monitor
+            .video_modes()
+            .filter(|mode| {
+                mode.size() == size
+                    && Self::matches(
+                        self.frame_rate,
+                        mode.refresh_rate_millihertz() as f32 / 1000.0,
+                    )
+            })
+            .max_by_key(VideoModeHandle::bit_depth)
+    }
+
+    fn matches(frame_rate: f32, refresh_rate: f32) -> bool {
+        (frame_rate - refresh_rate).abs() <= Self::TOLERANCE
+    }
+}
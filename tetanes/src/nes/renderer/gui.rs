@@ -1,9 +1,20 @@
+#[cfg(feature = "debug")]
+use crate::nes::renderer::gui::bus_heatmap::BusHeatmap;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::nes::renderer::gui::save_password::SavePassword;
 use crate::{
     feature,
     nes::{
         action::{Debug, DebugKind, DebugStep, Feature, Setting, Ui as UiAction},
+        annotation::Annotations,
         config::{Config, RendererConfig},
-        emulation::FrameStats,
+        emulation::{
+            clipboard::FrameCopy,
+            front_panel::FrontPanel,
+            quick_slots::{QuickSlot, QuickSlots},
+            replay::{HashStatus, ReplayInfo},
+            FrameStats,
+        },
         event::{
             ConfigEvent, DebugEvent, EmulationEvent, NesEvent, NesEventProxy, RendererEvent,
             Response, UiEvent,
@@ -11,13 +22,33 @@ use crate::{
         input::Gamepads,
         renderer::{
             gui::{
+                audio_scope::AudioScope,
+                bookmarks::Bookmarks,
+                cheat_files::CheatFiles,
+                compare::CompareReplays,
+                cpu_debugger::CpuDebugger,
+                failed_saves::FailedSaves,
+                gamepad_nav::GamepadNav,
+                import::Import,
+                input_test::InputTest,
+                interrupt_log::InterruptLog,
                 keybinds::Keybinds,
                 lib::{
-                    cursor_to_zapper, input_down, ShortcutText, ShowShortcut, ToggleValue,
-                    ViewportOptions,
+                    cursor_to_zapper, input_down, pixel_perfect_rect, ShortcutText, ShowShortcut,
+                    ToggleValue, ViewportOptions,
                 },
+                memory_search::MemorySearch,
+                netplay::Netplay,
                 ppu_viewer::PpuViewer,
                 preferences::Preferences,
+                region::RegionMismatch,
+                rewind_scrubber::RewindScrubber,
+                rom_audit::RomAudit,
+                save_states::SaveStates,
+                seek::Seek,
+                state_transfer::StateTransfer,
+                subtitle::AddSubtitle,
+                tv_static::TvStatic,
             },
             painter::RenderState,
             texture::Texture,
@@ -26,15 +57,18 @@ use crate::{
         version::Version,
         RunState,
     },
+    platform::open_dir_dialog,
     sys::{info::System, SystemInfo},
 };
+use cfg_if::cfg_if;
 use egui::{
     hex_color, include_image, menu,
     style::{HandleShape, Selection, TextCursorStyle, WidgetVisuals},
-    Align, Button, CentralPanel, Color32, Context, CursorIcon, Direction, FontData,
-    FontDefinitions, FontFamily, Frame, Grid, Image, Layout, Pos2, Rect, RichText, Rounding,
-    ScrollArea, Sense, Stroke, TopBottomPanel, Ui, ViewportClass, Visuals,
+    Align, Align2, Button, CentralPanel, Color32, Context, CursorIcon, Direction, FontData,
+    FontDefinitions, FontFamily, Frame, Grid, Image, Layout, PointerButton, Pos2, Rect, RichText,
+    Rounding, ScrollArea, Sense, Stroke, TopBottomPanel, Ui, Vec2, ViewportClass, Visuals,
 };
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
@@ -42,26 +76,65 @@ use std::sync::{
 };
 use tetanes_core::{
     action::Action as DeckAction,
+    apu::Channel,
     common::{NesRegion, ResetKind},
     control_deck::LoadedRom,
+    input::{MouseBtn, MousePort},
     ppu::Ppu,
     time::{Duration, Instant},
 };
 use tracing::{error, info, warn};
 use winit::event::WindowEvent;
 
+mod audio_scope;
+mod bookmarks;
+#[cfg(feature = "debug")]
+mod bus_heatmap;
+mod cheat_files;
+mod compare;
+mod cpu_debugger;
+mod failed_saves;
+mod gamepad_nav;
+mod import;
+mod input_test;
+mod interrupt_log;
 mod keybinds;
 pub mod lib;
+mod memory_search;
+mod netplay;
+mod overscan;
 mod ppu_viewer;
 mod preferences;
+mod region;
+mod replay_hash;
+mod rewind_scrubber;
+mod rom_audit;
+#[cfg(not(target_arch = "wasm32"))]
+mod save_password;
+mod save_states;
+mod seek;
+mod state_transfer;
+mod subtitle;
+mod tv_static;
+
+const FONT: &str = "pixeloid-sans";
+const BOLD_FONT: &str = "pixeloid-sans-bold";
+const MONO_FONT: &str = "pixeloid-mono";
+/// Font names, matching their `.ttf` asset filenames.
+const FONTS: [&str; 3] = [FONT, BOLD_FONT, MONO_FONT];
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum Menu {
     About,
     Keybinds,
     PerfStats,
+    CpuDebugger,
     PpuViewer,
+    InterruptLog,
+    MemorySearch,
     Preferences,
+    QuickSettings,
+    Bookmarks,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -83,6 +156,8 @@ pub struct Gui {
     pub run_state: RunState,
     pub menu_height: f32,
     pub nes_frame: Rect,
+    /// Mouse motion smaller than a whole count, carried over to the next frame.
+    pub mouse_remainder: Vec2,
     pub about_open: bool,
     pub gui_settings_open: Arc<AtomicBool>,
     #[cfg(debug_assertions)]
@@ -90,16 +165,49 @@ pub struct Gui {
     #[cfg(debug_assertions)]
     pub gui_memory_open: Arc<AtomicBool>,
     pub perf_stats_open: bool,
+    pub quick_settings_open: bool,
     pub update_window_open: bool,
     pub version: Version,
     pub keybinds: Keybinds,
+    pub input_test: InputTest,
+    pub bookmarks: Bookmarks,
+    pub save_states: SaveStates,
+    pub gamepad_nav: GamepadNav,
     pub preferences: Preferences,
-    pub debugger_open: bool,
+    pub cpu_debugger: CpuDebugger,
     pub ppu_viewer: PpuViewer,
+    pub interrupt_log: InterruptLog,
+    pub memory_search: MemorySearch,
+    /// Memory annotations for the loaded ROM, shared with debug windows.
+    pub annotations: Arc<Mutex<Annotations>>,
+    #[cfg(feature = "debug")]
+    pub bus_heatmap: BusHeatmap,
     pub apu_mixer_open: bool,
     pub viewport_info_open: bool,
     pub replay_recording: bool,
+    pub replay_info: Option<ReplayInfo>,
+    pub replay_subtitle: Option<String>,
+    pub replay_hash: Option<HashStatus>,
+    pub add_subtitle: AddSubtitle,
+    pub state_transfer: StateTransfer,
+    pub netplay: Netplay,
+    pub import: Import,
+    pub cheat_files: CheatFiles,
+    pub rom_audit: RomAudit,
+    pub seek: Seek,
+    pub rewind_scrubber: RewindScrubber,
+    pub compare_replays: CompareReplays,
+    pub region_mismatch: RegionMismatch,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub save_password: SavePassword,
+    pub failed_saves: FailedSaves,
     pub audio_recording: bool,
+    pub video_recording: bool,
+    pub dmc_sample_log: bool,
+    pub front_panel: FrontPanel,
+    pub quick_slots: [QuickSlot; QuickSlots::COUNT],
+    pub audio_scope: AudioScope,
+    pub tv_static: TvStatic,
     pub frame_stats: FrameStats,
     pub messages: Vec<(MessageType, String, Instant)>,
     pub loaded_rom: Option<LoadedRom>,
@@ -123,10 +231,11 @@ impl Gui {
     ) -> Self {
         let nes_texture = Texture::new(
             render_state,
-            cfg.texture_size(),
+            Config::texture_size(cfg.renderer.hide_overscan),
             cfg.deck.region.aspect_ratio(),
             Some("nes frame"),
         );
+        let annotations = Arc::new(Mutex::new(Annotations::default()));
 
         Self {
             ctx,
@@ -138,6 +247,7 @@ impl Gui {
             run_state: RunState::Running,
             menu_height: 0.0,
             nes_frame: Rect::ZERO,
+            mouse_remainder: Vec2::ZERO,
             about_open: false,
             gui_settings_open: Arc::new(AtomicBool::new(false)),
             #[cfg(debug_assertions)]
@@ -145,16 +255,48 @@ impl Gui {
             #[cfg(debug_assertions)]
             gui_memory_open: Arc::new(AtomicBool::new(false)),
             perf_stats_open: false,
+            quick_settings_open: false,
             update_window_open: false,
             version: Version::new(),
             keybinds: Keybinds::new(tx.clone()),
+            input_test: InputTest::new(),
+            bookmarks: Bookmarks::new(tx.clone()),
+            save_states: SaveStates::new(tx.clone()),
+            gamepad_nav: GamepadNav::new(),
             preferences: Preferences::new(tx.clone()),
-            debugger_open: false,
-            ppu_viewer: PpuViewer::new(tx, render_state),
+            cpu_debugger: CpuDebugger::new(tx.clone(), Arc::clone(&annotations)),
+            ppu_viewer: PpuViewer::new(tx.clone(), render_state),
+            interrupt_log: InterruptLog::new(tx.clone(), Arc::clone(&annotations)),
+            memory_search: MemorySearch::new(tx.clone(), Arc::clone(&annotations)),
+            annotations,
+            #[cfg(feature = "debug")]
+            bus_heatmap: BusHeatmap::new(tx.clone()),
             apu_mixer_open: false,
             viewport_info_open: false,
             replay_recording: false,
+            replay_info: None,
+            replay_subtitle: None,
+            replay_hash: None,
+            add_subtitle: AddSubtitle::new(tx.clone()),
+            state_transfer: StateTransfer::new(tx.clone()),
+            netplay: Netplay::new(tx.clone()),
+            import: Import::default(),
+            cheat_files: CheatFiles::new(tx.clone()),
+            rom_audit: RomAudit::default(),
+            seek: Seek::new(tx.clone()),
+            rewind_scrubber: RewindScrubber::new(tx.clone()),
+            compare_replays: CompareReplays::new(tx.clone()),
+            region_mismatch: RegionMismatch::new(tx.clone()),
+            #[cfg(not(target_arch = "wasm32"))]
+            save_password: SavePassword::new(tx.clone()),
+            failed_saves: FailedSaves::new(tx.clone()),
             audio_recording: false,
+            video_recording: false,
+            dmc_sample_log: false,
+            front_panel: FrontPanel::default(),
+            quick_slots: Default::default(),
+            audio_scope: AudioScope::default(),
+            tv_static: TvStatic::new(),
             frame_stats: FrameStats::new(),
             messages: Vec::new(),
             loaded_rom: None,
@@ -165,6 +307,16 @@ impl Gui {
         }
     }
 
+    /// Whether gamepad input navigates the GUI, which is when no ROM is loaded, emulation is
+    /// manually paused, or quick settings are open.
+    pub fn gamepad_navigation(&self) -> bool {
+        self.cfg.input.gamepad_navigation
+            && !self.keybinds.wants_input()
+            && (self.loaded_rom.is_none()
+                || self.run_state.manually_paused()
+                || self.quick_settings_open)
+    }
+
     pub fn on_window_event(&mut self, event: &WindowEvent) -> Response {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
@@ -215,16 +367,95 @@ impl Gui {
                         self.menu_height = 0.0;
                     }
                 }
-                RendererEvent::ReplayLoaded => self.run_state = RunState::Running,
+                RendererEvent::ReplayLoaded(info) => {
+                    self.run_state = RunState::ManuallyPaused;
+                    self.replay_info = Some(info.clone());
+                }
+                RendererEvent::ReplaySubtitle(subtitle) => {
+                    self.replay_subtitle.clone_from(subtitle);
+                }
+                RendererEvent::ReplayHash(status) => self.replay_hash = *status,
+                RendererEvent::StateTransferLink(link) => {
+                    self.state_transfer.set_link(link.clone());
+                }
+                RendererEvent::NetplayStatus(status) => self.netplay.set_status(status.clone()),
+                RendererEvent::SeekProgress {
+                    start,
+                    frame,
+                    target,
+                } => {
+                    self.seek.update_progress(*start, *frame, *target);
+                }
+                RendererEvent::SeekFinished => self.seek.finish(),
+                RendererEvent::CompareProgress { frame, total } => {
+                    self.compare_replays.update_progress(*frame, *total);
+                }
+                RendererEvent::CompareFinished(result) => {
+                    self.compare_replays.finish(&self.ctx, result.clone());
+                }
+                RendererEvent::CopyText(text) => self.ctx.copy_text(text.clone()),
+                RendererEvent::FrontPanel(front_panel) => self.front_panel = *front_panel,
+                RendererEvent::QuickSlots(quick_slots) => self.quick_slots = *quick_slots,
+                RendererEvent::AudioScope(samples) => {
+                    self.audio_scope.update(std::mem::take(samples));
+                }
+                RendererEvent::Bookmarks(bookmarks) => {
+                    self.bookmarks.set_bookmarks(std::mem::take(bookmarks));
+                }
+                RendererEvent::SaveStates(slots) => {
+                    self.save_states.set_slots(std::mem::take(slots));
+                }
+                RendererEvent::VideoRecording(recording) => {
+                    self.video_recording = *recording;
+                }
+                RendererEvent::RewindTimeline(timeline) => {
+                    self.rewind_scrubber.set_timeline(std::mem::take(timeline));
+                }
+                RendererEvent::FailedSaves(saves) => {
+                    let was_empty = self.failed_saves.is_empty();
+                    self.failed_saves.set_saves(std::mem::take(saves));
+                    // Pause until failed saves are resolved, resuming if nothing else paused
+                    if was_empty && !self.failed_saves.is_empty() {
+                        self.pause_for_failed_saves();
+                    } else if !was_empty
+                        && self.failed_saves.is_empty()
+                        && self.loaded_rom.is_some()
+                        && self.run_state == RunState::Paused
+                    {
+                        self.run_state = RunState::Running;
+                        self.tx.event(EmulationEvent::RunState(self.run_state));
+                    }
+                }
                 RendererEvent::RomUnloaded => {
                     self.run_state = RunState::Running;
+                    self.memory_search.clear();
+                    *self.annotations.lock() = Annotations::default();
+                    self.replay_subtitle = None;
+                    self.replay_hash = None;
+                    self.region_mismatch.clear();
+                    self.preferences.set_audio_channels(None);
+                    self.front_panel = FrontPanel::default();
+                    self.quick_slots = Default::default();
+                    self.audio_scope.clear();
+                    self.rewind_scrubber.set_open(false);
+                    self.dmc_sample_log = false;
                     self.loaded_rom = None;
                     self.title = Config::WINDOW_TITLE.to_string();
                 }
                 RendererEvent::RomLoaded(rom) => {
                     self.run_state = RunState::Running;
                     self.title = format!("{} :: {}", Config::WINDOW_TITLE, rom.name);
+                    self.region_mismatch.check(rom, &self.cfg);
+                    self.preferences
+                        .set_audio_channels(Some(rom.audio_channels.clone()));
+                    self.front_panel = FrontPanel::default();
+                    *self.annotations.lock() = Annotations::load(rom.crc32);
+                    self.memory_search.watch_annotated();
+                    self.tv_static.power_on();
                     self.loaded_rom = Some(rom.clone());
+                    if !self.failed_saves.is_empty() {
+                        self.pause_for_failed_saves();
+                    }
                 }
                 RendererEvent::Menu(menu) => match menu {
                     Menu::About => self.about_open = !self.about_open,
@@ -234,19 +465,55 @@ impl Gui {
                         self.tx
                             .event(EmulationEvent::ShowFrameStats(self.perf_stats_open));
                     }
+                    Menu::CpuDebugger => self.cpu_debugger.toggle_open(),
                     Menu::PpuViewer => self.ppu_viewer.toggle_open(),
+                    Menu::InterruptLog => self.interrupt_log.toggle_open(),
+                    Menu::MemorySearch => self.memory_search.toggle_open(),
                     Menu::Preferences => self.preferences.toggle_open(),
+                    Menu::QuickSettings => self.quick_settings_open = !self.quick_settings_open,
+                    Menu::Bookmarks => self.bookmarks.toggle_open(),
                 },
                 _ => (),
             },
+            NesEvent::Debug(DebugEvent::Cpu(debug_state)) => {
+                self.cpu_debugger.update_state(debug_state.clone());
+                self.ctx.request_repaint_of(self.cpu_debugger.id());
+            }
             NesEvent::Debug(DebugEvent::Ppu(ppu)) => {
-                self.ppu_viewer.update_ppu(queue, std::mem::take(ppu));
+                self.ppu_viewer.update_ppu(queue, *std::mem::take(ppu));
                 self.ctx.request_repaint_of(self.ppu_viewer.id());
             }
+            NesEvent::Debug(DebugEvent::Interrupts(interrupts)) => {
+                self.interrupt_log
+                    .push_interrupts(std::mem::take(interrupts));
+                self.ctx.request_repaint_of(self.interrupt_log.id());
+            }
+            NesEvent::Debug(DebugEvent::MemorySnapshots(snapshots)) => {
+                let (before, after) = &**snapshots;
+                self.memory_search.update_snapshots(before, after);
+                self.ctx.request_repaint_of(self.memory_search.id());
+            }
+            NesEvent::Debug(DebugEvent::MemoryWatch(values)) => {
+                self.memory_search.update_watches(values);
+                self.ctx.request_repaint_of(self.memory_search.id());
+            }
+            #[cfg(feature = "debug")]
+            NesEvent::Debug(DebugEvent::BusActivity((activity, frames))) => {
+                self.bus_heatmap
+                    .update_activity(std::mem::take(activity), *frames);
+                self.ctx.request_repaint_of(self.bus_heatmap.id());
+            }
             _ => (),
         }
     }
 
+    fn pause_for_failed_saves(&mut self) {
+        if self.loaded_rom.is_some() && !self.run_state.paused() {
+            self.run_state = RunState::Paused;
+            self.tx.event(EmulationEvent::RunState(self.run_state));
+        }
+    }
+
     pub fn add_message<S>(&mut self, ty: MessageType, text: S)
     where
         S: Into<String>,
@@ -265,16 +532,29 @@ impl Gui {
         self.loaded_rom.as_ref().map(|rom| rom.region)
     }
 
-    pub fn aspect_ratio(&self) -> f32 {
+    /// The region being emulated, accounting for any region chosen for the loaded ROM.
+    pub fn region(&self) -> NesRegion {
         let region = self
-            .cfg
-            .deck
-            .region
+            .loaded_rom
+            .as_ref()
+            .and_then(|rom| self.cfg.emulation.rom_regions.get(&rom.name))
+            .copied()
+            .unwrap_or(self.cfg.deck.region);
+        region
             .is_auto()
             .then(|| self.loaded_region())
             .flatten()
-            .unwrap_or(self.cfg.deck.region);
-        region.aspect_ratio()
+            .unwrap_or(region)
+    }
+
+    /// Whether overscan is hidden, accounting for any override for the loaded ROM.
+    pub fn hide_overscan(&self) -> bool {
+        self.cfg
+            .hide_overscan(self.loaded_rom.as_ref().map(|rom| rom.name.as_str()))
+    }
+
+    pub fn aspect_ratio(&self) -> f32 {
+        self.region().aspect_ratio()
     }
 
     pub fn prepare(&mut self, gamepads: &Gamepads, cfg: &Config) {
@@ -298,7 +578,7 @@ impl Gui {
         }
 
         let viewport_opts = ViewportOptions {
-            enabled: !self.keybinds.wants_input(),
+            enabled: !self.keybinds.wants_input() && self.failed_saves.is_empty(),
             always_on_top: self.cfg.renderer.always_on_top,
         };
 
@@ -310,13 +590,60 @@ impl Gui {
 
         self.preferences.show(ctx, viewport_opts);
         self.keybinds.show(ctx, viewport_opts);
+        self.cpu_debugger.show(ctx, viewport_opts);
         self.ppu_viewer.show(ctx, viewport_opts);
+        self.interrupt_log.show(ctx, viewport_opts);
+        self.memory_search.show(ctx, viewport_opts);
+        #[cfg(feature = "debug")]
+        self.bus_heatmap.show(ctx, viewport_opts);
 
         self.show_about_window(ctx, viewport_opts.enabled);
         self.show_about_homebrew_window(ctx, viewport_opts.enabled);
 
         self.show_performance_window(ctx, viewport_opts.enabled);
         self.show_update_window(ctx, viewport_opts.enabled);
+        self.show_replay_info_window(ctx, viewport_opts.enabled);
+        self.state_transfer.show(ctx, viewport_opts.enabled);
+        self.input_test.show(ctx, viewport_opts.enabled);
+        self.bookmarks
+            .show(ctx, viewport_opts.enabled, self.loaded_rom.is_some());
+        self.save_states.show(
+            ctx,
+            viewport_opts.enabled,
+            self.loaded_rom.is_some(),
+            self.cfg.emulation.save_slot,
+        );
+        self.netplay.show(
+            ctx,
+            viewport_opts.enabled,
+            self.loaded_rom.is_some(),
+            &self.cfg,
+        );
+        self.import.show(ctx, viewport_opts.enabled, &self.cfg);
+        self.cheat_files.show(
+            ctx,
+            viewport_opts.enabled,
+            &self.cfg,
+            self.loaded_rom.as_ref().map(|rom| rom.name.as_str()),
+        );
+        self.rom_audit.show(ctx, viewport_opts.enabled);
+        self.add_subtitle
+            .show(ctx, viewport_opts.enabled, self.replay_recording);
+        self.seek
+            .show(ctx, viewport_opts.enabled, self.loaded_rom.is_some());
+        self.rewind_scrubber
+            .show(ctx, viewport_opts.enabled, self.loaded_rom.is_some());
+        self.compare_replays
+            .show(ctx, viewport_opts.enabled, self.loaded_rom.is_some());
+        self.region_mismatch.show(ctx, viewport_opts.enabled);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.save_password.show(ctx, viewport_opts.enabled);
+        self.failed_saves.show(ctx);
+        self.show_quick_settings_window(ctx, viewport_opts.enabled);
+
+        if self.gamepad_navigation() {
+            self.gamepad_nav.show(ctx);
+        }
 
         Self::show_viewport(
             "🔧 UI Settings",
@@ -375,36 +702,40 @@ impl Gui {
             scroll.bar_width = 8.0;
         });
 
-        const FONT: (&str, &[u8]) = (
-            "pixeloid-sans",
-            include_bytes!("../../../assets/pixeloid-sans.ttf"),
-        );
-        const BOLD_FONT: (&str, &[u8]) = (
-            "pixeloid-sans-bold",
-            include_bytes!("../../../assets/pixeloid-sans-bold.ttf"),
-        );
-        const MONO_FONT: (&str, &[u8]) = (
-            "pixeloid-mono",
-            include_bytes!("../../../assets/pixeloid-mono.ttf"),
-        );
-
         egui_extras::install_image_loaders(ctx);
 
-        let mut fonts = FontDefinitions::default();
-        for (name, data) in [FONT, BOLD_FONT, MONO_FONT] {
-            let font_data = FontData::from_static(data);
-            fonts.font_data.insert(name.to_string(), font_data);
-        }
-
-        match fonts.families.get_mut(&FontFamily::Proportional) {
-            Some(font) => font.insert(0, FONT.0.to_string()),
-            None => tracing::warn!("failed to set proportional font"),
-        }
-        match fonts.families.get_mut(&FontFamily::Monospace) {
-            Some(font) => font.insert(0, MONO_FONT.0.to_string()),
-            None => tracing::warn!("failed to set monospace font"),
+        cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                // Fonts are fetched to keep them out of the wasm binary. Default fonts are used
+                // until they finish loading.
+                let ctx = ctx.clone();
+                crate::thread::spawn(async move {
+                    let mut fonts = Vec::with_capacity(FONTS.len());
+                    for name in FONTS {
+                        match crate::platform::fetch_asset(&format!("{name}.ttf")).await {
+                            Ok(data) => fonts.push((name, FontData::from_owned(data))),
+                            Err(err) => {
+                                tracing::error!("failed to fetch font {name}: {err:?}");
+                                return;
+                            }
+                        }
+                    }
+                    ctx.set_fonts(Self::font_definitions(fonts));
+                });
+            } else {
+                let fonts = [
+                    include_bytes!("../../../assets/pixeloid-sans.ttf").as_slice(),
+                    include_bytes!("../../../assets/pixeloid-sans-bold.ttf"),
+                    include_bytes!("../../../assets/pixeloid-mono.ttf"),
+                ];
+                ctx.set_fonts(Self::font_definitions(
+                    FONTS
+                        .into_iter()
+                        .zip(fonts)
+                        .map(|(name, data)| (name, FontData::from_static(data))),
+                ));
+            }
         }
-        ctx.set_fonts(fonts);
 
         // Check for update on start
         if self.version.requires_updates() {
@@ -415,6 +746,25 @@ impl Gui {
         self.initialized = true;
     }
 
+    fn font_definitions(
+        fonts: impl IntoIterator<Item = (&'static str, FontData)>,
+    ) -> FontDefinitions {
+        let mut definitions = FontDefinitions::default();
+        for (name, data) in fonts {
+            definitions.font_data.insert(name.to_string(), data);
+        }
+
+        match definitions.families.get_mut(&FontFamily::Proportional) {
+            Some(font) => font.insert(0, FONT.to_string()),
+            None => tracing::warn!("failed to set proportional font"),
+        }
+        match definitions.families.get_mut(&FontFamily::Monospace) {
+            Some(font) => font.insert(0, MONO_FONT.to_string()),
+            None => tracing::warn!("failed to set monospace font"),
+        }
+        definitions
+    }
+
     fn show_about_window(&mut self, ctx: &Context, enabled: bool) {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
@@ -525,6 +875,193 @@ impl Gui {
         });
     }
 
+    fn show_quick_settings_window(&mut self, ctx: &Context, enabled: bool) {
+        if !self.quick_settings_open {
+            return;
+        }
+
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let mut quick_settings_open = true;
+        egui::Window::new("⚡ Quick Settings")
+            .open(&mut quick_settings_open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| self.quick_settings(ui))
+            });
+        if !quick_settings_open {
+            self.quick_settings_open = false;
+        }
+    }
+
+    fn quick_settings(&mut self, ui: &mut Ui) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let tx = &self.tx;
+        let cfg = &self.cfg;
+        let rom_loaded = self.loaded_rom.is_some();
+
+        Grid::new("quick_settings")
+            .num_columns(2)
+            .spacing([20.0, 6.0])
+            .show(ui, |ui| {
+                ui.strong("Emulation:");
+                ui.horizontal(|ui| {
+                    ui.add_enabled_ui(rom_loaded, |ui| {
+                        let label = if self.run_state.paused() {
+                            "▶ Resume"
+                        } else {
+                            "⏸ Pause"
+                        };
+                        let res = ui.button(label).on_disabled_hover_text(Self::NO_ROM_LOADED);
+                        if res.clicked() {
+                            self.run_state = match self.run_state {
+                                RunState::Running => RunState::ManuallyPaused,
+                                RunState::ManuallyPaused | RunState::Paused => RunState::Running,
+                            };
+                            tx.event(EmulationEvent::RunState(self.run_state));
+                        }
+                    });
+                    let label = if cfg.audio.enabled {
+                        "🔇 Mute"
+                    } else {
+                        "🔊 Unmute"
+                    };
+                    if ui.button(label).clicked() {
+                        tx.event(ConfigEvent::AudioEnabled(!cfg.audio.enabled));
+                    }
+                });
+                ui.end_row();
+
+                if feature!(Storage) {
+                    ui.strong("Save Slot:");
+                    ui.horizontal(|ui| {
+                        for slot in 1..=8 {
+                            let selected = cfg.emulation.save_slot == slot;
+                            if ui.selectable_label(selected, slot.to_string()).clicked() {
+                                tx.event(ConfigEvent::SaveSlot(slot));
+                            }
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("");
+                    ui.horizontal(|ui| {
+                        ui.add_enabled_ui(rom_loaded, |ui| {
+                            let res = ui
+                                .button("💾 Save State")
+                                .on_disabled_hover_text(Self::NO_ROM_LOADED);
+                            if res.clicked() {
+                                tx.event(EmulationEvent::SaveState(cfg.emulation.save_slot));
+                            }
+                            let res = ui
+                                .button("⎗ Load State")
+                                .on_disabled_hover_text(Self::NO_ROM_LOADED);
+                            if res.clicked() {
+                                tx.event(EmulationEvent::LoadState(cfg.emulation.save_slot));
+                            }
+                        });
+                    });
+                    ui.end_row();
+                }
+
+                ui.strong("Speed:");
+                Preferences::speed_slider(tx, ui, cfg.emulation.speed);
+                ui.end_row();
+
+                ui.strong("Video Filter:");
+                ui.horizontal(|ui| Preferences::video_filter_radio(tx, ui, cfg.deck.filter));
+                ui.end_row();
+
+                ui.strong("Shaders:");
+                ui.horizontal(|ui| Preferences::shader_checkboxes(tx, ui, &cfg.renderer.shader));
+                ui.end_row();
+
+                ui.strong("Rewind:");
+                Preferences::rewind_checkbox(tx, ui, cfg.emulation.rewind, None);
+                ui.end_row();
+            });
+    }
+
+    fn show_replay_info_window(&mut self, ctx: &Context, enabled: bool) {
+        let Some(info) = &self.replay_info else {
+            return;
+        };
+
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let mut replay_info_open = true;
+        let mut play = None;
+        egui::Window::new("📼 Replay Info")
+            .open(&mut replay_info_open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| {
+                    let unknown = |val: &str| {
+                        if val.is_empty() {
+                            "Unknown".to_string()
+                        } else {
+                            val.to_string()
+                        }
+                    };
+                    let grid = Grid::new("replay_info").num_columns(2).spacing([40.0, 6.0]);
+                    grid.show(ui, |ui| {
+                        ui.strong("Author:");
+                        ui.label(unknown(info.author.as_str()));
+                        ui.end_row();
+
+                        ui.strong("Description:");
+                        ui.label(unknown(info.description.as_str()));
+                        ui.end_row();
+
+                        ui.strong("Version:");
+                        ui.label(unknown(info.version.as_str()));
+                        ui.end_row();
+
+                        ui.strong("Rerecords:");
+                        ui.label(format!("{}", info.rerecords));
+                        ui.end_row();
+
+                        ui.strong("Events:");
+                        ui.label(format!("{}", info.events));
+                        ui.end_row();
+                    });
+
+                    ui.add_space(15.0);
+                    ui.horizontal(|ui| {
+                        if ui.button("Play").clicked() {
+                            play = Some(true);
+                        }
+                        if ui.button("Cancel").clicked() {
+                            play = Some(false);
+                        }
+                    });
+                });
+            });
+
+        if !replay_info_open {
+            play = Some(false);
+        }
+        match play {
+            Some(true) => {
+                self.run_state = RunState::Running;
+                self.tx.event(EmulationEvent::RunState(self.run_state));
+                self.replay_info = None;
+            }
+            Some(false) => {
+                self.run_state = RunState::Running;
+                self.tx.event(EmulationEvent::StopReplay);
+                self.replay_info = None;
+            }
+            None => (),
+        }
+    }
+
     fn show_update_window(&mut self, ctx: &Context, enabled: bool) {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
@@ -704,6 +1241,50 @@ impl Gui {
                 }
             });
 
+            let res = ui
+                .button("📥 Import from Other Emulators...")
+                .on_hover_text("Import battery saves from FCEUX, Nestopia or Mesen.");
+            if res.clicked() {
+                self.import.open();
+                ui.close_menu();
+            }
+
+            let res = ui
+                .button("🔍 Audit ROM Set...")
+                .on_hover_text("Check a ROM folder against a No-Intro DAT file.");
+            if res.clicked() {
+                self.rom_audit.open(cfg);
+                ui.close_menu();
+            }
+
+            match &cfg.emulation.watch_folder {
+                Some(dir) => {
+                    let res = ui
+                        .button("👁 Stop Watching Folder")
+                        .on_hover_text(format!("Stop loading new ROMs from {}.", dir.display()));
+                    if res.clicked() {
+                        tx.event(ConfigEvent::WatchFolder(None));
+                        ui.close_menu();
+                    }
+                }
+                None => {
+                    let res = ui.button("👁 Watch Folder...").on_hover_text(
+                        "Load the newest ROM written to a folder automatically, e.g. homebrew builds.",
+                    );
+                    if res.clicked() {
+                        match open_dir_dialog(
+                            "Select Folder to Watch",
+                            cfg.renderer.roms_path.as_ref(),
+                        ) {
+                            Ok(Some(dir)) => tx.event(ConfigEvent::WatchFolder(Some(dir))),
+                            Ok(None) => (),
+                            Err(err) => error!("failed to open directory dialog: {err:?}"),
+                        }
+                        ui.close_menu();
+                    }
+                }
+            }
+
             ui.separator();
         }
 
@@ -730,6 +1311,144 @@ impl Gui {
                 }
             });
 
+            let mut save_states_open = self.save_states.open();
+            let toggle = ToggleValue::new(&mut save_states_open, "💾 Save States");
+            let res = ui.add(toggle).on_hover_text(
+                "List the save states for this game with thumbnails, when they were saved and play time.",
+            );
+            if res.clicked() {
+                self.save_states.set_open(save_states_open);
+                ui.close_menu();
+            }
+
+            ui.add_enabled_ui(self.loaded_rom.is_some(), |ui| {
+                let slot = cfg.emulation.save_slot;
+                let quick_slot = self
+                    .quick_slots
+                    .get(usize::from(slot).saturating_sub(1))
+                    .copied()
+                    .unwrap_or_default();
+
+                let button = Button::new("⚡ Quick Save State")
+                    .shortcut_text(cfg.shortcut(Feature::QuickSaveState));
+                let res = ui
+                    .add(button)
+                    .on_hover_text(
+                        "Save the current state in memory to the selected slot without writing to disk.",
+                    )
+                    .on_disabled_hover_text(Self::NO_ROM_LOADED);
+                if res.clicked() {
+                    tx.event(EmulationEvent::QuickSaveState(slot));
+                }
+
+                let button = Button::new("⚡ Quick Load State")
+                    .shortcut_text(cfg.shortcut(Feature::QuickLoadState));
+                let res = ui
+                    .add_enabled(quick_slot != QuickSlot::Empty, button)
+                    .on_hover_text("Load the state saved in memory to the selected slot.")
+                    .on_disabled_hover_text("No quick state saved to the selected slot.");
+                if res.clicked() {
+                    tx.event(EmulationEvent::QuickLoadState(slot));
+                }
+
+                let button = Button::new("💾 Persist Quick State")
+                    .shortcut_text(cfg.shortcut(Feature::PersistQuickState));
+                let res = ui
+                    .add_enabled(quick_slot == QuickSlot::Unsaved, button)
+                    .on_hover_text(
+                        "Write the quick state in the selected slot to disk, replacing its save state.",
+                    )
+                    .on_disabled_hover_text("No unsaved quick state in the selected slot.");
+                if res.clicked() {
+                    tx.event(EmulationEvent::PersistQuickState(slot));
+                }
+
+                let button = Button::new("📍 Save Checkpoint")
+                    .shortcut_text(cfg.shortcut(Feature::SaveCheckpoint));
+                let res = ui
+                    .add(button)
+                    .on_hover_text(
+                        "Save the current state in memory to a ring of recent checkpoints, independent of save slots.",
+                    )
+                    .on_disabled_hover_text(Self::NO_ROM_LOADED);
+                if res.clicked() {
+                    tx.event(EmulationEvent::SaveCheckpoint);
+                }
+
+                let button = Button::new("📍 Load Previous Checkpoint")
+                    .shortcut_text(cfg.shortcut(Feature::LoadPreviousCheckpoint));
+                let res = ui
+                    .add(button)
+                    .on_hover_text(
+                        "Load the most recent checkpoint, or the one before it when repeated.",
+                    )
+                    .on_disabled_hover_text(Self::NO_ROM_LOADED);
+                if res.clicked() {
+                    tx.event(EmulationEvent::LoadPreviousCheckpoint);
+                }
+
+                let button =
+                    Button::new("🔖 Add Bookmark").shortcut_text(cfg.shortcut(Feature::AddBookmark));
+                let res = ui
+                    .add(button)
+                    .on_hover_text(
+                        "Bookmark the current frame with a save state and note to jump back to later.",
+                    )
+                    .on_disabled_hover_text(Self::NO_ROM_LOADED);
+                if res.clicked() {
+                    tx.event(EmulationEvent::AddBookmark);
+                }
+            });
+
+            let mut bookmarks_open = self.bookmarks.open();
+            let toggle = ToggleValue::new(&mut bookmarks_open, "🔖 Bookmarks")
+                .shortcut_text(cfg.shortcut(Menu::Bookmarks));
+            let res = ui
+                .add(toggle)
+                .on_hover_text("List bookmarked frames with thumbnails to jump back to them.");
+            if res.clicked() {
+                self.bookmarks.set_open(bookmarks_open);
+                ui.close_menu();
+            }
+
+            let state_transfer = &mut self.state_transfer;
+            let netplay = &mut self.netplay;
+            ui.add_enabled_ui(self.loaded_rom.is_some(), |ui| {
+                if feature!(LocalServer) {
+                    let res = ui
+                        .button("📤 Send State to Device...")
+                        .on_hover_text(concat!(
+                            "Share the current state with another device on the local network ",
+                            "using a one-time link."
+                        ))
+                        .on_disabled_hover_text(Self::NO_ROM_LOADED);
+                    if res.clicked() {
+                        tx.event(EmulationEvent::SendState);
+                        ui.close_menu();
+                    }
+                }
+
+                let res = ui
+                    .button("📥 Receive State...")
+                    .on_hover_text("Load a state sent from another device.")
+                    .on_disabled_hover_text(Self::NO_ROM_LOADED);
+                if res.clicked() {
+                    state_transfer.open_receive();
+                    ui.close_menu();
+                }
+
+                if feature!(Netplay) {
+                    let res = ui
+                        .button("🌐 Netplay...")
+                        .on_hover_text("Host or join a game with someone on another device.")
+                        .on_disabled_hover_text(Self::NO_ROM_LOADED);
+                    if res.clicked() {
+                        netplay.open();
+                        ui.close_menu();
+                    }
+                }
+            });
+
             // icon: # in a square
             ui.menu_button("󾠬 Save Slot...", |ui| {
                 Preferences::save_slot_radio(
@@ -761,8 +1480,7 @@ impl Gui {
             for rom in HOMEBREW_ROMS {
                 ui.horizontal(|ui| {
                     if ui.button(rom.name).clicked() {
-                        self.tx
-                            .event(EmulationEvent::LoadRom((rom.name.to_string(), rom.data())));
+                        rom.load(&self.tx);
                         ui.close_menu();
                     }
                     let res = ui.button("ℹ").on_hover_ui(|ui| {
@@ -784,6 +1502,7 @@ impl Gui {
 
         let tx = &self.tx;
         let cfg = &self.cfg;
+        let hide_overscan = self.hide_overscan();
 
         ui.add_enabled_ui(self.loaded_rom.is_some(), |ui| {
             let button = Button::new(if self.run_state.paused() {
@@ -832,8 +1551,37 @@ impl Gui {
                     tx.event(EmulationEvent::InstantRewind);
                     ui.close_menu();
                 };
+
+                let res = ui
+                    .button("⟲ Rewind Timeline...")
+                    .on_hover_text("Scrub through recent rewind states to pick one to rewind to.")
+                    .on_disabled_hover_text(disabled_hover_text);
+                if res.clicked() {
+                    self.rewind_scrubber.toggle_open();
+                    ui.close_menu();
+                };
             });
 
+            let res = ui
+                .button("⏩ Seek to Frame...")
+                .on_hover_text("Fast-forward or rewind to a frame number.")
+                .on_disabled_hover_text(Self::NO_ROM_LOADED);
+            if res.clicked() {
+                self.seek.toggle_open();
+                ui.close_menu();
+            };
+
+            if feature!(Filesystem) {
+                let res = ui
+                    .button("🔀 Compare Replays...")
+                    .on_hover_text("Find the first frame where two replays of this game diverge.")
+                    .on_disabled_hover_text(Self::NO_ROM_LOADED);
+                if res.clicked() {
+                    self.compare_replays.open();
+                    ui.close_menu();
+                };
+            }
+
             let button = Button::new("🔃 Reset")
                 .shortcut_text(cfg.shortcut(DeckAction::Reset(ResetKind::Soft)));
             let res = ui
@@ -855,6 +1603,56 @@ impl Gui {
                 tx.event(EmulationEvent::Reset(ResetKind::Hard));
                 ui.close_menu();
             };
+
+            ui.separator();
+
+            let button = Button::new(if self.front_panel.powered {
+                "⏻ Power Off"
+            } else {
+                "⏻ Power On"
+            })
+            .shortcut_text(cfg.shortcut(DeckAction::TogglePower));
+            let res = ui
+                .add(button)
+                .on_hover_text("Flip the power switch, leaving the game inserted.")
+                .on_disabled_hover_text(Self::NO_ROM_LOADED);
+            if res.clicked() {
+                tx.event(EmulationEvent::TogglePower);
+                ui.close_menu();
+            };
+
+            let button =
+                Button::new("⏼ Reset Button").shortcut_text(cfg.shortcut(DeckAction::HoldReset));
+            let res = ui
+                .add_enabled(self.front_panel.powered, button)
+                .on_hover_text("Hold to keep the NES in reset, resetting once released.")
+                .on_disabled_hover_text("The power is off.");
+            let pressed = res.is_pointer_button_down_on();
+            if pressed != self.front_panel.reset_held {
+                tx.event(EmulationEvent::ResetButton(pressed));
+            }
+
+            let has_expansion_audio = self.loaded_rom.as_ref().is_some_and(|rom| {
+                rom.audio_channels
+                    .iter()
+                    .any(|audio_channel| audio_channel.channel == Channel::Mapper)
+            });
+            let mut expansion_audio = cfg.deck.channels_enabled[Channel::Mapper as usize];
+            let toggle = ToggleValue::new(&mut expansion_audio, "🎵 Expansion Audio")
+                .shortcut_text(cfg.shortcut(DeckAction::ToggleApuChannel(Channel::Mapper)));
+            let res = ui
+                .add_enabled(has_expansion_audio, toggle)
+                .on_hover_text(
+                    "Mix cartridge expansion audio, as a Famicom does. \
+                    An NES requires a hardware modification.",
+                )
+                .on_disabled_hover_text("The loaded game has no expansion audio.");
+            if res.clicked() {
+                tx.event(ConfigEvent::ApuChannelEnabled((
+                    Channel::Mapper,
+                    expansion_audio,
+                )));
+            }
         });
 
         if feature!(Filesystem) {
@@ -869,6 +1667,34 @@ impl Gui {
                     ui.close_menu();
                 };
 
+                let button =
+                    Button::new("🎞 Save Clip").shortcut_text(cfg.shortcut(Feature::SaveClip));
+                let res = ui
+                    .add(button)
+                    .on_hover_text(format!(
+                        "Save the last {} seconds of gameplay as an animated {} in your \
+                        pictures folder.",
+                        cfg.renderer.clip_seconds,
+                        cfg.renderer.clip_format.as_str(),
+                    ))
+                    .on_disabled_hover_text(Self::NO_ROM_LOADED);
+                if res.clicked() {
+                    tx.event(RendererEvent::SaveClip);
+                    ui.close_menu();
+                };
+
+                let res = ui
+                    .button("🖼 Export Save State Gallery")
+                    .on_hover_text(
+                        "Save a screenshot of every save state for this game, labeled with \
+                        its slot and when it was saved, to a new folder in your pictures folder.",
+                    )
+                    .on_disabled_hover_text(Self::NO_ROM_LOADED);
+                if res.clicked() {
+                    tx.event(EmulationEvent::ExportSaveStateGallery);
+                    ui.close_menu();
+                };
+
                 let button_txt = if self.replay_recording {
                     "⏹ Stop Replay Recording"
                 } else {
@@ -885,6 +1711,32 @@ impl Gui {
                     ui.close_menu();
                 };
 
+                let button = Button::new(format!(
+                    "⏺ Save Last {} Minute(s)",
+                    cfg.emulation.background_replay_minutes
+                ))
+                .shortcut_text(cfg.shortcut(Feature::SaveBackgroundReplay));
+                let res = ui
+                    .add_enabled(cfg.emulation.background_replay, button)
+                    .on_hover_text("Save the recent inputs kept by background recording as a replay.")
+                    .on_disabled_hover_text(
+                        "Enable background recording in the Emulation Preferences to save recent play.",
+                    );
+                if res.clicked() {
+                    tx.event(EmulationEvent::SaveBackgroundReplay);
+                    ui.close_menu();
+                };
+
+                if self.replay_recording {
+                    let res = ui
+                        .button("💬 Add Subtitle...")
+                        .on_hover_text("Add a subtitle to the replay being recorded.");
+                    if res.clicked() {
+                        self.add_subtitle.open();
+                        ui.close_menu();
+                    }
+                }
+
                 let button_txt = if self.audio_recording {
                     "⏹ Stop Audio Recording"
                 } else {
@@ -900,8 +1752,68 @@ impl Gui {
                     tx.event(EmulationEvent::AudioRecord(!self.audio_recording));
                     ui.close_menu();
                 };
+
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    let button_txt = if self.video_recording {
+                        "⏹ Stop Video Recording"
+                    } else {
+                        "🎥 Record Video"
+                    };
+                    let res = ui
+                        .button(button_txt)
+                        .on_hover_text("Record or stop recording a video file, using ffmpeg.")
+                        .on_disabled_hover_text(Self::NO_ROM_LOADED);
+                    if res.clicked() {
+                        tx.event(EmulationEvent::VideoRecord(!self.video_recording));
+                        ui.close_menu();
+                    };
+                }
             });
         }
+
+        ui.separator();
+
+        ui.add_enabled_ui(self.loaded_rom.is_some(), |ui| {
+            ui.menu_button("📋 Copy", |ui| {
+                if feature!(CopyImage) {
+                    let button =
+                        Button::new("Frame").shortcut_text(cfg.shortcut(Feature::CopyFrame));
+                    let res = ui
+                        .add(button)
+                        .on_hover_text("Copy the current frame at its native resolution.");
+                    if res.clicked() {
+                        tx.event(EmulationEvent::CopyFrame(FrameCopy::Native));
+                        ui.close_menu();
+                    }
+
+                    let button = Button::new("Displayed Frame")
+                        .shortcut_text(cfg.shortcut(Feature::CopyDisplayedFrame));
+                    let res = ui
+                        .add(button)
+                        .on_hover_text("Copy the current frame as shown in the window.");
+                    if res.clicked() {
+                        tx.event(EmulationEvent::CopyFrame(FrameCopy::Displayed {
+                            hide_overscan,
+                            scale: cfg.renderer.scale,
+                        }));
+                        ui.close_menu();
+                    }
+                }
+
+                let button = Button::new("ROM Name and Frame")
+                    .shortcut_text(cfg.shortcut(Feature::CopyFrameInfo));
+                let res = ui
+                    .add(button)
+                    .on_hover_text("Copy the ROM name and current frame number as text.");
+                if res.clicked() {
+                    tx.event(EmulationEvent::CopyFrameInfo);
+                    ui.close_menu();
+                }
+            })
+            .response
+            .on_disabled_hover_text(Self::NO_ROM_LOADED);
+        });
     }
 
     fn config_menu(&mut self, ui: &mut Ui) {
@@ -935,6 +1847,15 @@ impl Gui {
             cfg.renderer.hide_overscan,
             cfg.shortcut(Setting::ToggleOverscan),
         );
+        if let Some(rom) = &self.loaded_rom {
+            Preferences::rom_overscan_checkbox(
+                tx,
+                ui,
+                cfg,
+                &rom.name,
+                cfg.shortcut(Setting::ToggleRomOverscan),
+            );
+        }
 
         ui.separator();
 
@@ -969,7 +1890,7 @@ impl Gui {
             Preferences::video_filter_radio(tx, ui, cfg.deck.filter);
         });
         ui.menu_button("🕶 Shader...", |ui| {
-            Preferences::shader_radio(tx, ui, cfg.renderer.shader);
+            Preferences::shader_checkboxes(tx, ui, &cfg.renderer.shader);
         });
         ui.menu_button("🌎 Nes Region...", |ui| {
             Preferences::nes_region_radio(tx, ui, cfg.deck.region);
@@ -977,9 +1898,20 @@ impl Gui {
         ui.menu_button("🎮 Four Player...", |ui| {
             Preferences::four_player_radio(tx, ui, cfg.deck.four_player);
         });
+        ui.menu_button("🖱 Mouse...", |ui| {
+            Preferences::mouse_port_radio(tx, ui, cfg.deck.mouse);
+        });
         ui.menu_button("📓 Game Genie Codes...", |ui| {
             self.preferences.show_genie_codes_entry(ui, cfg);
 
+            let res = ui
+                .button("📄 Cheat Files...")
+                .on_hover_text("Import or export RetroArch, Nestopia or FCEUX cheat lists.");
+            if res.clicked() {
+                self.cheat_files.open();
+                ui.close_menu();
+            }
+
             ui.separator();
 
             Preferences::genie_codes_list(tx, ui, cfg, true);
@@ -1004,6 +1936,16 @@ impl Gui {
             self.keybinds.set_open(keybinds_open);
             ui.close_menu();
         };
+
+        let mut input_test_open = self.input_test.open();
+        let toggle = ToggleValue::new(&mut input_test_open, "🎮 Input Test");
+        let res = ui
+            .add(toggle)
+            .on_hover_text("Show inputs as they arrive and measure reaction latency.");
+        if res.clicked() {
+            self.input_test.set_open(input_test_open);
+            ui.close_menu();
+        };
     }
 
     fn window_menu(&mut self, ui: &mut Ui) {
@@ -1020,6 +1962,7 @@ impl Gui {
             always_on_top,
             show_menubar,
             show_messages,
+            translucent,
             ..
         } = cfg.renderer;
 
@@ -1041,6 +1984,21 @@ impl Gui {
             }
 
             Preferences::window_scale_radio(tx, ui, cfg.renderer.scale);
+
+            ui.separator();
+
+            for scale in [2, 3, 4] {
+                let button = Button::new(format!("Resize to {scale}x"))
+                    .shortcut_text(cfg.shortcut(Setting::ResizeToScale(scale)));
+                let res = ui
+                    .add(button)
+                    .on_hover_text("Resize the window to fit the game at this scale exactly.");
+                if res.clicked() {
+                    tx.event(ConfigEvent::Scale(f32::from(scale)));
+                    ui.close_menu();
+                }
+            }
+            Preferences::snap_window_size_checkbox(tx, ui, cfg.renderer.snap_window_size);
         });
         egui::gui_zoom::zoom_menu_buttons(ui);
 
@@ -1049,11 +2007,29 @@ impl Gui {
         Preferences::fullscreen_checkbox(tx, ui, fullscreen, cfg.shortcut(ToggleFullscreen));
         Preferences::embed_viewports_checkbox(tx, ui, cfg, cfg.shortcut(ToggleEmbedViewports));
         Preferences::always_on_top_checkbox(tx, ui, always_on_top, cfg.shortcut(ToggleAlwaysOnTop));
+        Preferences::translucent_checkbox(tx, ui, translucent, cfg.shortcut(ToggleTranslucent));
+        if translucent {
+            ui.indent("window_opacity", |ui| {
+                Preferences::window_opacity_slider(tx, ui, cfg.renderer.window_opacity);
+            });
+        }
 
         ui.separator();
 
         Preferences::menubar_checkbox(tx, ui, show_menubar, cfg.shortcut(ToggleMenubar));
         Preferences::messages_checkbox(tx, ui, show_messages, cfg.shortcut(ToggleMessages));
+        Preferences::audio_scope_checkbox(
+            tx,
+            ui,
+            cfg.renderer.show_audio_scope,
+            cfg.shortcut(ToggleAudioScope),
+        );
+        Preferences::overscan_outline_checkbox(
+            tx,
+            ui,
+            cfg.renderer.show_overscan_outline,
+            cfg.shortcut(ToggleOverscanOutline),
+        );
         if feature!(ScreenReader) {
             Preferences::screen_reader_checkbox(ui, cfg.shortcut(ToggleScreenReader));
         }
@@ -1130,18 +2106,17 @@ impl Gui {
 
         ui.separator();
 
-        ui.add_enabled_ui(false, |ui| {
-            let debugger_shortcut = cfg.shortcut(Debug::Toggle(DebugKind::Cpu));
-            let toggle = ToggleValue::new(&mut self.debugger_open, "🚧 Debugger")
-                .shortcut_text(debugger_shortcut);
-            let res = ui
-                .add(toggle)
-                .on_hover_text("Toggle the Debugger.")
-                .on_disabled_hover_text("Not yet implemented.");
-            if res.clicked() {
-                ui.close_menu();
-            }
-        });
+        let debugger_shortcut = cfg.shortcut(Debug::Toggle(DebugKind::Cpu));
+        let mut open = self.cpu_debugger.open();
+        let toggle =
+            ToggleValue::new(&mut open, "🐞 CPU Debugger").shortcut_text(debugger_shortcut);
+        let res = ui
+            .add(toggle)
+            .on_hover_text("Toggle the CPU Debugger with disassembly, registers and the stack.");
+        if res.clicked() {
+            self.cpu_debugger.set_open(open);
+            ui.close_menu();
+        }
 
         let ppu_viewer_shortcut = cfg.shortcut(Debug::Toggle(DebugKind::Ppu));
         let mut open = self.ppu_viewer.open();
@@ -1153,6 +2128,63 @@ impl Gui {
             ui.close_menu();
         }
 
+        let mut open = self.interrupt_log.open();
+        let toggle = ToggleValue::new(&mut open, "⚡ Interrupt Log")
+            .shortcut_text(cfg.shortcut(Menu::InterruptLog));
+        let res = ui
+            .add(toggle)
+            .on_hover_text("Toggle the Interrupt Log of recent NMIs and IRQs.");
+        if res.clicked() {
+            self.interrupt_log.set_open(open);
+            ui.close_menu();
+        }
+
+        let mut open = self.memory_search.open();
+        let toggle = ToggleValue::new(&mut open, "🔎 Memory Search")
+            .shortcut_text(cfg.shortcut(Menu::MemorySearch));
+        let res = ui
+            .add(toggle)
+            .on_hover_text("Toggle Memory Search to find RAM that changed between save states.");
+        if res.clicked() {
+            self.memory_search.set_open(open);
+            ui.close_menu();
+        }
+
+        ui.add_enabled_ui(self.loaded_rom.is_some(), |ui| {
+            let toggle = ToggleValue::new(&mut self.dmc_sample_log, "🎵 Log DMC Samples");
+            let res = ui
+                .add(toggle)
+                .on_hover_text("Log DPCM samples as the DMC plays them. Disabling clears the log.")
+                .on_disabled_hover_text(Self::NO_ROM_LOADED);
+            if res.clicked() {
+                tx.event(EmulationEvent::DmcSampleLog(self.dmc_sample_log));
+            }
+        });
+
+        ui.add_enabled_ui(self.dmc_sample_log, |ui| {
+            let res = ui
+                .button("💾 Export DMC Samples")
+                .on_hover_text("Export logged DMC samples as WAV files to the audio directory.")
+                .on_disabled_hover_text("Enable Log DMC Samples first.");
+            if res.clicked() {
+                tx.event(EmulationEvent::ExportDmcSamples);
+                ui.close_menu();
+            }
+        });
+
+        #[cfg(feature = "debug")]
+        {
+            let mut open = self.bus_heatmap.open();
+            let toggle = ToggleValue::new(&mut open, "🔥 Bus Heatmap");
+            let res = ui
+                .add(toggle)
+                .on_hover_text("Toggle the Bus Heatmap of CPU memory reads and writes.");
+            if res.clicked() {
+                self.bus_heatmap.set_open(open);
+                ui.close_menu();
+            }
+        }
+
         ui.add_enabled_ui(false, |ui| {
             let apu_mixer_shortcut = cfg.shortcut(Debug::Toggle(DebugKind::Apu));
             let toggle = ToggleValue::new(&mut self.apu_mixer_open, "🎼 APU Mixer")
@@ -1218,17 +2250,64 @@ impl Gui {
             if res.clicked() {
                 tx.event(EmulationEvent::DebugStep(DebugStep::Frame));
             }
+
+            let button = Button::new("⬅ Step Back")
+                .shortcut_text(cfg.shortcut(Debug::Step(DebugStep::Back)));
+            let res = ui
+                .add(button)
+                .on_hover_text("Undo the last CPU instruction stepped.")
+                .on_disabled_hover_text(Self::NO_ROM_LOADED);
+            if res.clicked() {
+                tx.event(EmulationEvent::DebugStep(DebugStep::Back));
+            }
+
+            ui.separator();
+
+            Preferences::frame_step_audio_checkbox(tx, ui, cfg.audio.frame_step_audio);
         });
     }
 
+    /// Forward pointer motion and buttons over the game to the mouse, scaled so the game's cursor
+    /// keeps pace with the pointer.
+    fn mouse_input(tx: &NesEventProxy, ui: &Ui, res: &egui::Response, remainder: &mut Vec2) {
+        if res.hovered() && res.rect.width() > 0.0 {
+            let delta = ui.input(|i| i.pointer.delta()) * (Ppu::WIDTH as f32 / res.rect.width());
+            let delta = delta + *remainder;
+            let counts = delta.round();
+            *remainder = delta - counts;
+            if counts != Vec2::ZERO {
+                let motion = (counts.x as i32, counts.y as i32);
+                tx.event(EmulationEvent::MouseMotion(motion));
+            }
+        }
+        for (button, pointer) in [
+            (MouseBtn::Left, PointerButton::Primary),
+            (MouseBtn::Right, PointerButton::Secondary),
+        ] {
+            if res.hovered() && ui.input(|i| i.pointer.button_pressed(pointer)) {
+                tx.event(EmulationEvent::MouseButton((button, true)));
+            } else if ui.input(|i| i.pointer.button_released(pointer)) {
+                tx.event(EmulationEvent::MouseButton((button, false)));
+            }
+        }
+    }
+
     fn nes_frame(&mut self, ui: &mut Ui, enabled: bool, gamepads: Option<&Gamepads>) {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
 
+        let hide_overscan = self.hide_overscan();
         ui.add_enabled_ui(enabled, |ui| {
             let tx = &self.tx;
+            let opacity = if self.cfg.renderer.translucent {
+                self.cfg.renderer.window_opacity
+            } else {
+                1.0
+            };
 
-            CentralPanel::default().show_inside(ui, |ui| {
+            let frame = Frame::central_panel(ui.style());
+            let frame = frame.fill(frame.fill.gamma_multiply(opacity));
+            CentralPanel::default().frame(frame).show_inside(ui, |ui| {
                 if self.loaded_rom.is_some() {
                     let layout = Layout {
                         main_dir: Direction::TopDown,
@@ -1239,16 +2318,48 @@ impl Gui {
                     ui.with_layout(layout, |ui| {
                         let image = Image::from_texture(self.nes_texture.sized())
                             .shrink_to_fit()
+                            .tint(Color32::WHITE.gamma_multiply(opacity))
                             .sense(Sense::click());
 
-                        let hover_cursor = if self.cfg.deck.zapper {
+                        // Games using the mouse draw their own cursor
+                        let hover_cursor = if self.cfg.deck.mouse != MousePort::Disabled {
+                            CursorIcon::None
+                        } else if self.cfg.deck.zapper {
                             CursorIcon::Crosshair
                         } else {
                             CursorIcon::Default
                         };
 
-                        let res = ui.add(image).on_hover_cursor(hover_cursor);
+                        let res = if self.cfg.renderer.pixel_perfect {
+                            let rect = pixel_perfect_rect(
+                                ui.available_rect_before_wrap(),
+                                self.nes_texture.size,
+                                self.nes_texture.aspect_ratio,
+                                ui.ctx().pixels_per_point(),
+                            );
+                            ui.put(rect, image.fit_to_exact_size(rect.size()))
+                        } else {
+                            ui.add(image)
+                        };
+                        let res = res.on_hover_cursor(hover_cursor);
                         self.nes_frame = res.rect;
+                        if self.cfg.renderer.tv_static {
+                            if let Some(opacity) = self.tv_static.power_on_opacity() {
+                                self.tv_static.draw(ui, res.rect, opacity);
+                            }
+                        }
+                        if let Some(text) = &self.replay_subtitle {
+                            subtitle::draw_subtitle(ui, res.rect, text);
+                        }
+                        if let Some(status) = &self.replay_hash {
+                            replay_hash::draw_hash_status(ui, res.rect, status);
+                        }
+                        if self.cfg.renderer.show_overscan_outline && !hide_overscan {
+                            overscan::draw_overscan_outline(ui, res.rect);
+                        }
+                        if self.cfg.renderer.show_audio_scope {
+                            self.audio_scope.draw(ui, res.rect);
+                        }
 
                         if self.cfg.deck.zapper {
                             if res.clicked() {
@@ -1269,7 +2380,14 @@ impl Gui {
                                 tx.event(EmulationEvent::ZapperAim(pos));
                             }
                         }
+
+                        if self.cfg.deck.mouse != MousePort::Disabled {
+                            Self::mouse_input(tx, ui, &res, &mut self.mouse_remainder);
+                        }
                     });
+                } else if self.cfg.renderer.tv_static {
+                    let rect = ui.available_rect_before_wrap();
+                    self.tv_static.draw(ui, rect, opacity);
                 } else {
                     ui.vertical_centered(|ui| {
                         ui.horizontal_centered(|ui| {
@@ -1289,6 +2407,29 @@ impl Gui {
             if self.audio_recording {
                 recording_labels.push("Audio");
             }
+            if self.video_recording {
+                recording_labels.push("Video");
+            }
+            let unsaved_quick_slots = self
+                .quick_slots
+                .iter()
+                .zip(1..)
+                .filter(|(quick_slot, _)| **quick_slot == QuickSlot::Unsaved)
+                .map(|(_, slot)| slot.to_string())
+                .collect::<Vec<_>>();
+            if !unsaved_quick_slots.is_empty() {
+                Frame::side_top_panel(ui.style()).show(ui, |ui| {
+                    ui.label(
+                        RichText::new(format!(
+                            "⚡ Quick State(s) {} in memory only",
+                            unsaved_quick_slots.join(", ")
+                        ))
+                        .italics(),
+                    )
+                    .on_hover_text("Quick states are lost on exit unless persisted to disk.");
+                });
+            }
+
             if !recording_labels.is_empty() {
                 Frame::side_top_panel(ui.style()).show(ui, |ui| {
                     ui.with_layout(
@@ -1320,7 +2461,15 @@ impl Gui {
                 });
             }
 
-            if self.run_state.paused() {
+            if self.loaded_rom.is_some() && !self.front_panel.powered {
+                Frame::none().inner_margin(5.0).show(ui, |ui| {
+                    ui.heading(RichText::new("⏻").color(Color32::LIGHT_GRAY).size(40.0));
+                });
+            } else if self.front_panel.reset_held {
+                Frame::none().inner_margin(5.0).show(ui, |ui| {
+                    ui.heading(RichText::new("⏼").color(Color32::LIGHT_GRAY).size(40.0));
+                });
+            } else if self.run_state.paused() {
                 Frame::none().inner_margin(5.0).show(ui, |ui| {
                     ui.heading(RichText::new("⏸").color(Color32::LIGHT_GRAY).size(40.0));
                 });
@@ -1401,6 +2550,27 @@ impl Gui {
             ui.label(format!("{}", self.frame_stats.frame_count));
             ui.end_row();
 
+            ui.strong("Lag Frames:");
+            ui.label(format!("{}", self.frame_stats.lag_frames));
+            ui.end_row();
+
+            let counters = self.frame_stats.counters;
+            ui.strong("CPU Cycles:");
+            ui.label(format!("{}", counters.cpu_cycles));
+            ui.end_row();
+
+            ui.strong("PPU Dots:");
+            ui.label(format!("{}", counters.ppu_dots));
+            ui.end_row();
+
+            ui.strong("DMA Cycles:");
+            ui.label(format!("{}", counters.dma_cycles));
+            ui.end_row();
+
+            ui.strong("Audio Samples:");
+            ui.label(format!("{}", counters.audio_samples));
+            ui.end_row();
+
             if let Some(stats) = self.sys.stats() {
                 let cpu_color = |cpu| match cpu {
                     cpu if cpu <= 25.0 => good_color,
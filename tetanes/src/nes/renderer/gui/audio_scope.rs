@@ -0,0 +1,83 @@
+use egui::{pos2, Color32, Rect, Rounding, Shape, Stroke, Ui, Vec2};
+
+/// Oscilloscope of the audio output drawn over the top-right corner of the NES frame.
+#[derive(Default, Debug)]
+#[must_use]
+pub struct AudioScope {
+    samples: Vec<f32>,
+}
+
+impl AudioScope {
+    /// Number of samples shown across the scope, about a frame's worth of audio.
+    const WINDOW: usize = 512;
+    /// Smallest peak scaled to the full height, so silence isn't amplified into noise.
+    const MIN_PEAK: f32 = 0.25;
+
+    /// Replace the samples with those from the latest frame.
+    pub fn update(&mut self, samples: Vec<f32>) {
+        self.samples = samples;
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Index to start drawing from, triggering on the first rising crossing of the average level
+    /// to keep periodic waveforms steady between frames.
+    fn trigger(&self) -> usize {
+        let searchable = self.samples.len().saturating_sub(Self::WINDOW);
+        if searchable == 0 {
+            return 0;
+        }
+        let mean = self.samples.iter().sum::<f32>() / self.samples.len() as f32;
+        self.samples[..=searchable]
+            .windows(2)
+            .position(|pair| pair[0] < mean && pair[1] >= mean)
+            .map_or(0, |index| index + 1)
+    }
+
+    pub fn draw(&self, ui: &Ui, frame: Rect) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let margin = (frame.height() * 0.02).max(4.0);
+        let size = Vec2::new(frame.width() * 0.3, frame.height() * 0.15);
+        let rect = Rect::from_min_size(
+            pos2(frame.right() - margin - size.x, frame.top() + margin),
+            size,
+        );
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, Rounding::same(4.0), Color32::from_black_alpha(160));
+        painter.hline(
+            rect.x_range(),
+            rect.center().y,
+            Stroke::new(1.0, Color32::from_white_alpha(40)),
+        );
+
+        let start = self.trigger();
+        let samples = &self.samples[start..self.samples.len().min(start + Self::WINDOW)];
+        if samples.len() < 2 {
+            return;
+        }
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+        let peak = samples.iter().fold(Self::MIN_PEAK, |peak, sample| {
+            peak.max((sample - mean).abs())
+        });
+        let half_height = rect.height() / 2.0 - 2.0;
+        let step = rect.width() / (samples.len() - 1) as f32;
+        let points = samples
+            .iter()
+            .enumerate()
+            .map(|(i, sample)| {
+                pos2(
+                    rect.left() + i as f32 * step,
+                    rect.center().y - (sample - mean) / peak * half_height,
+                )
+            })
+            .collect();
+        painter.add(Shape::line(
+            points,
+            Stroke::new(1.5, Color32::from_rgb(0x4C, 0xFF, 0x4C)),
+        ));
+    }
+}
@@ -0,0 +1,190 @@
+use crate::nes::{
+    emulation::bookmarks::BookmarkInfo,
+    event::{EmulationEvent, NesEventProxy},
+};
+use egui::{
+    load::SizedTexture, Align, Button, ColorImage, Context, Grid, Image, Layout, ScrollArea,
+    TextEdit, TextureHandle, TextureOptions, Ui, Vec2, Window,
+};
+
+/// A bookmark along with its thumbnail texture.
+#[must_use]
+struct Entry {
+    info: BookmarkInfo,
+    /// Note being edited, sent when the text field loses focus.
+    note: String,
+    texture: Option<TextureHandle>,
+}
+
+impl std::fmt::Debug for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Entry")
+            .field("info", &self.info)
+            .field("note", &self.note)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Window listing bookmarked frames for the loaded ROM to jump back to.
+#[derive(Debug)]
+#[must_use]
+pub struct Bookmarks {
+    tx: NesEventProxy,
+    open: bool,
+    entries: Vec<Entry>,
+}
+
+impl Bookmarks {
+    pub const fn new(tx: NesEventProxy) -> Self {
+        Self {
+            tx,
+            open: false,
+            entries: Vec::new(),
+        }
+    }
+
+    pub const fn open(&self) -> bool {
+        self.open
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        self.open = open;
+    }
+
+    pub fn toggle_open(&mut self) {
+        self.open = !self.open;
+    }
+
+    /// Update the listed bookmarks, keeping thumbnails and notes being edited for bookmarks
+    /// that are still listed.
+    pub fn set_bookmarks(&mut self, bookmarks: Vec<BookmarkInfo>) {
+        let mut entries = std::mem::take(&mut self.entries);
+        for info in bookmarks {
+            let entry = match entries.iter().position(|entry| entry.info.id == info.id) {
+                Some(index) => {
+                    let mut entry = entries.swap_remove(index);
+                    if entry.info.note != info.note {
+                        entry.note.clone_from(&info.note);
+                    }
+                    entry.info = info;
+                    entry
+                }
+                None => Entry {
+                    note: info.note.clone(),
+                    info,
+                    texture: None,
+                },
+            };
+            self.entries.push(entry);
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool, rom_loaded: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let mut open = self.open;
+        Window::new("🔖 Bookmarks")
+            .open(&mut open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled && rom_loaded, |ui| self.ui(ui));
+            });
+        self.open = open;
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if ui
+                .button("🔖 Add Bookmark")
+                .on_hover_text("Bookmark the current frame with a save state.")
+                .clicked()
+            {
+                self.tx.event(EmulationEvent::AddBookmark);
+            }
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                ui.label(format!("{} Bookmark(s)", self.entries.len()));
+            });
+        });
+
+        if self.entries.is_empty() {
+            ui.add_space(8.0);
+            ui.label("No bookmarks for this ROM yet.");
+            return;
+        }
+
+        ui.separator();
+        let thumbnail_size = Vec2::new(
+            BookmarkInfo::THUMBNAIL_WIDTH as f32,
+            BookmarkInfo::THUMBNAIL_HEIGHT as f32,
+        );
+        ScrollArea::vertical()
+            .max_height(400.0)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                Grid::new("bookmarks")
+                    .num_columns(3)
+                    .spacing([8.0, 8.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for entry in &mut self.entries {
+                            let texture = entry.texture.get_or_insert_with(|| {
+                                let image = ColorImage::from_rgba_unmultiplied(
+                                    [
+                                        BookmarkInfo::THUMBNAIL_WIDTH as usize,
+                                        BookmarkInfo::THUMBNAIL_HEIGHT as usize,
+                                    ],
+                                    &entry.info.thumbnail,
+                                );
+                                ui.ctx().load_texture(
+                                    format!("bookmark_{}", entry.info.id),
+                                    image,
+                                    TextureOptions::NEAREST,
+                                )
+                            });
+                            let res = ui
+                                .add(
+                                    Button::image(
+                                        Image::from_texture(SizedTexture::from_handle(texture))
+                                            .fit_to_exact_size(thumbnail_size),
+                                    )
+                                    .frame(false),
+                                )
+                                .on_hover_text("Load this bookmark.");
+                            if res.clicked() {
+                                self.tx.event(EmulationEvent::LoadBookmark(entry.info.id));
+                            }
+
+                            ui.vertical(|ui| {
+                                ui.strong(format!("Frame {}", entry.info.frame));
+                                let res = ui.add(
+                                    TextEdit::singleline(&mut entry.note)
+                                        .hint_text("Note")
+                                        .desired_width(160.0),
+                                );
+                                if res.lost_focus() && entry.note != entry.info.note {
+                                    self.tx.event(EmulationEvent::BookmarkNote((
+                                        entry.info.id,
+                                        entry.note.clone(),
+                                    )));
+                                }
+                            });
+
+                            ui.vertical(|ui| {
+                                if ui.button("Load").clicked() {
+                                    self.tx.event(EmulationEvent::LoadBookmark(entry.info.id));
+                                }
+                                if ui
+                                    .button("✖")
+                                    .on_hover_text("Remove this bookmark.")
+                                    .clicked()
+                                {
+                                    self.tx.event(EmulationEvent::RemoveBookmark(entry.info.id));
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+}
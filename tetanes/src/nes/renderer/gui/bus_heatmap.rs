@@ -0,0 +1,380 @@
+use crate::nes::{
+    event::{EmulationEvent, NesEventProxy},
+    renderer::gui::lib::ViewportOptions,
+};
+use egui::{
+    load::SizedTexture, show_tooltip_at_pointer, CentralPanel, Color32, ColorImage, ComboBox,
+    Context, CursorIcon, DragValue, Grid, Image, ScrollArea, Sense, SidePanel, TextureHandle,
+    TextureOptions, Ui, Vec2, ViewportClass, ViewportId,
+};
+use parking_lot::Mutex;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use tetanes_core::debug::BusActivity;
+
+#[must_use]
+struct State {
+    tx: NesEventProxy,
+    window_frames: u32,
+    mode: Mode,
+    region: Region,
+    activity: Option<BusActivity>,
+    frames: u32,
+    texture: Option<TextureHandle>,
+    dirty: bool,
+}
+
+impl std::fmt::Debug for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("State")
+            .field("window_frames", &self.window_frames)
+            .field("mode", &self.mode)
+            .field("region", &self.region)
+            .field("frames", &self.frames)
+            .field("dirty", &self.dirty)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+enum Mode {
+    Reads,
+    Writes,
+    #[default]
+    Both,
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+enum Region {
+    #[default]
+    ZeroPage,
+    Stack,
+    Ram,
+    RamMirrors,
+    PrgRam,
+    PrgRom,
+    All,
+}
+
+impl Region {
+    const ALL: [Self; 7] = [
+        Self::ZeroPage,
+        Self::Stack,
+        Self::Ram,
+        Self::RamMirrors,
+        Self::PrgRam,
+        Self::PrgRom,
+        Self::All,
+    ];
+
+    const fn label(&self) -> &'static str {
+        match self {
+            Self::ZeroPage => "Zero Page ($0000-$00FF)",
+            Self::Stack => "Stack ($0100-$01FF)",
+            Self::Ram => "RAM ($0200-$07FF)",
+            Self::RamMirrors => "RAM Mirrors ($0800-$1FFF)",
+            Self::PrgRam => "PRG-RAM ($6000-$7FFF)",
+            Self::PrgRom => "PRG-ROM ($8000-$FFFF)",
+            Self::All => "All ($0000-$FFFF)",
+        }
+    }
+
+    /// Returns the start address, length, and heatmap width in cells.
+    const fn layout(&self) -> (usize, usize, usize) {
+        match self {
+            Self::ZeroPage => (0x0000, 0x0100, 16),
+            Self::Stack => (0x0100, 0x0100, 16),
+            Self::Ram => (0x0200, 0x0600, 32),
+            Self::RamMirrors => (0x0800, 0x1800, 64),
+            Self::PrgRam => (0x6000, 0x2000, 64),
+            Self::PrgRom => (0x8000, 0x8000, 128),
+            Self::All => (0x0000, 0x10000, 256),
+        }
+    }
+}
+
+#[derive(Debug)]
+#[must_use]
+pub struct BusHeatmap {
+    id: ViewportId,
+    open: Arc<AtomicBool>,
+    state: Arc<Mutex<State>>,
+}
+
+impl BusHeatmap {
+    const TITLE: &'static str = "🔥 Bus Heatmap";
+    const DEFAULT_WINDOW_FRAMES: u32 = 60;
+    const MAX_WINDOW_FRAMES: u32 = 3600;
+    const TOP_ADDRESSES: usize = 16;
+
+    pub fn new(tx: NesEventProxy) -> Self {
+        Self {
+            id: ViewportId::from_hash_of(Self::TITLE),
+            open: Arc::new(AtomicBool::new(false)),
+            state: Arc::new(Mutex::new(State {
+                tx,
+                window_frames: Self::DEFAULT_WINDOW_FRAMES,
+                mode: Mode::default(),
+                region: Region::default(),
+                activity: None,
+                frames: 0,
+                texture: None,
+                dirty: false,
+            })),
+        }
+    }
+
+    pub const fn id(&self) -> ViewportId {
+        self.id
+    }
+
+    pub fn open(&self) -> bool {
+        self.open.load(Ordering::Acquire)
+    }
+
+    pub fn set_open(&self, open: bool) {
+        self.open.store(open, Ordering::Release);
+        self.state.lock().update_counting(open);
+    }
+
+    pub fn update_activity(&mut self, activity: BusActivity, frames: u32) {
+        let mut state = self.state.lock();
+        state.activity = Some(activity);
+        state.frames = frames;
+        state.dirty = true;
+    }
+
+    pub fn show(&mut self, ctx: &Context, opts: ViewportOptions) {
+        if !self.open.load(Ordering::Relaxed) {
+            return;
+        }
+
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let open = Arc::clone(&self.open);
+        let state = Arc::clone(&self.state);
+
+        let mut viewport_builder = egui::ViewportBuilder::default()
+            .with_title(Self::TITLE)
+            .with_inner_size(Vec2::new(800.0, 600.0));
+        if opts.always_on_top {
+            viewport_builder = viewport_builder.with_always_on_top();
+        }
+
+        ctx.show_viewport_deferred(self.id, viewport_builder, move |ctx, class| {
+            if class == ViewportClass::Embedded {
+                let mut window_open = open.load(Ordering::Acquire);
+                egui::Window::new(BusHeatmap::TITLE)
+                    .open(&mut window_open)
+                    .show(ctx, |ui| state.lock().ui(ui, opts.enabled));
+                open.store(window_open, Ordering::Release);
+            } else {
+                CentralPanel::default().show(ctx, |ui| state.lock().ui(ui, opts.enabled));
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    open.store(false, Ordering::Release);
+                }
+            }
+            if !open.load(Ordering::Acquire) {
+                state.lock().update_counting(false);
+            }
+        });
+    }
+}
+
+impl State {
+    fn update_counting(&mut self, open: bool) {
+        if !open {
+            self.activity = None;
+            self.texture = None;
+        }
+        self.tx.event(EmulationEvent::BusActivity(
+            open.then_some(self.window_frames),
+        ));
+    }
+
+    fn count(&self, activity: &BusActivity, addr: usize) -> u32 {
+        match self.mode {
+            Mode::Reads => activity.reads()[addr],
+            Mode::Writes => activity.writes()[addr],
+            Mode::Both => activity.reads()[addr].saturating_add(activity.writes()[addr]),
+        }
+    }
+
+    /// Maps a normalized intensity to a black-red-yellow-white heat gradient.
+    fn heat(t: f32) -> Color32 {
+        let channel = |offset: f32| ((3.0 * t - offset).clamp(0.0, 1.0) * 255.0) as u8;
+        Color32::from_rgb(channel(0.0).max(0x30), channel(1.0), channel(2.0))
+    }
+
+    fn update_texture(&mut self, ui: &Ui) {
+        let Some(activity) = &self.activity else {
+            return;
+        };
+        let (start, len, width) = self.region.layout();
+        let counts = (start..start + len)
+            .map(|addr| self.count(activity, addr))
+            .collect::<Vec<_>>();
+        let max = counts.iter().copied().max().unwrap_or(0);
+        let scale = (max as f32).ln_1p();
+
+        let mut image = ColorImage::new([width, len / width], Color32::BLACK);
+        for (pixel, count) in image.pixels.iter_mut().zip(counts) {
+            if count > 0 {
+                *pixel = Self::heat((count as f32).ln_1p() / scale);
+            }
+        }
+
+        match &mut self.texture {
+            Some(texture) => texture.set(image, TextureOptions::NEAREST),
+            None => {
+                self.texture = Some(ui.ctx().load_texture(
+                    "bus_heatmap",
+                    image,
+                    TextureOptions::NEAREST,
+                ));
+            }
+        }
+        self.dirty = false;
+    }
+
+    fn ui(&mut self, ui: &mut Ui, enabled: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        ui.add_enabled_ui(enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Window:");
+                let res = ui
+                    .add(
+                        DragValue::new(&mut self.window_frames)
+                            .range(1..=BusHeatmap::MAX_WINDOW_FRAMES)
+                            .suffix(" frames"),
+                    )
+                    .on_hover_text("Number of frames to accumulate bus activity over.");
+                if res.changed() {
+                    self.update_counting(true);
+                }
+
+                ui.separator();
+
+                let mode = self.mode;
+                ui.selectable_value(&mut self.mode, Mode::Reads, "Reads");
+                ui.selectable_value(&mut self.mode, Mode::Writes, "Writes");
+                ui.selectable_value(&mut self.mode, Mode::Both, "Both");
+                if mode != self.mode {
+                    self.dirty = true;
+                }
+
+                ui.separator();
+
+                let region = self.region;
+                ComboBox::from_id_salt("bus_heatmap_region")
+                    .selected_text(self.region.label())
+                    .show_ui(ui, |ui| {
+                        for region in Region::ALL {
+                            ui.selectable_value(&mut self.region, region, region.label());
+                        }
+                    });
+                if region != self.region {
+                    self.dirty = true;
+                }
+            });
+
+            ui.separator();
+
+            if self.activity.is_none() {
+                ui.label("Waiting for bus activity...");
+                return;
+            }
+            ui.label(format!("Accumulated over {} frames", self.frames));
+
+            if self.dirty || self.texture.is_none() {
+                self.update_texture(ui);
+            }
+
+            SidePanel::right("bus_heatmap_top")
+                .resizable(false)
+                .show_inside(ui, |ui| self.top_addresses(ui));
+
+            CentralPanel::default().show_inside(ui, |ui| self.heatmap(ui));
+        });
+    }
+
+    fn heatmap(&self, ui: &mut Ui) {
+        let (Some(activity), Some(texture)) = (&self.activity, &self.texture) else {
+            return;
+        };
+        let (start, len, width) = self.region.layout();
+        let size = Vec2::new(width as f32, (len / width) as f32);
+
+        ScrollArea::both().auto_shrink(false).show(ui, |ui| {
+            let zoom = (ui.available_width() / size.x).floor().max(1.0);
+            let image = Image::from_texture(SizedTexture::from_handle(texture))
+                .fit_to_exact_size(zoom * size)
+                .sense(Sense::hover());
+            let res = ui.add(image).on_hover_cursor(CursorIcon::Crosshair);
+
+            if let Some(pos) = res.hover_pos() {
+                let cell = ((pos - res.rect.min) / zoom).floor();
+                let (col, row) = (cell.x as usize, cell.y as usize);
+                if col < width && row * width < len {
+                    let addr = start + row * width + col;
+                    show_tooltip_at_pointer(
+                        ui.ctx(),
+                        ui.layer_id(),
+                        egui::Id::new("bus_heatmap_tooltip"),
+                        |ui| {
+                            Grid::new("bus_heatmap_tooltip_grid")
+                                .num_columns(2)
+                                .show(ui, |ui| {
+                                    ui.strong("Address:");
+                                    ui.monospace(format!("${addr:04X}"));
+                                    ui.end_row();
+                                    ui.strong("Reads:");
+                                    ui.label(activity.reads()[addr].to_string());
+                                    ui.end_row();
+                                    ui.strong("Writes:");
+                                    ui.label(activity.writes()[addr].to_string());
+                                    ui.end_row();
+                                });
+                        },
+                    );
+                }
+            }
+        });
+    }
+
+    fn top_addresses(&self, ui: &mut Ui) {
+        let Some(activity) = &self.activity else {
+            return;
+        };
+        let (start, len, _) = self.region.layout();
+        let mut addrs = (start..start + len)
+            .filter(|&addr| self.count(activity, addr) > 0)
+            .collect::<Vec<_>>();
+        addrs.sort_by_key(|&addr| std::cmp::Reverse(self.count(activity, addr)));
+        addrs.truncate(BusHeatmap::TOP_ADDRESSES);
+
+        ui.heading("Most Active");
+        Grid::new("bus_heatmap_top_grid")
+            .num_columns(3)
+            .spacing([20.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Address");
+                ui.strong("Reads");
+                ui.strong("Writes");
+                ui.end_row();
+
+                for addr in addrs {
+                    ui.monospace(format!("${addr:04X}"));
+                    ui.label(activity.reads()[addr].to_string());
+                    ui.label(activity.writes()[addr].to_string());
+                    ui.end_row();
+                }
+            });
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{
+    nes::{
+        config::Config,
+        event::{ConfigEvent, NesEventProxy},
+    },
+    platform::open_file_dialog,
+};
+use egui::{Button, ComboBox, Context, Grid, RichText, ScrollArea, TextEdit, Ui, Window};
+use std::path::{Path, PathBuf};
+use tetanes_core::cheats::{self, Cheat, Format};
+use tracing::{error, info};
+
+/// Cheats parsed from a cheat file, each with whether it's selected to be added.
+#[derive(Debug)]
+struct Opened {
+    format: Format,
+    cheats: Vec<(Cheat, bool)>,
+}
+
+/// Window for importing and exporting cheat lists in the formats used by other emulators.
+#[derive(Debug)]
+#[must_use]
+pub struct CheatFiles {
+    tx: NesEventProxy,
+    open: bool,
+    path: String,
+    opened: Option<Result<Opened, String>>,
+    export_format: Format,
+    exported: Option<Result<PathBuf, String>>,
+}
+
+impl CheatFiles {
+    const DIR: &'static str = "cheats";
+
+    pub const fn new(tx: NesEventProxy) -> Self {
+        Self {
+            tx,
+            open: false,
+            path: String::new(),
+            opened: None,
+            export_format: Format::RetroArch,
+            exported: None,
+        }
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool, cfg: &Config, rom_name: Option<&str>) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let mut open = self.open;
+        Window::new("📄 Cheat Files")
+            .open(&mut open)
+            .default_width(450.0)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| {
+                    ui.strong("Import");
+                    self.import_ui(ui, cfg);
+
+                    ui.separator();
+
+                    ui.strong("Export");
+                    self.export_ui(ui, cfg, rom_name);
+                });
+            });
+        self.open = open;
+    }
+
+    fn import_ui(&mut self, ui: &mut Ui, cfg: &Config) {
+        ui.label("Opens RetroArch or FCEUX .cht files and Nestopia .xml cheat lists.");
+        ui.horizontal(|ui| {
+            ui.label("File:");
+            ui.add(TextEdit::singleline(&mut self.path).desired_width(250.0));
+            if ui.button("Browse...").clicked() {
+                let dir = Path::new(&self.path).parent();
+                match open_file_dialog("Open Cheat File", "Cheat Files", &["cht", "xml"], dir) {
+                    Ok(Some(path)) => {
+                        self.path = path.to_string_lossy().into_owned();
+                        self.opened = Some(Self::load(&path));
+                    }
+                    Ok(None) => (),
+                    Err(err) => error!("failed to open file dialog: {err:?}"),
+                }
+            }
+            ui.add_enabled_ui(!self.path.is_empty(), |ui| {
+                if ui.button("Open").clicked() {
+                    self.opened = Some(Self::load(Path::new(&self.path)));
+                }
+            });
+        });
+
+        match &mut self.opened {
+            Some(Ok(opened)) => {
+                if opened.cheats.is_empty() {
+                    ui.label("No cheats found.");
+                    return;
+                }
+                ui.label(format!(
+                    "Found {} {} cheat(s). Enabled cheats are selected by default.",
+                    opened.cheats.len(),
+                    opened.format
+                ));
+                ScrollArea::vertical().max_height(250.0).show(ui, |ui| {
+                    Grid::new("cheat_file_grid")
+                        .num_columns(2)
+                        .spacing([20.0, 4.0])
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (cheat, selected) in &mut opened.cheats {
+                                ui.checkbox(selected, cheat.code.code());
+                                ui.label(&cheat.description);
+                                ui.end_row();
+                            }
+                        });
+                });
+
+                let selected = opened.cheats.iter().filter(|(_, selected)| *selected);
+                let count = selected.clone().count();
+                let res = ui
+                    .add_enabled(count > 0, Button::new(format!("Add {count} Cheat(s)")))
+                    .on_hover_text("Descriptions aren't kept, only the codes.");
+                if res.clicked() {
+                    for (cheat, _) in selected {
+                        if !cfg.deck.genie_codes.contains(&cheat.code) {
+                            self.tx
+                                .event(ConfigEvent::GenieCodeAdded(cheat.code.clone()));
+                        }
+                    }
+                }
+            }
+            Some(Err(err)) => {
+                ui.label(RichText::new(err.as_str()).color(ui.visuals().error_fg_color));
+            }
+            None => (),
+        }
+    }
+
+    fn export_ui(&mut self, ui: &mut Ui, cfg: &Config, rom_name: Option<&str>) {
+        ui.horizontal(|ui| {
+            ui.label("Format:");
+            ComboBox::from_id_salt("cheat_export_format")
+                .selected_text(self.export_format.as_ref())
+                .show_ui(ui, |ui| {
+                    for format in Format::as_slice() {
+                        ui.selectable_value(&mut self.export_format, *format, format.as_ref());
+                    }
+                });
+
+            let has_codes = !cfg.deck.genie_codes.is_empty();
+            let res = ui
+                .add_enabled(has_codes, Button::new("Export"))
+                .on_disabled_hover_text("No Game Genie codes added.");
+            if res.clicked() {
+                self.exported = Some(self.export(cfg, rom_name));
+            }
+        });
+
+        match &self.exported {
+            Some(Ok(path)) => {
+                ui.label(format!("Exported to {}", path.display()));
+            }
+            Some(Err(err)) => {
+                ui.label(RichText::new(err.as_str()).color(ui.visuals().error_fg_color));
+            }
+            None => (),
+        }
+    }
+
+    fn load(path: &Path) -> Result<Opened, String> {
+        let text = std::fs::read_to_string(path).map_err(|err| format!("failed to read: {err}"))?;
+        let (format, cheats) = cheats::parse(&text).map_err(|err| err.to_string())?;
+        info!("opened {} {format} cheat(s) from {path:?}", cheats.len());
+        Ok(Opened {
+            format,
+            cheats: cheats
+                .into_iter()
+                .map(|cheat| {
+                    let enabled = cheat.enabled;
+                    (cheat, enabled)
+                })
+                .collect(),
+        })
+    }
+
+    /// Write the current codes to the cheats folder in the data directory, named after the
+    /// loaded ROM.
+    fn export(&self, cfg: &Config, rom_name: Option<&str>) -> Result<PathBuf, String> {
+        let cheats = cfg
+            .deck
+            .genie_codes
+            .iter()
+            .cloned()
+            .map(Cheat::new)
+            .collect::<Vec<_>>();
+        let dir = Config::default_data_dir().join(Self::DIR);
+        std::fs::create_dir_all(&dir).map_err(|err| format!("failed to create {dir:?}: {err}"))?;
+        let path = dir.join(format!(
+            "{}.{}",
+            rom_name.unwrap_or("cheats"),
+            self.export_format.extension()
+        ));
+        std::fs::write(&path, cheats::export(self.export_format, &cheats))
+            .map_err(|err| format!("failed to save {path:?}: {err}"))?;
+        info!("exported {} cheat(s) to {path:?}", cheats.len());
+        Ok(path)
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{
+    nes::{
+        config::Config,
+        emulation::compare::CompareResult,
+        event::{EmulationEvent, NesEventProxy},
+    },
+    platform::open_file_dialog,
+};
+use egui::{
+    load::SizedTexture, ColorImage, Context, Image, ProgressBar, TextEdit, TextureHandle,
+    TextureOptions, Ui, Window,
+};
+use std::path::PathBuf;
+use tetanes_core::ppu::Ppu;
+use tracing::error;
+
+/// Window for comparing two replays to find the frame where they diverge.
+#[must_use]
+pub struct CompareReplays {
+    tx: NesEventProxy,
+    open: bool,
+    paths: [String; 2],
+    progress: Option<(u32, u32)>,
+    result: Option<CompareResult>,
+    screens: Option<[TextureHandle; 2]>,
+}
+
+impl std::fmt::Debug for CompareReplays {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompareReplays")
+            .field("open", &self.open)
+            .field("paths", &self.paths)
+            .field("progress", &self.progress)
+            .field("result", &self.result)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CompareReplays {
+    pub const fn new(tx: NesEventProxy) -> Self {
+        Self {
+            tx,
+            open: false,
+            paths: [String::new(), String::new()],
+            progress: None,
+            result: None,
+            screens: None,
+        }
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    pub fn update_progress(&mut self, frame: u32, total: u32) {
+        self.progress = Some((frame, total));
+    }
+
+    pub fn finish(&mut self, ctx: &Context, result: CompareResult) {
+        self.progress = None;
+        self.screens = Some([0, 1].map(|side| {
+            let image = ColorImage::from_rgba_unmultiplied(
+                [Ppu::WIDTH as usize, Ppu::HEIGHT as usize],
+                &result.screens[side],
+            );
+            ctx.load_texture(
+                format!("compare_replays_{side}"),
+                image,
+                TextureOptions::NEAREST,
+            )
+        }));
+        self.result = Some(result);
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool, rom_loaded: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let mut open = self.open;
+        let mut compare = false;
+        let mut cancel = false;
+        Window::new("🔀 Compare Replays")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled && rom_loaded, |ui| {
+                    ui.label("Plays both replays of the loaded game until their output differs.");
+                    for (label, path) in ["Replay A:", "Replay B:"].iter().zip(&mut self.paths) {
+                        Self::path_row(ui, label, path);
+                    }
+
+                    ui.add_space(8.0);
+                    match self.progress {
+                        Some((frame, total)) => {
+                            ui.horizontal(|ui| {
+                                ui.add(
+                                    ProgressBar::new(frame as f32 / total.max(1) as f32)
+                                        .text(format!("Frame {frame} / {total}"))
+                                        .desired_width(300.0),
+                                );
+                                if ui.button("Cancel").clicked() {
+                                    cancel = true;
+                                }
+                            });
+                        }
+                        None => {
+                            let ready = self.paths.iter().all(|path| !path.is_empty());
+                            ui.add_enabled_ui(ready, |ui| {
+                                if ui.button("Compare").clicked() {
+                                    compare = true;
+                                }
+                            });
+                        }
+                    }
+
+                    if let Some(result) = &self.result {
+                        ui.separator();
+                        Self::show_result(ui, result, self.screens.as_ref());
+                    }
+                });
+            });
+        self.open = open;
+
+        if compare {
+            let [a, b] = self.paths.clone().map(PathBuf::from);
+            self.result = None;
+            self.screens = None;
+            self.progress = Some((0, 0));
+            self.tx.event(EmulationEvent::CompareReplays((a, b)));
+        }
+        if cancel || (!self.open && self.progress.is_some()) {
+            self.tx.event(EmulationEvent::CancelCompare);
+        }
+    }
+
+    fn path_row(ui: &mut Ui, label: &str, path: &mut String) {
+        ui.horizontal(|ui| {
+            ui.label(label);
+            ui.add(TextEdit::singleline(path).desired_width(300.0));
+            if ui.button("Browse...").clicked() {
+                match open_file_dialog(
+                    "Load Replay",
+                    "Replay Recording",
+                    &["replay"],
+                    Some(Config::default_data_dir()),
+                ) {
+                    Ok(Some(selected)) => *path = selected.to_string_lossy().into_owned(),
+                    Ok(None) => (),
+                    Err(err) => error!("failed to open file dialog: {err:?}"),
+                }
+            }
+        });
+    }
+
+    fn show_result(ui: &mut Ui, result: &CompareResult, screens: Option<&[TextureHandle; 2]>) {
+        let frame_text = |frame: Option<u32>| frame.map_or("none".to_string(), |f| f.to_string());
+        ui.label(format!("Frames compared: {}", result.frames));
+        ui.label(format!(
+            "First input difference: {}",
+            frame_text(result.input_divergence)
+        ));
+        ui.label(format!(
+            "First output difference: {}",
+            frame_text(result.output_divergence)
+        ));
+        ui.small("Frames are counted from the start of each replay.");
+
+        if let Some(screens) = screens {
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                for (label, screen) in ["A", "B"].iter().zip(screens) {
+                    ui.vertical(|ui| {
+                        ui.label(*label);
+                        ui.add(Image::from_texture(SizedTexture::from_handle(screen)));
+                    });
+                }
+            });
+        }
+    }
+}
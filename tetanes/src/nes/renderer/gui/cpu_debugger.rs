@@ -0,0 +1,345 @@
+use crate::nes::{
+    action::DebugStep,
+    annotation::Annotations,
+    event::{EmulationEvent, NesEventProxy},
+    renderer::gui::lib::ViewportOptions,
+};
+use egui::{
+    Align, Button, CentralPanel, Context, Grid, RichText, ScrollArea, SelectableLabel, Ui, Vec2,
+    ViewportClass, ViewportId,
+};
+use parking_lot::Mutex;
+use std::{
+    collections::BTreeSet,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tetanes_core::{
+    cpu::Status,
+    debug::{Breakpoint, CpuBreakpoint, CpuDebugState},
+};
+
+#[derive(Debug)]
+#[must_use]
+struct State {
+    tx: NesEventProxy,
+    debug_state: Option<Box<CpuDebugState>>,
+    /// Execution breakpoints added from the disassembly view.
+    breakpoints: BTreeSet<u16>,
+    follow_pc: bool,
+    annotations: Arc<Mutex<Annotations>>,
+}
+
+#[derive(Debug)]
+#[must_use]
+pub struct CpuDebugger {
+    id: ViewportId,
+    open: Arc<AtomicBool>,
+    state: Arc<Mutex<State>>,
+}
+
+impl CpuDebugger {
+    const TITLE: &'static str = "🐞 CPU Debugger";
+
+    pub fn new(tx: NesEventProxy, annotations: Arc<Mutex<Annotations>>) -> Self {
+        Self {
+            id: ViewportId::from_hash_of(Self::TITLE),
+            open: Arc::new(AtomicBool::new(false)),
+            state: Arc::new(Mutex::new(State {
+                tx,
+                debug_state: None,
+                breakpoints: BTreeSet::new(),
+                follow_pc: true,
+                annotations,
+            })),
+        }
+    }
+
+    pub const fn id(&self) -> ViewportId {
+        self.id
+    }
+
+    pub fn open(&self) -> bool {
+        self.open.load(Ordering::Acquire)
+    }
+
+    pub fn set_open(&self, open: bool) {
+        self.open.store(open, Ordering::Release);
+        self.state.lock().update_debugging(open);
+    }
+
+    pub fn toggle_open(&self) {
+        let _ = self
+            .open
+            .fetch_update(Ordering::Release, Ordering::Acquire, |open| Some(!open));
+        self.state.lock().update_debugging(self.open());
+    }
+
+    pub fn update_state(&mut self, debug_state: Box<CpuDebugState>) {
+        self.state.lock().debug_state = Some(debug_state);
+    }
+
+    pub fn show(&mut self, ctx: &Context, opts: ViewportOptions) {
+        if !self.open.load(Ordering::Relaxed) {
+            return;
+        }
+
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let open = Arc::clone(&self.open);
+        let state = Arc::clone(&self.state);
+
+        let mut viewport_builder = egui::ViewportBuilder::default()
+            .with_title(Self::TITLE)
+            .with_inner_size(Vec2::new(720.0, 560.0));
+        if opts.always_on_top {
+            viewport_builder = viewport_builder.with_always_on_top();
+        }
+
+        ctx.show_viewport_deferred(self.id, viewport_builder, move |ctx, class| {
+            if class == ViewportClass::Embedded {
+                let mut window_open = open.load(Ordering::Acquire);
+                egui::Window::new(CpuDebugger::TITLE)
+                    .open(&mut window_open)
+                    .show(ctx, |ui| state.lock().ui(ui, opts.enabled));
+                open.store(window_open, Ordering::Release);
+            } else {
+                CentralPanel::default().show(ctx, |ui| state.lock().ui(ui, opts.enabled));
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    open.store(false, Ordering::Release);
+                }
+            }
+            if !open.load(Ordering::Acquire) {
+                state.lock().update_debugging(false);
+            }
+        });
+    }
+}
+
+impl State {
+    fn update_debugging(&self, open: bool) {
+        self.tx.event(EmulationEvent::CpuDebugger(open));
+    }
+
+    fn toggle_breakpoint(&mut self, addr: u16) {
+        let breakpoint = Breakpoint::Cpu(CpuBreakpoint::Exec(addr));
+        if self.breakpoints.remove(&addr) {
+            self.tx.event(EmulationEvent::RemoveBreakpoint(breakpoint));
+        } else {
+            self.breakpoints.insert(addr);
+            self.tx.event(EmulationEvent::AddBreakpoint(breakpoint));
+        }
+    }
+
+    fn ui(&mut self, ui: &mut Ui, enabled: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        ui.add_enabled_ui(enabled, |ui| {
+            self.step_ui(ui);
+
+            ui.separator();
+
+            let Some(debug_state) = self.debug_state.take() else {
+                ui.label("Load a ROM to start debugging.");
+                return;
+            };
+            ui.horizontal_top(|ui| {
+                ui.vertical(|ui| {
+                    ui.set_width(200.0);
+                    Self::registers_ui(ui, &debug_state);
+                    ui.separator();
+                    Self::stack_ui(ui, &debug_state);
+                });
+                ui.separator();
+                ui.vertical(|ui| self.disassembly_ui(ui, &debug_state));
+            });
+            self.debug_state = Some(debug_state);
+        });
+    }
+
+    fn step_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            let steps = [
+                ("➡ Step", DebugStep::Into, "Step a single CPU instruction."),
+                (
+                    "⮫ Step Over",
+                    DebugStep::Over,
+                    "Step over the next CPU instruction, running any subroutine it calls.",
+                ),
+                (
+                    "⬆ Step Out",
+                    DebugStep::Out,
+                    "Run until the current subroutine or interrupt handler returns.",
+                ),
+                (
+                    "⬅ Step Back",
+                    DebugStep::Back,
+                    "Undo the last CPU instruction stepped.",
+                ),
+            ];
+            for (label, step, hover) in steps {
+                if ui.button(label).on_hover_text(hover).clicked() {
+                    self.tx.event(EmulationEvent::DebugStep(step));
+                }
+            }
+
+            ui.separator();
+
+            ui.checkbox(&mut self.follow_pc, "Follow PC");
+            let res = ui
+                .add_enabled(
+                    !self.breakpoints.is_empty(),
+                    Button::new("Clear Breakpoints"),
+                )
+                .on_hover_text("Remove all breakpoints.");
+            if res.clicked() {
+                self.breakpoints.clear();
+                self.tx.event(EmulationEvent::ClearBreakpoints);
+            }
+        });
+    }
+
+    fn registers_ui(ui: &mut Ui, debug_state: &CpuDebugState) {
+        ui.strong("Registers");
+        Grid::new("cpu_debugger_registers")
+            .num_columns(2)
+            .spacing([20.0, 4.0])
+            .show(ui, |ui| {
+                let registers = [
+                    ("PC", format!("${:04X}", debug_state.pc)),
+                    ("A", format!("${:02X}", debug_state.acc)),
+                    ("X", format!("${:02X}", debug_state.x)),
+                    ("Y", format!("${:02X}", debug_state.y)),
+                    ("SP", format!("${:02X}", debug_state.sp)),
+                    ("P", format!("${:02X}", debug_state.status)),
+                    ("Cycle", debug_state.cycle.to_string()),
+                ];
+                for (name, value) in registers {
+                    ui.label(name);
+                    ui.monospace(value);
+                    ui.end_row();
+                }
+            });
+
+        ui.add_space(4.0);
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 4.0;
+            let status = Status::from_bits_truncate(debug_state.status);
+            let flags = [
+                ("N", Status::N, "Negative"),
+                ("V", Status::V, "Overflow"),
+                ("U", Status::U, "Unused"),
+                ("B", Status::B, "Break"),
+                ("D", Status::D, "Decimal"),
+                ("I", Status::I, "Interrupt Disable"),
+                ("Z", Status::Z, "Zero"),
+                ("C", Status::C, "Carry"),
+            ];
+            for (name, flag, hover) in flags {
+                let text = RichText::new(name).monospace();
+                let text = if status.contains(flag) {
+                    text.strong().color(ui.visuals().strong_text_color())
+                } else {
+                    text.weak()
+                };
+                ui.label(text).on_hover_text(hover);
+            }
+        });
+    }
+
+    fn stack_ui(ui: &mut Ui, debug_state: &CpuDebugState) {
+        ui.strong("Stack");
+        ScrollArea::vertical()
+            .id_salt("cpu_debugger_stack")
+            .auto_shrink([false, true])
+            .max_height(ui.available_height())
+            .show(ui, |ui| {
+                if debug_state.stack.is_empty() {
+                    ui.label("Empty");
+                }
+                let top = 0x0101 + u16::from(debug_state.sp);
+                for (addr, value) in (top..).zip(&debug_state.stack) {
+                    ui.monospace(format!("${addr:04X}: ${value:02X}"));
+                }
+            });
+    }
+
+    fn disassembly_ui(&mut self, ui: &mut Ui, debug_state: &CpuDebugState) {
+        ui.strong("Disassembly");
+        ui.label("Click an address to toggle a breakpoint.");
+        let mut toggled = None;
+        ScrollArea::vertical()
+            .id_salt("cpu_debugger_disassembly")
+            .auto_shrink(false)
+            .show(ui, |ui| {
+                Grid::new("cpu_debugger_disassembly_grid")
+                    .num_columns(3)
+                    .spacing([10.0, 2.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        let annotations = self.annotations.lock();
+                        for instr in &debug_state.disassembly {
+                            let is_pc = instr.addr == debug_state.pc;
+                            let has_breakpoint = self.breakpoints.contains(&instr.addr);
+                            let marker = match (is_pc, has_breakpoint) {
+                                (true, true) => "●▶",
+                                (true, false) => " ▶",
+                                (false, true) => "● ",
+                                (false, false) => "  ",
+                            };
+                            let label = ui.add(SelectableLabel::new(
+                                is_pc,
+                                RichText::new(format!("{marker} ${:04X}", instr.addr))
+                                    .monospace()
+                                    .color(if has_breakpoint {
+                                        ui.visuals().error_fg_color
+                                    } else {
+                                        ui.visuals().text_color()
+                                    }),
+                            ));
+                            if label.clicked() {
+                                toggled = Some(instr.addr);
+                            }
+                            if is_pc && self.follow_pc {
+                                label.scroll_to_me(Some(Align::Center));
+                            }
+
+                            let bytes = instr
+                                .bytes()
+                                .iter()
+                                .map(|byte| format!("{byte:02X}"))
+                                .collect::<Vec<_>>()
+                                .join(" ");
+                            ui.monospace(format!("{bytes:<8}"));
+
+                            // Skip the address and bytes already shown
+                            let text = instr.to_string();
+                            let asm = text.get(15..).unwrap_or_default();
+                            let target = match instr.target {
+                                Some(target) => annotations.name(target),
+                                None => None,
+                            };
+                            let res = match (annotations.name(instr.addr), target) {
+                                (Some(name), _) => ui.monospace(format!("{name}: {asm}")),
+                                (None, Some(target)) => ui.monospace(format!("{asm} ; {target}")),
+                                (None, None) => ui.monospace(asm),
+                            };
+                            if let Some(annotation) = annotations
+                                .get(instr.addr)
+                                .filter(|annotation| !annotation.comment.is_empty())
+                            {
+                                res.on_hover_text(annotation.comment.as_str());
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        if let Some(addr) = toggled {
+            self.toggle_breakpoint(addr);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{
+    feature,
+    nes::{
+        emulation::failed_saves::FailedSave,
+        event::{EmulationEvent, NesEventProxy},
+    },
+    platform::open_dir_dialog,
+};
+use egui::{Align2, Context, Grid, RichText, Window};
+use tracing::error;
+
+/// Dialog shown while saves that failed to be written are kept in memory, blocking play until
+/// they're retried, written elsewhere or discarded.
+#[derive(Debug)]
+#[must_use]
+pub struct FailedSaves {
+    tx: NesEventProxy,
+    saves: Vec<FailedSave>,
+    /// Discard has to be clicked twice, since progress is lost.
+    confirm_discard: bool,
+}
+
+impl FailedSaves {
+    pub const fn new(tx: NesEventProxy) -> Self {
+        Self {
+            tx,
+            saves: Vec::new(),
+            confirm_discard: false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.saves.is_empty()
+    }
+
+    pub fn set_saves(&mut self, saves: Vec<FailedSave>) {
+        self.saves = saves;
+        self.confirm_discard = false;
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        if self.saves.is_empty() {
+            return;
+        }
+
+        Window::new("⚠ Save Failed")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(
+                    "Progress couldn't be saved, e.g. because the disk is full or the folder \
+                    isn't writable. It's kept in memory and emulation is paused until it's saved.",
+                );
+
+                ui.add_space(8.0);
+                Grid::new("failed_saves")
+                    .num_columns(3)
+                    .spacing([20.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for save in &self.saves {
+                            ui.label(&save.rom);
+                            ui.label(save.kind.to_string());
+                            ui.label(RichText::new(&save.error).color(ui.visuals().error_fg_color))
+                                .on_hover_text(save.path.display().to_string());
+                            ui.end_row();
+                        }
+                    });
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Retry")
+                        .on_hover_text("Try saving to the same location again.")
+                        .clicked()
+                    {
+                        self.tx.event(EmulationEvent::RetryFailedSaves(None));
+                    }
+                    if feature!(Filesystem)
+                        && ui
+                            .button("Save to Folder...")
+                            .on_hover_text("Choose another folder to save to.")
+                            .clicked()
+                    {
+                        let dir = self.saves[0].path.parent();
+                        match open_dir_dialog("Save to Folder", dir) {
+                            Ok(Some(dir)) => {
+                                self.tx.event(EmulationEvent::RetryFailedSaves(Some(dir)));
+                            }
+                            Ok(None) => (),
+                            Err(err) => error!("failed to open directory dialog: {err:?}"),
+                        }
+                    }
+                    let discard = if self.confirm_discard {
+                        RichText::new("Discard Progress?").color(ui.visuals().warn_fg_color)
+                    } else {
+                        RichText::new("Discard")
+                    };
+                    if ui
+                        .button(discard)
+                        .on_hover_text("Give up on saving. Unsaved progress will be lost.")
+                        .clicked()
+                    {
+                        if self.confirm_discard {
+                            self.tx.event(EmulationEvent::DiscardFailedSaves);
+                        }
+                        self.confirm_discard = !self.confirm_discard;
+                    }
+                });
+            });
+    }
+}
@@ -0,0 +1,202 @@
+use egui::{Align2, Area, Context, Frame, Key, Modifiers, RichText, Ui};
+use gilrs::Button;
+
+/// Gamepad-driven GUI navigation, translating buttons into keyboard events egui uses to move
+/// focus and activate widgets, with an on-screen keyboard while editing text.
+#[derive(Default, Debug)]
+#[must_use]
+pub struct GamepadNav {
+    keyboard: OnScreenKeyboard,
+}
+
+impl GamepadNav {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Translate a gamepad button press into egui events. D-pad moves focus, A activates and B
+    /// goes back. While a text field has focus, input is routed to the on-screen keyboard instead.
+    pub fn on_button(&mut self, ctx: &Context, button: Button) -> Vec<egui::Event> {
+        if ctx.wants_keyboard_input() {
+            return self.keyboard.on_button(button);
+        }
+
+        let key = match button {
+            Button::DPadUp => Key::ArrowUp,
+            Button::DPadDown => Key::ArrowDown,
+            Button::DPadLeft => Key::ArrowLeft,
+            Button::DPadRight => Key::ArrowRight,
+            Button::East => Key::Enter,
+            Button::South => Key::Escape,
+            Button::LeftTrigger | Button::RightTrigger => Key::Tab,
+            _ => return Vec::new(),
+        };
+        let modifiers = Modifiers {
+            shift: button == Button::LeftTrigger,
+            ..Default::default()
+        };
+
+        let mut events = Vec::with_capacity(4);
+        // Arrow keys only move focus once a widget has focus, so focus the first widget
+        let is_arrow = matches!(
+            key,
+            Key::ArrowUp | Key::ArrowDown | Key::ArrowLeft | Key::ArrowRight
+        );
+        if is_arrow && ctx.memory(|mem| mem.focused().is_none()) {
+            events.extend(key_press(Key::Tab, Modifiers::NONE));
+        } else {
+            events.extend(key_press(key, modifiers));
+        }
+        events
+    }
+
+    /// Show the on-screen keyboard while a text field has focus.
+    pub fn show(&mut self, ctx: &Context) {
+        if ctx.wants_keyboard_input() {
+            self.keyboard.show(ctx);
+        }
+    }
+}
+
+/// Special on-screen keyboard keys.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum SpecialKey {
+    Shift,
+    Space,
+    Backspace,
+    Done,
+}
+
+impl SpecialKey {
+    const ALL: [Self; 4] = [Self::Shift, Self::Space, Self::Backspace, Self::Done];
+
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Shift => "⇧ Shift",
+            Self::Space => "Space",
+            Self::Backspace => "⌫ Backspace",
+            Self::Done => "✔ Done",
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+#[must_use]
+struct OnScreenKeyboard {
+    row: usize,
+    col: usize,
+    shift: bool,
+}
+
+impl OnScreenKeyboard {
+    const ROWS: [&'static str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl-", "zxcvbnm_.,"];
+
+    /// Number of rows including the row of special keys.
+    const fn row_count() -> usize {
+        Self::ROWS.len() + 1
+    }
+
+    fn row_len(row: usize) -> usize {
+        Self::ROWS
+            .get(row)
+            .map_or(SpecialKey::ALL.len(), |keys| keys.chars().count())
+    }
+
+    fn selected_char(&self) -> Option<char> {
+        let c = Self::ROWS.get(self.row)?.chars().nth(self.col)?;
+        Some(self.with_case(c))
+    }
+
+    const fn with_case(&self, c: char) -> char {
+        if self.shift {
+            c.to_ascii_uppercase()
+        } else {
+            c
+        }
+    }
+
+    fn on_button(&mut self, button: Button) -> Vec<egui::Event> {
+        match button {
+            Button::DPadUp => self.row = (self.row + Self::row_count() - 1) % Self::row_count(),
+            Button::DPadDown => self.row = (self.row + 1) % Self::row_count(),
+            Button::DPadLeft => {
+                let len = Self::row_len(self.row);
+                self.col = (self.col.min(len - 1) + len - 1) % len;
+            }
+            Button::DPadRight => self.col = (self.col + 1) % Self::row_len(self.row),
+            Button::East => {
+                if let Some(c) = self.selected_char() {
+                    return vec![egui::Event::Text(c.to_string())];
+                }
+                let special = SpecialKey::ALL[self.col.min(SpecialKey::ALL.len() - 1)];
+                return self.press(special);
+            }
+            Button::South => return key_press(Key::Escape, Modifiers::NONE).into(),
+            Button::West => return self.press(SpecialKey::Backspace),
+            Button::North => return self.press(SpecialKey::Space),
+            Button::Select => return self.press(SpecialKey::Shift),
+            Button::Start => return self.press(SpecialKey::Done),
+            _ => (),
+        }
+        Vec::new()
+    }
+
+    fn press(&mut self, key: SpecialKey) -> Vec<egui::Event> {
+        match key {
+            SpecialKey::Shift => {
+                self.shift = !self.shift;
+                Vec::new()
+            }
+            SpecialKey::Space => vec![egui::Event::Text(" ".to_string())],
+            SpecialKey::Backspace => key_press(Key::Backspace, Modifiers::NONE).into(),
+            SpecialKey::Done => key_press(Key::Enter, Modifiers::NONE).into(),
+        }
+    }
+
+    fn show(&self, ctx: &Context) {
+        Area::new(egui::Id::new("on_screen_keyboard"))
+            .anchor(Align2::CENTER_BOTTOM, [0.0, -16.0])
+            .interactable(false)
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                Frame::popup(ui.style()).show(ui, |ui| {
+                    for (row, keys) in Self::ROWS.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            for (col, c) in keys.chars().enumerate() {
+                                self.key(ui, row, col, &self.with_case(c).to_string());
+                            }
+                        });
+                    }
+                    ui.horizontal(|ui| {
+                        for (col, key) in SpecialKey::ALL.into_iter().enumerate() {
+                            self.key(ui, Self::ROWS.len(), col, key.label());
+                        }
+                    });
+                    ui.separator();
+                    ui.label("A: Type  B: Cancel  X: Backspace  Y: Space  Start: Done");
+                });
+            });
+    }
+
+    fn key(&self, ui: &mut Ui, row: usize, col: usize, label: &str) {
+        let selected = self.row == row && self.col.min(Self::row_len(row) - 1) == col;
+        let mut text = RichText::new(format!(" {label} ")).monospace().size(18.0);
+        if selected {
+            let selection = ui.visuals().selection;
+            text = text
+                .background_color(selection.bg_fill)
+                .color(selection.stroke.color);
+        }
+        ui.label(text);
+    }
+}
+
+fn key_press(key: Key, modifiers: Modifiers) -> [egui::Event; 2] {
+    [true, false].map(|pressed| egui::Event::Key {
+        key,
+        physical_key: None,
+        pressed,
+        repeat: false,
+        modifiers,
+    })
+}
@@ -0,0 +1,114 @@
+use crate::{
+    nes::{
+        config::Config,
+        import::{self, Report, Source},
+    },
+    platform::open_dir_dialog,
+};
+use egui::{ComboBox, Context, Grid, RichText, ScrollArea, TextEdit, Ui, Window};
+use std::path::PathBuf;
+use tetanes_core::fs;
+use tracing::error;
+
+/// Window for importing saves and settings from other emulators.
+#[derive(Default, Debug)]
+#[must_use]
+pub struct Import {
+    open: bool,
+    source: Source,
+    dir: String,
+    report: Option<Report>,
+}
+
+impl Import {
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool, cfg: &Config) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let mut open = self.open;
+        Window::new("📥 Import from Other Emulators")
+            .open(&mut open)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Emulator:");
+                        ComboBox::from_id_salt("import_source")
+                            .selected_text(self.source.as_ref())
+                            .show_ui(ui, |ui| {
+                                for source in Source::as_slice() {
+                                    ui.selectable_value(&mut self.source, *source, source.as_ref());
+                                }
+                            });
+                    });
+                    ui.label(format!("Looks for {}.", self.source.layout()));
+
+                    ui.horizontal(|ui| {
+                        ui.label("Directory:");
+                        ui.add(TextEdit::singleline(&mut self.dir).desired_width(300.0));
+                        if ui.button("Browse...").clicked() {
+                            let dir = Some(&self.dir).filter(|dir| !dir.is_empty());
+                            match open_dir_dialog(format!("Select {} Directory", self.source), dir)
+                            {
+                                Ok(Some(dir)) => self.dir = dir.to_string_lossy().into_owned(),
+                                Ok(None) => (),
+                                Err(err) => error!("failed to open directory dialog: {err:?}"),
+                            }
+                        }
+                    });
+
+                    ui.add_space(8.0);
+                    ui.add_enabled_ui(!self.dir.is_empty(), |ui| {
+                        let res = ui.button("Import").on_hover_text(concat!(
+                            "Battery saves are imported for games without an existing TetaNES ",
+                            "save. Import before loading a game, or reload it afterwards."
+                        ));
+                        if res.clicked() {
+                            let dir = PathBuf::from(&self.dir);
+                            self.report = Some(import::import(self.source, &dir, &cfg.deck));
+                        }
+                    });
+
+                    if let Some(report) = &self.report {
+                        ui.separator();
+                        Self::report(ui, report);
+                    }
+                });
+            });
+        self.open = open;
+    }
+
+    fn report(ui: &mut Ui, report: &Report) {
+        let imported = report.imported().count();
+        let skipped = report.skipped().count();
+        if imported + skipped == 0 {
+            ui.label("No files found to import.");
+            return;
+        }
+        ui.label(format!("Imported {imported} file(s), skipped {skipped}."));
+
+        ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            Grid::new("import_report_grid")
+                .num_columns(3)
+                .spacing([20.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    for entry in report.imported().chain(report.skipped()) {
+                        ui.label(entry.kind.as_ref());
+                        ui.label(fs::filename(&entry.path));
+                        match &entry.result {
+                            Ok(_) => ui.label("Imported"),
+                            Err(reason) => {
+                                ui.label(RichText::new(reason).color(ui.visuals().warn_fg_color))
+                            }
+                        };
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
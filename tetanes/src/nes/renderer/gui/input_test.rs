@@ -0,0 +1,285 @@
+use crate::nes::action::Action;
+use egui::{
+    Align, Align2, Button, Color32, Context, FontId, Grid, Layout, RichText, ScrollArea, Sense, Ui,
+    Vec2, Window,
+};
+use std::collections::VecDeque;
+use tetanes_core::{
+    action::Action as DeckAction,
+    input::{JoypadBtn, Player},
+    time::{Duration, Instant},
+};
+
+const BUTTONS: [JoypadBtn; 10] = [
+    JoypadBtn::Up,
+    JoypadBtn::Down,
+    JoypadBtn::Left,
+    JoypadBtn::Right,
+    JoypadBtn::Select,
+    JoypadBtn::Start,
+    JoypadBtn::B,
+    JoypadBtn::A,
+    JoypadBtn::TurboB,
+    JoypadBtn::TurboA,
+];
+
+/// A raw input event shown in the input test window.
+#[derive(Debug)]
+#[must_use]
+struct InputEvent {
+    time: Instant,
+    source: String,
+    pressed: Option<bool>,
+    action: Option<Action>,
+}
+
+#[derive(Default, Debug, Copy, Clone)]
+#[must_use]
+enum LatencyTest {
+    #[default]
+    Idle,
+    /// Waiting a random delay before flashing.
+    Waiting {
+        flash_at: Instant,
+    },
+    /// Flashing since the first frame it was drawn, waiting for a press.
+    Flashing {
+        shown: Option<Instant>,
+    },
+    TooEarly,
+}
+
+/// Window showing raw input events, the NES buttons they're mapped to, and a reaction latency
+/// tester.
+#[derive(Debug)]
+#[must_use]
+pub struct InputTest {
+    open: bool,
+    events: VecDeque<InputEvent>,
+    /// Buttons held by each player according to their mapped inputs.
+    held: [[bool; BUTTONS.len()]; 4],
+    latency_test: LatencyTest,
+    latencies: VecDeque<Duration>,
+}
+
+impl Default for InputTest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputTest {
+    const MAX_EVENTS: usize = 100;
+    const MAX_LATENCIES: usize = 10;
+
+    pub const fn new() -> Self {
+        Self {
+            open: false,
+            events: VecDeque::new(),
+            held: [[false; BUTTONS.len()]; 4],
+            latency_test: LatencyTest::Idle,
+            latencies: VecDeque::new(),
+        }
+    }
+
+    pub const fn open(&self) -> bool {
+        self.open
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        self.open = open;
+        if !open {
+            self.latency_test = LatencyTest::Idle;
+        }
+    }
+
+    /// Log an input as it arrives, along with the action it's mapped to, if any. `pressed` is
+    /// `None` for inputs without a pressed state, e.g. axis values.
+    pub fn push(&mut self, source: String, pressed: Option<bool>, action: Option<Action>) {
+        let time = Instant::now();
+        if let Some(Action::Deck(DeckAction::Joypad((player, button)))) = action {
+            if let (Some(pressed), Some(index)) =
+                (pressed, BUTTONS.iter().position(|&b| b == button))
+            {
+                self.held[player as usize][index] = pressed;
+            }
+        }
+        if pressed == Some(true) {
+            self.on_press(time);
+        }
+        if self.events.len() >= Self::MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(InputEvent {
+            time,
+            source,
+            pressed,
+            action,
+        });
+    }
+
+    fn on_press(&mut self, time: Instant) {
+        self.latency_test = match self.latency_test {
+            LatencyTest::Waiting { .. } => LatencyTest::TooEarly,
+            LatencyTest::Flashing { shown: Some(shown) } => {
+                if self.latencies.len() >= Self::MAX_LATENCIES {
+                    self.latencies.pop_front();
+                }
+                self.latencies
+                    .push_back(time.saturating_duration_since(shown));
+                LatencyTest::Idle
+            }
+            test => test,
+        };
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let mut open = self.open;
+        Window::new("🎮 Input Test")
+            .open(&mut open)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| self.ui(ui));
+            });
+        self.set_open(open);
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.label("Press keys or gamepad buttons to see how they're received and mapped.");
+
+        ui.add_space(8.0);
+        ui.strong("Mapped NES Buttons");
+        Grid::new("input_test_held")
+            .num_columns(BUTTONS.len() + 1)
+            .spacing([8.0, 4.0])
+            .show(ui, |ui| {
+                for (player, held) in [Player::One, Player::Two, Player::Three, Player::Four]
+                    .into_iter()
+                    .zip(&self.held)
+                {
+                    ui.label(format!("Player {player}"));
+                    for (button, &held) in BUTTONS.iter().zip(held) {
+                        let text = RichText::new(button.as_ref()).monospace();
+                        ui.label(if held {
+                            text.strong().color(ui.visuals().selection.stroke.color)
+                        } else {
+                            text.weak()
+                        });
+                    }
+                    ui.end_row();
+                }
+            });
+
+        ui.separator();
+        self.latency_ui(ui);
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.strong("Events");
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                if ui.button("Clear").clicked() {
+                    self.events.clear();
+                }
+            });
+        });
+        ScrollArea::vertical()
+            .max_height(200.0)
+            .stick_to_bottom(true)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                let start = self.events.front().map(|event| event.time);
+                Grid::new("input_test_events")
+                    .num_columns(4)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for event in &self.events {
+                            let elapsed = start.map_or(Duration::ZERO, |start| {
+                                event.time.saturating_duration_since(start)
+                            });
+                            ui.monospace(format!("{:>8.3}s", elapsed.as_secs_f32()));
+                            ui.label(&event.source);
+                            ui.label(match event.pressed {
+                                Some(true) => "Pressed",
+                                Some(false) => "Released",
+                                None => "",
+                            });
+                            match &event.action {
+                                Some(action) => ui.label(action.to_string()),
+                                None => ui.weak("Unmapped"),
+                            };
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    fn latency_ui(&mut self, ui: &mut Ui) {
+        ui.strong("Reaction Latency");
+        ui.label("Press any input as soon as the box flashes.");
+
+        let now = Instant::now();
+        if let LatencyTest::Waiting { flash_at } = self.latency_test {
+            if now >= flash_at {
+                self.latency_test = LatencyTest::Flashing { shown: None };
+            }
+        }
+
+        let (rect, _) =
+            ui.allocate_exact_size(Vec2::new(ui.available_width(), 48.0), Sense::hover());
+        let (fill, text) = match &mut self.latency_test {
+            LatencyTest::Idle => (ui.visuals().extreme_bg_color, "Press Start"),
+            LatencyTest::Waiting { .. } => (ui.visuals().extreme_bg_color, "Wait for it..."),
+            LatencyTest::Flashing { shown } => {
+                shown.get_or_insert(now);
+                (Color32::WHITE, "Press!")
+            }
+            LatencyTest::TooEarly => (ui.visuals().extreme_bg_color, "Too early! Try again."),
+        };
+        ui.painter().rect_filled(rect, 4.0, fill);
+        ui.painter().text(
+            rect.center(),
+            Align2::CENTER_CENTER,
+            text,
+            FontId::proportional(16.0),
+            if fill == Color32::WHITE {
+                Color32::BLACK
+            } else {
+                ui.visuals().text_color()
+            },
+        );
+
+        ui.horizontal(|ui| {
+            let testing = matches!(
+                self.latency_test,
+                LatencyTest::Waiting { .. } | LatencyTest::Flashing { .. }
+            );
+            if ui.add_enabled(!testing, Button::new("Start")).clicked() {
+                // Pseudo-random delay between 1 and 3 seconds so the flash can't be anticipated
+                let jitter = ui.input(|i| (i.time * 1000.0).fract()) as f32;
+                self.latency_test = LatencyTest::Waiting {
+                    flash_at: now + Duration::from_secs_f32(1.0 + 2.0 * jitter),
+                };
+            }
+            if let Some(last) = self.latencies.back() {
+                let avg = self.latencies.iter().sum::<Duration>() / self.latencies.len() as u32;
+                ui.label(format!(
+                    "Last: {} ms, Average: {} ms ({} tries)",
+                    last.as_millis(),
+                    avg.as_millis(),
+                    self.latencies.len()
+                ));
+            }
+        });
+        ui.small(
+            "Includes display, input and reaction time, so compare results between devices or \
+            settings rather than against zero.",
+        );
+
+        if !matches!(self.latency_test, LatencyTest::Idle | LatencyTest::TooEarly) {
+            ui.ctx().request_repaint();
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use crate::nes::{
+    annotation::Annotations,
+    event::{EmulationEvent, NesEventProxy},
+    renderer::gui::lib::ViewportOptions,
+};
+use egui::{CentralPanel, Context, Grid, ScrollArea, Ui, Vec2, ViewportClass, ViewportId};
+use parking_lot::Mutex;
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tetanes_core::{
+    cpu::Irq,
+    debug::{Interrupt, InterruptKind},
+};
+
+#[derive(Debug)]
+#[must_use]
+struct State {
+    tx: NesEventProxy,
+    interrupts: VecDeque<Interrupt>,
+    show_nmi: bool,
+    show_frame_counter: bool,
+    show_dmc: bool,
+    show_mapper: bool,
+    auto_scroll: bool,
+    annotations: Arc<Mutex<Annotations>>,
+}
+
+#[derive(Debug)]
+#[must_use]
+pub struct InterruptLog {
+    id: ViewportId,
+    open: Arc<AtomicBool>,
+    state: Arc<Mutex<State>>,
+}
+
+impl InterruptLog {
+    const TITLE: &'static str = "⚡ Interrupt Log";
+    const MAX_ENTRIES: usize = 4096;
+
+    pub fn new(tx: NesEventProxy, annotations: Arc<Mutex<Annotations>>) -> Self {
+        Self {
+            id: ViewportId::from_hash_of(Self::TITLE),
+            open: Arc::new(AtomicBool::new(false)),
+            state: Arc::new(Mutex::new(State {
+                tx,
+                interrupts: VecDeque::with_capacity(Self::MAX_ENTRIES),
+                show_nmi: true,
+                show_frame_counter: true,
+                show_dmc: true,
+                show_mapper: true,
+                auto_scroll: true,
+                annotations,
+            })),
+        }
+    }
+
+    pub const fn id(&self) -> ViewportId {
+        self.id
+    }
+
+    pub fn open(&self) -> bool {
+        self.open.load(Ordering::Acquire)
+    }
+
+    pub fn set_open(&self, open: bool) {
+        self.open.store(open, Ordering::Release);
+        self.state.lock().update_logging(open);
+    }
+
+    pub fn toggle_open(&self) {
+        let _ = self
+            .open
+            .fetch_update(Ordering::Release, Ordering::Acquire, |open| Some(!open));
+        self.state.lock().update_logging(self.open());
+    }
+
+    pub fn push_interrupts(&mut self, interrupts: Vec<Interrupt>) {
+        let mut state = self.state.lock();
+        for interrupt in interrupts {
+            if state.interrupts.len() >= Self::MAX_ENTRIES {
+                state.interrupts.pop_front();
+            }
+            state.interrupts.push_back(interrupt);
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context, opts: ViewportOptions) {
+        if !self.open.load(Ordering::Relaxed) {
+            return;
+        }
+
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let open = Arc::clone(&self.open);
+        let state = Arc::clone(&self.state);
+
+        let mut viewport_builder = egui::ViewportBuilder::default()
+            .with_title(Self::TITLE)
+            .with_inner_size(Vec2::new(640.0, 480.0));
+        if opts.always_on_top {
+            viewport_builder = viewport_builder.with_always_on_top();
+        }
+
+        ctx.show_viewport_deferred(self.id, viewport_builder, move |ctx, class| {
+            if class == ViewportClass::Embedded {
+                let mut window_open = open.load(Ordering::Acquire);
+                egui::Window::new(InterruptLog::TITLE)
+                    .open(&mut window_open)
+                    .show(ctx, |ui| state.lock().ui(ui, opts.enabled));
+                open.store(window_open, Ordering::Release);
+            } else {
+                CentralPanel::default().show(ctx, |ui| state.lock().ui(ui, opts.enabled));
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    open.store(false, Ordering::Release);
+                }
+            }
+            if !open.load(Ordering::Acquire) {
+                state.lock().update_logging(false);
+            }
+        });
+    }
+}
+
+impl State {
+    fn update_logging(&self, open: bool) {
+        self.tx.event(EmulationEvent::InterruptLog(open));
+    }
+
+    const fn visible(&self, interrupt: &Interrupt) -> bool {
+        match interrupt.kind {
+            InterruptKind::Nmi => self.show_nmi,
+            InterruptKind::Irq(irq) => {
+                (self.show_frame_counter && irq.contains(Irq::FRAME_COUNTER))
+                    || (self.show_dmc && irq.contains(Irq::DMC))
+                    || (self.show_mapper && irq.contains(Irq::MAPPER))
+            }
+        }
+    }
+
+    fn ui(&mut self, ui: &mut Ui, enabled: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        ui.add_enabled_ui(enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Show:");
+                ui.checkbox(&mut self.show_nmi, "NMI");
+                ui.checkbox(&mut self.show_frame_counter, "APU Frame IRQ");
+                ui.checkbox(&mut self.show_dmc, "DMC IRQ");
+                ui.checkbox(&mut self.show_mapper, "Mapper IRQ");
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.auto_scroll, "Auto-scroll");
+                if ui.button("Clear").clicked() {
+                    self.interrupts.clear();
+                }
+            });
+
+            ui.separator();
+
+            ScrollArea::both()
+                .auto_shrink(false)
+                .stick_to_bottom(self.auto_scroll)
+                .show(ui, |ui| {
+                    Grid::new("interrupt_log")
+                        .num_columns(6)
+                        .spacing([20.0, 4.0])
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("Type");
+                            ui.strong("Frame");
+                            ui.strong("Scanline");
+                            ui.strong("Cycle");
+                            ui.strong("CPU Cycle");
+                            ui.strong("PC");
+                            ui.end_row();
+
+                            let annotations = self.annotations.lock();
+                            for interrupt in self.interrupts.iter().filter(|i| self.visible(i)) {
+                                ui.label(interrupt.kind.to_string());
+                                ui.label(interrupt.frame.to_string());
+                                ui.label(interrupt.scanline.to_string());
+                                ui.label(interrupt.cycle.to_string());
+                                ui.label(interrupt.cpu_cycle.to_string());
+                                let pc = ui.monospace(match annotations.name(interrupt.pc) {
+                                    Some(name) => format!("${:04X} {name}", interrupt.pc),
+                                    None => format!("${:04X}", interrupt.pc),
+                                });
+                                if let Some(annotation) = annotations
+                                    .get(interrupt.pc)
+                                    .filter(|annotation| !annotation.comment.is_empty())
+                                {
+                                    pc.on_hover_text(annotation.comment.as_str());
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+        });
+    }
+}
@@ -1,19 +1,32 @@
-use crate::nes::{
-    action::Action,
-    config::Config,
-    event::{ConfigEvent, NesEventProxy},
-    input::{Gamepads, Input},
-    renderer::gui::lib::ViewportOptions,
+use crate::{
+    feature,
+    nes::{
+        action::{Action, Ui as UiAction},
+        config::Config,
+        event::{ConfigEvent, NesEventProxy, UiEvent},
+        input::{ActionBindings, Gamepads, Input},
+        renderer::gui::{lib::ViewportOptions, Menu, MessageType},
+    },
 };
 use egui::{
-    Align2, Button, CentralPanel, Context, Grid, ScrollArea, Ui, Vec2, ViewportClass, ViewportId,
+    Align2, Button, CentralPanel, Context, Grid, RichText, ScrollArea, Ui, Vec2, ViewportClass,
+    ViewportId,
 };
 use parking_lot::Mutex;
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tetanes_core::{
+    action::Action as DeckAction,
+    common::ResetKind,
+    fs,
+    input::{JoypadBtn, Player},
 };
-use tetanes_core::input::Player;
 use tracing::warn;
 use uuid::Uuid;
 use winit::event::ElementState;
@@ -23,8 +36,30 @@ pub enum Tab {
     #[default]
     Shortcuts,
     Joypad(Player),
+    Report,
 }
 
+const PLAYERS: [Player; 4] = [Player::One, Player::Two, Player::Three, Player::Four];
+
+/// Actions that should be bound to play and get around without the menus.
+const IMPORTANT_ACTIONS: [Action; 15] = [
+    Action::Ui(UiAction::TogglePause),
+    Action::Ui(UiAction::LoadRom),
+    Action::Menu(Menu::Keybinds),
+    Action::Menu(Menu::Preferences),
+    Action::Deck(DeckAction::Reset(ResetKind::Soft)),
+    Action::Deck(DeckAction::SaveState),
+    Action::Deck(DeckAction::LoadState),
+    Action::Deck(DeckAction::Joypad((Player::One, JoypadBtn::Left))),
+    Action::Deck(DeckAction::Joypad((Player::One, JoypadBtn::Right))),
+    Action::Deck(DeckAction::Joypad((Player::One, JoypadBtn::Up))),
+    Action::Deck(DeckAction::Joypad((Player::One, JoypadBtn::Down))),
+    Action::Deck(DeckAction::Joypad((Player::One, JoypadBtn::A))),
+    Action::Deck(DeckAction::Joypad((Player::One, JoypadBtn::B))),
+    Action::Deck(DeckAction::Joypad((Player::One, JoypadBtn::Select))),
+    Action::Deck(DeckAction::Joypad((Player::One, JoypadBtn::Start))),
+];
+
 #[derive(Debug)]
 #[must_use]
 pub struct State {
@@ -66,6 +101,97 @@ pub struct ConnectedGamepad {
     pub assignment: Option<Player>,
 }
 
+/// Actions bound to the same input, paired with their player for joypad actions.
+pub type BoundActions = Vec<(Option<Player>, Action)>;
+
+/// Bindings that are missing or conflicting. Joypad actions are paired with their player.
+#[derive(Debug)]
+#[must_use]
+pub struct Report {
+    pub unbound: Vec<Action>,
+    pub duplicates: Vec<(Input, BoundActions)>,
+}
+
+impl Report {
+    pub fn new(cfg: &Config) -> Self {
+        let unbound = IMPORTANT_ACTIONS
+            .into_iter()
+            .filter(|action| {
+                let bindings = if action.is_joypad() {
+                    &cfg.input.joypads[Player::One as usize]
+                } else {
+                    &cfg.input.shortcuts
+                };
+                !bindings
+                    .get(action)
+                    .is_some_and(|bind| bind.bindings.iter().any(Option::is_some))
+            })
+            .collect();
+
+        let mut actions_by_input = HashMap::<_, Vec<_>>::new();
+        for (player, bind) in all_bindings(cfg) {
+            for input in bind.bindings.iter().flatten() {
+                actions_by_input
+                    .entry(*input)
+                    .or_default()
+                    .push((player, bind.action));
+            }
+        }
+        let mut duplicates = actions_by_input
+            .into_iter()
+            .filter(|(_, actions)| actions.len() > 1)
+            .collect::<Vec<_>>();
+        duplicates.sort_by_cached_key(|(input, _)| Input::fmt(*input));
+
+        Self {
+            unbound,
+            duplicates,
+        }
+    }
+}
+
+/// Formats the current bindings as a Markdown cheat sheet, omitting unbound actions.
+pub fn cheat_sheet(cfg: &Config) -> String {
+    let mut sheet = String::from("# TetaNES Keybinds\n");
+    let sections = std::iter::once((None, &cfg.input.shortcuts))
+        .chain(PLAYERS.into_iter().map(Some).zip(&cfg.input.joypads));
+    for (player, bindings) in sections {
+        let _ = write!(
+            sheet,
+            "\n## {}\n\n| Action | Bindings |\n| --- | --- |\n",
+            context_label(player)
+        );
+        for (action, bind) in bindings {
+            let inputs = bind
+                .bindings
+                .iter()
+                .flatten()
+                .map(|input| Input::fmt(*input).replace('|', "\\|"))
+                .collect::<Vec<_>>();
+            if !inputs.is_empty() {
+                let _ = writeln!(sheet, "| {action} | {} |", inputs.join(", "));
+            }
+        }
+    }
+    sheet
+}
+
+fn all_bindings(cfg: &Config) -> impl Iterator<Item = (Option<Player>, &ActionBindings)> {
+    let shortcuts = cfg.input.shortcuts.values().map(|bind| (None, bind));
+    let joypads = PLAYERS
+        .into_iter()
+        .zip(&cfg.input.joypads)
+        .flat_map(|(player, binds)| binds.values().map(move |bind| (Some(player), bind)));
+    shortcuts.chain(joypads)
+}
+
+fn context_label(player: Option<Player>) -> String {
+    match player {
+        None => "Shortcuts".to_string(),
+        Some(player) => format!("Player {}", player as usize + 1),
+    }
+}
+
 impl Keybinds {
     const TITLE: &'static str = "🖮 Keybinds";
 
@@ -193,6 +319,7 @@ impl State {
                 ui.selectable_value(&mut self.tab, Tab::Joypad(Player::Two), "Player2");
                 ui.selectable_value(&mut self.tab, Tab::Joypad(Player::Three), "Player3");
                 ui.selectable_value(&mut self.tab, Tab::Joypad(Player::Four), "Player4");
+                ui.selectable_value(&mut self.tab, Tab::Report, "Report");
             });
 
             ui.separator();
@@ -202,6 +329,7 @@ impl State {
                 Tab::Joypad(player) => {
                     self.list(ui, Some(player), cfg, gamepad_state.connected.as_deref())
                 }
+                Tab::Report => self.report(ui, cfg),
             }
         });
     }
@@ -272,6 +400,84 @@ impl State {
         });
     }
 
+    fn report(&mut self, ui: &mut Ui, cfg: &Config) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let report = Report::new(cfg);
+
+        ui.horizontal(|ui| {
+            if ui
+                .button("📋 Copy Cheat Sheet")
+                .on_hover_text("Copy the current bindings as Markdown.")
+                .clicked()
+            {
+                ui.ctx().copy_text(cheat_sheet(cfg));
+            }
+            if feature!(Filesystem) {
+                let res = ui
+                    .button("💾 Save Cheat Sheet")
+                    .on_hover_text("Save the current bindings as a printable Markdown file.");
+                if res.clicked() {
+                    let path = Config::default_data_dir().join("keybinds.md");
+                    let message = match fs::save_raw(&path, cheat_sheet(cfg).as_bytes()) {
+                        Ok(()) => (
+                            MessageType::Info,
+                            format!("Saved Keybinds Cheat Sheet: {}", path.display()),
+                        ),
+                        Err(err) => (
+                            MessageType::Error,
+                            format!("Failed to save keybinds cheat sheet: {err}"),
+                        ),
+                    };
+                    self.tx.event(UiEvent::Message(message));
+                }
+            }
+        });
+
+        ui.separator();
+
+        ScrollArea::vertical().auto_shrink(false).show(ui, |ui| {
+            ui.heading("Unbound Actions");
+            if report.unbound.is_empty() {
+                ui.label("All important actions are bound.");
+            } else {
+                for action in &report.unbound {
+                    let label = if action.is_joypad() {
+                        format!("{action} ({})", context_label(Some(Player::One)))
+                    } else {
+                        action.to_string()
+                    };
+                    ui.label(RichText::new(label).color(ui.visuals().warn_fg_color));
+                }
+            }
+
+            ui.add_space(8.0);
+            ui.heading("Duplicate Bindings");
+            if report.duplicates.is_empty() {
+                ui.label("No input is bound to more than one action.");
+            } else {
+                Grid::new("keybind_duplicates")
+                    .num_columns(2)
+                    .spacing([20.0, 6.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (input, actions) in &report.duplicates {
+                            ui.strong(Input::fmt(*input));
+                            let actions = actions
+                                .iter()
+                                .map(|(player, action)| {
+                                    format!("{action} ({})", context_label(*player))
+                                })
+                                .collect::<Vec<_>>();
+                            ui.label(actions.join(", "));
+                            ui.end_row();
+                        }
+                    });
+            }
+        });
+    }
+
     fn player_gamepad_combo(
         &mut self,
         ui: &mut Ui,
@@ -7,7 +7,7 @@ use crate::nes::{
 };
 use egui::{
     Checkbox, Context, KeyboardShortcut, Pos2, Rect, Response, Sense, TextStyle, TextWrapMode, Ui,
-    Widget, WidgetText,
+    Vec2, Widget, WidgetText,
 };
 use std::ops::{Deref, DerefMut};
 use tetanes_core::ppu::Ppu;
@@ -57,6 +57,30 @@ pub fn cursor_to_zapper(x: f32, y: f32, rect: Rect) -> Option<Pos2> {
     ((0.0..width).contains(&x) && (0.0..height).contains(&y)).then_some(Pos2::new(x, y))
 }
 
+/// Returns the largest rect centered in `available` that scales a texture of `size` pixels by a
+/// whole number of physical pixels, aligned to the physical pixel grid.
+///
+/// Rows are scaled by an integer factor and the `aspect_ratio` stretch is rounded to whole
+/// physical pixels, so the image stays crisp regardless of fractional `pixels_per_point`.
+pub fn pixel_perfect_rect(
+    available: Rect,
+    size: Vec2,
+    aspect_ratio: f32,
+    pixels_per_point: f32,
+) -> Rect {
+    let available_px = available.size() * pixels_per_point;
+    let scale = (available_px.x / (size.x * aspect_ratio))
+        .min(available_px.y / size.y)
+        .floor()
+        .max(1.0);
+    let size_px = Vec2::new((size.x * aspect_ratio * scale).round(), size.y * scale);
+    let min_px = ((available.center().to_vec2() * pixels_per_point) - size_px / 2.0).round();
+    Rect::from_min_size(
+        (min_px / pixels_per_point).to_pos2(),
+        size_px / pixels_per_point,
+    )
+}
+
 pub fn input_down(ui: &mut Ui, gamepads: Option<&Gamepads>, cfg: &Config, input: Input) -> bool {
     ui.input_mut(|i| match input {
         Input::Key(keycode, modifier_state) => key_from_keycode(keycode).is_some_and(|key| {
@@ -0,0 +1,528 @@
+use crate::nes::{
+    annotation::{Annotation, Annotations, DataType},
+    emulation::memory::MemorySource,
+    event::{ConfigEvent, EmulationEvent, NesEventProxy},
+    renderer::gui::lib::ViewportOptions,
+};
+use egui::{
+    Button, CentralPanel, ComboBox, Context, DragValue, Grid, ScrollArea, TextEdit, Ui, Vec2,
+    ViewportClass, ViewportId,
+};
+use parking_lot::Mutex;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use tetanes_core::{
+    debug::{search_memory, MemoryComparison, MemoryMatch, MemorySnapshot},
+    genie::GenieCode,
+};
+
+/// A watched RAM address.
+#[derive(Debug, Copy, Clone)]
+#[must_use]
+struct Watch {
+    addr: u16,
+    val: Option<u8>,
+    frozen: bool,
+}
+
+#[derive(Debug)]
+#[must_use]
+struct State {
+    tx: NesEventProxy,
+    before: MemorySource,
+    after: MemorySource,
+    comparison: MemoryComparison,
+    equals: u8,
+    /// Narrow down existing results on the next search instead of starting a new one.
+    narrow: bool,
+    results: Option<Vec<MemoryMatch>>,
+    watches: Vec<Watch>,
+    annotations: Arc<Mutex<Annotations>>,
+    /// Address to annotate that isn't being watched, e.g. a subroutine in ROM.
+    annotate_addr: u16,
+    /// Address and annotation being edited.
+    editing: Option<(u16, Annotation)>,
+}
+
+#[derive(Debug)]
+#[must_use]
+pub struct MemorySearch {
+    id: ViewportId,
+    open: Arc<AtomicBool>,
+    state: Arc<Mutex<State>>,
+}
+
+impl MemorySearch {
+    const TITLE: &'static str = "🔎 Memory Search";
+    const SAVE_SLOTS: u8 = 8;
+    const MAX_DISPLAYED_RESULTS: usize = 1000;
+
+    pub fn new(tx: NesEventProxy, annotations: Arc<Mutex<Annotations>>) -> Self {
+        Self {
+            id: ViewportId::from_hash_of(Self::TITLE),
+            open: Arc::new(AtomicBool::new(false)),
+            state: Arc::new(Mutex::new(State {
+                tx,
+                before: MemorySource::SaveSlot(1),
+                after: MemorySource::Current,
+                comparison: MemoryComparison::Changed,
+                equals: 0,
+                narrow: false,
+                results: None,
+                watches: Vec::new(),
+                annotations,
+                annotate_addr: 0x0000,
+                editing: None,
+            })),
+        }
+    }
+
+    pub const fn id(&self) -> ViewportId {
+        self.id
+    }
+
+    pub fn open(&self) -> bool {
+        self.open.load(Ordering::Acquire)
+    }
+
+    pub fn set_open(&self, open: bool) {
+        self.open.store(open, Ordering::Release);
+        self.state.lock().update_watching(open);
+    }
+
+    pub fn toggle_open(&self) {
+        let _ = self
+            .open
+            .fetch_update(Ordering::Release, Ordering::Acquire, |open| Some(!open));
+        self.state.lock().update_watching(self.open());
+    }
+
+    /// Search RAM snapshots requested by the last search.
+    pub fn update_snapshots(&mut self, before: &MemorySnapshot, after: &MemorySnapshot) {
+        self.state.lock().search(before, after);
+    }
+
+    pub fn update_watches(&mut self, values: &[(u16, u8)]) {
+        let mut state = self.state.lock();
+        for &(addr, val) in values {
+            if let Some(watch) = state.watches.iter_mut().find(|watch| watch.addr == addr) {
+                watch.val = Some(val);
+            }
+        }
+    }
+
+    /// Clear results and watches, which are specific to the loaded ROM.
+    pub fn clear(&mut self) {
+        let mut state = self.state.lock();
+        state.results = None;
+        state.watches.clear();
+        state.editing = None;
+    }
+
+    /// Watch every annotated RAM address of the loaded ROM.
+    pub fn watch_annotated(&mut self) {
+        let mut state = self.state.lock();
+        let addrs = state
+            .annotations
+            .lock()
+            .iter()
+            .map(|(addr, _)| addr)
+            .filter(|&addr| MemorySnapshot::index(addr).is_some())
+            .collect::<Vec<_>>();
+        for addr in addrs {
+            if !state.watches.iter().any(|watch| watch.addr == addr) {
+                state.watches.push(Watch {
+                    addr,
+                    val: None,
+                    frozen: false,
+                });
+            }
+        }
+        state.update_watching(self.open());
+    }
+
+    pub fn show(&mut self, ctx: &Context, opts: ViewportOptions) {
+        if !self.open.load(Ordering::Relaxed) {
+            return;
+        }
+
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let open = Arc::clone(&self.open);
+        let state = Arc::clone(&self.state);
+
+        let mut viewport_builder = egui::ViewportBuilder::default()
+            .with_title(Self::TITLE)
+            .with_inner_size(Vec2::new(480.0, 560.0));
+        if opts.always_on_top {
+            viewport_builder = viewport_builder.with_always_on_top();
+        }
+
+        ctx.show_viewport_deferred(self.id, viewport_builder, move |ctx, class| {
+            if class == ViewportClass::Embedded {
+                let mut window_open = open.load(Ordering::Acquire);
+                egui::Window::new(MemorySearch::TITLE)
+                    .open(&mut window_open)
+                    .show(ctx, |ui| state.lock().ui(ui, opts.enabled));
+                open.store(window_open, Ordering::Release);
+            } else {
+                CentralPanel::default().show(ctx, |ui| state.lock().ui(ui, opts.enabled));
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    open.store(false, Ordering::Release);
+                }
+            }
+            if !open.load(Ordering::Acquire) {
+                state.lock().update_watching(false);
+            }
+        });
+    }
+}
+
+impl State {
+    fn update_watching(&self, open: bool) {
+        let addrs = if open {
+            self.watches.iter().map(|watch| watch.addr).collect()
+        } else {
+            Vec::new()
+        };
+        self.tx.event(EmulationEvent::MemoryWatch(addrs));
+    }
+
+    fn search(&mut self, before: &MemorySnapshot, after: &MemorySnapshot) {
+        let candidates = self
+            .results
+            .as_ref()
+            .filter(|_| self.narrow)
+            .map(|results| results.iter().map(|m| m.addr).collect::<Vec<_>>());
+        self.results = Some(search_memory(
+            before,
+            after,
+            self.comparison,
+            candidates.as_deref(),
+        ));
+    }
+
+    fn add_watch(&mut self, addr: u16, val: u8) {
+        if !self.watches.iter().any(|watch| watch.addr == addr) {
+            self.watches.push(Watch {
+                addr,
+                val: Some(val),
+                frozen: false,
+            });
+            self.update_watching(true);
+        }
+    }
+
+    fn freeze(&mut self, index: usize, frozen: bool) {
+        let watch = &mut self.watches[index];
+        watch.frozen = frozen;
+        let val = watch.val.filter(|_| frozen);
+        self.tx
+            .event(EmulationEvent::MemoryFreeze((watch.addr, val)));
+    }
+
+    /// Add a cheat that keeps a watched address at its current value. PRG-ROM addresses are
+    /// exported as Game Genie codes, while others, like RAM, use raw address codes.
+    fn export(&mut self, ui: &Ui, index: usize) {
+        let watch = &self.watches[index];
+        let Some(val) = watch.val else {
+            return;
+        };
+        let code = GenieCode::encode(watch.addr, val, None)
+            .unwrap_or_else(|| GenieCode::from_addr(watch.addr, val, None));
+        ui.ctx().copy_text(code.code().to_string());
+        self.tx.event(ConfigEvent::GenieCodeAdded(code));
+    }
+
+    fn source_combo(ui: &mut Ui, id: &str, source: &mut MemorySource) {
+        ComboBox::from_id_salt(id)
+            .selected_text(source.to_string())
+            .show_ui(ui, |ui| {
+                ui.selectable_value(
+                    source,
+                    MemorySource::Current,
+                    MemorySource::Current.to_string(),
+                );
+                for slot in 1..=MemorySearch::SAVE_SLOTS {
+                    let slot = MemorySource::SaveSlot(slot);
+                    ui.selectable_value(source, slot, slot.to_string());
+                }
+            });
+    }
+
+    fn ui(&mut self, ui: &mut Ui, enabled: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        ui.add_enabled_ui(enabled, |ui| {
+            self.search_ui(ui);
+            ui.separator();
+            self.watches_ui(ui);
+            self.annotation_ui(ui);
+            ui.separator();
+            self.results_ui(ui);
+        });
+    }
+
+    fn search_ui(&mut self, ui: &mut Ui) {
+        Grid::new("memory_search")
+            .num_columns(2)
+            .spacing([20.0, 6.0])
+            .show(ui, |ui| {
+                ui.strong("Compare:");
+                ui.horizontal(|ui| {
+                    Self::source_combo(ui, "memory_search_before", &mut self.before);
+                    ui.label("to");
+                    Self::source_combo(ui, "memory_search_after", &mut self.after);
+                });
+                ui.end_row();
+
+                ui.strong("Values That:");
+                ui.horizontal_wrapped(|ui| {
+                    ui.radio_value(&mut self.comparison, MemoryComparison::Changed, "Changed");
+                    ui.radio_value(
+                        &mut self.comparison,
+                        MemoryComparison::Unchanged,
+                        "Unchanged",
+                    );
+                    ui.radio_value(
+                        &mut self.comparison,
+                        MemoryComparison::Increased,
+                        "Increased",
+                    );
+                    ui.radio_value(
+                        &mut self.comparison,
+                        MemoryComparison::Decreased,
+                        "Decreased",
+                    );
+                    let equals = matches!(self.comparison, MemoryComparison::Equals(_));
+                    if ui.radio(equals, "Equal").clicked() {
+                        self.comparison = MemoryComparison::Equals(self.equals);
+                    }
+                    let res = ui.add(DragValue::new(&mut self.equals).hexadecimal(2, false, true));
+                    if res.changed() && equals {
+                        self.comparison = MemoryComparison::Equals(self.equals);
+                    }
+                });
+                ui.end_row();
+            });
+
+        ui.horizontal(|ui| {
+            if ui.button("🔎 Search").clicked() {
+                self.tx
+                    .event(EmulationEvent::MemorySnapshots((self.before, self.after)));
+            }
+            ui.add_enabled_ui(self.results.is_some(), |ui| {
+                ui.checkbox(&mut self.narrow, "Narrow Results")
+                    .on_hover_text("Only search addresses matched by the previous search.");
+                if ui.button("Reset").clicked() {
+                    self.results = None;
+                    self.narrow = false;
+                }
+            });
+        });
+    }
+
+    fn watches_ui(&mut self, ui: &mut Ui) {
+        ui.strong("Watches");
+        if self.watches.is_empty() {
+            ui.label("Watch addresses from search results to see their live values.");
+            return;
+        }
+
+        let mut freeze = None;
+        let mut export = None;
+        let mut remove = None;
+        let annotations = self.annotations.lock();
+        Grid::new("memory_watches")
+            .num_columns(5)
+            .spacing([20.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Address");
+                ui.strong("Name");
+                ui.strong("Value");
+                ui.strong("Frozen");
+                ui.end_row();
+
+                for (index, watch) in self.watches.iter_mut().enumerate() {
+                    ui.monospace(format!("${:04X}", watch.addr));
+                    let name = ui.label(annotations.name(watch.addr).unwrap_or_default());
+                    if let Some(annotation) = annotations
+                        .get(watch.addr)
+                        .filter(|annotation| !annotation.comment.is_empty())
+                    {
+                        name.on_hover_text(annotation.comment.as_str());
+                    }
+                    match &mut watch.val {
+                        Some(val) if watch.frozen => {
+                            let res = ui
+                                .add(DragValue::new(val).hexadecimal(2, false, true))
+                                .on_hover_text("Value to keep this address at.");
+                            if res.changed() {
+                                freeze = Some((index, true));
+                            }
+                        }
+                        Some(val) => {
+                            ui.monospace(annotations.format(watch.addr, *val));
+                        }
+                        None => {
+                            ui.monospace("--");
+                        }
+                    }
+                    let mut frozen = watch.frozen;
+                    if ui
+                        .checkbox(&mut frozen, "")
+                        .on_hover_text("Keep this address at its current value.")
+                        .changed()
+                    {
+                        freeze = Some((index, frozen));
+                    }
+                    ui.horizontal(|ui| {
+                        let res = ui
+                            .add_enabled(watch.val.is_some(), Button::new("💾"))
+                            .on_hover_text(
+                                "Add a cheat keeping this address at its current value and copy \
+                                its code to the clipboard. ROM addresses are exported as Game \
+                                Genie codes, other addresses as raw address codes.",
+                            );
+                        if res.clicked() {
+                            export = Some(index);
+                        }
+                        if ui.button("✏").on_hover_text("Edit annotation").clicked() {
+                            let annotation = annotations.get(watch.addr).cloned();
+                            self.editing = Some((watch.addr, annotation.unwrap_or_default()));
+                        }
+                        if ui.button("✖").on_hover_text("Remove watch").clicked() {
+                            remove = Some(index);
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        drop(annotations);
+
+        if let Some((index, frozen)) = freeze {
+            self.freeze(index, frozen);
+        }
+        if let Some(index) = export {
+            self.export(ui, index);
+        }
+        if let Some(index) = remove {
+            if self.watches[index].frozen {
+                self.freeze(index, false);
+            }
+            let _ = self.watches.remove(index);
+            self.update_watching(true);
+        }
+    }
+
+    fn annotation_ui(&mut self, ui: &mut Ui) {
+        let Some((addr, annotation)) = &mut self.editing else {
+            ui.horizontal(|ui| {
+                ui.label("Annotate Address:");
+                ui.add(DragValue::new(&mut self.annotate_addr).hexadecimal(4, false, true));
+                if ui.button("✏ Edit").clicked() {
+                    let addr = self.annotate_addr;
+                    let annotation = self.annotations.lock().get(addr).cloned();
+                    self.editing = Some((addr, annotation.unwrap_or_default()));
+                }
+            });
+            return;
+        };
+
+        ui.add_space(4.0);
+        ui.strong(format!("Annotate ${addr:04X}"));
+        Grid::new("memory_annotation")
+            .num_columns(2)
+            .spacing([20.0, 4.0])
+            .show(ui, |ui| {
+                ui.label("Name:");
+                ui.add(TextEdit::singleline(&mut annotation.name).desired_width(200.0));
+                ui.end_row();
+
+                ui.label("Comment:");
+                ui.add(
+                    TextEdit::multiline(&mut annotation.comment)
+                        .desired_rows(2)
+                        .desired_width(200.0),
+                );
+                ui.end_row();
+
+                ui.label("Type:");
+                ComboBox::from_id_salt("memory_annotation_type")
+                    .selected_text(annotation.data_type.as_ref())
+                    .show_ui(ui, |ui| {
+                        for data_type in DataType::ALL {
+                            ui.selectable_value(
+                                &mut annotation.data_type,
+                                data_type,
+                                data_type.as_ref(),
+                            );
+                        }
+                    });
+                ui.end_row();
+            });
+
+        ui.horizontal(|ui| {
+            if ui
+                .button("Save")
+                .on_hover_text("Annotations are saved for this ROM and restored when it's loaded.")
+                .clicked()
+            {
+                if let Some((addr, annotation)) = self.editing.take() {
+                    self.annotations.lock().set(addr, annotation);
+                }
+            }
+            if ui.button("Cancel").clicked() {
+                self.editing = None;
+            }
+        });
+    }
+
+    fn results_ui(&mut self, ui: &mut Ui) {
+        let Some(results) = &self.results else {
+            ui.label("Save a state, play until something changes, then search.");
+            return;
+        };
+
+        ui.strong(format!("{} Matches", results.len()));
+        if results.len() > MemorySearch::MAX_DISPLAYED_RESULTS {
+            ui.label(format!(
+                "Showing the first {}. Narrow results to see more.",
+                MemorySearch::MAX_DISPLAYED_RESULTS
+            ));
+        }
+
+        let mut watch = None;
+        ScrollArea::vertical().auto_shrink(false).show(ui, |ui| {
+            Grid::new("memory_search_results")
+                .num_columns(4)
+                .spacing([20.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Address");
+                    ui.strong("Before");
+                    ui.strong("After");
+                    ui.end_row();
+
+                    for result in results.iter().take(MemorySearch::MAX_DISPLAYED_RESULTS) {
+                        ui.monospace(format!("${:04X}", result.addr));
+                        ui.monospace(format!("${:02X}", result.before));
+                        ui.monospace(format!("${:02X}", result.after));
+                        if ui.button("👁 Watch").clicked() {
+                            watch = Some((result.addr, result.after));
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+        if let Some((addr, val)) = watch {
+            self.add_watch(addr, val);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use crate::nes::{
+    config::Config,
+    event::{ConfigEvent, EmulationEvent, NesEventProxy},
+    netplay::{NetplayStatus, DEFAULT_PORT},
+};
+use egui::{Button, Context, DragValue, Grid, Key, TextEdit, Window};
+
+/// Window for hosting or joining a netplay session.
+#[derive(Debug)]
+#[must_use]
+pub struct Netplay {
+    tx: NesEventProxy,
+    open: bool,
+    port: u16,
+    addr: String,
+    status: NetplayStatus,
+}
+
+impl Netplay {
+    pub const fn new(tx: NesEventProxy) -> Self {
+        Self {
+            tx,
+            open: false,
+            port: DEFAULT_PORT,
+            addr: String::new(),
+            status: NetplayStatus::Disconnected,
+        }
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    pub fn set_status(&mut self, status: NetplayStatus) {
+        self.status = status;
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool, rom_loaded: bool, cfg: &Config) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let mut open = self.open;
+        Window::new("🌐 Netplay")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| {
+                    let disconnected = self.status == NetplayStatus::Disconnected;
+                    ui.add_enabled_ui(disconnected && rom_loaded, |ui| {
+                        ui.label(
+                            "Play with someone on another device running the same ROM. The host \
+                            is player one and the peer joining is player two.",
+                        );
+                        ui.add_space(8.0);
+
+                        Grid::new("netplay_connect")
+                            .num_columns(3)
+                            .spacing([20.0, 6.0])
+                            .show(ui, |ui| {
+                                ui.label("Port:");
+                                ui.add(DragValue::new(&mut self.port));
+                                if ui
+                                    .button("Host")
+                                    .on_hover_text(
+                                        "Wait for a peer to join. Play continues until they do.",
+                                    )
+                                    .clicked()
+                                {
+                                    self.tx.event(EmulationEvent::NetplayHost(self.port));
+                                }
+                                ui.end_row();
+
+                                ui.label("Address:");
+                                let res = ui.add(
+                                    TextEdit::singleline(&mut self.addr)
+                                        .hint_text(format!("192.168.1.2:{DEFAULT_PORT}")),
+                                );
+                                let submitted =
+                                    res.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+                                let addr = self.addr.trim();
+                                let join = ui.add_enabled(!addr.is_empty(), Button::new("Join"));
+                                if (join.clicked() || submitted) && !addr.is_empty() {
+                                    self.tx.event(EmulationEvent::NetplayJoin(addr.to_string()));
+                                }
+                                ui.end_row();
+                            });
+                    });
+
+                    ui.add_space(8.0);
+                    let mut input_delay = cfg.emulation.netplay_input_delay;
+                    ui.horizontal(|ui| {
+                        ui.label("Input Delay:");
+                        let res = ui
+                            .add(
+                                DragValue::new(&mut input_delay)
+                                    .range(0..=10)
+                                    .suffix(" frames"),
+                            )
+                            .on_hover_text(
+                                "Delay local input to hide network latency. Increase it if play \
+                                stutters.",
+                            );
+                        if res.changed() {
+                            self.tx.event(ConfigEvent::NetplayInputDelay(input_delay));
+                        }
+                    });
+
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label(match &self.status {
+                            NetplayStatus::Disconnected => "Disconnected".to_string(),
+                            NetplayStatus::Hosting { port } => {
+                                format!("Waiting for a peer on port {port}...")
+                            }
+                            NetplayStatus::Joining { addr } => format!("Joining {addr}..."),
+                            NetplayStatus::Syncing { received, total } => {
+                                format!("Syncing state... {received}/{total}")
+                            }
+                            NetplayStatus::Connected { peer, player } => {
+                                format!("Connected to {peer} as Player {player}")
+                            }
+                        });
+                        if !disconnected && ui.button("Disconnect").clicked() {
+                            self.tx.event(EmulationEvent::NetplayDisconnect);
+                        }
+                    });
+                });
+            });
+        self.open = open;
+    }
+}
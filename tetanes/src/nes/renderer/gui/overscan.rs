@@ -0,0 +1,27 @@
+use egui::{vec2, Color32, Rect, Stroke, Ui};
+use tetanes_core::ppu::Ppu;
+
+/// Scanlines at the top and bottom of the frame cropped by Hide Overscan.
+const OVERSCAN_LINES: f32 = 8.0;
+
+/// Shade the overscan at the top and bottom of the NES frame and outline the area left visible when
+/// it's hidden, to show what a CRT would normally crop.
+pub fn draw_overscan_outline(ui: &Ui, frame: Rect) {
+    #[cfg(feature = "profiling")]
+    puffin::profile_function!();
+
+    let height = frame.height() * OVERSCAN_LINES / Ppu::HEIGHT as f32;
+    let top = Rect::from_min_max(frame.min, frame.right_top() + vec2(0.0, height));
+    let bottom = Rect::from_min_max(frame.left_bottom() - vec2(0.0, height), frame.max);
+    let visible = Rect::from_min_max(top.left_bottom(), bottom.right_top());
+
+    let painter = ui.painter_at(frame);
+    let fill = Color32::from_rgba_unmultiplied(0xFF, 0x40, 0x40, 60);
+    painter.rect_filled(top, 0.0, fill);
+    painter.rect_filled(bottom, 0.0, fill);
+    painter.rect_stroke(
+        visible,
+        0.0,
+        Stroke::new(1.0, Color32::from_rgb(0xFF, 0x40, 0x40)),
+    );
+}
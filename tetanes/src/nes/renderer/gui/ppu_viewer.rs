@@ -8,8 +8,8 @@ use crate::nes::{
     },
 };
 use egui::{
-    show_tooltip_at_pointer, CentralPanel, Color32, Context, CursorIcon, DragValue, Grid, Image,
-    Label, Pos2, Rect, ScrollArea, Sense, SidePanel, Slider, TopBottomPanel, Ui, Vec2,
+    show_tooltip_at_pointer, CentralPanel, Color32, ComboBox, Context, CursorIcon, DragValue, Grid,
+    Image, Label, Pos2, Rect, ScrollArea, Sense, SidePanel, Slider, TopBottomPanel, Ui, Vec2,
     ViewportClass, ViewportId,
 };
 use parking_lot::Mutex;
@@ -59,6 +59,9 @@ struct PatternTablesState {
     texture: Texture,
     zoom: f32,
     selected: Option<Vec2>,
+    /// Palette to color tiles with, where `0..=3` are background palettes and `4..=7` are sprite
+    /// palettes.
+    palette: u8,
 }
 
 #[derive(Debug)]
@@ -216,6 +219,7 @@ impl PpuViewer {
                     ),
                     zoom: 3.0,
                     selected: None,
+                    palette: 0,
                 },
                 oam: OamState {
                     // 64 8x8 sprites with 4 color channels (RGBA)
@@ -283,18 +287,16 @@ impl PpuViewer {
         match state.tab {
             Tab::Nametables => {
                 ppu.load_nametables(&mut state.nametables.pixels);
-                let mut pixels = std::mem::take(&mut state.palette.pixels);
-                let mut colors = std::mem::take(&mut state.palette.colors);
-                ppu.load_palettes(&mut pixels, &mut colors);
-                state.palette.pixels = pixels;
-                state.palette.colors = colors;
+                state.load_palettes(&ppu);
                 state
                     .nametables
                     .texture
                     .update(queue, &state.nametables.pixels);
             }
             Tab::PatternTables => {
-                ppu.load_pattern_tables(&mut state.pattern_tables.pixels);
+                let palette = state.pattern_tables.palette;
+                ppu.load_pattern_tables_with_palette(&mut state.pattern_tables.pixels, palette);
+                state.load_palettes(&ppu);
                 state
                     .pattern_tables
                     .texture
@@ -317,6 +319,7 @@ impl PpuViewer {
                 state.oam.oam_pixels = oam_pixels;
                 state.oam.sprite_pixels = sprite_pixels;
                 state.oam.sprites = sprites;
+                state.load_palettes(&ppu);
 
                 state.oam.oam_texture.update(queue, &state.oam.oam_pixels);
                 state
@@ -324,13 +327,7 @@ impl PpuViewer {
                     .sprites_texture
                     .update(queue, &state.oam.sprite_pixels);
             }
-            Tab::Palette => {
-                let mut pixels = std::mem::take(&mut state.palette.pixels);
-                let mut colors = std::mem::take(&mut state.palette.colors);
-                ppu.load_palettes(&mut pixels, &mut colors);
-                state.palette.pixels = pixels;
-                state.palette.colors = colors;
-            }
+            Tab::Palette => state.load_palettes(&ppu),
         }
         state.ppu = ppu;
     }
@@ -371,12 +368,16 @@ impl PpuViewer {
 }
 
 impl State {
+    fn load_palettes(&mut self, ppu: &Ppu) {
+        ppu.load_palettes(&mut self.palette.pixels, &mut self.palette.colors);
+    }
+
     fn update_debugger(&self, open: bool) {
         let tx = self.tx.clone();
         let debugger = PpuDebugger {
             cycle: self.refresh_cycle,
             scanline: self.refresh_scanline,
-            callback: Arc::new(move |ppu| tx.event(DebugEvent::Ppu(ppu))),
+            callback: Arc::new(move |ppu| tx.event(DebugEvent::Ppu(Box::new(ppu)))),
         };
         self.tx.event(if open {
             EmulationEvent::AddDebugger(debugger.into())
@@ -876,13 +877,17 @@ impl State {
                     self.pattern_tables.selected,
                 );
 
+                ui.add_space(16.0);
+                ui.heading("Palette");
+                ui.separator();
+                self.pattern_tables_palette(ui);
+
                 ui.add_space(16.0);
                 ui.separator();
 
                 ui.collapsing("Settings", |ui| {
                     self.general_settings(ui);
                     self.grid_settings(ui);
-                    // TODO: Selectable palette/last known palette
                     zoom_slider(ui, &mut self.pattern_tables.zoom);
                 });
             });
@@ -928,6 +933,29 @@ impl State {
         });
     }
 
+    fn pattern_tables_palette(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ComboBox::from_id_salt("pattern_tables_palette")
+                .selected_text(palette_name(self.pattern_tables.palette))
+                .show_ui(ui, |ui| {
+                    for palette in 0..8 {
+                        ui.selectable_value(
+                            &mut self.pattern_tables.palette,
+                            palette,
+                            palette_name(palette),
+                        );
+                    }
+                })
+                .response
+                .on_hover_text("Palette to color tiles with, updated on the next refresh.");
+
+            let size = Vec2::splat(16.0);
+            let (rect, _) = ui.allocate_exact_size(Vec2::new(4.0 * size.x, size.y), Sense::hover());
+            let palette = usize::from(self.pattern_tables.palette);
+            self.palette_row(ui, palette, rect.min, size, true);
+        });
+    }
+
     fn pattern_tables_hover(&mut self, ui: &mut Ui, res: &egui::Response, pos: Pos2) {
         let image_rect = res.rect;
         let texture_size = self.pattern_tables.texture.size;
@@ -1109,10 +1137,73 @@ impl State {
                         }
                     }
                 });
+
+                ui.add_space(8.0);
+                ui.separator();
+                self.sprite_list(ui);
             });
         });
     }
 
+    fn sprite_list(&mut self, ui: &mut Ui) {
+        let texture_size = self.oam.oam_texture.size;
+        let mut selected = None;
+        Grid::new("oam_sprite_list")
+            .num_columns(7)
+            .spacing([16.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Sprite");
+                ui.strong("Preview");
+                ui.strong("X, Y");
+                ui.strong("Tile Address");
+                ui.strong("Palette");
+                ui.strong("Flip");
+                ui.strong("Priority");
+                ui.end_row();
+
+                for (index, sprite) in self.oam.sprites.iter().enumerate() {
+                    let offset = Vec2::new((index % 8) as f32, (index / 8) as f32) * 8.0;
+                    let is_selected = self.oam.oam_selected == Some(offset);
+                    if ui
+                        .selectable_label(is_selected, format!("{index:02}"))
+                        .clicked()
+                    {
+                        selected = Some(offset);
+                    }
+
+                    let uv = Rect::from_min_size(
+                        (offset / texture_size).to_pos2(),
+                        Vec2::splat(8.0) / texture_size,
+                    );
+                    let preview = Image::from_texture(self.oam.oam_texture.sized())
+                        .uv(uv)
+                        .maintain_aspect_ratio(false) // Ignore original aspect ratio
+                        .fit_to_exact_size(Vec2::splat(24.0));
+                    ui.add(preview);
+
+                    ui.label(format!("{}, {}", sprite.x, sprite.y));
+                    ui.label(format!("${:04X}", sprite.tile_addr));
+
+                    let size = Vec2::splat(12.0);
+                    let (rect, _) =
+                        ui.allocate_exact_size(Vec2::new(4.0 * size.x, size.y), Sense::hover());
+                    self.palette_row(ui, usize::from(sprite.palette >> 2), rect.min, size, true);
+
+                    ui.label(flip_name(sprite));
+                    ui.label(if sprite.bg_priority {
+                        "Behind BG"
+                    } else {
+                        "In Front"
+                    });
+                    ui.end_row();
+                }
+            });
+        if selected.is_some() {
+            self.oam.oam_selected = selected;
+        }
+    }
+
     fn oam_hover(&mut self, ui: &mut Ui, res: &egui::Response, pos: Pos2) {
         let image_rect = res.rect;
         let texture_size = self.oam.oam_texture.size;
@@ -1197,9 +1288,17 @@ impl State {
             ui.add(tile_image);
             ui.end_row();
 
+            let sprite = tile.and_then(|_| self.oam.sprites.get(usize::from(index)));
+
+            ui.strong("Sprite Index:");
+            if tile.is_some() {
+                ui.label(format!("{index}"));
+            }
+            ui.end_row();
+
             ui.strong("Tile Index:");
             if tile.is_some() {
-                ui.label(format!("${index:02X}"));
+                ui.label(format!("${:02X}", (tile_addr >> 4) & 0xFF));
             }
             ui.end_row();
 
@@ -1209,8 +1308,46 @@ impl State {
             }
             ui.end_row();
 
-            // TODO: sprite index, palete address, position, horizontal/vertical flip/backgroud
-            // priority, palette row
+            ui.strong("X, Y:");
+            if let Some(sprite) = sprite {
+                ui.label(format!("{}, {}", sprite.x, sprite.y));
+            }
+            ui.end_row();
+
+            ui.strong("Palette:");
+            if let Some(sprite) = sprite {
+                self.palette_row(
+                    ui,
+                    usize::from(sprite.palette >> 2),
+                    ui.cursor().min,
+                    Vec2::splat(16.0),
+                    true,
+                );
+            }
+            ui.end_row();
+
+            ui.strong("Palette Address:");
+            if let Some(sprite) = sprite {
+                let palette_addr = Ppu::PALETTE_START | u16::from(sprite.palette);
+                ui.label(format!("${palette_addr:04X}"));
+            }
+            ui.end_row();
+
+            ui.strong("Flip:");
+            if let Some(sprite) = sprite {
+                ui.label(flip_name(sprite));
+            }
+            ui.end_row();
+
+            ui.strong("Priority:");
+            if let Some(sprite) = sprite {
+                ui.label(if sprite.bg_priority {
+                    "Behind Background"
+                } else {
+                    "In Front of Background"
+                });
+            }
+            ui.end_row();
         });
     }
 
@@ -1405,6 +1542,25 @@ impl State {
     }
 }
 
+/// Name of one of the 8 palettes, where `0..=3` are background palettes and `4..=7` are sprite
+/// palettes.
+fn palette_name(palette: u8) -> String {
+    if palette < 4 {
+        format!("Background {palette}")
+    } else {
+        format!("Sprite {}", palette - 4)
+    }
+}
+
+const fn flip_name(sprite: &Sprite) -> &'static str {
+    match (sprite.flip_horizontal, sprite.flip_vertical) {
+        (false, false) => "None",
+        (true, false) => "Horizontal",
+        (false, true) => "Vertical",
+        (true, true) => "Both",
+    }
+}
+
 /// A Zoom slider
 fn zoom_slider(ui: &mut Ui, zoom: &mut f32) {
     ui.horizontal(|ui| {
@@ -1,30 +1,42 @@
 use crate::{
     feature,
     nes::{
-        config::{AudioConfig, Config, EmulationConfig, RendererConfig},
+        audio::recording::RecordingFormat,
+        config::{AttractModeConfig, AudioConfig, Config, EmulationConfig, RendererConfig},
         event::{ConfigEvent, EmulationEvent, NesEventProxy, UiEvent},
         renderer::{
             gui::{
                 lib::{RadioValue, ShortcutText, ShowShortcut, ViewportOptions},
                 MessageType,
             },
-            shader::Shader,
+            shader::{Shader, ShaderPass, ShaderPipeline},
         },
     },
+    platform::open_file_dialog,
 };
 use egui::{
-    Align, CentralPanel, Checkbox, Context, CursorIcon, DragValue, Grid, Key, Layout, ScrollArea,
-    Slider, TextEdit, Ui, Vec2, ViewportClass, ViewportId,
+    Align, Button, CentralPanel, Checkbox, ComboBox, Context, CursorIcon, DragValue, Grid, Key,
+    Layout, ScrollArea, Slider, TextEdit, Ui, Vec2, ViewportClass, ViewportId,
 };
 use parking_lot::Mutex;
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
 };
 use tetanes_core::{
-    action::Action as DeckAction, apu::Channel, common::NesRegion,
-    control_deck::Config as DeckConfig, fs, genie::GenieCode, input::FourPlayer, mem::RamState,
-    time::Duration, video::VideoFilter,
+    action::Action as DeckAction,
+    apu::{AudioChannel, Channel},
+    common::NesRegion,
+    control_deck::Config as DeckConfig,
+    fs,
+    genie::GenieCode,
+    input::{FourPlayer, MousePort},
+    mem::{RamRetention, RamState},
+    time::Duration,
+    video::{ColorBlindMode, ColorBlindness, ColorFilter, VideoFilter},
 };
 use tracing::warn;
 
@@ -34,6 +46,8 @@ pub struct State {
     tx: NesEventProxy,
     tab: Tab,
     genie_entry: GenieEntry,
+    /// Audio channels output by the loaded ROM.
+    audio_channels: Vec<AudioChannel>,
 }
 
 #[derive(Debug)]
@@ -71,6 +85,7 @@ impl Preferences {
                 tx,
                 tab: Tab::default(),
                 genie_entry: GenieEntry::default(),
+                audio_channels: Self::default_audio_channels(),
             })),
             resources: None,
         }
@@ -94,6 +109,16 @@ impl Preferences {
             .fetch_update(Ordering::Release, Ordering::Acquire, |open| Some(!open));
     }
 
+    /// Set the audio channels to list, or all channels if no ROM is loaded.
+    pub fn set_audio_channels(&self, audio_channels: Option<Vec<AudioChannel>>) {
+        self.state.lock().audio_channels =
+            audio_channels.unwrap_or_else(Self::default_audio_channels);
+    }
+
+    fn default_audio_channels() -> Vec<AudioChannel> {
+        Channel::ALL.map(AudioChannel::from).to_vec()
+    }
+
     pub fn prepare(&mut self, cfg: &Config) {
         self.resources = Some(cfg.clone());
     }
@@ -303,6 +328,163 @@ impl Preferences {
         }
     }
 
+    pub fn rom_overscan_checkbox(
+        tx: &NesEventProxy,
+        ui: &mut Ui,
+        cfg: &Config,
+        rom_name: &str,
+        shortcut: impl Into<Option<String>>,
+    ) {
+        let shortcut = shortcut.into();
+        let icon = shortcut.is_some().then_some("📺 ").unwrap_or_default();
+        let mut hide_overscan = cfg.hide_overscan(Some(rom_name));
+        let checkbox = Checkbox::new(&mut hide_overscan, format!("{icon}Hide Overscan for ROM"))
+            .shortcut_text(shortcut.unwrap_or_default());
+        let res = ui
+            .add(checkbox)
+            .on_hover_text("Override Hide Overscan for the loaded ROM only.");
+        if res.clicked() {
+            let hide_overscan =
+                (hide_overscan != cfg.renderer.hide_overscan).then_some(hide_overscan);
+            tx.event(ConfigEvent::RomOverscan((
+                rom_name.to_string(),
+                hide_overscan,
+            )));
+        }
+    }
+
+    pub fn overscan_outline_checkbox(
+        tx: &NesEventProxy,
+        ui: &mut Ui,
+        mut show_overscan_outline: bool,
+        shortcut: impl Into<Option<String>>,
+    ) {
+        let shortcut = shortcut.into();
+        let icon = shortcut.is_some().then_some("🔲 ").unwrap_or_default();
+        let checkbox = Checkbox::new(
+            &mut show_overscan_outline,
+            format!("{icon}Show Overscan Outline"),
+        )
+        .shortcut_text(shortcut.unwrap_or_default());
+        let res = ui.add(checkbox).on_hover_text(
+            "Outline the overscan area over the game while Hide Overscan is disabled.",
+        );
+        if res.clicked() {
+            tx.event(ConfigEvent::ShowOverscanOutline(show_overscan_outline));
+        }
+    }
+
+    pub fn pixel_perfect_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut pixel_perfect: bool) {
+        let res = ui
+            .checkbox(&mut pixel_perfect, "Pixel Perfect")
+            .on_hover_text("Scale the game by whole physical pixels with letterboxing, keeping pixels crisp on displays with fractional scaling.");
+        if res.clicked() {
+            tx.event(ConfigEvent::PixelPerfect(pixel_perfect));
+        }
+    }
+
+    pub fn snap_window_size_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut snap_window_size: bool) {
+        let res = ui
+            .checkbox(&mut snap_window_size, "Snap Window Size")
+            .on_hover_text("Snap the window to the nearest whole window scale when resized, keeping pixels crisp without going fullscreen.");
+        if res.clicked() {
+            tx.event(ConfigEvent::SnapWindowSize(snap_window_size));
+        }
+    }
+
+    pub fn translucent_checkbox(
+        tx: &NesEventProxy,
+        ui: &mut Ui,
+        mut translucent: bool,
+        shortcut: impl Into<Option<String>>,
+    ) {
+        let shortcut = shortcut.into();
+        let icon = shortcut.is_some().then_some("👻 ").unwrap_or_default();
+        let checkbox = Checkbox::new(&mut translucent, format!("{icon}Translucent Window"))
+            .shortcut_text(shortcut.unwrap_or_default());
+        let res = ui
+            .add(checkbox)
+            .on_hover_text("Show windows underneath through the game, e.g. to follow a guide or map while playing. Not supported on all platforms.");
+        if res.clicked() {
+            tx.event(ConfigEvent::Translucent(translucent));
+        }
+    }
+
+    pub fn window_opacity_slider(tx: &NesEventProxy, ui: &mut Ui, mut opacity: f32) {
+        let slider = Slider::new(&mut opacity, 0.1..=1.0)
+            .step_by(0.05)
+            .custom_formatter(|opacity, _| format!("{:.0}%", opacity * 100.0));
+        let res = ui
+            .add(slider)
+            .on_hover_text("Opacity of the game while the window is translucent.");
+        if res.changed() {
+            tx.event(ConfigEvent::WindowOpacity(opacity));
+        }
+    }
+
+    pub fn frame_step_audio_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut frame_step_audio: bool) {
+        let res = ui
+            .checkbox(&mut frame_step_audio, "Frame Step Audio")
+            .on_hover_text("Play the audio for each frame stepped while paused, to line up sound effects with frames.");
+        if res.clicked() {
+            tx.event(ConfigEvent::AudioFrameStep(frame_step_audio));
+        }
+    }
+
+    pub fn monitor_recording_checkbox(
+        tx: &NesEventProxy,
+        ui: &mut Ui,
+        mut monitor_recording: bool,
+    ) {
+        let res = ui
+            .checkbox(&mut monitor_recording, "Monitor Audio Recordings")
+            .on_hover_text("Keep playing audio through the speakers while recording it to a file. Disable to record without any sound playing.");
+        if res.clicked() {
+            tx.event(ConfigEvent::AudioMonitorRecording(monitor_recording));
+        }
+    }
+
+    pub fn split_channels_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut split_channels: bool) {
+        let res = ui
+            .checkbox(&mut split_channels, "Split Audio Recording Channels")
+            .on_hover_text("Record each APU channel to its own file, such as pulse1.wav and triangle.wav, instead of the mixed output.");
+        if res.clicked() {
+            tx.event(ConfigEvent::AudioSplitChannels(split_channels));
+        }
+    }
+
+    pub fn recording_format_radio(
+        tx: &NesEventProxy,
+        ui: &mut Ui,
+        mut recording_format: RecordingFormat,
+    ) {
+        let previous_format = recording_format;
+        ui.radio_value(
+            &mut recording_format,
+            RecordingFormat::Wav,
+            RecordingFormat::Wav.as_ref(),
+        )
+        .on_hover_text("Uncompressed 32-bit float audio.");
+        ui.radio_value(
+            &mut recording_format,
+            RecordingFormat::Flac,
+            RecordingFormat::Flac.as_ref(),
+        )
+        .on_hover_text("Lossless compressed 16-bit audio.");
+        if recording_format != previous_format {
+            tx.event(ConfigEvent::AudioRecordingFormat(recording_format));
+        }
+    }
+
+    pub fn match_frame_rate_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut match_frame_rate: bool) {
+        let res = ui
+            .checkbox(&mut match_frame_rate, "Match Frame Rate")
+            .on_hover_text("Present games at their native frame rate, such as 50Hz for PAL games, by switching display modes in fullscreen when available or evenly pacing frames otherwise.");
+        if res.clicked() {
+            tx.event(ConfigEvent::MatchFrameRate(match_frame_rate));
+        }
+    }
+
     pub fn video_filter_radio(tx: &NesEventProxy, ui: &mut Ui, mut filter: VideoFilter) {
         let previous_filter = filter;
         ui.radio_value(&mut filter, VideoFilter::Pixellate, "Pixellate")
@@ -316,14 +498,129 @@ impl Preferences {
         }
     }
 
-    pub fn shader_radio(tx: &NesEventProxy, ui: &mut Ui, mut shader: Shader) {
-        let previous_shader = shader;
-        ui.radio_value(&mut shader, Shader::None, "None")
-            .on_hover_text("No shader.");
-        ui.radio_value(&mut shader, Shader::CrtEasymode, "CRT Easymode")
-            .on_hover_text("Emulate traditional CRT aperture grill masking.");
-        if shader != previous_shader {
-            tx.event(ConfigEvent::Shader(shader));
+    pub fn color_filter_settings(tx: &NesEventProxy, ui: &mut Ui, color_filter: ColorFilter) {
+        let mut new_filter = color_filter;
+        ComboBox::from_id_salt("color_blindness")
+            .selected_text(new_filter.deficiency.as_ref())
+            .show_ui(ui, |ui| {
+                for deficiency in ColorBlindness::as_slice() {
+                    ui.selectable_value(
+                        &mut new_filter.deficiency,
+                        *deficiency,
+                        deficiency.as_ref(),
+                    );
+                }
+            });
+        ui.add_enabled_ui(new_filter.deficiency != ColorBlindness::None, |ui| {
+            ui.horizontal(|ui| {
+                ui.radio_value(
+                    &mut new_filter.mode,
+                    ColorBlindMode::Correct,
+                    ColorBlindMode::Correct.as_ref(),
+                )
+                .on_hover_text("Shift colors so sprites are easier to tell apart.");
+                ui.radio_value(
+                    &mut new_filter.mode,
+                    ColorBlindMode::Simulate,
+                    ColorBlindMode::Simulate.as_ref(),
+                )
+                .on_hover_text("Show colors as they appear with the selected color blindness.");
+            });
+            ui.add(Slider::new(&mut new_filter.strength, 0.0..=1.0).text("Strength"));
+        });
+        if new_filter != color_filter {
+            tx.event(ConfigEvent::ColorFilter(new_filter));
+        }
+    }
+
+    pub fn shader_checkboxes(tx: &NesEventProxy, ui: &mut Ui, shaders: &ShaderPipeline) {
+        if shaders.passes.is_empty() {
+            ui.label("None");
+            return;
+        }
+        let mut new_shaders = shaders.clone();
+        for pass in &mut new_shaders.passes {
+            ui.checkbox(&mut pass.enabled, pass.shader.as_ref())
+                .on_hover_text(Self::shader_hover_text(pass.shader));
+        }
+        if new_shaders != *shaders {
+            tx.event(ConfigEvent::Shader(new_shaders));
+        }
+    }
+
+    pub fn shader_pipeline(tx: &NesEventProxy, ui: &mut Ui, shaders: &ShaderPipeline) {
+        let mut new_shaders = shaders.clone();
+        let passes = &mut new_shaders.passes;
+        let mut move_up = None;
+        let mut remove = None;
+        let len = passes.len();
+        for (i, pass) in passes.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.checkbox(
+                    &mut pass.enabled,
+                    format!("{}. {}", i + 1, pass.shader.as_ref()),
+                )
+                .on_hover_text(Self::shader_hover_text(pass.shader));
+                if ui
+                    .add_enabled(i > 0, Button::new("⏶"))
+                    .on_hover_text("Move up.")
+                    .clicked()
+                {
+                    move_up = Some(i);
+                }
+                if ui
+                    .add_enabled(i + 1 < len, Button::new("⏷"))
+                    .on_hover_text("Move down.")
+                    .clicked()
+                {
+                    move_up = Some(i + 1);
+                }
+                if ui.button("🗑").on_hover_text("Remove pass.").clicked() {
+                    remove = Some(i);
+                }
+            });
+            ui.add_enabled_ui(pass.enabled, |ui| {
+                ui.indent(("shader_pass", i), |ui| {
+                    for (param, value) in pass.shader.params().iter().zip(&mut pass.params) {
+                        ui.add(Slider::new(value, param.range.clone()).text(param.name))
+                            .on_hover_text(format!("Default: {}", param.default));
+                    }
+                });
+            });
+        }
+        if let Some(i) = move_up {
+            passes.swap(i - 1, i);
+        }
+        if let Some(i) = remove {
+            let _ = passes.remove(i);
+        }
+
+        // Passes are applied in order, each to the output of the last
+        ComboBox::from_id_salt("add_shader_pass")
+            .selected_text("➕ Add Pass")
+            .show_ui(ui, |ui| {
+                for shader in Shader::as_slice() {
+                    if *shader != Shader::None
+                        && ui
+                            .selectable_label(false, shader.as_ref())
+                            .on_hover_text(Self::shader_hover_text(*shader))
+                            .clicked()
+                    {
+                        passes.push(ShaderPass::new(*shader));
+                    }
+                }
+            });
+
+        if new_shaders != *shaders {
+            tx.event(ConfigEvent::Shader(new_shaders));
+        }
+    }
+
+    const fn shader_hover_text(shader: Shader) -> &'static str {
+        match shader {
+            Shader::None => "No shader.",
+            Shader::CrtEasymode => "Emulate traditional CRT aperture grill masking.",
+            Shader::Sharpen => "Sharpen edges, e.g. after blurrier passes.",
         }
     }
 
@@ -331,14 +628,30 @@ impl Preferences {
         let previous_four_player = four_player;
         ui.radio_value(&mut four_player, FourPlayer::Disabled, "Disabled");
         ui.radio_value(&mut four_player, FourPlayer::FourScore, "Four Score")
-            .on_hover_text("Enable NES Four Score for games that support 4 players.");
-        ui.radio_value(&mut four_player, FourPlayer::Satellite, "Satellite")
-            .on_hover_text("Enable NES Satellite for games that support 4 players.");
+            .on_hover_text("Enable NES Four Score or Satellite for games that support 4 players.");
+        ui.radio_value(&mut four_player, FourPlayer::Famicom, "Famicom")
+            .on_hover_text(
+                "Enable a Famicom expansion port adapter for games that support 4 players.",
+            );
+        ui.radio_value(&mut four_player, FourPlayer::Auto, "Auto")
+            .on_hover_text("Use the adapter specified by the ROM header, if any.");
         if four_player != previous_four_player {
             tx.event(ConfigEvent::FourPlayer(four_player));
         }
     }
 
+    pub fn mouse_port_radio(tx: &NesEventProxy, ui: &mut Ui, mut port: MousePort) {
+        let previous_port = port;
+        ui.radio_value(&mut port, MousePort::Disabled, "Disabled");
+        ui.radio_value(&mut port, MousePort::One, "Port 1")
+            .on_hover_text("Plug a SNES mouse into controller port 1 in place of player 1.");
+        ui.radio_value(&mut port, MousePort::Two, "Port 2")
+            .on_hover_text("Plug a SNES mouse into controller port 2 in place of player 2.");
+        if port != previous_port {
+            tx.event(ConfigEvent::MousePort(port));
+        }
+    }
+
     pub fn nes_region_radio(tx: &NesEventProxy, ui: &mut Ui, mut region: NesRegion) {
         let previous_region = region;
         ui.radio_value(&mut region, NesRegion::Auto, "Auto")
@@ -349,6 +662,8 @@ impl Preferences {
             .on_hover_text("Emulate PAL timing and aspect-ratio.");
         ui.radio_value(&mut region, NesRegion::Dendy, "Dendy")
             .on_hover_text("Emulate Dendy timing and aspect-ratio.");
+        ui.radio_value(&mut region, NesRegion::PalM, "PAL-M")
+            .on_hover_text("Emulate Brazilian PAL-M famiclones, which use NTSC timing at 60Hz.");
         if region != previous_region {
             tx.event(ConfigEvent::Region(region));
         }
@@ -367,6 +682,19 @@ impl Preferences {
         }
     }
 
+    pub fn ram_retention_radio(tx: &NesEventProxy, ui: &mut Ui, mut ram_retention: RamRetention) {
+        let previous_ram_retention = ram_retention;
+        ui.radio_value(&mut ram_retention, RamRetention::Hardware, "Hardware")
+            .on_hover_text("Keep RAM on reset and clear it on power cycle, like real hardware.");
+        ui.radio_value(&mut ram_retention, RamRetention::KeepAll, "Keep All")
+            .on_hover_text("Keep RAM on both reset and power cycle.");
+        ui.radio_value(&mut ram_retention, RamRetention::ClearAll, "Clear All")
+            .on_hover_text("Clear RAM on both reset and power cycle.");
+        if ram_retention != previous_ram_retention {
+            tx.event(ConfigEvent::RamRetention(ram_retention));
+        }
+    }
+
     pub fn menubar_checkbox(
         tx: &NesEventProxy,
         ui: &mut Ui,
@@ -402,6 +730,33 @@ impl Preferences {
         }
     }
 
+    pub fn audio_scope_checkbox(
+        tx: &NesEventProxy,
+        ui: &mut Ui,
+        mut show_audio_scope: bool,
+        shortcut: impl Into<Option<String>>,
+    ) {
+        let shortcut = shortcut.into();
+        let icon = shortcut.is_some().then_some("〰 ").unwrap_or_default();
+        let checkbox = Checkbox::new(&mut show_audio_scope, format!("{icon}Show Audio Scope"))
+            .shortcut_text(shortcut.unwrap_or_default());
+        let res = ui
+            .add(checkbox)
+            .on_hover_text("Draw an oscilloscope of the audio output over the game.");
+        if res.clicked() {
+            tx.event(ConfigEvent::ShowAudioScope(show_audio_scope));
+        }
+    }
+
+    pub fn tv_static_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut tv_static: bool) {
+        let res = ui.checkbox(&mut tv_static, "TV Static").on_hover_text(
+            "Show CRT static while no game is loaded and briefly when one powers on. Cosmetic only.",
+        );
+        if res.clicked() {
+            tx.event(ConfigEvent::TvStatic(tv_static));
+        }
+    }
+
     pub fn screen_reader_checkbox(ui: &mut Ui, shortcut: impl Into<Option<String>>) {
         let shortcut = shortcut.into();
         // icon: document with text
@@ -516,7 +871,7 @@ impl State {
             ScrollArea::both().show(ui, |ui| {
                 match self.tab {
                     Tab::Emulation => self.emulation_tab(ui, cfg),
-                    Tab::Audio => Self::audio_tab(&self.tx, ui, cfg),
+                    Tab::Audio => Self::audio_tab(&self.tx, ui, cfg, &self.audio_channels),
                     Tab::Video => Self::video_tab(&self.tx, ui, cfg),
                     Tab::Input => Self::input_tab(&self.tx, ui, cfg),
                 }
@@ -556,9 +911,11 @@ impl State {
             mut auto_save,
             auto_save_interval,
             mut auto_load,
+            mut allow_multiple_instances,
             rewind,
             mut rewind_interval,
             mut rewind_seconds,
+            mut rewind_memory_budget,
             run_ahead,
             save_slot,
             speed,
@@ -568,7 +925,9 @@ impl State {
             cycle_accurate,
             mut emulate_ppu_warmup,
             four_player,
+            mouse,
             ram_state,
+            ram_retention,
             region,
             ..
         } = cfg.deck;
@@ -618,6 +977,18 @@ impl State {
                                 tx.event(ConfigEvent::RewindInterval(rewind_interval));
                             }
                         });
+
+                        ui.horizontal(|ui| {
+                            let drag = DragValue::new(&mut rewind_memory_budget)
+                                .range(16..=4096)
+                                .prefix("up to ")
+                                .suffix(" MB");
+                            let res = ui.add(drag)
+                                .on_hover_text("The maximum memory to use for rewinding, which shortens the rewind duration if needed.");
+                            if res.changed() {
+                                tx.event(ConfigEvent::RewindMemoryBudget(rewind_memory_budget));
+                            }
+                        });
                     });
                 });
             });
@@ -666,6 +1037,32 @@ impl State {
             if res.clicked() {
                 tx.event(EmulationEvent::EmulatePpuWarmup(emulate_ppu_warmup));
             }
+            if feature!(Filesystem) {
+                let res = ui.checkbox(&mut allow_multiple_instances, "Allow Multiple Instances")
+                    .on_hover_text(concat!(
+                        "Allow running more than one TetaNES at a time. ",
+                        "When disabled, ROMs opened while TetaNES is running are loaded in the existing window."
+                    ));
+                if res.changed() {
+                    tx.event(ConfigEvent::AllowMultipleInstances(allow_multiple_instances));
+                }
+            }
+            ui.end_row();
+
+            Self::attract_mode_settings(tx, ui, &cfg.emulation.attract_mode);
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let mut encrypt_saves = cfg.emulation.encrypt_saves;
+                let res = ui.checkbox(&mut encrypt_saves, "Encrypt Save States and SRAM")
+                    .on_hover_text(concat!(
+                        "Encrypt save states, SRAM and replays with a password entered each session. ",
+                        "Encrypted saves can't be loaded without the password or by older versions of TetaNES. ",
+                        "Existing saves stay unencrypted until they're saved again."
+                    ));
+                if res.changed() {
+                    tx.event(ConfigEvent::EncryptSaves(encrypt_saves));
+                }
+            }
             ui.end_row();
         });
 
@@ -726,6 +1123,14 @@ impl State {
             ui.vertical(|ui| Preferences::four_player_radio(tx, ui, four_player));
             ui.end_row();
 
+            ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+                ui.strong("Mouse:")
+                    .on_hover_cursor(CursorIcon::Help)
+                    .on_hover_text("Some homebrew titles support a SNES mouse through an adapter.");
+            });
+            ui.vertical(|ui| Preferences::mouse_port_radio(tx, ui, mouse));
+            ui.end_row();
+
             ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
                 ui.strong("NES Region:")
                     .on_hover_cursor(CursorIcon::Help)
@@ -740,6 +1145,65 @@ impl State {
             });
             ui.vertical(|ui| Preferences::ram_state_radio(tx, ui, ram_state));
             ui.end_row();
+
+            ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+                ui.strong("RAM Retention:")
+                    .on_hover_cursor(CursorIcon::Help)
+                    .on_hover_text(
+                        "Whether RAM is cleared to the RAM State on reset and power cycle.",
+                    );
+            });
+            ui.vertical(|ui| Preferences::ram_retention_radio(tx, ui, ram_retention));
+            ui.end_row();
+        });
+
+        ui.separator();
+
+        let grid = Grid::new("replay_info").num_columns(2).spacing([40.0, 6.0]);
+        grid.show(ui, |ui| {
+            let tx = &self.tx;
+
+            ui.strong("Background Recording:")
+                .on_hover_cursor(CursorIcon::Help)
+                .on_hover_text(
+                    "Always record inputs so the last few minutes of play can be saved as a replay at any time.",
+                );
+            ui.horizontal(|ui| {
+                let mut background_replay = cfg.emulation.background_replay;
+                if ui.checkbox(&mut background_replay, "Enabled").changed() {
+                    tx.event(ConfigEvent::BackgroundReplay(background_replay));
+                }
+                ui.add_enabled_ui(background_replay, |ui| {
+                    let mut minutes = cfg.emulation.background_replay_minutes;
+                    let suffix = if minutes == 1 { " minute" } else { " minutes" };
+                    let drag = DragValue::new(&mut minutes).range(1..=60).suffix(suffix);
+                    let res = ui
+                        .add(drag)
+                        .on_hover_text("The number of minutes of recent play to keep.");
+                    if res.changed() {
+                        tx.event(ConfigEvent::BackgroundReplayMinutes(minutes));
+                    }
+                });
+            });
+            ui.end_row();
+
+            ui.strong("Replay Author:")
+                .on_hover_cursor(CursorIcon::Help)
+                .on_hover_text("Author name saved with new replay recordings.");
+            let mut replay_author = cfg.emulation.replay_author.clone();
+            if ui.text_edit_singleline(&mut replay_author).changed() {
+                tx.event(ConfigEvent::ReplayAuthor(replay_author));
+            }
+            ui.end_row();
+
+            ui.strong("Replay Description:")
+                .on_hover_cursor(CursorIcon::Help)
+                .on_hover_text("Description saved with new replay recordings.");
+            let mut replay_description = cfg.emulation.replay_description.clone();
+            if ui.text_edit_multiline(&mut replay_description).changed() {
+                tx.event(ConfigEvent::ReplayDescription(replay_description));
+            }
+            ui.end_row();
         });
 
         let grid = Grid::new("genie_codes").num_columns(2).spacing([40.0, 6.0]);
@@ -749,7 +1213,7 @@ impl State {
         });
     }
 
-    fn audio_tab(tx: &NesEventProxy, ui: &mut Ui, cfg: &Config) {
+    fn audio_tab(tx: &NesEventProxy, ui: &mut Ui, cfg: &Config, audio_channels: &[AudioChannel]) {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
 
@@ -757,6 +1221,10 @@ impl State {
             latency,
             mut buffer_size,
             mut enabled,
+            frame_step_audio,
+            monitor_recording,
+            recording_format,
+            split_channels,
         } = cfg.audio;
         let DeckConfig {
             channels_enabled, ..
@@ -773,36 +1241,16 @@ impl State {
                     .spacing([60.0, 6.0])
                     .num_columns(2)
                     .show(ui, |ui| {
-
-                        let mut pulse1_enabled = channels_enabled[0];
-                        if ui.checkbox(&mut pulse1_enabled, "Enable Pulse1").clicked() {
-                            tx.event(ConfigEvent::ApuChannelEnabled((Channel::Pulse1, pulse1_enabled)));
-                        }
-                        let mut noise_enabled = channels_enabled[3];
-                        if ui.checkbox(&mut noise_enabled, "Enable Noise").clicked() {
-                            tx.event(ConfigEvent::ApuChannelEnabled((Channel::Noise, noise_enabled)));
-                        }
-                        ui.end_row();
-
-                        let mut pulse1_enabled = channels_enabled[1];
-                        if ui.checkbox(&mut pulse1_enabled, "Enable Pulse2").clicked() {
-                            tx.event(ConfigEvent::ApuChannelEnabled((Channel::Pulse2, pulse1_enabled)));
-                        }
-                        let mut dmc_enabled = channels_enabled[4];
-                        if ui.checkbox(&mut dmc_enabled, "Enable DMC").clicked() {
-                            tx.event(ConfigEvent::ApuChannelEnabled((Channel::Dmc, dmc_enabled)));
-                        }
-                        ui.end_row();
-
-                        let mut triangle_enabled = channels_enabled[2];
-                        if ui.checkbox(&mut triangle_enabled, "Enable Triangle").clicked() {
-                            tx.event(ConfigEvent::ApuChannelEnabled((Channel::Triangle, triangle_enabled)));
-                        }
-                        let mut mapper_enabled = channels_enabled[5];
-                        if ui.checkbox(&mut mapper_enabled, "Enable Mapper").clicked() {
-                            tx.event(ConfigEvent::ApuChannelEnabled((Channel::Mapper, mapper_enabled)));
+                        for row in audio_channels.chunks(2) {
+                            for audio_channel in row {
+                                let AudioChannel { channel, name } = *audio_channel;
+                                let mut enabled = channels_enabled[channel as usize];
+                                if ui.checkbox(&mut enabled, format!("Enable {name}")).clicked() {
+                                    tx.event(ConfigEvent::ApuChannelEnabled((channel, enabled)));
+                                }
+                            }
+                            ui.end_row();
                         }
-                        ui.end_row();
                     });
 
                 ui.separator();
@@ -842,6 +1290,16 @@ impl State {
                     });
                         ui.end_row();
                     });
+
+                ui.separator();
+
+                Preferences::frame_step_audio_checkbox(tx, ui, frame_step_audio);
+                Preferences::monitor_recording_checkbox(tx, ui, monitor_recording);
+                Preferences::split_channels_checkbox(tx, ui, split_channels);
+                ui.horizontal(|ui| {
+                    ui.label("Recording Format:");
+                    Preferences::recording_format_radio(tx, ui, recording_format);
+                });
             });
         });
     }
@@ -854,13 +1312,24 @@ impl State {
             always_on_top,
             fullscreen,
             hide_overscan,
+            pixel_perfect,
+            match_frame_rate,
             scale,
-            shader,
+            snap_window_size,
             show_menubar,
             show_messages,
+            show_audio_scope,
+            show_overscan_outline,
+            translucent,
+            tv_static,
+            window_opacity,
             ..
         } = cfg.renderer;
-        let DeckConfig { filter, .. } = cfg.deck;
+        let DeckConfig {
+            filter,
+            color_filter,
+            ..
+        } = cfg.deck;
 
         Grid::new("video_checkboxes")
             .spacing([80.0, 6.0])
@@ -877,6 +1346,21 @@ impl State {
                 Preferences::overscan_checkbox(tx, ui, hide_overscan, None);
                 Preferences::always_on_top_checkbox(tx, ui, always_on_top, None);
                 ui.end_row();
+
+                Preferences::pixel_perfect_checkbox(tx, ui, pixel_perfect);
+                Preferences::match_frame_rate_checkbox(tx, ui, match_frame_rate);
+                ui.end_row();
+
+                Preferences::snap_window_size_checkbox(tx, ui, snap_window_size);
+                Preferences::translucent_checkbox(tx, ui, translucent, None);
+                ui.end_row();
+
+                Preferences::audio_scope_checkbox(tx, ui, show_audio_scope, None);
+                Preferences::overscan_outline_checkbox(tx, ui, show_overscan_outline, None);
+                ui.end_row();
+
+                Preferences::tv_static_checkbox(tx, ui, tv_static);
+                ui.end_row();
             });
 
         ui.separator();
@@ -903,9 +1387,27 @@ impl State {
                 ui.end_row();
 
                 ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
-                    ui.strong("Shader:");
+                    ui.strong("Window Opacity:");
                 });
-                ui.vertical(|ui| Preferences::shader_radio(tx, ui, shader));
+                ui.add_enabled_ui(translucent, |ui| {
+                    Preferences::window_opacity_slider(tx, ui, window_opacity);
+                });
+                ui.end_row();
+
+                ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+                    ui.strong("Color Blindness:")
+                        .on_hover_cursor(CursorIcon::Help)
+                        .on_hover_text(
+                            "Adjust colors to help colorblind players distinguish sprites.",
+                        );
+                });
+                ui.vertical(|ui| Preferences::color_filter_settings(tx, ui, color_filter));
+                ui.end_row();
+
+                ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+                    ui.strong("Shaders:");
+                });
+                ui.vertical(|ui| Preferences::shader_pipeline(tx, ui, &cfg.renderer.shader));
             });
     }
 
@@ -916,8 +1418,10 @@ impl State {
         let DeckConfig {
             mut concurrent_dpad,
             zapper,
+            vs_dip_switches,
             ..
         } = cfg.deck;
+        let mut gamepad_navigation = cfg.input.gamepad_navigation;
 
         Grid::new("input_checkboxes")
             .num_columns(2)
@@ -930,9 +1434,38 @@ impl State {
                 if res.clicked() {
                     tx.event(ConfigEvent::ConcurrentDpad(concurrent_dpad));
                 }
+                ui.end_row();
+
+                let res = ui
+                    .checkbox(&mut gamepad_navigation, "Enable Gamepad Navigation")
+                    .on_hover_text(
+                        "Navigate menus with a gamepad while paused or no ROM is loaded. Press the Guide button to open Quick Settings.",
+                    );
+                if res.clicked() {
+                    tx.event(ConfigEvent::GamepadNavigation(gamepad_navigation));
+                }
+                ui.end_row();
+
+                Self::vs_dip_switches(tx, ui, vs_dip_switches);
             });
     }
 
+    fn vs_dip_switches(tx: &NesEventProxy, ui: &mut Ui, dip_switches: u8) {
+        ui.strong("Vs. System DIP Switches:")
+            .on_hover_cursor(CursorIcon::Help)
+            .on_hover_text(
+                "Game settings like difficulty, lives and coins per credit for Vs. System arcade games. Which switches do what varies by game.",
+            );
+        ui.horizontal(|ui| {
+            for switch in 0..8 {
+                let mut on = dip_switches & (1 << switch) != 0;
+                if ui.checkbox(&mut on, format!("{}", switch + 1)).clicked() {
+                    tx.event(ConfigEvent::VsDipSwitches(dip_switches ^ (1 << switch)));
+                }
+            }
+        });
+    }
+
     pub fn genie_codes_entry(&mut self, ui: &mut Ui, cfg: &Config) {
         let tx = &self.tx;
         ui.vertical(|ui| {
@@ -942,7 +1475,7 @@ impl State {
             let genie_label = ui.strong("Add Genie Code(s):")
                 .on_hover_cursor(CursorIcon::Help)
                 .on_hover_text(
-                    "A Game Genie Code is a 6 or 8 letter string that temporarily modifies game memory during operation. e.g. `AATOZE` will start Super Mario Bros. with 9 lives.\n\nYou can enter one code per line."
+                    "A Game Genie Code is a 6 or 8 letter string that temporarily modifies game memory during operation. e.g. `AATOZE` will start Super Mario Bros. with 9 lives.\n\nRaw address codes such as `0075:09`, or `0075?05:09` to only replace a value of `05`, override reads from any address.\n\nYou can enter one code per line."
                 );
 
             let text_edit = TextEdit::multiline(&mut self.genie_entry.code)
@@ -974,9 +1507,8 @@ impl State {
                     if code.is_empty() {
                         continue;
                     }
-                    match GenieCode::parse(code) {
-                        Ok(hex) => {
-                            let code = GenieCode::from_raw(code.to_string(), hex);
+                    match GenieCode::new(code.to_string()) {
+                        Ok(code) => {
                             if !cfg.deck.genie_codes.contains(&code) {
                                 tx.event(ConfigEvent::GenieCodeAdded(code));
                             }
@@ -995,6 +1527,97 @@ impl State {
         });
     }
 
+    fn attract_mode_settings(tx: &NesEventProxy, ui: &mut Ui, attract_mode: &AttractModeConfig) {
+        let mut attract_mode = attract_mode.clone();
+        ui.vertical(|ui| {
+            let mut changed = ui
+                .checkbox(&mut attract_mode.enabled, "Attract Mode")
+                .on_hover_text(concat!(
+                    "Play a demo as a screensaver after being idle without a game loaded. ",
+                    "Any input stops the demo."
+                ))
+                .changed();
+
+            ui.add_enabled_ui(attract_mode.enabled, |ui| {
+                ui.indent("attract_mode_settings", |ui| {
+                    let suffix = if attract_mode.idle_minutes == 1 {
+                        " minute"
+                    } else {
+                        " minutes"
+                    };
+                    let drag = DragValue::new(&mut attract_mode.idle_minutes)
+                        .range(1..=120)
+                        .prefix("after ")
+                        .suffix(suffix);
+                    changed |= ui
+                        .add(drag)
+                        .on_hover_text("How long to wait without input before starting the demo.")
+                        .changed();
+
+                    if feature!(Filesystem) {
+                        changed |= Self::attract_mode_path(
+                            ui,
+                            ("ROM:", "Bundled homebrew"),
+                            &mut attract_mode.rom,
+                            ("Load ROM", "NES ROMs", "nes"),
+                        );
+                        ui.add_enabled_ui(attract_mode.rom.is_some(), |ui| {
+                            changed |= Self::attract_mode_path(
+                                ui,
+                                ("Replay:", "None"),
+                                &mut attract_mode.replay,
+                                ("Load Replay", "Replay Recording", "replay"),
+                            );
+                        });
+                    }
+                });
+            });
+
+            if changed {
+                tx.event(ConfigEvent::AttractMode(attract_mode));
+            }
+        });
+    }
+
+    /// Shows a path to play in attract mode with buttons to change or clear it, returning whether
+    /// it changed.
+    fn attract_mode_path(
+        ui: &mut Ui,
+        (label, unset): (&str, &str),
+        path: &mut Option<PathBuf>,
+        (title, name, extension): (&str, &str, &str),
+    ) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label(label);
+            let filename = path
+                .as_deref()
+                .and_then(Path::file_name)
+                .map_or_else(|| unset.into(), |name| name.to_string_lossy());
+            ui.label(filename);
+            if ui.button("Browse...").clicked() {
+                match open_file_dialog(
+                    title,
+                    name,
+                    &[extension],
+                    path.as_deref().and_then(Path::parent),
+                ) {
+                    Ok(Some(selected)) => {
+                        *path = Some(selected);
+                        changed = true;
+                    }
+                    Ok(None) => (),
+                    Err(err) => warn!("failed to open file dialog: {err:?}"),
+                }
+            }
+            if path.is_some() && ui.button("Clear").clicked() {
+                *path = None;
+                changed = true;
+            }
+        });
+        changed
+    }
+
     fn restore_defaults(tx: &NesEventProxy, ctx: &Context) {
         ctx.memory_mut(|mem| *mem = Default::default());
 
@@ -1009,37 +1632,64 @@ impl State {
 
         let events = [
             ConfigEvent::ActionBindings(input.action_bindings),
+            ConfigEvent::AllowMultipleInstances(emulation.allow_multiple_instances),
             ConfigEvent::AlwaysOnTop(renderer.always_on_top),
             ConfigEvent::ApuChannelsEnabled(deck.channels_enabled),
+            ConfigEvent::AttractMode(emulation.attract_mode),
             ConfigEvent::AudioBuffer(audio.buffer_size),
             ConfigEvent::AudioEnabled(audio.enabled),
+            ConfigEvent::AudioFrameStep(audio.frame_step_audio),
             ConfigEvent::AudioLatency(audio.latency),
+            ConfigEvent::AudioMonitorRecording(audio.monitor_recording),
+            ConfigEvent::AudioRecordingFormat(audio.recording_format),
+            ConfigEvent::AudioSplitChannels(audio.split_channels),
             ConfigEvent::AutoLoad(emulation.auto_load),
             ConfigEvent::AutoSave(emulation.auto_save),
             ConfigEvent::AutoSaveInterval(emulation.auto_save_interval),
+            ConfigEvent::BackgroundReplay(emulation.background_replay),
+            ConfigEvent::BackgroundReplayMinutes(emulation.background_replay_minutes),
+            ConfigEvent::ColorFilter(deck.color_filter),
             ConfigEvent::ConcurrentDpad(deck.concurrent_dpad),
             ConfigEvent::CycleAccurate(deck.cycle_accurate),
             ConfigEvent::DarkTheme(renderer.dark_theme),
             ConfigEvent::EmbedViewports(renderer.embed_viewports),
+            ConfigEvent::EncryptSaves(emulation.encrypt_saves),
             ConfigEvent::FourPlayer(deck.four_player),
             ConfigEvent::Fullscreen(renderer.fullscreen),
             ConfigEvent::GamepadAssignments(input.gamepad_assignments),
+            ConfigEvent::GamepadNavigation(input.gamepad_navigation),
             ConfigEvent::GenieCodeClear,
             ConfigEvent::HideOverscan(renderer.hide_overscan),
             ConfigEvent::MapperRevisions(deck.mapper_revisions),
+            ConfigEvent::MatchFrameRate(renderer.match_frame_rate),
+            ConfigEvent::MousePort(deck.mouse),
+            ConfigEvent::NetplayInputDelay(emulation.netplay_input_delay),
+            ConfigEvent::PixelPerfect(renderer.pixel_perfect),
+            ConfigEvent::RamRetention(deck.ram_retention),
             ConfigEvent::RamState(deck.ram_state),
             // Clearing recent roms is handled in a separate button
+            ConfigEvent::ReplayAuthor(emulation.replay_author),
+            ConfigEvent::ReplayDescription(emulation.replay_description),
             ConfigEvent::Region(deck.region),
             ConfigEvent::RewindEnabled(emulation.rewind),
             ConfigEvent::RewindInterval(emulation.rewind_interval),
+            ConfigEvent::RewindMemoryBudget(emulation.rewind_memory_budget),
             ConfigEvent::RewindSeconds(emulation.rewind_seconds),
             ConfigEvent::RunAhead(emulation.run_ahead),
             ConfigEvent::SaveSlot(emulation.save_slot),
             ConfigEvent::Shader(renderer.shader),
+            ConfigEvent::ShowAudioScope(renderer.show_audio_scope),
             ConfigEvent::ShowMenubar(renderer.show_menubar),
             ConfigEvent::ShowMessages(renderer.show_messages),
+            ConfigEvent::ShowOverscanOutline(renderer.show_overscan_outline),
+            ConfigEvent::SnapWindowSize(renderer.snap_window_size),
             ConfigEvent::Speed(emulation.speed),
+            ConfigEvent::Translucent(renderer.translucent),
+            ConfigEvent::TvStatic(renderer.tv_static),
             ConfigEvent::VideoFilter(deck.filter),
+            ConfigEvent::VsDipSwitches(deck.vs_dip_switches),
+            ConfigEvent::WatchFolder(emulation.watch_folder),
+            ConfigEvent::WindowOpacity(renderer.window_opacity),
             ConfigEvent::ZapperConnected(deck.zapper),
         ];
 
@@ -0,0 +1,95 @@
+use crate::nes::{
+    config::Config,
+    event::{ConfigEvent, NesEventProxy},
+};
+use egui::{Context, Window};
+use tetanes_core::{common::NesRegion, control_deck::LoadedRom};
+
+/// A loaded ROM whose detected region differs from the configured NES region.
+#[derive(Debug, Clone)]
+struct Mismatch {
+    name: String,
+    rom_region: NesRegion,
+    region: NesRegion,
+}
+
+/// Prompt offering to switch regions when a loaded ROM was made for a different region.
+#[derive(Debug)]
+#[must_use]
+pub struct RegionMismatch {
+    tx: NesEventProxy,
+    mismatch: Option<Mismatch>,
+}
+
+impl RegionMismatch {
+    pub const fn new(tx: NesEventProxy) -> Self {
+        Self { tx, mismatch: None }
+    }
+
+    /// Check a newly loaded ROM against the configured region, unless a region was already chosen
+    /// for it.
+    pub fn check(&mut self, rom: &LoadedRom, cfg: &Config) {
+        let region = cfg.deck.region;
+        // PAL-M famiclones play NTSC games as-is
+        let compatible = rom.region == region || (rom.region.is_ntsc() && region.is_ntsc());
+        self.mismatch = (!region.is_auto()
+            && !compatible
+            && !cfg.emulation.rom_regions.contains_key(&rom.name))
+        .then(|| Mismatch {
+            name: rom.name.clone(),
+            rom_region: rom.region,
+            region,
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.mismatch = None;
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let Some(mismatch) = &self.mismatch else {
+            return;
+        };
+
+        let mut open = true;
+        let mut choice = None;
+        Window::new("⚠ Region Mismatch")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| {
+                    ui.label(format!(
+                        "{} appears to be a {} game, but the NES region is set to {}. \
+                        It may run too fast or too slow, with the wrong audio pitch.",
+                        mismatch.name, mismatch.rom_region, mismatch.region
+                    ));
+
+                    ui.add_space(8.0);
+                    ui.horizontal(|ui| {
+                        let switch = format!("Switch to {} for this game", mismatch.rom_region);
+                        if ui.button(switch).clicked() {
+                            choice = Some(mismatch.rom_region);
+                        }
+                        if ui.button(format!("Keep {}", mismatch.region)).clicked() {
+                            choice = Some(mismatch.region);
+                        }
+                    });
+                    ui.small("Your choice is remembered for this game.");
+                });
+            });
+
+        if let Some(region) = choice {
+            self.tx.event(ConfigEvent::RomRegion((
+                mismatch.name.clone(),
+                Some(region),
+            )));
+        }
+        if !open || choice.is_some() {
+            self.mismatch = None;
+        }
+    }
+}
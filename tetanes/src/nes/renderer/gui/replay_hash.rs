@@ -0,0 +1,34 @@
+use crate::nes::emulation::replay::HashStatus;
+use egui::{Align2, Color32, FontId, Rect, Rounding, Ui, Vec2};
+
+/// Draws the state hash of the current frame in the top-left of the NES frame while a replay
+/// plays back, along with a warning once it has desynced from the recording.
+pub fn draw_hash_status(ui: &Ui, frame: Rect, status: &HashStatus) {
+    #[cfg(feature = "profiling")]
+    puffin::profile_function!();
+
+    let painter = ui.painter_at(frame);
+    let padding = Vec2::splat(4.0);
+    let font = FontId::monospace((frame.height() / 40.0).max(10.0));
+    let (text, color) = match status.desync {
+        Some(desync) => (
+            format!(
+                "Frame {} #{:08X}\n⚠ Desynced at frame {desync}",
+                status.frame, status.hash
+            ),
+            ui.visuals().error_fg_color,
+        ),
+        None => (
+            format!("Frame {} #{:08X}", status.frame, status.hash),
+            Color32::WHITE,
+        ),
+    };
+    let galley = painter.layout_no_wrap(text, font, color);
+    let rect = Align2::LEFT_TOP.anchor_size(frame.left_top() + padding * 2.0, galley.size());
+    painter.rect_filled(
+        rect.expand2(padding),
+        Rounding::same(4.0),
+        Color32::from_black_alpha(180),
+    );
+    painter.galley(rect.min, galley, color);
+}
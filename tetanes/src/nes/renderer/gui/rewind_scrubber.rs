@@ -0,0 +1,175 @@
+use crate::nes::{
+    emulation::rewind::{RewindKeyframe, RewindTimeline},
+    event::{EmulationEvent, NesEventProxy},
+};
+use egui::{
+    load::SizedTexture, Button, ColorImage, Context, Image, ScrollArea, Slider, TextureHandle,
+    TextureOptions, Ui, Vec2, Window,
+};
+
+/// Window with a timeline of rewind keyframes and a scrubber bar to preview rewind states before
+/// rewinding to one.
+#[must_use]
+pub struct RewindScrubber {
+    tx: NesEventProxy,
+    open: bool,
+    timeline: RewindTimeline,
+    /// Thumbnail textures, indexed by keyframe.
+    textures: Vec<Option<TextureHandle>>,
+    /// Number of rewind states back from the latest being previewed.
+    offset: u32,
+}
+
+impl std::fmt::Debug for RewindScrubber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RewindScrubber")
+            .field("open", &self.open)
+            .field("timeline", &self.timeline)
+            .field("offset", &self.offset)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RewindScrubber {
+    const FPS: f32 = 60.0;
+
+    pub fn new(tx: NesEventProxy) -> Self {
+        Self {
+            tx,
+            open: false,
+            timeline: RewindTimeline::default(),
+            textures: Vec::new(),
+            offset: 0,
+        }
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        if self.open != open {
+            self.tx.event(EmulationEvent::ShowRewindTimeline(open));
+            self.offset = 0;
+        }
+        self.open = open;
+    }
+
+    pub fn toggle_open(&mut self) {
+        self.set_open(!self.open);
+    }
+
+    pub fn set_timeline(&mut self, timeline: RewindTimeline) {
+        self.textures = vec![None; timeline.keyframes.len()];
+        self.timeline = timeline;
+        self.offset = 0;
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool, rom_loaded: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let mut open = self.open;
+        Window::new("⟲ Rewind Timeline")
+            .open(&mut open)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled && rom_loaded, |ui| self.ui(ui));
+            });
+        if self.open {
+            self.set_open(open);
+        }
+    }
+
+    fn preview(&mut self, offset: u32) {
+        if self.offset != offset {
+            self.offset = offset;
+            self.tx.event(EmulationEvent::PreviewRewind(offset));
+        }
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        if self.timeline.len == 0 {
+            ui.label("No rewind history yet.");
+            return;
+        }
+
+        let thumbnail_size = Vec2::new(
+            RewindKeyframe::THUMBNAIL_WIDTH as f32,
+            RewindKeyframe::THUMBNAIL_HEIGHT as f32,
+        );
+        let mut clicked = None;
+        ScrollArea::horizontal()
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    for (keyframe, texture) in
+                        self.timeline.keyframes.iter().zip(&mut self.textures)
+                    {
+                        let texture = texture.get_or_insert_with(|| {
+                            ui.ctx().load_texture(
+                                format!("rewind_keyframe_{}", keyframe.frame),
+                                ColorImage::from_rgba_unmultiplied(
+                                    [
+                                        RewindKeyframe::THUMBNAIL_WIDTH as usize,
+                                        RewindKeyframe::THUMBNAIL_HEIGHT as usize,
+                                    ],
+                                    &keyframe.thumbnail,
+                                ),
+                                TextureOptions::NEAREST,
+                            )
+                        });
+                        let res = ui
+                            .add(
+                                Button::image(
+                                    Image::from_texture(SizedTexture::from_handle(texture))
+                                        .fit_to_exact_size(thumbnail_size),
+                                )
+                                .selected(keyframe.offset == self.offset),
+                            )
+                            .on_hover_text(format!("Frame {}", keyframe.frame));
+                        if res.clicked() {
+                            clicked = Some(keyframe.offset);
+                        }
+                    }
+                });
+            });
+        if let Some(offset) = clicked {
+            self.preview(offset);
+        }
+
+        // The slider runs from the oldest state on the left to the present on the right
+        let len = self.timeline.len;
+        let mut position = len - self.offset.min(len);
+        ui.spacing_mut().slider_width = ui.available_width();
+        let res = ui.add(
+            Slider::new(&mut position, 0..=len)
+                .show_value(false)
+                .trailing_fill(true),
+        );
+        if res.changed() {
+            self.preview(len - position);
+        }
+
+        let seconds_ago = (self.offset * self.timeline.interval) as f32 / Self::FPS;
+        ui.label(if self.offset == 0 {
+            "Present".to_string()
+        } else {
+            format!("{seconds_ago:.1}s ago")
+        });
+
+        ui.add_space(8.0);
+        ui.horizontal(|ui| {
+            let res = ui
+                .add_enabled(self.offset > 0, Button::new("⟲ Rewind Here"))
+                .on_hover_text("Rewind to the previewed state, discarding the states after it.");
+            if res.clicked() {
+                self.tx.event(EmulationEvent::CommitRewind(self.offset));
+                self.set_open(false);
+            }
+            if ui
+                .button("Cancel")
+                .on_hover_text("Return to the present.")
+                .clicked()
+            {
+                self.set_open(false);
+            }
+        });
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{
+    nes::{
+        audit::{self, Dat, Report, Status},
+        config::Config,
+    },
+    platform::{open_dir_dialog, open_file_dialog},
+};
+use egui::{Context, Grid, RichText, ScrollArea, TextEdit, Ui, Window};
+use std::path::PathBuf;
+use tetanes_core::fs;
+use tracing::error;
+
+/// Window for checking a ROM folder against a No-Intro DAT file.
+#[derive(Default, Debug)]
+#[must_use]
+pub struct RomAudit {
+    open: bool,
+    dat_path: String,
+    dir: String,
+    report: Option<Result<Report, String>>,
+}
+
+impl RomAudit {
+    pub fn open(&mut self, cfg: &Config) {
+        self.open = true;
+        if self.dir.is_empty() {
+            if let Some(dir) = &cfg.renderer.roms_path {
+                self.dir = dir.to_string_lossy().into_owned();
+            }
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let mut open = self.open;
+        Window::new("🔍 Audit ROM Set")
+            .open(&mut open)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| {
+                    Grid::new("rom_audit_paths")
+                        .num_columns(3)
+                        .spacing([8.0, 4.0])
+                        .show(ui, |ui| {
+                            ui.label("DAT File:");
+                            ui.add(TextEdit::singleline(&mut self.dat_path).desired_width(300.0));
+                            if ui.button("Browse...").clicked() {
+                                let dir = PathBuf::from(&self.dat_path);
+                                match open_file_dialog(
+                                    "Load No-Intro DAT File",
+                                    "DAT Files",
+                                    &["dat", "xml"],
+                                    dir.parent(),
+                                ) {
+                                    Ok(Some(path)) => {
+                                        self.dat_path = path.to_string_lossy().into_owned();
+                                    }
+                                    Ok(None) => (),
+                                    Err(err) => error!("failed to open file dialog: {err:?}"),
+                                }
+                            }
+                            ui.end_row();
+
+                            ui.label("ROM Folder:");
+                            ui.add(TextEdit::singleline(&mut self.dir).desired_width(300.0));
+                            if ui.button("Browse...").clicked() {
+                                let dir = Some(&self.dir).filter(|dir| !dir.is_empty());
+                                match open_dir_dialog("Select ROM Folder", dir) {
+                                    Ok(Some(dir)) => {
+                                        self.dir = dir.to_string_lossy().into_owned();
+                                    }
+                                    Ok(None) => (),
+                                    Err(err) => error!("failed to open directory dialog: {err:?}"),
+                                }
+                            }
+                            ui.end_row();
+                        });
+
+                    ui.add_space(8.0);
+                    ui.add_enabled_ui(!self.dat_path.is_empty() && !self.dir.is_empty(), |ui| {
+                        let res = ui.button("Audit").on_hover_text(
+                            "Check each .nes file in the folder against the good dumps in the DAT file.",
+                        );
+                        if res.clicked() {
+                            let dir = PathBuf::from(&self.dir);
+                            self.report = Some(
+                                Dat::load(&self.dat_path)
+                                    .and_then(|dat| audit::audit(&dat, &dir))
+                                    .map_err(|err| format!("{err:#}")),
+                            );
+                        }
+                    });
+
+                    match &mut self.report {
+                        Some(Ok(report)) => {
+                            ui.separator();
+                            Self::report(ui, report);
+                        }
+                        Some(Err(err)) => {
+                            ui.separator();
+                            ui.label(RichText::new(err.as_str()).color(ui.visuals().error_fg_color));
+                        }
+                        None => (),
+                    }
+                });
+            });
+        self.open = open;
+    }
+
+    fn report(ui: &mut Ui, report: &mut Report) {
+        if report.entries.is_empty() {
+            ui.label("No .nes files found.");
+            return;
+        }
+        let summary = report.summary();
+        ui.label(format!(
+            "Verified {}, rename {}, overdump {}, bad dump {}, unknown {}. \
+            {} ROM(s) in the DAT file not found.",
+            summary.verified,
+            summary.renames,
+            summary.overdumps,
+            summary.bad_dumps,
+            summary.unknown,
+            report.missing,
+        ));
+
+        if summary.renames > 0 {
+            let res = ui
+                .button(format!(
+                    "Rename {} File(s) to Canonical Names",
+                    summary.renames
+                ))
+                .on_hover_text("Files are skipped if one with the canonical name already exists.");
+            if res.clicked() {
+                report.rename_all();
+            }
+        }
+
+        ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            Grid::new("rom_audit_report_grid")
+                .num_columns(3)
+                .spacing([20.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    for entry in &report.entries {
+                        let color = match entry.status {
+                            Status::Verified => ui.visuals().text_color(),
+                            Status::Rename(_) | Status::Unknown => ui.visuals().warn_fg_color,
+                            Status::Overdump(_) | Status::BadDump(_) => ui.visuals().error_fg_color,
+                        };
+                        ui.label(RichText::new(entry.status.as_ref()).color(color));
+                        ui.label(fs::filename(&entry.path));
+                        match &entry.status {
+                            Status::Rename(name) => ui.label(format!("→ {name}")),
+                            Status::Overdump(name) => ui.label(format!("Good dump: {name}")),
+                            Status::BadDump(name) => ui.label(format!("Expected: {name}")),
+                            Status::Verified | Status::Unknown => ui.label(""),
+                        };
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
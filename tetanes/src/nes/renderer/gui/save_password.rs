@@ -0,0 +1,79 @@
+use crate::nes::{
+    event::{ConfigEvent, NesEventProxy, UiEvent},
+    renderer::gui::MessageType,
+};
+use egui::{Button, Context, Key, TextEdit, Window};
+use tetanes_core::fs::{self, Encryption};
+
+/// Prompt for the password used to encrypt save states and SRAM, asked once per session while
+/// save encryption is enabled.
+#[derive(Debug)]
+#[must_use]
+pub struct SavePassword {
+    tx: NesEventProxy,
+    password: String,
+}
+
+impl SavePassword {
+    pub const fn new(tx: NesEventProxy) -> Self {
+        Self {
+            tx,
+            password: String::new(),
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        if fs::encryption() != Encryption::Locked {
+            return;
+        }
+
+        Window::new("🔒 Save Password")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| {
+                    ui.label(
+                        "Save states and SRAM are encrypted. Enter the save password to load and \
+                        save them this session.",
+                    );
+                    ui.small(
+                        "Encrypted saves can't be loaded without this password, and saves made \
+                        with a different password won't load.",
+                    );
+
+                    ui.add_space(8.0);
+                    let res = ui.add(
+                        TextEdit::singleline(&mut self.password)
+                            .password(true)
+                            .hint_text("Password"),
+                    );
+                    let submitted = res.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+
+                    ui.horizontal(|ui| {
+                        let unlock =
+                            ui.add_enabled(!self.password.is_empty(), Button::new("Unlock"));
+                        if (unlock.clicked() || submitted) && !self.password.is_empty() {
+                            // Set directly rather than sent as an event so the password is
+                            // never logged
+                            if let Err(err) = fs::set_encryption_password(Some(&self.password)) {
+                                self.tx
+                                    .event(UiEvent::Message((MessageType::Error, err.to_string())));
+                            }
+                            self.password.clear();
+                        }
+                        if ui
+                            .button("Disable Encryption")
+                            .on_hover_text("Save unencrypted from now on.")
+                            .clicked()
+                        {
+                            self.password.clear();
+                            self.tx.event(ConfigEvent::EncryptSaves(false));
+                        }
+                    });
+                });
+            });
+    }
+}
@@ -0,0 +1,214 @@
+use crate::{
+    feature,
+    nes::{
+        emulation::save_states::{SaveStateSlot, SAVE_SLOTS},
+        event::{EmulationEvent, NesEventProxy},
+    },
+    platform::open_dir_dialog,
+};
+use egui::{
+    load::SizedTexture, Button, ColorImage, Context, Grid, Image, RichText, ScrollArea,
+    TextureHandle, TextureOptions, Ui, Vec2, Window,
+};
+use std::path::PathBuf;
+use tetanes_core::{control_deck::SaveStateInfo, time::Duration};
+use tracing::error;
+
+/// Window listing the save states for the loaded ROM with a thumbnail, when they were saved and
+/// play time.
+#[must_use]
+pub struct SaveStates {
+    tx: NesEventProxy,
+    open: bool,
+    slots: Vec<SaveStateSlot>,
+    /// Thumbnail textures, indexed by slot.
+    textures: [Option<TextureHandle>; SAVE_SLOTS as usize],
+    /// Slot to delete once confirmed by clicking delete again.
+    confirm_delete: Option<u8>,
+}
+
+impl std::fmt::Debug for SaveStates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SaveStates")
+            .field("open", &self.open)
+            .field("slots", &self.slots)
+            .field("confirm_delete", &self.confirm_delete)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SaveStates {
+    const THUMBNAIL_LEN: usize =
+        4 * (SaveStateInfo::THUMBNAIL_WIDTH * SaveStateInfo::THUMBNAIL_HEIGHT) as usize;
+
+    pub fn new(tx: NesEventProxy) -> Self {
+        Self {
+            tx,
+            open: false,
+            slots: Vec::new(),
+            textures: Default::default(),
+            confirm_delete: None,
+        }
+    }
+
+    pub const fn open(&self) -> bool {
+        self.open
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        if self.open != open {
+            self.tx.event(EmulationEvent::ShowSaveStates(open));
+        }
+        self.open = open;
+    }
+
+    pub fn set_slots(&mut self, slots: Vec<SaveStateSlot>) {
+        self.slots = slots;
+        self.textures = Default::default();
+        self.confirm_delete = None;
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool, rom_loaded: bool, save_slot: u8) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let mut open = self.open;
+        Window::new("💾 Save States")
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled && rom_loaded, |ui| self.ui(ui, save_slot));
+            });
+        self.set_open(open);
+    }
+
+    fn ui(&mut self, ui: &mut Ui, save_slot: u8) {
+        let thumbnail_size = Vec2::new(
+            SaveStateInfo::THUMBNAIL_WIDTH as f32,
+            SaveStateInfo::THUMBNAIL_HEIGHT as f32,
+        );
+        ScrollArea::vertical()
+            .max_height(520.0)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                Grid::new("save_states")
+                    .num_columns(3)
+                    .spacing([8.0, 8.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for slot in 1..=SAVE_SLOTS {
+                            let save = self.slots.iter().find(|save| save.slot == slot);
+                            let saved = save.is_some();
+                            let texture = &mut self.textures[usize::from(slot - 1)];
+                            let info = save
+                                .and_then(|save| save.info.as_ref())
+                                .filter(|info| info.thumbnail.len() == Self::THUMBNAIL_LEN);
+                            match info {
+                                Some(info) => {
+                                    let texture = texture.get_or_insert_with(|| {
+                                        ui.ctx().load_texture(
+                                            format!("save_state_{slot}"),
+                                            ColorImage::from_rgba_unmultiplied(
+                                                [
+                                                    SaveStateInfo::THUMBNAIL_WIDTH as usize,
+                                                    SaveStateInfo::THUMBNAIL_HEIGHT as usize,
+                                                ],
+                                                &info.thumbnail,
+                                            ),
+                                            TextureOptions::NEAREST,
+                                        )
+                                    });
+                                    ui.add(
+                                        Image::from_texture(SizedTexture::from_handle(texture))
+                                            .fit_to_exact_size(thumbnail_size),
+                                    );
+                                }
+                                None => {
+                                    ui.allocate_ui(thumbnail_size, |ui| {
+                                        ui.centered_and_justified(|ui| {
+                                            ui.weak(if saved { "No Thumbnail" } else { "Empty" });
+                                        });
+                                    });
+                                }
+                            }
+
+                            ui.vertical(|ui| {
+                                ui.horizontal(|ui| {
+                                    ui.strong(format!("Slot {slot}"));
+                                    if slot == save_slot {
+                                        ui.weak("(Selected)");
+                                    }
+                                });
+                                if let Some(save) = save {
+                                    if let Some(saved_at) = save.saved_at {
+                                        ui.label(saved_at.format("%Y-%m-%d %H:%M:%S").to_string());
+                                    }
+                                    if let Some(info) = &save.info {
+                                        ui.label(format!(
+                                            "Play Time: {}",
+                                            format_play_time(info.play_time)
+                                        ));
+                                        ui.weak(format!("Frame {}", info.frame_number));
+                                    }
+                                }
+                            });
+
+                            ui.vertical(|ui| self.slot_buttons(ui, slot, saved));
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    fn slot_buttons(&mut self, ui: &mut Ui, slot: u8, saved: bool) {
+        if ui
+            .button("Save")
+            .on_hover_text("Save the current state to this slot.")
+            .clicked()
+        {
+            self.tx.event(EmulationEvent::SaveState(slot));
+        }
+        if ui
+            .add_enabled(saved, Button::new("Load"))
+            .on_hover_text("Load the state saved in this slot.")
+            .clicked()
+        {
+            self.tx.event(EmulationEvent::LoadState(slot));
+        }
+        if feature!(Filesystem)
+            && ui
+                .add_enabled(saved, Button::new("Export..."))
+                .on_hover_text("Copy the state saved in this slot to another folder.")
+                .clicked()
+        {
+            match open_dir_dialog("Export Save State", None::<PathBuf>) {
+                Ok(Some(dir)) => self.tx.event(EmulationEvent::ExportSaveState((slot, dir))),
+                Ok(None) => (),
+                Err(err) => error!("failed to open directory dialog: {err:?}"),
+            }
+        }
+        let confirm = self.confirm_delete == Some(slot);
+        let delete = if confirm {
+            RichText::new("Delete?").color(ui.visuals().warn_fg_color)
+        } else {
+            RichText::new("Delete")
+        };
+        if ui
+            .add_enabled(saved, Button::new(delete))
+            .on_hover_text("Delete the state saved in this slot.")
+            .clicked()
+        {
+            if confirm {
+                self.tx.event(EmulationEvent::DeleteSaveState(slot));
+                self.confirm_delete = None;
+            } else {
+                self.confirm_delete = Some(slot);
+            }
+        }
+    }
+}
+
+fn format_play_time(play_time: Duration) -> String {
+    let secs = play_time.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
@@ -0,0 +1,129 @@
+use crate::nes::event::{EmulationEvent, NesEventProxy};
+use egui::{Context, DragValue, ProgressBar, Window};
+
+/// Progress of an in-progress seek.
+#[derive(Debug, Copy, Clone)]
+struct Progress {
+    start: u32,
+    frame: u32,
+    target: u32,
+}
+
+impl Progress {
+    fn fraction(&self) -> f32 {
+        let total = self.target.saturating_sub(self.start);
+        if total == 0 {
+            1.0
+        } else {
+            self.frame.saturating_sub(self.start) as f32 / total as f32
+        }
+    }
+}
+
+/// Windows for seeking to a frame number and showing seek progress.
+#[derive(Debug)]
+#[must_use]
+pub struct Seek {
+    tx: NesEventProxy,
+    open: bool,
+    frame: u32,
+    video: bool,
+    progress: Option<Progress>,
+}
+
+impl Seek {
+    pub const fn new(tx: NesEventProxy) -> Self {
+        Self {
+            tx,
+            open: false,
+            frame: 0,
+            video: false,
+            progress: None,
+        }
+    }
+
+    pub fn toggle_open(&mut self) {
+        self.open = !self.open;
+    }
+
+    pub fn update_progress(&mut self, start: u32, frame: u32, target: u32) {
+        self.progress = Some(Progress {
+            start,
+            frame,
+            target,
+        });
+    }
+
+    pub fn finish(&mut self) {
+        self.progress = None;
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool, rom_loaded: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        match self.progress {
+            Some(progress) => self.show_progress_window(ctx, enabled, progress),
+            None => self.show_seek_window(ctx, enabled, rom_loaded),
+        }
+    }
+
+    fn show_seek_window(&mut self, ctx: &Context, enabled: bool, rom_loaded: bool) {
+        let mut open = self.open;
+        let mut seek = false;
+        Window::new("⏩ Seek to Frame")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled && rom_loaded, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Frame:");
+                        ui.add(DragValue::new(&mut self.frame).range(0..=u32::MAX))
+                            .on_hover_text("The frame number to fast-forward or rewind to.");
+                    });
+                    ui.checkbox(&mut self.video, "Render Video").on_hover_text(
+                        "Show frames while seeking. Seeking is faster without video.",
+                    );
+
+                    ui.add_space(8.0);
+                    if ui.button("Seek").clicked() {
+                        seek = true;
+                    }
+                });
+            });
+        self.open = open;
+
+        if seek {
+            self.tx.event(EmulationEvent::SeekToFrame {
+                frame: self.frame,
+                video: self.video,
+            });
+            self.open = false;
+        }
+    }
+
+    fn show_progress_window(&mut self, ctx: &Context, enabled: bool, progress: Progress) {
+        let mut open = true;
+        let mut cancel = false;
+        Window::new("⏩ Seeking...")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| {
+                    ui.add(
+                        ProgressBar::new(progress.fraction())
+                            .text(format!("Frame {} / {}", progress.frame, progress.target))
+                            .desired_width(300.0),
+                    );
+                    ui.add_space(8.0);
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if !open || cancel {
+            self.tx.event(EmulationEvent::CancelSeek);
+        }
+    }
+}
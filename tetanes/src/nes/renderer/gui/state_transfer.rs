@@ -0,0 +1,107 @@
+use crate::nes::{emulation::transfer, event::NesEventProxy};
+use egui::{Context, RichText, TextEdit, Window};
+
+/// Windows for sending and receiving save states between devices.
+#[derive(Debug)]
+#[must_use]
+pub struct StateTransfer {
+    tx: NesEventProxy,
+    link: Option<String>,
+    receive_open: bool,
+    receive_link: String,
+}
+
+impl StateTransfer {
+    pub const fn new(tx: NesEventProxy) -> Self {
+        Self {
+            tx,
+            link: None,
+            receive_open: false,
+            receive_link: String::new(),
+        }
+    }
+
+    /// Show a link another device can download the current state from.
+    pub fn set_link(&mut self, link: String) {
+        self.link = Some(link);
+    }
+
+    pub fn open_receive(&mut self) {
+        self.receive_open = true;
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        self.show_send_window(ctx, enabled);
+        self.show_receive_window(ctx, enabled);
+    }
+
+    fn show_send_window(&mut self, ctx: &Context, enabled: bool) {
+        let Some(link) = &self.link else {
+            return;
+        };
+
+        let mut open = true;
+        let mut close = false;
+        Window::new("📤 Send State")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| {
+                    ui.label(concat!(
+                        "Receive this link in TetaNES on another device with the same ROM loaded ",
+                        "to continue playing. It can be downloaded once and expires in 5 minutes.",
+                    ));
+                    ui.add_space(8.0);
+                    ui.label(RichText::new(link).monospace().strong());
+                    ui.add_space(8.0);
+                    ui.horizontal(|ui| {
+                        if ui.button("📋 Copy Link").clicked() {
+                            ui.ctx().copy_text(link.clone());
+                        }
+                        if ui.button("Close").clicked() {
+                            close = true;
+                        }
+                    });
+                });
+            });
+        if !open || close {
+            self.link = None;
+        }
+    }
+
+    fn show_receive_window(&mut self, ctx: &Context, enabled: bool) {
+        let mut open = self.receive_open;
+        let mut receive = false;
+        Window::new("📥 Receive State")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| {
+                    ui.label("Paste a state link sent from another device:");
+                    let res = ui.add(
+                        TextEdit::singleline(&mut self.receive_link)
+                            .hint_text("http://")
+                            .desired_width(f32::INFINITY),
+                    );
+                    if res.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        receive = true;
+                    }
+                    ui.add_space(8.0);
+                    ui.add_enabled_ui(!self.receive_link.trim().is_empty(), |ui| {
+                        if ui.button("Receive").clicked() {
+                            receive = true;
+                        }
+                    });
+                });
+            });
+        self.receive_open = open;
+
+        if receive && !self.receive_link.trim().is_empty() {
+            transfer::receive(self.tx.clone(), std::mem::take(&mut self.receive_link));
+            self.receive_open = false;
+        }
+    }
+}
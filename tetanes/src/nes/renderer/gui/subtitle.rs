@@ -0,0 +1,94 @@
+use crate::nes::event::{EmulationEvent, NesEventProxy};
+use egui::{
+    Align2, Color32, Context, DragValue, FontId, Rect, Rounding, TextEdit, Ui, Vec2, Window,
+};
+
+/// Window for adding subtitles to the replay being recorded.
+#[derive(Debug)]
+#[must_use]
+pub struct AddSubtitle {
+    tx: NesEventProxy,
+    open: bool,
+    text: String,
+    seconds: f32,
+}
+
+impl AddSubtitle {
+    pub const fn new(tx: NesEventProxy) -> Self {
+        Self {
+            tx,
+            open: false,
+            text: String::new(),
+            seconds: 3.0,
+        }
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool, recording: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let mut open = self.open && recording;
+        let mut add = false;
+        Window::new("💬 Add Subtitle")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| {
+                    ui.label("Shown from the current frame when the replay is played back.");
+                    ui.add(
+                        TextEdit::multiline(&mut self.text)
+                            .desired_rows(2)
+                            .hint_text("Subtitle text"),
+                    );
+                    ui.horizontal(|ui| {
+                        ui.label("Duration:");
+                        ui.add(
+                            DragValue::new(&mut self.seconds)
+                                .range(0.1..=60.0)
+                                .speed(0.1)
+                                .suffix(" s"),
+                        );
+                    });
+
+                    ui.add_space(8.0);
+                    ui.add_enabled_ui(!self.text.trim().is_empty(), |ui| {
+                        if ui.button("Add").clicked() {
+                            add = true;
+                        }
+                    });
+                });
+            });
+        self.open = open;
+
+        if add {
+            self.tx.event(EmulationEvent::AddSubtitle {
+                text: std::mem::take(&mut self.text),
+                seconds: self.seconds,
+            });
+        }
+    }
+}
+
+/// Draws replay subtitle text centered along the bottom of the NES frame.
+pub fn draw_subtitle(ui: &Ui, frame: Rect, text: &str) {
+    let painter = ui.painter_at(frame);
+    let padding = Vec2::splat(4.0);
+    let galley = painter.layout(
+        text.to_string(),
+        FontId::proportional((frame.height() / 20.0).max(12.0)),
+        Color32::WHITE,
+        frame.width() * 0.9,
+    );
+    let pos = frame.center_bottom() - Vec2::new(0.0, frame.height() * 0.05);
+    let rect = Align2::CENTER_BOTTOM.anchor_size(pos, galley.size());
+    painter.rect_filled(
+        rect.expand2(padding),
+        Rounding::same(4.0),
+        Color32::from_black_alpha(180),
+    );
+    painter.galley(rect.min, galley, Color32::WHITE);
+}
@@ -0,0 +1,121 @@
+use egui::{pos2, Color32, ColorImage, Rect, TextureHandle, TextureOptions, Ui};
+use tetanes_core::time::{Duration, Instant};
+
+/// Cosmetic CRT static shown while no ROM is loaded and as a brief burst when one powers on,
+/// with a rolling band and streaks like a noisy RF/composite connection.
+#[must_use]
+pub struct TvStatic {
+    texture: Option<TextureHandle>,
+    pixels: Vec<Color32>,
+    seed: u32,
+    start: Instant,
+    power_on: Option<Instant>,
+}
+
+impl std::fmt::Debug for TvStatic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TvStatic")
+            .field("seed", &self.seed)
+            .field("start", &self.start)
+            .field("power_on", &self.power_on)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for TvStatic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TvStatic {
+    /// Noise resolution, half of the NES frame for chunkier static.
+    const WIDTH: usize = 128;
+    const HEIGHT: usize = 120;
+    const POWER_ON_DURATION: Duration = Duration::from_millis(750);
+    /// Seconds for the rolling band to travel down the screen.
+    const ROLL_SECONDS: f32 = 2.5;
+
+    pub fn new() -> Self {
+        Self {
+            texture: None,
+            pixels: vec![Color32::BLACK; Self::WIDTH * Self::HEIGHT],
+            seed: 0x2545_F491,
+            start: Instant::now(),
+            power_on: None,
+        }
+    }
+
+    /// Start a burst of static that fades out to the game.
+    pub fn power_on(&mut self) {
+        self.power_on = Some(Instant::now());
+    }
+
+    /// Opacity of the power-on burst, or `None` once it's faded out.
+    pub fn power_on_opacity(&mut self) -> Option<f32> {
+        let elapsed = self.power_on?.elapsed();
+        if elapsed >= Self::POWER_ON_DURATION {
+            self.power_on = None;
+            return None;
+        }
+        Some(1.0 - elapsed.as_secs_f32() / Self::POWER_ON_DURATION.as_secs_f32())
+    }
+
+    /// Draw a new frame of static over `rect`.
+    pub fn draw(&mut self, ui: &Ui, rect: Rect, opacity: f32) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        self.generate();
+        let image = ColorImage {
+            size: [Self::WIDTH, Self::HEIGHT],
+            pixels: self.pixels.clone(),
+        };
+        let texture = self.texture.get_or_insert_with(|| {
+            ui.ctx().load_texture(
+                "tv_static",
+                ColorImage::new([Self::WIDTH, Self::HEIGHT], Color32::BLACK),
+                TextureOptions::NEAREST,
+            )
+        });
+        texture.set(image, TextureOptions::NEAREST);
+
+        let uv = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
+        ui.painter_at(rect).image(
+            texture.id(),
+            rect,
+            uv,
+            Color32::WHITE.gamma_multiply(opacity),
+        );
+        ui.ctx().request_repaint();
+    }
+
+    fn generate(&mut self) {
+        let roll = (self.start.elapsed().as_secs_f32() / Self::ROLL_SECONDS).fract();
+        let band = roll * Self::HEIGHT as f32;
+        for (y, row) in self.pixels.chunks_exact_mut(Self::WIDTH).enumerate() {
+            // Brighter band rolling down the screen, like a mistuned RF signal
+            let distance = (y as f32 - band).abs();
+            let band_boost = (1.0 - distance / 12.0).max(0.0) * 60.0;
+            // Occasional bright streaks along a scanline, like composite interference
+            let streak = if Self::next(&mut self.seed) % 64 == 0 {
+                50.0
+            } else {
+                0.0
+            };
+            for pixel in row {
+                let noise = (Self::next(&mut self.seed) & 0xFF) as f32 * 0.75;
+                let luma = (noise + band_boost + streak).min(255.0) as u8;
+                *pixel = Color32::from_gray(luma);
+            }
+        }
+    }
+
+    /// Xorshift pseudo-random numbers, plenty for visual noise.
+    fn next(seed: &mut u32) -> u32 {
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 17;
+        *seed ^= *seed << 5;
+        *seed
+    }
+}
@@ -1,4 +1,4 @@
-use crate::nes::renderer::shader::{self, Shader};
+use crate::nes::renderer::shader::{self, ShaderPipeline};
 use anyhow::{anyhow, Context};
 use egui::{
     ahash::HashMap,
@@ -20,9 +20,11 @@ use winit::{dpi::PhysicalSize, window::Window};
 #[must_use]
 pub struct Surface {
     inner: wgpu::Surface<'static>,
-    shader_resources: Option<shader::Resources>,
+    shader_passes: Vec<shader::Resources>,
     width: u32,
     height: u32,
+    /// Composite with the windows underneath using the alpha channel.
+    translucent: bool,
 }
 
 impl Surface {
@@ -33,9 +35,10 @@ impl Surface {
     ) -> anyhow::Result<Self> {
         Ok(Self {
             inner: instance.create_surface(window)?,
-            shader_resources: None,
+            shader_passes: Vec::new(),
             width: size.width,
             height: size.height,
+            translucent: false,
         })
     }
 
@@ -63,23 +66,38 @@ impl Surface {
             .create_view(&wgpu::TextureViewDescriptor::default())
     }
 
-    fn set_shader(
+    fn set_shaders(
         &mut self,
         device: &wgpu::Device,
         format: wgpu::TextureFormat,
         uniform_bind_group_layout: &wgpu::BindGroupLayout,
-        shader: Shader,
+        shaders: &ShaderPipeline,
     ) {
-        if matches!(shader, Shader::None) {
-            self.shader_resources = None;
+        self.shader_passes = shaders
+            .active()
+            .map(|pass| {
+                shader::Resources::new(
+                    device,
+                    format,
+                    self.create_texture_view(device, format),
+                    uniform_bind_group_layout,
+                    pass,
+                )
+            })
+            .collect();
+    }
+
+    fn set_shader_params(&self, queue: &wgpu::Queue, shaders: &ShaderPipeline) {
+        for (resources, pass) in self.shader_passes.iter().zip(shaders.active()) {
+            resources.set_params(queue, pass);
+        }
+    }
+
+    const fn clear_color(&self) -> wgpu::Color {
+        if self.translucent {
+            wgpu::Color::TRANSPARENT
         } else {
-            self.shader_resources = Some(shader::Resources::new(
-                device,
-                format,
-                self.create_texture_view(device, format),
-                uniform_bind_group_layout,
-                shader,
-            ));
+            wgpu::Color::BLACK
         }
     }
 }
@@ -97,6 +115,8 @@ pub struct Painter {
     instance: wgpu::Instance,
     render_state: Option<RenderState>,
     surfaces: ViewportIdMap<Surface>,
+    /// Whether the root window is translucent.
+    translucent: bool,
 }
 
 impl Default for Painter {
@@ -105,6 +125,7 @@ impl Default for Painter {
             instance: wgpu::Instance::new(wgpu::InstanceDescriptor::default()),
             render_state: None,
             surfaces: Default::default(),
+            translucent: false,
         }
     }
 }
@@ -114,20 +135,47 @@ impl Painter {
         Self::default()
     }
 
-    pub fn set_shader(&mut self, shader: Shader) {
+    pub fn set_shaders(&mut self, shaders: &ShaderPipeline) {
         if let Some(render_state) = &mut self.render_state {
-            render_state.shader = shader;
+            // Only recreate passes when shaders are added, removed or reordered
+            let same_shaders = render_state.shaders.same_shaders(shaders);
+            render_state.shaders.clone_from(shaders);
             for surface in self.surfaces.values_mut() {
-                surface.set_shader(
-                    &render_state.device,
-                    render_state.format,
-                    &render_state.uniform_bind_group_layout,
-                    shader,
-                );
+                if same_shaders {
+                    surface.set_shader_params(&render_state.queue, shaders);
+                } else {
+                    surface.set_shaders(
+                        &render_state.device,
+                        render_state.format,
+                        &render_state.uniform_bind_group_layout,
+                        shaders,
+                    );
+                }
             }
         }
     }
 
+    /// Set whether the root window composites with the windows underneath it. Returns `false` if
+    /// the platform doesn't support transparent surfaces.
+    pub fn set_translucent(&mut self, translucent: bool) -> bool {
+        self.translucent = translucent;
+        let Some(render_state) = &self.render_state else {
+            return true;
+        };
+        if let Some(surface) = self.surfaces.get_mut(&ViewportId::ROOT) {
+            if surface.translucent != translucent {
+                surface.translucent = translucent;
+                if let (Some(width), Some(height)) = (
+                    NonZeroU32::new(surface.width),
+                    NonZeroU32::new(surface.height),
+                ) {
+                    render_state.resize_surface(surface, width, height);
+                }
+            }
+        }
+        !translucent || render_state.translucent_alpha_mode.is_some()
+    }
+
     pub async fn set_window(
         &mut self,
         viewport_id: ViewportId,
@@ -137,6 +185,7 @@ impl Painter {
             if let Entry::Vacant(entry) = self.surfaces.entry(viewport_id) {
                 let size = window.inner_size();
                 let mut surface = Surface::new(&self.instance, window, size)?;
+                surface.translucent = self.translucent && viewport_id == ViewportId::ROOT;
 
                 let render_state = match &mut self.render_state {
                     Some(render_state) => render_state,
@@ -174,7 +223,7 @@ impl Painter {
         let Some(render_state) = &mut self.render_state else {
             return;
         };
-        let Some(surface) = self.surfaces.get(&viewport_id) else {
+        let Some(surface) = self.surfaces.get_mut(&viewport_id) else {
             return;
         };
 
@@ -201,19 +250,30 @@ impl Painter {
         let output_frame = match surface.get_current_texture() {
             Ok(frame) => frame,
             Err(err) => {
-                if err != wgpu::SurfaceError::Outdated {
-                    tracing::error!("failed to acquire next frame: {:?}", err);
+                match err {
+                    wgpu::SurfaceError::Outdated => (),
+                    // Surfaces can be lost when suspended, so reconfigure and try again next frame
+                    wgpu::SurfaceError::Lost => {
+                        if let (Some(width), Some(height)) = (
+                            NonZeroU32::new(surface.width),
+                            NonZeroU32::new(surface.height),
+                        ) {
+                            render_state.resize_surface(surface, width, height);
+                        }
+                    }
+                    _ => tracing::error!("failed to acquire next frame: {:?}", err),
                 }
                 return;
             }
         };
 
+        let output_view = output_frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
         {
-            let view = match &surface.shader_resources {
+            let view = match surface.shader_passes.first() {
                 Some(shader) => &shader.view,
-                None => &output_frame
-                    .texture
-                    .create_view(&wgpu::TextureViewDescriptor::default()),
+                None => &output_view,
             };
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("main_render_pass"),
@@ -221,7 +281,7 @@ impl Painter {
                     view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        load: wgpu::LoadOp::Clear(surface.clear_color()),
                         store: wgpu::StoreOp::Store,
                     },
                 })],
@@ -233,10 +293,12 @@ impl Painter {
             render_state.render(&mut render_pass, clipped_primitives, &screen_descriptor);
         }
 
-        if let Some(shader) = &surface.shader_resources {
-            let view = &output_frame
-                .texture
-                .create_view(&wgpu::TextureViewDescriptor::default());
+        // Each pass renders into the input of the next, with the last rendering to the surface
+        for (i, shader) in surface.shader_passes.iter().enumerate() {
+            let view = surface
+                .shader_passes
+                .get(i + 1)
+                .map_or(&output_view, |next| &next.view);
 
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("main_render_pass"),
@@ -244,7 +306,7 @@ impl Painter {
                     view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        load: wgpu::LoadOp::Clear(surface.clear_color()),
                         store: wgpu::StoreOp::Store,
                     },
                 })],
@@ -319,6 +381,8 @@ pub struct RenderState {
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
     pub format: wgpu::TextureFormat,
+    /// Alpha mode used for translucent surfaces, if any are supported.
+    translucent_alpha_mode: Option<wgpu::CompositeAlphaMode>,
 
     pipeline: wgpu::RenderPipeline,
 
@@ -331,7 +395,7 @@ pub struct RenderState {
     uniform_bind_group_layout: wgpu::BindGroupLayout,
     texture_bind_group_layout: wgpu::BindGroupLayout,
 
-    shader: Shader,
+    shaders: ShaderPipeline,
     /// Map of egui texture IDs to textures and their associated bindgroups (texture view +
     /// sampler). The texture may be None if the `TextureId` is just a handle to a user-provided
     /// sampler.
@@ -403,6 +467,12 @@ impl RenderState {
                 )
             })
             .unwrap_or(capabilities.formats[0]); // TODO: Is falling back to first available okay?
+        let translucent_alpha_mode = [
+            wgpu::CompositeAlphaMode::PreMultiplied,
+            wgpu::CompositeAlphaMode::PostMultiplied,
+        ]
+        .into_iter()
+        .find(|mode| capabilities.alpha_modes.contains(mode));
 
         let (device, queue) =
             connection.map_err(|err| anyhow!("failed to create wgpu device: {err:?}"))?;
@@ -542,6 +612,7 @@ impl RenderState {
             device,
             queue,
             format,
+            translucent_alpha_mode,
 
             pipeline,
 
@@ -554,7 +625,7 @@ impl RenderState {
             uniform_bind_group_layout,
             texture_bind_group_layout,
 
-            shader: Shader::default(),
+            shaders: ShaderPipeline::default(),
             textures: Default::default(),
             next_texture_id: 0,
             samplers: Default::default(),
@@ -625,15 +696,18 @@ impl RenderState {
                 // TODO: Support disabling vsync
                 present_mode: wgpu::PresentMode::AutoVsync,
                 desired_maximum_frame_latency: 2,
-                alpha_mode: wgpu::CompositeAlphaMode::Auto,
+                alpha_mode: self
+                    .translucent_alpha_mode
+                    .filter(|_| surface.translucent)
+                    .unwrap_or(wgpu::CompositeAlphaMode::Auto),
                 view_formats: vec![self.format],
             },
         );
-        surface.set_shader(
+        surface.set_shaders(
             &self.device,
             self.format,
             &self.uniform_bind_group_layout,
-            self.shader,
+            &self.shaders,
         );
     }
 
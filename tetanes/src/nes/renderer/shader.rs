@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
 use thiserror::Error;
+use wgpu::util::DeviceExt;
 
 #[derive(Error, Debug)]
 #[must_use]
@@ -12,11 +14,54 @@ pub enum Shader {
     None,
     #[default]
     CrtEasymode,
+    Sharpen,
+}
+
+/// A tunable shader parameter, passed to the shader in declaration order.
+#[derive(Debug, Clone, PartialEq)]
+#[must_use]
+pub struct Param {
+    pub name: &'static str,
+    pub default: f32,
+    pub range: RangeInclusive<f32>,
+}
+
+impl Param {
+    const fn new(name: &'static str, default: f32, range: RangeInclusive<f32>) -> Self {
+        Self {
+            name,
+            default,
+            range,
+        }
+    }
 }
 
 impl Shader {
+    /// Maximum number of parameters a shader can have, matching the size of the parameter
+    /// uniform buffer.
+    pub const MAX_PARAMS: usize = 8;
+
     pub const fn as_slice() -> &'static [Self] {
-        &[Self::None, Self::CrtEasymode]
+        &[Self::None, Self::CrtEasymode, Self::Sharpen]
+    }
+
+    pub const fn params(&self) -> &'static [Param] {
+        const CRT_EASYMODE: [Param; 8] = [
+            Param::new("Horizontal Sharpness", 0.5, 0.0..=1.0),
+            Param::new("Vertical Sharpness", 1.0, 0.0..=1.0),
+            Param::new("Mask Strength", 0.3, 0.0..=1.0),
+            Param::new("Scanline Strength", 1.0, 0.0..=1.0),
+            Param::new("Input Gamma", 2.0, 1.0..=5.0),
+            Param::new("Output Gamma", 1.8, 1.0..=5.0),
+            Param::new("Brightness Boost", 1.2, 1.0..=2.0),
+            Param::new("Dilation", 1.0, 0.0..=1.0),
+        ];
+        const SHARPEN: [Param; 1] = [Param::new("Strength", 0.5, 0.0..=2.0)];
+        match self {
+            Self::None => &[],
+            Self::CrtEasymode => &CRT_EASYMODE,
+            Self::Sharpen => &SHARPEN,
+        }
     }
 }
 
@@ -25,6 +70,7 @@ impl AsRef<str> for Shader {
         match self {
             Self::None => "None",
             Self::CrtEasymode => "CRT Easymode",
+            Self::Sharpen => "Sharpen",
         }
     }
 }
@@ -36,17 +82,110 @@ impl TryFrom<usize> for Shader {
         Ok(match value {
             0 => Self::None,
             1 => Self::CrtEasymode,
+            2 => Self::Sharpen,
             _ => return Err(ParseShaderError),
         })
     }
 }
 
+/// A shader in a [`ShaderPipeline`] along with its parameter values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[must_use]
+pub struct ShaderPass {
+    pub shader: Shader,
+    pub enabled: bool,
+    pub params: Vec<f32>,
+}
+
+impl ShaderPass {
+    pub fn new(shader: Shader) -> Self {
+        Self {
+            shader,
+            enabled: true,
+            params: shader.params().iter().map(|param| param.default).collect(),
+        }
+    }
+
+    /// Parameter values for the parameter uniform buffer, using defaults for any that are
+    /// missing, e.g. from a hand-edited configuration.
+    pub fn param_values(&self) -> [f32; Shader::MAX_PARAMS] {
+        let mut values = [0.0; Shader::MAX_PARAMS];
+        for (i, (value, param)) in values.iter_mut().zip(self.shader.params()).enumerate() {
+            *value = self.params.get(i).copied().unwrap_or(param.default);
+        }
+        values
+    }
+}
+
+/// An ordered list of shader passes, each rendering the output of the previous pass.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[must_use]
+#[serde(from = "ShaderPipelineRepr")]
+pub struct ShaderPipeline {
+    pub passes: Vec<ShaderPass>,
+}
+
+impl Default for ShaderPipeline {
+    fn default() -> Self {
+        Self {
+            passes: vec![ShaderPass::new(Shader::default())],
+        }
+    }
+}
+
+impl ShaderPipeline {
+    /// Passes to render, skipping disabled passes.
+    pub fn active(&self) -> impl Iterator<Item = &ShaderPass> {
+        self.passes
+            .iter()
+            .filter(|pass| pass.enabled && pass.shader != Shader::None)
+    }
+
+    /// Whether `other` renders the same shaders in the same order, ignoring parameter values.
+    pub fn same_shaders(&self, other: &Self) -> bool {
+        self.active()
+            .map(|pass| pass.shader)
+            .eq(other.active().map(|pass| pass.shader))
+    }
+}
+
+/// Configurations before shader pipelines stored a single shader.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ShaderPipelineRepr {
+    Single(Shader),
+    Pipeline { passes: Vec<ShaderPass> },
+}
+
+impl From<ShaderPipelineRepr> for ShaderPipeline {
+    fn from(repr: ShaderPipelineRepr) -> Self {
+        match repr {
+            ShaderPipelineRepr::Single(Shader::None) => Self { passes: Vec::new() },
+            ShaderPipelineRepr::Single(shader) => Self {
+                passes: vec![ShaderPass::new(shader)],
+            },
+            ShaderPipelineRepr::Pipeline { passes } => Self {
+                passes: passes
+                    .into_iter()
+                    .map(|pass| ShaderPass {
+                        params: pass.param_values()[..pass.shader.params().len()].to_vec(),
+                        ..pass
+                    })
+                    .collect(),
+            },
+        }
+    }
+}
+
+/// GPU resources for a single shader pass.
 #[derive(Debug)]
 #[must_use]
 pub struct Resources {
+    /// Input texture for this pass, rendered to by the previous pass.
     pub view: wgpu::TextureView,
     pub texture_bind_group: wgpu::BindGroup,
     pub render_pipeline: wgpu::RenderPipeline,
+    params_buffer: wgpu::Buffer,
 }
 
 impl Resources {
@@ -55,14 +194,17 @@ impl Resources {
         format: wgpu::TextureFormat,
         view: wgpu::TextureView,
         uniform_bind_group_layout: &wgpu::BindGroupLayout,
-        shader: Shader,
+        pass: &ShaderPass,
     ) -> Self {
-        let shader_module_desc = match shader {
+        let shader_module_desc = match pass.shader {
             Shader::None => panic!("No shader selected"),
             Shader::CrtEasymode => wgpu::include_wgsl!(concat!(
                 env!("CARGO_MANIFEST_DIR"),
                 "/shaders/crt-easymode.wgsl"
             )),
+            Shader::Sharpen => {
+                wgpu::include_wgsl!(concat!(env!("CARGO_MANIFEST_DIR"), "/shaders/sharpen.wgsl"))
+            }
         };
         let shader_module = device.create_shader_module(shader_module_desc);
 
@@ -86,6 +228,16 @@ impl Resources {
                         ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                         count: None,
                     },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
                 ],
             });
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
@@ -98,6 +250,11 @@ impl Resources {
             mipmap_filter: wgpu::FilterMode::Nearest,
             ..Default::default()
         });
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("shader params buffer"),
+            contents: bytemuck::cast_slice(&pass.param_values()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
         let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("nes frame bind group"),
             layout: &texture_bind_group_layout,
@@ -110,6 +267,10 @@ impl Resources {
                     binding: 1,
                     resource: wgpu::BindingResource::Sampler(&sampler),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: params_buffer.as_entire_binding(),
+                },
             ],
         });
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -148,6 +309,16 @@ impl Resources {
             view,
             texture_bind_group,
             render_pipeline,
+            params_buffer,
         }
     }
+
+    /// Update parameter values without recreating the pass.
+    pub fn set_params(&self, queue: &wgpu::Queue, pass: &ShaderPass) {
+        queue.write_buffer(
+            &self.params_buffer,
+            0,
+            bytemuck::cast_slice(&pass.param_values()),
+        );
+    }
 }
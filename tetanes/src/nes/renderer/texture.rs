@@ -21,29 +21,58 @@ impl Texture {
         label: Option<&'static str>,
     ) -> Self {
         let max_texture_side = render_state.max_texture_side() as f32;
-        let texture = render_state
-            .device
-            .create_texture(&wgpu::TextureDescriptor {
-                label,
-                size: wgpu::Extent3d {
-                    width: size.x.min(max_texture_side) as u32,
-                    height: size.y.min(max_texture_side) as u32,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Rgba8UnormSrgb,
-                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-                view_formats: &[],
-            });
+        let (texture, view) = Self::create(&render_state.device, size, max_texture_side, label);
+        let id = render_state.register_texture(label, &view, Self::sampler_descriptor());
+        Self::from_parts(texture, view, id, size, aspect_ratio, label)
+    }
+
+    /// Create a texture on `device`, calling `register` to register its view with an external
+    /// `egui` renderer, e.g. `egui_wgpu::Renderer::register_native_texture`.
+    pub fn with_device(
+        device: &wgpu::Device,
+        size: Vec2,
+        aspect_ratio: f32,
+        label: Option<&'static str>,
+        register: impl FnOnce(&wgpu::TextureView) -> TextureId,
+    ) -> Self {
+        let max_texture_side = device.limits().max_texture_dimension_2d as f32;
+        let (texture, view) = Self::create(device, size, max_texture_side, label);
+        let id = register(&view);
+        Self::from_parts(texture, view, id, size, aspect_ratio, label)
+    }
+
+    fn create(
+        device: &wgpu::Device,
+        size: Vec2,
+        max_texture_side: f32,
+        label: Option<&'static str>,
+    ) -> (wgpu::Texture, wgpu::TextureView) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label,
+            size: wgpu::Extent3d {
+                width: size.x.min(max_texture_side) as u32,
+                height: size.y.min(max_texture_side) as u32,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor {
             label,
             dimension: Some(wgpu::TextureViewDimension::D2),
             ..Default::default()
         });
-        let sampler_descriptor = wgpu::SamplerDescriptor {
+
+        (texture, view)
+    }
+
+    fn sampler_descriptor() -> wgpu::SamplerDescriptor<'static> {
+        wgpu::SamplerDescriptor {
             label: Some("sampler"),
             address_mode_u: wgpu::AddressMode::ClampToEdge,
             address_mode_v: wgpu::AddressMode::ClampToEdge,
@@ -52,9 +81,17 @@ impl Texture {
             min_filter: wgpu::FilterMode::Nearest,
             mipmap_filter: wgpu::FilterMode::Nearest,
             ..Default::default()
-        };
-        let id = render_state.register_texture(label, &view, sampler_descriptor);
+        }
+    }
 
+    fn from_parts(
+        texture: wgpu::Texture,
+        view: wgpu::TextureView,
+        id: TextureId,
+        size: Vec2,
+        aspect_ratio: f32,
+        label: Option<&'static str>,
+    ) -> Self {
         Self {
             label,
             texture,
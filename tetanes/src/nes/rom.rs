@@ -1,3 +1,8 @@
+use crate::nes::event::{EmulationEvent, NesEventProxy};
+#[cfg(target_arch = "wasm32")]
+use crate::{nes::event::UiEvent, platform, thread};
+use cfg_if::cfg_if;
+
 #[derive(Clone, PartialEq)]
 pub struct RomData(pub Vec<u8>);
 
@@ -17,9 +22,12 @@ impl AsRef<[u8]> for RomData {
 #[must_use]
 pub struct RomAsset {
     pub name: &'static str,
+    pub filename: &'static str,
     pub authors: &'static str,
     pub description: &'static str,
     pub source: &'static str,
+    /// Embedded ROM data. Web builds fetch ROMs on demand instead to keep the wasm binary small.
+    #[cfg(not(target_arch = "wasm32"))]
     pub data_fn: &'static dyn Fn() -> Vec<u8>,
 }
 
@@ -27,6 +35,7 @@ impl std::fmt::Debug for RomAsset {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RomAsset")
             .field("name", &self.name)
+            .field("filename", &self.filename)
             .field("authors", &self.authors)
             .field("description", &self.description)
             .field("source", &self.source)
@@ -35,30 +44,37 @@ impl std::fmt::Debug for RomAsset {
 }
 
 impl RomAsset {
-    pub const fn new(
-        name: &'static str,
-        authors: &'static str,
-        description: &'static str,
-        source: &'static str,
-        data_fn: &'static dyn Fn() -> Vec<u8>,
-    ) -> Self {
-        Self {
-            name,
-            authors,
-            description,
-            source,
-            data_fn,
+    /// Loads the ROM data and sends it to the emulation thread to be loaded.
+    pub fn load(self, tx: &NesEventProxy) {
+        cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                let tx = tx.clone();
+                thread::spawn(async move {
+                    match platform::fetch_asset(&format!("roms/{}", self.filename)).await {
+                        Ok(data) => tx.event(EmulationEvent::LoadRom((
+                            self.name.to_string(),
+                            RomData(data),
+                        ))),
+                        Err(err) => {
+                            tracing::error!("failed to fetch {}: {err:?}", self.filename);
+                            tx.event(UiEvent::Error(format!("failed to load {}", self.name)));
+                        }
+                    }
+                });
+            } else {
+                tx.event(EmulationEvent::LoadRom((
+                    self.name.to_string(),
+                    RomData((self.data_fn)()),
+                )));
+            }
         }
     }
-
-    pub fn data(&self) -> RomData {
-        RomData((self.data_fn)())
-    }
 }
 
 macro_rules! rom_assets {
     ($(($name:expr, $filename:expr, $authors:expr, $description:expr, $source:expr$(,)?)),*$(,)?) => {[$(
         {
+            #[cfg(not(target_arch = "wasm32"))]
             fn data_fn() -> Vec<u8> {
                 include_bytes!(concat!(
                     env!("CARGO_MANIFEST_DIR"),
@@ -66,13 +82,15 @@ macro_rules! rom_assets {
                     $filename
                 )).to_vec()
             }
-            RomAsset::new(
-                $name,
-                $authors,
-                $description,
-                $source,
-                &data_fn
-            )
+            RomAsset {
+                name: $name,
+                filename: $filename,
+                authors: $authors,
+                description: $description,
+                source: $source,
+                #[cfg(not(target_arch = "wasm32"))]
+                data_fn: &data_fn,
+            }
         },
     )*]};
 }
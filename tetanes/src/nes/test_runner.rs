@@ -0,0 +1,323 @@
+//! Running a ROM headlessly against a TOML test spec, e.g. for homebrew CI with
+//! `tetanes test spec.toml`.
+//!
+//! A spec names the ROM to run, buttons to press at given frames, and assertions on RAM values or
+//! the screen at given frames:
+//!
+//! ```toml
+//! rom = "game.nes"   # Relative to the spec file
+//! region = "ntsc"    # Optional, auto-detected by default
+//!
+//! [[input]]
+//! frame = 60
+//! buttons = ["start"]
+//! player = 1         # Optional, defaults to 1
+//! duration = 2       # Frames to hold the buttons, defaults to 1
+//!
+//! [[assert]]
+//! frame = 120
+//! name = "title screen"
+//! screen = 0x1A2B3C4D  # CRC32 of the RGBA frame buffer
+//!
+//! [[assert]]
+//! frame = 300
+//! memory = 0x075A
+//! equals = 3
+//! ```
+//!
+//! Failed screen assertions print the actual CRC32, so a new assertion can be written with any
+//! value and updated after the first run.
+
+use anyhow::{anyhow, bail, Context};
+use std::path::{Path, PathBuf};
+use tetanes_core::{
+    common::NesRegion,
+    control_deck::{Config, ControlDeck, HeadlessMode},
+    fs,
+    input::{JoypadBtn, Player},
+    mem::{Mem, RamState},
+};
+use toml_edit::{DocumentMut, Item, Table};
+
+/// Buttons held for a number of frames.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Input {
+    pub frame: u32,
+    pub player: Player,
+    pub buttons: Vec<JoypadBtn>,
+    pub duration: u32,
+}
+
+impl Input {
+    const fn is_held(&self, frame: u32) -> bool {
+        frame >= self.frame && frame < self.frame + self.duration
+    }
+}
+
+/// What an [`Assertion`] checks.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum Check {
+    /// CRC32 of the RGBA frame buffer.
+    Screen(u32),
+    /// Value of a CPU address.
+    Memory { addr: u16, equals: u8 },
+}
+
+/// A check made at the end of a frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Assertion {
+    pub frame: u32,
+    pub name: Option<String>,
+    pub check: Check,
+}
+
+impl std::fmt::Display for Assertion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "frame {}", self.frame)?;
+        if let Some(name) = &self.name {
+            write!(f, " ({name})")?;
+        }
+        match self.check {
+            Check::Screen(crc32) => write!(f, ": screen == {crc32:#010X}"),
+            Check::Memory { addr, equals } => write!(f, ": ${addr:04X} == ${equals:02X}"),
+        }
+    }
+}
+
+/// A parsed test spec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct TestSpec {
+    pub rom: PathBuf,
+    pub region: NesRegion,
+    pub inputs: Vec<Input>,
+    pub assertions: Vec<Assertion>,
+}
+
+impl TestSpec {
+    /// Load a test spec, resolving the ROM path relative to the spec file.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let spec =
+            std::fs::read_to_string(path).with_context(|| format!("failed to read {path:?}"))?;
+        let mut spec = Self::parse(&spec).with_context(|| format!("invalid test spec {path:?}"))?;
+        if let Some(dir) = path.parent() {
+            spec.rom = dir.join(&spec.rom);
+        }
+        Ok(spec)
+    }
+
+    pub fn parse(spec: &str) -> anyhow::Result<Self> {
+        let doc = spec.parse::<DocumentMut>()?;
+        let rom = doc
+            .get("rom")
+            .and_then(Item::as_str)
+            .ok_or_else(|| anyhow!("missing `rom` path"))?
+            .into();
+        let region = match doc.get("region").and_then(Item::as_str) {
+            Some(region) => {
+                NesRegion::try_from(region).map_err(|_| anyhow!("invalid `region`: {region:?}"))?
+            }
+            None => NesRegion::Auto,
+        };
+        let inputs = tables(&doc, "input")?
+            .map(|(index, table)| {
+                Self::parse_input(table).with_context(|| format!("invalid input #{}", index + 1))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        let assertions = tables(&doc, "assert")?
+            .map(|(index, table)| {
+                Self::parse_assertion(table)
+                    .with_context(|| format!("invalid assert #{}", index + 1))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        if assertions.is_empty() {
+            bail!("no `[[assert]]` entries");
+        }
+
+        Ok(Self {
+            rom,
+            region,
+            inputs,
+            assertions,
+        })
+    }
+
+    fn parse_input(table: &Table) -> anyhow::Result<Input> {
+        let player = match int(table, "player")?.unwrap_or(1) {
+            1 => Player::One,
+            2 => Player::Two,
+            3 => Player::Three,
+            4 => Player::Four,
+            player => bail!("invalid `player`: {player}"),
+        };
+        let buttons = table
+            .get("buttons")
+            .and_then(Item::as_array)
+            .ok_or_else(|| anyhow!("missing `buttons` list"))?
+            .iter()
+            .map(|button| {
+                let button = button
+                    .as_str()
+                    .ok_or_else(|| anyhow!("invalid button: {button}"))?;
+                parse_button(button).ok_or_else(|| anyhow!("invalid button: {button:?}"))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Input {
+            frame: required(table, "frame")?,
+            player,
+            buttons,
+            duration: int(table, "duration")?.unwrap_or(1),
+        })
+    }
+
+    fn parse_assertion(table: &Table) -> anyhow::Result<Assertion> {
+        let check = match (int(table, "screen")?, int(table, "memory")?) {
+            (Some(crc32), None) => Check::Screen(crc32),
+            (None, Some(addr)) => Check::Memory {
+                addr,
+                equals: required(table, "equals")?,
+            },
+            _ => bail!("expected one of `screen` or `memory`"),
+        };
+        Ok(Assertion {
+            frame: required(table, "frame")?,
+            name: table.get("name").and_then(Item::as_str).map(String::from),
+            check,
+        })
+    }
+
+    /// The last frame any input or assertion happens on.
+    #[must_use]
+    pub fn last_frame(&self) -> u32 {
+        let inputs = self.inputs.iter().map(|input| input.frame + input.duration);
+        let assertions = self.assertions.iter().map(|assertion| assertion.frame);
+        inputs.chain(assertions).max().unwrap_or_default()
+    }
+
+    /// Run the ROM headlessly, returning the failure message for each failed assertion.
+    pub fn run(&self) -> anyhow::Result<Vec<String>> {
+        let mut deck = ControlDeck::with_config(Config {
+            region: self.region,
+            // Keep runs deterministic
+            ram_state: RamState::AllZeros,
+            headless_mode: HeadlessMode::NO_AUDIO,
+            // Don't load battery-backed saves from normal play
+            data_dir: std::env::temp_dir().join(format!("tetanes-test-{}", std::process::id())),
+            ..Default::default()
+        });
+        deck.load_rom_path(&self.rom)?;
+
+        let mut failures = Vec::new();
+        let last_frame = self.last_frame();
+        while deck.frame_number() < last_frame {
+            let frame = deck.frame_number();
+            for player in [Player::One, Player::Two, Player::Three, Player::Four] {
+                let joypad = deck.joypad_mut(player);
+                for &button in &[
+                    JoypadBtn::Left,
+                    JoypadBtn::Right,
+                    JoypadBtn::Up,
+                    JoypadBtn::Down,
+                    JoypadBtn::A,
+                    JoypadBtn::B,
+                    JoypadBtn::Select,
+                    JoypadBtn::Start,
+                ] {
+                    let pressed = self.inputs.iter().any(|input| {
+                        input.player == player
+                            && input.is_held(frame)
+                            && input.buttons.contains(&button)
+                    });
+                    joypad.set_button(button, pressed);
+                }
+            }
+
+            deck.clock_frame()?;
+            deck.clear_audio_samples();
+
+            let frame = deck.frame_number();
+            for assertion in self.assertions.iter().filter(|a| a.frame == frame) {
+                let actual = match assertion.check {
+                    Check::Screen(expected) => {
+                        let actual = fs::compute_crc32(deck.frame_buffer());
+                        (actual != expected).then(|| format!("actual: {actual:#010X}"))
+                    }
+                    Check::Memory { addr, equals } => {
+                        let actual = deck.cpu().peek(addr);
+                        (actual != equals).then(|| format!("actual: ${actual:02X}"))
+                    }
+                };
+                if let Some(actual) = actual {
+                    failures.push(format!("{assertion}, {actual}"));
+                }
+            }
+        }
+
+        Ok(failures)
+    }
+}
+
+/// Run a test spec, printing results and returning an error if any assertion failed.
+pub fn run(path: impl AsRef<Path>) -> anyhow::Result<()> {
+    let spec = TestSpec::load(path)?;
+    let failures = spec.run()?;
+    let total = spec.assertions.len();
+    for failure in &failures {
+        println!("FAILED {failure}");
+    }
+    if failures.is_empty() {
+        println!("{total} assertion(s) passed");
+        Ok(())
+    } else {
+        bail!("{} of {total} assertion(s) failed", failures.len())
+    }
+}
+
+fn parse_button(button: &str) -> Option<JoypadBtn> {
+    Some(match button.to_ascii_lowercase().as_str() {
+        "left" => JoypadBtn::Left,
+        "right" => JoypadBtn::Right,
+        "up" => JoypadBtn::Up,
+        "down" => JoypadBtn::Down,
+        "a" => JoypadBtn::A,
+        "b" => JoypadBtn::B,
+        "select" => JoypadBtn::Select,
+        "start" => JoypadBtn::Start,
+        _ => return None,
+    })
+}
+
+/// Returns the tables of an array of tables, e.g. `[[input]]`, with their indexes.
+fn tables<'a>(
+    doc: &'a DocumentMut,
+    key: &str,
+) -> anyhow::Result<impl Iterator<Item = (usize, &'a Table)>> {
+    let tables = match doc.get(key) {
+        Some(item) => item
+            .as_array_of_tables()
+            .ok_or_else(|| anyhow!("expected `[[{key}]]` entries"))?
+            .iter()
+            .collect(),
+        None => Vec::new(),
+    };
+    Ok(tables.into_iter().enumerate())
+}
+
+fn int<T: TryFrom<i64>>(table: &Table, key: &str) -> anyhow::Result<Option<T>> {
+    table
+        .get(key)
+        .map(|item| {
+            item.as_integer()
+                .and_then(|val| T::try_from(val).ok())
+                .ok_or_else(|| anyhow!("invalid `{key}`: {item}"))
+        })
+        .transpose()
+}
+
+fn required<T: TryFrom<i64>>(table: &Table, key: &str) -> anyhow::Result<T> {
+    int(table, key)?.ok_or_else(|| anyhow!("missing `{key}`"))
+}
@@ -0,0 +1,285 @@
+//! An embeddable NES widget for `egui` applications rendering with `wgpu`.
+//!
+//! [`NesWidget`] owns its own [`ControlDeck`] and texture, so it can be drawn inside any `egui`
+//! app without the rest of the `TetaNES` frontend:
+//!
+//! ```ignore
+//! let mut nes = NesWidget::new(device, ControlDeckConfig::default(), |view| {
+//!     egui_renderer.register_native_texture(device, view, wgpu::FilterMode::Nearest)
+//! });
+//! nes.load_rom_path("game.nes")?;
+//!
+//! // Each frame
+//! nes.update(queue)?;
+//! ui.add(&mut nes);
+//! ```
+
+use crate::nes::{
+    config::FrameRate, renderer::gui::lib::cursor_to_zapper, renderer::texture::Texture,
+};
+use egui::{Image, Key, Pos2, Response, Sense, TextureId, Ui, Vec2, Widget};
+use tetanes_core::{
+    common::Regional,
+    control_deck::{self, Config as ControlDeckConfig, ControlDeck, LoadedRom},
+    input::{JoypadBtn, Player},
+    ppu::Ppu,
+    time::{Duration, Instant},
+};
+
+/// Default keyboard mapping for Player One, matching the `TetaNES` defaults.
+pub const DEFAULT_KEYMAP: [(Key, Player, JoypadBtn); 10] = [
+    (Key::Z, Player::One, JoypadBtn::A),
+    (Key::A, Player::One, JoypadBtn::TurboA),
+    (Key::X, Player::One, JoypadBtn::B),
+    (Key::S, Player::One, JoypadBtn::TurboB),
+    (Key::ArrowUp, Player::One, JoypadBtn::Up),
+    (Key::ArrowDown, Player::One, JoypadBtn::Down),
+    (Key::ArrowLeft, Player::One, JoypadBtn::Left),
+    (Key::ArrowRight, Player::One, JoypadBtn::Right),
+    (Key::W, Player::One, JoypadBtn::Select),
+    (Key::Q, Player::One, JoypadBtn::Start),
+];
+
+/// An NES emulator that can be added to an `egui` [`Ui`].
+///
+/// Call [`NesWidget::update`] once per frame to clock emulation in real time and upload the
+/// latest frame to its texture, then add it with `ui.add(&mut nes)`. Keyboard input is forwarded
+/// to the emulator while the widget has focus, which it takes when clicked.
+#[must_use]
+pub struct NesWidget {
+    control_deck: ControlDeck,
+    texture: Texture,
+    keymap: Vec<(Key, Player, JoypadBtn)>,
+    paused: bool,
+    target_frame_duration: Duration,
+    last_update: Instant,
+    accumulator: f32,
+    audio_samples: Vec<f32>,
+}
+
+impl std::fmt::Debug for NesWidget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NesWidget")
+            .field("texture_id", &self.texture.id)
+            .field("loaded_rom", &self.control_deck.loaded_rom())
+            .field("paused", &self.paused)
+            .finish_non_exhaustive()
+    }
+}
+
+impl NesWidget {
+    /// Emulation falls behind rather than clocking more than this many frames in one update.
+    const MAX_FRAMES_PER_UPDATE: f32 = 3.0;
+
+    /// Create a `NesWidget` with a texture on `device`. `register_texture` is called with the
+    /// texture view to register it with the host application's `egui` renderer, e.g. using
+    /// `egui_wgpu::Renderer::register_native_texture`.
+    pub fn new(
+        device: &wgpu::Device,
+        cfg: ControlDeckConfig,
+        register_texture: impl FnOnce(&wgpu::TextureView) -> TextureId,
+    ) -> Self {
+        let region = cfg.region;
+        let control_deck = ControlDeck::with_config(cfg);
+        let texture = Texture::with_device(
+            device,
+            Vec2::new(Ppu::WIDTH as f32, Ppu::HEIGHT as f32),
+            region.aspect_ratio(),
+            Some("nes widget frame"),
+            register_texture,
+        );
+        Self {
+            control_deck,
+            texture,
+            keymap: DEFAULT_KEYMAP.to_vec(),
+            paused: false,
+            target_frame_duration: FrameRate::from(region).duration(),
+            last_update: Instant::now(),
+            accumulator: 0.0,
+            audio_samples: Vec::new(),
+        }
+    }
+
+    /// The `egui` texture the NES frame is rendered to.
+    #[must_use]
+    pub const fn texture_id(&self) -> TextureId {
+        self.texture.id
+    }
+
+    /// The `wgpu` texture the NES frame is rendered to.
+    #[must_use]
+    pub const fn texture(&self) -> &wgpu::Texture {
+        &self.texture.texture
+    }
+
+    /// Size of the NES frame, corrected for the pixel aspect ratio of the current region.
+    #[must_use]
+    pub const fn output_size(&self) -> Vec2 {
+        self.texture.output_size
+    }
+
+    pub const fn control_deck(&self) -> &ControlDeck {
+        &self.control_deck
+    }
+
+    pub fn control_deck_mut(&mut self) -> &mut ControlDeck {
+        &mut self.control_deck
+    }
+
+    /// Replace the keyboard mapping used to forward input to the emulator.
+    pub fn set_keymap(&mut self, keymap: impl IntoIterator<Item = (Key, Player, JoypadBtn)>) {
+        self.keymap = keymap.into_iter().collect();
+    }
+
+    /// Load a ROM from a reader.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ROM is invalid or unsupported.
+    pub fn load_rom(
+        &mut self,
+        name: &str,
+        rom: &mut impl std::io::Read,
+    ) -> control_deck::Result<LoadedRom> {
+        let rom = self.control_deck.load_rom(name, rom)?;
+        self.on_load_rom();
+        Ok(rom)
+    }
+
+    /// Load a ROM from a file path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ROM can't be read, or is invalid or unsupported.
+    pub fn load_rom_path(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+    ) -> control_deck::Result<LoadedRom> {
+        let rom = self.control_deck.load_rom_path(path)?;
+        self.on_load_rom();
+        Ok(rom)
+    }
+
+    fn on_load_rom(&mut self) {
+        self.target_frame_duration = FrameRate::from(self.control_deck.region()).duration();
+        self.last_update = Instant::now();
+        self.accumulator = 0.0;
+    }
+
+    #[must_use]
+    pub const fn paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        self.last_update = Instant::now();
+        self.accumulator = 0.0;
+    }
+
+    /// Audio samples generated since they were last taken, at the control deck sample rate. The
+    /// host application is responsible for playing them.
+    pub fn take_audio_samples(&mut self) -> Vec<f32> {
+        std::mem::take(&mut self.audio_samples)
+    }
+
+    /// Clock as many frames as real time has elapsed since the last update, then upload the
+    /// latest frame to the texture.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the CPU encounters an invalid opcode or hits a breakpoint.
+    pub fn update(&mut self, queue: &wgpu::Queue) -> control_deck::Result<()> {
+        let elapsed = self.last_update.elapsed().as_secs_f32();
+        self.last_update = Instant::now();
+        if self.paused || !self.control_deck.is_running() {
+            return Ok(());
+        }
+
+        let frame_duration = self.target_frame_duration.as_secs_f32();
+        self.accumulator =
+            (self.accumulator + elapsed).min(frame_duration * Self::MAX_FRAMES_PER_UPDATE);
+        if self.accumulator < frame_duration {
+            return Ok(());
+        }
+        while self.accumulator >= frame_duration {
+            self.accumulator -= frame_duration;
+            self.control_deck
+                .clock_frame_output(|_cycles, _frame, audio| {
+                    self.audio_samples.extend_from_slice(audio);
+                })?;
+        }
+        self.texture.update(queue, self.control_deck.frame_buffer());
+
+        Ok(())
+    }
+
+    /// Forward keyboard input from `ui` to the emulator.
+    pub fn handle_keyboard(&mut self, ui: &Ui) {
+        ui.input(|i| {
+            for &(key, player, button) in &self.keymap {
+                self.control_deck
+                    .joypad_mut(player)
+                    .set_button(button, i.key_down(key));
+            }
+        });
+    }
+
+    /// Release all buttons in the keymap, e.g. when the widget loses focus.
+    pub fn release_keyboard(&mut self) {
+        for &(_, player, button) in &self.keymap {
+            self.control_deck
+                .joypad_mut(player)
+                .set_button(button, false);
+        }
+    }
+
+    /// Forward pointer input over the widget response to the Zapper, if connected.
+    pub fn handle_zapper(&mut self, res: &Response) {
+        if !self.control_deck.zapper_connected() {
+            return;
+        }
+        if let Some(Pos2 { x, y }) = res
+            .hover_pos()
+            .and_then(|Pos2 { x, y }| cursor_to_zapper(x, y, res.rect))
+        {
+            self.control_deck
+                .aim_zapper(x.round() as u32, y.round() as u32);
+        }
+        if res.clicked() {
+            self.control_deck.trigger_zapper();
+        }
+    }
+}
+
+impl Widget for &mut NesWidget {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let image = Image::from_texture((self.texture.id, self.texture.output_size))
+            .maintain_aspect_ratio(true)
+            .shrink_to_fit()
+            .sense(Sense::click());
+        let res = ui.add(image);
+        if res.clicked() {
+            res.request_focus();
+        }
+        if res.has_focus() {
+            // Keep arrow keys from moving focus to other widgets
+            ui.memory_mut(|mem| {
+                mem.set_focus_lock_filter(
+                    res.id,
+                    egui::EventFilter {
+                        tab: false,
+                        horizontal_arrows: true,
+                        vertical_arrows: true,
+                        escape: false,
+                    },
+                );
+            });
+            self.handle_keyboard(ui);
+        } else if res.lost_focus() {
+            self.release_keyboard();
+        }
+        self.handle_zapper(&res);
+        res
+    }
+}
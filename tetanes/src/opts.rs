@@ -1,6 +1,6 @@
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
-use tetanes::nes::config::Config;
+use tetanes::nes::{config::Config, emulation::replay};
 use tetanes_core::genie::GenieCode;
 
 #[derive(Debug, Clone)]
@@ -9,7 +9,7 @@ pub(crate) struct FourPlayer(tetanes_core::input::FourPlayer);
 impl ValueEnum for FourPlayer {
     fn value_variants<'a>() -> &'a [Self] {
         use tetanes_core::input::FourPlayer::*;
-        &[Self(Disabled), Self(FourScore), Self(Satellite)]
+        &[Self(Disabled), Self(FourScore), Self(Famicom), Self(Auto)]
     }
 
     fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
@@ -37,7 +37,7 @@ pub(crate) struct NesRegion(tetanes_core::common::NesRegion);
 impl ValueEnum for NesRegion {
     fn value_variants<'a>() -> &'a [Self] {
         use tetanes_core::common::NesRegion::*;
-        &[Self(Ntsc), Self(Pal), Self(Dendy)]
+        &[Self(Ntsc), Self(Pal), Self(Dendy), Self(PalM)]
     }
 
     fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
@@ -45,11 +45,39 @@ impl ValueEnum for NesRegion {
     }
 }
 
+#[derive(Debug, Clone)]
+pub(crate) struct ReplayFormat(replay::ReplayFormat);
+
+impl ValueEnum for ReplayFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        use replay::ReplayFormat::*;
+        &[Self(Tetanes), Self(Fm2), Self(Bk2)]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        Some(clap::builder::PossibleValue::new(self.0.as_str()))
+    }
+}
+
+/// `TetaNES` CLI Subcommands
+#[derive(Subcommand, Debug)]
+#[must_use]
+pub(crate) enum Command {
+    /// Run a ROM headlessly against a TOML test spec of inputs and assertions, exiting with a
+    /// non-zero status if any assertion fails.
+    Test {
+        /// The TOML test spec.
+        spec: PathBuf,
+    },
+}
+
 /// `TetaNES` CLI Config Options
 #[derive(Parser, Debug)]
-#[command(version, author, about, long_about = None)]
+#[command(version, author, about, long_about = None, args_conflicts_with_subcommands = true)]
 #[must_use]
 pub struct Opts {
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
     /// The NES ROM to load or a directory containing `.nes` ROM files. [default: current directory]
     pub(crate) path: Option<PathBuf>,
     /// Enable rewinding.
@@ -70,6 +98,12 @@ pub struct Opts {
     /// Disable multi-threaded.
     #[arg(long)]
     pub(crate) no_threaded: bool,
+    /// Raise the emulation thread priority.
+    #[arg(long)]
+    pub(crate) high_priority: bool,
+    /// Pin the emulation thread to a CPU core index.
+    #[arg(long)]
+    pub(crate) cpu_affinity: Option<usize>,
     /// Choose power-up RAM state. [default: "all-zeros"]
     #[arg(short = 'm', long, value_enum)]
     pub(crate) ram_state: Option<RamState>,
@@ -97,9 +131,18 @@ pub struct Opts {
     /// "Default Config" (skip user config and previous save states)
     #[arg(short, long)]
     pub(crate) clean: bool,
+    /// Also export replay recordings as an FCEUX or BizHawk movie. [default: "tetanes"]
+    #[arg(long, value_enum)]
+    pub(crate) replay_format: Option<ReplayFormat>,
     /// Start with debugger open.
     #[arg(short, long)]
     pub(crate) debug: bool,
+    /// Register `.nes` and `.replay` file associations for the current user, then exit.
+    #[arg(long, conflicts_with = "uninstall_associations")]
+    pub(crate) install_associations: bool,
+    /// Remove file associations for the current user, then exit.
+    #[arg(long)]
+    pub(crate) uninstall_associations: bool,
 }
 
 impl Opts {
@@ -144,6 +187,13 @@ impl Opts {
             cfg.emulation.speed = speed
         }
         cfg.emulation.threaded = !self.no_threaded && cfg.emulation.threaded;
+        cfg.emulation.high_priority = self.high_priority || cfg.emulation.high_priority;
+        if let Some(core) = self.cpu_affinity {
+            cfg.emulation.cpu_affinity = Some(core);
+        }
+        if let Some(ReplayFormat(format)) = self.replay_format {
+            cfg.emulation.replay_format = format;
+        }
 
         cfg.audio.enabled = !self.silent && cfg.audio.enabled;
 
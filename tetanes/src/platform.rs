@@ -25,6 +25,19 @@ pub fn open_file_dialog(
     platform::open_file_dialog_impl(title, name, extensions, dir)
 }
 
+/// Method for platforms supporting opening a directory picker dialog.
+pub fn open_dir_dialog(
+    title: impl Into<String>,
+    dir: Option<impl AsRef<Path>>,
+) -> anyhow::Result<Option<PathBuf>> {
+    platform::open_dir_dialog_impl(title, dir)
+}
+
+/// Copy an RGBA image to the system clipboard for platforms that support it.
+pub fn copy_image(width: usize, height: usize, rgba: &[u8]) -> anyhow::Result<()> {
+    platform::copy_image_impl(width, height, rgba)
+}
+
 /// Speak the given text out loud for platforms that support it.
 #[allow(clippy::missing_const_for_fn)]
 pub fn speak_text(text: &str) {
@@ -52,7 +65,16 @@ pub enum Feature {
     Blocking,
     ConstrainedViewport,
     ConsumePaste,
+    /// Copying images to the system clipboard.
+    CopyImage,
+    /// Switching display modes in fullscreen. Not supported on Wayland, which can't be ruled out
+    /// at compile time on Linux.
+    ExclusiveFullscreen,
     Filesystem,
+    /// Serving files to other devices on the local network.
+    LocalServer,
+    /// Peer-to-peer netplay over UDP.
+    Netplay,
     ScreenReader,
     Storage,
     Suspend,
@@ -67,12 +89,13 @@ macro_rules! feature {
         match $feature {
             // Wasm should never be able to exit
             AbortOnExit => cfg!(target_arch = "wasm32"),
-            Blocking | Filesystem | OsViewports => {
+            Blocking | CopyImage | Filesystem | LocalServer | Netplay | OsViewports => {
                 cfg!(not(target_arch = "wasm32"))
             }
             ConstrainedViewport | ConsumePaste | ScreenReader => {
                 cfg!(target_arch = "wasm32")
             }
+            ExclusiveFullscreen => cfg!(any(target_os = "windows", target_os = "macos")),
             Storage => true,
             Suspend => cfg!(target_os = "android"),
         }
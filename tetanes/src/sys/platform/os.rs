@@ -2,10 +2,13 @@ use crate::{
     nes::{event::EmulationEvent, renderer::Renderer, Running},
     platform::{BuilderExt, Initialize},
 };
+use anyhow::Context;
 use std::path::{Path, PathBuf};
 use tracing::error;
 use winit::window::WindowAttributes;
 
+pub mod associations;
+
 /// Method for platforms supporting opening a file dialog.
 pub fn open_file_dialog_impl(
     title: impl Into<String>,
@@ -22,6 +25,30 @@ pub fn open_file_dialog_impl(
     Ok(dialog.pick_file())
 }
 
+/// Method for platforms supporting opening a directory picker dialog.
+pub fn open_dir_dialog_impl(
+    title: impl Into<String>,
+    dir: Option<impl AsRef<Path>>,
+) -> anyhow::Result<Option<PathBuf>> {
+    let mut dialog = rfd::FileDialog::new().set_title(title);
+    if let Some(dir) = dir {
+        dialog = dialog.set_directory(dir.as_ref());
+    }
+    Ok(dialog.pick_folder())
+}
+
+/// Method for platforms supporting copying images to the clipboard.
+pub fn copy_image_impl(width: usize, height: usize, rgba: &[u8]) -> anyhow::Result<()> {
+    let image = arboard::ImageData {
+        width,
+        height,
+        bytes: rgba.into(),
+    };
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_image(image))
+        .context("failed to copy image to clipboard")
+}
+
 /// Speak the given text out loud.
 pub const fn speak_text_impl(_text: &str) {}
 
@@ -33,7 +60,7 @@ impl Initialize for Running {
                 if let Some(parent) = path.parent() {
                     self.cfg.renderer.roms_path = Some(parent.to_path_buf());
                 }
-                self.event(EmulationEvent::LoadRomPath(path));
+                self.event(EmulationEvent::load_path(path));
             } else if path.exists() {
                 self.cfg.renderer.roms_path = Some(path);
             }
@@ -52,7 +79,6 @@ impl Initialize for Renderer {
 impl BuilderExt for WindowAttributes {
     /// Sets platform-specific window options.
     fn with_platform(self, _title: &str) -> Self {
-        use anyhow::Context;
         use image::{ImageFormat, ImageReader};
         use std::io::Cursor;
 
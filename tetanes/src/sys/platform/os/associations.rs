@@ -0,0 +1,241 @@
+//! Per-user file associations so ROMs and replays open in `TetaNES` from the file manager.
+
+use std::{path::PathBuf, process::Command};
+use tracing::warn;
+
+/// A file type `TetaNES` can open.
+struct FileType {
+    extension: &'static str,
+    #[cfg_attr(target_os = "macos", allow(unused))]
+    mime_type: &'static str,
+    #[cfg_attr(not(target_os = "windows"), allow(unused))]
+    description: &'static str,
+}
+
+const FILE_TYPES: [FileType; 2] = [
+    FileType {
+        extension: "nes",
+        mime_type: "application/x-nes-rom",
+        description: "NES ROM",
+    },
+    FileType {
+        extension: "replay",
+        mime_type: "application/x-tetanes-replay",
+        description: "TetaNES Replay",
+    },
+];
+
+/// File extensions associated with `TetaNES`.
+pub fn extensions() -> impl Iterator<Item = &'static str> {
+    FILE_TYPES.iter().map(|file_type| file_type.extension)
+}
+
+fn current_exe() -> anyhow::Result<PathBuf> {
+    let exe = std::env::current_exe()?;
+    Ok(exe.canonicalize().unwrap_or(exe))
+}
+
+/// Run a command, returning an error if it fails to run or exits unsuccessfully.
+fn run(program: &str, args: &[&str]) -> anyhow::Result<()> {
+    let status = Command::new(program).args(args).status()?;
+    if !status.success() {
+        anyhow::bail!("`{program}` failed: {status}");
+    }
+    Ok(())
+}
+
+/// Run a command that only refreshes cached state, logging failures instead of returning them.
+#[cfg_attr(target_os = "windows", allow(unused))]
+fn run_best_effort(program: &str, args: &[&str]) {
+    if let Err(err) = run(program, args) {
+        warn!("{err:?}");
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod imp {
+    use super::{current_exe, run_best_effort, FILE_TYPES};
+    use crate::nes::config::Config;
+    use anyhow::Context;
+    use std::{fs, io, path::PathBuf};
+
+    const DESKTOP_ENTRY: &str = include_str!("../../../../../assets/linux/tetanes.desktop");
+    const MIME_PACKAGE: &[u8] = include_bytes!("../../../../../assets/linux/tetanes-mime.xml");
+    const ICON: &[u8] = include_bytes!("../../../../../assets/linux/icon.png");
+    const DESKTOP_FILENAME: &str = "tetanes.desktop";
+
+    fn data_dir() -> anyhow::Result<PathBuf> {
+        dirs::data_dir().context("failed to find user data directory")
+    }
+
+    fn refresh(data_dir: &std::path::Path) {
+        run_best_effort(
+            "update-mime-database",
+            &[&data_dir.join("mime").to_string_lossy()],
+        );
+        run_best_effort(
+            "update-desktop-database",
+            &[&data_dir.join("applications").to_string_lossy()],
+        );
+    }
+
+    fn remove_file(path: PathBuf) -> anyhow::Result<()> {
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                Err(err).with_context(|| format!("failed to remove {path:?}"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn install() -> anyhow::Result<()> {
+        let data_dir = data_dir()?;
+        let exe = current_exe()?;
+
+        let icon_path = Config::default_data_dir().join("icon.png");
+        fs::create_dir_all(Config::default_data_dir())?;
+        fs::write(&icon_path, ICON)?;
+
+        let desktop_entry = DESKTOP_ENTRY
+            .lines()
+            .map(|line| {
+                if line.starts_with("Exec=") {
+                    format!("Exec=\"{}\" %f", exe.display())
+                } else if line.starts_with("Icon=") {
+                    format!("Icon={}", icon_path.display())
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let applications_dir = data_dir.join("applications");
+        fs::create_dir_all(&applications_dir)?;
+        fs::write(
+            applications_dir.join(DESKTOP_FILENAME),
+            desktop_entry + "\n",
+        )?;
+
+        let mime_dir = data_dir.join("mime/packages");
+        fs::create_dir_all(&mime_dir)?;
+        fs::write(mime_dir.join("tetanes.xml"), MIME_PACKAGE)?;
+
+        refresh(&data_dir);
+        for file_type in &FILE_TYPES {
+            run_best_effort(
+                "xdg-mime",
+                &["default", DESKTOP_FILENAME, file_type.mime_type],
+            );
+        }
+        Ok(())
+    }
+
+    pub fn uninstall() -> anyhow::Result<()> {
+        let data_dir = data_dir()?;
+        remove_file(data_dir.join("applications").join(DESKTOP_FILENAME))?;
+        remove_file(data_dir.join("mime/packages/tetanes.xml"))?;
+        refresh(&data_dir);
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use super::{current_exe, run};
+    use anyhow::Context;
+    use std::path::PathBuf;
+
+    /// File types are declared in the app bundle's `Info.plist`, so registering the bundle with
+    /// Launch Services is all that's needed.
+    const LSREGISTER: &str = "/System/Library/Frameworks/CoreServices.framework/Frameworks/LaunchServices.framework/Support/lsregister";
+
+    fn app_bundle() -> anyhow::Result<PathBuf> {
+        let exe = current_exe()?;
+        exe.ancestors()
+            .find(|path| path.extension().is_some_and(|ext| ext == "app"))
+            .map(PathBuf::from)
+            .context("file associations require running from the TetaNES.app bundle")
+    }
+
+    pub fn install() -> anyhow::Result<()> {
+        run(LSREGISTER, &["-f", &app_bundle()?.to_string_lossy()])
+    }
+
+    pub fn uninstall() -> anyhow::Result<()> {
+        run(LSREGISTER, &["-u", &app_bundle()?.to_string_lossy()])
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod imp {
+    use super::{current_exe, run, FILE_TYPES};
+
+    /// Per-user classes don't require administrator privileges.
+    const CLASSES: &str = r"HKCU\Software\Classes";
+
+    fn reg_add(key: &str, value: &str) -> anyhow::Result<()> {
+        run("reg", &["add", key, "/ve", "/d", value, "/f"])
+    }
+
+    pub fn install() -> anyhow::Result<()> {
+        let exe = current_exe()?;
+        let exe = exe.display();
+        for file_type in &FILE_TYPES {
+            let prog_id = format!("TetaNES.{}", file_type.extension);
+            let class = format!(r"{CLASSES}\{prog_id}");
+            reg_add(&format!(r"{CLASSES}\.{}", file_type.extension), &prog_id)?;
+            reg_add(&class, file_type.description)?;
+            reg_add(&format!(r"{class}\DefaultIcon"), &format!("\"{exe}\",0"))?;
+            reg_add(
+                &format!(r"{class}\shell\open\command"),
+                &format!("\"{exe}\" \"%1\""),
+            )?;
+            run(
+                "reg",
+                &[
+                    "add",
+                    &format!(r"{CLASSES}\.{}", file_type.extension),
+                    "/v",
+                    "Content Type",
+                    "/d",
+                    file_type.mime_type,
+                    "/f",
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn uninstall() -> anyhow::Result<()> {
+        for file_type in &FILE_TYPES {
+            // Associations may have never been installed, so failing to delete is not an error
+            let _ = run(
+                "reg",
+                &[
+                    "delete",
+                    &format!(r"{CLASSES}\TetaNES.{}", file_type.extension),
+                    "/f",
+                ],
+            );
+            let _ = run(
+                "reg",
+                &[
+                    "delete",
+                    &format!(r"{CLASSES}\.{}", file_type.extension),
+                    "/f",
+                ],
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Register file associations for the current user.
+pub fn install() -> anyhow::Result<()> {
+    imp::install()
+}
+
+/// Remove file associations for the current user.
+pub fn uninstall() -> anyhow::Result<()> {
+    imp::uninstall()
+}
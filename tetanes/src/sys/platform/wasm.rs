@@ -3,7 +3,10 @@
 
 use crate::{
     nes::{
-        event::{EmulationEvent, NesEventProxy, RendererEvent, ReplayData, UiEvent},
+        event::{
+            ConfigEvent, EmulationEvent, NesEvent, NesEventProxy, RendererEvent, ReplayData,
+            UiEvent,
+        },
         renderer::{gui, Renderer, State},
         rom::RomData,
         Running,
@@ -11,8 +14,9 @@ use crate::{
     platform::{BuilderExt, Initialize},
     thread,
 };
-use anyhow::{bail, Context};
+use anyhow::{anyhow, bail, Context};
 use std::{
+    cell::{Cell, RefCell},
     path::{Path, PathBuf},
     rc::Rc,
 };
@@ -32,6 +36,11 @@ const OS_OPTIONS: [(Os, Arch, &str); 5] = [
     (Os::Linux, Arch::X86_64, html_ids::LINXU_X86_LINK),
 ];
 
+thread_local! {
+    /// Event proxy used by the functions exported to JavaScript.
+    static JS_EVENT_TX: RefCell<Option<NesEventProxy>> = const { RefCell::new(None) };
+}
+
 #[derive(Debug)]
 pub struct System;
 
@@ -66,6 +75,42 @@ pub fn open_file_dialog_impl(
     Ok(None)
 }
 
+/// Directory pickers aren't supported on the web.
+pub fn open_dir_dialog_impl(
+    _title: impl Into<String>,
+    _dir: Option<impl AsRef<Path>>,
+) -> anyhow::Result<Option<PathBuf>> {
+    bail!("directory selection is not supported")
+}
+
+/// Fetches a static asset relative to the page URL.
+pub async fn fetch_asset(path: &str) -> anyhow::Result<Vec<u8>> {
+    use wasm_bindgen_futures::JsFuture;
+
+    let window = web_sys::window().context("valid window")?;
+    let response = JsFuture::from(window.fetch_with_str(path))
+        .await
+        .map_err(|err| anyhow!("failed to fetch {path}: {err:?}"))?
+        .dyn_into::<web_sys::Response>()
+        .map_err(|err| anyhow!("invalid response for {path}: {err:?}"))?;
+    if !response.ok() {
+        bail!("failed to fetch {path}: status {}", response.status());
+    }
+    let buffer = response
+        .array_buffer()
+        .map_err(|err| anyhow!("failed to read {path}: {err:?}"))?;
+    let buffer = JsFuture::from(buffer)
+        .await
+        .map_err(|err| anyhow!("failed to read {path}: {err:?}"))?;
+
+    Ok(Uint8Array::new(&buffer).to_vec())
+}
+
+/// Method for platforms supporting copying images to the clipboard.
+pub fn copy_image_impl(_width: usize, _height: usize, _rgba: &[u8]) -> anyhow::Result<()> {
+    bail!("copying images is not supported yet on this platform")
+}
+
 /// Speak the given text out loud.
 pub fn speak_text_impl(text: &str) {
     if text.is_empty() {
@@ -126,6 +171,50 @@ fn set_resize_handler(window: &web_sys::Window, tx: &NesEventProxy) {
     on_resize.forget();
 }
 
+/// Sets up the visibility handler for pausing when the tab is hidden and resuming audio on the next
+/// user gesture, since browsers suspend audio for backgrounded tabs and until user interaction.
+fn set_visibility_handlers(document: &web_sys::Document, tx: &NesEventProxy) {
+    let audio_suspended = Rc::new(Cell::new(true));
+
+    let on_visibility_change = Closure::<dyn FnMut(_)>::new({
+        let tx = tx.clone();
+        let audio_suspended = Rc::clone(&audio_suspended);
+        move |_: web_sys::Event| {
+            let visible = web_sys::window()
+                .and_then(|window| window.document())
+                .map_or(true, |document| !document.hidden());
+            if !visible {
+                audio_suspended.set(true);
+            }
+            tx.event(UiEvent::VisibilityChanged(visible));
+        }
+    });
+    if let Err(err) = document.add_event_listener_with_callback(
+        "visibilitychange",
+        on_visibility_change.as_ref().unchecked_ref(),
+    ) {
+        on_error(tx, err);
+    }
+    on_visibility_change.forget();
+
+    let on_gesture = Closure::<dyn FnMut(_)>::new({
+        let tx = tx.clone();
+        move |_: web_sys::Event| {
+            if audio_suspended.replace(false) {
+                tx.event(EmulationEvent::ResumeAudio);
+            }
+        }
+    });
+    for event in ["pointerdown", "keydown", "touchend"] {
+        if let Err(err) =
+            document.add_event_listener_with_callback(event, on_gesture.as_ref().unchecked_ref())
+        {
+            on_error(tx, err);
+        }
+    }
+    on_gesture.forget();
+}
+
 /// Sets up the onload handler for reading loaded files.
 fn set_file_onload_handler(
     tx: NesEventProxy,
@@ -644,8 +733,10 @@ impl Initialize for Running {
         let window = web_sys::window().context("valid window")?;
         let document = window.document().context("valid html document")?;
 
+        JS_EVENT_TX.set(Some(self.tx.clone()));
         set_download_versions(&document);
         set_resize_handler(&window, &self.tx);
+        set_visibility_handlers(&document, &self.tx);
         for input_id in [html_ids::ROM_INPUT, html_ids::REPLAY_INPUT] {
             set_file_onchange_handlers(&document, &self.tx, input_id)?;
         }
@@ -791,6 +882,46 @@ impl Initialize for Renderer {
     }
 }
 
+/// Send an event from a function exported to JavaScript, if `TetaNES` has started.
+fn send_js_event(event: impl Into<NesEvent>) {
+    JS_EVENT_TX.with_borrow(|tx| match tx {
+        Some(tx) => tx.event(event),
+        None => tracing::warn!("TetaNES hasn't started yet"),
+    });
+}
+
+/// Set the number of frames to run ahead to reduce input lag, from `0` to `4`. Each frame
+/// run ahead requires emulating an additional frame, so slower devices may not keep up.
+#[wasm_bindgen(js_name = setRunAhead)]
+pub fn set_run_ahead(frames: u32) {
+    send_js_event(ConfigEvent::RunAhead(frames.min(4) as usize));
+}
+
+/// Enable or disable rewinding.
+#[wasm_bindgen(js_name = setRewindEnabled)]
+pub fn set_rewind_enabled(enabled: bool) {
+    send_js_event(ConfigEvent::RewindEnabled(enabled));
+}
+
+/// Set the maximum number of seconds to rewind, from `1` to `360`.
+#[wasm_bindgen(js_name = setRewindSeconds)]
+pub fn set_rewind_seconds(seconds: u32) {
+    send_js_event(ConfigEvent::RewindSeconds(seconds.clamp(1, 360)));
+}
+
+/// Set the maximum megabytes of rewind history to keep, from `16` to `4096`, which shortens
+/// the rewind duration if needed.
+#[wasm_bindgen(js_name = setRewindMemoryBudget)]
+pub fn set_rewind_memory_budget(mb: u32) {
+    send_js_event(ConfigEvent::RewindMemoryBudget(mb.clamp(16, 4096)));
+}
+
+/// Rewind ~2 seconds.
+#[wasm_bindgen(js_name = instantRewind)]
+pub fn instant_rewind() {
+    send_js_event(EmulationEvent::InstantRewind);
+}
+
 pub fn download_save_states() -> anyhow::Result<()> {
     use crate::nes::config::Config;
     use anyhow::{anyhow, Context};
@@ -1,4 +1,6 @@
-use std::{future::Future, thread};
+use anyhow::bail;
+use cfg_if::cfg_if;
+use std::{future::Future, io, thread};
 use tetanes_core::time::{Duration, Instant};
 
 /// Spawn a future to be run until completion.
@@ -29,3 +31,75 @@ pub async fn sleep_impl(dur: Duration) {
     // TODO: Async is a lie and is only required to allow the web impl to be non-blocking
     thread::sleep(dur);
 }
+
+/// Raises the priority of the current thread.
+pub fn set_high_priority_impl() -> anyhow::Result<()> {
+    cfg_if! {
+        if #[cfg(target_os = "linux")] {
+            // Threads have their own nice value on Linux. Lowering it below zero requires
+            // `CAP_SYS_NICE` or an `RLIMIT_NICE` allowance.
+            // SAFETY: Neither call has memory safety requirements.
+            let res = unsafe {
+                libc::setpriority(libc::PRIO_PROCESS, libc::gettid() as libc::id_t, -10)
+            };
+            if res != 0 {
+                bail!("failed to set thread priority: {}", io::Error::last_os_error());
+            }
+        } else if #[cfg(target_os = "macos")] {
+            // SAFETY: Only affects the calling thread.
+            let res = unsafe {
+                libc::pthread_set_qos_class_self_np(libc::qos_class_t::QOS_CLASS_USER_INTERACTIVE, 0)
+            };
+            if res != 0 {
+                bail!("failed to set thread priority: {}", io::Error::from_raw_os_error(res));
+            }
+        } else if #[cfg(windows)] {
+            use windows_sys::Win32::System::Threading::{
+                GetCurrentThread, SetThreadPriority, THREAD_PRIORITY_HIGHEST,
+            };
+
+            // SAFETY: `GetCurrentThread` returns a pseudo handle that is always valid.
+            if unsafe { SetThreadPriority(GetCurrentThread(), THREAD_PRIORITY_HIGHEST) } == 0 {
+                bail!("failed to set thread priority: {}", io::Error::last_os_error());
+            }
+        } else {
+            bail!("thread priority is not supported on this platform");
+        }
+    }
+    Ok(())
+}
+
+/// Pins the current thread to the given CPU core index.
+pub fn set_affinity_impl(core: usize) -> anyhow::Result<()> {
+    let cores = thread::available_parallelism().map_or(1, |count| count.get());
+    if core >= cores {
+        bail!("cpu core {core} not found, only {cores} available");
+    }
+    cfg_if! {
+        if #[cfg(target_os = "linux")] {
+            // SAFETY: `cpu_set_t` is plain data and `core` was checked to be in range.
+            let res = unsafe {
+                let mut set = std::mem::zeroed::<libc::cpu_set_t>();
+                libc::CPU_SET(core, &mut set);
+                libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set)
+            };
+            if res != 0 {
+                bail!("failed to pin thread to cpu core {core}: {}", io::Error::last_os_error());
+            }
+        } else if #[cfg(windows)] {
+            use windows_sys::Win32::System::Threading::{GetCurrentThread, SetThreadAffinityMask};
+
+            let Some(mask) = 1usize.checked_shl(core as u32) else {
+                bail!("cpu core {core} is outside of the current processor group");
+            };
+            // SAFETY: `GetCurrentThread` returns a pseudo handle that is always valid.
+            if unsafe { SetThreadAffinityMask(GetCurrentThread(), mask) } == 0 {
+                bail!("failed to pin thread to cpu core {core}: {}", io::Error::last_os_error());
+            }
+        } else {
+            // macOS only supports affinity hints between threads, not pinning to a core
+            bail!("thread affinity is not supported on this platform");
+        }
+    }
+    Ok(())
+}
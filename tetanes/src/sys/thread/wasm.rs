@@ -1,3 +1,4 @@
+use anyhow::bail;
 use std::future::Future;
 use tetanes_core::time::Duration;
 use wasm_bindgen_futures::JsFuture;
@@ -31,3 +32,13 @@ pub async fn sleep_impl(dur: Duration) {
         tracing::error!("failed to create sleep future: {err:?}");
     }
 }
+
+/// Thread priorities are not supported in wasm.
+pub fn set_high_priority_impl() -> anyhow::Result<()> {
+    bail!("thread priority is not supported")
+}
+
+/// Thread affinity is not supported in wasm.
+pub fn set_affinity_impl(_core: usize) -> anyhow::Result<()> {
+    bail!("thread affinity is not supported")
+}
@@ -20,3 +20,22 @@ pub fn park_timeout(dur: Duration) {
 pub async fn sleep(dur: Duration) {
     thread::sleep_impl(dur).await
 }
+
+/// Raises the priority of the current thread.
+///
+/// # Errors
+///
+/// Returns an error if the platform doesn't support thread priorities or the process lacks
+/// permission to raise them.
+pub fn set_high_priority() -> anyhow::Result<()> {
+    thread::set_high_priority_impl()
+}
+
+/// Pins the current thread to the given CPU core index.
+///
+/// # Errors
+///
+/// Returns an error if the core doesn't exist or the platform doesn't support thread affinity.
+pub fn set_affinity(core: usize) -> anyhow::Result<()> {
+    thread::set_affinity_impl(core)
+}